@@ -57,6 +57,9 @@ impl RecordOptions {
             retrieve_type: false,
             retrieve_value: true,
             retrieve_tags: false,
+            sort_by: None,
+            offset: None,
+            limit: None,
         };
 
         serde_json::to_string(&options).unwrap()
@@ -69,6 +72,9 @@ impl Default for RecordOptions {
             retrieve_type: false,
             retrieve_value: true,
             retrieve_tags: false,
+            sort_by: None,
+            offset: None,
+            limit: None,
         }
     }
 }
@@ -86,6 +92,34 @@ pub struct SearchOptions {
     retrieve_value: bool,
     #[serde(default = "default_false")]
     retrieve_tags: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort_by: Option<SortBy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+// Plain tag to order search results by, its name is encrypted and prefixed the same way as in queries ("~<base64>")
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SortBy {
+    pub tag_name: String,
+    #[serde(default)]
+    pub order: SortOrder,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl Default for SortOrder {
+    fn default() -> SortOrder {
+        SortOrder::Asc
+    }
 }
 
 impl SearchOptions {
@@ -1727,6 +1761,7 @@ impl WalletStorage for PostgresStorage {
                 retrieve_tags: search_options.retrieve_tags,
                 retrieve_type: search_options.retrieve_type,
             };
+            let order = query::SearchOrder::from_options(&search_options)?;
 
             let (query_string, query_arguments) = match query_qualifier {
                 Some(_) => {
@@ -1734,6 +1769,7 @@ impl WalletStorage for PostgresStorage {
                     query_arguments.push(&wallet_id_arg);
                     let arg_str = format!(" AND i.wallet_id = ${}", query_arguments.len());
                     query_string.push_str(&arg_str);
+                    query_string.push_str(&query::order_to_sql(&order, &mut query_arguments));
                     let mut with_clause = false;
                     if query_string.contains("tags_plaintext") {
                         query_arguments.push(&wallet_id_arg);
@@ -1753,7 +1789,11 @@ impl WalletStorage for PostgresStorage {
                     }
                     (query_string, query_arguments)
                 }
-                None => query::wql_to_sql(&type_, query, options)?
+                None => {
+                    let (mut query_string, mut query_arguments) = query::wql_to_sql(&type_, query, options)?;
+                    query_string.push_str(&query::order_to_sql(&order, &mut query_arguments));
+                    (query_string, query_arguments)
+                }
             };
 
             let statement = self._prepare_statement(&query_string)?;
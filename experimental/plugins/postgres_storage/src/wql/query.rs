@@ -2,6 +2,8 @@ use postgres::types::ToSql;
 
 use errors::wallet::WalletQueryError;
 use language::{Operator,TagName,TargetValue};
+use postgres_storage::{SearchOptions, SortOrder};
+use utils::crypto::base64;
 
 
 // Translates Wallet Query Language to SQL
@@ -32,6 +34,66 @@ pub fn wql_to_sql_count<'a>(class: &'a Vec<u8>, op: &'a Operator) -> Result<(Str
     Ok((convert_query_to_psql_args(&query_string), arguments))
 }
 
+// Sorting and paging of search results prepared for binding as query arguments
+pub struct SearchOrder {
+    sort_tag_name: Option<Vec<u8>>,
+    descending: bool,
+    limit: Option<i64>,
+    offset: i64,
+}
+
+impl SearchOrder {
+    pub fn from_options(options: &SearchOptions) -> Result<SearchOrder, WalletQueryError> {
+        let sort_tag_name = match options.sort_by {
+            Some(ref sort_by) if sort_by.tag_name.starts_with("~") => Some(
+                base64::decode(&sort_by.tag_name[1..])
+                    .map_err(|err| WalletQueryError::ValueErr(format!("Invalid sort tag name: {}", err)))?
+            ),
+            Some(_) => return Err(WalletQueryError::StructureErr("Only plain tags can be used for sorting".to_string())),
+            None => None
+        };
+
+        Ok(SearchOrder {
+            sort_tag_name,
+            descending: options.sort_by.as_ref().map(|sort_by| sort_by.order == SortOrder::Desc).unwrap_or(false),
+            limit: options.limit.map(|limit| limit as i64),
+            offset: options.offset.unwrap_or(0) as i64,
+        })
+    }
+
+    fn is_paged(&self) -> bool {
+        self.limit.is_some() || self.offset > 0
+    }
+}
+
+
+// Translates sorting and paging of search results to the tail of a search query
+// Placeholders are numbered after the arguments already collected for the query
+pub fn order_to_sql<'a>(order: &'a SearchOrder, arguments: &mut Vec<&'a dyn ToSql>) -> String {
+    let mut query_string = String::new();
+
+    // item id keeps pages stable between requests
+    if let Some(ref sort_tag_name) = order.sort_tag_name {
+        arguments.push(sort_tag_name);
+        query_string.push_str(&format!(" ORDER BY (SELECT value FROM tags_plaintext WHERE item_id = i.id AND name = ${}) {} NULLS LAST, i.id",
+                                       arguments.len(), if order.descending { "DESC" } else { "ASC" }));
+    } else if order.is_paged() {
+        query_string.push_str(" ORDER BY i.id");
+    }
+
+    if let Some(ref limit) = order.limit {
+        arguments.push(limit);
+        query_string.push_str(&format!(" LIMIT ${}", arguments.len()));
+    }
+
+    if order.offset > 0 {
+        arguments.push(&order.offset);
+        query_string.push_str(&format!(" OFFSET ${}", arguments.len()));
+    }
+
+    query_string
+}
+
 fn convert_query_to_psql_args(query: &str) -> String {
     let mut index = 1;
     let mut s: String = query.to_owned();
@@ -256,4 +318,22 @@ mod tests {
         let (query, _arguments) = wql_to_sql(&class, &query, None).unwrap();
         assert_eq!(query, "SELECT i.id, i.name, i.value, i.key, i.type FROM items as i WHERE i.type = $1 AND (((i.id in (SELECT item_id FROM tags_encrypted WHERE name = $2 AND value = $3)) AND (i.id in (SELECT item_id FROM tags_plaintext WHERE name = $4 AND value = $5))) OR ((i.id in (SELECT item_id FROM tags_encrypted WHERE name = $6 AND value = $7)) AND NOT ((i.id in (SELECT item_id FROM tags_plaintext WHERE name = $8 AND value = $9)))))")
     }
+
+    #[test]
+    fn sort_and_page() {
+        let query = Operator::Eq(TagName::PlainTagName(vec![7,8,9]), TargetValue::Unencrypted("spam".to_string()));
+        let class = vec![100,100,100];
+        let options: SearchOptions = ::serde_json::from_str(r#"{"sortBy": {"tagName": "~AQID", "order": "desc"}, "offset": 10, "limit": 5}"#).unwrap();
+        let order = SearchOrder::from_options(&options).unwrap();
+        let (mut query, mut arguments) = wql_to_sql(&class, &query, None).unwrap();
+        query.push_str(&order_to_sql(&order, &mut arguments));
+        assert_eq!(query, "SELECT i.id, i.name, i.value, i.key, i.type FROM items as i WHERE i.type = $1 AND (i.id in (SELECT item_id FROM tags_plaintext WHERE name = $2 AND value = $3)) ORDER BY (SELECT value FROM tags_plaintext WHERE item_id = i.id AND name = $4) DESC NULLS LAST, i.id LIMIT $5 OFFSET $6");
+        assert_eq!(arguments.len(), 6);
+    }
+
+    #[test]
+    fn sort_by_encrypted_tag_fails() {
+        let options: SearchOptions = ::serde_json::from_str(r#"{"sortBy": {"tagName": "AQID"}}"#).unwrap();
+        assert!(SearchOrder::from_options(&options).is_err());
+    }
 }
@@ -193,6 +193,17 @@ extern "C" {
                                                                             indy_u32_t    total_count)
                                                        );
 
+    extern indy_error_t indy_prover_search_credentials_with_options(indy_handle_t command_handle,
+                                                                    indy_handle_t wallet_handle,
+                                                                    const char *  query_json,
+                                                                    const char *  options_json,
+
+                                                                    void           (*cb)(indy_handle_t command_handle_,
+                                                                                         indy_error_t  err,
+                                                                                         indy_handle_t search_handle,
+                                                                                         indy_u32_t    total_count)
+                                                                    );
+
     extern indy_error_t indy_prover_fetch_credentials(indy_handle_t command_handle,
                                                       indy_handle_t search_handle,
                                                       indy_u32_t    count,
@@ -209,6 +220,26 @@ extern "C" {
                                                                                   indy_error_t  err)
                                                              );
 
+    extern indy_error_t indy_prover_count_credentials_by_tag(indy_handle_t command_handle,
+                                                             indy_handle_t wallet_handle,
+                                                             const char *  query_json,
+                                                             const char *  tag_name,
+
+                                                             void           (*cb)(indy_handle_t command_handle_,
+                                                                                  indy_error_t  err,
+                                                                                  const char*   counts_json)
+                                                             );
+
+    extern indy_error_t indy_prover_get_distinct_credential_tag_values(indy_handle_t command_handle,
+                                                                       indy_handle_t wallet_handle,
+                                                                       const char *  query_json,
+                                                                       const char *  tag_name,
+
+                                                                       void           (*cb)(indy_handle_t command_handle_,
+                                                                                            indy_error_t  err,
+                                                                                            const char*   values_json)
+                                                                       );
+
     extern indy_error_t indy_prover_get_credentials_for_proof_req(indy_handle_t command_handle,
                                                                   indy_handle_t wallet_handle,
                                                                   const char *  proof_request_json,
@@ -35,6 +35,32 @@ extern "C" {
                                                                     indy_error_t err)
                                               );
 
+    /// Create a new non-secret record in the wallet that expires at the given time.
+    /// Expired record isn't returned by indy_get_wallet_record and wallet searches,
+    /// but it is physically deleted only by indy_purge_expired_wallet_records call.
+    /// Expiry time isn't preserved by export of the wallet.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context
+    /// wallet_handle: wallet handle (created by open_wallet)
+    /// type_: allows to separate different record types collections
+    /// id: the id of record
+    /// value: the value of record
+    /// tags_json: the record tags used for search and storing meta information as json.
+    ///   Tags json has the same format as for indy_add_wallet_record
+    /// expires_at: expiry time of the record as unix timestamp in seconds
+
+    extern indy_error_t indy_add_wallet_record_with_expiry(indy_handle_t  command_handle,
+                                                           indy_handle_t  wallet_handle,
+                                                           const char*    type_,
+                                                           const char*    id,
+                                                           const char*    value,
+                                                           const char*    tags_json,
+                                                           indy_u64_t     expires_at,
+                                                           void           (*fn)(indy_handle_t command_handle_,
+                                                                                indy_error_t err)
+                                                          );
+
     /// Update a non-secret wallet record value
     ///
     /// #Params
@@ -144,6 +170,162 @@ extern "C" {
                                                                        indy_error_t err)
                                                  );
 
+    /// Apply several non-secret record operations to the wallet as a single atomic unit.
+    ///
+    /// Either all operations are stored or, if any of them fails, the wallet is left unchanged.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context
+    /// wallet_handle: wallet handle (created by open_wallet)
+    /// operations_json: the list of operations to apply in order as json array:
+    ///   [
+    ///     {"op": "add", "type": <str>, "id": <str>, "value": <str>, "tags": <tags json> (optional),
+    ///      "expiresAt": <expiry time as unix timestamp in seconds> (optional)},
+    ///     {"op": "updateValue", "type": <str>, "id": <str>, "value": <str>},
+    ///     {"op": "addTags", "type": <str>, "id": <str>, "tags": <tags json>},
+    ///     {"op": "updateTags", "type": <str>, "id": <str>, "tags": <tags json>},
+    ///     {"op": "deleteTags", "type": <str>, "id": <str>, "tagNames": [<str>, ...]},
+    ///     {"op": "delete", "type": <str>, "id": <str>},
+    ///   ]
+    ///   Tags json has the same format as for indy_add_wallet_record
+
+    extern indy_error_t indy_wallet_batch(indy_handle_t  command_handle,
+                                          indy_handle_t  wallet_handle,
+                                          const char*    operations_json,
+                                          void           (*fn)(indy_handle_t command_handle_,
+                                                               indy_error_t err)
+                                         );
+
+    /// Start staged batch of non-secret record operations.
+    ///
+    /// Operations staged with indy_wallet_batch_stage are kept in memory and nothing is written
+    /// to the wallet until indy_wallet_batch_commit applies all of them as a single atomic unit.
+    /// Batch is discarded by indy_wallet_batch_rollback or when wallet_handle is closed.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context
+    /// wallet_handle: wallet handle (created by open_wallet)
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// batch_handle: staged batch handle
+
+    extern indy_error_t indy_wallet_batch_begin(indy_handle_t  command_handle,
+                                                indy_handle_t  wallet_handle,
+                                                void           (*fn)(indy_handle_t command_handle_,
+                                                                     indy_error_t err,
+                                                                     indy_handle_t batch_handle)
+                                               );
+
+    /// Stage non-secret record operations to the batch started by indy_wallet_batch_begin.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context
+    /// batch_handle: staged batch handle (created by indy_wallet_batch_begin)
+    /// operations_json: the list of operations to add to the batch in the same format as for indy_wallet_batch
+    /// cb: Callback that takes command result as parameter.
+
+    extern indy_error_t indy_wallet_batch_stage(indy_handle_t  command_handle,
+                                                indy_handle_t  batch_handle,
+                                                const char*    operations_json,
+                                                void           (*fn)(indy_handle_t command_handle_,
+                                                                     indy_error_t err)
+                                               );
+
+    /// Apply all staged operations of the batch as a single atomic unit.
+    ///
+    /// Either all operations are stored or, if any of them fails, the wallet is left unchanged.
+    /// Batch handle is released in both cases.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context
+    /// batch_handle: staged batch handle (created by indy_wallet_batch_begin)
+    /// cb: Callback that takes command result as parameter.
+
+    extern indy_error_t indy_wallet_batch_commit(indy_handle_t  command_handle,
+                                                 indy_handle_t  batch_handle,
+                                                 void           (*fn)(indy_handle_t command_handle_,
+                                                                      indy_error_t err)
+                                                );
+
+    /// Discard all staged operations of the batch and release batch handle.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context
+    /// batch_handle: staged batch handle (created by indy_wallet_batch_begin)
+    /// cb: Callback that takes command result as parameter.
+
+    extern indy_error_t indy_wallet_batch_rollback(indy_handle_t  command_handle,
+                                                   indy_handle_t  batch_handle,
+                                                   void           (*fn)(indy_handle_t command_handle_,
+                                                                        indy_error_t err)
+                                                  );
+
+    /// Delete all expired non-secret records of the wallet
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context
+    /// wallet_handle: wallet handle (created by open_wallet)
+    ///
+    /// #Returns
+    /// purged_count: count of deleted records
+
+    extern indy_error_t indy_purge_expired_wallet_records(indy_handle_t  command_handle,
+                                                          indy_handle_t  wallet_handle,
+                                                          void           (*fn)(indy_handle_t command_handle_,
+                                                                               indy_error_t err,
+                                                                               indy_u32_t   purged_count)
+                                                         );
+
+    /// Count wallet records matching the query per value of the tag.
+    /// Records without the tag are not counted.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context
+    /// wallet_handle: wallet handle (created by open_wallet)
+    /// type_: allows to separate different record types collections
+    /// query_json: MongoDB style query to wallet record tags (see indy_open_wallet_search)
+    /// tag_name: the name of the tag to aggregate values of ("~tagName" for un-encrypted tag)
+    ///
+    /// #Returns
+    /// counts_json: tag values mapped to the count of records, ordered by value:
+    /// {
+    ///   "tagValue1": <int>,
+    ///   "tagValue2": <int>,
+    /// }
+
+    extern indy_error_t indy_wallet_count_records_by_tag(indy_handle_t  command_handle,
+                                                         indy_handle_t  wallet_handle,
+                                                         const char*    type_,
+                                                         const char*    query_json,
+                                                         const char*    tag_name,
+                                                         void           (*fn)(indy_handle_t command_handle_,
+                                                                              indy_error_t err,
+                                                                              const char*  counts_json)
+                                                        );
+
+    /// Get the sorted set of distinct values of the tag over wallet records matching the query.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context
+    /// wallet_handle: wallet handle (created by open_wallet)
+    /// type_: allows to separate different record types collections
+    /// query_json: MongoDB style query to wallet record tags (see indy_open_wallet_search)
+    /// tag_name: the name of the tag to aggregate values of ("~tagName" for un-encrypted tag)
+    ///
+    /// #Returns
+    /// values_json: ["tagValue1", "tagValue2"]
+
+    extern indy_error_t indy_wallet_get_distinct_tag_values(indy_handle_t  command_handle,
+                                                            indy_handle_t  wallet_handle,
+                                                            const char*    type_,
+                                                            const char*    query_json,
+                                                            const char*    tag_name,
+                                                            void           (*fn)(indy_handle_t command_handle_,
+                                                                                 indy_error_t err,
+                                                                                 const char*  values_json)
+                                                           );
+
     /// Get an wallet record by id
     ///
     /// #Params
@@ -200,6 +382,12 @@ extern "C" {
     ///    retrieveType: (optional, false by default) Retrieve record type,
     ///    retrieveValue: (optional, true by default) Retrieve record value,
     ///    retrieveTags: (optional, false by default) Retrieve record tags,
+    ///    sortBy: (optional) {
+    ///      tagName: name of un-encrypted tag ("~tagName") to order records by,
+    ///      order: (optional, "asc" by default) "asc" or "desc"
+    ///    }, Sorting isn't supported by plugged storages (WalletQueryError),
+    ///    offset: (optional, 0 by default) Number of records to skip,
+    ///    limit: (optional, no limit by default) Max number of records to return,
     ///  }
     /// #Returns
     /// search_handle: Wallet search handle that can be used later
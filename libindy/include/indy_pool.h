@@ -25,6 +25,11 @@ extern "C" {
                                                  void          (*cb)(indy_handle_t command_handle_, indy_error_t err)
                                                  );
 
+    extern indy_error_t indy_get_pool_status(indy_handle_t command_handle,
+                                             indy_handle_t handle,
+                                             void          (*cb)(indy_handle_t command_handle_, indy_error_t err, const char *const status_json)
+                                             );
+
     extern indy_error_t indy_list_pools(indy_handle_t command_handle,
                                         void          (*fn)(indy_handle_t command_handle_, indy_error_t err, const char *const pools)
                                         );
@@ -43,6 +48,24 @@ extern "C" {
                                                   indy_u64_t    protocol_version,
                                                   void          (*cb)(indy_handle_t command_handle_, indy_error_t err)
                                                   );
+
+    typedef indy_error_t (*indyCustomTransportSend)(indy_handle_t pool_handle,
+                                                    const char *  node_alias,
+                                                    const char *  node_address,
+                                                    const char *  message);
+
+    extern indy_error_t indy_register_pool_transport(indy_handle_t           command_handle,
+                                                     const char *            transport_name,
+                                                     indyCustomTransportSend send,
+                                                     void                    (*cb)(indy_handle_t command_handle_, indy_error_t err)
+                                                     );
+
+    extern indy_error_t indy_deliver_pool_transport_reply(indy_handle_t command_handle,
+                                                          indy_handle_t handle,
+                                                          const char *  node_alias,
+                                                          const char *  reply,
+                                                          void          (*cb)(indy_handle_t command_handle_, indy_error_t err)
+                                                          );
 #ifdef __cplusplus
 }
 #endif
@@ -144,6 +144,45 @@ extern "C" {
                                                   void         (*fn)(indy_handle_t command_handle_, indy_error_t err)
                                                   );
 
+    /// Register custom key wrap provider that protects wallet master key (KMS, HSM, OS keystore).
+    ///
+    /// Wallets created with WRAPPED key derivation method keep random master key wrapped by the provider
+    /// in the storage metadata, "key" of wallet credentials is used as id of the wrapping key.
+    /// 'file' provider that keeps wrapping key unprotected in the file is registered only by builds
+    /// with file_key_wrap_provider feature and is intended for testing.
+    ///
+    /// #Params
+    /// command_handle: Command handle to map callback to caller context.
+    /// name: Key wrap provider name.
+    /// wrap_key: Handler that wraps wallet master key with the key identified by key id
+    /// unwrap_key: Handler that unwraps wallet master key with the key identified by key id
+    /// free_key: Handler that allows to de-allocate keys returned by wrap_key and unwrap_key handlers
+    ///
+    /// #Returns
+    /// Error code
+
+    extern indy_error_t indy_register_wallet_key_wrap_provider(indy_handle_t  command_handle,
+                                                               const char*    name,
+
+                                                               indy_error_t (*wrapKeyFn)(const char*          key_id,
+                                                                                         const indy_u8_t*     key,
+                                                                                         indy_u32_t           key_len,
+                                                                                         const indy_u8_t**    wrapped_key_p,
+                                                                                         indy_u32_t*          wrapped_key_len_p,
+                                                                                         indy_handle_t*       key_handle_p),
+
+                                                               indy_error_t (*unwrapKeyFn)(const char*          key_id,
+                                                                                           const indy_u8_t*     wrapped_key,
+                                                                                           indy_u32_t           wrapped_key_len,
+                                                                                           const indy_u8_t**    key_p,
+                                                                                           indy_u32_t*          key_len_p,
+                                                                                           indy_handle_t*       key_handle_p),
+
+                                                               indy_error_t (*freeKeyFn)(indy_handle_t key_handle),
+
+                                                               void           (*fn)(indy_handle_t command_handle_, indy_error_t err)
+                                                               );
+
     /// Create a new secure wallet.
     ///
     /// #Params
@@ -153,6 +192,8 @@ extern "C" {
     ///         Configured storage uses this identifier to lookup exact wallet data placement.
     ///   "storage_type": optional<string>, Type of the wallet storage. Defaults to 'default'.
     ///                  'Default' storage type allows to store wallet data in the local file.
+    ///                  'indy_inmem' storage type keeps wallet data in process memory until the wallet is deleted.
+    ///                  'postgres' storage type (libindy built with postgres_storage feature) keeps wallet data in Postgres database.
     ///                  Custom storage types can be registered with indy_register_wallet_storage call.
     ///   "storage_config": optional<object>, Storage configuration json. Storage type defines set of supported keys.
     ///                     Can be optional if storage supports default configuration.
@@ -162,6 +203,9 @@ extern "C" {
     ///             Defaults to $HOME/.indy_client/wallet.
     ///             Wallet will be stored in the file {path}/{id}/sqlite.db
     ///   }
+    ///   "profile": optional<string>, Name of the profile to create inside already created storage of the wallet.
+    ///              Profile is independent logical wallet with own keys, the storage is shared with its default profile.
+    ///              Supported by 'default' storage type only.
     /// }
     /// credentials: Wallet credentials json
     /// {
@@ -175,6 +219,11 @@ extern "C" {
     ///                          ARGON2I_INT - derive secured wallet master key (less secured but faster)
     ///                          RAW - raw wallet key master provided (skip derivation).
     ///                                RAW keys can be generated with indy_generate_wallet_key call
+    ///                          WRAPPED - random wallet master key wrapped by key wrap provider, key is id of the wrapping key.
+    ///   "key_wrap_provider": optional<string> Name of key wrap provider used with WRAPPED key derivation method.
+    ///                         'file' provider (testing builds only) keeps wrapping key in the file, key is name of the file
+    ///                         in wrapping_keys directory of indy home.
+    ///                         Custom providers can be registered with indy_register_wallet_key_wrap_provider call.
     /// }
     ///
     /// #Returns
@@ -201,6 +250,8 @@ extern "C" {
     ///             Configured storage uses this identifier to lookup exact wallet data placement.
     ///       "storage_type": optional<string>, Type of the wallet storage. Defaults to 'default'.
     ///                       'Default' storage type allows to store wallet data in the local file.
+    ///                       'indy_inmem' storage type keeps wallet data in process memory until the wallet is deleted.
+    ///                       'postgres' storage type (libindy built with postgres_storage feature) keeps wallet data in Postgres database.
     ///                       Custom storage types can be registered with indy_register_wallet_storage call.
     ///       "storage_config": optional<object>, Storage configuration json. Storage type defines set of supported keys.
     ///                         Can be optional if storage supports default configuration.
@@ -210,6 +261,17 @@ extern "C" {
     ///                      Defaults to $HOME/.indy_client/wallet.
     ///                      Wallet will be stored in the file {path}/{id}/sqlite.db
     ///           }
+    ///       "read_only": optional<bool>, Open the wallet in read-only mode. Defaults to false.
+    ///                    Wallet can be opened in read-only mode by several handles and processes at the same time,
+    ///                    mutating calls return WalletReadOnlyError. Opening fails with WalletReadOnlyError
+    ///                    if rotation of item keys was interrupted and wasn't finished in read-write mode.
+    ///       "profile": optional<string>, Name of the profile created by indy_create_wallet to open.
+    ///                  Default profile of the storage is opened if not set.
+    ///       "prefix_indexes": optional<object>, Map of encrypted tag names to max prefix length (1..64) of their blind indexes.
+    ///                         Enables $prefix WQL operator for these tags. The map is stored in the wallet, so map
+    ///                         stored on previous opening is used if not set. Indexes of all records are rebuilt
+    ///                         on opening with other map, so opening with other map fails with WalletReadOnlyError
+    ///                         in read-only mode.
     ///
     ///   }
     /// credentials: Wallet credentials json
@@ -225,11 +287,17 @@ extern "C" {
     ///                          ARGON2I_INT - derive secured wallet master key (less secured but faster)
     ///                          RAW - raw wallet key master provided (skip derivation).
     ///                                RAW keys can be generated with indy_generate_wallet_key call
+    ///                          WRAPPED - random wallet master key wrapped by key wrap provider, key is id of the wrapping key.
     ///       "rekey_derivation_method": optional<string> Algorithm to use for wallet rekey derivation:
     ///                          ARGON2I_MOD - derive secured wallet master rekey (used by default)
     ///                          ARGON2I_INT - derive secured wallet master rekey (less secured but faster)
     ///                          RAW - raw wallet key master provided (skip derivation).
     ///                                RAW keys can be generated with indy_generate_wallet_key call
+    ///                          WRAPPED - random wallet master rekey wrapped by key wrap provider, rekey is id of the wrapping key.
+    ///       "key_wrap_provider": optional<string> Name of key wrap provider used with WRAPPED key derivation method.
+    ///                             'file' provider (testing builds only) keeps wrapping key in the file, key is name of the file
+    ///                             in wrapping_keys directory of indy home.
+    ///                             Custom providers can be registered with indy_register_wallet_key_wrap_provider call.
     ///   }
     ///
     /// #Returns
@@ -260,6 +328,16 @@ extern "C" {
     ///                              ARGON2I_INT - derive secured export key (less secured but faster)
     ///                              RAW - raw export key provided (skip derivation).
     ///                                RAW keys can be generated with indy_generate_wallet_key call
+    ///     "types": optional<[string]>, Types of records to export (all records are exported by default)
+    ///     "query": optional<object>, WQL query to filter exported records of listed types (requires "types")
+    ///     "since_revision": optional<u64>, Export only changes made after this wallet revision (delta export).
+    ///                       Current revision can be got with indy_get_wallet_revision call.
+    ///                       Delta contains changed records and ids of deleted ones, it can be applied
+    ///                       with indy_import_wallet_delta. Can't be combined with "query".
+    ///     "version": optional<int>, Version of export format (1 by default).
+    ///                1 - single encrypted stream, any damage breaks the rest of the export.
+    ///                2 - each record is authenticated separately and the trailer keeps records count,
+    ///                    so damaged records can be skipped by tolerant import.
     ///   }
     ///
     /// #Returns
@@ -275,6 +353,50 @@ extern "C" {
                                            void           (*fn)(indy_handle_t command_handle_, indy_error_t err)
                                            );
 
+    /// Exports opened wallet to the application provided sink instead of the file.
+    /// Exported data has the same encrypted format as the file produced by indy_export_wallet.
+    ///
+    /// #Params:
+    /// wallet_handle: wallet handle returned by indy_open_wallet
+    /// export_config: JSON containing settings for input operation.
+    ///   {
+    ///     "key": <string>, Key or passphrase used for wallet export key derivation.
+    ///                     Look to key_derivation_method param for information about supported key derivation methods.
+    ///     "key_derivation_method": optional<string> Algorithm to use for export key derivation:
+    ///                              ARGON2I_MOD - derive secured export key (used by default)
+    ///                              ARGON2I_INT - derive secured export key (less secured but faster)
+    ///                              RAW - raw export key provided (skip derivation).
+    ///                                RAW keys can be generated with indy_generate_wallet_key call
+    ///     "types": optional<[string]>, Types of records to export (all records are exported by default)
+    ///     "query": optional<object>, WQL query to filter exported records of listed types (requires "types")
+    ///     "since_revision": optional<u64>, Export only changes made after this wallet revision (delta export).
+    ///                       Current revision can be got with indy_get_wallet_revision call.
+    ///                       Delta contains changed records and ids of deleted ones, it can be applied
+    ///                       with indy_import_wallet_delta. Can't be combined with "query".
+    ///     "version": optional<int>, Version of export format (1 by default).
+    ///                1 - single encrypted stream, any damage breaks the rest of the export.
+    ///                2 - each record is authenticated separately and the trailer keeps records count,
+    ///                    so damaged records can be skipped by tolerant import.
+    ///   }
+    /// sink_handle: application defined handle passed to write callback
+    /// write: callback that receives exported data. It is called from libindy thread
+    ///        several times before cb and must return Success to continue export.
+    ///
+    /// #Returns
+    /// Error code
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+
+    extern indy_error_t indy_export_wallet_to_sink(indy_handle_t  command_handle,
+                                                   indy_handle_t  wallet_handle,
+                                                   const char*    export_config_json,
+                                                   indy_handle_t  sink_handle,
+                                                   indy_error_t   (*writeFn)(indy_handle_t sink_handle, const indy_u8_t* data, size_t data_len),
+                                                   void           (*fn)(indy_handle_t command_handle_, indy_error_t err)
+                                                   );
+
 
     /// Creates a new secure wallet and then imports its content
     /// according to fields provided in import_config
@@ -287,6 +409,8 @@ extern "C" {
     ///         Configured storage uses this identifier to lookup exact wallet data placement.
     ///   "storage_type": optional<string>, Type of the wallet storage. Defaults to 'default'.
     ///                  'Default' storage type allows to store wallet data in the local file.
+    ///                  'indy_inmem' storage type keeps wallet data in process memory until the wallet is deleted.
+    ///                  'postgres' storage type (libindy built with postgres_storage feature) keeps wallet data in Postgres database.
     ///                  Custom storage types can be registered with indy_register_wallet_storage call.
     ///   "storage_config": optional<object>, Storage configuration json. Storage type defines set of supported keys.
     ///                     Can be optional if storage supports default configuration.
@@ -309,11 +433,18 @@ extern "C" {
     ///                             ARGON2I_INT - derive secured wallet master key (less secured but faster)
     ///                             RAW - raw wallet key master provided (skip derivation).
     ///                                RAW keys can be generated with indy_generate_wallet_key call
+    ///                             WRAPPED - random wallet master key wrapped by key wrap provider, key is id of the wrapping key.
+    ///   "key_wrap_provider": optional<string> Name of key wrap provider used with WRAPPED key derivation method.
+    ///                         'file' provider (testing builds only) keeps wrapping key in the file, key is name of the file
+    ///                         in wrapping_keys directory of indy home.
+    ///                         Custom providers can be registered with indy_register_wallet_key_wrap_provider call.
     /// }
     /// import_config: Import settings json.
     /// {
     ///   "path": <string>, path of the file that contains exported wallet content
     ///   "key": <string>, key used for export of the wallet
+    ///   "tolerant": optional<bool>, skip damaged records of version 2 export instead of failing (false by default).
+    ///               Use indy_import_wallet_with_report to know how many records were skipped.
     /// }
     ///
     /// #Returns
@@ -330,6 +461,99 @@ extern "C" {
                                            void           (*fn)(indy_handle_t command_handle_, indy_error_t err)
                                            );
 
+    /// Creates a new secure wallet and then imports its content like indy_import_wallet
+    /// and returns report about imported records.
+    ///
+    /// #Params
+    /// config: Wallet configuration json (See indy_import_wallet).
+    /// credentials: Wallet credentials json (See indy_import_wallet).
+    /// import_config: Import settings json (See indy_import_wallet).
+    ///
+    /// #Returns
+    /// report: Import report json.
+    /// {
+    ///   "imported": <int>, count of imported records
+    ///   "skipped": <int>, count of damaged records skipped by tolerant import
+    ///   "expected": optional<int>, count of exported records according to trailer of version 2 export
+    ///               (missed for version 1 export and damaged trailer)
+    /// }
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+
+    extern indy_error_t indy_import_wallet_with_report(indy_handle_t  command_handle,
+                                                       const char*    config,
+                                                       const char*    credentials,
+                                                       const char*    import_config_json,
+                                                       void           (*fn)(indy_handle_t command_handle_, indy_error_t err, const char* report_json)
+                                                       );
+
+    /// Creates a new secure wallet and then imports its content from the application provided source
+    /// instead of the file. Source must provide data in the format produced by indy_export_wallet
+    /// or indy_export_wallet_to_sink.
+    ///
+    /// #Params
+    /// config: Wallet configuration json (See indy_import_wallet).
+    /// credentials: Wallet credentials json (See indy_import_wallet).
+    /// import_config: Import settings json.
+    /// {
+    ///   "key": <string>, key used for export of the wallet
+    ///   "tolerant": optional<bool>, skip damaged records of version 2 export instead of failing (false by default).
+    /// }
+    /// source_handle: application defined handle passed to read callback
+    /// read: callback that provides exported data. It is called from libindy thread
+    ///       several times before cb and must set 0 read length at the end of data.
+    ///
+    /// #Returns
+    /// Error code
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+
+    extern indy_error_t indy_import_wallet_from_source(indy_handle_t  command_handle,
+                                                       const char*    config,
+                                                       const char*    credentials,
+                                                       const char*    import_config_json,
+                                                       indy_handle_t  source_handle,
+                                                       indy_error_t   (*readFn)(indy_handle_t source_handle, indy_u8_t* buf, size_t buf_len, size_t* read_len_p),
+                                                       void           (*fn)(indy_handle_t command_handle_, indy_error_t err)
+                                                       );
+
+    /// Creates a new wallet and copies all records of existing wallet into it.
+    /// Records are streamed directly between wallet storages of the same or different types
+    /// (for example from 'default' to 'postgres' or custom storage), so no export file is needed.
+    ///
+    /// Item keys of the source wallet are kept, so records are copied in encrypted form without re-encryption.
+    /// Only the new wallet master key is derived from destination credentials.
+    /// Numbers of records in both wallets are compared at the end, destination wallet is deleted on failure.
+    /// Source wallet is left untouched and must not be opened during migration.
+    /// Note that expired records aren't migrated, expiry time of other records is kept.
+    /// Wallet history isn't migrated: revisions and records of deleted items used by delta export aren't copied,
+    /// so revision got from source wallet can't be used as "since_revision" for destination wallet.
+    ///
+    /// #Params
+    /// src_config: Source wallet configuration json (See indy_create_wallet).
+    /// src_credentials: Source wallet credentials json (See indy_create_wallet).
+    /// dst_config: Destination wallet configuration json (See indy_create_wallet).
+    /// dst_credentials: Destination wallet credentials json (See indy_create_wallet).
+    ///
+    /// #Returns
+    /// Error code
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+
+    extern indy_error_t indy_migrate_wallet(indy_handle_t  command_handle,
+                                            const char*    src_config,
+                                            const char*    src_credentials,
+                                            const char*    dst_config,
+                                            const char*    dst_credentials,
+                                            void           (*fn)(indy_handle_t command_handle_, indy_error_t err)
+                                            );
+
     /// Closes opened wallet and frees allocated resources.
     ///
     /// #Params
@@ -356,6 +580,8 @@ extern "C" {
     ///         Configured storage uses this identifier to lookup exact wallet data placement.
     ///   "storage_type": optional<string>, Type of the wallet storage. Defaults to 'default'.
     ///                  'Default' storage type allows to store wallet data in the local file.
+    ///                  'indy_inmem' storage type keeps wallet data in process memory until the wallet is deleted.
+    ///                  'postgres' storage type (libindy built with postgres_storage feature) keeps wallet data in Postgres database.
     ///                  Custom storage types can be registered with indy_register_wallet_storage call.
     ///   "storage_config": optional<object>, Storage configuration json. Storage type defines set of supported keys.
     ///                     Can be optional if storage supports default configuration.
@@ -365,6 +591,8 @@ extern "C" {
     ///             Defaults to $HOME/.indy_client/wallet.
     ///             Wallet will be stored in the file {path}/{id}/sqlite.db
     ///   }
+    ///   "profile": optional<string>, Name of the profile to delete, the storage of the wallet with all its profiles
+    ///              is deleted if not set.
     /// }
     /// credentials: Wallet credentials json
     /// {
@@ -378,6 +606,11 @@ extern "C" {
     ///                             ARGON2I_INT - derive secured wallet master key (less secured but faster)
     ///                             RAW - raw wallet key master provided (skip derivation).
     ///                                RAW keys can be generated with indy_generate_wallet_key call
+    ///                             WRAPPED - random wallet master key wrapped by key wrap provider, key is id of the wrapping key.
+    ///   "key_wrap_provider": optional<string> Name of key wrap provider used with WRAPPED key derivation method.
+    ///                         'file' provider (testing builds only) keeps wrapping key in the file, key is name of the file
+    ///                         in wrapping_keys directory of indy home.
+    ///                         Custom providers can be registered with indy_register_wallet_key_wrap_provider call.
     /// }
     ///
     /// #Returns
@@ -393,6 +626,208 @@ extern "C" {
                                            void           (*fn)(indy_handle_t command_handle_, indy_error_t err)
                                           );
 
+    /// Lists profiles created inside the storage of the wallet.
+    ///
+    /// Profiles are independent logical wallets with own keys kept in one storage instance.
+    /// Profile is created, opened and deleted by indy_create_wallet, indy_open_wallet and indy_delete_wallet
+    /// with "profile" set in wallet config. Only 'default' storage type supports profiles.
+    ///
+    /// #Params
+    /// config: Wallet configuration json of the default profile of the storage (See indy_create_wallet), "profile" must not be set.
+    /// credentials: Wallet credentials json of the default profile of the storage (See indy_create_wallet).
+    ///
+    /// #Returns
+    /// Error code
+    /// profiles: json array with names of the profiles
+    ///   [string, ...]
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+
+    extern indy_error_t indy_list_wallet_profiles(indy_handle_t  command_handle,
+                                                  const char*    config,
+                                                  const char*    credentials,
+                                                  void           (*fn)(indy_handle_t command_handle_, indy_error_t err, const char* profiles)
+                                                 );
+
+    /// Rotates keys used for encryption of wallet records (item keys) of opened wallet.
+    ///
+    /// Fresh item keys are generated and every record value, id, type and tags are re-encrypted with them in place.
+    /// Records are processed by chunks that are stored atomically and the progress is kept in storage metadata,
+    /// so if the rotation is interrupted it will be finished on the next opening of the wallet.
+    /// Master key (wallet passphrase) isn't changed by this call, use "rekey" of indy_open_wallet for that.
+    ///
+    /// #Params
+    /// wallet_handle: wallet handle returned by indy_open_wallet.
+    /// credentials: Wallet credentials json used to open the wallet (new item keys are protected with wallet master key)
+    /// {
+    ///   "key": string, Key or passphrase used for wallet key derivation.
+    ///                  Look to key_derivation_method param for information about supported key derivation methods.
+    ///   "key_derivation_method": optional<string> Algorithm to use for wallet key derivation:
+    ///                             ARGON2I_MOD - derive secured wallet master key (used by default)
+    ///                             ARGON2I_INT - derive secured wallet master key (less secured but faster)
+    ///                             RAW - raw wallet key master provided (skip derivation).
+    ///                                RAW keys can be generated with indy_generate_wallet_key call
+    ///                             WRAPPED - random wallet master key wrapped by key wrap provider, key is id of the wrapping key.
+    ///   "key_wrap_provider": optional<string> Name of key wrap provider used with WRAPPED key derivation method.
+    ///                         'file' provider (testing builds only) keeps wrapping key in the file, key is name of the file
+    ///                         in wrapping_keys directory of indy home.
+    ///                         Custom providers can be registered with indy_register_wallet_key_wrap_provider call.
+    /// }
+    ///
+    /// #Returns
+    /// Error code
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+
+    extern indy_error_t indy_rotate_wallet_item_keys(indy_handle_t  command_handle,
+                                                     indy_handle_t  wallet_handle,
+                                                     const char*    credentials,
+                                                     void           (*fn)(indy_handle_t command_handle_, indy_error_t err)
+                                                    );
+
+    /// Applies delta export (created by indy_export_wallet with "since_revision") to opened wallet.
+    ///
+    /// Records changed in delta are created or replaced and records deleted since base revision are removed.
+    /// Applying the same delta twice has no additional effect.
+    ///
+    /// #Params
+    /// wallet_handle: wallet handle returned by indy_open_wallet.
+    /// import_config: Import settings json.
+    /// {
+    ///   "path": <string>, path of the file that contains delta export
+    ///   "key": <string>, key used for export of the delta
+    /// }
+    ///
+    /// #Returns
+    /// Error code
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+
+    extern indy_error_t indy_import_wallet_delta(indy_handle_t  command_handle,
+                                                 indy_handle_t  wallet_handle,
+                                                 const char*    import_config,
+                                                 void           (*fn)(indy_handle_t command_handle_, indy_error_t err)
+                                                );
+
+    /// Returns current revision of opened wallet.
+    ///
+    /// Revision is increased on every change of wallet records and can be passed as "since_revision"
+    /// to indy_export_wallet for creation of delta export.
+    /// Only 'default' storage type supports change tracking.
+    ///
+    /// #Params
+    /// wallet_handle: wallet handle returned by indy_open_wallet.
+    ///
+    /// #Returns
+    /// revision: current wallet revision
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+
+    extern indy_error_t indy_get_wallet_revision(indy_handle_t  command_handle,
+                                                 indy_handle_t  wallet_handle,
+                                                 void           (*fn)(indy_handle_t command_handle_, indy_error_t err, unsigned long long revision)
+                                                );
+
+    /// Checks integrity of opened wallet and optionally repairs it.
+    ///
+    /// Every wallet record is checked that its type, id, value and tags can be decrypted with the wallet keys
+    /// and that value of known Indy record types (Indy::Did, Indy::Key, Indy::Credential, ...) is a valid json
+    /// of corresponding object. Also storage is checked for tags that reference missing records.
+    ///
+    /// In repair mode records that failed the check are moved to the storage quarantine (they are kept in
+    /// the wallet file, but aren't visible through any wallet call) and orphan tags are removed.
+    /// Repair is supported only by 'default' storage type.
+    ///
+    /// #Params
+    /// wallet_handle: wallet handle returned by indy_open_wallet.
+    /// config: (optional) check configuration json.
+    /// {
+    ///   "repair": optional<bool>, Quarantine records that failed the check and remove orphan tags (false by default)
+    /// }
+    ///
+    /// #Returns
+    /// report: Check report json.
+    /// {
+    ///   "records": <int>, count of checked records
+    ///   "orphan_tags": <int>, count of tags that reference missing records
+    ///   "problems": [{
+    ///       "type": <string>, record type (missed if it can't be decrypted)
+    ///       "id": <string>, record id (missed if it can't be decrypted)
+    ///       "kind": <string>, one of "type_decryption", "id_decryption", "value_decryption", "tags_decryption", "invalid_value"
+    ///       "message": <string>, problem description
+    ///       "quarantined": <bool>, whether record was moved to quarantine
+    ///   }],
+    ///   "repaired": <bool>, whether repair was performed
+    /// }
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+
+    extern indy_error_t indy_check_wallet(indy_handle_t  command_handle,
+                                          indy_handle_t  wallet_handle,
+                                          const char*    config,
+                                          void           (*fn)(indy_handle_t command_handle_, indy_error_t err, const char* report)
+                                         );
+
+    /// Subscribes to changes of wallet records.
+    ///
+    /// on_change callback is called every time a record is added, updated or deleted
+    /// through any handle opened for the same wallet (including read-only ones).
+    /// Changes made by indy_import_wallet_delta are reported too, records quarantined by indy_check_wallet
+    /// are reported as deleted.
+    /// Subscription is active until indy_wallet_unsubscribe is called or wallet_handle is closed.
+    ///
+    /// Note that on_change is called from libindy thread, so it must not block.
+    ///
+    /// #Params
+    /// wallet_handle: wallet handle returned by indy_open_wallet.
+    /// context: caller handle that will be passed to on_change callback
+    /// on_change: record change callback
+    ///     context: context passed to indy_wallet_subscribe
+    ///     type_: type of changed record
+    ///     id: id of changed record
+    ///     operation: kind of change: "add", "update" or "delete"
+    ///
+    /// #Returns
+    /// subscription_handle: subscription handle (See indy_wallet_unsubscribe)
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+
+    extern indy_error_t indy_wallet_subscribe(indy_handle_t  command_handle,
+                                              indy_handle_t  wallet_handle,
+                                              indy_handle_t  context,
+                                              void           (*on_change)(indy_handle_t context, const char* type_, const char* id, const char* operation),
+                                              void           (*fn)(indy_handle_t command_handle_, indy_error_t err, indy_handle_t subscription_handle)
+                                             );
+
+    /// Cancels subscription to changes of wallet records.
+    ///
+    /// #Params
+    /// subscription_handle: subscription handle returned by indy_wallet_subscribe.
+    ///
+    /// #Returns
+    /// err: Error code
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+
+    extern indy_error_t indy_wallet_unsubscribe(indy_handle_t  command_handle,
+                                                indy_handle_t  subscription_handle,
+                                                void           (*fn)(indy_handle_t command_handle_, indy_error_t err)
+                                               );
+
     /// Generate wallet master key.
     /// Returned key is compatible with "RAW" key derivation method.
     /// It allows to avoid expensive key derivation for use cases when wallet keys can be stored in a secure enclave.
@@ -8,6 +8,14 @@ pub struct Config {
     pub id: String,
     pub storage_type: Option<String>,
     pub storage_config: Option<Value>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub read_only: bool,
+    // name of the profile inside the storage, default profile of the storage is used if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    // max prefix length of blind indexes by names of encrypted tags, enables $prefix queries on these tags
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub prefix_indexes: HashMap<String, usize>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -18,7 +26,10 @@ pub struct Credentials {
     #[serde(default = "default_key_derivation_method")]
     pub key_derivation_method: KeyDerivationMethod,
     #[serde(default = "default_key_derivation_method")]
-    pub rekey_derivation_method: KeyDerivationMethod
+    pub rekey_derivation_method: KeyDerivationMethod,
+    // name of registered key wrap provider, used with WRAPPED key derivation method
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_wrap_provider: Option<String>,
 }
 
 #[allow(non_camel_case_types)]
@@ -26,7 +37,9 @@ pub struct Credentials {
 pub enum KeyDerivationMethod {
     RAW,
     ARGON2I_MOD,
-    ARGON2I_INT
+    ARGON2I_INT,
+    // master key is random and stored wrapped by key wrap provider, key is id of wrapping key
+    WRAPPED
 }
 
 fn default_key_derivation_method() -> KeyDerivationMethod {
@@ -36,9 +49,22 @@ fn default_key_derivation_method() -> KeyDerivationMethod {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ExportConfig {
     pub key: String,
+    #[serde(default)]
     pub path: String,
     #[serde(default = "default_key_derivation_method")]
-    pub key_derivation_method: KeyDerivationMethod
+    pub key_derivation_method: KeyDerivationMethod,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub types: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since_revision: Option<u64>,
+    // version of export format, v1 is used by default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<u32>,
+    // import skips damaged records of v2 export instead of failing
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub tolerant: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -46,6 +72,12 @@ pub struct KeyConfig {
     pub seed: Option<String>
 }
 
+#[derive(Debug, Deserialize, Default)]
+pub struct CheckConfig {
+    #[serde(default)]
+    pub repair: bool
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Record {
     // Wallet record type
@@ -57,15 +89,54 @@ pub struct Record {
     pub value: String,
     // Wallet record tags
     pub tags: HashMap<String, String>,
+    // Expiry time of wallet record as unix timestamp in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
 }
 
 pub type Tags = HashMap<String, String>;
 
+// limits count of blind index tags stored for every value of indexed tag
+pub const MAX_PREFIX_INDEX_LEN: usize = 64;
+
 impl Validatable for Config {
     fn validate(&self) -> Result<(), String> {
         if self.id.is_empty() {
             return Err("Wallet id is empty".to_string());
         }
+        if let Some(ref profile) = self.profile {
+            if profile.is_empty() {
+                return Err("Wallet profile is empty".to_string());
+            }
+        }
+        for (tag_name, len) in self.prefix_indexes.iter() {
+            if tag_name.is_empty() || tag_name.starts_with('~') {
+                return Err(format!("Prefix index can be set for encrypted tags only, got: \"{}\"", tag_name));
+            }
+            if *len == 0 || *len > MAX_PREFIX_INDEX_LEN {
+                return Err(format!("Prefix index length for tag \"{}\" must be between 1 and {}", tag_name, MAX_PREFIX_INDEX_LEN));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Validatable for ExportConfig {
+    fn validate(&self) -> Result<(), String> {
+        if self.query.is_some() && self.types.is_none() {
+            return Err("Export query can be used only together with types".to_string());
+        }
+        if self.query.is_some() && self.since_revision.is_some() {
+            return Err("Export query can't be used for delta export".to_string());
+        }
+        if let Some(version) = self.version {
+            if version != 1 && version != 2 {
+                return Err(format!("Unsupported export version: {}", version));
+            }
+        }
+        if let KeyDerivationMethod::WRAPPED = self.key_derivation_method {
+            return Err("WRAPPED key derivation method can't be used for export".to_string());
+        }
         Ok(())
     }
 }
@@ -96,6 +96,8 @@ pub enum IndyErrorKind {
     WalletItemAlreadyExists,
     #[fail(display = "Wallet query error")]
     WalletQueryError,
+    #[fail(display = "Wallet is opened in read-only mode")]
+    WalletReadOnly,
     // DID errors
     #[fail(display = "DID already exists")]
     DIDAlreadyExists,
@@ -276,6 +278,24 @@ impl From<rusqlite::Error> for IndyError {
     }
 }
 
+#[cfg(feature = "postgres_storage")]
+impl From<postgres::Error> for IndyError {
+    fn from(err: postgres::Error) -> IndyError {
+        if err.code() == Some(&postgres::error::UNIQUE_VIOLATION) || err.code() == Some(&postgres::error::INTEGRITY_CONSTRAINT_VIOLATION) {
+            err.to_indy(IndyErrorKind::WalletItemAlreadyExists, "Wallet item already exists")
+        } else {
+            err.to_indy(IndyErrorKind::IOError, "Unexpected postgres error")
+        }
+    }
+}
+
+#[cfg(feature = "postgres_storage")]
+impl From<r2d2::Error> for IndyError {
+    fn from(err: r2d2::Error) -> IndyError {
+        err.to_indy(IndyErrorKind::IOError, "Unable to get postgres connection from pool")
+    }
+}
+
 impl<T> From<IndyResult<T>> for ErrorCode {
     fn from(r: Result<T, IndyError>) -> ErrorCode {
         match r {
@@ -358,6 +378,7 @@ impl From<IndyErrorKind> for ErrorCode {
             IndyErrorKind::WalletItemNotFound => ErrorCode::WalletItemNotFound,
             IndyErrorKind::WalletItemAlreadyExists => ErrorCode::WalletItemAlreadyExists,
             IndyErrorKind::WalletQueryError => ErrorCode::WalletQueryError,
+            IndyErrorKind::WalletReadOnly => ErrorCode::WalletReadOnlyError,
             IndyErrorKind::DIDAlreadyExists => ErrorCode::DidAlreadyExistsError,
             IndyErrorKind::UnknownPaymentMethodType => ErrorCode::PaymentUnknownMethodError,
             IndyErrorKind::IncompatiblePaymentMethods => ErrorCode::PaymentIncompatibleMethodsError,
@@ -448,6 +469,7 @@ impl From<ErrorCode> for IndyErrorKind {
             ErrorCode::WalletItemNotFound => IndyErrorKind::WalletItemNotFound,
             ErrorCode::WalletItemAlreadyExists => IndyErrorKind::WalletItemAlreadyExists,
             ErrorCode::WalletQueryError => IndyErrorKind::WalletQueryError,
+            ErrorCode::WalletReadOnlyError => IndyErrorKind::WalletReadOnly,
             ErrorCode::DidAlreadyExistsError => IndyErrorKind::DIDAlreadyExists,
             ErrorCode::PaymentUnknownMethodError => IndyErrorKind::UnknownPaymentMethodType,
             ErrorCode::PaymentIncompatibleMethodsError => IndyErrorKind::IncompatiblePaymentMethods,
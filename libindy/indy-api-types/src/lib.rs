@@ -190,6 +190,9 @@ pub enum ErrorCode
     // Returned if provided wallet query is invalid
     WalletQueryError = 214,
 
+    // Returned if mutating operation is called on the wallet opened in read-only mode
+    WalletReadOnlyError = 215,
+
     // Ledger errors
     // Trying to open pool ledger that wasn't created before
     PoolLedgerNotCreatedError = 300,
@@ -538,6 +541,12 @@ pub mod wallet {
     ///    retrieveType: (optional, false by default) Retrieve record type,
     ///    retrieveValue: (optional, true by default) Retrieve record value,
     ///    retrieveTags: (optional, false by default) Retrieve record tags,
+    ///    sortBy: (optional) {
+    ///      tagName: name of un-encrypted tag in the same form as in query_json ("~<base64 of encrypted name>"),
+    ///      order: (optional, "asc" by default) "asc" or "desc"
+    ///    },
+    ///    offset: (optional, 0 by default) Number of records to skip,
+    ///    limit: (optional, no limit by default) Max number of records to return,
     ///  }
     /// search_handle_p: pointer to store wallet search handle
     pub type WalletSearchRecords = extern fn(storage_handle: StorageHandle,
@@ -586,4 +595,77 @@ pub mod wallet {
     pub type WalletFreeSearch = extern fn(storage_handle: StorageHandle,
                                           search_handle: i32) -> ErrorCode;
 
+    /// Write the next portion of exported wallet data to the export sink
+    ///
+    /// #Params
+    /// sink_handle: export sink handle (See indy_export_wallet_to_sink)
+    /// data: exported data (pointer to buffer)
+    /// data_len: exported data (buffer size)
+    pub type WalletExportSinkWrite = extern fn(sink_handle: IndyHandle,
+                                               data: *const u8,
+                                               data_len: usize) -> ErrorCode;
+
+    /// Read the next portion of exported wallet data from the import source
+    ///
+    /// #Params
+    /// source_handle: import source handle (See indy_import_wallet_from_source)
+    /// buf: buffer to fill with data
+    /// buf_len: buffer size
+    /// read_len_p: pointer to store count of bytes written to buffer
+    ///             Note 0 must be returned if there is no more data in source
+    pub type WalletImportSourceRead = extern fn(source_handle: IndyHandle,
+                                                buf: *mut u8,
+                                                buf_len: usize,
+                                                read_len_p: *mut usize) -> ErrorCode;
+
+    /// Wrap wallet master key with the key managed by key wrap provider (For example, KMS key)
+    ///
+    /// #Params
+    /// key_id: id of wrapping key (the key passed in wallet credentials)
+    /// key: wallet master key (pointer to buffer)
+    /// key_len: wallet master key (buffer size)
+    /// wrapped_key_p: pointer to store wrapped key buffer, must stay valid until free handler is called
+    /// wrapped_key_len_p: pointer to store wrapped key buffer size
+    /// key_handle_p: pointer to store handle of returned buffer (See WalletKeyFree)
+    pub type WalletKeyWrap = extern fn(key_id: *const c_char,
+                                       key: *const u8,
+                                       key_len: usize,
+                                       wrapped_key_p: *mut *const u8,
+                                       wrapped_key_len_p: *mut usize,
+                                       key_handle_p: *mut IndyHandle) -> ErrorCode;
+
+    /// Unwrap wallet master key previously wrapped by WalletKeyWrap
+    ///
+    /// #Params
+    /// key_id: id of wrapping key (the key passed in wallet credentials)
+    /// wrapped_key: wrapped master key (pointer to buffer)
+    /// wrapped_key_len: wrapped master key (buffer size)
+    /// key_p: pointer to store master key buffer, must stay valid until free handler is called
+    /// key_len_p: pointer to store master key buffer size
+    /// key_handle_p: pointer to store handle of returned buffer (See WalletKeyFree)
+    pub type WalletKeyUnwrap = extern fn(key_id: *const c_char,
+                                         wrapped_key: *const u8,
+                                         wrapped_key_len: usize,
+                                         key_p: *mut *const u8,
+                                         key_len_p: *mut usize,
+                                         key_handle_p: *mut IndyHandle) -> ErrorCode;
+
+    /// Free buffer returned by WalletKeyWrap or WalletKeyUnwrap
+    ///
+    /// #Params
+    /// key_handle: handle of returned buffer
+    pub type WalletKeyFree = extern fn(key_handle: IndyHandle) -> ErrorCode;
+
+    /// Notify about change of wallet record
+    ///
+    /// #Params
+    /// context: context handle (See indy_wallet_subscribe)
+    /// type_: type of changed record
+    /// id: id of changed record
+    /// operation: kind of change: "add", "update" or "delete"
+    pub type WalletRecordChanged = extern fn(context: IndyHandle,
+                                             type_: *const c_char,
+                                             id: *const c_char,
+                                             operation: *const c_char);
+
 }
\ No newline at end of file
@@ -99,6 +99,25 @@ pub fn decrypt(data: &[u8], key: &Key, nonce: &Nonce) -> Result<Vec<u8>, IndyErr
         .map_err(|_| IndyError::from_msg(IndyErrorKind::InvalidStructure, "Unable to open sodium chacha20poly1305_ietf"))
 }
 
+pub fn encrypt_with_ad(data: &[u8], ad: &[u8], key: &Key, nonce: &Nonce) -> Vec<u8> {
+    chacha20poly1305_ietf::seal(
+        data,
+        Some(ad),
+        &nonce.0,
+        &key.0,
+    )
+}
+
+pub fn decrypt_with_ad(data: &[u8], ad: &[u8], key: &Key, nonce: &Nonce) -> Result<Vec<u8>, IndyError> {
+    chacha20poly1305_ietf::open(
+        &data,
+        Some(ad),
+        &nonce.0,
+        &key.0,
+    )
+        .map_err(|_| IndyError::from_msg(IndyErrorKind::InvalidStructure, "Unable to open sodium chacha20poly1305_ietf"))
+}
+
 pub struct Writer<W: Write> {
     buffer: Vec<u8>,
     chunk_size: usize,
@@ -319,6 +338,21 @@ mod tests {
         assert_eq!(data, u)
     }
 
+    #[test]
+    fn encrypt_with_ad_decrypt_with_ad_works() {
+        let data = randombytes(16);
+        let ad = b"additional data";
+
+        let key = gen_key();
+        let nonce = gen_nonce();
+        let c = encrypt_with_ad(&data, ad, &key, &nonce);
+        let u = decrypt_with_ad(&c, ad, &key, &nonce).unwrap();
+        assert_eq!(data, u);
+
+        assert!(decrypt_with_ad(&c, b"other data", &key, &nonce).is_err());
+        assert!(decrypt(&c, &key, &nonce).is_err());
+    }
+
     #[test]
     fn nonce_serialize_deserialize_works() {
         let nonce = gen_nonce();
@@ -0,0 +1,82 @@
+//! `std::future` helpers for Rust-native async API.
+//!
+//! Libindy doesn't depend on any async runtime, so results computed on other threads
+//! are delivered through `oneshot` channels which receiving halves can be awaited
+//! from any executor (tokio, async-std, `block_on` below).
+
+use futures::executor::ThreadPool;
+
+pub use futures::channel::oneshot;
+pub use futures::executor::block_on;
+
+/// Number of worker threads running blocking computations.
+const BLOCKING_POOL_SIZE: usize = 4;
+
+lazy_static! {
+    static ref BLOCKING_POOL: ThreadPool = ThreadPool::builder()
+        .pool_size(BLOCKING_POOL_SIZE)
+        .name_prefix("indy-blocking-")
+        .create()
+        .expect("Can't create pool of blocking computations");
+}
+
+/// Runs `f` on a bounded pool of worker threads and returns future resolving to its result.
+/// Intended for expensive computations like key derivation that mustn't block executor,
+/// computations are queued if all workers are busy.
+pub fn spawn_blocking<T, F>(f: F) -> oneshot::Receiver<T> where T: Send + 'static, F: FnOnce() -> T + Send + 'static {
+    let (sender, receiver) = oneshot::channel();
+
+    BLOCKING_POOL.spawn_ok(async move {
+        // receiver may be dropped if its future is canceled
+        let _ = sender.send(f());
+    });
+
+    receiver
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+    use std::time::Duration;
+
+    use futures::future::join_all;
+
+    use super::*;
+
+    #[test]
+    fn spawn_blocking_works() {
+        let res = block_on(async {
+            let a = spawn_blocking(|| 2).await.unwrap();
+            spawn_blocking(move || a * 3).await.unwrap()
+        });
+
+        assert_eq!(6, res);
+    }
+
+    #[test]
+    fn spawn_blocking_works_for_bounded_pool() {
+        let running = Arc::new(AtomicUsize::new(0));
+        let max_running = Arc::new(AtomicUsize::new(0));
+
+        let receivers = (0..BLOCKING_POOL_SIZE * 3)
+            .map(|_| {
+                let running = running.clone();
+                let max_running = max_running.clone();
+
+                spawn_blocking(move || {
+                    let count = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_running.fetch_max(count, Ordering::SeqCst);
+                    thread::sleep(Duration::from_millis(10));
+                    running.fetch_sub(1, Ordering::SeqCst);
+                })
+            });
+
+        for res in block_on(join_all(receivers)) {
+            res.unwrap();
+        }
+
+        assert!(max_running.load(Ordering::SeqCst) <= BLOCKING_POOL_SIZE);
+    }
+}
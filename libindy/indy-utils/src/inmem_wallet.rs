@@ -5,7 +5,9 @@ use super::ErrorCode;
 use super::sequence;
 
 use self::libc::c_char;
+use self::serde_json::Value;
 
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::ffi::{CString, CStr};
 use std::str::Utf8Error;
@@ -593,8 +595,14 @@ impl InmemWallet {
         ErrorCode::Success
     }
 
-    pub extern "C" fn search_records(xhandle: i32, type_: *const c_char, _query_json: *const c_char, _options_json: *const c_char, handle: *mut i32) -> ErrorCode {
+    pub extern "C" fn search_records(xhandle: i32, type_: *const c_char, query_json: *const c_char, _options_json: *const c_char, handle: *mut i32) -> ErrorCode {
         check_useful_c_str!(type_, ErrorCode::CommonInvalidStructure);
+        check_useful_c_str!(query_json, ErrorCode::CommonInvalidStructure);
+
+        let query = match serde_json::from_str::<Value>(&query_json) {
+            Ok(query) => query,
+            Err(_) => return ErrorCode::CommonInvalidStructure
+        };
 
         let handles = INMEM_OPEN_WALLETS.lock().unwrap();
 
@@ -613,9 +621,13 @@ impl InmemWallet {
         let wallet = wallets.get(&wallet_context.id).unwrap();
 
         let search_records = wallet.records
-            .iter()
-            .filter(|&(key, _)| key.starts_with(&type_))
-            .map(|(_, value)| value.clone())
+            .values()
+            .filter(|record| record.type_.to_str() == Ok(type_.as_str()))
+            .filter(|record| {
+                let tags = serde_json::from_str::<HashMap<String, String>>(record.tags.to_str().unwrap()).unwrap();
+                InmemWallet::match_query(&query, &tags)
+            })
+            .cloned()
             .collect::<Vec<InmemWalletRecord>>();
 
         let search_handle = sequence::get_next_id();
@@ -753,6 +765,71 @@ impl InmemWallet {
         ErrorCode::Success
     }
 
+    // Matches record tags against WQL query in the form it is passed by plugged storage.
+    // Tag names and values of encrypted tags are base64 encoded both in the query and in the tags.
+    fn match_query(query: &Value, tags: &HashMap<String, String>) -> bool {
+        let query = match query.as_object() {
+            Some(query) => query,
+            None => return false
+        };
+
+        query.iter().all(|(key, value)| match key.as_str() {
+            "$and" => value.as_array().map(|queries| queries.iter().all(|query| InmemWallet::match_query(query, tags))).unwrap_or(false),
+            "$or" => value.as_array().map(|queries| queries.iter().any(|query| InmemWallet::match_query(query, tags))).unwrap_or(false),
+            "$not" => !InmemWallet::match_query(value, tags),
+            tag_name => InmemWallet::match_tag(tags.get(tag_name).map(String::as_str), value),
+        })
+    }
+
+    fn match_tag(tag_value: Option<&str>, condition: &Value) -> bool {
+        let condition = match *condition {
+            Value::String(ref target) => return tag_value == Some(target.as_str()),
+            Value::Object(ref condition) => condition,
+            _ => return false
+        };
+
+        condition.iter().all(|(operator, target)| {
+            let tag_value = match (operator.as_str(), tag_value) {
+                ("$exist", tag_value) => return target.as_bool() == Some(tag_value.is_some()),
+                (_, Some(tag_value)) => tag_value,
+                (_, None) => return false
+            };
+
+            match operator.as_str() {
+                "$neq" => target.as_str() != Some(tag_value),
+                "$in" => target.as_array().map(|targets| targets.iter().any(|target| target.as_str() == Some(tag_value))).unwrap_or(false),
+                "$gt" => InmemWallet::compare(tag_value, target) == Some(Ordering::Greater),
+                "$gte" => InmemWallet::compare(tag_value, target).map(|ordering| ordering != Ordering::Less).unwrap_or(false),
+                "$lt" => InmemWallet::compare(tag_value, target) == Some(Ordering::Less),
+                "$lte" => InmemWallet::compare(tag_value, target).map(|ordering| ordering != Ordering::Greater).unwrap_or(false),
+                "$like" => target.as_str().map(|pattern| InmemWallet::like(tag_value.as_bytes(), pattern.as_bytes())).unwrap_or(false),
+                "$prefix" => target.as_str().map(|prefix| tag_value.starts_with(prefix)).unwrap_or(false),
+                _ => false
+            }
+        })
+    }
+
+    // Numeric targets are compared as numbers, others as strings
+    fn compare(tag_value: &str, target: &Value) -> Option<Ordering> {
+        match *target {
+            Value::Number(ref target) => tag_value.parse::<f64>().ok()?.partial_cmp(&target.as_f64()?),
+            Value::String(ref target) => Some(tag_value.cmp(target.as_str())),
+            _ => None
+        }
+    }
+
+    // SQL LIKE matching where '%' matches any sequence and '_' matches any single symbol
+    fn like(value: &[u8], pattern: &[u8]) -> bool {
+        match pattern.split_first() {
+            None => value.is_empty(),
+            Some((&b'%', rest)) => (0..=value.len()).any(|skip| InmemWallet::like(&value[skip..], rest)),
+            Some((&symbol, rest)) => match value.split_first() {
+                Some((&value_symbol, value_rest)) if symbol == b'_' || symbol == value_symbol => InmemWallet::like(value_rest, rest),
+                _ => false
+            }
+        }
+    }
+
     pub fn cleanup() {
         let mut wallets = INMEM_WALLETS.lock().unwrap();
         wallets.clear();
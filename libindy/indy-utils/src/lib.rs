@@ -32,6 +32,7 @@ macro_rules! secret {
 pub mod crypto;
 pub mod ctypes;
 pub mod environment;
+pub mod future;
 pub mod inmem_wallet;
 pub mod sequence;
 #[macro_use]
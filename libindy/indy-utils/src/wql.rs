@@ -18,6 +18,13 @@ pub enum Query {
     Lte(String, String),
     Like(String, String),
     In(String, Vec<String>),
+    Exist(String),
+    // Numeric comparisons, value is kept as the text of JSON number
+    NumGt(String, String),
+    NumGte(String, String),
+    NumLt(String, String),
+    NumLte(String, String),
+    Prefix(String, String),
 }
 
 impl Serialize for Query {
@@ -131,6 +138,12 @@ impl Query {
             Query::Lte(ref tag_name, ref tag_value) => json!({tag_name: {"$lte": tag_value}}),
             Query::Like(ref tag_name, ref tag_value) => json!({tag_name: {"$like": tag_value}}),
             Query::In(ref tag_name, ref tag_values) => json!({tag_name: {"$in": tag_values}}),
+            Query::Exist(ref tag_name) => json!({tag_name: {"$exist": true}}),
+            Query::NumGt(ref tag_name, ref tag_value) => json!({tag_name: {"$gt": number_to_value(tag_value)}}),
+            Query::NumGte(ref tag_name, ref tag_value) => json!({tag_name: {"$gte": number_to_value(tag_value)}}),
+            Query::NumLt(ref tag_name, ref tag_value) => json!({tag_name: {"$lt": number_to_value(tag_value)}}),
+            Query::NumLte(ref tag_name, ref tag_value) => json!({tag_name: {"$lte": number_to_value(tag_value)}}),
+            Query::Prefix(ref tag_name, ref tag_value) => json!({tag_name: {"$prefix": tag_value}}),
             Query::And(ref operators) => {
                 if !operators.is_empty() {
                     json!({
@@ -154,6 +167,12 @@ impl Query {
     }
 }
 
+fn number_to_value(value: &str) -> serde_json::Value {
+    serde_json::from_str::<serde_json::Number>(value)
+        .map(serde_json::Value::Number)
+        .unwrap_or_else(|_| serde_json::Value::String(value.to_string()))
+}
+
 impl Default for Query {
     fn default() -> Self {
         Query::And(Vec::new())
@@ -239,15 +258,24 @@ fn parse_single_operator(operator_name: String, key: String, value: serde_json::
         ("$neq", serde_json::Value::String(value_)) => Ok(Query::Neq(key, value_)),
         ("$neq", _) => Err("$neq must be used with string"),
         ("$gt", serde_json::Value::String(value_)) => Ok(Query::Gt(key, value_)),
-        ("$gt", _) => Err("$gt must be used with string"),
+        ("$gt", serde_json::Value::Number(value_)) => Ok(Query::NumGt(key, value_.to_string())),
+        ("$gt", _) => Err("$gt must be used with string or number"),
         ("$gte", serde_json::Value::String(value_)) => Ok(Query::Gte(key, value_)),
-        ("$gte", _) => Err("$gte must be used with string"),
+        ("$gte", serde_json::Value::Number(value_)) => Ok(Query::NumGte(key, value_.to_string())),
+        ("$gte", _) => Err("$gte must be used with string or number"),
         ("$lt", serde_json::Value::String(value_)) => Ok(Query::Lt(key, value_)),
-        ("$lt", _) => Err("$lt must be used with string"),
+        ("$lt", serde_json::Value::Number(value_)) => Ok(Query::NumLt(key, value_.to_string())),
+        ("$lt", _) => Err("$lt must be used with string or number"),
         ("$lte", serde_json::Value::String(value_)) => Ok(Query::Lte(key, value_)),
-        ("$lte", _) => Err("$lte must be used with string"),
+        ("$lte", serde_json::Value::Number(value_)) => Ok(Query::NumLte(key, value_.to_string())),
+        ("$lte", _) => Err("$lte must be used with string or number"),
+        ("$exist", serde_json::Value::Bool(true)) => Ok(Query::Exist(key)),
+        ("$exist", serde_json::Value::Bool(false)) => Ok(Query::Not(Box::new(Query::Exist(key)))),
+        ("$exist", _) => Err("$exist must be used with boolean"),
         ("$like", serde_json::Value::String(value_)) => Ok(Query::Like(key, value_)),
         ("$like", _) => Err("$like must be used with string"),
+        ("$prefix", serde_json::Value::String(value_)) => Ok(Query::Prefix(key, value_)),
+        ("$prefix", _) => Err("$prefix must be used with string"),
         ("$in", serde_json::Value::Array(values)) => {
             let mut target_values: Vec<String> = Vec::with_capacity(values.len());
 
@@ -419,6 +447,52 @@ mod tests {
         assert_eq!(query, expected);
     }
 
+    #[test]
+    fn test_simple_operator_numeric_parse() {
+        let name1 = _random_string(10);
+
+        let query: Query = ::serde_json::from_str(&format!(r#"{{"{}":{{"$gt":10}}}}"#, name1)).unwrap();
+        assert_eq!(query, Query::NumGt(name1.clone(), "10".to_string()));
+
+        let query: Query = ::serde_json::from_str(&format!(r#"{{"{}":{{"$gte":-1.5}}}}"#, name1)).unwrap();
+        assert_eq!(query, Query::NumGte(name1.clone(), "-1.5".to_string()));
+
+        let query: Query = ::serde_json::from_str(&format!(r#"{{"{}":{{"$lt":0}}}}"#, name1)).unwrap();
+        assert_eq!(query, Query::NumLt(name1.clone(), "0".to_string()));
+
+        let query: Query = ::serde_json::from_str(&format!(r#"{{"{}":{{"$lte":9}}}}"#, name1)).unwrap();
+        assert_eq!(query, Query::NumLte(name1.clone(), "9".to_string()));
+
+        let res = ::serde_json::from_str::<Query>(&format!(r#"{{"{}":{{"$like":9}}}}"#, name1));
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_simple_operator_exist_parse() {
+        let name1 = _random_string(10);
+
+        let query: Query = ::serde_json::from_str(&format!(r#"{{"{}":{{"$exist":true}}}}"#, name1)).unwrap();
+        assert_eq!(query, Query::Exist(name1.clone()));
+
+        let query: Query = ::serde_json::from_str(&format!(r#"{{"{}":{{"$exist":false}}}}"#, name1)).unwrap();
+        assert_eq!(query, Query::Not(Box::new(Query::Exist(name1.clone()))));
+
+        let res = ::serde_json::from_str::<Query>(&format!(r#"{{"{}":{{"$exist":"true"}}}}"#, name1));
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_simple_operator_prefix_parse() {
+        let name1 = _random_string(10);
+        let value1 = _random_string(3);
+
+        let query: Query = ::serde_json::from_str(&format!(r#"{{"{}":{{"$prefix":"{}"}}}}"#, name1, value1)).unwrap();
+        assert_eq!(query, Query::Prefix(name1.clone(), value1.clone()));
+
+        let res = ::serde_json::from_str::<Query>(&format!(r#"{{"{}":{{"$prefix":1}}}}"#, name1));
+        assert!(res.is_err());
+    }
+
     #[test]
     fn test_simple_operator_in_plaintext_parse() {
         let name1 = _random_string(10);
@@ -1824,6 +1898,44 @@ mod tests {
         assert_eq!(json, expected);
     }
 
+    #[test]
+    fn test_simple_operator_numeric_to_string() {
+        let name1 = _random_string(10);
+
+        let query = Query::NumGt(name1.clone(), "10".to_string());
+        assert_eq!(::serde_json::to_string(&query).unwrap(), format!(r#"{{"{}":{{"$gt":10}}}}"#, name1));
+
+        let query = Query::NumLte(name1.clone(), "-1.5".to_string());
+        assert_eq!(::serde_json::to_string(&query).unwrap(), format!(r#"{{"{}":{{"$lte":-1.5}}}}"#, name1));
+    }
+
+    #[test]
+    fn test_simple_operator_prefix_to_string() {
+        let name1 = _random_string(10);
+        let value1 = _random_string(3);
+
+        let query = Query::Prefix(name1.clone(), value1.clone());
+
+        let json = ::serde_json::to_string(&query).unwrap();
+
+        let expected = format!(r#"{{"{}":{{"$prefix":"{}"}}}}"#, name1, value1);
+
+        assert_eq!(json, expected);
+    }
+
+    #[test]
+    fn test_simple_operator_exist_to_string() {
+        let name1 = _random_string(10);
+
+        let query = Query::Exist(name1.clone());
+
+        let json = ::serde_json::to_string(&query).unwrap();
+
+        let expected = format!(r#"{{"{}":{{"$exist":true}}}}"#, name1);
+
+        assert_eq!(json, expected);
+    }
+
     #[test]
     fn test_simple_operator_in_multimply_to_string() {
         let name1 = _random_string(10);
@@ -0,0 +1,368 @@
+//! Rust-native async versions of WalletService operations that derive master keys.
+//!
+//! Expensive key derivation runs on the pool of blocking computations, everything else runs
+//! on the thread polling the future. WalletService isn't `Send`, so futures of its `*_async`
+//! methods aren't `Send` too and must be polled on the thread owning the service
+//! (for example inside of tokio `LocalSet` or current thread runtime).
+//!
+//! `WalletServiceHandle` owns WalletService on a dedicated thread and returns `Send` futures,
+//! so it can be used from any executor.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::future::Future;
+use std::rc::Rc;
+use std::thread;
+
+use futures::channel::mpsc;
+use futures::executor::LocalPool;
+use futures::future::LocalBoxFuture;
+use futures::stream::StreamExt;
+use futures::task::LocalSpawnExt;
+
+use indy_api_types::WalletHandle;
+use indy_api_types::domain::wallet::{Config, Credentials, ExportConfig, Tags};
+use indy_api_types::errors::prelude::*;
+use indy_utils::crypto::chacha20poly1305_ietf::Key as MasterKey;
+use indy_utils::future::{oneshot, spawn_blocking};
+
+use crate::{export_version, ImportReport, KeyDerivationData, WalletRecord, WalletService};
+
+// Removes pending data of the operation if its future is dropped before continue step
+// or key derivation failed. Continue step removes the data itself, so removal is no-op then.
+struct PendingGuard<'a, V> {
+    pending: &'a RefCell<HashMap<WalletHandle, V>>,
+    wallet_handle: WalletHandle,
+}
+
+impl<V> Drop for PendingGuard<'_, V> {
+    fn drop(&mut self) {
+        self.pending.borrow_mut().remove(&self.wallet_handle);
+    }
+}
+
+async fn derive_master_key(key_data: &KeyDerivationData) -> IndyResult<MasterKey> {
+    let key_data = key_data.clone();
+
+    spawn_blocking(move || key_data.calc_master_key()).await
+        .map_err(|_| err_msg(IndyErrorKind::InvalidState, "Key derivation was canceled"))?
+}
+
+impl WalletService {
+    pub async fn create_wallet_async(&self, config: &Config, credentials: &Credentials) -> IndyResult<()> {
+        let key_data = self.new_key_derivation_data(&credentials.key, &credentials.key_derivation_method, credentials.key_wrap_provider.as_ref())?;
+
+        let master_key = derive_master_key(&key_data).await?;
+
+        self.create_wallet(config, credentials, (&key_data, &master_key))
+    }
+
+    pub async fn open_wallet_async(&self, config: &Config, credentials: &Credentials) -> IndyResult<WalletHandle> {
+        let (wallet_handle, key_data, rekey_data) = self.open_wallet_prepare(config, credentials)?;
+        let _guard = PendingGuard { pending: &self.pending_for_open, wallet_handle };
+
+        let master_key = derive_master_key(&key_data).await?;
+
+        let rekey = match rekey_data {
+            Some(ref rekey_data) => Some(derive_master_key(rekey_data).await?),
+            None => None
+        };
+
+        self.open_wallet_continue(wallet_handle, (&master_key, rekey.as_ref()))
+    }
+
+    pub async fn delete_wallet_async(&self, config: &Config, credentials: &Credentials) -> IndyResult<()> {
+        let (metadata, key_data) = self.delete_wallet_prepare(config, credentials)?;
+
+        let master_key = derive_master_key(&key_data).await?;
+
+        self.delete_wallet_continue(config, credentials, &metadata, &master_key)
+    }
+
+    pub async fn list_profiles_async(&self, config: &Config, credentials: &Credentials) -> IndyResult<Vec<String>> {
+        let (metadata, key_data) = self.list_profiles_prepare(config, credentials)?;
+
+        let master_key = derive_master_key(&key_data).await?;
+
+        self.list_profiles_continue(config, credentials, &metadata, &master_key)
+    }
+
+    pub async fn rotate_item_keys_async(&self, wallet_handle: WalletHandle, credentials: &Credentials) -> IndyResult<()> {
+        let key_data = self.rotate_item_keys_prepare(wallet_handle, credentials)?;
+
+        let master_key = derive_master_key(&key_data).await?;
+
+        self.rotate_item_keys_continue(wallet_handle, &master_key)
+    }
+
+    pub async fn export_wallet_async(&self, wallet_handle: WalletHandle, export_config: &ExportConfig) -> IndyResult<()> {
+        let version = export_version(export_config.version)?;
+        let key_data = KeyDerivationData::from_passphrase_with_new_salt(&export_config.key, &export_config.key_derivation_method)?;
+
+        let export_key = derive_master_key(&key_data).await?;
+
+        self.export_wallet(wallet_handle, export_config, version, (&key_data, &export_key))
+    }
+
+    pub async fn import_wallet_async(&self, config: &Config, credentials: &Credentials, export_config: &ExportConfig) -> IndyResult<ImportReport> {
+        let (wallet_handle, key_data, import_key_data) = self.import_wallet_prepare(config, credentials, export_config)?;
+        let _guard = PendingGuard { pending: &self.pending_for_import, wallet_handle };
+
+        let import_key = derive_master_key(&import_key_data).await?;
+        let master_key = derive_master_key(&key_data).await?;
+
+        self.import_wallet_continue(wallet_handle, config, credentials, (import_key, master_key))
+    }
+
+    pub async fn import_wallet_delta_async(&self, wallet_handle: WalletHandle, export_config: &ExportConfig) -> IndyResult<()> {
+        let import_key_data = self.import_wallet_delta_prepare(wallet_handle, export_config)?;
+        let _guard = PendingGuard { pending: &self.pending_for_delta_import, wallet_handle };
+
+        let import_key = derive_master_key(&import_key_data).await?;
+
+        self.import_wallet_delta_continue(wallet_handle, &import_key)
+    }
+
+    pub async fn migrate_wallet_async(&self,
+                                      src_config: &Config,
+                                      src_credentials: &Credentials,
+                                      dst_config: &Config,
+                                      dst_credentials: &Credentials) -> IndyResult<usize> {
+        let (wallet_handle, src_key_data, key_data) = self.migrate_wallet_prepare(src_config, src_credentials, dst_config, dst_credentials)?;
+        let _guard = PendingGuard { pending: &self.pending_for_migrate, wallet_handle };
+
+        let src_master_key = derive_master_key(&src_key_data).await?;
+        let master_key = derive_master_key(&key_data).await?;
+
+        self.migrate_wallet_continue(wallet_handle, dst_config, dst_credentials, (src_master_key, master_key))
+    }
+}
+
+type Job = Box<dyn FnOnce(Rc<WalletService>) -> LocalBoxFuture<'static, ()> + Send>;
+
+///
+/// Handle of WalletService owned by a dedicated thread.
+///
+/// The thread polls futures of the service operations, so operations of different wallets
+/// are executed concurrently while their keys are derived on the pool of blocking computations.
+/// The handle can be cloned and shared between threads, returned futures are `Send`.
+/// The thread is stopped when all clones of the handle are dropped.
+///
+#[derive(Clone)]
+pub struct WalletServiceHandle {
+    jobs: mpsc::UnboundedSender<Job>,
+}
+
+impl WalletServiceHandle {
+    pub fn new() -> WalletServiceHandle {
+        let (jobs, mut receiver) = mpsc::unbounded::<Job>();
+
+        thread::Builder::new()
+            .name("indy-wallet".to_string())
+            .spawn(move || {
+                let service = Rc::new(WalletService::new());
+                let mut pool = LocalPool::new();
+                let spawner = pool.spawner();
+
+                pool.run_until(async move {
+                    while let Some(job) = receiver.next().await {
+                        if let Err(err) = spawner.spawn_local(job(service.clone())) {
+                            error!("Can't spawn wallet operation: {:?}", err);
+                        }
+                    }
+                });
+
+                // operations started before the last handle was dropped are completed
+                pool.run();
+            })
+            .expect("Can't start wallet thread");
+
+        WalletServiceHandle { jobs }
+    }
+
+    pub async fn create_wallet(&self, config: &Config, credentials: &Credentials) -> IndyResult<()> {
+        let (config, credentials) = (config.clone(), credentials.clone());
+
+        self._execute(move |service| async move {
+            service.create_wallet_async(&config, &credentials).await
+        }).await
+    }
+
+    pub async fn open_wallet(&self, config: &Config, credentials: &Credentials) -> IndyResult<WalletHandle> {
+        let (config, credentials) = (config.clone(), credentials.clone());
+
+        self._execute(move |service| async move {
+            service.open_wallet_async(&config, &credentials).await
+        }).await
+    }
+
+    pub async fn close_wallet(&self, wallet_handle: WalletHandle) -> IndyResult<()> {
+        self._execute(move |service| async move {
+            service.close_wallet(wallet_handle)
+        }).await
+    }
+
+    pub async fn delete_wallet(&self, config: &Config, credentials: &Credentials) -> IndyResult<()> {
+        let (config, credentials) = (config.clone(), credentials.clone());
+
+        self._execute(move |service| async move {
+            service.delete_wallet_async(&config, &credentials).await
+        }).await
+    }
+
+    pub async fn list_profiles(&self, config: &Config, credentials: &Credentials) -> IndyResult<Vec<String>> {
+        let (config, credentials) = (config.clone(), credentials.clone());
+
+        self._execute(move |service| async move {
+            service.list_profiles_async(&config, &credentials).await
+        }).await
+    }
+
+    pub async fn rotate_item_keys(&self, wallet_handle: WalletHandle, credentials: &Credentials) -> IndyResult<()> {
+        let credentials = credentials.clone();
+
+        self._execute(move |service| async move {
+            service.rotate_item_keys_async(wallet_handle, &credentials).await
+        }).await
+    }
+
+    pub async fn export_wallet(&self, wallet_handle: WalletHandle, export_config: &ExportConfig) -> IndyResult<()> {
+        let export_config = export_config.clone();
+
+        self._execute(move |service| async move {
+            service.export_wallet_async(wallet_handle, &export_config).await
+        }).await
+    }
+
+    pub async fn import_wallet(&self, config: &Config, credentials: &Credentials, export_config: &ExportConfig) -> IndyResult<ImportReport> {
+        let (config, credentials, export_config) = (config.clone(), credentials.clone(), export_config.clone());
+
+        self._execute(move |service| async move {
+            service.import_wallet_async(&config, &credentials, &export_config).await
+        }).await
+    }
+
+    pub async fn import_wallet_delta(&self, wallet_handle: WalletHandle, export_config: &ExportConfig) -> IndyResult<()> {
+        let export_config = export_config.clone();
+
+        self._execute(move |service| async move {
+            service.import_wallet_delta_async(wallet_handle, &export_config).await
+        }).await
+    }
+
+    pub async fn migrate_wallet(&self,
+                                src_config: &Config,
+                                src_credentials: &Credentials,
+                                dst_config: &Config,
+                                dst_credentials: &Credentials) -> IndyResult<usize> {
+        let (src_config, src_credentials) = (src_config.clone(), src_credentials.clone());
+        let (dst_config, dst_credentials) = (dst_config.clone(), dst_credentials.clone());
+
+        self._execute(move |service| async move {
+            service.migrate_wallet_async(&src_config, &src_credentials, &dst_config, &dst_credentials).await
+        }).await
+    }
+
+    pub async fn get_revision(&self, wallet_handle: WalletHandle) -> IndyResult<u64> {
+        self._execute(move |service| async move {
+            service.get_revision(wallet_handle)
+        }).await
+    }
+
+    pub async fn add_record(&self, wallet_handle: WalletHandle, type_: &str, name: &str, value: &str, tags: &Tags, expires_at: Option<u64>) -> IndyResult<()> {
+        let (type_, name, value, tags) = (type_.to_string(), name.to_string(), value.to_string(), tags.clone());
+
+        self._execute(move |service| async move {
+            service.add_record(wallet_handle, &type_, &name, &value, &tags, expires_at)
+        }).await
+    }
+
+    pub async fn update_record_value(&self, wallet_handle: WalletHandle, type_: &str, name: &str, value: &str) -> IndyResult<()> {
+        let (type_, name, value) = (type_.to_string(), name.to_string(), value.to_string());
+
+        self._execute(move |service| async move {
+            service.update_record_value(wallet_handle, &type_, &name, &value)
+        }).await
+    }
+
+    pub async fn add_record_tags(&self, wallet_handle: WalletHandle, type_: &str, name: &str, tags: &Tags) -> IndyResult<()> {
+        let (type_, name, tags) = (type_.to_string(), name.to_string(), tags.clone());
+
+        self._execute(move |service| async move {
+            service.add_record_tags(wallet_handle, &type_, &name, &tags)
+        }).await
+    }
+
+    pub async fn update_record_tags(&self, wallet_handle: WalletHandle, type_: &str, name: &str, tags: &Tags) -> IndyResult<()> {
+        let (type_, name, tags) = (type_.to_string(), name.to_string(), tags.clone());
+
+        self._execute(move |service| async move {
+            service.update_record_tags(wallet_handle, &type_, &name, &tags)
+        }).await
+    }
+
+    pub async fn delete_record_tags(&self, wallet_handle: WalletHandle, type_: &str, name: &str, tag_names: &[&str]) -> IndyResult<()> {
+        let (type_, name) = (type_.to_string(), name.to_string());
+        let tag_names: Vec<String> = tag_names.iter().map(|tag_name| tag_name.to_string()).collect();
+
+        self._execute(move |service| async move {
+            let tag_names: Vec<&str> = tag_names.iter().map(String::as_str).collect();
+            service.delete_record_tags(wallet_handle, &type_, &name, &tag_names)
+        }).await
+    }
+
+    pub async fn delete_record(&self, wallet_handle: WalletHandle, type_: &str, name: &str) -> IndyResult<()> {
+        let (type_, name) = (type_.to_string(), name.to_string());
+
+        self._execute(move |service| async move {
+            service.delete_record(wallet_handle, &type_, &name)
+        }).await
+    }
+
+    pub async fn get_record(&self, wallet_handle: WalletHandle, type_: &str, name: &str, options_json: &str) -> IndyResult<WalletRecord> {
+        let (type_, name, options_json) = (type_.to_string(), name.to_string(), options_json.to_string());
+
+        self._execute(move |service| async move {
+            service.get_record(wallet_handle, &type_, &name, &options_json)
+        }).await
+    }
+
+    ///
+    /// Returns total count of found records (if it's requested by options) and the records.
+    /// All found records are fetched, so "offset" and "limit" of options should be used
+    /// to page through big results.
+    ///
+    pub async fn search_records(&self, wallet_handle: WalletHandle, type_: &str, query_json: &str, options_json: &str) -> IndyResult<(Option<usize>, Vec<WalletRecord>)> {
+        let (type_, query_json, options_json) = (type_.to_string(), query_json.to_string(), options_json.to_string());
+
+        self._execute(move |service| async move {
+            let mut search = service.search_records(wallet_handle, &type_, &query_json, &options_json)?;
+            let total_count = search.get_total_count()?;
+
+            let mut records = Vec::new();
+
+            while let Some(record) = search.fetch_next_record()? {
+                records.push(record);
+            }
+
+            Ok((total_count, records))
+        }).await
+    }
+
+    // Runs the operation on the wallet thread and returns its result
+    async fn _execute<T, F, R>(&self, operation: F) -> IndyResult<T>
+        where T: Send + 'static, F: FnOnce(Rc<WalletService>) -> R + Send + 'static, R: Future<Output=IndyResult<T>> + 'static {
+        let (sender, receiver) = oneshot::channel();
+
+        let job: Job = Box::new(move |service| Box::pin(async move {
+            // receiver is dropped if the future of operation is canceled
+            let _ = sender.send(operation(service).await);
+        }));
+
+        self.jobs.unbounded_send(job)
+            .map_err(|_| err_msg(IndyErrorKind::InvalidState, "Wallet thread is stopped"))?;
+
+        receiver.await
+            .map_err(|_| err_msg(IndyErrorKind::InvalidState, "Wallet operation was canceled"))?
+    }
+}
@@ -27,20 +27,26 @@ pub enum KeyDerivationData {
     Raw(String),
     Argon2iMod(String, pwhash_argon2i13::Salt),
     Argon2iInt(String, pwhash_argon2i13::Salt),
+    // key wrap provider name, wrapped master key and unwrapped master key
+    Wrapped(String, Vec<u8>, chacha20poly1305_ietf::Key),
 }
 
 impl KeyDerivationData {
-    pub fn from_passphrase_with_new_salt(passphrase: &str, derivation_method: &KeyDerivationMethod) -> Self {
+    pub fn from_passphrase_with_new_salt(passphrase: &str, derivation_method: &KeyDerivationMethod) -> IndyResult<Self> {
         let salt = pwhash_argon2i13::gen_salt();
         let passphrase = passphrase.to_owned();
-        match *derivation_method {
+        let data = match *derivation_method {
             KeyDerivationMethod::ARGON2I_INT =>
                 KeyDerivationData::Argon2iInt(passphrase, salt),
             KeyDerivationMethod::ARGON2I_MOD =>
                 KeyDerivationData::Argon2iMod(passphrase, salt),
             KeyDerivationMethod::RAW =>
-                KeyDerivationData::Raw(passphrase)
-        }
+                KeyDerivationData::Raw(passphrase),
+            KeyDerivationMethod::WRAPPED =>
+                return Err(err_msg(IndyErrorKind::InvalidStructure, "WRAPPED key derivation method requires key wrap provider"))
+        };
+
+        Ok(data)
     }
 
     pub(super) fn from_passphrase_and_metadata(passphrase: &str, metadata: &Metadata, derivation_method: &KeyDerivationMethod) -> IndyResult<Self> {
@@ -69,6 +75,7 @@ impl KeyDerivationData {
             KeyDerivationData::Raw(passphrase) => _raw_master_key(passphrase),
             KeyDerivationData::Argon2iInt(passphrase, salt) => _derive_master_key(passphrase, &salt, &KeyDerivationMethod::ARGON2I_INT),
             KeyDerivationData::Argon2iMod(passphrase, salt) => _derive_master_key(passphrase, &salt, &KeyDerivationMethod::ARGON2I_MOD),
+            KeyDerivationData::Wrapped(_, _, key) => Ok(key.clone()),
         }
     }
 }
@@ -121,6 +128,39 @@ pub(super) fn encrypt_tags(tags: &HashMap<String, String>,
 }
 
 
+// Names of blind prefix index tags start with this char, so they can't clash
+// with tags set by user and are hidden on decryption of record tags
+const PREFIX_INDEX_MARKER: char = '\0';
+
+pub(super) fn prefix_index_tag_name(tag_name: &str, len: usize) -> String {
+    format!("{}prefix{}:{}", PREFIX_INDEX_MARKER, len, tag_name)
+}
+
+// Builds blind prefix index tags for indexed tags: a tag per every prefix length up to configured one.
+// Index values are stored as encrypted tags, so exact match on the index works as prefix match on the tag.
+// Values shorter than the prefix length are indexed whole, so updated tags always overwrite stale indexes.
+pub(super) fn prefix_index_tags(tags: &HashMap<String, String>, prefix_indexes: &HashMap<String, usize>) -> HashMap<String, String> {
+    let mut index_tags = HashMap::new();
+
+    for (tag_name, max_len) in prefix_indexes {
+        if let Some(tag_value) = tags.get(tag_name) {
+            for len in 1..=*max_len {
+                index_tags.insert(prefix_index_tag_name(tag_name, len), tag_value.chars().take(len).collect());
+            }
+        }
+    }
+
+    index_tags
+}
+
+pub(super) fn prefix_index_tag_names(tag_names: &[&str], prefix_indexes: &HashMap<String, usize>) -> Vec<String> {
+    tag_names
+        .iter()
+        .filter_map(|tag_name| prefix_indexes.get(*tag_name).map(|max_len| (tag_name, max_len)))
+        .flat_map(|(tag_name, max_len)| (1..=*max_len).map(move |len| prefix_index_tag_name(tag_name, len)))
+        .collect()
+}
+
 pub(super) fn encrypt_as_searchable(data: &[u8], key: &chacha20poly1305_ietf::Key, hmac_key: &hmacsha256::Key) -> Vec<u8> {
     let tag = hmacsha256::authenticate(data, hmac_key);
     let nonce = chacha20poly1305_ietf::Nonce::from_slice(&tag[..chacha20poly1305_ietf::NONCEBYTES]).unwrap(); // We can safely unwrap here
@@ -170,6 +210,9 @@ pub(super) fn decrypt_tags(etags: &Option<Vec<Tag>>, tag_name_key: &chacha20poly
                     }
                     Tag::Encrypted(ref ename, ref evalue) => {
                         let name = String::from_utf8(decrypt_merged(&ename, tag_name_key)?).to_indy(IndyErrorKind::WalletEncryptionError, "Tag name is invalid utf8")?;
+                        if name.starts_with(PREFIX_INDEX_MARKER) {
+                            continue;
+                        }
                         let value = String::from_utf8(decrypt_merged(&evalue, tag_value_key)?).to_indy(IndyErrorKind::WalletEncryptionError, "Tag value is invalid utf8")?;
                         (name, value)
                     }
@@ -203,7 +246,7 @@ pub(super) fn decrypt_storage_record(record: &StorageRecord, keys: &Keys) -> Ind
     };
 
     let decrypted_tags = decrypt_tags(&record.tags, &keys.tag_name_key, &keys.tag_value_key)?;
-    Ok(WalletRecord::new(decrypted_name, decrypted_type, decrypted_value, decrypted_tags))
+    Ok(WalletRecord::new(decrypted_name, decrypted_type, decrypted_value, decrypted_tags).with_expiry(record.expires_at))
 }
 
 
@@ -365,6 +408,41 @@ mod tests {
         assert_eq!(tags, u);
     }
 
+    #[test]
+    fn test_prefix_index_tags_works() {
+        let tags = serde_json::from_str(r#"{"tag1":"value1", "tag2":"v2", "~tag3":"value3"}"#).unwrap();
+        let prefix_indexes = serde_json::from_str(r#"{"tag1":2, "tag2":3, "tag4":1}"#).unwrap();
+
+        let index_tags = prefix_index_tags(&tags, &prefix_indexes);
+
+        assert_eq!(5, index_tags.len());
+        assert_eq!("v", index_tags[&prefix_index_tag_name("tag1", 1)]);
+        assert_eq!("va", index_tags[&prefix_index_tag_name("tag1", 2)]);
+        assert_eq!("v2", index_tags[&prefix_index_tag_name("tag2", 2)]);
+        assert_eq!("v2", index_tags[&prefix_index_tag_name("tag2", 3)]);
+
+        let mut index_tag_names = prefix_index_tag_names(&["tag1", "~tag3"], &prefix_indexes);
+        index_tag_names.sort();
+        assert_eq!(vec![prefix_index_tag_name("tag1", 1), prefix_index_tag_name("tag1", 2)], index_tag_names);
+    }
+
+    #[test]
+    fn test_decrypt_tags_skips_prefix_indexes() {
+        let tags: HashMap<String, String> = serde_json::from_str(r#"{"tag1":"value1", "~tag2":"value2"}"#).unwrap();
+        let prefix_indexes = serde_json::from_str(r#"{"tag1":3}"#).unwrap();
+
+        let tag_name_key = chacha20poly1305_ietf::gen_key();
+        let tag_value_key = chacha20poly1305_ietf::gen_key();
+        let hmac_key = hmacsha256::gen_key();
+
+        let mut c = encrypt_tags(&tags, &tag_name_key, &tag_value_key, &hmac_key);
+        c.extend(encrypt_tags(&prefix_index_tags(&tags, &prefix_indexes), &tag_name_key, &tag_value_key, &hmac_key));
+        assert_eq!(5, c.len());
+
+        let u = decrypt_tags(&Some(c), &tag_name_key, &tag_value_key).unwrap().unwrap();
+        assert_eq!(tags, u);
+    }
+
     #[test]
     fn test_decrypt_tags_works_for_none() {
         let tag_name_key = chacha20poly1305_ietf::gen_key();
@@ -393,6 +471,7 @@ mod tests {
             value: Some(encrypted_value),
             type_: Some(encrypted_type),
             tags: Some(encrypted_tags),
+            expires_at: None,
         };
         let decrypted_wallet_record = decrypt_storage_record(&storage_record, &keys).unwrap();
 
@@ -422,6 +501,7 @@ mod tests {
             value: Some(encrypted_value),
             type_: Some(encrypted_type),
             tags: Some(encrypted_tags),
+            expires_at: None,
         };
         let res = decrypt_storage_record(&storage_record, &keys2);
 
@@ -1,21 +1,54 @@
 use std::io;
-use std::io::{BufReader, BufWriter, Read, Write};
+use std::io::{BufReader, BufWriter, Cursor, Read, Write};
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use byteorder::{ByteOrder, LittleEndian, ReadBytesExt, WriteBytesExt};
 use rmp_serde;
+use serde::de::DeserializeOwned;
+use serde_json;
 
+use indy_api_types::{ErrorCode, IndyHandle};
 use indy_api_types::domain::wallet::Record;
 use indy_api_types::domain::wallet::KeyDerivationMethod;
 use indy_api_types::errors::prelude::*;
+use indy_api_types::wallet::{WalletExportSinkWrite, WalletImportSourceRead};
 use crate::encryption::KeyDerivationData;
 use indy_utils::crypto::{chacha20poly1305_ietf, pwhash_argon2i13};
 use indy_utils::crypto::hash::{hash, HASHBYTES};
 
-use super::{Wallet, WalletRecord};
+use super::{RecordOptions, SearchOptions, Wallet, WalletOperation, WalletRecord};
+use super::iterator::WalletIterator;
 
 const CHUNK_SIZE: usize = 1024;
 
+// Versions of export format, note that header version numbering starts from 0
+pub const EXPORT_VERSION_1: u32 = 0;
+pub const EXPORT_VERSION_2: u32 = 1;
+
+// Returns version of export format for version of export config, v1 is used by default
+pub fn export_version(version: Option<u32>) -> IndyResult<u32> {
+    match version {
+        None | Some(1) => Ok(EXPORT_VERSION_1),
+        Some(2) => Ok(EXPORT_VERSION_2),
+        Some(version) => Err(err_msg(IndyErrorKind::InvalidStructure, format!("Unsupported export version: {}", version))),
+    }
+}
+
+// Kinds of v2 chunks, kind is the first byte of encrypted chunk data
+const CHUNK_HEADER_HASH: u8 = 0;
+const CHUNK_RECORD: u8 = 1;
+const CHUNK_END: u8 = 2;
+const CHUNK_TRAILER: u8 = 3;
+
+// Max number of adjacent damaged chunks skipped by tolerant import
+const MAX_SKIPPED_CHUNKS: u64 = 16;
+
+// Max length of chunk read by tolerant import, longer chunks are treated as damaged
+const MAX_TOLERANT_CHUNK_LEN: usize = 1024 * 1024;
+
+// Length of the window after a damaged chunk searched for the next one by tolerant import
+const SKIP_WINDOW_LEN: usize = MAX_SKIPPED_CHUNKS as usize * MAX_TOLERANT_CHUNK_LEN;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub enum EncryptionMethod {
     // **ChaCha20-Poly1305-IETF** cypher in blocks per chunk_size bytes
@@ -51,8 +84,59 @@ pub struct Header {
     pub encryption_method: EncryptionMethod,
     // Export time in seconds from UNIX Epoch
     pub time: u64,
-    // Version of header
-    pub version: u32
+    // Version of export format (See EXPORT_VERSION_1 and EXPORT_VERSION_2)
+    pub version: u32,
+    // Revisions range of delta export. Absent for full export
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delta: Option<Delta>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Delta {
+    // Records changed after this revision are exported
+    pub since_revision: u64,
+    // Revision of the wallet at the moment of export
+    pub revision: u64,
+}
+
+// Last chunk of v2 export, allows to detect lost records
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Trailer {
+    // Number of exported records
+    pub records: u64,
+    // Number of exported deleted records. Absent for full export
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_records: Option<u64>,
+}
+
+///
+/// Result of wallet import.
+/// Tolerant import of v2 export skips damaged records and reports them here.
+///
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct ImportReport {
+    // Number of imported records
+    pub imported: u64,
+    // Number of damaged records skipped
+    pub skipped: u64,
+    // Number of records in export according to trailer. Absent for v1 export and damaged trailer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected: Option<u64>,
+}
+
+// Record deleted after delta base revision
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeletedRecord {
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub id: String,
+}
+
+#[derive(Debug, Default)]
+pub(super) struct ExportFilter<'a> {
+    pub types: Option<&'a [String]>,
+    pub query: Option<&'a str>,
+    pub since_revision: Option<u64>,
 }
 
 // Note that we use externally tagged enum serialization and header will be represented as:
@@ -67,9 +151,98 @@ pub struct Header {
 //   },
 //   "time": ..,
 //   "version": ..,
+//   "delta": { // only for delta export
+//     "since_revision": ..,
+//     "revision": ..,
+//   },
 // }
+//
+// Delta export contains records changed after since_revision followed by END message
+// and then records deleted after since_revision followed by END message.
+//
+// v1 export after plain header is a stream encrypted in chunks of chunk_size bytes that contains
+// hash of header and then records as u32 length + msgpack pairs, END message is zero length.
+// Damage of any byte breaks the rest of the stream.
+//
+// v2 export after plain header is a sequence of u32 length + chunk pairs, each chunk is encrypted separately
+// with its own MAC and nonce incremented for each chunk starting from the header one.
+// Chunk data starts with kind of the chunk and length is bound to MAC as additional data:
+//
+// chunk(hash of header) | chunk(record) .. | chunk(END) | [chunk(deleted record) .. | chunk(END)] | chunk(trailer)
+//
+// Damaged chunk or its length fails MAC check. Tolerant import skips it and continues from the next chunk
+// that passes MAC check with one of the next nonces. Trailer contains number of exported records,
+// so lost records are detected too.
+
+// Export sink that passes exported data to the application provided write callback
+#[derive(Clone, Copy)]
+pub struct ExportSink {
+    handle: IndyHandle,
+    write_cb: WalletExportSinkWrite,
+}
+
+impl ExportSink {
+    pub fn new(handle: IndyHandle, write_cb: WalletExportSinkWrite) -> ExportSink {
+        ExportSink { handle, write_cb }
+    }
+}
+
+impl Write for ExportSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match (self.write_cb)(self.handle, buf.as_ptr(), buf.len()) {
+            ErrorCode::Success => Ok(buf.len()),
+            err => Err(io::Error::new(io::ErrorKind::Other, format!("Export sink write failed: {:?}", err)))
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Import source that takes exported data from the application provided read callback
+#[derive(Clone, Copy)]
+pub struct ImportSource {
+    handle: IndyHandle,
+    read_cb: WalletImportSourceRead,
+}
+
+impl ImportSource {
+    pub fn new(handle: IndyHandle, read_cb: WalletImportSourceRead) -> ImportSource {
+        ImportSource { handle, read_cb }
+    }
+}
+
+impl Read for ImportSource {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut read_len: usize = 0;
+
+        match (self.read_cb)(self.handle, buf.as_mut_ptr(), buf.len(), &mut read_len) {
+            ErrorCode::Success if read_len <= buf.len() => Ok(read_len),
+            ErrorCode::Success => Err(io::Error::new(io::ErrorKind::InvalidData, "Import source returned more data than requested")),
+            err => Err(io::Error::new(io::ErrorKind::Other, format!("Import source read failed: {:?}", err)))
+        }
+    }
+}
+
+pub(super) fn export_continue(wallet: &Wallet, writer: &mut dyn Write, version: u32, key: chacha20poly1305_ietf::Key, key_data: &KeyDerivationData, filter: &ExportFilter) -> IndyResult<()> {
+    if filter.types.is_none() && filter.query.is_some() {
+        return Err(err_msg(IndyErrorKind::InvalidStructure, "Export query can be used only together with types"));
+    }
+
+    if filter.since_revision.is_some() && filter.query.is_some() {
+        return Err(err_msg(IndyErrorKind::InvalidStructure, "Export query can't be used for delta export"));
+    }
+
+    if version != EXPORT_VERSION_1 && version != EXPORT_VERSION_2 {
+        return Err(err_msg(IndyErrorKind::InvalidState, "Unsupported version"));
+    }
+
+    let delta = match filter.since_revision {
+        Some(since_revision) => Some(Delta { since_revision, revision: wallet.get_revision()? }),
+        None => None
+    };
 
-pub(super) fn export_continue(wallet: &Wallet, writer: &mut dyn Write, version: u32, key: chacha20poly1305_ietf::Key, key_data: &KeyDerivationData) -> IndyResult<()> {
     let nonce = chacha20poly1305_ietf::gen_nonce();
     let chunk_size = CHUNK_SIZE;
 
@@ -87,13 +260,16 @@ pub(super) fn export_continue(wallet: &Wallet, writer: &mut dyn Write, version:
         KeyDerivationData::Raw(_) => EncryptionMethod::ChaCha20Poly1305IETFRaw {
             nonce: nonce[..].to_vec(),
             chunk_size,
-        }
+        },
+        KeyDerivationData::Wrapped(..) =>
+            return Err(err_msg(IndyErrorKind::InvalidStructure, "WRAPPED key derivation method can't be used for export"))
     };
 
     let header = Header {
         encryption_method,
         time: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
-        version
+        version,
+        delta,
     };
 
     let header = rmp_serde::to_vec(&header)
@@ -104,41 +280,382 @@ pub(super) fn export_continue(wallet: &Wallet, writer: &mut dyn Write, version:
     writer.write_u32::<LittleEndian>(header.len() as u32)?;
     writer.write_all(&header)?;
 
-    // Write ecnrypted
-    let mut writer = chacha20poly1305_ietf::Writer::new(writer,
-                                                        key,
-                                                        nonce,
-                                                        chunk_size);
+    if version == EXPORT_VERSION_1 {
+        // Write ecnrypted
+        let mut writer = chacha20poly1305_ietf::Writer::new(writer,
+                                                            key,
+                                                            nonce,
+                                                            chunk_size);
+
+        writer.write_all(&hash(&header)?)?;
+        _write_content(wallet, &mut writer, filter)?;
+        writer.flush()?;
+    } else {
+        let mut writer = ChunkWriter::new(writer, key, nonce);
+
+        writer.write_chunk(CHUNK_HEADER_HASH, &hash(&header)?)?;
+        let (records, deleted_records) = _write_content(wallet, &mut writer, filter)?;
+
+        let trailer = rmp_serde::to_vec(&Trailer { records, deleted_records })
+            .to_indy(IndyErrorKind::InvalidState, "Can't serialize wallet export file trailer")?;
+
+        writer.write_chunk(CHUNK_TRAILER, &trailer)?;
+        writer.flush()?;
+    }
+
+    Ok(())
+}
+
+// Writes exported records followed by END message. Returns number of records and deleted records of delta export
+fn _write_content(wallet: &Wallet, writer: &mut dyn EntryWriter, filter: &ExportFilter) -> IndyResult<(u64, Option<u64>)> {
+    if let Some(since_revision) = filter.since_revision {
+        let is_exported = |type_: &str| filter.types.map(|types| types.iter().any(|t| t == type_)).unwrap_or(true);
+
+        let mut records = wallet.get_changed(since_revision)?;
+        let mut count = 0;
+
+        while let Some(record) = records.next()? {
+            if record.type_.as_ref().map(|type_| is_exported(type_)).unwrap_or(false) {
+                _write_record(writer, record)?;
+                count += 1;
+            }
+        }
+
+        writer.write_end()?;
+
+        let mut deleted_count = 0;
+
+        for (type_, id) in wallet.get_deleted(since_revision)? {
+            if !is_exported(&type_) {
+                continue;
+            }
+
+            let record = rmp_serde::to_vec(&DeletedRecord { type_, id })
+                .to_indy(IndyErrorKind::InvalidState, "Can't serialize deleted record")?;
+
+            writer.write_entry(&record)?;
+            deleted_count += 1;
+        }
+
+        writer.write_end()?;
+        return Ok((count, Some(deleted_count)));
+    }
+
+    let mut count = 0;
+
+    match filter.types {
+        Some(types) => {
+            let options = SearchOptions {
+                retrieve_records: true,
+                retrieve_total_count: false,
+                retrieve_type: true,
+                retrieve_value: true,
+                retrieve_tags: true,
+                sort_by: None,
+                offset: None,
+                limit: None,
+            };
+
+            let options = serde_json::to_string(&options)
+                .to_indy(IndyErrorKind::InvalidState, "Can't serialize search options")?;
+
+            for type_ in types {
+                let mut records = wallet.search(type_, filter.query.unwrap_or("{}"), Some(&options))?;
+                count += _write_records(writer, &mut records)?;
+            }
+        }
+        None => {
+            let mut records = wallet.get_all()?;
+            count += _write_records(writer, &mut records)?;
+        }
+    }
+
+    writer.write_end()?;
+    Ok((count, None))
+}
+
+fn _write_records(writer: &mut dyn EntryWriter, records: &mut WalletIterator) -> IndyResult<u64> {
+    let mut count = 0;
+
+    while let Some(record) = records.next()? {
+        _write_record(writer, record)?;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+fn _write_record(writer: &mut dyn EntryWriter, record: WalletRecord) -> IndyResult<()> {
+    let WalletRecord { type_, id, value, tags, expires_at } = record;
+
+    let record = Record {
+        type_: type_.ok_or_else(|| err_msg(IndyErrorKind::InvalidState, "No type fetched for exported record"))?,
+        id,
+        value: value.ok_or_else(|| err_msg(IndyErrorKind::InvalidState, "No value fetched for exported record"))?,
+        tags: tags.ok_or_else(|| err_msg(IndyErrorKind::InvalidState, "No tags fetched for exported record"))?,
+        expires_at,
+    };
+
+    let record = rmp_serde::to_vec(&record)
+        .to_indy(IndyErrorKind::InvalidState, "Can't serialize record")?;
+
+    writer.write_entry(&record)
+}
+
+// Writes length prefixed entries and END messages in format of export version
+trait EntryWriter {
+    fn write_entry(&mut self, entry: &[u8]) -> IndyResult<()>;
+    fn write_end(&mut self) -> IndyResult<()>;
+}
+
+impl<W: Write> EntryWriter for chacha20poly1305_ietf::Writer<W> {
+    fn write_entry(&mut self, entry: &[u8]) -> IndyResult<()> {
+        self.write_u32::<LittleEndian>(entry.len() as u32)?;
+        self.write_all(entry)?;
+        Ok(())
+    }
+
+    fn write_end(&mut self) -> IndyResult<()> {
+        self.write_u32::<LittleEndian>(0)?;
+        Ok(())
+    }
+}
+
+// Writer of v2 export, each chunk is encrypted separately with the next nonce
+struct ChunkWriter<W: Write> {
+    inner: W,
+    key: chacha20poly1305_ietf::Key,
+    nonce: chacha20poly1305_ietf::Nonce,
+}
+
+impl<W: Write> ChunkWriter<W> {
+    fn new(inner: W, key: chacha20poly1305_ietf::Key, nonce: chacha20poly1305_ietf::Nonce) -> ChunkWriter<W> {
+        ChunkWriter { inner, key, nonce }
+    }
+
+    fn write_chunk(&mut self, kind: u8, data: &[u8]) -> IndyResult<()> {
+        let mut plain = Vec::with_capacity(1 + data.len());
+        plain.push(kind);
+        plain.extend_from_slice(data);
+
+        let mut len = [0u8; 4];
+        LittleEndian::write_u32(&mut len, (plain.len() + chacha20poly1305_ietf::TAGBYTES) as u32);
+
+        let chunk = chacha20poly1305_ietf::encrypt_with_ad(&plain, &len, &self.key, &self.nonce);
+        self.nonce.increment();
+
+        self.inner.write_all(&len)?;
+        self.inner.write_all(&chunk)?;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> IndyResult<()> {
+        self.inner.flush()?;
+        Ok(())
+    }
+}
+
+impl<W: Write> EntryWriter for ChunkWriter<W> {
+    fn write_entry(&mut self, entry: &[u8]) -> IndyResult<()> {
+        self.write_chunk(CHUNK_RECORD, entry)
+    }
 
-    writer.write_all(&hash(&header)?)?;
+    fn write_end(&mut self) -> IndyResult<()> {
+        self.write_chunk(CHUNK_END, &[])
+    }
+}
 
-    let mut records = wallet.get_all()?;
+// Reader of v2 export
+struct ChunkReader<R: Read> {
+    inner: R,
+    key: chacha20poly1305_ietf::Key,
+    nonce: chacha20poly1305_ietf::Nonce,
+    // Data read ahead to find the chunk following a damaged one
+    rest: Option<Cursor<Vec<u8>>>,
+    // Trailer met instead of damaged END message
+    trailer: Option<Vec<u8>>,
+    // Number of damaged chunks skipped by tolerant reading
+    skipped: u64,
+}
 
-    while let Some(WalletRecord { type_, id, value, tags }) = records.next()? {
-        let record = Record {
-            type_: type_.ok_or_else(|| err_msg(IndyErrorKind::InvalidState, "No type fetched for exported record"))?,
-            id,
-            value: value.ok_or_else(|| err_msg(IndyErrorKind::InvalidState, "No value fetched for exported record"))?,
-            tags: tags.ok_or_else(|| err_msg(IndyErrorKind::InvalidState, "No tags fetched for exported record"))?,
+impl<R: Read> ChunkReader<R> {
+    fn new(inner: R, key: chacha20poly1305_ietf::Key, nonce: chacha20poly1305_ietf::Nonce) -> ChunkReader<R> {
+        ChunkReader { inner, key, nonce, rest: None, trailer: None, skipped: 0 }
+    }
+
+    // Returns kind and data of the next chunk. Damaged chunk fails reading unless tolerant,
+    // tolerant reading skips it together with the next damaged ones.
+    fn read_chunk(&mut self, tolerant: bool) -> IndyResult<(u8, Vec<u8>)> {
+        match self._read_frame(tolerant)? {
+            Ok(chunk) => Ok(chunk),
+            Err(_) if !tolerant => Err(err_msg(IndyErrorKind::InvalidStructure, "Damaged chunk of export file")),
+            Err(frame) => self._skip_damaged(frame),
+        }
+    }
+
+    fn check_header_hash(&mut self, header_bytes: &[u8]) -> IndyResult<()> {
+        let header_hash = match self._read_frame(false)? {
+            Ok((CHUNK_HEADER_HASH, header_hash)) => header_hash,
+            _ => return Err(err_msg(IndyErrorKind::InvalidStructure, "Invalid header hash"))
         };
 
-        let record = rmp_serde::to_vec(&record)
-            .to_indy(IndyErrorKind::InvalidState, "Can't serialize record")?;
+        if hash(header_bytes)? != header_hash {
+            return Err(err_msg(IndyErrorKind::InvalidStructure, "Invalid header hash"));
+        }
 
-        writer.write_u32::<LittleEndian>(record.len() as u32)?;
-        writer.write_all(&record)?;
+        Ok(())
     }
 
-    writer.write_u32::<LittleEndian>(0)?; // END message
-    writer.flush()?;
-    Ok(())
+    // Damaged trailer is returned as inner error, so tolerant import can ignore it.
+    // Trailer is the last chunk, so cut or extended file is returned as outer error.
+    fn read_trailer(&mut self) -> IndyResult<IndyResult<Trailer>> {
+        let trailer = match self.trailer.take() {
+            Some(trailer) => Ok(trailer),
+            None => match self._read_frame(false)? {
+                Ok((CHUNK_TRAILER, trailer)) => Ok(trailer),
+                Ok(_) => return Err(err_msg(IndyErrorKind::InvalidStructure, "Trailer of export file is absent")),
+                Err(_) => Err(err_msg(IndyErrorKind::InvalidStructure, "Damaged trailer of export file")),
+            }
+        };
+
+        if !self._read_bytes(1)?.is_empty() {
+            return Err(err_msg(IndyErrorKind::InvalidStructure, "Unexpected data after trailer of export file"));
+        }
+
+        Ok(trailer.and_then(|trailer| rmp_serde::from_slice(&trailer)
+            .to_indy(IndyErrorKind::InvalidStructure, "Trailer is malformed msgpack")))
+    }
+
+    // Returns opened chunk or bytes of the frame that failed MAC check.
+    // Frame cut by the end of file is damaged one only for tolerant reading, as length may be damaged.
+    fn _read_frame(&mut self, tolerant: bool) -> IndyResult<Result<(u8, Vec<u8>), Vec<u8>>> {
+        let mut frame = self._read_bytes(4)?;
+
+        if frame.len() != 4 {
+            return Err(err_msg(IndyErrorKind::InvalidStructure, "Invalid export file format"));
+        }
+
+        let chunk_len = LittleEndian::read_u32(&frame) as usize;
+        let chunk = self._read_bytes(if tolerant { chunk_len.min(MAX_TOLERANT_CHUNK_LEN) } else { chunk_len })?;
+
+        if chunk.len() != chunk_len && !tolerant {
+            return Err(err_msg(IndyErrorKind::InvalidStructure, "Invalid export file format"));
+        }
+
+        if chunk.len() == chunk_len {
+            if let Some(opened) = self._open(&frame, &chunk, &self.nonce) {
+                self.nonce.increment();
+                return Ok(Ok(opened));
+            }
+        }
+
+        frame.extend_from_slice(&chunk);
+        Ok(Err(frame))
+    }
+
+    // Damaged length may point anywhere, so the next chunk is searched at each position of a bounded
+    // window that starts with the damaged frame, as the frame itself may follow dropped chunks.
+    // The window is read incrementally, only as far as checked chunks need.
+    fn _skip_damaged(&mut self, frame: Vec<u8>) -> IndyResult<(u8, Vec<u8>)> {
+        let mut data = frame;
+
+        for pos in 0..SKIP_WINDOW_LEN {
+            if !self._fill(&mut data, pos + 4)? {
+                break;
+            }
+
+            let chunk_len = LittleEndian::read_u32(&data[pos..pos + 4]) as usize;
+
+            if chunk_len <= chacha20poly1305_ietf::TAGBYTES || chunk_len > MAX_TOLERANT_CHUNK_LEN {
+                continue;
+            }
+
+            let end = pos + 4 + chunk_len;
+
+            if !self._fill(&mut data, end)? {
+                continue;
+            }
+
+            let mut nonce = self.nonce.clone();
+
+            for skipped in 1..=MAX_SKIPPED_CHUNKS {
+                nonce.increment();
+
+                if let Some(opened) = self._open(&data[pos..pos + 4], &data[pos + 4..end], &nonce) {
+                    warn!("{} damaged chunks of wallet export are skipped", skipped);
+
+                    nonce.increment();
+                    self.nonce = nonce;
+                    self.skipped += skipped;
+
+                    let rest = data.split_off(end);
+                    self._unread(rest);
+
+                    return Ok(opened);
+                }
+            }
+        }
+
+        Err(err_msg(IndyErrorKind::InvalidStructure, "Damaged chunk of export file"))
+    }
+
+    // Reads more data until it has len bytes, returns false if the end of file is reached before
+    fn _fill(&mut self, data: &mut Vec<u8>, len: usize) -> IndyResult<bool> {
+        if data.len() < len {
+            let more = self._read_bytes(len - data.len())?;
+            data.extend_from_slice(&more);
+        }
+
+        Ok(data.len() >= len)
+    }
+
+    // Returns data read ahead, so it is read again before the rest of the file
+    fn _unread(&mut self, mut data: Vec<u8>) {
+        if let Some(rest) = self.rest.take() {
+            data.extend_from_slice(&rest.get_ref()[rest.position() as usize..]);
+        }
+
+        self.rest = Some(Cursor::new(data));
+    }
+
+    fn _open(&self, len: &[u8], chunk: &[u8], nonce: &chacha20poly1305_ietf::Nonce) -> Option<(u8, Vec<u8>)> {
+        let mut plain = chacha20poly1305_ietf::decrypt_with_ad(chunk, len, &self.key, nonce).ok()?;
+
+        if plain.is_empty() {
+            return None;
+        }
+
+        let kind = plain.remove(0);
+        Some((kind, plain))
+    }
+
+    // Reads up to len bytes, less only at the end of file
+    fn _read_bytes(&mut self, len: usize) -> IndyResult<Vec<u8>> {
+        let mut data = Vec::new();
+
+        if let Some(ref mut rest) = self.rest {
+            rest.by_ref().take(len as u64).read_to_end(&mut data).map_err(_map_io_err)?;
+        }
+
+        if data.len() < len {
+            self.rest = None;
+            self.inner.by_ref().take((len - data.len()) as u64).read_to_end(&mut data).map_err(_map_io_err)?;
+        }
+
+        Ok(data)
+    }
 }
 
 #[cfg(test)]
 fn import<T>(wallet: &Wallet, reader: T, passphrase: &str) -> IndyResult<()> where T: Read {
+    import_tolerant(wallet, reader, passphrase, false).map(|_| ())
+}
+
+#[cfg(test)]
+fn import_tolerant<T>(wallet: &Wallet, reader: T, passphrase: &str, tolerant: bool) -> IndyResult<ImportReport> where T: Read {
     let (reader, import_key_derivation_data, nonce, chunk_size, header_bytes) = preparse_file_to_import(reader, passphrase)?;
     let import_key = import_key_derivation_data.calc_master_key()?;
-    finish_import(wallet, reader, import_key, nonce, chunk_size, header_bytes)
+    finish_import(wallet, reader, import_key, nonce, chunk_size, header_bytes, tolerant)
 }
 
 pub(super) fn preparse_file_to_import<T>(reader: T, passphrase: &str) -> IndyResult<(BufReader<T>, KeyDerivationData, chacha20poly1305_ietf::Nonce, usize, Vec<u8>)> where T: Read {
@@ -154,10 +671,9 @@ pub(super) fn preparse_file_to_import<T>(reader: T, passphrase: &str) -> IndyRes
     let mut header_bytes = vec![0u8; header_len];
     reader.read_exact(&mut header_bytes).map_err(_map_io_err)?;
 
-    let header: Header = rmp_serde::from_slice(&header_bytes)
-        .to_indy(IndyErrorKind::InvalidStructure, "Header is malformed json")?;
+    let header = _parse_header(&header_bytes)?;
 
-    if header.version != 0 {
+    if header.version != EXPORT_VERSION_1 && header.version != EXPORT_VERSION_2 {
         return Err(err_msg(IndyErrorKind::InvalidStructure, "Unsupported version"));
     }
 
@@ -200,17 +716,160 @@ pub(super) fn preparse_file_to_import<T>(reader: T, passphrase: &str) -> IndyRes
     Ok((reader, import_key_derivation_data, nonce, chunk_size, header_bytes))
 }
 
-pub(super) fn finish_import<T>(wallet: &Wallet, reader: BufReader<T>, key: chacha20poly1305_ietf::Key, nonce: chacha20poly1305_ietf::Nonce, chunk_size: usize, header_bytes: Vec<u8>) -> IndyResult<()> where T: Read {
-    // Reads encrypted
-    let mut reader = chacha20poly1305_ietf::Reader::new(reader, key, nonce, chunk_size);
+///
+/// Imports full export into new wallet.
+/// Tolerant import of v2 export skips records which chunks fail MAC check instead of failing.
+///
+pub(super) fn finish_import<T>(wallet: &Wallet, reader: BufReader<T>, key: chacha20poly1305_ietf::Key, nonce: chacha20poly1305_ietf::Nonce, chunk_size: usize, header_bytes: Vec<u8>, tolerant: bool) -> IndyResult<ImportReport> where T: Read {
+    let header = _parse_header(&header_bytes)?;
+
+    if header.delta.is_some() {
+        return Err(err_msg(IndyErrorKind::InvalidStructure, "Delta export can be applied only to existing wallet"));
+    }
+
+    let mut report = ImportReport::default();
+
+    if header.version == EXPORT_VERSION_1 {
+        // Reads encrypted
+        let mut reader = chacha20poly1305_ietf::Reader::new(reader, key, nonce, chunk_size);
+
+        _check_header_hash(&mut reader, &header_bytes)?;
+
+        _read_entries(&mut reader, |record: Record| {
+            wallet.add(&record.type_, &record.id, &record.value, &record.tags, record.expires_at)?;
+            report.imported += 1;
+            Ok(())
+        })?;
+
+        return Ok(report);
+    }
+
+    let mut reader = ChunkReader::new(reader, key, nonce);
+
+    reader.check_header_hash(&header_bytes)?;
+
+    let mut imported = 0;
+
+    report.skipped = _read_chunks(&mut reader, tolerant, |record: Record| {
+        wallet.add(&record.type_, &record.id, &record.value, &record.tags, record.expires_at)?;
+        imported += 1;
+        Ok(())
+    })?;
+
+    report.imported = imported;
+
+    match reader.read_trailer()? {
+        Ok(trailer) => {
+            if !tolerant && trailer.records != report.imported {
+                return Err(err_msg(IndyErrorKind::InvalidStructure, "Number of imported records doesn't match trailer of export file"));
+            }
+
+            report.expected = Some(trailer.records);
+        }
+        Err(err) => {
+            if !tolerant {
+                return Err(err);
+            }
+
+            warn!("Damaged trailer of wallet export is ignored: {}", err);
+        }
+    }
+
+    Ok(report)
+}
+
+///
+/// Applies full or delta export on top of existing wallet content.
+/// Exported records replace existing ones and deleted records of delta export are removed.
+/// Returns operations applied to the wallet.
+///
+pub(super) fn finish_import_delta<T>(wallet: &Wallet, reader: BufReader<T>, key: chacha20poly1305_ietf::Key, nonce: chacha20poly1305_ietf::Nonce, chunk_size: usize, header_bytes: Vec<u8>) -> IndyResult<Vec<WalletOperation>> where T: Read {
+    let header = _parse_header(&header_bytes)?;
+
+    // records are applied in a single batch only after the whole file is read and verified,
+    // so a broken or truncated file doesn't leave the wallet partially updated
+    let mut operations: Vec<WalletOperation> = Vec::new();
+
+    let mut apply_record = |record: Record| -> IndyResult<()> {
+        if _exists(wallet, &record.type_, &record.id)? {
+            operations.push(WalletOperation::Delete { type_: record.type_.clone(), id: record.id.clone() });
+        }
+
+        operations.push(WalletOperation::Add { type_: record.type_, id: record.id, value: record.value, tags: Some(record.tags), expires_at: record.expires_at });
+        Ok(())
+    };
+
+    if header.version == EXPORT_VERSION_1 {
+        // Reads encrypted
+        let mut reader = chacha20poly1305_ietf::Reader::new(reader, key, nonce, chunk_size);
+
+        _check_header_hash(&mut reader, &header_bytes)?;
+
+        _read_entries(&mut reader, &mut apply_record)?;
+
+        if header.delta.is_some() {
+            _read_entries(&mut reader, |record: DeletedRecord| _apply_deleted(wallet, &mut operations, record))?;
+        }
+
+        wallet.batch(&operations)?;
+        return Ok(operations);
+    }
+
+    let mut reader = ChunkReader::new(reader, key, nonce);
+
+    reader.check_header_hash(&header_bytes)?;
+
+    let mut records = 0;
+    _read_chunks(&mut reader, false, |record: Record| {
+        records += 1;
+        apply_record(record)
+    })?;
+
+    let mut deleted_records = None;
+    if header.delta.is_some() {
+        let mut count = 0;
+        _read_chunks(&mut reader, false, |record: DeletedRecord| {
+            count += 1;
+            _apply_deleted(wallet, &mut operations, record)
+        })?;
+        deleted_records = Some(count);
+    }
+
+    let trailer = reader.read_trailer()??;
+
+    if trailer.records != records || trailer.deleted_records != deleted_records {
+        return Err(err_msg(IndyErrorKind::InvalidStructure, "Number of imported records doesn't match trailer of export file"));
+    }
+
+    wallet.batch(&operations)?;
+    Ok(operations)
+}
+
+fn _apply_deleted(wallet: &Wallet, operations: &mut Vec<WalletOperation>, record: DeletedRecord) -> IndyResult<()> {
+    if _exists(wallet, &record.type_, &record.id)? {
+        operations.push(WalletOperation::Delete { type_: record.type_, id: record.id });
+    }
+
+    Ok(())
+}
+
+fn _parse_header(header_bytes: &[u8]) -> IndyResult<Header> {
+    rmp_serde::from_slice(header_bytes)
+        .to_indy(IndyErrorKind::InvalidStructure, "Header is malformed json")
+}
 
+fn _check_header_hash<T>(reader: &mut T, header_bytes: &[u8]) -> IndyResult<()> where T: Read {
     let mut header_hash = vec![0u8; HASHBYTES];
     reader.read_exact(&mut header_hash).map_err(_map_io_err)?;
 
-    if hash(&header_bytes)? != header_hash {
+    if hash(header_bytes)? != header_hash {
         return Err(err_msg(IndyErrorKind::InvalidStructure, "Invalid header hash"));
     }
 
+    Ok(())
+}
+
+fn _read_entries<T, E, F>(reader: &mut T, mut apply: F) -> IndyResult<()> where T: Read, E: DeserializeOwned, F: FnMut(E) -> IndyResult<()> {
     loop {
         let record_len = reader.read_u32::<LittleEndian>().map_err(_map_io_err)? as usize;
 
@@ -221,15 +880,54 @@ pub(super) fn finish_import<T>(wallet: &Wallet, reader: BufReader<T>, key: chach
         let mut record = vec![0u8; record_len];
         reader.read_exact(&mut record).map_err(_map_io_err)?;
 
-        let record: Record = rmp_serde::from_slice(&record)
+        let record: E = rmp_serde::from_slice(&record)
             .to_indy(IndyErrorKind::InvalidStructure, "Record is malformed msgpack")?;
 
-        wallet.add(&record.type_, &record.id, &record.value, &record.tags)?;
+        apply(record)?;
     }
 
     Ok(())
 }
 
+// Reads v2 chunks until END message. Returns number of damaged chunks skipped in tolerant mode
+fn _read_chunks<T, E, F>(reader: &mut ChunkReader<T>, tolerant: bool, mut apply: F) -> IndyResult<u64> where T: Read, E: DeserializeOwned, F: FnMut(E) -> IndyResult<()> {
+    let skipped_chunks = reader.skipped;
+    let mut skipped = 0;
+
+    loop {
+        match reader.read_chunk(tolerant)? {
+            (CHUNK_RECORD, chunk) => {
+                match rmp_serde::from_slice::<E>(&chunk) {
+                    Ok(record) => apply(record)?,
+                    Err(_) if !tolerant => return Err(err_msg(IndyErrorKind::InvalidStructure, "Record is malformed msgpack")),
+                    Err(err) => {
+                        warn!("Malformed record of wallet export is skipped: {}", err);
+                        skipped += 1;
+                    }
+                }
+            }
+            (CHUNK_END, _) => break,
+            // END message was damaged and skipped, it isn't counted as skipped record
+            (CHUNK_TRAILER, chunk) if tolerant && reader.skipped > skipped_chunks => {
+                reader.trailer = Some(chunk);
+                reader.skipped -= 1;
+                break;
+            }
+            _ => return Err(err_msg(IndyErrorKind::InvalidStructure, "Unexpected chunk of export file")),
+        }
+    }
+
+    Ok(skipped + reader.skipped - skipped_chunks)
+}
+
+fn _exists(wallet: &Wallet, type_: &str, id: &str) -> IndyResult<bool> {
+    match wallet.get(type_, id, &RecordOptions::id()) {
+        Ok(_) => Ok(true),
+        Err(ref err) if err.kind() == IndyErrorKind::WalletItemNotFound => Ok(false),
+        Err(err) => Err(err)
+    }
+}
+
 fn _map_io_err(e: io::Error) -> IndyError {
     match e {
         ref e if e.kind() == io::ErrorKind::UnexpectedEof
@@ -256,14 +954,30 @@ mod tests {
     use super::*;
 
     fn export(wallet: &Wallet, writer: &mut dyn Write, passphrase: &str, version: u32, key_derivation_method: &KeyDerivationMethod) -> IndyResult<()> {
-        if version != 0 {
-            Err(err_msg(IndyErrorKind::InvalidState, "Unsupported version"))?;
-        }
+        let key_data = KeyDerivationData::from_passphrase_with_new_salt(passphrase, key_derivation_method)?;
+        let key = key_data.calc_master_key()?;
+
+        export_continue(wallet, writer, version, key, &key_data, &ExportFilter::default())
+    }
+
+    fn export_filtered(wallet: &Wallet, writer: &mut dyn Write, passphrase: &str, types: Option<&[String]>, query: Option<&str>) -> IndyResult<()> {
+        let key_data = KeyDerivationData::from_passphrase_with_new_salt(passphrase, &KeyDerivationMethod::ARGON2I_MOD)?;
+        let key = key_data.calc_master_key()?;
+
+        export_continue(wallet, writer, _version1(), key, &key_data, &ExportFilter { types, query, since_revision: None })
+    }
 
-        let key_data = KeyDerivationData::from_passphrase_with_new_salt(passphrase, key_derivation_method);
+    fn export_delta(wallet: &Wallet, writer: &mut dyn Write, passphrase: &str, version: u32, since_revision: u64) -> IndyResult<()> {
+        let key_data = KeyDerivationData::from_passphrase_with_new_salt(passphrase, &KeyDerivationMethod::ARGON2I_MOD)?;
         let key = key_data.calc_master_key()?;
 
-        export_continue(wallet, writer, version, key, &key_data)
+        export_continue(wallet, writer, version, key, &key_data, &ExportFilter { types: None, query: None, since_revision: Some(since_revision) })
+    }
+
+    fn import_delta<T>(wallet: &Wallet, reader: T, passphrase: &str) -> IndyResult<()> where T: Read {
+        let (reader, import_key_derivation_data, nonce, chunk_size, header_bytes) = preparse_file_to_import(reader, passphrase)?;
+        let import_key = import_key_derivation_data.calc_master_key()?;
+        finish_import_delta(wallet, reader, import_key, nonce, chunk_size, header_bytes).map(|_| ())
     }
 
     #[test]
@@ -347,6 +1061,170 @@ mod tests {
         _cleanup("export_import_works_for_multiple_items2");
     }
 
+    #[test]
+    fn export_import_works_for_types_filter() {
+        _cleanup("export_import_works_for_types_filter1");
+        _cleanup("export_import_works_for_types_filter2");
+        {
+            let mut output: Vec<u8> = Vec::new();
+            export_filtered(&_add_2_records(_wallet("export_import_works_for_types_filter1")), &mut output, _passphrase(), Some(&[_type1()]), None).unwrap();
+
+            let wallet = _wallet("export_import_works_for_types_filter2");
+            import(&wallet, &mut output.as_slice(), _passphrase()).unwrap();
+
+            let record = wallet.get(&_type1(), &_id1(), _options()).unwrap();
+            assert_eq!(record.value.unwrap(), _value1());
+
+            let res = wallet.get(&_type2(), &_id2(), _options());
+            assert_eq!(IndyErrorKind::WalletItemNotFound, res.unwrap_err().kind());
+        }
+        _cleanup("export_import_works_for_types_filter1");
+        _cleanup("export_import_works_for_types_filter2");
+    }
+
+    #[test]
+    fn export_import_works_for_types_and_query_filter() {
+        _cleanup("export_import_works_for_types_and_query_filter1");
+        _cleanup("export_import_works_for_types_and_query_filter2");
+        {
+            let mut output: Vec<u8> = Vec::new();
+            export_filtered(&_add_300_records(_wallet("export_import_works_for_types_and_query_filter1")),
+                            &mut output, _passphrase(), Some(&[_type(0)]), Some(r#"{"tag_id_3_1": "tag_value_3_1"}"#)).unwrap();
+
+            let wallet = _wallet("export_import_works_for_types_and_query_filter2");
+            import(&wallet, &mut output.as_slice(), _passphrase()).unwrap();
+
+            let record = wallet.get(&_type(0), &_id(3), _options()).unwrap();
+            assert_eq!(record.value.unwrap(), _value(3));
+            assert_eq!(record.tags.unwrap(), _tags(3));
+
+            let mut records = wallet.get_all().unwrap();
+            assert!(records.next().unwrap().is_some());
+            assert!(records.next().unwrap().is_none());
+        }
+        _cleanup("export_import_works_for_types_and_query_filter1");
+        _cleanup("export_import_works_for_types_and_query_filter2");
+    }
+
+    #[test]
+    fn export_works_for_query_without_types() {
+        _cleanup("export_works_for_query_without_types");
+
+        let mut output: Vec<u8> = Vec::new();
+        let res = export_filtered(&_add_2_records(_wallet("export_works_for_query_without_types")), &mut output, _passphrase(), None, Some("{}"));
+        assert_eq!(IndyErrorKind::InvalidStructure, res.unwrap_err().kind());
+
+        _cleanup("export_works_for_query_without_types");
+    }
+
+    #[test]
+    fn export_import_works_for_delta() {
+        _cleanup("export_import_works_for_delta1");
+        _cleanup("export_import_works_for_delta2");
+        {
+            let wallet1 = _add_2_records(_wallet("export_import_works_for_delta1"));
+            let revision = wallet1.get_revision().unwrap();
+
+            let mut output: Vec<u8> = Vec::new();
+            export(&wallet1, &mut output, _passphrase(), _version1(), &KeyDerivationMethod::ARGON2I_MOD).unwrap();
+
+            let wallet2 = _wallet("export_import_works_for_delta2");
+            import(&wallet2, &mut output.as_slice(), _passphrase()).unwrap();
+            _assert_has_2_records(&wallet2);
+
+            wallet1.update(&_type1(), &_id1(), &_value(3)).unwrap();
+            wallet1.delete(&_type2(), &_id2()).unwrap();
+            wallet1.add(&_type(3), &_id(3), &_value(3), &_tags(3)).unwrap();
+
+            let mut delta: Vec<u8> = Vec::new();
+            export_delta(&wallet1, &mut delta, _passphrase(), _version1(), revision).unwrap();
+
+            import_delta(&wallet2, &mut delta.as_slice(), _passphrase()).unwrap();
+
+            let record = wallet2.get(&_type1(), &_id1(), _options()).unwrap();
+            assert_eq!(record.value.unwrap(), _value(3));
+            assert_eq!(record.tags.unwrap(), _tags1());
+
+            let res = wallet2.get(&_type2(), &_id2(), _options());
+            assert_eq!(IndyErrorKind::WalletItemNotFound, res.unwrap_err().kind());
+
+            let record = wallet2.get(&_type(3), &_id(3), _options()).unwrap();
+            assert_eq!(record.value.unwrap(), _value(3));
+            assert_eq!(record.tags.unwrap(), _tags(3));
+
+            // delta can be applied again
+            import_delta(&wallet2, &mut delta.as_slice(), _passphrase()).unwrap();
+        }
+        _cleanup("export_import_works_for_delta1");
+        _cleanup("export_import_works_for_delta2");
+    }
+
+    #[test]
+    fn import_delta_works_for_cut_file() {
+        _cleanup("import_delta_works_for_cut_file1");
+        _cleanup("import_delta_works_for_cut_file2");
+        {
+            let wallet1 = _add_2_records(_wallet("import_delta_works_for_cut_file1"));
+            let revision = wallet1.get_revision().unwrap();
+
+            let mut output: Vec<u8> = Vec::new();
+            export(&wallet1, &mut output, _passphrase(), _version1(), &KeyDerivationMethod::ARGON2I_MOD).unwrap();
+
+            let wallet2 = _wallet("import_delta_works_for_cut_file2");
+            import(&wallet2, &mut output.as_slice(), _passphrase()).unwrap();
+
+            wallet1.update(&_type1(), &_id1(), &_value(3)).unwrap();
+            wallet1.delete(&_type2(), &_id2()).unwrap();
+
+            let mut delta: Vec<u8> = Vec::new();
+            export_delta(&wallet1, &mut delta, _passphrase(), _version1(), revision).unwrap();
+
+            // the end of the deleted records list is cut, so nothing must be applied
+            let len = delta.len() - 4;
+            delta.truncate(len);
+
+            let res = import_delta(&wallet2, &mut delta.as_slice(), _passphrase());
+            assert_eq!(IndyErrorKind::InvalidStructure, res.unwrap_err().kind());
+            _assert_has_2_records(&wallet2);
+        }
+        _cleanup("import_delta_works_for_cut_file1");
+        _cleanup("import_delta_works_for_cut_file2");
+    }
+
+    #[test]
+    fn export_delta_works_for_no_changes() {
+        _cleanup("export_delta_works_for_no_changes1");
+        _cleanup("export_delta_works_for_no_changes2");
+        {
+            let wallet1 = _add_2_records(_wallet("export_delta_works_for_no_changes1"));
+            let revision = wallet1.get_revision().unwrap();
+
+            let mut delta: Vec<u8> = Vec::new();
+            export_delta(&wallet1, &mut delta, _passphrase(), _version1(), revision).unwrap();
+
+            let wallet2 = _wallet("export_delta_works_for_no_changes2");
+            import_delta(&wallet2, &mut delta.as_slice(), _passphrase()).unwrap();
+            _assert_is_empty(&wallet2);
+        }
+        _cleanup("export_delta_works_for_no_changes1");
+        _cleanup("export_delta_works_for_no_changes2");
+    }
+
+    #[test]
+    fn import_works_for_delta_as_new_wallet() {
+        _cleanup("import_works_for_delta_as_new_wallet1");
+        _cleanup("import_works_for_delta_as_new_wallet2");
+        {
+            let mut delta: Vec<u8> = Vec::new();
+            export_delta(&_add_2_records(_wallet("import_works_for_delta_as_new_wallet1")), &mut delta, _passphrase(), _version1(), 0).unwrap();
+
+            let res = import(&_wallet("import_works_for_delta_as_new_wallet2"), &mut delta.as_slice(), _passphrase());
+            assert_eq!(IndyErrorKind::InvalidStructure, res.unwrap_err().kind());
+        }
+        _cleanup("import_works_for_delta_as_new_wallet1");
+        _cleanup("import_works_for_delta_as_new_wallet2");
+    }
+
     #[test]
     fn import_works_for_empty() {
         _cleanup("import_works_for_empty");
@@ -460,6 +1338,297 @@ mod tests {
         _cleanup("import_works_for_data_extended2");
     }
 
+    #[test]
+    fn export_version_works() {
+        assert_eq!(EXPORT_VERSION_1, export_version(None).unwrap());
+        assert_eq!(EXPORT_VERSION_1, export_version(Some(1)).unwrap());
+        assert_eq!(EXPORT_VERSION_2, export_version(Some(2)).unwrap());
+        assert_eq!(IndyErrorKind::InvalidStructure, export_version(Some(3)).unwrap_err().kind());
+    }
+
+    #[test]
+    fn export_works_for_unsupported_version() {
+        _cleanup("export_works_for_unsupported_version");
+
+        let mut output: Vec<u8> = Vec::new();
+        let res = export(&_wallet("export_works_for_unsupported_version"), &mut output, _passphrase(), 2, &KeyDerivationMethod::ARGON2I_MOD);
+        assert_eq!(IndyErrorKind::InvalidState, res.unwrap_err().kind());
+        _cleanup("export_works_for_unsupported_version");
+    }
+
+    #[test]
+    fn export_import_works_for_version2_and_2_items() {
+        _cleanup("export_import_works_for_version2_and_2_items1");
+        _cleanup("export_import_works_for_version2_and_2_items2");
+
+        let mut output: Vec<u8> = Vec::new();
+        export(&_add_2_records(_wallet("export_import_works_for_version2_and_2_items1")), &mut output, _passphrase(), _version2(), &KeyDerivationMethod::ARGON2I_MOD).unwrap();
+
+        let wallet = _wallet("export_import_works_for_version2_and_2_items2");
+        let report = import_tolerant(&wallet, &mut output.as_slice(), _passphrase(), false).unwrap();
+        assert_eq!(ImportReport { imported: 2, skipped: 0, expected: Some(2) }, report);
+        _assert_has_2_records(&wallet);
+
+        _cleanup("export_import_works_for_version2_and_2_items1");
+        _cleanup("export_import_works_for_version2_and_2_items2");
+    }
+
+    #[test]
+    fn export_import_works_for_version2_and_multiple_items() {
+        _cleanup("export_import_works_for_version2_and_multiple_items1");
+        _cleanup("export_import_works_for_version2_and_multiple_items2");
+
+        let mut output: Vec<u8> = Vec::new();
+        export(&_add_300_records(_wallet("export_import_works_for_version2_and_multiple_items1")), &mut output, _passphrase(), _version2(), &KeyDerivationMethod::ARGON2I_MOD).unwrap();
+
+        let wallet = _wallet("export_import_works_for_version2_and_multiple_items2");
+        import(&wallet, &mut output.as_slice(), _passphrase()).unwrap();
+        _assert_has_300_records(&wallet);
+
+        _cleanup("export_import_works_for_version2_and_multiple_items1");
+        _cleanup("export_import_works_for_version2_and_multiple_items2");
+    }
+
+    #[test]
+    fn export_import_works_for_version2_and_empty_wallet() {
+        _cleanup("export_import_works_for_version2_and_empty_wallet1");
+        _cleanup("export_import_works_for_version2_and_empty_wallet2");
+
+        let mut output: Vec<u8> = Vec::new();
+        export(&_wallet("export_import_works_for_version2_and_empty_wallet1"), &mut output, _passphrase(), _version2(), &KeyDerivationMethod::RAW).unwrap();
+
+        let wallet = _wallet("export_import_works_for_version2_and_empty_wallet2");
+        import(&wallet, &mut output.as_slice(), _passphrase()).unwrap();
+        _assert_is_empty(&wallet);
+
+        _cleanup("export_import_works_for_version2_and_empty_wallet1");
+        _cleanup("export_import_works_for_version2_and_empty_wallet2");
+    }
+
+    #[test]
+    fn export_import_works_for_version2_and_delta() {
+        _cleanup("export_import_works_for_version2_and_delta1");
+        _cleanup("export_import_works_for_version2_and_delta2");
+        {
+            let wallet1 = _add_2_records(_wallet("export_import_works_for_version2_and_delta1"));
+            let revision = wallet1.get_revision().unwrap();
+
+            let mut output: Vec<u8> = Vec::new();
+            export(&wallet1, &mut output, _passphrase(), _version2(), &KeyDerivationMethod::ARGON2I_MOD).unwrap();
+
+            let wallet2 = _wallet("export_import_works_for_version2_and_delta2");
+            import(&wallet2, &mut output.as_slice(), _passphrase()).unwrap();
+
+            wallet1.update(&_type1(), &_id1(), &_value(3)).unwrap();
+            wallet1.delete(&_type2(), &_id2()).unwrap();
+
+            let mut delta: Vec<u8> = Vec::new();
+            export_delta(&wallet1, &mut delta, _passphrase(), _version2(), revision).unwrap();
+
+            import_delta(&wallet2, &mut delta.as_slice(), _passphrase()).unwrap();
+
+            let record = wallet2.get(&_type1(), &_id1(), _options()).unwrap();
+            assert_eq!(record.value.unwrap(), _value(3));
+
+            let res = wallet2.get(&_type2(), &_id2(), _options());
+            assert_eq!(IndyErrorKind::WalletItemNotFound, res.unwrap_err().kind());
+
+            // Damaged delta is never applied partially in tolerant way
+            let pos = delta.len() - 40;
+            _change_byte(&mut delta, pos);
+
+            let res = import_delta(&wallet2, &mut delta.as_slice(), _passphrase());
+            assert_eq!(IndyErrorKind::InvalidStructure, res.unwrap_err().kind());
+        }
+        _cleanup("export_import_works_for_version2_and_delta1");
+        _cleanup("export_import_works_for_version2_and_delta2");
+    }
+
+    #[test]
+    fn import_works_for_version2_and_changed_record() {
+        _cleanup("import_works_for_version2_and_changed_record1");
+        _cleanup("import_works_for_version2_and_changed_record2");
+        _cleanup("import_works_for_version2_and_changed_record3");
+
+        let mut output: Vec<u8> = Vec::new();
+        export(&_add_300_records(_wallet("import_works_for_version2_and_changed_record1")), &mut output, _passphrase(), _version2(), &KeyDerivationMethod::ARGON2I_MOD).unwrap();
+
+        // Modifying one byte inside of first record chunk
+        let pos = _chunk_pos(&output, 1) + 4 + 2;
+        _change_byte(&mut output, pos);
+
+        let res = import(&_wallet("import_works_for_version2_and_changed_record2"), &mut output.as_slice(), _passphrase());
+        assert_eq!(IndyErrorKind::InvalidStructure, res.unwrap_err().kind());
+
+        let report = import_tolerant(&_wallet("import_works_for_version2_and_changed_record3"), &mut output.as_slice(), _passphrase(), true).unwrap();
+        assert_eq!(1, report.skipped);
+        assert_eq!(299, report.imported);
+        assert_eq!(Some(300), report.expected);
+
+        _cleanup("import_works_for_version2_and_changed_record1");
+        _cleanup("import_works_for_version2_and_changed_record2");
+        _cleanup("import_works_for_version2_and_changed_record3");
+    }
+
+    #[test]
+    fn import_works_for_version2_and_changed_chunk_length() {
+        _cleanup("import_works_for_version2_and_changed_chunk_length1");
+        _cleanup("import_works_for_version2_and_changed_chunk_length2");
+        _cleanup("import_works_for_version2_and_changed_chunk_length3");
+
+        let mut output: Vec<u8> = Vec::new();
+        export(&_add_300_records(_wallet("import_works_for_version2_and_changed_chunk_length1")), &mut output, _passphrase(), _version2(), &KeyDerivationMethod::RAW).unwrap();
+
+        // Modifying length of first record chunk
+        let pos = _chunk_pos(&output, 1);
+        _change_byte(&mut output, pos);
+
+        let res = import(&_wallet("import_works_for_version2_and_changed_chunk_length2"), &mut output.as_slice(), _passphrase());
+        assert_eq!(IndyErrorKind::InvalidStructure, res.unwrap_err().kind());
+
+        let report = import_tolerant(&_wallet("import_works_for_version2_and_changed_chunk_length3"), &mut output.as_slice(), _passphrase(), true).unwrap();
+        assert_eq!(1, report.skipped);
+        assert_eq!(299, report.imported);
+        assert_eq!(Some(300), report.expected);
+
+        _cleanup("import_works_for_version2_and_changed_chunk_length1");
+        _cleanup("import_works_for_version2_and_changed_chunk_length2");
+        _cleanup("import_works_for_version2_and_changed_chunk_length3");
+    }
+
+    #[test]
+    fn import_works_for_version2_and_zero_chunk_length() {
+        _cleanup("import_works_for_version2_and_zero_chunk_length1");
+        _cleanup("import_works_for_version2_and_zero_chunk_length2");
+        _cleanup("import_works_for_version2_and_zero_chunk_length3");
+
+        let mut output: Vec<u8> = Vec::new();
+        export(&_add_300_records(_wallet("import_works_for_version2_and_zero_chunk_length1")), &mut output, _passphrase(), _version2(), &KeyDerivationMethod::RAW).unwrap();
+
+        // Zero length of 100th record chunk looks like END message of v1 export
+        let pos = _chunk_pos(&output, 100);
+        for byte in &mut output[pos..pos + 4] {
+            *byte = 0;
+        }
+
+        let res = import(&_wallet("import_works_for_version2_and_zero_chunk_length2"), &mut output.as_slice(), _passphrase());
+        assert_eq!(IndyErrorKind::InvalidStructure, res.unwrap_err().kind());
+
+        let report = import_tolerant(&_wallet("import_works_for_version2_and_zero_chunk_length3"), &mut output.as_slice(), _passphrase(), true).unwrap();
+        assert_eq!(1, report.skipped);
+        assert_eq!(299, report.imported);
+        assert_eq!(Some(300), report.expected);
+
+        _cleanup("import_works_for_version2_and_zero_chunk_length1");
+        _cleanup("import_works_for_version2_and_zero_chunk_length2");
+        _cleanup("import_works_for_version2_and_zero_chunk_length3");
+    }
+
+    #[test]
+    fn import_works_for_version2_and_changed_end() {
+        _cleanup("import_works_for_version2_and_changed_end1");
+        _cleanup("import_works_for_version2_and_changed_end2");
+        _cleanup("import_works_for_version2_and_changed_end3");
+
+        let mut output: Vec<u8> = Vec::new();
+        export(&_add_2_records(_wallet("import_works_for_version2_and_changed_end1")), &mut output, _passphrase(), _version2(), &KeyDerivationMethod::RAW).unwrap();
+
+        // Modifying one byte inside of END chunk that follows header hash and 2 records
+        let pos = _chunk_pos(&output, 3) + 4 + 2;
+        _change_byte(&mut output, pos);
+
+        let res = import(&_wallet("import_works_for_version2_and_changed_end2"), &mut output.as_slice(), _passphrase());
+        assert_eq!(IndyErrorKind::InvalidStructure, res.unwrap_err().kind());
+
+        let report = import_tolerant(&_wallet("import_works_for_version2_and_changed_end3"), &mut output.as_slice(), _passphrase(), true).unwrap();
+        assert_eq!(0, report.skipped);
+        assert_eq!(2, report.imported);
+        assert_eq!(Some(2), report.expected);
+
+        _cleanup("import_works_for_version2_and_changed_end1");
+        _cleanup("import_works_for_version2_and_changed_end2");
+        _cleanup("import_works_for_version2_and_changed_end3");
+    }
+
+    #[test]
+    fn import_works_for_version2_and_dropped_end() {
+        _cleanup("import_works_for_version2_and_dropped_end1");
+        _cleanup("import_works_for_version2_and_dropped_end2");
+        _cleanup("import_works_for_version2_and_dropped_end3");
+
+        let mut output: Vec<u8> = Vec::new();
+        export(&_add_2_records(_wallet("import_works_for_version2_and_dropped_end1")), &mut output, _passphrase(), _version2(), &KeyDerivationMethod::RAW).unwrap();
+
+        // Removing END chunk that follows header hash and 2 records, so trailer follows the records
+        let (start, end) = (_chunk_pos(&output, 3), _chunk_pos(&output, 4));
+        output.drain(start..end);
+
+        let res = import(&_wallet("import_works_for_version2_and_dropped_end2"), &mut output.as_slice(), _passphrase());
+        assert_eq!(IndyErrorKind::InvalidStructure, res.unwrap_err().kind());
+
+        let report = import_tolerant(&_wallet("import_works_for_version2_and_dropped_end3"), &mut output.as_slice(), _passphrase(), true).unwrap();
+        assert_eq!(0, report.skipped);
+        assert_eq!(2, report.imported);
+        assert_eq!(Some(2), report.expected);
+
+        _cleanup("import_works_for_version2_and_dropped_end1");
+        _cleanup("import_works_for_version2_and_dropped_end2");
+        _cleanup("import_works_for_version2_and_dropped_end3");
+    }
+
+    #[test]
+    fn import_works_for_version2_and_changed_header_hash() {
+        _cleanup("import_works_for_version2_and_changed_header_hash1");
+        _cleanup("import_works_for_version2_and_changed_header_hash2");
+
+        let mut output: Vec<u8> = Vec::new();
+        export(&_add_2_records(_wallet("import_works_for_version2_and_changed_header_hash1")), &mut output, _passphrase(), _version2(), &KeyDerivationMethod::RAW).unwrap();
+
+        // Modifying one byte inside of header hash chunk
+        let pos = _chunk_pos(&output, 0) + 4 + 2;
+        _change_byte(&mut output, pos);
+
+        let res = import_tolerant(&_wallet("import_works_for_version2_and_changed_header_hash2"), &mut output.as_slice(), _passphrase(), true);
+        assert_eq!(IndyErrorKind::InvalidStructure, res.unwrap_err().kind());
+
+        _cleanup("import_works_for_version2_and_changed_header_hash1");
+        _cleanup("import_works_for_version2_and_changed_header_hash2");
+    }
+
+    #[test]
+    fn import_works_for_version2_and_data_cut() {
+        _cleanup("import_works_for_version2_and_data_cut1");
+        _cleanup("import_works_for_version2_and_data_cut2");
+
+        let mut output: Vec<u8> = Vec::new();
+        export(&_add_2_records(_wallet("import_works_for_version2_and_data_cut1")), &mut output, _passphrase(), _version2(), &KeyDerivationMethod::RAW).unwrap();
+
+        output.pop().unwrap();
+
+        let res = import_tolerant(&_wallet("import_works_for_version2_and_data_cut2"), &mut output.as_slice(), _passphrase(), true);
+        assert_eq!(IndyErrorKind::InvalidStructure, res.unwrap_err().kind());
+
+        _cleanup("import_works_for_version2_and_data_cut1");
+        _cleanup("import_works_for_version2_and_data_cut2");
+    }
+
+    #[test]
+    fn import_works_for_version2_and_data_extended() {
+        _cleanup("import_works_for_version2_and_data_extended1");
+        _cleanup("import_works_for_version2_and_data_extended2");
+
+        let mut output: Vec<u8> = Vec::new();
+        export(&_add_2_records(_wallet("import_works_for_version2_and_data_extended1")), &mut output, _passphrase(), _version2(), &KeyDerivationMethod::RAW).unwrap();
+
+        output.push(10);
+
+        let res = import_tolerant(&_wallet("import_works_for_version2_and_data_extended2"), &mut output.as_slice(), _passphrase(), true);
+        assert_eq!(IndyErrorKind::InvalidStructure, res.unwrap_err().kind());
+
+        _cleanup("import_works_for_version2_and_data_extended1");
+        _cleanup("import_works_for_version2_and_data_extended2");
+    }
+
     fn _cleanup(name: &str) {
         test::cleanup_storage(name)
     }
@@ -480,6 +1649,9 @@ mod tests {
             let metadata = Metadata::MetadataArgon(MetadataArgon {
                 master_key_salt: master_key_salt[..].to_vec(),
                 keys: keys.serialize_encrypted(&master_key).unwrap(),
+                keys_rotation: None,
+                prefix_indexes: HashMap::new(),
+                pending_prefix_indexes: None,
             });
 
             serde_json::to_vec(&metadata)
@@ -501,8 +1673,8 @@ mod tests {
     }
 
     fn _add_2_records(wallet: Wallet) -> Wallet {
-        wallet.add(&_type1(), &_id1(), &_value1(), &_tags1()).unwrap();
-        wallet.add(&_type2(), &_id2(), &_value2(), &_tags2()).unwrap();
+        wallet.add(&_type1(), &_id1(), &_value1(), &_tags1(), None).unwrap();
+        wallet.add(&_type2(), &_id2(), &_value2(), &_tags2(), None).unwrap();
         wallet
     }
 
@@ -522,7 +1694,7 @@ mod tests {
 
     fn _add_300_records(wallet: Wallet) -> Wallet {
         for i in 0..300 {
-            wallet.add(&_type(i % 3), &_id(i), &_value(i), &_tags(i)).unwrap();
+            wallet.add(&_type(i % 3), &_id(i), &_value(i), &_tags(i), None).unwrap();
         }
 
         wallet
@@ -546,6 +1718,17 @@ mod tests {
         chacha20poly1305_ietf::gen_nonce()
     }
 
+    // Returns position of n-th chunk of v2 export
+    fn _chunk_pos(data: &[u8], n: usize) -> usize {
+        let mut pos = 4 + (&data[..4]).read_u32::<LittleEndian>().unwrap() as usize;
+
+        for _ in 0..n {
+            pos += 4 + (&data[pos..pos + 4]).read_u32::<LittleEndian>().unwrap() as usize;
+        }
+
+        pos
+    }
+
     fn _change_byte(data: &mut [u8], pos: usize) {
         let value = data[pos];
         data[pos] = if value < 255 { value + 1 } else { 0 };
@@ -568,6 +1751,10 @@ mod tests {
         0
     }
 
+    fn _version2() -> u32 {
+        1
+    }
+
     fn _id(suffix: usize) -> String {
         format!("id_{}", suffix)
     }
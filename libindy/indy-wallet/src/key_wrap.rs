@@ -0,0 +1,257 @@
+use std::ffi::CString;
+use std::ptr;
+use std::slice;
+
+use indy_api_types::{ErrorCode, IndyHandle};
+use indy_api_types::errors::prelude::*;
+use indy_api_types::wallet::{WalletKeyFree, WalletKeyUnwrap, WalletKeyWrap};
+
+///
+/// Wraps wallet master key with the key kept outside of the wallet (KMS, HSM, OS keystore).
+/// Only the wrapped master key is stored in wallet metadata.
+///
+pub trait KeyWrapProvider {
+    fn wrap_key(&self, key_id: &str, key: &[u8]) -> IndyResult<Vec<u8>>;
+    fn unwrap_key(&self, key_id: &str, wrapped_key: &[u8]) -> IndyResult<Vec<u8>>;
+}
+
+struct KeyGuard {
+    key_handle: IndyHandle,
+    free_handler: WalletKeyFree,
+}
+
+impl Drop for KeyGuard {
+    fn drop(&mut self) {
+        (self.free_handler)(self.key_handle);
+    }
+}
+
+// Key wrap provider implemented by application through C callbacks
+pub(crate) struct PluggedKeyWrapProvider {
+    wrap_handler: WalletKeyWrap,
+    unwrap_handler: WalletKeyUnwrap,
+    free_handler: WalletKeyFree,
+}
+
+impl PluggedKeyWrapProvider {
+    pub fn new(wrap_handler: WalletKeyWrap, unwrap_handler: WalletKeyUnwrap, free_handler: WalletKeyFree) -> PluggedKeyWrapProvider {
+        PluggedKeyWrapProvider { wrap_handler, unwrap_handler, free_handler }
+    }
+
+    // wrap and unwrap handlers have the same signature
+    fn _call(&self, handler: WalletKeyWrap, key_id: &str, data: &[u8]) -> IndyResult<Vec<u8>> {
+        let key_id = CString::new(key_id)?;
+        let mut res_ptr: *const u8 = ptr::null();
+        let mut res_len: usize = 0;
+        let mut key_handle: IndyHandle = -1;
+
+        let err = handler(key_id.as_ptr(), data.as_ptr(), data.len(), &mut res_ptr, &mut res_len, &mut key_handle);
+
+        if err != ErrorCode::Success {
+            return Err(err.into());
+        }
+
+        let _key_guard = KeyGuard { key_handle, free_handler: self.free_handler };
+
+        if res_ptr.is_null() {
+            return Err(err_msg(IndyErrorKind::InvalidState, "Key wrap provider returned empty key"));
+        }
+
+        Ok(unsafe { slice::from_raw_parts(res_ptr, res_len) }.to_vec())
+    }
+}
+
+impl KeyWrapProvider for PluggedKeyWrapProvider {
+    fn wrap_key(&self, key_id: &str, key: &[u8]) -> IndyResult<Vec<u8>> {
+        self._call(self.wrap_handler, key_id, key)
+    }
+
+    fn unwrap_key(&self, key_id: &str, wrapped_key: &[u8]) -> IndyResult<Vec<u8>> {
+        self._call(self.unwrap_handler, key_id, wrapped_key)
+    }
+}
+
+#[cfg(any(test, feature = "file_key_wrap_provider"))]
+pub(crate) use self::file::FileKeyWrapProvider;
+
+// The key is kept unprotected on the disk, so the provider is available only for tests
+// and with `file_key_wrap_provider` feature
+#[cfg(any(test, feature = "file_key_wrap_provider"))]
+mod file {
+    use std::fs;
+    use std::io::Write;
+    #[cfg(unix)]
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::path::{Path, PathBuf};
+
+    use rust_base58::{FromBase58, ToBase58};
+    use zeroize::Zeroize;
+
+    use indy_api_types::errors::prelude::*;
+    use indy_utils::crypto::chacha20poly1305_ietf;
+    use indy_utils::environment;
+
+    use super::KeyWrapProvider;
+    use crate::encryption::{decrypt_merged, encrypt_as_not_searchable};
+
+    ///
+    /// Reference provider that keeps wrapping key in a file.
+    /// Key id is a name of the file with base58 encoded key in `wrapping_keys` directory of indy home,
+    /// the file is created with random key on first wrapping.
+    ///
+    pub(crate) struct FileKeyWrapProvider {}
+
+    impl FileKeyWrapProvider {
+        pub fn new() -> FileKeyWrapProvider {
+            FileKeyWrapProvider {}
+        }
+
+        pub fn key_path(key_id: &str) -> IndyResult<PathBuf> {
+            let is_valid = !key_id.is_empty()
+                && !key_id.starts_with('.')
+                && key_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.');
+
+            if !is_valid {
+                return Err(err_msg(IndyErrorKind::InvalidStructure, format!("Invalid wrapping key id: {:?}", key_id)));
+            }
+
+            Ok(environment::indy_home_path().join("wrapping_keys").join(key_id))
+        }
+
+        fn _read_key(path: &Path) -> IndyResult<chacha20poly1305_ietf::Key> {
+            let mut encoded = fs::read_to_string(path)
+                .to_indy(IndyErrorKind::WalletAccessFailed, format!("Can't read wrapping key file: {:?}", path))?;
+
+            let decoded = encoded.trim().from_base58();
+            encoded.zeroize();
+
+            let mut decoded = decoded
+                .map_err(|_| err_msg(IndyErrorKind::WalletAccessFailed, format!("Invalid wrapping key in file: {:?}", path)))?;
+
+            let key = chacha20poly1305_ietf::Key::from_slice(&decoded)
+                .map_err(|err| err.map(IndyErrorKind::WalletAccessFailed, format!("Invalid wrapping key in file: {:?}", path)));
+            decoded.zeroize();
+
+            key
+        }
+
+        fn _create_key(path: &Path) -> IndyResult<chacha20poly1305_ietf::Key> {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+
+            let key = chacha20poly1305_ietf::gen_key();
+
+            let mut options = fs::OpenOptions::new();
+            options.write(true).create_new(true);
+            #[cfg(unix)]
+            options.mode(0o600);
+
+            let mut file = options.open(path)?;
+
+            let mut encoded = key[..].to_base58();
+            let res = file.write_all(encoded.as_bytes());
+            encoded.zeroize();
+            res?;
+
+            Ok(key)
+        }
+    }
+
+    impl KeyWrapProvider for FileKeyWrapProvider {
+        fn wrap_key(&self, key_id: &str, key: &[u8]) -> IndyResult<Vec<u8>> {
+            let path = FileKeyWrapProvider::key_path(key_id)?;
+
+            let wrapping_key = if path.exists() {
+                FileKeyWrapProvider::_read_key(&path)?
+            } else {
+                FileKeyWrapProvider::_create_key(&path)?
+            };
+
+            Ok(encrypt_as_not_searchable(key, &wrapping_key))
+        }
+
+        fn unwrap_key(&self, key_id: &str, wrapped_key: &[u8]) -> IndyResult<Vec<u8>> {
+            let wrapping_key = FileKeyWrapProvider::_read_key(&FileKeyWrapProvider::key_path(key_id)?)?;
+
+            decrypt_merged(wrapped_key, &wrapping_key)
+                .map_err(|err| err.map(IndyErrorKind::WalletAccessFailed, "Invalid wrapping key"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use indy_utils::crypto::chacha20poly1305_ietf;
+
+    use super::*;
+
+    fn _key_path(name: &str) -> String {
+        let _ = fs::remove_file(FileKeyWrapProvider::key_path(name).unwrap());
+        name.to_string()
+    }
+
+    #[test]
+    fn file_key_wrap_provider_works() {
+        let key_id = _key_path("file_key_wrap_provider_works");
+        let provider = FileKeyWrapProvider::new();
+
+        let key = chacha20poly1305_ietf::gen_key();
+        let wrapped_key = provider.wrap_key(&key_id, &key[..]).unwrap();
+        assert_ne!(key[..].to_vec(), wrapped_key);
+
+        // the same wrapping key is used for next wrapping
+        let wrapped_key_2 = provider.wrap_key(&key_id, &key[..]).unwrap();
+
+        assert_eq!(key[..].to_vec(), provider.unwrap_key(&key_id, &wrapped_key).unwrap());
+        assert_eq!(key[..].to_vec(), provider.unwrap_key(&key_id, &wrapped_key_2).unwrap());
+    }
+
+    #[test]
+    fn file_key_wrap_provider_works_for_other_key() {
+        let key_id = _key_path("file_key_wrap_provider_works_for_other_key");
+        let other_key_id = _key_path("file_key_wrap_provider_works_for_other_key_2");
+        let provider = FileKeyWrapProvider::new();
+
+        let key = chacha20poly1305_ietf::gen_key();
+        let wrapped_key = provider.wrap_key(&key_id, &key[..]).unwrap();
+
+        let res = provider.unwrap_key(&other_key_id, &wrapped_key);
+        assert_kind!(IndyErrorKind::WalletAccessFailed, res);
+
+        provider.wrap_key(&other_key_id, &key[..]).unwrap();
+
+        let res = provider.unwrap_key(&other_key_id, &wrapped_key);
+        assert_kind!(IndyErrorKind::WalletAccessFailed, res);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn file_key_wrap_provider_creates_key_file_readable_by_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let key_id = _key_path("file_key_wrap_provider_creates_key_file_readable_by_owner_only");
+        let provider = FileKeyWrapProvider::new();
+
+        provider.wrap_key(&key_id, &chacha20poly1305_ietf::gen_key()[..]).unwrap();
+
+        let metadata = fs::metadata(FileKeyWrapProvider::key_path(&key_id).unwrap()).unwrap();
+        assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+    }
+
+    #[test]
+    fn file_key_wrap_provider_returns_error_for_path_as_key_id() {
+        let provider = FileKeyWrapProvider::new();
+        let key = chacha20poly1305_ietf::gen_key();
+
+        for key_id in &["", "../key", "/tmp/key", "dir/key", ".key"] {
+            let res = provider.wrap_key(key_id, &key[..]);
+            assert_kind!(IndyErrorKind::InvalidStructure, res);
+
+            let res = provider.unwrap_key(key_id, &key[..]);
+            assert_kind!(IndyErrorKind::InvalidStructure, res);
+        }
+    }
+}
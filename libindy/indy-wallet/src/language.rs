@@ -67,6 +67,21 @@ pub enum Operator {
     Lte(TagName, TargetValue),
     Like(TagName, TargetValue),
     In(TagName, Vec<TargetValue>),
+    Exist(TagName),
+    NumGt(TagName, TargetValue),
+    NumGte(TagName, TargetValue),
+    NumLt(TagName, TargetValue),
+    NumLte(TagName, TargetValue),
+    // Encrypted tags are matched by prefix through blind indexes, so only plain tags reach storage
+    Prefix(TagName, TargetValue),
+}
+
+// Numeric comparison values are rendered as bare JSON numbers
+fn _number_to_string(value: &TargetValue) -> String {
+    match *value {
+        TargetValue::Unencrypted(ref s) => s.to_string(),
+        TargetValue::Encrypted(_) => value.to_string(),
+    }
 }
 
 impl string::ToString for Operator {
@@ -79,6 +94,12 @@ impl string::ToString for Operator {
             Operator::Lt(ref tag_name, ref tag_value) => format!(r#"{{{}:{{"$lt":{}}}}}"#, tag_name.to_string(), tag_value.to_string()),
             Operator::Lte(ref tag_name, ref tag_value) => format!(r#"{{{}:{{"$lte":{}}}}}"#, tag_name.to_string(), tag_value.to_string()),
             Operator::Like(ref tag_name, ref tag_value) => format!(r#"{{{}:{{"$like":{}}}}}"#, tag_name.to_string(), tag_value.to_string()),
+            Operator::Exist(ref tag_name) => format!(r#"{{{}:{{"$exist":true}}}}"#, tag_name.to_string()),
+            Operator::NumGt(ref tag_name, ref tag_value) => format!(r#"{{{}:{{"$gt":{}}}}}"#, tag_name.to_string(), _number_to_string(tag_value)),
+            Operator::NumGte(ref tag_name, ref tag_value) => format!(r#"{{{}:{{"$gte":{}}}}}"#, tag_name.to_string(), _number_to_string(tag_value)),
+            Operator::NumLt(ref tag_name, ref tag_value) => format!(r#"{{{}:{{"$lt":{}}}}}"#, tag_name.to_string(), _number_to_string(tag_value)),
+            Operator::NumLte(ref tag_name, ref tag_value) => format!(r#"{{{}:{{"$lte":{}}}}}"#, tag_name.to_string(), _number_to_string(tag_value)),
+            Operator::Prefix(ref tag_name, ref tag_value) => format!(r#"{{{}:{{"$prefix":{}}}}}"#, tag_name.to_string(), tag_value.to_string()),
             Operator::In(ref tag_name, ref tag_values) => {
                 format!(
                     r#"{{{}:{{"$in":[{}]}}}}"#,
@@ -12,9 +12,9 @@ extern crate serde;
 extern crate serde_derive;
 
 use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
-use std::io::BufReader;
+use std::io::{BufReader, Read, Write};
 use std::path::PathBuf;
 use std::rc::Rc;
 
@@ -22,19 +22,31 @@ use serde_json::Value as SValue;
 
 use indy_api_types::wallet::*;
 
-use indy_api_types::domain::wallet::{Config, Credentials, ExportConfig, Tags};
+use indy_api_types::domain::wallet::{Config, Credentials, ExportConfig, KeyDerivationMethod, Tags};
 use indy_api_types::errors::prelude::*;
 pub use crate::encryption::KeyDerivationData;
+pub use self::async_api::WalletServiceHandle;
+use indy_utils::crypto::base64;
 use indy_utils::crypto::chacha20poly1305_ietf;
 use indy_utils::crypto::chacha20poly1305_ietf::Key as MasterKey;
 
-use self::export_import::{export_continue, finish_import, preparse_file_to_import};
-use self::storage::{WalletStorage, WalletStorageType};
+pub use self::export_import::{export_version, ExportSink, ImportReport, ImportSource, EXPORT_VERSION_1, EXPORT_VERSION_2};
+use self::export_import::{export_continue, ExportFilter, finish_import, finish_import_delta, preparse_file_to_import};
+pub use self::key_wrap::KeyWrapProvider;
+#[cfg(any(test, feature = "file_key_wrap_provider"))]
+use self::key_wrap::FileKeyWrapProvider;
+use self::key_wrap::PluggedKeyWrapProvider;
+use self::storage::{StorageOperation, WalletStorage, WalletStorageType};
 use self::storage::default::SQLiteStorageType;
+use self::storage::inmem::InmemStorageType;
 use self::storage::plugged::PluggedStorageType;
-use self::wallet::{Keys, Wallet};
-use indy_api_types::{WalletHandle};
+use self::storage::read_only::ReadOnlyStorage;
+#[cfg(feature = "postgres_storage")]
+use self::storage::postgres::PostgresStorageType;
+use self::wallet::{Keys, KEYS_ROTATION_CHUNK_SIZE, PREFIX_INDEXES_REBUILD_CHUNK_SIZE, Wallet};
+use indy_api_types::{IndyHandle, WalletHandle};
 
+mod async_api;
 mod storage;
 mod encryption;
 mod query_encryption;
@@ -42,14 +54,49 @@ mod iterator;
 // TODO: Remove query language out of wallet module
 pub mod language;
 mod export_import;
+mod key_wrap;
 mod wallet;
 
+// Number of records copied by one storage batch during wallet migration
+const MIGRATE_BATCH_SIZE: usize = 100;
+
+// Separates the profile name from id of its storage in id of the opened wallet.
+// Separator is escaped in the storage id, so the first unescaped one always starts the profile name.
+const PROFILE_ID_SEPARATOR: &str = "#";
+
+/// Kind of wallet record change reported to subscribers (See WalletService::subscribe)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecordChange {
+    Add,
+    Update,
+    Delete,
+}
+
+impl RecordChange {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            RecordChange::Add => "add",
+            RecordChange::Update => "update",
+            RecordChange::Delete => "delete",
+        }
+    }
+}
+
+/// Receives type, id and kind of change of wallet record
+pub type RecordChangeListener = Box<dyn Fn(&str, &str, RecordChange)>;
+
 pub struct WalletService {
     storage_types: RefCell<HashMap<String, Box<dyn WalletStorageType>>>,
+    key_wrap_providers: RefCell<HashMap<String, Box<dyn KeyWrapProvider>>>,
     wallets: RefCell<HashMap<WalletHandle, Box<Wallet>>>,
+    // ids of wallets opened in read-write mode, wallet can be opened in read-only mode by several handles
     wallet_ids: RefCell<HashSet<String>>,
-    pending_for_open: RefCell<HashMap<WalletHandle, (String /* id */, Box<dyn WalletStorage>, Metadata, Option<KeyDerivationData>)>>,
-    pending_for_import: RefCell<HashMap<WalletHandle, (BufReader<::std::fs::File>, chacha20poly1305_ietf::Nonce, usize, Vec<u8>, KeyDerivationData)>>,
+    pending_for_open: RefCell<HashMap<WalletHandle, (String /* id */, Box<dyn WalletStorage>, Metadata, Option<KeyDerivationData>, bool /* read_only */, HashMap<String, usize> /* prefix_indexes */)>>,
+    pending_for_import: RefCell<HashMap<WalletHandle, (BufReader<Box<dyn Read>>, chacha20poly1305_ietf::Nonce, usize, Vec<u8>, KeyDerivationData, bool)>>,
+    pending_for_delta_import: RefCell<HashMap<WalletHandle, (BufReader<Box<dyn Read>>, chacha20poly1305_ietf::Nonce, usize, Vec<u8>)>>,
+    pending_for_migrate: RefCell<HashMap<WalletHandle, (Box<dyn WalletStorage> /* source storage */, Metadata /* source metadata */, KeyDerivationData)>>,
+    subscriptions: RefCell<HashMap<IndyHandle, (WalletHandle, String /* wallet id */, Rc<dyn Fn(&str, &str, RecordChange)>)>>,
+    batches: RefCell<HashMap<IndyHandle, (WalletHandle, Vec<WalletOperation>)>>,
 }
 
 impl WalletService {
@@ -57,15 +104,31 @@ impl WalletService {
         let storage_types = {
             let mut map: HashMap<String, Box<dyn WalletStorageType>> = HashMap::new();
             map.insert("default".to_string(), Box::new(SQLiteStorageType::new()));
+            map.insert("indy_inmem".to_string(), Box::new(InmemStorageType::new()));
+            #[cfg(feature = "postgres_storage")]
+            map.insert("postgres".to_string(), Box::new(PostgresStorageType::new()));
+            RefCell::new(map)
+        };
+
+        let key_wrap_providers = {
+            #[allow(unused_mut)]
+            let mut map: HashMap<String, Box<dyn KeyWrapProvider>> = HashMap::new();
+            #[cfg(any(test, feature = "file_key_wrap_provider"))]
+            map.insert("file".to_string(), Box::new(FileKeyWrapProvider::new()));
             RefCell::new(map)
         };
 
         WalletService {
             storage_types,
+            key_wrap_providers,
             wallets: RefCell::new(HashMap::new()),
             wallet_ids: RefCell::new(HashSet::new()),
             pending_for_open: RefCell::new(HashMap::new()),
             pending_for_import: RefCell::new(HashMap::new()),
+            pending_for_delta_import: RefCell::new(HashMap::new()),
+            pending_for_migrate: RefCell::new(HashMap::new()),
+            subscriptions: RefCell::new(HashMap::new()),
+            batches: RefCell::new(HashMap::new()),
         }
     }
 
@@ -119,6 +182,51 @@ impl WalletService {
         Ok(())
     }
 
+    pub fn register_key_wrap_provider(&self,
+                                      name: &str,
+                                      wrap_key: WalletKeyWrap,
+                                      unwrap_key: WalletKeyUnwrap,
+                                      free_key: WalletKeyFree) -> IndyResult<()> {
+        trace!("register_key_wrap_provider >>> name: {:?}", name);
+
+        let mut key_wrap_providers = self.key_wrap_providers.borrow_mut();
+
+        if key_wrap_providers.contains_key(name) {
+            return Err(err_msg(IndyErrorKind::InvalidState, format!("Key wrap provider is already registered with name: {}", name)));
+        }
+
+        key_wrap_providers.insert(name.to_string(),
+                                  Box::new(PluggedKeyWrapProvider::new(wrap_key, unwrap_key, free_key)));
+
+        trace!("register_key_wrap_provider <<<");
+        Ok(())
+    }
+
+    ///
+    /// Builds key derivation data for a new master key.
+    /// For WRAPPED method random master key is generated and wrapped by the key wrap provider,
+    /// `key` is used as id of the wrapping key.
+    ///
+    pub fn new_key_derivation_data(&self, key: &str, method: &KeyDerivationMethod, key_wrap_provider: Option<&String>) -> IndyResult<KeyDerivationData> {
+        match method {
+            KeyDerivationMethod::WRAPPED => {
+                let provider_name = key_wrap_provider
+                    .ok_or_else(|| err_msg(IndyErrorKind::InvalidStructure, "WRAPPED key derivation method requires key wrap provider"))?;
+
+                let key_wrap_providers = self.key_wrap_providers.borrow();
+
+                let provider = key_wrap_providers.get(provider_name)
+                    .ok_or_else(|| err_msg(IndyErrorKind::InvalidStructure, format!("Unknown key wrap provider: {}", provider_name)))?;
+
+                let master_key = chacha20poly1305_ietf::gen_key();
+                let wrapped_master_key = provider.wrap_key(key, &master_key[..])?;
+
+                Ok(KeyDerivationData::Wrapped(provider_name.to_string(), wrapped_master_key, master_key))
+            }
+            _ => KeyDerivationData::from_passphrase_with_new_salt(key, method)
+        }
+    }
+
     pub fn create_wallet(&self,
                          config: &Config,
                          credentials: &Credentials,
@@ -132,12 +240,26 @@ impl WalletService {
                       (key_data, master_key): (&KeyDerivationData, &MasterKey)) -> IndyResult<Keys> {
         trace!("create_wallet >>> config: {:?}, credentials: {:?}", config, secret!(credentials));
 
+        let keys = Keys::new();
+        let metadata = self._prepare_metadata(master_key, key_data, &keys, &config.prefix_indexes)?;
+
+        self._create_storage(config, credentials, &metadata)?;
+
+        Ok(keys)
+    }
+
+    fn _create_storage(&self, config: &Config, credentials: &Credentials, metadata: &[u8]) -> IndyResult<()> {
         let storage_types = self.storage_types.borrow();
 
         let (storage_type, storage_config, storage_credentials) = WalletService::_get_config_and_cred_for_storage(config, credentials, &storage_types)?;
 
-        let keys = Keys::new();
-        let metadata = self._prepare_metadata(master_key, key_data, &keys)?;
+        if let Some(ref profile) = config.profile {
+            return storage_type.create_profile(&config.id,
+                                               storage_config.as_ref().map(String::as_str),
+                                               storage_credentials.as_ref().map(String::as_str),
+                                               profile,
+                                               metadata);
+        }
 
         storage_type.create_storage(&config.id,
                                     storage_config
@@ -146,16 +268,20 @@ impl WalletService {
                                     storage_credentials
                                         .as_ref()
                                         .map(String::as_str),
-                                    &metadata)?;
-
-        Ok(keys)
+                                    metadata)
     }
 
     pub fn delete_wallet_prepare(&self, config: &Config, credentials: &Credentials) -> IndyResult<(Metadata, KeyDerivationData)> {
         trace!("delete_wallet >>> config: {:?}, credentials: {:?}", config, secret!(credentials));
 
-        if self.wallet_ids.borrow_mut().contains(&WalletService::_get_wallet_id(config)) {
-            return Err(err_msg(IndyErrorKind::InvalidState, format!("Wallet has to be closed before deleting: {:?}", WalletService::_get_wallet_id(config))));
+        let id = WalletService::_get_wallet_id(config);
+
+        // storage can't be deleted while any of its profiles is opened
+        let profiles_prefix = format!("{}{}", id, PROFILE_ID_SEPARATOR);
+        let in_use = |wallet_id: &str| wallet_id == id || (config.profile.is_none() && wallet_id.starts_with(&profiles_prefix));
+
+        if self.wallet_ids.borrow().iter().any(|wallet_id| in_use(wallet_id)) || self.wallets.borrow().values().any(|wallet| in_use(wallet.get_id())) {
+            return Err(err_msg(IndyErrorKind::InvalidState, format!("Wallet has to be closed before deleting: {:?}", id)));
         }
 
         // check credentials and close connection before deleting wallet
@@ -173,63 +299,283 @@ impl WalletService {
             self._restore_keys(metadata, &master_key)?;
         }
 
+        self._delete_storage(config, credentials)?;
+
+        trace!("delete_wallet <<<");
+        Ok(())
+    }
+
+    fn _delete_storage(&self, config: &Config, credentials: &Credentials) -> IndyResult<()> {
         let storage_types = self.storage_types.borrow();
 
         let (storage_type, storage_config, storage_credentials) = WalletService::_get_config_and_cred_for_storage(config, credentials, &storage_types)?;
 
+        if let Some(ref profile) = config.profile {
+            return storage_type.delete_profile(&config.id,
+                                               storage_config.as_ref().map(String::as_str),
+                                               storage_credentials.as_ref().map(String::as_str),
+                                               profile);
+        }
+
         storage_type.delete_storage(&config.id,
                                     storage_config
                                         .as_ref()
                                         .map(String::as_str),
                                     storage_credentials
                                         .as_ref()
-                                        .map(String::as_str))?;
+                                        .map(String::as_str))
+    }
 
-        trace!("delete_wallet <<<");
-        Ok(())
+    pub fn list_profiles_prepare(&self, config: &Config, credentials: &Credentials) -> IndyResult<(Metadata, KeyDerivationData)> {
+        trace!("list_profiles >>> config: {:?}, credentials: {:?}", config, secret!(credentials));
+
+        if config.profile.is_some() {
+            return Err(err_msg(IndyErrorKind::InvalidStructure, "Profiles are listed with config of the default profile of the storage"));
+        }
+
+        // only owner of the default profile can list profiles of the storage
+        let (_, metadata, key_derivation_data) = self._open_storage_and_fetch_metadata(config, credentials)?;
+
+        Ok((metadata, key_derivation_data))
+    }
+
+    pub fn list_profiles_continue(&self, config: &Config, credentials: &Credentials, metadata: &Metadata, master_key: &MasterKey) -> IndyResult<Vec<String>> {
+        self._restore_keys(metadata, master_key)?;
+
+        let storage_types = self.storage_types.borrow();
+
+        let (storage_type, storage_config, storage_credentials) = WalletService::_get_config_and_cred_for_storage(config, credentials, &storage_types)?;
+
+        let profiles = storage_type.list_profiles(&config.id,
+                                                  storage_config.as_ref().map(String::as_str),
+                                                  storage_credentials.as_ref().map(String::as_str))?;
+
+        trace!("list_profiles <<< profiles: {:?}", profiles);
+        Ok(profiles)
     }
 
     pub fn open_wallet_prepare(&self, config: &Config, credentials: &Credentials) -> IndyResult<(WalletHandle, KeyDerivationData, Option<KeyDerivationData>)> {
         trace!("open_wallet >>> config: {:?}, credentials: {:?}", config, secret!(&credentials));
 
-        self._is_id_from_config_not_used(config)?;
+        self._check_open_mode(config, credentials)?;
 
         let (storage, metadata, key_derivation_data) = self._open_storage_and_fetch_metadata(config, credentials)?;
 
+        WalletService::_check_metadata_for_open(config, &metadata)?;
+
         let wallet_handle = indy_utils::next_wallet_handle();
 
-        let rekey_data: Option<KeyDerivationData> = credentials.rekey.as_ref().map(|ref rekey|
-            KeyDerivationData::from_passphrase_with_new_salt(rekey, &credentials.rekey_derivation_method));
+        let rekey_data: Option<KeyDerivationData> = match credentials.rekey {
+            Some(ref rekey) => Some(self.new_key_derivation_data(rekey, &credentials.rekey_derivation_method, credentials.key_wrap_provider.as_ref())?),
+            None => None
+        };
 
-        self.pending_for_open.borrow_mut().insert(wallet_handle, (WalletService::_get_wallet_id(config), storage, metadata, rekey_data.clone()));
+        self.pending_for_open.borrow_mut().insert(wallet_handle, (WalletService::_get_wallet_id(config), storage, metadata, rekey_data.clone(), config.read_only, config.prefix_indexes.clone()));
 
         Ok((wallet_handle, key_derivation_data, rekey_data))
     }
 
+    fn _check_metadata_for_open(config: &Config, metadata: &Metadata) -> IndyResult<()> {
+        // records re-encrypted by interrupted rotation of item keys can't be read with the current keys
+        if config.read_only && metadata.get_keys_rotation().is_some() {
+            return Err(err_msg(IndyErrorKind::WalletReadOnly, "Rotation of item keys has to be finished by opening the wallet in read-write mode"));
+        }
+
+        // records indexed for other prefix indexes config would be missed by prefix queries
+        if config.read_only && WalletService::_get_prefix_indexes(&config.prefix_indexes, metadata).1 {
+            return Err(err_msg(IndyErrorKind::WalletReadOnly, "Prefix indexes of the wallet can be changed only by opening the wallet in read-write mode"));
+        }
+
+        Ok(())
+    }
+
+    // Prefix indexes config stored in metadata is used if config of opening doesn't set it.
+    // Returns config to use and whether indexes of all records have to be rebuilt for it.
+    fn _get_prefix_indexes(prefix_indexes: &HashMap<String, usize>, metadata: &Metadata) -> (HashMap<String, usize>, bool) {
+        let res = if !prefix_indexes.is_empty() {
+            prefix_indexes.clone()
+        } else if let Some(pending) = metadata.get_pending_prefix_indexes() {
+            pending.clone()
+        } else {
+            metadata.get_prefix_indexes().clone()
+        };
+
+        let rebuild = metadata.get_pending_prefix_indexes().is_some() || res != *metadata.get_prefix_indexes();
+
+        (res, rebuild)
+    }
+
     pub fn open_wallet_continue(&self, wallet_handle: WalletHandle, master_key: (&MasterKey, Option<&MasterKey>)) -> IndyResult<WalletHandle> {
-        let (id, storage, metadata, rekey_data) = self.pending_for_open.borrow_mut().remove(&wallet_handle)
+        let (id, storage, metadata, rekey_data, read_only, prefix_indexes) = self.pending_for_open.borrow_mut().remove(&wallet_handle)
             .ok_or_else(|| err_msg(IndyErrorKind::InvalidState, "Open data not found"))?;
 
         let (master_key, rekey) = master_key;
         let keys = self._restore_keys(&metadata, &master_key)?;
 
+        let mut wallet = if read_only {
+            Wallet::new_read_only(id.clone(), storage, Rc::new(keys))
+        } else {
+            Wallet::new(id.clone(), storage, Rc::new(keys))
+        };
+        let (prefix_indexes, rebuild_prefix_indexes) = WalletService::_get_prefix_indexes(&prefix_indexes, &metadata);
+        wallet.set_prefix_indexes(prefix_indexes);
+
+        // Finish rotation of item keys interrupted before the wallet was closed
+        if let Some(keys_rotation) = metadata.get_keys_rotation().cloned() {
+            let new_keys = Keys::deserialize_encrypted(&keys_rotation.keys, master_key)
+                .map_err(|err| err.map(IndyErrorKind::InvalidState, "Invalid keys of item keys rotation"))?;
+            WalletService::_rotate_item_keys(&mut wallet, metadata, new_keys)?;
+        }
+
+        // Rebuild prefix indexes of all records for changed or interrupted prefix indexes config
+        if rebuild_prefix_indexes {
+            WalletService::_rebuild_prefix_indexes(&wallet)?;
+        }
+
         // Rotate master key
         if let (Some(rekey), Some(rekey_data)) = (rekey, rekey_data) {
-            let metadata = self._prepare_metadata(rekey, &rekey_data, &keys)?;
-            storage.set_storage_metadata(&metadata)?;
+            let metadata = self._prepare_metadata(rekey, &rekey_data, wallet.get_keys(), wallet.get_prefix_indexes())?;
+            wallet.set_storage_metadata(&metadata)?;
         }
 
-        let wallet = Wallet::new(id.clone(), storage, Rc::new(keys));
-
         let mut wallets = self.wallets.borrow_mut();
         wallets.insert(wallet_handle, Box::new(wallet));
-        let mut wallet_ids = self.wallet_ids.borrow_mut();
-        wallet_ids.insert(id.to_string());
+
+        if !read_only {
+            let mut wallet_ids = self.wallet_ids.borrow_mut();
+            wallet_ids.insert(id.to_string());
+        }
 
         trace!("open_wallet <<< res: {:?}", wallet_handle);
         Ok(wallet_handle)
     }
 
+    pub fn rotate_item_keys_prepare(&self, wallet_handle: WalletHandle, credentials: &Credentials) -> IndyResult<KeyDerivationData> {
+        trace!("rotate_item_keys_prepare >>> wallet_handle: {:?}, credentials: {:?}", wallet_handle, secret!(credentials));
+
+        let metadata = match self.wallets.borrow().get(&wallet_handle) {
+            Some(wallet) => WalletService::_get_metadata(wallet)?,
+            None => return Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
+        };
+
+        let res = self._key_derivation_data_from_metadata(credentials, &metadata)?;
+
+        trace!("rotate_item_keys_prepare <<<");
+        Ok(res)
+    }
+
+    ///
+    /// Generates new item keys and re-encrypts all records of the wallet with them.
+    /// Progress is kept in storage metadata, so if the process is interrupted
+    /// the rotation is finished on the next opening of the wallet.
+    ///
+    pub fn rotate_item_keys_continue(&self, wallet_handle: WalletHandle, master_key: &MasterKey) -> IndyResult<()> {
+        trace!("rotate_item_keys_continue >>> wallet_handle: {:?}", wallet_handle);
+
+        let mut wallets = self.wallets.borrow_mut();
+        let wallet = wallets.get_mut(&wallet_handle)
+            .ok_or_else(|| err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))?;
+
+        let mut metadata = WalletService::_get_metadata(wallet)?;
+
+        // check that provided credentials correspond to the wallet
+        self._restore_keys(&metadata, master_key)?;
+
+        let new_keys = match metadata.get_keys_rotation() {
+            Some(keys_rotation) => Keys::deserialize_encrypted(&keys_rotation.keys, master_key)
+                .map_err(|err| err.map(IndyErrorKind::InvalidState, "Invalid keys of item keys rotation"))?,
+            None => Keys::new()
+        };
+
+        if metadata.get_keys_rotation().is_none() {
+            metadata.set_keys_rotation(Some(KeysRotation { keys: new_keys.serialize_encrypted(master_key)?, rotated: 0 }));
+            wallet.set_storage_metadata(&metadata.to_bytes()?)?;
+        }
+
+        WalletService::_rotate_item_keys(wallet, metadata, new_keys)?;
+
+        trace!("rotate_item_keys_continue <<<");
+        Ok(())
+    }
+
+    fn _rotate_item_keys(wallet: &mut Wallet, mut metadata: Metadata, new_keys: Keys) -> IndyResult<()> {
+        let mut rotated = metadata.get_keys_rotation().map(|keys_rotation| keys_rotation.rotated).unwrap_or(0);
+
+        // records changed during a pass could be missed by storage reads, so passes are repeated until nothing is left
+        loop {
+            let mut pending = wallet.get_keys_rotation_pending(&new_keys)?;
+            let mut found = false;
+
+            loop {
+                let records = pending.next_chunk(KEYS_ROTATION_CHUNK_SIZE)?;
+
+                if records.is_empty() {
+                    break;
+                }
+
+                found = true;
+                rotated += wallet.rotate_keys_chunk(&new_keys, &records)?;
+
+                let keys = metadata.get_keys_rotation()
+                    .map(|keys_rotation| keys_rotation.keys.clone())
+                    .ok_or_else(|| err_msg(IndyErrorKind::InvalidState, "Item keys rotation is not started"))?;
+                metadata.set_keys_rotation(Some(KeysRotation { keys, rotated }));
+                wallet.set_storage_metadata(&metadata.to_bytes()?)?;
+
+                debug!("_rotate_item_keys: {} records re-encrypted", rotated);
+            }
+
+            if !found {
+                break;
+            }
+        }
+
+        let deleted = wallet.rotate_deleted_keys(&new_keys)?;
+        debug!("_rotate_item_keys: {} deletion marks re-encrypted", deleted);
+
+        metadata.finish_keys_rotation();
+        wallet.set_storage_metadata(&metadata.to_bytes()?)?;
+        wallet.set_keys(new_keys);
+
+        Ok(())
+    }
+
+    ///
+    /// Rebuilds prefix indexes of all records for prefix indexes config of the wallet.
+    /// The config is kept in storage metadata as pending until all records are reindexed,
+    /// so if the process is interrupted the rebuild is repeated on the next opening of the wallet.
+    ///
+    fn _rebuild_prefix_indexes(wallet: &Wallet) -> IndyResult<()> {
+        let mut metadata = WalletService::_get_metadata(wallet)?;
+
+        metadata.set_pending_prefix_indexes(Some(wallet.get_prefix_indexes().clone()));
+        wallet.set_storage_metadata(&metadata.to_bytes()?)?;
+
+        let mut pending = wallet.get_prefix_indexes_rebuild_pending()?;
+        let mut rebuilt = 0;
+
+        loop {
+            let records = pending.next_chunk(PREFIX_INDEXES_REBUILD_CHUNK_SIZE)?;
+
+            if records.is_empty() {
+                break;
+            }
+
+            rebuilt += wallet.rebuild_prefix_indexes_chunk(&records)?;
+            debug!("_rebuild_prefix_indexes: {} records reindexed", rebuilt);
+        }
+
+        metadata.finish_prefix_indexes_rebuild();
+        wallet.set_storage_metadata(&metadata.to_bytes()?)?;
+
+        Ok(())
+    }
+
+    fn _get_metadata(wallet: &Wallet) -> IndyResult<Metadata> {
+        serde_json::from_slice(&wallet.get_storage_metadata()?)
+            .to_indy(IndyErrorKind::InvalidState, "Cannot deserialize metadata")
+    }
+
     fn _open_storage_and_fetch_metadata(&self, config: &Config, credentials: &Credentials) -> IndyResult<(Box<dyn WalletStorage>, Metadata, KeyDerivationData)> {
         let storage = self._open_storage(config, credentials)?;
         let metadata: Metadata = {
@@ -237,21 +583,51 @@ impl WalletService {
             serde_json::from_slice(&metadata)
                 .to_indy(IndyErrorKind::InvalidState, "Cannot deserialize metadata")?
         };
-        let key_derivation_data = KeyDerivationData::from_passphrase_and_metadata(&credentials.key, &metadata, &credentials.key_derivation_method)?;
+        let key_derivation_data = self._key_derivation_data_from_metadata(credentials, &metadata)?;
         Ok((storage, metadata, key_derivation_data))
     }
 
+    fn _key_derivation_data_from_metadata(&self, credentials: &Credentials, metadata: &Metadata) -> IndyResult<KeyDerivationData> {
+        match (&credentials.key_derivation_method, metadata) {
+            (KeyDerivationMethod::WRAPPED, Metadata::MetadataWrapped(ref metadata)) => {
+                if let Some(ref provider_name) = credentials.key_wrap_provider {
+                    if *provider_name != metadata.key_wrap_provider {
+                        return Err(err_msg(IndyErrorKind::WalletAccessFailed, "Wallet master key is wrapped by other key wrap provider"));
+                    }
+                }
+
+                let key_wrap_providers = self.key_wrap_providers.borrow();
+
+                let provider = key_wrap_providers.get(&metadata.key_wrap_provider)
+                    .ok_or_else(|| err_msg(IndyErrorKind::WalletAccessFailed, format!("Unknown key wrap provider: {}", metadata.key_wrap_provider)))?;
+
+                let master_key = provider.unwrap_key(&credentials.key, &metadata.wrapped_master_key)?;
+
+                let master_key = MasterKey::from_slice(&master_key)
+                    .map_err(|err| err.map(IndyErrorKind::WalletAccessFailed, "Invalid master key unwrapped"))?;
+
+                Ok(KeyDerivationData::Wrapped(metadata.key_wrap_provider.clone(), metadata.wrapped_master_key.clone(), master_key))
+            }
+            _ => KeyDerivationData::from_passphrase_and_metadata(&credentials.key, metadata, &credentials.key_derivation_method)
+        }
+    }
+
     pub fn close_wallet(&self, handle: WalletHandle) -> IndyResult<()> {
         trace!("close_wallet >>> handle: {:?}", handle);
 
         match self.wallets.borrow_mut().remove(&handle) {
             Some(mut wallet) => {
-                self.wallet_ids.borrow_mut().remove(wallet.get_id());
+                if !wallet.is_read_only() {
+                    self.wallet_ids.borrow_mut().remove(wallet.get_id());
+                }
                 wallet.close()
             },
             None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
         }?;
 
+        self.subscriptions.borrow_mut().retain(|_, (subscribed_handle, _, _)| *subscribed_handle != handle);
+        self.batches.borrow_mut().retain(|_, (batch_wallet_handle, _)| *batch_wallet_handle != handle);
+
         trace!("close_wallet <<<");
         Ok(())
     }
@@ -264,17 +640,25 @@ impl WalletService {
         }
     }
 
-    pub fn add_record(&self, wallet_handle: WalletHandle, type_: &str, name: &str, value: &str, tags: &Tags) -> IndyResult<()> {
+    ///
+    /// Adds the record to the wallet. If `expires_at` (unix timestamp in seconds) is set
+    /// the record is hidden from reading and searching after this time and is removed
+    /// by the next `purge_expired_records` call.
+    ///
+    pub fn add_record(&self, wallet_handle: WalletHandle, type_: &str, name: &str, value: &str, tags: &Tags, expires_at: Option<u64>) -> IndyResult<()> {
         match self.wallets.borrow_mut().get_mut(&wallet_handle) {
-            Some(wallet) => wallet.add(type_, name, value, tags)
+            Some(wallet) => wallet.add(type_, name, value, tags, expires_at)
                 .map_err(|err| WalletService::_map_wallet_storage_error(err, type_, name)),
             None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
-        }
+        }?;
+
+        self._notify(wallet_handle, type_, name, RecordChange::Add);
+        Ok(())
     }
 
     pub fn add_indy_record<T>(&self, wallet_handle: WalletHandle, name: &str, value: &str, tags: &Tags)
                               -> IndyResult<()> where T: Sized {
-        self.add_record(wallet_handle, &self.add_prefix(short_type_name::<T>()), name, value,tags)
+        self.add_record(wallet_handle, &self.add_prefix(short_type_name::<T>()), name, value, tags, None)
     }
 
     pub fn add_indy_object<T>(&self, wallet_handle: WalletHandle, name: &str, object: &T, tags: &Tags)
@@ -292,20 +676,26 @@ impl WalletService {
                 wallet.update(type_, name, value)
                     .map_err(|err| WalletService::_map_wallet_storage_error(err, type_, name)),
             None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
-        }
+        }?;
+
+        self._notify(wallet_handle, type_, name, RecordChange::Update);
+        Ok(())
     }
 
     pub fn update_indy_object<T>(&self, wallet_handle: WalletHandle, name: &str, object: &T) -> IndyResult<String> where T: ::serde::Serialize + Sized {
-        let type_ = short_type_name::<T>();
-        match self.wallets.borrow().get(&wallet_handle) {
+        let type_ = self.add_prefix(short_type_name::<T>());
+        let object_json = match self.wallets.borrow().get(&wallet_handle) {
             Some(wallet) => {
                 let object_json = serde_json::to_string(object)
                     .to_indy(IndyErrorKind::InvalidState, format!("Cannot serialize {:?}", type_))?;
-                wallet.update(&self.add_prefix(type_), name, &object_json)?;
+                wallet.update(&type_, name, &object_json)?;
                 Ok(object_json)
             }
             None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
-        }
+        }?;
+
+        self._notify(wallet_handle, &type_, name, RecordChange::Update);
+        Ok(object_json)
     }
 
     pub fn add_record_tags(&self, wallet_handle: WalletHandle, type_: &str, name: &str, tags: &Tags) -> IndyResult<()> {
@@ -313,7 +703,10 @@ impl WalletService {
             Some(wallet) => wallet.add_tags(type_, name, tags)
                 .map_err(|err| WalletService::_map_wallet_storage_error(err, type_, name)),
             None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
-        }
+        }?;
+
+        self._notify(wallet_handle, type_, name, RecordChange::Update);
+        Ok(())
     }
 
     pub fn update_record_tags(&self, wallet_handle: WalletHandle, type_: &str, name: &str, tags: &Tags) -> IndyResult<()> {
@@ -321,7 +714,10 @@ impl WalletService {
             Some(wallet) => wallet.update_tags(type_, name, tags)
                 .map_err(|err| WalletService::_map_wallet_storage_error(err, type_, name)),
             None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
-        }
+        }?;
+
+        self._notify(wallet_handle, type_, name, RecordChange::Update);
+        Ok(())
     }
 
     pub fn delete_record_tags(&self, wallet_handle: WalletHandle, type_: &str, name: &str, tag_names: &[&str]) -> IndyResult<()> {
@@ -329,7 +725,10 @@ impl WalletService {
             Some(wallet) => wallet.delete_tags(type_, name, tag_names)
                 .map_err(|err| WalletService::_map_wallet_storage_error(err, type_, name)),
             None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
-        }
+        }?;
+
+        self._notify(wallet_handle, type_, name, RecordChange::Update);
+        Ok(())
     }
 
     pub fn delete_record(&self, wallet_handle: WalletHandle, type_: &str, name: &str) -> IndyResult<()> {
@@ -337,7 +736,10 @@ impl WalletService {
             Some(wallet) => wallet.delete(type_, name)
                 .map_err(|err| WalletService::_map_wallet_storage_error(err, type_, name)),
             None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
-        }
+        }?;
+
+        self._notify(wallet_handle, type_, name, RecordChange::Delete);
+        Ok(())
     }
 
     pub fn delete_indy_record<T>(&self, wallet_handle: WalletHandle, name: &str) -> IndyResult<()> where T: Sized {
@@ -399,6 +801,31 @@ impl WalletService {
         self.search_records(wallet_handle, &self.add_prefix(short_type_name::<T>()), query_json, options_json)
     }
 
+    ///
+    /// Counts records matching the query per value of the tag. Records without the tag are not counted.
+    ///
+    pub fn count_records_by_tag(&self, wallet_handle: WalletHandle, type_: &str, query_json: &str, tag_name: &str) -> IndyResult<BTreeMap<String, usize>> {
+        match self.wallets.borrow().get(&wallet_handle) {
+            Some(wallet) => wallet.count_by_tag(type_, query_json, tag_name),
+            None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
+        }
+    }
+
+    pub fn count_indy_records_by_tag<T>(&self, wallet_handle: WalletHandle, query_json: &str, tag_name: &str) -> IndyResult<BTreeMap<String, usize>> where T: Sized {
+        self.count_records_by_tag(wallet_handle, &self.add_prefix(short_type_name::<T>()), query_json, tag_name)
+    }
+
+    pub fn get_distinct_record_tag_values(&self, wallet_handle: WalletHandle, type_: &str, query_json: &str, tag_name: &str) -> IndyResult<Vec<String>> {
+        match self.wallets.borrow().get(&wallet_handle) {
+            Some(wallet) => wallet.get_distinct_tag_values(type_, query_json, tag_name),
+            None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
+        }
+    }
+
+    pub fn get_distinct_indy_record_tag_values<T>(&self, wallet_handle: WalletHandle, query_json: &str, tag_name: &str) -> IndyResult<Vec<String>> where T: Sized {
+        self.get_distinct_record_tag_values(wallet_handle, &self.add_prefix(short_type_name::<T>()), query_json, tag_name)
+    }
+
     #[allow(dead_code)] // TODO: Should we implement getting all records or delete everywhere?
     pub fn search_all_records(&self, _wallet_handle: WalletHandle) -> IndyResult<WalletSearch> {
         //        match self.wallets.borrow().get(&wallet_handle) {
@@ -408,6 +835,77 @@ impl WalletService {
         unimplemented!()
     }
 
+    ///
+    /// Applies all operations of the batch atomically: either every operation is stored
+    /// or, if any of them fails, none of them are and the wallet stays unchanged.
+    ///
+    pub fn batch_records(&self, wallet_handle: WalletHandle, operations: &[WalletOperation]) -> IndyResult<()> {
+        match self.wallets.borrow().get(&wallet_handle) {
+            Some(wallet) => wallet.batch(operations),
+            None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
+        }?;
+
+        self._notify_operations(wallet_handle, operations);
+
+        Ok(())
+    }
+
+    ///
+    /// Starts staged batch for the wallet. Operations staged with `stage_batch` are kept
+    /// in memory and nothing is written until `commit_batch` applies them as `batch_records` does.
+    /// Staged batch is dropped by `rollback_batch` or when the wallet handle is closed.
+    ///
+    pub fn begin_batch(&self, wallet_handle: WalletHandle) -> IndyResult<IndyHandle> {
+        trace!("begin_batch >>> wallet_handle: {:?}", wallet_handle);
+
+        self.check(wallet_handle)?;
+
+        let batch_handle = indy_utils::sequence::get_next_id();
+        self.batches.borrow_mut().insert(batch_handle, (wallet_handle, Vec::new()));
+
+        trace!("begin_batch <<< batch_handle: {:?}", batch_handle);
+        Ok(batch_handle)
+    }
+
+    pub fn stage_batch(&self, batch_handle: IndyHandle, operations: Vec<WalletOperation>) -> IndyResult<()> {
+        trace!("stage_batch >>> batch_handle: {:?}, operations: {:?}", batch_handle, operations.len());
+
+        match self.batches.borrow_mut().get_mut(&batch_handle) {
+            Some((_, staged)) => staged.extend(operations),
+            None => return Err(err_msg(IndyErrorKind::InvalidWalletHandle, format!("Unknown wallet batch handle: {:?}", batch_handle)))
+        }
+
+        trace!("stage_batch <<<");
+        Ok(())
+    }
+
+    ///
+    /// Applies staged operations atomically. Batch handle is released even if commit fails.
+    ///
+    pub fn commit_batch(&self, batch_handle: IndyHandle) -> IndyResult<()> {
+        trace!("commit_batch >>> batch_handle: {:?}", batch_handle);
+
+        let (wallet_handle, operations) = self.batches.borrow_mut().remove(&batch_handle)
+            .ok_or_else(|| err_msg(IndyErrorKind::InvalidWalletHandle, format!("Unknown wallet batch handle: {:?}", batch_handle)))?;
+
+        self.batch_records(wallet_handle, &operations)?;
+
+        trace!("commit_batch <<<");
+        Ok(())
+    }
+
+    pub fn rollback_batch(&self, batch_handle: IndyHandle) -> IndyResult<()> {
+        trace!("rollback_batch >>> batch_handle: {:?}", batch_handle);
+
+        match self.batches.borrow_mut().remove(&batch_handle) {
+            Some(_) => Ok(()),
+            None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, format!("Unknown wallet batch handle: {:?}", batch_handle)))
+        }?;
+
+        trace!("rollback_batch <<<");
+        Ok(())
+    }
+
     pub fn upsert_indy_object<T>(&self, wallet_handle: WalletHandle, name: &str, object: &T) -> IndyResult<String>
         where T: ::serde::Serialize + Sized {
         if self.record_exists::<T>(wallet_handle, name)? {
@@ -429,6 +927,70 @@ impl WalletService {
         }
     }
 
+    ///
+    /// Registers listener that is called after each record change made through any handle
+    /// of the same wallet. Subscription is dropped when the wallet handle is closed.
+    ///
+    pub fn subscribe(&self, wallet_handle: WalletHandle, listener: RecordChangeListener) -> IndyResult<IndyHandle> {
+        trace!("subscribe >>> wallet_handle: {:?}", wallet_handle);
+
+        let wallet_id = match self.wallets.borrow().get(&wallet_handle) {
+            Some(wallet) => wallet.get_id().to_string(),
+            None => return Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
+        };
+
+        let subscription_handle = indy_utils::sequence::get_next_id();
+        self.subscriptions.borrow_mut().insert(subscription_handle, (wallet_handle, wallet_id, Rc::from(listener)));
+
+        trace!("subscribe <<< subscription_handle: {:?}", subscription_handle);
+        Ok(subscription_handle)
+    }
+
+    pub fn unsubscribe(&self, subscription_handle: IndyHandle) -> IndyResult<()> {
+        trace!("unsubscribe >>> subscription_handle: {:?}", subscription_handle);
+
+        match self.subscriptions.borrow_mut().remove(&subscription_handle) {
+            Some(_) => Ok(()),
+            None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, format!("Unknown wallet subscription handle: {:?}", subscription_handle)))
+        }?;
+
+        trace!("unsubscribe <<<");
+        Ok(())
+    }
+
+    fn _notify(&self, wallet_handle: WalletHandle, type_: &str, name: &str, change: RecordChange) {
+        if self.subscriptions.borrow().is_empty() {
+            return;
+        }
+
+        let wallet_id = match self.wallets.borrow().get(&wallet_handle) {
+            Some(wallet) => wallet.get_id().to_string(),
+            None => return
+        };
+
+        // listeners are collected first, so they can subscribe and unsubscribe while being called
+        let listeners: Vec<Rc<dyn Fn(&str, &str, RecordChange)>> = self.subscriptions.borrow().values()
+            .filter(|(_, subscribed_wallet_id, _)| *subscribed_wallet_id == wallet_id)
+            .map(|(_, _, listener)| listener.clone())
+            .collect();
+
+        for listener in listeners {
+            listener(type_, name, change);
+        }
+    }
+
+    fn _notify_operations(&self, wallet_handle: WalletHandle, operations: &[WalletOperation]) {
+        for operation in operations {
+            let change = match *operation {
+                WalletOperation::Add { .. } => RecordChange::Add,
+                WalletOperation::Delete { .. } => RecordChange::Delete,
+                _ => RecordChange::Update,
+            };
+
+            self._notify(wallet_handle, operation.get_type(), operation.get_id(), change);
+        }
+    }
+
     pub fn check(&self, handle: WalletHandle) -> IndyResult<()> {
         match self.wallets.borrow().get(&handle) {
             Some(_) => Ok(()),
@@ -439,16 +1001,15 @@ impl WalletService {
     pub fn export_wallet(&self, wallet_handle: WalletHandle, export_config: &ExportConfig, version: u32, key: (&KeyDerivationData, &MasterKey)) -> IndyResult<()> {
         trace!("export_wallet >>> wallet_handle: {:?}, export_config: {:?}, version: {:?}", wallet_handle, secret!(export_config), version);
 
-        if version != 0 {
+        if version != EXPORT_VERSION_1 && version != EXPORT_VERSION_2 {
             return Err(err_msg(IndyErrorKind::InvalidState, "Unsupported version"));
         }
 
-        let (key_data, key) = key;
+        if export_config.path.is_empty() {
+            return Err(err_msg(IndyErrorKind::InvalidStructure, "Export path is empty"));
+        }
 
-        let wallets = self.wallets.borrow();
-        let wallet = wallets
-            .get(&wallet_handle)
-            .ok_or_else(|| err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))?;
+        self.check(wallet_handle)?;
 
         let path = PathBuf::from(&export_config.path);
 
@@ -464,38 +1025,81 @@ impl WalletService {
                 .create_new(true)
                 .open(export_config.path.clone())?;
 
-        let res = export_continue(wallet, &mut export_file, version, key.clone(), key_data);
+        let res = self.export_wallet_to_writer(wallet_handle, export_config, version, key, &mut export_file);
 
         trace!("export_wallet <<<");
 
         res
     }
 
+    pub fn export_wallet_to_writer(&self, wallet_handle: WalletHandle, export_config: &ExportConfig, version: u32, key: (&KeyDerivationData, &MasterKey), writer: &mut dyn Write) -> IndyResult<()> {
+        trace!("export_wallet_to_writer >>> wallet_handle: {:?}, export_config: {:?}, version: {:?}", wallet_handle, secret!(export_config), version);
+
+        if version != EXPORT_VERSION_1 && version != EXPORT_VERSION_2 {
+            return Err(err_msg(IndyErrorKind::InvalidState, "Unsupported version"));
+        }
+
+        let (key_data, key) = key;
+
+        let wallets = self.wallets.borrow();
+        let wallet = wallets
+            .get(&wallet_handle)
+            .ok_or_else(|| err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))?;
+
+        let query = export_config.query.as_ref().map(SValue::to_string);
+
+        let filter = ExportFilter {
+            types: export_config.types.as_ref().map(Vec::as_slice),
+            query: query.as_ref().map(String::as_str),
+            since_revision: export_config.since_revision,
+        };
+
+        let res = export_continue(wallet, writer, version, key.clone(), key_data, &filter);
+
+        trace!("export_wallet_to_writer <<<");
+
+        res
+    }
+
     pub fn import_wallet_prepare(&self,
                                  config: &Config,
                                  credentials: &Credentials,
                                  export_config: &ExportConfig) -> IndyResult<(WalletHandle, KeyDerivationData, KeyDerivationData)> {
         trace!("import_wallet_prepare >>> config: {:?}, credentials: {:?}, export_config: {:?}", config, secret!(export_config), secret!(export_config));
 
+        if export_config.path.is_empty() {
+            return Err(err_msg(IndyErrorKind::InvalidStructure, "Import path is empty"));
+        }
+
         let exported_file_to_import =
             fs::OpenOptions::new()
                 .read(true)
                 .open(&export_config.path)?;
 
-        let (reader, import_key_derivation_data, nonce, chunk_size, header_bytes) = preparse_file_to_import(exported_file_to_import, &export_config.key)?;
-        let key_data = KeyDerivationData::from_passphrase_with_new_salt(&credentials.key, &credentials.key_derivation_method);
+        self.import_wallet_prepare_from_reader(config, credentials, export_config, Box::new(exported_file_to_import))
+    }
+
+    pub fn import_wallet_prepare_from_reader(&self,
+                                             config: &Config,
+                                             credentials: &Credentials,
+                                             export_config: &ExportConfig,
+                                             reader: Box<dyn Read>) -> IndyResult<(WalletHandle, KeyDerivationData, KeyDerivationData)> {
+        trace!("import_wallet_prepare_from_reader >>> config: {:?}, credentials: {:?}, export_config: {:?}", config, secret!(export_config), secret!(export_config));
+
+        let (reader, import_key_derivation_data, nonce, chunk_size, header_bytes) = preparse_file_to_import(reader, &export_config.key)?;
+        let key_data = self.new_key_derivation_data(&credentials.key, &credentials.key_derivation_method, credentials.key_wrap_provider.as_ref())?;
 
         let wallet_handle = indy_utils::next_wallet_handle();
 
         let stashed_key_data = key_data.clone();
 
-        self.pending_for_import.borrow_mut().insert(wallet_handle, (reader, nonce, chunk_size, header_bytes, stashed_key_data));
+        self.pending_for_import.borrow_mut().insert(wallet_handle, (reader, nonce, chunk_size, header_bytes, stashed_key_data, export_config.tolerant));
 
         Ok((wallet_handle, key_data, import_key_derivation_data))
     }
 
-    pub fn import_wallet_continue(&self, wallet_handle: WalletHandle, config: &Config, credentials: &Credentials, key: (MasterKey, MasterKey)) -> IndyResult<()> {
-        let (reader, nonce, chunk_size, header_bytes, key_data) = self.pending_for_import.borrow_mut().remove(&wallet_handle).unwrap();
+    pub fn import_wallet_continue(&self, wallet_handle: WalletHandle, config: &Config, credentials: &Credentials, key: (MasterKey, MasterKey)) -> IndyResult<ImportReport> {
+        let (reader, nonce, chunk_size, header_bytes, key_data, tolerant) = self.pending_for_import.borrow_mut().remove(&wallet_handle).unwrap();
 
         let (import_key, master_key) = key;
 
@@ -505,10 +1109,12 @@ impl WalletService {
         let storage = self._open_storage(config, credentials)?;
         let metadata = storage.get_storage_metadata()?;
 
+        // wallet is created by the import, so there are no subscriptions to notify about imported records
         let res = {
-            let wallet = Wallet::new(WalletService::_get_wallet_id(&config), storage, Rc::new(keys));
+            let mut wallet = Wallet::new(WalletService::_get_wallet_id(&config), storage, Rc::new(keys));
+            wallet.set_prefix_indexes(config.prefix_indexes.clone());
 
-            finish_import(&wallet, reader, import_key, nonce, chunk_size, header_bytes)
+            finish_import(&wallet, reader, import_key, nonce, chunk_size, header_bytes, tolerant)
         };
 
         if res.is_err() {
@@ -524,6 +1130,226 @@ impl WalletService {
         res
     }
 
+    pub fn import_wallet_delta_prepare(&self, wallet_handle: WalletHandle, export_config: &ExportConfig) -> IndyResult<KeyDerivationData> {
+        trace!("import_wallet_delta_prepare >>> wallet_handle: {:?}, export_config: {:?}", wallet_handle, secret!(export_config));
+
+        if export_config.path.is_empty() {
+            return Err(err_msg(IndyErrorKind::InvalidStructure, "Import path is empty"));
+        }
+
+        self.check(wallet_handle)?;
+
+        let exported_file_to_import =
+            fs::OpenOptions::new()
+                .read(true)
+                .open(&export_config.path)?;
+
+        self.import_wallet_delta_prepare_from_reader(wallet_handle, export_config, Box::new(exported_file_to_import))
+    }
+
+    pub fn import_wallet_delta_prepare_from_reader(&self, wallet_handle: WalletHandle, export_config: &ExportConfig, reader: Box<dyn Read>) -> IndyResult<KeyDerivationData> {
+        trace!("import_wallet_delta_prepare_from_reader >>> wallet_handle: {:?}, export_config: {:?}", wallet_handle, secret!(export_config));
+
+        self.check(wallet_handle)?;
+
+        let (reader, import_key_derivation_data, nonce, chunk_size, header_bytes) = preparse_file_to_import(reader, &export_config.key)?;
+
+        self.pending_for_delta_import.borrow_mut().insert(wallet_handle, (reader, nonce, chunk_size, header_bytes));
+
+        Ok(import_key_derivation_data)
+    }
+
+    pub fn import_wallet_delta_continue(&self, wallet_handle: WalletHandle, import_key: &MasterKey) -> IndyResult<()> {
+        trace!("import_wallet_delta_continue >>> wallet_handle: {:?}", wallet_handle);
+
+        let (reader, nonce, chunk_size, header_bytes) = self.pending_for_delta_import.borrow_mut().remove(&wallet_handle)
+            .ok_or_else(|| err_msg(IndyErrorKind::InvalidState, "No pending delta import for wallet"))?;
+
+        let operations = match self.wallets.borrow().get(&wallet_handle) {
+            Some(wallet) => finish_import_delta(wallet, reader, import_key.clone(), nonce, chunk_size, header_bytes),
+            None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
+        }?;
+
+        self._notify_operations(wallet_handle, &operations);
+
+        trace!("import_wallet_delta_continue <<<");
+        Ok(())
+    }
+
+    pub fn migrate_wallet_prepare(&self,
+                                  src_config: &Config,
+                                  src_credentials: &Credentials,
+                                  dst_config: &Config,
+                                  dst_credentials: &Credentials) -> IndyResult<(WalletHandle, KeyDerivationData, KeyDerivationData)> {
+        trace!("migrate_wallet_prepare >>> src_config: {:?}, src_credentials: {:?}, dst_config: {:?}, dst_credentials: {:?}",
+               src_config, secret!(src_credentials), dst_config, secret!(dst_credentials));
+
+        if dst_config.read_only {
+            return Err(err_msg(IndyErrorKind::InvalidStructure, "Wallet can't be migrated to read-only wallet"));
+        }
+
+        if WalletService::_get_wallet_id(src_config) == WalletService::_get_wallet_id(dst_config)
+            && src_config.storage_type == dst_config.storage_type {
+            return Err(err_msg(IndyErrorKind::InvalidStructure, "Wallet can't be migrated to itself"));
+        }
+
+        // records changed during migration would be lost
+        self._is_id_from_config_not_used(src_config)?;
+        self._is_id_from_config_not_used(dst_config)?;
+
+        let src_config = Config { read_only: true, ..src_config.clone() };
+        let (src_storage, src_metadata, src_key_data) = self._open_storage_and_fetch_metadata(&src_config, src_credentials)?;
+
+        if src_metadata.get_keys_rotation().is_some() {
+            return Err(err_msg(IndyErrorKind::InvalidState, "Rotation of item keys has to be finished before migration of the wallet"));
+        }
+
+        if src_metadata.get_pending_prefix_indexes().is_some() {
+            return Err(err_msg(IndyErrorKind::InvalidState, "Rebuild of prefix indexes has to be finished before migration of the wallet"));
+        }
+
+        let key_data = self.new_key_derivation_data(&dst_credentials.key, &dst_credentials.key_derivation_method, dst_credentials.key_wrap_provider.as_ref())?;
+
+        let wallet_handle = indy_utils::next_wallet_handle();
+
+        self.pending_for_migrate.borrow_mut().insert(wallet_handle, (src_storage, src_metadata, key_data.clone()));
+
+        trace!("migrate_wallet_prepare <<<");
+        Ok((wallet_handle, src_key_data, key_data))
+    }
+
+    ///
+    /// Creates destination wallet and copies records of source wallet into it.
+    /// Item keys of source wallet are kept, so records are copied in encrypted form
+    /// and only item keys are encrypted with the new master key.
+    /// Destination wallet is deleted if number of copied records can't be verified.
+    ///
+    pub fn migrate_wallet_continue(&self, wallet_handle: WalletHandle, dst_config: &Config, dst_credentials: &Credentials, key: (MasterKey, MasterKey)) -> IndyResult<usize> {
+        trace!("migrate_wallet_continue >>> wallet_handle: {:?}, dst_config: {:?}", wallet_handle, dst_config);
+
+        let (src_storage, src_metadata, key_data) = self.pending_for_migrate.borrow_mut().remove(&wallet_handle)
+            .ok_or_else(|| err_msg(IndyErrorKind::InvalidState, "No pending migration for wallet"))?;
+
+        let (src_master_key, master_key) = key;
+
+        let keys = self._restore_keys(&src_metadata, &src_master_key)?;
+        let metadata = self._prepare_metadata(&master_key, &key_data, &keys, src_metadata.get_prefix_indexes())?;
+
+        self._create_storage(dst_config, dst_credentials, &metadata)?;
+
+        let res = self._open_storage(dst_config, dst_credentials)
+            .and_then(|storage| WalletService::_copy_records(src_storage.as_ref(), storage.as_ref()));
+
+        // the original error is returned, cleanup error is only logged
+        if res.is_err() {
+            if let Err(err) = self._delete_storage(dst_config, dst_credentials) {
+                warn!("Can't delete destination wallet of failed migration: {}", err);
+            }
+        }
+
+        trace!("migrate_wallet_continue <<< res: {:?}", res);
+        res
+    }
+
+    // Copies encrypted records as is and checks that destination contains all of them
+    fn _copy_records(src_storage: &dyn WalletStorage, dst_storage: &dyn WalletStorage) -> IndyResult<usize> {
+        let mut records = src_storage.get_all()?;
+
+        let mut operations = Vec::with_capacity(MIGRATE_BATCH_SIZE);
+        let mut pending = 0;
+        let mut copied = 0;
+
+        while let Some(record) = records.next()? {
+            let type_ = record.type_
+                .ok_or_else(|| err_msg(IndyErrorKind::InvalidState, "No type fetched for migrated record"))?;
+            let value = record.value
+                .ok_or_else(|| err_msg(IndyErrorKind::InvalidState, "No value fetched for migrated record"))?;
+
+            if let Some(expires_at) = record.expires_at {
+                operations.push(StorageOperation::Add(type_.clone(), record.id.clone(), value, record.tags.unwrap_or_else(Vec::new)));
+                operations.push(StorageOperation::SetExpiry(type_, record.id, Some(expires_at)));
+            } else {
+                operations.push(StorageOperation::Add(type_, record.id, value, record.tags.unwrap_or_else(Vec::new)));
+            }
+
+            pending += 1;
+
+            if pending == MIGRATE_BATCH_SIZE {
+                dst_storage.batch(&operations)?;
+                copied += pending;
+                pending = 0;
+                operations.clear();
+            }
+        }
+
+        if !operations.is_empty() {
+            dst_storage.batch(&operations)?;
+            copied += pending;
+        }
+
+        let mut migrated = 0;
+        let mut records = dst_storage.get_all()?;
+
+        while let Some(_) = records.next()? {
+            migrated += 1;
+        }
+
+        if migrated != copied {
+            return Err(err_msg(IndyErrorKind::InvalidState,
+                               format!("Number of migrated records {} doesn't match number of source records {}", migrated, copied)));
+        }
+
+        debug!("_copy_records: {} records migrated", migrated);
+
+        Ok(migrated)
+    }
+
+    ///
+    /// Deletes expired records of opened wallet and returns their count.
+    /// Subscribers aren't notified about purged records as they have been already
+    /// invisible since their expiry time.
+    ///
+    pub fn purge_expired_records(&self, wallet_handle: WalletHandle) -> IndyResult<usize> {
+        trace!("purge_expired_records >>> wallet_handle: {:?}", wallet_handle);
+
+        let res = match self.wallets.borrow().get(&wallet_handle) {
+            Some(wallet) => wallet.purge_expired()?,
+            None => return Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
+        };
+
+        trace!("purge_expired_records <<< res: {:?}", res);
+        Ok(res)
+    }
+
+    pub fn get_revision(&self, wallet_handle: WalletHandle) -> IndyResult<u64> {
+        match self.wallets.borrow().get(&wallet_handle) {
+            Some(wallet) => wallet.get_revision(),
+            None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
+        }
+    }
+
+    ///
+    /// Checks integrity of records of opened wallet. See `Wallet::check` for details.
+    ///
+    pub fn check_wallet(&self, wallet_handle: WalletHandle, repair: bool, validate_value: &dyn Fn(&str, &str) -> Result<(), String>) -> IndyResult<WalletCheckReport> {
+        trace!("check_wallet >>> wallet_handle: {:?}, repair: {:?}", wallet_handle, repair);
+
+        let res = match self.wallets.borrow().get(&wallet_handle) {
+            Some(wallet) => wallet.check(repair, validate_value)?,
+            None => return Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
+        };
+
+        // quarantined records are gone from the wallet, records with undecryptable type or id can't be reported
+        for problem in res.problems.iter().filter(|problem| problem.quarantined) {
+            if let (Some(type_), Some(id)) = (&problem.type_, &problem.id) {
+                self._notify(wallet_handle, type_, id, RecordChange::Delete);
+            }
+        }
+
+        trace!("check_wallet <<< res: {:?}", res);
+        Ok(res)
+    }
+
     pub fn get_wallets_count(&self) -> usize {
         self.wallets.borrow().len()
     }
@@ -567,28 +1393,73 @@ impl WalletService {
         Ok(())
     }
 
+    fn _check_open_mode(&self, config: &Config, credentials: &Credentials) -> IndyResult<()> {
+        if !config.read_only {
+            return self._is_id_from_config_not_used(config);
+        }
+
+        if credentials.rekey.is_some() {
+            return Err(err_msg(IndyErrorKind::WalletReadOnly, "Wallet opened in read-only mode can't be rekeyed"));
+        }
+
+        Ok(())
+    }
+
     fn _get_wallet_id(config: &Config) -> String {
         let wallet_path = config.storage_config.as_ref().and_then(|storage_config| storage_config["path"].as_str()).unwrap_or("");
-        let wallet_id = format!("{}{}", config.id, wallet_path);
-        wallet_id
+        let wallet_id = format!("{}{}", config.id, wallet_path)
+            .replace('\\', "\\\\")
+            .replace(PROFILE_ID_SEPARATOR, &format!("\\{}", PROFILE_ID_SEPARATOR));
+
+        match config.profile {
+            Some(ref profile) => format!("{}{}{}", wallet_id, PROFILE_ID_SEPARATOR, profile),
+            None => wallet_id
+        }
     }
 
     fn _open_storage(&self, config: &Config, credentials: &Credentials) -> IndyResult<Box<dyn WalletStorage>> {
         let storage_types = self.storage_types.borrow();
         let (storage_type, storage_config, storage_credentials) =
             WalletService::_get_config_and_cred_for_storage(config, credentials, &storage_types)?;
+
+        if let Some(ref profile) = config.profile {
+            let storage = storage_type.open_profile(&config.id,
+                                                    storage_config.as_ref().map(String::as_str),
+                                                    storage_credentials.as_ref().map(String::as_str),
+                                                    profile,
+                                                    config.read_only)?;
+
+            if config.read_only {
+                return Ok(Box::new(ReadOnlyStorage::new(storage)));
+            }
+
+            return Ok(storage);
+        }
+
+        if config.read_only {
+            let storage = storage_type.open_storage_read_only(&config.id,
+                                                              storage_config.as_ref().map(String::as_str),
+                                                              storage_credentials.as_ref().map(String::as_str))?;
+            return Ok(Box::new(ReadOnlyStorage::new(storage)));
+        }
+
         let storage = storage_type.open_storage(&config.id,
                                                 storage_config.as_ref().map(String::as_str),
                                                 storage_credentials.as_ref().map(String::as_str))?;
         Ok(storage)
     }
 
-    fn _prepare_metadata(&self, master_key: &chacha20poly1305_ietf::Key, key_data: &KeyDerivationData, keys: &Keys) -> IndyResult<Vec<u8>> {
+    fn _prepare_metadata(&self, master_key: &chacha20poly1305_ietf::Key, key_data: &KeyDerivationData, keys: &Keys, prefix_indexes: &HashMap<String, usize>) -> IndyResult<Vec<u8>> {
         let encrypted_keys = keys.serialize_encrypted(master_key)?;
         let metadata = match key_data {
             KeyDerivationData::Raw(_) => {
                 Metadata::MetadataRaw(
-                    MetadataRaw { keys: encrypted_keys }
+                    MetadataRaw {
+                        keys: encrypted_keys,
+                        keys_rotation: None,
+                        prefix_indexes: prefix_indexes.clone(),
+                        pending_prefix_indexes: None,
+                    }
                 )
             }
             KeyDerivationData::Argon2iInt(_, salt) | KeyDerivationData::Argon2iMod(_, salt) => {
@@ -596,6 +1467,21 @@ impl WalletService {
                     MetadataArgon {
                         keys: encrypted_keys,
                         master_key_salt: salt[..].to_vec(),
+                        keys_rotation: None,
+                        prefix_indexes: prefix_indexes.clone(),
+                        pending_prefix_indexes: None,
+                    }
+                )
+            }
+            KeyDerivationData::Wrapped(provider_name, wrapped_master_key, _) => {
+                Metadata::MetadataWrapped(
+                    MetadataWrapped {
+                        keys: encrypted_keys,
+                        key_wrap_provider: provider_name.clone(),
+                        wrapped_master_key: wrapped_master_key.clone(),
+                        keys_rotation: None,
+                        prefix_indexes: prefix_indexes.clone(),
+                        pending_prefix_indexes: None,
                     }
                 )
             }
@@ -621,12 +1507,18 @@ impl WalletService {
     pub fn add_prefix(&self, type_: &str) -> String {
         format!("{}::{}", WalletService::PREFIX, type_)
     }
+
+    pub fn indy_type<T>(&self) -> String where T: Sized {
+        self.add_prefix(short_type_name::<T>())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(untagged)]
 pub enum Metadata {
     MetadataArgon(MetadataArgon),
+    // must precede MetadataRaw as untagged MetadataRaw accepts any object with keys
+    MetadataWrapped(MetadataWrapped),
     MetadataRaw(MetadataRaw),
 }
 
@@ -634,20 +1526,166 @@ impl Metadata {
     pub fn get_keys(&self) -> &Vec<u8> {
         match *self {
             Metadata::MetadataArgon(ref metadata) => &metadata.keys,
+            Metadata::MetadataWrapped(ref metadata) => &metadata.keys,
             Metadata::MetadataRaw(ref metadata) => &metadata.keys,
         }
     }
+
+    pub fn get_keys_rotation(&self) -> Option<&KeysRotation> {
+        match *self {
+            Metadata::MetadataArgon(ref metadata) => metadata.keys_rotation.as_ref(),
+            Metadata::MetadataWrapped(ref metadata) => metadata.keys_rotation.as_ref(),
+            Metadata::MetadataRaw(ref metadata) => metadata.keys_rotation.as_ref(),
+        }
+    }
+
+    fn set_keys_rotation(&mut self, keys_rotation: Option<KeysRotation>) {
+        match *self {
+            Metadata::MetadataArgon(ref mut metadata) => metadata.keys_rotation = keys_rotation,
+            Metadata::MetadataWrapped(ref mut metadata) => metadata.keys_rotation = keys_rotation,
+            Metadata::MetadataRaw(ref mut metadata) => metadata.keys_rotation = keys_rotation,
+        }
+    }
+
+    fn finish_keys_rotation(&mut self) {
+        let (keys, keys_rotation) = match *self {
+            Metadata::MetadataArgon(ref mut metadata) => (&mut metadata.keys, &mut metadata.keys_rotation),
+            Metadata::MetadataWrapped(ref mut metadata) => (&mut metadata.keys, &mut metadata.keys_rotation),
+            Metadata::MetadataRaw(ref mut metadata) => (&mut metadata.keys, &mut metadata.keys_rotation),
+        };
+
+        if let Some(rotation) = keys_rotation.take() {
+            *keys = rotation.keys;
+        }
+    }
+
+    pub fn get_prefix_indexes(&self) -> &HashMap<String, usize> {
+        match *self {
+            Metadata::MetadataArgon(ref metadata) => &metadata.prefix_indexes,
+            Metadata::MetadataWrapped(ref metadata) => &metadata.prefix_indexes,
+            Metadata::MetadataRaw(ref metadata) => &metadata.prefix_indexes,
+        }
+    }
+
+    pub fn get_pending_prefix_indexes(&self) -> Option<&HashMap<String, usize>> {
+        match *self {
+            Metadata::MetadataArgon(ref metadata) => metadata.pending_prefix_indexes.as_ref(),
+            Metadata::MetadataWrapped(ref metadata) => metadata.pending_prefix_indexes.as_ref(),
+            Metadata::MetadataRaw(ref metadata) => metadata.pending_prefix_indexes.as_ref(),
+        }
+    }
+
+    fn set_pending_prefix_indexes(&mut self, pending_prefix_indexes: Option<HashMap<String, usize>>) {
+        match *self {
+            Metadata::MetadataArgon(ref mut metadata) => metadata.pending_prefix_indexes = pending_prefix_indexes,
+            Metadata::MetadataWrapped(ref mut metadata) => metadata.pending_prefix_indexes = pending_prefix_indexes,
+            Metadata::MetadataRaw(ref mut metadata) => metadata.pending_prefix_indexes = pending_prefix_indexes,
+        }
+    }
+
+    fn finish_prefix_indexes_rebuild(&mut self) {
+        let (prefix_indexes, pending_prefix_indexes) = match *self {
+            Metadata::MetadataArgon(ref mut metadata) => (&mut metadata.prefix_indexes, &mut metadata.pending_prefix_indexes),
+            Metadata::MetadataWrapped(ref mut metadata) => (&mut metadata.prefix_indexes, &mut metadata.pending_prefix_indexes),
+            Metadata::MetadataRaw(ref mut metadata) => (&mut metadata.prefix_indexes, &mut metadata.pending_prefix_indexes),
+        };
+
+        if let Some(pending) = pending_prefix_indexes.take() {
+            *prefix_indexes = pending;
+        }
+    }
+
+    fn to_bytes(&self) -> IndyResult<Vec<u8>> {
+        serde_json::to_vec(self)
+            .to_indy(IndyErrorKind::InvalidState, "Cannot serialize wallet metadata")
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct MetadataArgon {
     pub keys: Vec<u8>,
     pub master_key_salt: Vec<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keys_rotation: Option<KeysRotation>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub prefix_indexes: HashMap<String, usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending_prefix_indexes: Option<HashMap<String, usize>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MetadataWrapped {
+    pub keys: Vec<u8>,
+    pub key_wrap_provider: String,
+    pub wrapped_master_key: Vec<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keys_rotation: Option<KeysRotation>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub prefix_indexes: HashMap<String, usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending_prefix_indexes: Option<HashMap<String, usize>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct MetadataRaw {
-    pub keys: Vec<u8>
+    pub keys: Vec<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keys_rotation: Option<KeysRotation>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub prefix_indexes: HashMap<String, usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending_prefix_indexes: Option<HashMap<String, usize>>,
+}
+
+///
+/// State of item keys rotation that is in progress.
+/// Stays in metadata until all records are re-encrypted, so an interrupted rotation
+/// is continued on the next opening of the wallet.
+///
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KeysRotation {
+    /// New keys encrypted with the master key
+    pub keys: Vec<u8>,
+    /// Count of records already re-encrypted with the new keys
+    pub rotated: usize,
+}
+
+///
+/// Result of wallet integrity check.
+///
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct WalletCheckReport {
+    /// Count of checked records
+    pub records: usize,
+    /// Count of tags that reference missing records
+    pub orphan_tags: usize,
+    /// Records that failed the check
+    pub problems: Vec<WalletCheckProblem>,
+    /// Whether bad records were quarantined and orphan tags were removed
+    pub repaired: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WalletCheckProblem {
+    /// Record type, missed if it can't be decrypted
+    #[serde(rename = "type")]
+    pub type_: Option<String>,
+    /// Record id, missed if it can't be decrypted
+    pub id: Option<String>,
+    pub kind: WalletCheckProblemKind,
+    pub message: String,
+    /// Whether the record was moved out of the wallet by repair
+    pub quarantined: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WalletCheckProblemKind {
+    TypeDecryption,
+    IdDecryption,
+    ValueDecryption,
+    TagsDecryption,
+    InvalidValue,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -657,6 +1695,8 @@ pub struct WalletRecord {
     id: String,
     value: Option<String>,
     tags: Option<Tags>,
+    #[serde(skip)]
+    expires_at: Option<u64>,
 }
 
 impl Ord for WalletRecord {
@@ -678,9 +1718,15 @@ impl WalletRecord {
             type_,
             value,
             tags,
+            expires_at: None,
         }
     }
 
+    pub fn with_expiry(mut self, expires_at: Option<u64>) -> WalletRecord {
+        self.expires_at = expires_at;
+        self
+    }
+
     pub fn get_id(&self) -> &str {
         self.id.as_str()
     }
@@ -698,6 +1744,78 @@ impl WalletRecord {
     pub fn get_tags(&self) -> Option<&Tags> {
         self.tags.as_ref()
     }
+
+    pub fn get_expires_at(&self) -> Option<u64> {
+        self.expires_at
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum WalletOperation {
+    Add {
+        #[serde(rename = "type")]
+        type_: String,
+        id: String,
+        value: String,
+        tags: Option<Tags>,
+        #[serde(rename = "expiresAt", default, skip_serializing_if = "Option::is_none")]
+        expires_at: Option<u64>,
+    },
+    UpdateValue {
+        #[serde(rename = "type")]
+        type_: String,
+        id: String,
+        value: String,
+    },
+    AddTags {
+        #[serde(rename = "type")]
+        type_: String,
+        id: String,
+        tags: Tags,
+    },
+    UpdateTags {
+        #[serde(rename = "type")]
+        type_: String,
+        id: String,
+        tags: Tags,
+    },
+    DeleteTags {
+        #[serde(rename = "type")]
+        type_: String,
+        id: String,
+        #[serde(rename = "tagNames")]
+        tag_names: Vec<String>,
+    },
+    Delete {
+        #[serde(rename = "type")]
+        type_: String,
+        id: String,
+    },
+}
+
+impl WalletOperation {
+    pub fn get_type(&self) -> &str {
+        match *self {
+            WalletOperation::Add { ref type_, .. } |
+            WalletOperation::UpdateValue { ref type_, .. } |
+            WalletOperation::AddTags { ref type_, .. } |
+            WalletOperation::UpdateTags { ref type_, .. } |
+            WalletOperation::DeleteTags { ref type_, .. } |
+            WalletOperation::Delete { ref type_, .. } => type_,
+        }
+    }
+
+    pub fn get_id(&self) -> &str {
+        match *self {
+            WalletOperation::Add { ref id, .. } |
+            WalletOperation::UpdateValue { ref id, .. } |
+            WalletOperation::AddTags { ref id, .. } |
+            WalletOperation::UpdateTags { ref id, .. } |
+            WalletOperation::DeleteTags { ref id, .. } |
+            WalletOperation::Delete { ref id, .. } => id,
+        }
+    }
 }
 
 fn default_true() -> bool { true }
@@ -774,6 +1892,12 @@ pub struct SearchOptions {
     retrieve_value: bool,
     #[serde(default = "default_false")]
     retrieve_tags: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sort_by: Option<SortBy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    offset: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    limit: Option<usize>,
 }
 
 impl SearchOptions {
@@ -784,10 +1908,40 @@ impl SearchOptions {
             retrieve_type: true,
             retrieve_value: true,
             retrieve_tags: false,
+            sort_by: None,
+            offset: None,
+            limit: None,
         };
 
         serde_json::to_string(&options).unwrap()
     }
+
+    ///
+    /// Same as `id_value` but keeps paging from the given options json.
+    /// Sorting is rejected as records of indy types have encrypted tags only.
+    /// All the other fields of the given options are ignored.
+    ///
+    pub fn id_value_with_paging(options_json: Option<&str>) -> IndyResult<String> {
+        let order: SearchOptions = serde_json::from_str(options_json.unwrap_or("{}"))
+            .to_indy(IndyErrorKind::InvalidStructure, "Search options is malformed json")?;
+
+        if order.sort_by.is_some() {
+            return Err(err_msg(IndyErrorKind::InvalidStructure, "Sorting isn't supported for records with encrypted tags only"));
+        }
+
+        let options = SearchOptions {
+            retrieve_records: true,
+            retrieve_total_count: true,
+            retrieve_type: true,
+            retrieve_value: true,
+            retrieve_tags: false,
+            sort_by: None,
+            offset: order.offset,
+            limit: order.limit,
+        };
+
+        Ok(serde_json::to_string(&options).unwrap())
+    }
 }
 
 impl Default for SearchOptions {
@@ -798,7 +1952,53 @@ impl Default for SearchOptions {
             retrieve_type: false,
             retrieve_value: true,
             retrieve_tags: false,
+            sort_by: None,
+            offset: None,
+            limit: None,
+        }
+    }
+}
+
+///
+/// Ordering of search results by the value of an un-encrypted tag.
+/// Records without the tag always go after the ones that have it.
+///
+/// Above the storage layer `tag_name` is the plain tag name ("~tagName"),
+/// storages receive it encrypted in the same form as plain tag names of a query ("~<base64>").
+///
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SortBy {
+    tag_name: String,
+    #[serde(default)]
+    order: SortOrder,
+}
+
+impl SortBy {
+    pub(crate) fn encrypted_tag_name(&self) -> IndyResult<Vec<u8>> {
+        if !self.tag_name.starts_with('~') {
+            return Err(err_msg(IndyErrorKind::WalletQueryError, "Only un-encrypted tags can be used for sorting"));
         }
+
+        base64::decode(&self.tag_name[1..])
+            .to_indy(IndyErrorKind::InvalidStructure, "Unable to decode sort tag name from base64")
+    }
+
+    pub(crate) fn is_descending(&self) -> bool {
+        self.order == SortOrder::Desc
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl Default for SortOrder {
+    fn default() -> SortOrder {
+        SortOrder::Asc
     }
 }
 
@@ -825,6 +2025,7 @@ mod tests {
 
     use indy_api_types::domain::wallet::KeyDerivationMethod;
     use indy_utils::environment;
+    use indy_utils::future::block_on;
     use indy_utils::inmem_wallet::InmemWallet;
     use indy_utils::test;
     use indy_utils::next_wallet_handle;
@@ -833,16 +2034,20 @@ mod tests {
 
     impl WalletService {
         fn open_wallet(&self, config: &Config, credentials: &Credentials) -> IndyResult<WalletHandle> {
-            self._is_id_from_config_not_used(config)?;
+            self._check_open_mode(config, credentials)?;
 
             let (storage, metadata, key_derivation_data) = self._open_storage_and_fetch_metadata(config, credentials)?;
 
+            WalletService::_check_metadata_for_open(config, &metadata)?;
+
             let wallet_handle = next_wallet_handle();
 
-            let rekey_data: Option<KeyDerivationData> = credentials.rekey.as_ref().map(|ref rekey|
-                KeyDerivationData::from_passphrase_with_new_salt(rekey, &credentials.rekey_derivation_method));
+            let rekey_data: Option<KeyDerivationData> = match credentials.rekey {
+                Some(ref rekey) => Some(self.new_key_derivation_data(rekey, &credentials.rekey_derivation_method, credentials.key_wrap_provider.as_ref())?),
+                None => None
+            };
 
-            self.pending_for_open.borrow_mut().insert(wallet_handle, (WalletService::_get_wallet_id(config), storage, metadata, rekey_data.clone()));
+            self.pending_for_open.borrow_mut().insert(wallet_handle, (WalletService::_get_wallet_id(config), storage, metadata, rekey_data.clone(), config.read_only, config.prefix_indexes.clone()));
 
             let key = key_derivation_data.calc_master_key()?;
 
@@ -857,10 +2062,27 @@ mod tests {
             self.open_wallet_continue(wallet_handle, (&key, rekey.as_ref()))
         }
 
+        fn rotate_item_keys(&self, wallet_handle: WalletHandle, credentials: &Credentials) -> IndyResult<()> {
+            let key_derivation_data = self.rotate_item_keys_prepare(wallet_handle, credentials)?;
+            let key = key_derivation_data.calc_master_key()?;
+            self.rotate_item_keys_continue(wallet_handle, &key)
+        }
+
+        pub fn migrate_wallet(&self,
+                              src_config: &Config,
+                              src_credentials: &Credentials,
+                              dst_config: &Config,
+                              dst_credentials: &Credentials) -> IndyResult<usize> {
+            let (wallet_handle, src_key_data, key_data) = self.migrate_wallet_prepare(src_config, src_credentials, dst_config, dst_credentials)?;
+            let src_master_key = src_key_data.calc_master_key()?;
+            let master_key = key_data.calc_master_key()?;
+            self.migrate_wallet_continue(wallet_handle, dst_config, dst_credentials, (src_master_key, master_key))
+        }
+
         pub fn import_wallet(&self,
                              config: &Config,
                              credentials: &Credentials,
-                             export_config: &ExportConfig) -> IndyResult<()> {
+                             export_config: &ExportConfig) -> IndyResult<ImportReport> {
             trace!("import_wallet_prepare >>> config: {:?}, credentials: {:?}, export_config: {:?}", config, secret!(export_config), secret!(export_config));
 
             let exported_file_to_import =
@@ -868,15 +2090,15 @@ mod tests {
                     .read(true)
                     .open(&export_config.path)?;
 
-            let (reader, import_key_derivation_data, nonce, chunk_size, header_bytes) = preparse_file_to_import(exported_file_to_import, &export_config.key)?;
-            let key_data = KeyDerivationData::from_passphrase_with_new_salt(&credentials.key, &credentials.key_derivation_method);
+            let (reader, import_key_derivation_data, nonce, chunk_size, header_bytes) = preparse_file_to_import(Box::new(exported_file_to_import) as Box<dyn Read>, &export_config.key)?;
+            let key_data = self.new_key_derivation_data(&credentials.key, &credentials.key_derivation_method, credentials.key_wrap_provider.as_ref())?;
 
             let wallet_handle = next_wallet_handle();
 
             let import_key = import_key_derivation_data.calc_master_key()?;
             let master_key = key_data.calc_master_key()?;
 
-            self.pending_for_import.borrow_mut().insert(wallet_handle, (reader, nonce, chunk_size, header_bytes, key_data));
+            self.pending_for_import.borrow_mut().insert(wallet_handle, (reader, nonce, chunk_size, header_bytes, key_data, export_config.tolerant));
 
             self.import_wallet_continue(wallet_handle, config, credentials, (import_key, master_key))
         }
@@ -892,6 +2114,14 @@ mod tests {
 
             self.delete_wallet_continue(config, credentials, &metadata, &master_key)
         }
+
+        pub fn list_profiles(&self, config: &Config, credentials: &Credentials) -> IndyResult<Vec<String>> {
+            let (metadata, key_derivation_data) = self.list_profiles_prepare(config, credentials)?;
+
+            let master_key = key_derivation_data.calc_master_key()?;
+
+            self.list_profiles_continue(config, credentials, &metadata, &master_key)
+        }
     }
 
     #[test]
@@ -1125,6 +2355,144 @@ mod tests {
         test::cleanup_wallet("wallet_service_open_wallet_works");
     }
 
+    #[test]
+    fn wallet_service_rotate_item_keys_works() {
+        test::cleanup_wallet("wallet_service_rotate_item_keys_works");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_rotate_item_keys_works"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_rotate_item_keys_works"), &RAW_CREDENTIAL).unwrap();
+
+            let tags = serde_json::from_str::<Tags>(r#"{"tag1": "value1", "~tag2": "value2"}"#).unwrap();
+            for i in 0..(KEYS_ROTATION_TEST_RECORDS) {
+                wallet_service.add_record(wallet_handle, "type", &format!("key{}", i), "value", &tags, None).unwrap();
+            }
+            wallet_service.add_record(wallet_handle, "expiring_type", "key1", "value", &tags, Some(u64::max_value() >> 1)).unwrap();
+
+            let old_metadata = wallet_service.wallets.borrow().get(&wallet_handle).unwrap().get_storage_metadata().unwrap();
+
+            wallet_service.rotate_item_keys(wallet_handle, &RAW_CREDENTIAL).unwrap();
+
+            let new_metadata = wallet_service.wallets.borrow().get(&wallet_handle).unwrap().get_storage_metadata().unwrap();
+            assert_ne!(old_metadata, new_metadata);
+            let new_metadata: Metadata = serde_json::from_slice(&new_metadata).unwrap();
+            assert!(new_metadata.get_keys_rotation().is_none());
+
+            let record = wallet_service.get_record(wallet_handle, "type", "key1", &_fetch_options(true, true, true)).unwrap();
+            assert_eq!("value", record.get_value().unwrap());
+            assert_eq!(&tags, record.get_tags().unwrap());
+
+            let search = wallet_service.search_records(wallet_handle, "type", r#"{"tag1": "value1", "~tag2": "value2"}"#, r#"{"retrieveTotalCount": true}"#).unwrap();
+            assert_eq!(Some(KEYS_ROTATION_TEST_RECORDS), search.get_total_count().unwrap());
+
+            let record = wallet_service.get_record(wallet_handle, "expiring_type", "key1", "{}").unwrap();
+            assert_eq!(Some(u64::max_value() >> 1), record.get_expires_at());
+
+            wallet_service.close_wallet(wallet_handle).unwrap();
+
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_rotate_item_keys_works"), &RAW_CREDENTIAL).unwrap();
+            let record = wallet_service.get_record(wallet_handle, "type", "key1", &_fetch_options(true, true, true)).unwrap();
+            assert_eq!("value", record.get_value().unwrap());
+            wallet_service.close_wallet(wallet_handle).unwrap();
+        }
+        test::cleanup_wallet("wallet_service_rotate_item_keys_works");
+    }
+
+    #[test]
+    fn wallet_service_rotate_item_keys_works_for_deleted_records() {
+        test::cleanup_wallet("wallet_service_rotate_item_keys_works_for_deleted_records");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_rotate_item_keys_works_for_deleted_records"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_rotate_item_keys_works_for_deleted_records"), &RAW_CREDENTIAL).unwrap();
+
+            for i in 0..(KEYS_ROTATION_TEST_RECORDS) {
+                wallet_service.add_record(wallet_handle, "type", &format!("key{}", i), "value", &HashMap::new(), None).unwrap();
+            }
+            wallet_service.delete_record(wallet_handle, "type", "key0").unwrap();
+
+            let revision = wallet_service.get_revision(wallet_handle).unwrap();
+
+            wallet_service.rotate_item_keys(wallet_handle, &RAW_CREDENTIAL).unwrap();
+
+            {
+                let wallets = wallet_service.wallets.borrow();
+                let wallet = wallets.get(&wallet_handle).unwrap();
+
+                assert_eq!(vec![("type".to_string(), "key0".to_string())], wallet.get_deleted(0).unwrap());
+                assert!(wallet.get_deleted(revision).unwrap().is_empty());
+            }
+
+            wallet_service.close_wallet(wallet_handle).unwrap();
+        }
+        test::cleanup_wallet("wallet_service_rotate_item_keys_works_for_deleted_records");
+    }
+
+    #[test]
+    fn wallet_service_rotate_item_keys_works_for_interrupted_rotation() {
+        test::cleanup_wallet("wallet_service_rotate_item_keys_works_for_interrupted_rotation");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_rotate_item_keys_works_for_interrupted_rotation"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_rotate_item_keys_works_for_interrupted_rotation"), &RAW_CREDENTIAL).unwrap();
+
+            for i in 0..(KEYS_ROTATION_TEST_RECORDS) {
+                wallet_service.add_record(wallet_handle, "type", &format!("key{}", i), "value", &HashMap::new(), None).unwrap();
+            }
+
+            // rotate only the first chunk of records as if the process was interrupted
+            {
+                let wallets = wallet_service.wallets.borrow();
+                let wallet = wallets.get(&wallet_handle).unwrap();
+
+                let new_keys = Keys::new();
+                let mut metadata = WalletService::_get_metadata(wallet).unwrap();
+                metadata.set_keys_rotation(Some(KeysRotation { keys: new_keys.serialize_encrypted(&RAW_MASTER_KEY).unwrap(), rotated: 0 }));
+                wallet.set_storage_metadata(&metadata.to_bytes().unwrap()).unwrap();
+
+                let chunk = wallet.get_keys_rotation_pending(&new_keys).unwrap().next_chunk(KEYS_ROTATION_CHUNK_SIZE).unwrap();
+                assert_eq!(KEYS_ROTATION_CHUNK_SIZE, chunk.len());
+                assert_eq!(KEYS_ROTATION_CHUNK_SIZE, wallet.rotate_keys_chunk(&new_keys, &chunk).unwrap());
+                assert_eq!(KEYS_ROTATION_TEST_RECORDS - KEYS_ROTATION_CHUNK_SIZE,
+                           wallet.get_keys_rotation_pending(&new_keys).unwrap().next_chunk(KEYS_ROTATION_TEST_RECORDS).unwrap().len());
+            }
+
+            wallet_service.close_wallet(wallet_handle).unwrap();
+
+            let res = wallet_service.open_wallet(&_config_read_only("wallet_service_rotate_item_keys_works_for_interrupted_rotation"), &RAW_CREDENTIAL);
+            assert_kind!(IndyErrorKind::WalletReadOnly, res);
+
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_rotate_item_keys_works_for_interrupted_rotation"), &RAW_CREDENTIAL).unwrap();
+
+            let metadata = WalletService::_get_metadata(wallet_service.wallets.borrow().get(&wallet_handle).unwrap()).unwrap();
+            assert!(metadata.get_keys_rotation().is_none());
+
+            for i in 0..(KEYS_ROTATION_TEST_RECORDS) {
+                let record = wallet_service.get_record(wallet_handle, "type", &format!("key{}", i), &_fetch_options(false, true, false)).unwrap();
+                assert_eq!("value", record.get_value().unwrap());
+            }
+
+            wallet_service.close_wallet(wallet_handle).unwrap();
+        }
+        test::cleanup_wallet("wallet_service_rotate_item_keys_works_for_interrupted_rotation");
+    }
+
+    #[test]
+    fn wallet_service_rotate_item_keys_works_for_invalid_credentials() {
+        test::cleanup_wallet("wallet_service_rotate_item_keys_works_for_invalid_credentials");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_rotate_item_keys_works_for_invalid_credentials"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_rotate_item_keys_works_for_invalid_credentials"), &RAW_CREDENTIAL).unwrap();
+
+            let res = wallet_service.rotate_item_keys(wallet_handle, &ARGON_INT_CREDENTIAL);
+            assert_kind!(IndyErrorKind::WalletAccessFailed, res);
+
+            wallet_service.close_wallet(wallet_handle).unwrap();
+        }
+        test::cleanup_wallet("wallet_service_rotate_item_keys_works_for_invalid_credentials");
+    }
+
     #[test]
     fn wallet_service_open_wallet_works_for_interactive_key_derivation() {
         test::cleanup_wallet("wallet_service_open_wallet_works_for_interactive_key_derivation");
@@ -1163,6 +2531,9 @@ mod tests {
             id: String::from("same_id"),
             storage_type: None,
             storage_config: None,
+            read_only: false,
+            profile: None,
+            prefix_indexes: HashMap::new(),
         };
 
         wallet_service.create_wallet(&config_1, &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
@@ -1174,6 +2545,9 @@ mod tests {
             storage_config: Some(json!({
                 "path": _custom_path("wallet_service_open_wallet_works_for_two_wallets_with_same_ids_but_different_paths")
             })),
+            read_only: false,
+            profile: None,
+            prefix_indexes: HashMap::new(),
         };
 
         wallet_service.create_wallet(&config_2, &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
@@ -1189,6 +2563,56 @@ mod tests {
         _cleanup("wallet_service_open_wallet_works_for_two_wallets_with_same_ids_but_different_paths");
     }
 
+    #[test]
+    fn wallet_service_open_wallet_works_for_read_only() {
+        _cleanup("wallet_service_open_wallet_works_for_read_only");
+
+        let wallet_service = WalletService::new();
+        wallet_service.create_wallet(&_config("wallet_service_open_wallet_works_for_read_only"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+
+        let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_open_wallet_works_for_read_only"), &RAW_CREDENTIAL).unwrap();
+        wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), None).unwrap();
+
+        let read_only_handle_1 = wallet_service.open_wallet(&_config_read_only("wallet_service_open_wallet_works_for_read_only"), &RAW_CREDENTIAL).unwrap();
+        let read_only_handle_2 = wallet_service.open_wallet(&_config_read_only("wallet_service_open_wallet_works_for_read_only"), &RAW_CREDENTIAL).unwrap();
+
+        let record = wallet_service.get_record(read_only_handle_1, "type", "key1", &_fetch_options(false, true, false)).unwrap();
+        assert_eq!("value1", record.get_value().unwrap());
+
+        let res = wallet_service.add_record(read_only_handle_2, "type", "key2", "value2", &HashMap::new(), None);
+        assert_kind!(IndyErrorKind::WalletReadOnly, res);
+
+        let res = wallet_service.delete_record(read_only_handle_2, "type", "key1");
+        assert_kind!(IndyErrorKind::WalletReadOnly, res);
+
+        // closing of read-only handle doesn't release read-write one
+        wallet_service.close_wallet(read_only_handle_1).unwrap();
+
+        let res = wallet_service.open_wallet(&_config("wallet_service_open_wallet_works_for_read_only"), &RAW_CREDENTIAL);
+        assert_kind!(IndyErrorKind::WalletAlreadyOpened, res);
+
+        wallet_service.close_wallet(wallet_handle).unwrap();
+
+        let res = wallet_service.delete_wallet(&_config("wallet_service_open_wallet_works_for_read_only"), &RAW_CREDENTIAL);
+        assert_kind!(IndyErrorKind::InvalidState, res);
+
+        wallet_service.close_wallet(read_only_handle_2).unwrap();
+        wallet_service.delete_wallet(&_config("wallet_service_open_wallet_works_for_read_only"), &RAW_CREDENTIAL).unwrap();
+    }
+
+    #[test]
+    fn wallet_service_open_wallet_works_for_read_only_and_rekey() {
+        _cleanup("wallet_service_open_wallet_works_for_read_only_and_rekey");
+
+        let wallet_service = WalletService::new();
+        wallet_service.create_wallet(&_config("wallet_service_open_wallet_works_for_read_only_and_rekey"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+
+        let res = wallet_service.open_wallet(&_config_read_only("wallet_service_open_wallet_works_for_read_only_and_rekey"), &_rekey_credentials_raw());
+        assert_kind!(IndyErrorKind::WalletReadOnly, res);
+
+        wallet_service.delete_wallet(&_config("wallet_service_open_wallet_works_for_read_only_and_rekey"), &RAW_CREDENTIAL).unwrap();
+    }
+
     #[test]
     fn wallet_service_open_unknown_wallet() {
         test::cleanup_wallet("wallet_service_open_unknown_wallet");
@@ -1230,10 +2654,103 @@ mod tests {
             let wallet_service = WalletService::new();
             wallet_service.create_wallet(&_config("wallet_service_open_wallet_returns_error_if_used_different_methods_for_creating_and_opening"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
 
-            let res = wallet_service.open_wallet(&_config("wallet_service_open_wallet_returns_error_if_used_different_methods_for_creating_and_opening"), &ARGON_INT_CREDENTIAL);
+            let res = wallet_service.open_wallet(&_config("wallet_service_open_wallet_returns_error_if_used_different_methods_for_creating_and_opening"), &ARGON_INT_CREDENTIAL);
+            assert_kind!(IndyErrorKind::WalletAccessFailed, res);
+        }
+        test::cleanup_wallet("wallet_service_open_wallet_returns_error_if_used_different_methods_for_creating_and_opening");
+    }
+
+    #[test]
+    fn wallet_service_open_wallet_works_for_wrapped_key() {
+        test::cleanup_wallet("wallet_service_open_wallet_works_for_wrapped_key");
+        {
+            let config = _config("wallet_service_open_wallet_works_for_wrapped_key");
+            let credentials = _credentials_wrapped("wallet_service_open_wallet_works_for_wrapped_key");
+
+            let wallet_service = WalletService::new();
+            let kdd = wallet_service.new_key_derivation_data(&credentials.key, &credentials.key_derivation_method, credentials.key_wrap_provider.as_ref()).unwrap();
+            let master_key = kdd.calc_master_key().unwrap();
+
+            wallet_service.create_wallet(&config, &credentials, (&kdd, &master_key)).unwrap();
+            let handle = wallet_service.open_wallet(&config, &credentials).unwrap();
+            wallet_service.add_record(handle, "type", "key1", "value1", &HashMap::new(), None).unwrap();
+            wallet_service.close_wallet(handle).unwrap();
+
+            let handle = wallet_service.open_wallet(&config, &credentials).unwrap();
+            let record = wallet_service.get_record(handle, "type", "key1", &_fetch_options(false, true, false)).unwrap();
+            assert_eq!("value1", record.get_value().unwrap());
+            wallet_service.close_wallet(handle).unwrap();
+
+            wallet_service.delete_wallet(&config, &credentials).unwrap();
+        }
+        test::cleanup_wallet("wallet_service_open_wallet_works_for_wrapped_key");
+    }
+
+    #[test]
+    fn wallet_service_open_wallet_works_for_rekey_to_wrapped_key() {
+        test::cleanup_wallet("wallet_service_open_wallet_works_for_rekey_to_wrapped_key");
+        {
+            let config = _config("wallet_service_open_wallet_works_for_rekey_to_wrapped_key");
+            let wrapped_credentials = _credentials_wrapped("wallet_service_open_wallet_works_for_rekey_to_wrapped_key");
+
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&config, &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+
+            let rekey_credentials = Credentials {
+                rekey: Some(wrapped_credentials.key.clone()),
+                rekey_derivation_method: KeyDerivationMethod::WRAPPED,
+                key_wrap_provider: wrapped_credentials.key_wrap_provider.clone(),
+                ..RAW_CREDENTIAL.clone()
+            };
+
+            let handle = wallet_service.open_wallet(&config, &rekey_credentials).unwrap();
+            wallet_service.close_wallet(handle).unwrap();
+
+            let res = wallet_service.open_wallet(&config, &RAW_CREDENTIAL);
+            assert_kind!(IndyErrorKind::WalletAccessFailed, res);
+
+            let handle = wallet_service.open_wallet(&config, &wrapped_credentials).unwrap();
+            wallet_service.close_wallet(handle).unwrap();
+        }
+        test::cleanup_wallet("wallet_service_open_wallet_works_for_rekey_to_wrapped_key");
+    }
+
+    #[test]
+    fn wallet_service_open_wallet_returns_error_for_wrapped_key_and_other_wrapping_key() {
+        test::cleanup_wallet("wallet_service_open_wallet_returns_error_for_wrapped_key_and_other_wrapping_key");
+        {
+            let config = _config("wallet_service_open_wallet_returns_error_for_wrapped_key_and_other_wrapping_key");
+            let credentials = _credentials_wrapped("wallet_service_open_wallet_returns_error_for_wrapped_key_and_other_wrapping_key");
+
+            let wallet_service = WalletService::new();
+            let kdd = wallet_service.new_key_derivation_data(&credentials.key, &credentials.key_derivation_method, credentials.key_wrap_provider.as_ref()).unwrap();
+            let master_key = kdd.calc_master_key().unwrap();
+            wallet_service.create_wallet(&config, &credentials, (&kdd, &master_key)).unwrap();
+
+            let other_credentials = _credentials_wrapped("wallet_service_open_wallet_returns_error_for_wrapped_key_and_other_wrapping_key_2");
+            wallet_service.new_key_derivation_data(&other_credentials.key, &other_credentials.key_derivation_method, other_credentials.key_wrap_provider.as_ref()).unwrap();
+
+            let res = wallet_service.open_wallet(&config, &other_credentials);
+            assert_kind!(IndyErrorKind::WalletAccessFailed, res);
+
+            let res = wallet_service.open_wallet(&config, &Credentials { key_wrap_provider: Some("unknown".to_string()), ..credentials.clone() });
+            assert_kind!(IndyErrorKind::WalletAccessFailed, res);
+
+            let res = wallet_service.open_wallet(&config, &RAW_CREDENTIAL);
             assert_kind!(IndyErrorKind::WalletAccessFailed, res);
         }
-        test::cleanup_wallet("wallet_service_open_wallet_returns_error_if_used_different_methods_for_creating_and_opening");
+        test::cleanup_wallet("wallet_service_open_wallet_returns_error_for_wrapped_key_and_other_wrapping_key");
+    }
+
+    #[test]
+    fn wallet_service_new_key_derivation_data_returns_error_for_wrapped_key_without_provider() {
+        let wallet_service = WalletService::new();
+
+        let res = wallet_service.new_key_derivation_data("key_id", &KeyDerivationMethod::WRAPPED, None);
+        assert_kind!(IndyErrorKind::InvalidStructure, res);
+
+        let res = wallet_service.new_key_derivation_data("key_id", &KeyDerivationMethod::WRAPPED, Some(&"unknown".to_string()));
+        assert_kind!(IndyErrorKind::InvalidStructure, res);
     }
 
     #[test]
@@ -1291,7 +2808,7 @@ mod tests {
             wallet_service.create_wallet(&_config("wallet_service_add_record_works"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
             let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_add_record_works"), &RAW_CREDENTIAL).unwrap();
 
-            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), None).unwrap();
             wallet_service.get_record(wallet_handle, "type", "key1", "{}").unwrap();
         }
         test::cleanup_wallet("wallet_service_add_record_works");
@@ -1307,8 +2824,79 @@ mod tests {
         wallet_service.create_wallet(&_config_inmem(), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
         let wallet_handle = wallet_service.open_wallet(&_config_inmem(), &RAW_CREDENTIAL).unwrap();
 
-        wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+        wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), None).unwrap();
+        wallet_service.get_record(wallet_handle, "type", "key1", "{}").unwrap();
+    }
+
+    #[test]
+    fn wallet_service_add_record_works_for_expiry() {
+        test::cleanup_wallet("wallet_service_add_record_works_for_expiry");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_add_record_works_for_expiry"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_add_record_works_for_expiry"), &RAW_CREDENTIAL).unwrap();
+
+            _check_expiry(&wallet_service, wallet_handle);
+        }
+        test::cleanup_wallet("wallet_service_add_record_works_for_expiry");
+    }
+
+    #[test]
+    fn wallet_service_add_record_works_for_expiry_and_plugged() {
+        _cleanup("wallet_service_add_record_works_for_expiry_and_plugged");
+
+        let wallet_service = WalletService::new();
+        _register_inmem_wallet(&wallet_service);
+
+        wallet_service.create_wallet(&_config_inmem(), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+        let wallet_handle = wallet_service.open_wallet(&_config_inmem(), &RAW_CREDENTIAL).unwrap();
+
+        _check_expiry(&wallet_service, wallet_handle);
+    }
+
+    fn _check_expiry(wallet_service: &WalletService, wallet_handle: WalletHandle) {
+        wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), Some(1)).unwrap();
+        wallet_service.add_record(wallet_handle, "type", "key2", "value2", &HashMap::new(), Some(u64::max_value() >> 1)).unwrap();
+
+        let res = wallet_service.get_record(wallet_handle, "type", "key1", "{}");
+        assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+        let record = wallet_service.get_record(wallet_handle, "type", "key2", &_fetch_options(true, true, true)).unwrap();
+        assert_eq!(HashMap::new(), record.get_tags().unwrap().clone());
+
+        let mut search = wallet_service.search_records(wallet_handle, "type", "{}", &_fetch_options(true, true, true)).unwrap();
+
+        let record = search.fetch_next_record().unwrap().unwrap();
+        assert_eq!("value2", record.get_value().unwrap());
+        assert_eq!(HashMap::new(), record.get_tags().unwrap().clone());
+        assert!(search.fetch_next_record().unwrap().is_none());
+
+        // expired record can be replaced before it is purged
+        wallet_service.add_record(wallet_handle, "type", "key1", "value3", &HashMap::new(), None).unwrap();
+        wallet_service.add_record(wallet_handle, "type", "key3", "value3", &HashMap::new(), Some(1)).unwrap();
+
+        assert_eq!(1, wallet_service.purge_expired_records(wallet_handle).unwrap());
+        assert_eq!(0, wallet_service.purge_expired_records(wallet_handle).unwrap());
+
         wallet_service.get_record(wallet_handle, "type", "key1", "{}").unwrap();
+
+        let record = wallet_service.get_record(wallet_handle, "type", "key2", "{}").unwrap();
+        assert_eq!(Some(u64::max_value() >> 1), record.get_expires_at());
+
+        // expired records are neither counted nor take place in pages
+        wallet_service.add_record(wallet_handle, "type", "key4", "value4", &HashMap::new(), Some(1)).unwrap();
+
+        let search = wallet_service.search_records(wallet_handle, "type", "{}", r#"{"retrieveRecords": false, "retrieveTotalCount": true}"#).unwrap();
+        assert_eq!(Some(2), search.get_total_count().unwrap());
+
+        let mut search = wallet_service.search_records(wallet_handle, "type", "{}", r#"{"limit": 2}"#).unwrap();
+        assert!(search.fetch_next_record().unwrap().is_some());
+        assert!(search.fetch_next_record().unwrap().is_some());
+        assert!(search.fetch_next_record().unwrap().is_none());
+
+        let mut search = wallet_service.search_records(wallet_handle, "type", "{}", r#"{"offset": 1, "limit": 2}"#).unwrap();
+        assert!(search.fetch_next_record().unwrap().is_some());
+        assert!(search.fetch_next_record().unwrap().is_none());
     }
 
     #[test]
@@ -1319,7 +2907,7 @@ mod tests {
             wallet_service.create_wallet(&_config("wallet_service_get_record_works_for_id_only"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
             let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_get_record_works_for_id_only"), &RAW_CREDENTIAL).unwrap();
 
-            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), None).unwrap();
             let record = wallet_service.get_record(wallet_handle, "type", "key1", &_fetch_options(false, false, false)).unwrap();
 
             assert!(record.get_value().is_none());
@@ -1340,7 +2928,7 @@ mod tests {
         wallet_service.create_wallet(&_config_inmem(), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
         let wallet_handle = wallet_service.open_wallet(&_config_inmem(), &RAW_CREDENTIAL).unwrap();
 
-        wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+        wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), None).unwrap();
         let record = wallet_service.get_record(wallet_handle, "type", "key1", &_fetch_options(false, false, false)).unwrap();
 
         assert!(record.get_value().is_none());
@@ -1356,7 +2944,7 @@ mod tests {
             wallet_service.create_wallet(&_config("wallet_service_get_record_works_for_id_value"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
             let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_get_record_works_for_id_value"), &RAW_CREDENTIAL).unwrap();
 
-            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), None).unwrap();
             let record = wallet_service.get_record(wallet_handle, "type", "key1", &_fetch_options(false, true, false)).unwrap();
 
             assert_eq!("value1", record.get_value().unwrap());
@@ -1376,7 +2964,7 @@ mod tests {
         wallet_service.create_wallet(&_config_inmem(), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
         let wallet_handle = wallet_service.open_wallet(&_config_inmem(), &RAW_CREDENTIAL).unwrap();
 
-        wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+        wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), None).unwrap();
         let record = wallet_service.get_record(wallet_handle, "type", "key1", &_fetch_options(false, true, false)).unwrap();
 
         assert_eq!("value1", record.get_value().unwrap());
@@ -1394,7 +2982,7 @@ mod tests {
             let mut tags = HashMap::new();
             tags.insert(String::from("1"), String::from("some"));
 
-            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &tags).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &tags, None).unwrap();
             let record = wallet_service.get_record(wallet_handle, "type", "key1", &_fetch_options(true, true, true)).unwrap();
 
             assert_eq!("type", record.get_type().unwrap());
@@ -1415,7 +3003,7 @@ mod tests {
         let wallet_handle = wallet_service.open_wallet(&_config_inmem(), &RAW_CREDENTIAL).unwrap();
         let tags = serde_json::from_str(r#"{"1":"some"}"#).unwrap();
 
-        wallet_service.add_record(wallet_handle, "type", "key1", "value1", &tags).unwrap();
+        wallet_service.add_record(wallet_handle, "type", "key1", "value1", &tags, None).unwrap();
         let record = wallet_service.get_record(wallet_handle, "type", "key1", &_fetch_options(true, true, true)).unwrap();
 
         assert_eq!("type", record.get_type().unwrap());
@@ -1430,7 +3018,7 @@ mod tests {
             let wallet_service = WalletService::new();
             wallet_service.create_wallet(&_config("wallet_service_add_get_works_for_reopen"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
             let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_add_get_works_for_reopen"), &RAW_CREDENTIAL).unwrap();
-            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), None).unwrap();
             wallet_service.close_wallet(wallet_handle).unwrap();
 
             let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_add_get_works_for_reopen"), &RAW_CREDENTIAL).unwrap();
@@ -1484,7 +3072,7 @@ mod tests {
             wallet_service.create_wallet(&_config("wallet_service_update"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
             let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_update"), &RAW_CREDENTIAL).unwrap();
 
-            wallet_service.add_record(wallet_handle, type_, name, value, &HashMap::new()).unwrap();
+            wallet_service.add_record(wallet_handle, type_, name, value, &HashMap::new(), None).unwrap();
             let record = wallet_service.get_record(wallet_handle, type_, name, &_fetch_options(false, true, false)).unwrap();
             assert_eq!(value, record.get_value().unwrap());
 
@@ -1510,7 +3098,7 @@ mod tests {
         wallet_service.create_wallet(&_config_inmem(), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
         let wallet_handle = wallet_service.open_wallet(&_config_inmem(), &RAW_CREDENTIAL).unwrap();
 
-        wallet_service.add_record(wallet_handle, type_, name, value, &HashMap::new()).unwrap();
+        wallet_service.add_record(wallet_handle, type_, name, value, &HashMap::new(), None).unwrap();
         let record = wallet_service.get_record(wallet_handle, type_, name, &_fetch_options(false, true, false)).unwrap();
         assert_eq!(value, record.get_value().unwrap());
 
@@ -1534,7 +3122,7 @@ mod tests {
             wallet_service.create_wallet(&_config("wallet_service_delete_record"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
             let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_delete_record"), &RAW_CREDENTIAL).unwrap();
 
-            wallet_service.add_record(wallet_handle, type_, name, value, &HashMap::new()).unwrap();
+            wallet_service.add_record(wallet_handle, type_, name, value, &HashMap::new(), None).unwrap();
             let record = wallet_service.get_record(wallet_handle, type_, name, &_fetch_options(false, true, false)).unwrap();
             assert_eq!(value, record.get_value().unwrap());
 
@@ -1559,7 +3147,7 @@ mod tests {
         wallet_service.create_wallet(&_config_inmem(), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
         let wallet_handle = wallet_service.open_wallet(&_config_inmem(), &RAW_CREDENTIAL).unwrap();
 
-        wallet_service.add_record(wallet_handle, type_, name, value, &HashMap::new()).unwrap();
+        wallet_service.add_record(wallet_handle, type_, name, value, &HashMap::new(), None).unwrap();
         let record = wallet_service.get_record(wallet_handle, type_, name, &_fetch_options(false, true, false)).unwrap();
         assert_eq!(value, record.get_value().unwrap());
 
@@ -1584,7 +3172,7 @@ mod tests {
             wallet_service.create_wallet(&_config("wallet_service_add_tags"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
             let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_add_tags"), &RAW_CREDENTIAL).unwrap();
 
-            wallet_service.add_record(wallet_handle, type_, name, value, &tags).unwrap();
+            wallet_service.add_record(wallet_handle, type_, name, value, &tags, None).unwrap();
 
             let new_tags = serde_json::from_str(r#"{"tag_name_2":"tag_value_2", "~tag_name_3":"tag_value_3"}"#).unwrap();
             wallet_service.add_record_tags(wallet_handle, type_, name, &new_tags).unwrap();
@@ -1613,7 +3201,7 @@ mod tests {
         wallet_service.create_wallet(&_config_inmem(), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
         let wallet_handle = wallet_service.open_wallet(&_config_inmem(), &RAW_CREDENTIAL).unwrap();
 
-        wallet_service.add_record(wallet_handle, type_, name, value, &tags).unwrap();
+        wallet_service.add_record(wallet_handle, type_, name, value, &tags, None).unwrap();
 
         let new_tags = serde_json::from_str(r#"{"tag_name_2":"tag_value_2", "~tag_name_3":"tag_value_3"}"#).unwrap();
         wallet_service.add_record_tags(wallet_handle, type_, name, &new_tags).unwrap();
@@ -1641,7 +3229,7 @@ mod tests {
             wallet_service.create_wallet(&_config("wallet_service_update_tags"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
             let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_update_tags"), &RAW_CREDENTIAL).unwrap();
 
-            wallet_service.add_record(wallet_handle, type_, name, value, &tags).unwrap();
+            wallet_service.add_record(wallet_handle, type_, name, value, &tags, None).unwrap();
 
             let new_tags = serde_json::from_str(r#"{"tag_name_1":"tag_value_1", "tag_name_2":"new_tag_value_2", "~tag_name_3":"new_tag_value_3"}"#).unwrap();
 
@@ -1668,7 +3256,7 @@ mod tests {
             wallet_service.create_wallet(&_config_inmem(), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
             let wallet_handle = wallet_service.open_wallet(&_config_inmem(), &RAW_CREDENTIAL).unwrap();
 
-            wallet_service.add_record(wallet_handle, type_, name, value, &tags).unwrap();
+            wallet_service.add_record(wallet_handle, type_, name, value, &tags, None).unwrap();
 
             let new_tags = serde_json::from_str(r#"{"tag_name_1":"tag_value_1", "tag_name_2":"new_tag_value_2", "~tag_name_3":"new_tag_value_3"}"#).unwrap();
 
@@ -1698,7 +3286,7 @@ mod tests {
             wallet_service.create_wallet(&_config("wallet_service_delete_tags"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
             let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_delete_tags"), &RAW_CREDENTIAL).unwrap();
 
-            wallet_service.add_record(wallet_handle, type_, name, value, &tags).unwrap();
+            wallet_service.add_record(wallet_handle, type_, name, value, &tags, None).unwrap();
 
             let tag_names = vec!["tag_name_1", "~tag_name_3"];
             wallet_service.delete_record_tags(wallet_handle, type_, name, &tag_names).unwrap();
@@ -1728,7 +3316,7 @@ mod tests {
             wallet_service.create_wallet(&_config_inmem(), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
             let wallet_handle = wallet_service.open_wallet(&_config_inmem(), &RAW_CREDENTIAL).unwrap();
 
-            wallet_service.add_record(wallet_handle, type_, name, value, &tags).unwrap();
+            wallet_service.add_record(wallet_handle, type_, name, value, &tags, None).unwrap();
 
             let tag_names = vec!["tag_name_1", "~tag_name_3"];
             wallet_service.delete_record_tags(wallet_handle, type_, name, &tag_names).unwrap();
@@ -1742,6 +3330,354 @@ mod tests {
         _cleanup("wallet_service_delete_tags_for_plugged");
     }
 
+    /**
+     * Batch tests
+    */
+    #[test]
+    fn wallet_service_batch_records_works() {
+        test::cleanup_wallet("wallet_service_batch_records_works");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_batch_records_works"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_batch_records_works"), &RAW_CREDENTIAL).unwrap();
+
+            wallet_service.add_record(wallet_handle, "offer", "offer1", "offer_value", &HashMap::new(), None).unwrap();
+
+            let operations: Vec<WalletOperation> = serde_json::from_value(json!([
+                {"op": "add", "type": "credential", "id": "cred1", "value": "cred_value", "tags": {"tag1": "value1"}},
+                {"op": "updateTags", "type": "credential", "id": "cred1", "tags": {"~tag2": "value2"}},
+                {"op": "delete", "type": "offer", "id": "offer1"}
+            ])).unwrap();
+
+            wallet_service.batch_records(wallet_handle, &operations).unwrap();
+
+            let record = wallet_service.get_record(wallet_handle, "credential", "cred1", &_fetch_options(false, true, true)).unwrap();
+            assert_eq!("cred_value", record.get_value().unwrap());
+            let expected_tags: Tags = serde_json::from_str(r#"{"~tag2":"value2"}"#).unwrap();
+            assert_eq!(expected_tags, record.tags.unwrap());
+
+            let res = wallet_service.get_record(wallet_handle, "offer", "offer1", &_fetch_options(false, true, false));
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+        }
+        test::cleanup_wallet("wallet_service_batch_records_works");
+    }
+
+    #[test]
+    fn wallet_service_batch_records_works_for_rollback() {
+        test::cleanup_wallet("wallet_service_batch_records_works_for_rollback");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_batch_records_works_for_rollback"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_batch_records_works_for_rollback"), &RAW_CREDENTIAL).unwrap();
+
+            wallet_service.add_record(wallet_handle, "offer", "offer1", "offer_value", &HashMap::new(), None).unwrap();
+
+            let operations: Vec<WalletOperation> = serde_json::from_value(json!([
+                {"op": "add", "type": "credential", "id": "cred1", "value": "cred_value"},
+                {"op": "delete", "type": "offer", "id": "offer1"},
+                {"op": "updateValue", "type": "offer", "id": "unknown", "value": "value"}
+            ])).unwrap();
+
+            let res = wallet_service.batch_records(wallet_handle, &operations);
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+            let res = wallet_service.get_record(wallet_handle, "credential", "cred1", &_fetch_options(false, true, false));
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+            let record = wallet_service.get_record(wallet_handle, "offer", "offer1", &_fetch_options(false, true, false)).unwrap();
+            assert_eq!("offer_value", record.get_value().unwrap());
+        }
+        test::cleanup_wallet("wallet_service_batch_records_works_for_rollback");
+    }
+
+    #[test]
+    fn wallet_service_batch_records_works_for_plugged_rollback() {
+        _cleanup("wallet_service_batch_records_works_for_plugged_rollback");
+
+        let wallet_service = WalletService::new();
+        _register_inmem_wallet(&wallet_service);
+
+        wallet_service.create_wallet(&_config_inmem(), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+        let wallet_handle = wallet_service.open_wallet(&_config_inmem(), &RAW_CREDENTIAL).unwrap();
+
+        let tags: Tags = serde_json::from_str(r#"{"tag1":"value1"}"#).unwrap();
+        wallet_service.add_record(wallet_handle, "offer", "offer1", "offer_value", &tags, None).unwrap();
+
+        let operations: Vec<WalletOperation> = serde_json::from_value(json!([
+            {"op": "add", "type": "credential", "id": "cred1", "value": "cred_value"},
+            {"op": "updateValue", "type": "offer", "id": "offer1", "value": "new_offer_value"},
+            {"op": "deleteTags", "type": "offer", "id": "offer1", "tagNames": ["tag1"]},
+            {"op": "delete", "type": "offer", "id": "unknown"}
+        ])).unwrap();
+
+        let res = wallet_service.batch_records(wallet_handle, &operations);
+        assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+        let res = wallet_service.get_record(wallet_handle, "credential", "cred1", &_fetch_options(false, true, false));
+        assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+        let record = wallet_service.get_record(wallet_handle, "offer", "offer1", &_fetch_options(false, true, true)).unwrap();
+        assert_eq!("offer_value", record.get_value().unwrap());
+        assert_eq!(tags, record.tags.unwrap());
+    }
+
+    #[test]
+    fn wallet_service_staged_batch_works() {
+        test::cleanup_wallet("wallet_service_staged_batch_works");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_staged_batch_works"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_staged_batch_works"), &RAW_CREDENTIAL).unwrap();
+
+            wallet_service.add_record(wallet_handle, "offer", "offer1", "offer_value", &HashMap::new(), None).unwrap();
+
+            let batch_handle = wallet_service.begin_batch(wallet_handle).unwrap();
+
+            wallet_service.stage_batch(batch_handle, serde_json::from_value(json!([
+                {"op": "add", "type": "credential", "id": "cred1", "value": "cred_value"}
+            ])).unwrap()).unwrap();
+
+            wallet_service.stage_batch(batch_handle, serde_json::from_value(json!([
+                {"op": "addTags", "type": "credential", "id": "cred1", "tags": {"tag1": "value1"}},
+                {"op": "delete", "type": "offer", "id": "offer1"}
+            ])).unwrap()).unwrap();
+
+            // nothing is written before commit
+            wallet_service.get_record(wallet_handle, "offer", "offer1", &_fetch_options(false, true, false)).unwrap();
+            let res = wallet_service.get_record(wallet_handle, "credential", "cred1", &_fetch_options(false, true, false));
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+            wallet_service.commit_batch(batch_handle).unwrap();
+
+            let record = wallet_service.get_record(wallet_handle, "credential", "cred1", &_fetch_options(false, true, true)).unwrap();
+            assert_eq!("cred_value", record.get_value().unwrap());
+            let expected_tags: Tags = serde_json::from_str(r#"{"tag1":"value1"}"#).unwrap();
+            assert_eq!(expected_tags, record.tags.unwrap());
+
+            let res = wallet_service.get_record(wallet_handle, "offer", "offer1", &_fetch_options(false, true, false));
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+            let res = wallet_service.commit_batch(batch_handle);
+            assert_kind!(IndyErrorKind::InvalidWalletHandle, res);
+        }
+        test::cleanup_wallet("wallet_service_staged_batch_works");
+    }
+
+    #[test]
+    fn wallet_service_staged_batch_works_for_rollback() {
+        test::cleanup_wallet("wallet_service_staged_batch_works_for_rollback");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_staged_batch_works_for_rollback"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_staged_batch_works_for_rollback"), &RAW_CREDENTIAL).unwrap();
+
+            let batch_handle = wallet_service.begin_batch(wallet_handle).unwrap();
+
+            wallet_service.stage_batch(batch_handle, serde_json::from_value(json!([
+                {"op": "add", "type": "credential", "id": "cred1", "value": "cred_value"}
+            ])).unwrap()).unwrap();
+
+            wallet_service.rollback_batch(batch_handle).unwrap();
+
+            let res = wallet_service.commit_batch(batch_handle);
+            assert_kind!(IndyErrorKind::InvalidWalletHandle, res);
+
+            let res = wallet_service.get_record(wallet_handle, "credential", "cred1", &_fetch_options(false, true, false));
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+        }
+        test::cleanup_wallet("wallet_service_staged_batch_works_for_rollback");
+    }
+
+    /**
+     * Subscription tests
+    */
+    #[test]
+    fn wallet_service_subscribe_works() {
+        _cleanup("wallet_service_subscribe_works");
+
+        let wallet_service = WalletService::new();
+        wallet_service.create_wallet(&_config("wallet_service_subscribe_works"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+        let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_subscribe_works"), &RAW_CREDENTIAL).unwrap();
+        let read_only_handle = wallet_service.open_wallet(&_config_read_only("wallet_service_subscribe_works"), &RAW_CREDENTIAL).unwrap();
+
+        let (listener, changes) = _listener();
+        wallet_service.subscribe(read_only_handle, listener).unwrap();
+
+        wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), None).unwrap();
+        wallet_service.update_record_value(wallet_handle, "type", "key1", "value2").unwrap();
+        wallet_service.add_record_tags(wallet_handle, "type", "key1", &serde_json::from_str(r#"{"tag1":"value1"}"#).unwrap()).unwrap();
+        wallet_service.delete_record(wallet_handle, "type", "key1").unwrap();
+
+        let operations: Vec<WalletOperation> = serde_json::from_value(json!([
+            {"op": "add", "type": "credential", "id": "cred1", "value": "cred_value"},
+            {"op": "updateValue", "type": "credential", "id": "cred1", "value": "new_cred_value"},
+            {"op": "delete", "type": "credential", "id": "cred1"}
+        ])).unwrap();
+        wallet_service.batch_records(wallet_handle, &operations).unwrap();
+
+        assert_eq!(vec![
+            ("type".to_string(), "key1".to_string(), RecordChange::Add),
+            ("type".to_string(), "key1".to_string(), RecordChange::Update),
+            ("type".to_string(), "key1".to_string(), RecordChange::Update),
+            ("type".to_string(), "key1".to_string(), RecordChange::Delete),
+            ("credential".to_string(), "cred1".to_string(), RecordChange::Add),
+            ("credential".to_string(), "cred1".to_string(), RecordChange::Update),
+            ("credential".to_string(), "cred1".to_string(), RecordChange::Delete),
+        ], *changes.borrow());
+
+        wallet_service.close_wallet(read_only_handle).unwrap();
+        wallet_service.close_wallet(wallet_handle).unwrap();
+        wallet_service.delete_wallet(&_config("wallet_service_subscribe_works"), &RAW_CREDENTIAL).unwrap();
+    }
+
+    #[test]
+    fn wallet_service_subscribe_works_for_failed_changes() {
+        _cleanup("wallet_service_subscribe_works_for_failed_changes");
+
+        let wallet_service = WalletService::new();
+        wallet_service.create_wallet(&_config("wallet_service_subscribe_works_for_failed_changes"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+        let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_subscribe_works_for_failed_changes"), &RAW_CREDENTIAL).unwrap();
+
+        let (listener, changes) = _listener();
+        wallet_service.subscribe(wallet_handle, listener).unwrap();
+
+        let res = wallet_service.delete_record(wallet_handle, "type", "unknown");
+        assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+        let operations: Vec<WalletOperation> = serde_json::from_value(json!([
+            {"op": "add", "type": "credential", "id": "cred1", "value": "cred_value"},
+            {"op": "delete", "type": "credential", "id": "unknown"}
+        ])).unwrap();
+        let res = wallet_service.batch_records(wallet_handle, &operations);
+        assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+        assert!(changes.borrow().is_empty());
+
+        wallet_service.close_wallet(wallet_handle).unwrap();
+        wallet_service.delete_wallet(&_config("wallet_service_subscribe_works_for_failed_changes"), &RAW_CREDENTIAL).unwrap();
+    }
+
+    #[test]
+    fn wallet_service_subscribe_works_for_invalid_handle() {
+        let wallet_service = WalletService::new();
+        let (listener, _) = _listener();
+
+        let res = wallet_service.subscribe(INVALID_WALLET_HANDLE, listener);
+        assert_kind!(IndyErrorKind::InvalidWalletHandle, res);
+    }
+
+    #[test]
+    fn wallet_service_unsubscribe_works() {
+        _cleanup("wallet_service_unsubscribe_works");
+
+        let wallet_service = WalletService::new();
+        wallet_service.create_wallet(&_config("wallet_service_unsubscribe_works"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+        let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_unsubscribe_works"), &RAW_CREDENTIAL).unwrap();
+
+        let (listener, changes) = _listener();
+        let subscription_handle = wallet_service.subscribe(wallet_handle, listener).unwrap();
+
+        wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), None).unwrap();
+        wallet_service.unsubscribe(subscription_handle).unwrap();
+        wallet_service.add_record(wallet_handle, "type", "key2", "value2", &HashMap::new(), None).unwrap();
+
+        assert_eq!(1, changes.borrow().len());
+
+        let res = wallet_service.unsubscribe(subscription_handle);
+        assert_kind!(IndyErrorKind::InvalidWalletHandle, res);
+
+        wallet_service.close_wallet(wallet_handle).unwrap();
+        wallet_service.delete_wallet(&_config("wallet_service_unsubscribe_works"), &RAW_CREDENTIAL).unwrap();
+    }
+
+    #[test]
+    fn wallet_service_close_wallet_works_for_subscription() {
+        _cleanup("wallet_service_close_wallet_works_for_subscription");
+
+        let wallet_service = WalletService::new();
+        wallet_service.create_wallet(&_config("wallet_service_close_wallet_works_for_subscription"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+        let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_close_wallet_works_for_subscription"), &RAW_CREDENTIAL).unwrap();
+        let read_only_handle = wallet_service.open_wallet(&_config_read_only("wallet_service_close_wallet_works_for_subscription"), &RAW_CREDENTIAL).unwrap();
+
+        let (listener, changes) = _listener();
+        let subscription_handle = wallet_service.subscribe(read_only_handle, listener).unwrap();
+
+        wallet_service.close_wallet(read_only_handle).unwrap();
+        wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), None).unwrap();
+
+        assert!(changes.borrow().is_empty());
+
+        let res = wallet_service.unsubscribe(subscription_handle);
+        assert_kind!(IndyErrorKind::InvalidWalletHandle, res);
+
+        wallet_service.close_wallet(wallet_handle).unwrap();
+        wallet_service.delete_wallet(&_config("wallet_service_close_wallet_works_for_subscription"), &RAW_CREDENTIAL).unwrap();
+    }
+
+    #[test]
+    fn wallet_service_subscribe_works_for_unsubscribe_in_listener() {
+        _cleanup("wallet_service_subscribe_works_for_unsubscribe_in_listener");
+
+        let wallet_service = Rc::new(WalletService::new());
+        wallet_service.create_wallet(&_config("wallet_service_subscribe_works_for_unsubscribe_in_listener"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+        let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_subscribe_works_for_unsubscribe_in_listener"), &RAW_CREDENTIAL).unwrap();
+
+        let subscription_handle: Rc<RefCell<Option<IndyHandle>>> = Rc::new(RefCell::new(None));
+        let calls = Rc::new(RefCell::new(0));
+
+        let listener: RecordChangeListener = {
+            let wallet_service = Rc::downgrade(&wallet_service);
+            let subscription_handle = subscription_handle.clone();
+            let calls = calls.clone();
+
+            Box::new(move |_: &str, _: &str, _: RecordChange| {
+                *calls.borrow_mut() += 1;
+
+                if let (Some(wallet_service), Some(handle)) = (wallet_service.upgrade(), subscription_handle.borrow_mut().take()) {
+                    wallet_service.unsubscribe(handle).unwrap();
+                }
+            })
+        };
+
+        *subscription_handle.borrow_mut() = Some(wallet_service.subscribe(wallet_handle, listener).unwrap());
+
+        wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), None).unwrap();
+        wallet_service.add_record(wallet_handle, "type", "key2", "value2", &HashMap::new(), None).unwrap();
+
+        assert_eq!(1, *calls.borrow());
+
+        wallet_service.close_wallet(wallet_handle).unwrap();
+        wallet_service.delete_wallet(&_config("wallet_service_subscribe_works_for_unsubscribe_in_listener"), &RAW_CREDENTIAL).unwrap();
+    }
+
+    #[test]
+    fn wallet_service_check_wallet_works_for_subscription() {
+        _cleanup("wallet_service_check_wallet_works_for_subscription");
+
+        let wallet_service = WalletService::new();
+        wallet_service.create_wallet(&_config("wallet_service_check_wallet_works_for_subscription"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+        let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_check_wallet_works_for_subscription"), &RAW_CREDENTIAL).unwrap();
+
+        wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), None).unwrap();
+        wallet_service.add_record(wallet_handle, "type", "key2", "bad", &HashMap::new(), None).unwrap();
+
+        let (listener, changes) = _listener();
+        wallet_service.subscribe(wallet_handle, listener).unwrap();
+
+        let validate_value = |_: &str, value: &str| if value == "bad" { Err("Bad value".to_string()) } else { Ok(()) };
+
+        let report = wallet_service.check_wallet(wallet_handle, false, &validate_value).unwrap();
+        assert_eq!(1, report.problems.len());
+        assert!(changes.borrow().is_empty());
+
+        let report = wallet_service.check_wallet(wallet_handle, true, &validate_value).unwrap();
+        assert!(report.problems[0].quarantined);
+        assert_eq!(vec![("type".to_string(), "key2".to_string(), RecordChange::Delete)], *changes.borrow());
+
+        wallet_service.close_wallet(wallet_handle).unwrap();
+        wallet_service.delete_wallet(&_config("wallet_service_check_wallet_works_for_subscription"), &RAW_CREDENTIAL).unwrap();
+    }
+
     #[test]
     fn wallet_service_search_records_works() {
         test::cleanup_wallet("wallet_service_search_records_works");
@@ -1750,19 +3686,150 @@ mod tests {
             wallet_service.create_wallet(&_config("wallet_service_search_records_works"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
             let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_search_records_works"), &RAW_CREDENTIAL).unwrap();
 
-            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
-            wallet_service.add_record(wallet_handle, "type", "key2", "value2", &HashMap::new()).unwrap();
-            wallet_service.add_record(wallet_handle, "type3", "key3", "value3", &HashMap::new()).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), None).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key2", "value2", &HashMap::new(), None).unwrap();
+            wallet_service.add_record(wallet_handle, "type3", "key3", "value3", &HashMap::new(), None).unwrap();
 
             let mut search = wallet_service.search_records(wallet_handle, "type3", "{}", &_fetch_options(true, true, true)).unwrap();
 
-            let record = search.fetch_next_record().unwrap().unwrap();
-            assert_eq!("value3", record.get_value().unwrap());
-            assert_eq!(HashMap::new(), record.get_tags().unwrap().clone());
+            let record = search.fetch_next_record().unwrap().unwrap();
+            assert_eq!("value3", record.get_value().unwrap());
+            assert_eq!(HashMap::new(), record.get_tags().unwrap().clone());
+
+            assert!(search.fetch_next_record().unwrap().is_none());
+        }
+        test::cleanup_wallet("wallet_service_search_records_works");
+    }
+
+    #[test]
+    fn wallet_service_open_wallet_works_for_changed_prefix_indexes() {
+        test::cleanup_wallet("wallet_service_open_wallet_works_for_changed_prefix_indexes");
+        {
+            let wallet_service = WalletService::new();
+            let config = _config("wallet_service_open_wallet_works_for_changed_prefix_indexes");
+            let config_indexed = Config { prefix_indexes: serde_json::from_value(json!({"tag": 3})).unwrap(), ..config.clone() };
+            let search_ids = |wallet_handle: WalletHandle, query: &str| -> IndyResult<Vec<String>> {
+                let mut search = wallet_service.search_records(wallet_handle, "type", query, &_fetch_options(false, false, false))?;
+                let mut ids = Vec::new();
+                while let Some(record) = search.fetch_next_record()? {
+                    ids.push(record.get_id().to_string());
+                }
+                ids.sort();
+                Ok(ids)
+            };
+
+            wallet_service.create_wallet(&config, &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+
+            // record is added before prefix indexes are configured
+            let wallet_handle = wallet_service.open_wallet(&config, &RAW_CREDENTIAL).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &serde_json::from_value(json!({"tag": "value1"})).unwrap(), None).unwrap();
+            wallet_service.close_wallet(wallet_handle).unwrap();
+
+            // changed prefix indexes can't be applied in read-only mode
+            let res = wallet_service.open_wallet(&Config { read_only: true, ..config_indexed.clone() }, &RAW_CREDENTIAL);
+            assert_kind!(IndyErrorKind::WalletReadOnly, res);
+
+            let wallet_handle = wallet_service.open_wallet(&config_indexed, &RAW_CREDENTIAL).unwrap();
+            assert_eq!(vec!["key1"], search_ids(wallet_handle, r#"{"tag": {"$prefix": "val"}}"#).unwrap());
+            wallet_service.close_wallet(wallet_handle).unwrap();
+
+            // prefix indexes stored in the wallet are used if config doesn't set them
+            let wallet_handle = wallet_service.open_wallet(&_config_read_only("wallet_service_open_wallet_works_for_changed_prefix_indexes"), &RAW_CREDENTIAL).unwrap();
+            assert_eq!(vec!["key1"], search_ids(wallet_handle, r#"{"tag": {"$prefix": "val"}}"#).unwrap());
+            wallet_service.close_wallet(wallet_handle).unwrap();
+
+            // stale indexes are removed on change
+            let config_other = Config { prefix_indexes: serde_json::from_value(json!({"tag": 1})).unwrap(), ..config.clone() };
+            let wallet_handle = wallet_service.open_wallet(&config_other, &RAW_CREDENTIAL).unwrap();
+            assert_eq!(vec!["key1"], search_ids(wallet_handle, r#"{"tag": {"$prefix": "v"}}"#).unwrap());
+            let res = search_ids(wallet_handle, r#"{"tag": {"$prefix": "val"}}"#);
+            assert_kind!(IndyErrorKind::WalletQueryError, res);
+
+            let metadata = WalletService::_get_metadata(wallet_service.wallets.borrow().get(&wallet_handle).unwrap()).unwrap();
+            assert_eq!(&config_other.prefix_indexes, metadata.get_prefix_indexes());
+            assert!(metadata.get_pending_prefix_indexes().is_none());
+
+            wallet_service.close_wallet(wallet_handle).unwrap();
+        }
+        test::cleanup_wallet("wallet_service_open_wallet_works_for_changed_prefix_indexes");
+    }
+
+    #[test]
+    fn wallet_service_count_records_by_tag_works() {
+        test::cleanup_wallet("wallet_service_count_records_by_tag_works");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_count_records_by_tag_works"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_count_records_by_tag_works"), &RAW_CREDENTIAL).unwrap();
+
+            _check_count_by_tag(&wallet_service, wallet_handle);
+        }
+        test::cleanup_wallet("wallet_service_count_records_by_tag_works");
+    }
+
+    #[test]
+    fn wallet_service_count_records_by_tag_works_for_plugged() {
+        _cleanup("wallet_service_count_records_by_tag_works_for_plugged");
+
+        let wallet_service = WalletService::new();
+        _register_inmem_wallet(&wallet_service);
+
+        wallet_service.create_wallet(&_config_inmem(), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+        let wallet_handle = wallet_service.open_wallet(&_config_inmem(), &RAW_CREDENTIAL).unwrap();
+
+        _check_count_by_tag(&wallet_service, wallet_handle);
+    }
+
+    fn _check_count_by_tag(wallet_service: &WalletService, wallet_handle: WalletHandle) {
+        for (id, cred_def_id, schema_name) in &[("key1", "cd1", "gvt"), ("key2", "cd2", "gvt"), ("key3", "cd1", "xyz")] {
+            let tags = serde_json::from_str::<Tags>(&format!(r#"{{"cred_def_id": "{}", "~schema_name": "{}"}}"#, cred_def_id, schema_name)).unwrap();
+            wallet_service.add_record(wallet_handle, "type", id, "value", &tags, None).unwrap();
+        }
+        wallet_service.add_record(wallet_handle, "type", "key4", "value", &HashMap::new(), None).unwrap();
+        wallet_service.add_record(wallet_handle, "type2", "key5", "value", &serde_json::from_str::<Tags>(r#"{"cred_def_id": "cd3"}"#).unwrap(), None).unwrap();
+
+        let counts = wallet_service.count_records_by_tag(wallet_handle, "type", "{}", "cred_def_id").unwrap();
+        assert_eq!(counts, vec![("cd1".to_string(), 2), ("cd2".to_string(), 1)].into_iter().collect());
+
+        let counts = wallet_service.count_records_by_tag(wallet_handle, "type", r#"{"~schema_name": "gvt"}"#, "cred_def_id").unwrap();
+        assert_eq!(counts, vec![("cd1".to_string(), 1), ("cd2".to_string(), 1)].into_iter().collect());
+
+        let values = wallet_service.get_distinct_record_tag_values(wallet_handle, "type", "{}", "~schema_name").unwrap();
+        assert_eq!(values, vec!["gvt".to_string(), "xyz".to_string()]);
+
+        let res = wallet_service.count_records_by_tag(INVALID_WALLET_HANDLE, "type", "{}", "cred_def_id");
+        assert_kind!(IndyErrorKind::InvalidWalletHandle, res);
+    }
+
+    #[test]
+    fn wallet_service_search_records_works_for_sort_and_page() {
+        test::cleanup_wallet("wallet_service_search_records_works_for_sort_and_page");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_search_records_works_for_sort_and_page"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_search_records_works_for_sort_and_page"), &RAW_CREDENTIAL).unwrap();
+
+            for (id, order) in &[("key1", "2"), ("key2", "3"), ("key3", "1")] {
+                let tags = serde_json::from_str::<Tags>(&format!(r#"{{"~order": "{}"}}"#, order)).unwrap();
+                wallet_service.add_record(wallet_handle, "type", id, "value", &tags, None).unwrap();
+            }
+
+            let options = r#"{"sortBy": {"tagName": "~order"}, "offset": 1, "limit": 1}"#;
+            let mut search = wallet_service.search_records(wallet_handle, "type", "{}", options).unwrap();
+            assert_eq!("key1", search.fetch_next_record().unwrap().unwrap().get_id());
+            assert!(search.fetch_next_record().unwrap().is_none());
 
+            let options = r#"{"sortBy": {"tagName": "~order", "order": "desc"}}"#;
+            let mut search = wallet_service.search_records(wallet_handle, "type", "{}", options).unwrap();
+            assert_eq!("key2", search.fetch_next_record().unwrap().unwrap().get_id());
+            assert_eq!("key1", search.fetch_next_record().unwrap().unwrap().get_id());
+            assert_eq!("key3", search.fetch_next_record().unwrap().unwrap().get_id());
             assert!(search.fetch_next_record().unwrap().is_none());
+
+            let res = wallet_service.search_records(wallet_handle, "type", "{}", r#"{"sortBy": {"tagName": "order"}}"#);
+            assert_kind!(IndyErrorKind::WalletQueryError, res);
         }
-        test::cleanup_wallet("wallet_service_search_records_works");
+        test::cleanup_wallet("wallet_service_search_records_works_for_sort_and_page");
     }
 
     #[test]
@@ -1775,9 +3842,9 @@ mod tests {
         wallet_service.create_wallet(&_config_inmem(), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
         let wallet_handle = wallet_service.open_wallet(&_config_inmem(), &RAW_CREDENTIAL).unwrap();
 
-        wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
-        wallet_service.add_record(wallet_handle, "type", "key2", "value2", &HashMap::new()).unwrap();
-        wallet_service.add_record(wallet_handle, "type3", "key3", "value3", &HashMap::new()).unwrap();
+        wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), None).unwrap();
+        wallet_service.add_record(wallet_handle, "type", "key2", "value2", &HashMap::new(), None).unwrap();
+        wallet_service.add_record(wallet_handle, "type3", "key3", "value3", &HashMap::new(), None).unwrap();
 
         let mut search = wallet_service.search_records(wallet_handle, "type3", "{}", &_fetch_options(true, true, true)).unwrap();
 
@@ -1800,7 +3867,7 @@ mod tests {
             wallet_service.create_wallet(config, &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
             let wallet_handle = wallet_service.open_wallet(config, &RAW_CREDENTIAL).unwrap();
 
-            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), None).unwrap();
             let record = wallet_service.get_record(wallet_handle, "type", "key1", &_fetch_options(true, true, true)).unwrap();
             assert_eq!("type", record.get_type().unwrap());
             assert_eq!("value1", record.get_value().unwrap());
@@ -1835,7 +3902,7 @@ mod tests {
             wallet_service.create_wallet(config, &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
             let wallet_handle = wallet_service.open_wallet(config, &RAW_CREDENTIAL).unwrap();
 
-            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), None).unwrap();
             let record = wallet_service.get_record(wallet_handle, "type", "key1", &_fetch_options(true, true, true)).unwrap();
             assert_eq!("type", record.get_type().unwrap());
             assert_eq!("value1", record.get_value().unwrap());
@@ -1870,7 +3937,7 @@ mod tests {
             wallet_service.create_wallet(config, &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
             let wallet_handle = wallet_service.open_wallet(config, &RAW_CREDENTIAL).unwrap();
 
-            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), None).unwrap();
             let record = wallet_service.get_record(wallet_handle, "type", "key1", &_fetch_options(true, true, true)).unwrap();
             assert_eq!("type", record.get_type().unwrap());
             assert_eq!("value1", record.get_value().unwrap());
@@ -1933,7 +4000,7 @@ mod tests {
             wallet_service.create_wallet(&_config("wallet_service_export_wallet_1_item"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
             let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_export_wallet_1_item"), &RAW_CREDENTIAL).unwrap();
 
-            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), None).unwrap();
             wallet_service.get_record(wallet_handle, "type", "key1", "{}").unwrap();
 
             let export_path = remove_exported_wallet(&export_config);
@@ -1954,7 +4021,7 @@ mod tests {
             wallet_service.create_wallet(&_config("wallet_service_export_wallet_1_item_interactive_method"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
             let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_export_wallet_1_item_interactive_method"), &RAW_CREDENTIAL).unwrap();
 
-            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), None).unwrap();
             wallet_service.get_record(wallet_handle, "type", "key1", "{}").unwrap();
 
             let export_path = remove_exported_wallet(&export_config);
@@ -1975,7 +4042,7 @@ mod tests {
             wallet_service.create_wallet(&_config("wallet_service_export_wallet_1_item_raw_method"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
             let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_export_wallet_1_item_raw_method"), &RAW_CREDENTIAL).unwrap();
 
-            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), None).unwrap();
             wallet_service.get_record(wallet_handle, "type", "key1", "{}").unwrap();
 
             let export_path = remove_exported_wallet(&export_config);
@@ -2039,7 +4106,7 @@ mod tests {
             wallet_service.create_wallet(&_config("wallet_service_export_import_wallet_1_item"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
             let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_export_import_wallet_1_item"), &RAW_CREDENTIAL).unwrap();
 
-            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), None).unwrap();
             wallet_service.get_record(wallet_handle, "type", "key1", "{}").unwrap();
 
             let (kdd, master_key) = _export_key_raw("key_wallet_service_export_import_wallet_1_item");
@@ -2059,6 +4126,45 @@ mod tests {
         test::cleanup_wallet("wallet_service_export_import_wallet_1_item");
     }
 
+    #[test]
+    fn wallet_service_export_import_wallet_works_for_expiry() {
+        test::cleanup_wallet("wallet_service_export_import_wallet_works_for_expiry");
+        let export_config = _export_config_raw("wallet_service_export_import_wallet_works_for_expiry");
+        {
+            let wallet_service = WalletService::new();
+            let config = _config("wallet_service_export_import_wallet_works_for_expiry");
+            wallet_service.create_wallet(&config, &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&config, &RAW_CREDENTIAL).unwrap();
+
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), Some(u64::max_value() >> 1)).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key2", "value2", &HashMap::new(), None).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key3", "value3", &HashMap::new(), Some(1)).unwrap();
+
+            let (kdd, master_key) = _export_key_raw("key_wallet_service_export_import_wallet_works_for_expiry");
+            let _export_path = remove_exported_wallet(&export_config);
+            wallet_service.export_wallet(wallet_handle, &export_config, 0, (&kdd, &master_key)).unwrap();
+
+            wallet_service.close_wallet(wallet_handle).unwrap();
+            wallet_service.delete_wallet(&config, &RAW_CREDENTIAL).unwrap();
+
+            wallet_service.import_wallet(&config, &RAW_CREDENTIAL, &export_config).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&config, &RAW_CREDENTIAL).unwrap();
+
+            let record = wallet_service.get_record(wallet_handle, "type", "key1", "{}").unwrap();
+            assert_eq!(Some(u64::max_value() >> 1), record.get_expires_at());
+
+            let record = wallet_service.get_record(wallet_handle, "type", "key2", "{}").unwrap();
+            assert_eq!(None, record.get_expires_at());
+
+            let res = wallet_service.get_record(wallet_handle, "type", "key3", "{}");
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+            wallet_service.close_wallet(wallet_handle).unwrap();
+        }
+        let _export_path = remove_exported_wallet(&export_config);
+        test::cleanup_wallet("wallet_service_export_import_wallet_works_for_expiry");
+    }
+
     #[test]
     fn wallet_service_export_import_wallet_1_item_for_interactive_method() {
         test::cleanup_wallet("wallet_service_export_import_wallet_1_item_for_interactive_method");
@@ -2068,7 +4174,7 @@ mod tests {
             wallet_service.create_wallet(&_config("wallet_service_export_import_wallet_1_item_for_interactive_method"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
             let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_export_import_wallet_1_item_for_interactive_method"), &RAW_CREDENTIAL).unwrap();
 
-            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), None).unwrap();
             wallet_service.get_record(wallet_handle, "type", "key1", "{}").unwrap();
 
             let (kdd, master_key) = _export_key_interactive("wallet_service_export_import_wallet_1_item_for_interactive_method");
@@ -2096,7 +4202,7 @@ mod tests {
             wallet_service.create_wallet(&_config("wallet_service_export_import_wallet_1_item_for_moderate_method"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
             let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_export_import_wallet_1_item_for_moderate_method"), &RAW_CREDENTIAL).unwrap();
 
-            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), None).unwrap();
             wallet_service.get_record(wallet_handle, "type", "key1", "{}").unwrap();
 
             let (kdd, master_key) = _export_key_raw("key_wallet_service_export_import_wallet_1_item_for_moderate_method");
@@ -2125,7 +4231,7 @@ mod tests {
             wallet_service.create_wallet(config, &ARGON_INT_CREDENTIAL, (&INTERACTIVE_KDD, &INTERACTIVE_MASTER_KEY)).unwrap();
             let wallet_handle = wallet_service.open_wallet(config, &ARGON_INT_CREDENTIAL).unwrap();
 
-            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), None).unwrap();
             wallet_service.get_record(wallet_handle, "type", "key1", "{}").unwrap();
 
             let (kdd, master_key) = _export_key_interactive("wallet_service_export_import_wallet_1_item_for_export_interactive_import_as_raw");
@@ -2154,7 +4260,7 @@ mod tests {
             wallet_service.create_wallet(config, &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
             let wallet_handle = wallet_service.open_wallet(config, &RAW_CREDENTIAL).unwrap();
 
-            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), None).unwrap();
             wallet_service.get_record(wallet_handle, "type", "key1", "{}").unwrap();
 
             let (kdd, master_key) = _export_key_interactive("wallet_service_export_import_wallet_1_item_for_export_raw_import_as_interactive");
@@ -2198,6 +4304,498 @@ mod tests {
         test::cleanup_wallet("wallet_service_export_import_wallet_if_empty");
     }
 
+    #[test]
+    fn wallet_service_export_import_wallet_to_writer_works_for_types_filter() {
+        test::cleanup_wallet("wallet_service_export_import_wallet_to_writer_works_for_types_filter");
+        {
+            let wallet_service = WalletService::new();
+            let config: &Config = &_config("wallet_service_export_import_wallet_to_writer_works_for_types_filter");
+            wallet_service.create_wallet(config, &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(config, &RAW_CREDENTIAL).unwrap();
+
+            wallet_service.add_record(wallet_handle, "type1", "key1", "value1", &HashMap::new(), None).unwrap();
+            wallet_service.add_record(wallet_handle, "type2", "key2", "value2", &HashMap::new(), None).unwrap();
+
+            let mut export_config = _export_config_raw("wallet_service_export_import_wallet_to_writer_works_for_types_filter");
+            export_config.path = String::new();
+            export_config.types = Some(vec!["type1".to_string()]);
+
+            let (kdd, master_key) = _calc_key(&export_config);
+            let mut output: Vec<u8> = Vec::new();
+            wallet_service.export_wallet_to_writer(wallet_handle, &export_config, 0, (&kdd, &master_key), &mut output).unwrap();
+
+            wallet_service.close_wallet(wallet_handle).unwrap();
+            wallet_service.delete_wallet(config, &RAW_CREDENTIAL).unwrap();
+
+            let (wallet_handle, key_data, import_key_data) =
+                wallet_service.import_wallet_prepare_from_reader(config, &RAW_CREDENTIAL, &export_config, Box::new(::std::io::Cursor::new(output))).unwrap();
+            let import_key = import_key_data.calc_master_key().unwrap();
+            let master_key = key_data.calc_master_key().unwrap();
+            wallet_service.import_wallet_continue(wallet_handle, config, &RAW_CREDENTIAL, (import_key, master_key)).unwrap();
+
+            let wallet_handle = wallet_service.open_wallet(config, &RAW_CREDENTIAL).unwrap();
+            wallet_service.get_record(wallet_handle, "type1", "key1", "{}").unwrap();
+
+            let res = wallet_service.get_record(wallet_handle, "type2", "key2", "{}");
+            assert_eq!(IndyErrorKind::WalletItemNotFound, res.unwrap_err().kind());
+        }
+        test::cleanup_wallet("wallet_service_export_import_wallet_to_writer_works_for_types_filter");
+    }
+
+    #[test]
+    fn wallet_service_export_import_wallet_to_writer_works_for_version2() {
+        test::cleanup_wallet("wallet_service_export_import_wallet_to_writer_works_for_version2");
+        {
+            let wallet_service = WalletService::new();
+            let config: &Config = &_config("wallet_service_export_import_wallet_to_writer_works_for_version2");
+            wallet_service.create_wallet(config, &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(config, &RAW_CREDENTIAL).unwrap();
+
+            wallet_service.add_record(wallet_handle, "type1", "key1", "value1", &HashMap::new(), None).unwrap();
+            wallet_service.add_record(wallet_handle, "type2", "key2", "value2", &HashMap::new(), None).unwrap();
+
+            let mut export_config = _export_config_raw("wallet_service_export_import_wallet_to_writer_works_for_version2");
+            export_config.path = String::new();
+            export_config.tolerant = true;
+
+            let (kdd, master_key) = _calc_key(&export_config);
+            let mut output: Vec<u8> = Vec::new();
+            wallet_service.export_wallet_to_writer(wallet_handle, &export_config, EXPORT_VERSION_2, (&kdd, &master_key), &mut output).unwrap();
+
+            wallet_service.close_wallet(wallet_handle).unwrap();
+            wallet_service.delete_wallet(config, &RAW_CREDENTIAL).unwrap();
+
+            let (wallet_handle, key_data, import_key_data) =
+                wallet_service.import_wallet_prepare_from_reader(config, &RAW_CREDENTIAL, &export_config, Box::new(::std::io::Cursor::new(output))).unwrap();
+            let import_key = import_key_data.calc_master_key().unwrap();
+            let master_key = key_data.calc_master_key().unwrap();
+            let report = wallet_service.import_wallet_continue(wallet_handle, config, &RAW_CREDENTIAL, (import_key, master_key)).unwrap();
+            assert_eq!(ImportReport { imported: 2, skipped: 0, expected: Some(2) }, report);
+
+            let wallet_handle = wallet_service.open_wallet(config, &RAW_CREDENTIAL).unwrap();
+            wallet_service.get_record(wallet_handle, "type1", "key1", "{}").unwrap();
+            wallet_service.get_record(wallet_handle, "type2", "key2", "{}").unwrap();
+        }
+        test::cleanup_wallet("wallet_service_export_import_wallet_to_writer_works_for_version2");
+    }
+
+    #[test]
+    fn wallet_service_migrate_wallet_works() {
+        test::cleanup_wallet("wallet_service_migrate_wallet_works_src");
+        test::cleanup_wallet("wallet_service_migrate_wallet_works_dst");
+        {
+            let wallet_service = WalletService::new();
+            let src_config = _config("wallet_service_migrate_wallet_works_src");
+            let dst_config = _config("wallet_service_migrate_wallet_works_dst");
+
+            wallet_service.create_wallet(&src_config, &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&src_config, &RAW_CREDENTIAL).unwrap();
+
+            // more than a single batch of migration
+            let tags = serde_json::from_str::<Tags>(r#"{"tag1": "value1", "~tag2": "value2"}"#).unwrap();
+            for i in 0..(KEYS_ROTATION_TEST_RECORDS) {
+                wallet_service.add_record(wallet_handle, "type", &format!("key{}", i), "value", &tags, None).unwrap();
+            }
+
+            wallet_service.close_wallet(wallet_handle).unwrap();
+
+            let migrated = wallet_service.migrate_wallet(&src_config, &RAW_CREDENTIAL, &dst_config, &_credentials_for_new_key_raw()).unwrap();
+            assert_eq!(KEYS_ROTATION_TEST_RECORDS, migrated);
+
+            let wallet_handle = wallet_service.open_wallet(&dst_config, &_credentials_for_new_key_raw()).unwrap();
+
+            let record = wallet_service.get_record(wallet_handle, "type", "key1", &_fetch_options(true, true, true)).unwrap();
+            assert_eq!("value", record.get_value().unwrap());
+            assert_eq!(&tags, record.get_tags().unwrap());
+
+            let search = wallet_service.search_records(wallet_handle, "type", r#"{"tag1": "value1", "~tag2": "value2"}"#, r#"{"retrieveTotalCount": true}"#).unwrap();
+            assert_eq!(Some(KEYS_ROTATION_TEST_RECORDS), search.get_total_count().unwrap());
+
+            wallet_service.close_wallet(wallet_handle).unwrap();
+
+            // source wallet is kept
+            let wallet_handle = wallet_service.open_wallet(&src_config, &RAW_CREDENTIAL).unwrap();
+            wallet_service.get_record(wallet_handle, "type", "key1", "{}").unwrap();
+            wallet_service.close_wallet(wallet_handle).unwrap();
+        }
+        test::cleanup_wallet("wallet_service_migrate_wallet_works_src");
+        test::cleanup_wallet("wallet_service_migrate_wallet_works_dst");
+    }
+
+    #[test]
+    fn wallet_service_migrate_wallet_works_for_other_storage_type() {
+        test::cleanup_wallet("wallet_service_migrate_wallet_works_for_other_storage_type");
+        {
+            let wallet_service = WalletService::new();
+            let src_config = _config("wallet_service_migrate_wallet_works_for_other_storage_type");
+            let dst_config = Config {
+                id: "wallet_service_migrate_wallet_works_for_other_storage_type".to_string(),
+                storage_type: Some("indy_inmem".to_string()),
+                storage_config: None,
+                read_only: false,
+                profile: None,
+                prefix_indexes: HashMap::new(),
+            };
+
+            wallet_service.create_wallet(&src_config, &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&src_config, &RAW_CREDENTIAL).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), None).unwrap();
+            wallet_service.close_wallet(wallet_handle).unwrap();
+
+            let migrated = wallet_service.migrate_wallet(&src_config, &RAW_CREDENTIAL, &dst_config, &ARGON_INT_CREDENTIAL).unwrap();
+            assert_eq!(1, migrated);
+
+            let wallet_handle = wallet_service.open_wallet(&dst_config, &ARGON_INT_CREDENTIAL).unwrap();
+            let record = wallet_service.get_record(wallet_handle, "type", "key1", &_fetch_options(false, true, false)).unwrap();
+            assert_eq!("value1", record.get_value().unwrap());
+            wallet_service.close_wallet(wallet_handle).unwrap();
+
+            wallet_service.delete_wallet(&dst_config, &ARGON_INT_CREDENTIAL).unwrap();
+        }
+        test::cleanup_wallet("wallet_service_migrate_wallet_works_for_other_storage_type");
+    }
+
+    #[test]
+    fn wallet_service_migrate_wallet_works_for_expiry_and_plugged() {
+        _cleanup("wallet_service_migrate_wallet_works_for_expiry_and_plugged");
+        {
+            let wallet_service = WalletService::new();
+            _register_inmem_wallet(&wallet_service);
+
+            let src_config = _config("wallet_service_migrate_wallet_works_for_expiry_and_plugged");
+            let dst_config = _config_inmem();
+
+            wallet_service.create_wallet(&src_config, &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&src_config, &RAW_CREDENTIAL).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), Some(u64::max_value() >> 1)).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key2", "value2", &HashMap::new(), None).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key3", "value3", &HashMap::new(), Some(1)).unwrap();
+            wallet_service.close_wallet(wallet_handle).unwrap();
+
+            let migrated = wallet_service.migrate_wallet(&src_config, &RAW_CREDENTIAL, &dst_config, &RAW_CREDENTIAL).unwrap();
+            assert_eq!(2, migrated);
+
+            let wallet_handle = wallet_service.open_wallet(&dst_config, &RAW_CREDENTIAL).unwrap();
+
+            let record = wallet_service.get_record(wallet_handle, "type", "key1", "{}").unwrap();
+            assert_eq!(Some(u64::max_value() >> 1), record.get_expires_at());
+
+            let record = wallet_service.get_record(wallet_handle, "type", "key2", "{}").unwrap();
+            assert_eq!(None, record.get_expires_at());
+
+            let res = wallet_service.get_record(wallet_handle, "type", "key3", "{}");
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+            wallet_service.close_wallet(wallet_handle).unwrap();
+        }
+        _cleanup("wallet_service_migrate_wallet_works_for_expiry_and_plugged");
+    }
+
+    #[test]
+    fn wallet_service_migrate_wallet_returns_error_for_invalid_source_key() {
+        test::cleanup_wallet("wallet_service_migrate_wallet_returns_error_for_invalid_source_key_src");
+        test::cleanup_wallet("wallet_service_migrate_wallet_returns_error_for_invalid_source_key_dst");
+        {
+            let wallet_service = WalletService::new();
+            let src_config = _config("wallet_service_migrate_wallet_returns_error_for_invalid_source_key_src");
+            let dst_config = _config("wallet_service_migrate_wallet_returns_error_for_invalid_source_key_dst");
+
+            wallet_service.create_wallet(&src_config, &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+
+            let res = wallet_service.migrate_wallet(&src_config, &_credentials_invalid_raw(), &dst_config, &RAW_CREDENTIAL);
+            assert_kind!(IndyErrorKind::WalletAccessFailed, res);
+
+            let res = wallet_service.open_wallet(&dst_config, &RAW_CREDENTIAL);
+            assert_kind!(IndyErrorKind::WalletNotFound, res);
+        }
+        test::cleanup_wallet("wallet_service_migrate_wallet_returns_error_for_invalid_source_key_src");
+        test::cleanup_wallet("wallet_service_migrate_wallet_returns_error_for_invalid_source_key_dst");
+    }
+
+    #[test]
+    fn wallet_service_migrate_wallet_returns_error_for_opened_source() {
+        test::cleanup_wallet("wallet_service_migrate_wallet_returns_error_for_opened_source_src");
+        {
+            let wallet_service = WalletService::new();
+            let src_config = _config("wallet_service_migrate_wallet_returns_error_for_opened_source_src");
+            let dst_config = _config("wallet_service_migrate_wallet_returns_error_for_opened_source_dst");
+
+            wallet_service.create_wallet(&src_config, &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            wallet_service.open_wallet(&src_config, &RAW_CREDENTIAL).unwrap();
+
+            let res = wallet_service.migrate_wallet(&src_config, &RAW_CREDENTIAL, &dst_config, &RAW_CREDENTIAL);
+            assert_kind!(IndyErrorKind::WalletAlreadyOpened, res);
+
+            let res = wallet_service.migrate_wallet(&src_config, &RAW_CREDENTIAL, &src_config, &RAW_CREDENTIAL);
+            assert_kind!(IndyErrorKind::InvalidStructure, res);
+        }
+        test::cleanup_wallet("wallet_service_migrate_wallet_returns_error_for_opened_source_src");
+    }
+
+    #[test]
+    fn wallet_service_profiles_work() {
+        test::cleanup_wallet("wallet_service_profiles_work");
+        {
+            let wallet_service = WalletService::new();
+            let config = _config("wallet_service_profiles_work");
+            let profile_config = _config_profile("wallet_service_profiles_work", "profile1");
+            let profile_credentials = _credentials_for_new_key_raw();
+            let profile_kdd = wallet_service.new_key_derivation_data(&profile_credentials.key, &profile_credentials.key_derivation_method, None).unwrap();
+            let profile_master_key = profile_kdd.calc_master_key().unwrap();
+
+            wallet_service.create_wallet(&config, &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            wallet_service.create_wallet(&profile_config, &profile_credentials, (&profile_kdd, &profile_master_key)).unwrap();
+
+            let res = wallet_service.create_wallet(&profile_config, &profile_credentials, (&profile_kdd, &profile_master_key));
+            assert_kind!(IndyErrorKind::WalletAlreadyExists, res);
+
+            assert_eq!(vec!["profile1".to_string()], wallet_service.list_profiles(&config, &RAW_CREDENTIAL).unwrap());
+
+            let wallet_handle = wallet_service.open_wallet(&config, &RAW_CREDENTIAL).unwrap();
+            let profile_handle = wallet_service.open_wallet(&profile_config, &profile_credentials).unwrap();
+
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), None).unwrap();
+            wallet_service.add_record(profile_handle, "type", "key1", "value2", &HashMap::new(), None).unwrap();
+            wallet_service.add_record(profile_handle, "type", "key2", "value2", &HashMap::new(), None).unwrap();
+
+            let record = wallet_service.get_record(wallet_handle, "type", "key1", &_fetch_options(false, true, false)).unwrap();
+            assert_eq!("value1", record.get_value().unwrap());
+
+            let record = wallet_service.get_record(profile_handle, "type", "key1", &_fetch_options(false, true, false)).unwrap();
+            assert_eq!("value2", record.get_value().unwrap());
+
+            let res = wallet_service.get_record(wallet_handle, "type", "key2", &_fetch_options(false, true, false));
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+            let search = wallet_service.search_records(profile_handle, "type", "{}", r#"{"retrieveTotalCount": true}"#).unwrap();
+            assert_eq!(Some(2), search.get_total_count().unwrap());
+
+            wallet_service.close_wallet(wallet_handle).unwrap();
+
+            let res = wallet_service.delete_wallet_prepare(&config, &RAW_CREDENTIAL);
+            assert_kind!(IndyErrorKind::InvalidState, res);
+
+            wallet_service.close_wallet(profile_handle).unwrap();
+
+            wallet_service.delete_wallet(&profile_config, &profile_credentials).unwrap();
+            assert!(wallet_service.list_profiles(&config, &RAW_CREDENTIAL).unwrap().is_empty());
+
+            let wallet_handle = wallet_service.open_wallet(&config, &RAW_CREDENTIAL).unwrap();
+            let record = wallet_service.get_record(wallet_handle, "type", "key1", &_fetch_options(false, true, false)).unwrap();
+            assert_eq!("value1", record.get_value().unwrap());
+            wallet_service.close_wallet(wallet_handle).unwrap();
+        }
+        test::cleanup_wallet("wallet_service_profiles_work");
+    }
+
+    #[test]
+    fn wallet_service_open_wallet_returns_error_for_profile_and_invalid_key() {
+        test::cleanup_wallet("wallet_service_open_wallet_returns_error_for_profile_and_invalid_key");
+        {
+            let wallet_service = WalletService::new();
+            let config = _config("wallet_service_open_wallet_returns_error_for_profile_and_invalid_key");
+            let profile_config = _config_profile("wallet_service_open_wallet_returns_error_for_profile_and_invalid_key", "profile1");
+            let profile_credentials = _credentials_for_new_key_raw();
+            let profile_kdd = wallet_service.new_key_derivation_data(&profile_credentials.key, &profile_credentials.key_derivation_method, None).unwrap();
+            let profile_master_key = profile_kdd.calc_master_key().unwrap();
+
+            wallet_service.create_wallet(&config, &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            wallet_service.create_wallet(&profile_config, &profile_credentials, (&profile_kdd, &profile_master_key)).unwrap();
+
+            // key of the default profile doesn't open other profiles
+            let res = wallet_service.open_wallet(&profile_config, &RAW_CREDENTIAL);
+            assert_kind!(IndyErrorKind::WalletAccessFailed, res);
+
+            let res = wallet_service.list_profiles(&config, &profile_credentials);
+            assert_kind!(IndyErrorKind::WalletAccessFailed, res);
+
+            let res = wallet_service.open_wallet(&_config_profile("wallet_service_open_wallet_returns_error_for_profile_and_invalid_key", "unknown"), &RAW_CREDENTIAL);
+            assert_kind!(IndyErrorKind::WalletNotFound, res);
+        }
+        test::cleanup_wallet("wallet_service_open_wallet_returns_error_for_profile_and_invalid_key");
+    }
+
+    #[test]
+    fn wallet_service_get_wallet_id_works_for_separator_in_id() {
+        let profile_id = WalletService::_get_wallet_id(&_config_profile("wallet#", "profile"));
+
+        assert_ne!(profile_id, WalletService::_get_wallet_id(&_config("wallet##profile")));
+        assert_ne!(profile_id, WalletService::_get_wallet_id(&_config_profile("wallet", "#profile")));
+        assert_ne!(WalletService::_get_wallet_id(&_config("wallet#profile")), WalletService::_get_wallet_id(&_config_profile("wallet", "profile")));
+        assert_ne!(WalletService::_get_wallet_id(&_config_profile("wallet\\", "profile")), WalletService::_get_wallet_id(&_config("wallet\\#profile")));
+    }
+
+    #[test]
+    fn wallet_service_create_wallet_returns_error_for_profile_of_unsupported_storage() {
+        let wallet_service = WalletService::new();
+        let config = Config {
+            profile: Some("profile1".to_string()),
+            storage_type: Some("indy_inmem".to_string()),
+            .._config("wallet_service_create_wallet_returns_error_for_profile_of_unsupported_storage")
+        };
+
+        let res = wallet_service.create_wallet(&config, &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY));
+        assert_kind!(IndyErrorKind::InvalidState, res);
+    }
+
+    #[test]
+    fn wallet_service_async_api_works() {
+        _cleanup("wallet_service_async_api_works");
+        _cleanup("wallet_service_async_api_works_imported");
+        {
+            let wallet_service = WalletService::new();
+            let config = _config("wallet_service_async_api_works");
+            let imported_config = _config("wallet_service_async_api_works_imported");
+            let export_config = _export_config_raw("wallet_service_async_api_works");
+
+            block_on(async {
+                wallet_service.create_wallet_async(&config, &RAW_CREDENTIAL).await.unwrap();
+
+                let wallet_handle = wallet_service.open_wallet_async(&config, &RAW_CREDENTIAL).await.unwrap();
+                wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), None).unwrap();
+
+                wallet_service.rotate_item_keys_async(wallet_handle, &RAW_CREDENTIAL).await.unwrap();
+                wallet_service.export_wallet_async(wallet_handle, &export_config).await.unwrap();
+                wallet_service.close_wallet(wallet_handle).unwrap();
+
+                wallet_service.import_wallet_async(&imported_config, &RAW_CREDENTIAL, &export_config).await.unwrap();
+
+                let wallet_handle = wallet_service.open_wallet_async(&imported_config, &RAW_CREDENTIAL).await.unwrap();
+                let record = wallet_service.get_record(wallet_handle, "type", "key1", &_fetch_options(false, true, false)).unwrap();
+                assert_eq!("value1", record.get_value().unwrap());
+                wallet_service.close_wallet(wallet_handle).unwrap();
+
+                assert!(wallet_service.list_profiles_async(&config, &RAW_CREDENTIAL).await.unwrap().is_empty());
+
+                wallet_service.delete_wallet_async(&config, &RAW_CREDENTIAL).await.unwrap();
+                wallet_service.delete_wallet_async(&imported_config, &RAW_CREDENTIAL).await.unwrap();
+            });
+
+            let res = wallet_service.open_wallet(&config, &RAW_CREDENTIAL);
+            assert_kind!(IndyErrorKind::WalletNotFound, res);
+        }
+        _cleanup("wallet_service_async_api_works");
+        _cleanup("wallet_service_async_api_works_imported");
+    }
+
+    #[test]
+    fn wallet_service_handle_works() {
+        _cleanup("wallet_service_handle_works");
+        {
+            let wallet_service = WalletServiceHandle::new();
+            let config = _config("wallet_service_handle_works");
+
+            let (sender, receiver) = std::sync::mpsc::channel();
+            let handle = wallet_service.clone();
+
+            // futures are Send, so they can be awaited on other threads
+            std::thread::spawn(move || {
+                sender.send(block_on(async {
+                    handle.create_wallet(&config, &RAW_CREDENTIAL).await?;
+                    handle.open_wallet(&config, &RAW_CREDENTIAL).await
+                })).unwrap();
+            });
+
+            let wallet_handle = receiver.recv().unwrap().unwrap();
+
+            block_on(async {
+                wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), None).await.unwrap();
+                wallet_service.add_record(wallet_handle, "type", "key2", "value2", &HashMap::new(), None).await.unwrap();
+                wallet_service.update_record_value(wallet_handle, "type", "key1", "value3").await.unwrap();
+                wallet_service.update_record_tags(wallet_handle, "type", "key1", &serde_json::from_str::<Tags>(r#"{"tag1": "a", "tag2": "b"}"#).unwrap()).await.unwrap();
+                wallet_service.add_record_tags(wallet_handle, "type", "key1", &serde_json::from_str::<Tags>(r#"{"tag3": "c"}"#).unwrap()).await.unwrap();
+                wallet_service.delete_record_tags(wallet_handle, "type", "key1", &["tag2"]).await.unwrap();
+
+                let record = wallet_service.get_record(wallet_handle, "type", "key1", &_fetch_options(false, true, true)).await.unwrap();
+                assert_eq!("value3", record.get_value().unwrap());
+                assert_eq!(&serde_json::from_str::<Tags>(r#"{"tag1": "a", "tag3": "c"}"#).unwrap(), record.get_tags().unwrap());
+
+                let (total_count, records) = wallet_service.search_records(wallet_handle, "type", "{}", r#"{"retrieveTotalCount": true}"#).await.unwrap();
+                assert_eq!(Some(2), total_count);
+                assert_eq!(2, records.len());
+
+                wallet_service.delete_record(wallet_handle, "type", "key2").await.unwrap();
+
+                let res = wallet_service.get_record(wallet_handle, "type", "key2", &_fetch_options(false, true, false)).await;
+                assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+                wallet_service.get_revision(wallet_handle).await.unwrap();
+                wallet_service.close_wallet(wallet_handle).await.unwrap();
+                wallet_service.delete_wallet(&_config("wallet_service_handle_works"), &RAW_CREDENTIAL).await.unwrap();
+            });
+        }
+        _cleanup("wallet_service_handle_works");
+    }
+
+    #[test]
+    fn wallet_service_open_wallet_async_returns_error_for_invalid_raw_key() {
+        test::cleanup_wallet("wallet_service_open_wallet_async_returns_error_for_invalid_raw_key");
+        {
+            let wallet_service = WalletService::new();
+            let config = _config("wallet_service_open_wallet_async_returns_error_for_invalid_raw_key");
+            wallet_service.create_wallet(&config, &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+
+            let res = block_on(wallet_service.open_wallet_async(&config, &_credentials_invalid_raw()));
+            assert!(res.is_err());
+
+            // pending storage is released if key derivation fails
+            assert_eq!(0, wallet_service.get_pending_for_open_count());
+        }
+        test::cleanup_wallet("wallet_service_open_wallet_async_returns_error_for_invalid_raw_key");
+    }
+
+    #[test]
+    fn wallet_service_import_wallet_delta_works() {
+        test::cleanup_wallet("wallet_service_import_wallet_delta_works");
+        {
+            let wallet_service = WalletService::new();
+            let config: &Config = &_config("wallet_service_import_wallet_delta_works");
+            wallet_service.create_wallet(config, &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(config, &RAW_CREDENTIAL).unwrap();
+
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new(), None).unwrap();
+            let revision = wallet_service.get_revision(wallet_handle).unwrap();
+
+            wallet_service.update_record_value(wallet_handle, "type", "key1", "value2").unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key2", "value2", &HashMap::new(), None).unwrap();
+            assert!(wallet_service.get_revision(wallet_handle).unwrap() > revision);
+
+            let mut export_config = _export_config_raw("wallet_service_import_wallet_delta_works");
+            export_config.path = String::new();
+            export_config.since_revision = Some(revision);
+
+            let (kdd, master_key) = _calc_key(&export_config);
+            let mut output: Vec<u8> = Vec::new();
+            wallet_service.export_wallet_to_writer(wallet_handle, &export_config, 0, (&kdd, &master_key), &mut output).unwrap();
+
+            wallet_service.delete_record(wallet_handle, "type", "key1").unwrap();
+            wallet_service.delete_record(wallet_handle, "type", "key2").unwrap();
+
+            let (listener, changes) = _listener();
+            wallet_service.subscribe(wallet_handle, listener).unwrap();
+
+            let import_key_data = wallet_service.import_wallet_delta_prepare_from_reader(wallet_handle, &export_config, Box::new(::std::io::Cursor::new(output))).unwrap();
+            let import_key = import_key_data.calc_master_key().unwrap();
+            wallet_service.import_wallet_delta_continue(wallet_handle, &import_key).unwrap();
+
+            let mut changes = changes.borrow().clone();
+            changes.sort_by(|a, b| a.1.cmp(&b.1));
+            assert_eq!(vec![
+                ("type".to_string(), "key1".to_string(), RecordChange::Add),
+                ("type".to_string(), "key2".to_string(), RecordChange::Add),
+            ], changes);
+
+            let record = wallet_service.get_record(wallet_handle, "type", "key1", &_fetch_options(false, true, false)).unwrap();
+            assert_eq!("value2", record.get_value().unwrap());
+
+            let record = wallet_service.get_record(wallet_handle, "type", "key2", &_fetch_options(false, true, false)).unwrap();
+            assert_eq!("value2", record.get_value().unwrap());
+
+            wallet_service.close_wallet(wallet_handle).unwrap();
+        }
+        test::cleanup_wallet("wallet_service_import_wallet_delta_works");
+    }
+
     #[test]
     fn wallet_service_export_import_returns_error_if_path_missing() {
         _cleanup("wallet_service_export_import_returns_error_if_path_missing");
@@ -2227,6 +4825,38 @@ mod tests {
             id: name.to_string(),
             storage_type: None,
             storage_config: None,
+            read_only: false,
+            profile: None,
+            prefix_indexes: HashMap::new(),
+        }
+    }
+
+    fn _listener() -> (RecordChangeListener, Rc<RefCell<Vec<(String, String, RecordChange)>>>) {
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        let changes_ = changes.clone();
+
+        let listener: RecordChangeListener = Box::new(move |type_: &str, id: &str, change: RecordChange| {
+            changes_.borrow_mut().push((type_.to_string(), id.to_string(), change));
+        });
+
+        (listener, changes)
+    }
+
+    fn _config_profile(name: &str, profile: &str) -> Config {
+        Config {
+            profile: Some(profile.to_string()),
+            .._config(name)
+        }
+    }
+
+    fn _config_read_only(name: &str) -> Config {
+        Config {
+            id: name.to_string(),
+            storage_type: None,
+            storage_config: None,
+            read_only: true,
+            profile: None,
+            prefix_indexes: HashMap::new(),
         }
     }
 
@@ -2235,6 +4865,9 @@ mod tests {
             id: name.to_string(),
             storage_type: Some("default".to_string()),
             storage_config: None,
+            read_only: false,
+            profile: None,
+            prefix_indexes: HashMap::new(),
         }
     }
 
@@ -2243,6 +4876,9 @@ mod tests {
             id: "w1".to_string(),
             storage_type: Some("inmem".to_string()),
             storage_config: None,
+            read_only: false,
+            profile: None,
+            prefix_indexes: HashMap::new(),
         }
     }
 
@@ -2251,9 +4887,15 @@ mod tests {
             id: name.to_string(),
             storage_type: Some("unknown".to_string()),
             storage_config: None,
+            read_only: false,
+            profile: None,
+            prefix_indexes: HashMap::new(),
         }
     }
 
+    // more than a single chunk of keys rotation
+    const KEYS_ROTATION_TEST_RECORDS: usize = 150;
+
     #[allow(non_upper_case_globals)]
     lazy_static! {
         static ref ARGON_MOD_CREDENTIAL: Credentials = Credentials {
@@ -2262,6 +4904,7 @@ mod tests {
             storage_credentials: None,
             key_derivation_method: KeyDerivationMethod::ARGON2I_MOD,
             rekey_derivation_method: KeyDerivationMethod::ARGON2I_MOD,
+            key_wrap_provider: None,
         };
     }
 
@@ -2273,6 +4916,7 @@ mod tests {
             storage_credentials: None,
             key_derivation_method: KeyDerivationMethod::ARGON2I_INT,
             rekey_derivation_method: KeyDerivationMethod::ARGON2I_INT,
+            key_wrap_provider: None,
         };
     }
 
@@ -2285,12 +4929,13 @@ mod tests {
             storage_credentials: None,
             key_derivation_method: KeyDerivationMethod::RAW,
             rekey_derivation_method: KeyDerivationMethod::RAW,
+            key_wrap_provider: None,
         };
     }
 
     #[allow(non_upper_case_globals)]
     lazy_static! {
-        static ref MODERATE_KDD: KeyDerivationData = KeyDerivationData::from_passphrase_with_new_salt("my_key", &KeyDerivationMethod::ARGON2I_MOD);
+        static ref MODERATE_KDD: KeyDerivationData = KeyDerivationData::from_passphrase_with_new_salt("my_key", &KeyDerivationMethod::ARGON2I_MOD).unwrap();
     }
 
     #[allow(non_upper_case_globals)]
@@ -2300,7 +4945,7 @@ mod tests {
 
     #[allow(non_upper_case_globals)]
     lazy_static! {
-        static ref INTERACTIVE_KDD: KeyDerivationData = KeyDerivationData::from_passphrase_with_new_salt("my_key", &KeyDerivationMethod::ARGON2I_INT);
+        static ref INTERACTIVE_KDD: KeyDerivationData = KeyDerivationData::from_passphrase_with_new_salt("my_key", &KeyDerivationMethod::ARGON2I_INT).unwrap();
     }
 
     #[allow(non_upper_case_globals)]
@@ -2310,7 +4955,7 @@ mod tests {
 
     #[allow(non_upper_case_globals)]
     lazy_static! {
-        static ref RAW_KDD: KeyDerivationData = KeyDerivationData::from_passphrase_with_new_salt("6nxtSiXFvBd593Y2DCed2dYvRY1PGK9WMtxCBjLzKgbw", &KeyDerivationMethod::RAW);
+        static ref RAW_KDD: KeyDerivationData = KeyDerivationData::from_passphrase_with_new_salt("6nxtSiXFvBd593Y2DCed2dYvRY1PGK9WMtxCBjLzKgbw", &KeyDerivationMethod::RAW).unwrap();
     }
 
     #[allow(non_upper_case_globals)]
@@ -2325,6 +4970,20 @@ mod tests {
             storage_credentials: None,
             key_derivation_method: KeyDerivationMethod::RAW,
             rekey_derivation_method: KeyDerivationMethod::RAW,
+            key_wrap_provider: None,
+        }
+    }
+
+    fn _credentials_wrapped(name: &str) -> Credentials {
+        let _ = fs::remove_file(FileKeyWrapProvider::key_path(name).unwrap());
+
+        Credentials {
+            key: name.to_string(),
+            rekey: None,
+            storage_credentials: None,
+            key_derivation_method: KeyDerivationMethod::WRAPPED,
+            rekey_derivation_method: KeyDerivationMethod::ARGON2I_MOD,
+            key_wrap_provider: Some("file".to_string()),
         }
     }
 
@@ -2335,6 +4994,7 @@ mod tests {
             storage_credentials: None,
             key_derivation_method: KeyDerivationMethod::RAW,
             rekey_derivation_method: KeyDerivationMethod::ARGON2I_MOD,
+            key_wrap_provider: None,
         }
     }
 
@@ -2345,6 +5005,7 @@ mod tests {
             storage_credentials: None,
             key_derivation_method: KeyDerivationMethod::RAW,
             rekey_derivation_method: KeyDerivationMethod::ARGON2I_INT,
+            key_wrap_provider: None,
         }
     }
 
@@ -2355,6 +5016,7 @@ mod tests {
             storage_credentials: None,
             key_derivation_method: KeyDerivationMethod::RAW,
             rekey_derivation_method: KeyDerivationMethod::RAW,
+            key_wrap_provider: None,
         }
     }
 
@@ -2365,6 +5027,7 @@ mod tests {
             storage_credentials: None,
             key_derivation_method: KeyDerivationMethod::ARGON2I_MOD,
             rekey_derivation_method: KeyDerivationMethod::ARGON2I_MOD,
+            key_wrap_provider: None,
         }
     }
 
@@ -2375,6 +5038,7 @@ mod tests {
             storage_credentials: None,
             key_derivation_method: KeyDerivationMethod::ARGON2I_INT,
             rekey_derivation_method: KeyDerivationMethod::ARGON2I_INT,
+            key_wrap_provider: None,
         }
     }
 
@@ -2385,6 +5049,7 @@ mod tests {
             storage_credentials: None,
             key_derivation_method: KeyDerivationMethod::RAW,
             rekey_derivation_method: KeyDerivationMethod::RAW,
+            key_wrap_provider: None,
         }
     }
 
@@ -2399,11 +5064,16 @@ mod tests {
             key: "export_key".to_string(),
             path: _export_file_path(name).to_str().unwrap().to_string(),
             key_derivation_method: KeyDerivationMethod::ARGON2I_MOD,
+            types: None,
+            query: None,
+            since_revision: None,
+            version: None,
+            tolerant: false,
         }
     }
 
     fn _calc_key(export_config: &ExportConfig) -> (KeyDerivationData, MasterKey) {
-        let kdd = KeyDerivationData::from_passphrase_with_new_salt(&export_config.key, &export_config.key_derivation_method);
+        let kdd = KeyDerivationData::from_passphrase_with_new_salt(&export_config.key, &export_config.key_derivation_method).unwrap();
         let master_key = kdd.calc_master_key().unwrap();
         (kdd, master_key)
     }
@@ -2417,6 +5087,11 @@ mod tests {
             key: "export_key".to_string(),
             path: _export_file_path(name).to_str().unwrap().to_string(),
             key_derivation_method: KeyDerivationMethod::ARGON2I_INT,
+            types: None,
+            query: None,
+            since_revision: None,
+            version: None,
+            tolerant: false,
         }
     }
 
@@ -2429,6 +5104,11 @@ mod tests {
             key: "6nxtSiXFvBd593Y2DCed2dYvRY1PGK9WMtxCBjLzKgbw".to_string(),
             path: _export_file_path(name).to_str().unwrap().to_string(),
             key_derivation_method: KeyDerivationMethod::RAW,
+            types: None,
+            query: None,
+            since_revision: None,
+            version: None,
+            tolerant: false,
         }
     }
 
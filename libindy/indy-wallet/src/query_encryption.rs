@@ -1,17 +1,20 @@
+use std::collections::HashMap;
+
 use indy_api_types::errors::prelude::*;
 
 use super::wallet::Keys;
 use super::language::{Operator, TargetValue, TagName};
-use super::encryption::encrypt_as_searchable;
+use super::encryption::{encrypt_as_searchable, prefix_index_tag_name};
 use indy_utils::wql::Query;
 
 // Performs encryption of WQL query
 // WQL query is provided as top-level Operator
-pub(super) fn encrypt_query(query: Query, keys: &Keys) -> IndyResult<Operator> {
-    transform(query, keys)
+// $prefix on encrypted tags is rewritten into match on blind prefix indexes configured for the wallet
+pub(super) fn encrypt_query(query: Query, keys: &Keys, prefix_indexes: &HashMap<String, usize>) -> IndyResult<Operator> {
+    transform(query, keys, prefix_indexes)
 }
 
-fn transform(query: Query, keys: &Keys) -> IndyResult<Operator> {
+fn transform(query: Query, keys: &Keys, prefix_indexes: &HashMap<String, usize>) -> IndyResult<Operator> {
     match query {
         Query::Eq(name, value) => {
             let (encrypted_name, encrypted_value) = encrypt_name_value(name, value, keys)?;
@@ -42,17 +45,7 @@ fn transform(query: Query, keys: &Keys) -> IndyResult<Operator> {
             Ok(Operator::Like(encrypted_name, encrypted_value))
         }
         Query::In(name, values) => {
-            let ename = TagName::from(name.clone())?;
-            let ename = match ename {
-                TagName::EncryptedTagName(ref name) => {
-                    let encrypted_name = encrypt_as_searchable(&name[..], &keys.tag_name_key, &keys.tags_hmac_key);
-                    TagName::EncryptedTagName(encrypted_name)
-                }
-                TagName::PlainTagName(ref name) => {
-                    let encrypted_name = encrypt_as_searchable(&name[..], &keys.tag_name_key, &keys.tags_hmac_key);
-                    TagName::PlainTagName(encrypted_name)
-                }
-            };
+            let ename = encrypt_name(name.clone(), keys)?;
             let mut encrypted_values: Vec<TargetValue> = Vec::with_capacity(values.len());
 
             for value in values {
@@ -60,23 +53,82 @@ fn transform(query: Query, keys: &Keys) -> IndyResult<Operator> {
             }
             Ok(Operator::In(ename, encrypted_values))
         }
-        Query::And(operators) => Ok(Operator::And(transform_list_operators(operators, keys)?)),
-        Query::Or(operators) => Ok(Operator::Or(transform_list_operators(operators, keys)?)),
-        Query::Not(boxed_operator) => Ok(Operator::Not(Box::new(transform(*boxed_operator, keys)?)))
+        Query::Exist(name) => Ok(Operator::Exist(encrypt_name(name, keys)?)),
+        Query::NumGt(name, value) => {
+            let (encrypted_name, encrypted_value) = encrypt_name_value(name, value, keys)?;
+            Ok(Operator::NumGt(encrypted_name, encrypted_value))
+        }
+        Query::NumGte(name, value) => {
+            let (encrypted_name, encrypted_value) = encrypt_name_value(name, value, keys)?;
+            Ok(Operator::NumGte(encrypted_name, encrypted_value))
+        }
+        Query::NumLt(name, value) => {
+            let (encrypted_name, encrypted_value) = encrypt_name_value(name, value, keys)?;
+            Ok(Operator::NumLt(encrypted_name, encrypted_value))
+        }
+        Query::NumLte(name, value) => {
+            let (encrypted_name, encrypted_value) = encrypt_name_value(name, value, keys)?;
+            Ok(Operator::NumLte(encrypted_name, encrypted_value))
+        }
+        Query::Prefix(name, value) => transform_prefix(name, value, keys, prefix_indexes),
+        Query::And(operators) => Ok(Operator::And(transform_list_operators(operators, keys, prefix_indexes)?)),
+        Query::Or(operators) => Ok(Operator::Or(transform_list_operators(operators, keys, prefix_indexes)?)),
+        Query::Not(boxed_operator) => Ok(Operator::Not(Box::new(transform(*boxed_operator, keys, prefix_indexes)?)))
     }
 }
 
-fn transform_list_operators(operators: Vec<Query>, keys: &Keys) -> IndyResult<Vec<Operator>> {
+fn transform_prefix(name: String, value: String, keys: &Keys, prefix_indexes: &HashMap<String, usize>) -> IndyResult<Operator> {
+    if name.starts_with('~') {
+        let (encrypted_name, value) = encrypt_name_value(name, value, keys)?;
+        return Ok(Operator::Prefix(encrypted_name, value));
+    }
+
+    let len = value.chars().count();
+
+    if len == 0 {
+        return Ok(Operator::Exist(encrypt_name(name, keys)?));
+    }
+
+    match prefix_indexes.get(&name) {
+        Some(max_len) if len <= *max_len => {
+            let (encrypted_name, encrypted_value) = encrypt_name_value(prefix_index_tag_name(&name, len), value, keys)?;
+            Ok(Operator::Eq(encrypted_name, encrypted_value))
+        }
+        Some(max_len) =>
+            Err(err_msg(IndyErrorKind::WalletQueryError,
+                        format!("$prefix for encrypted tag \"{}\" is longer than its prefix index: {} > {}", name, len, max_len))),
+        None =>
+            Err(err_msg(IndyErrorKind::WalletQueryError,
+                        format!("$prefix can be used for encrypted tag \"{}\" only if prefix index is configured for it", name)))
+    }
+}
+
+fn transform_list_operators(operators: Vec<Query>, keys: &Keys, prefix_indexes: &HashMap<String, usize>) -> IndyResult<Vec<Operator>> {
     let mut transformed = Vec::with_capacity(operators.len());
 
     for operator in operators {
-        let transformed_operator = transform(operator, keys)?;
+        let transformed_operator = transform(operator, keys, prefix_indexes)?;
         transformed.push(transformed_operator);
     }
 
     Ok(transformed)
 }
 
+// Encrypts a single tag name keeping its plain/encrypted kind
+fn encrypt_name(name: String, keys: &Keys) -> IndyResult<TagName> {
+    let name = match TagName::from(name)? {
+        TagName::EncryptedTagName(ref name) => {
+            let encrypted_name = encrypt_as_searchable(&name[..], &keys.tag_name_key, &keys.tags_hmac_key);
+            TagName::EncryptedTagName(encrypted_name)
+        }
+        TagName::PlainTagName(ref name) => {
+            let encrypted_name = encrypt_as_searchable(&name[..], &keys.tag_name_key, &keys.tags_hmac_key);
+            TagName::PlainTagName(encrypted_name)
+        }
+    };
+    Ok(name)
+}
+
 // Encrypts a single tag name, tag value pair.
 // If the tag name is EncryptedTagName enum variant, encrypts both the tag name and the tag value
 // If the tag name is PlainTagName enum variant, encrypts only the tag name
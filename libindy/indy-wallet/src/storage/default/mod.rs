@@ -5,13 +5,14 @@ use std::fs;
 use std::rc::Rc;
 
 use rusqlite;
+use rusqlite::OptionalExtension;
 use serde_json;
 
 use indy_api_types::errors::prelude::*;
 use crate::language;
 use indy_utils::environment;
 
-use super::{EncryptedValue, StorageIterator, StorageRecord, Tag, TagName, WalletStorage, WalletStorageType};
+use super::{EncryptedValue, StorageIterator, StorageOperation, StorageRecord, Tag, TagName, WalletStorage, WalletStorageType};
 use super::super::{RecordOptions, SearchOptions};
 
 use self::owning_ref::OwningHandle;
@@ -86,6 +87,207 @@ const _CREATE_SCHEMA: &str = "
     END TRANSACTION;
 ";
 
+// Revision of item is bumped on each mutation of the item or its tags.
+// Deleted items are kept as tombstones to be able to export deletions.
+const _CREATE_REVISIONS_SCHEMA: &str = "
+    /*** Item Revisions Table ***/
+
+    CREATE TABLE item_revisions(
+        type NOT NULL,
+        name NOT NULL,
+        revision INTEGER NOT NULL,
+        deleted INTEGER NOT NULL DEFAULT 0,
+        PRIMARY KEY(type, name)
+    );
+
+    CREATE INDEX ix_item_revisions_revision ON item_revisions(revision);
+
+    INSERT INTO item_revisions(type, name, revision, deleted) SELECT type, name, 0, 0 FROM items;
+
+    CREATE TRIGGER tr_items_insert AFTER INSERT ON items BEGIN
+        INSERT OR REPLACE INTO item_revisions(type, name, revision, deleted)
+            VALUES (NEW.type, NEW.name, (SELECT IFNULL(MAX(revision), 0) + 1 FROM item_revisions), 0);
+    END;
+
+    CREATE TRIGGER tr_items_update AFTER UPDATE ON items BEGIN
+        INSERT OR REPLACE INTO item_revisions(type, name, revision, deleted)
+            VALUES (NEW.type, NEW.name, (SELECT IFNULL(MAX(revision), 0) + 1 FROM item_revisions), 0);
+    END;
+
+    CREATE TRIGGER tr_items_delete AFTER DELETE ON items BEGIN
+        INSERT OR REPLACE INTO item_revisions(type, name, revision, deleted)
+            VALUES (OLD.type, OLD.name, (SELECT IFNULL(MAX(revision), 0) + 1 FROM item_revisions), 1);
+    END;
+
+    CREATE TRIGGER tr_tags_encrypted_insert AFTER INSERT ON tags_encrypted BEGIN
+        UPDATE item_revisions SET revision = (SELECT MAX(revision) + 1 FROM item_revisions)
+            WHERE type = (SELECT type FROM items WHERE id = NEW.item_id) AND name = (SELECT name FROM items WHERE id = NEW.item_id);
+    END;
+
+    CREATE TRIGGER tr_tags_encrypted_update AFTER UPDATE ON tags_encrypted BEGIN
+        UPDATE item_revisions SET revision = (SELECT MAX(revision) + 1 FROM item_revisions)
+            WHERE type = (SELECT type FROM items WHERE id = NEW.item_id) AND name = (SELECT name FROM items WHERE id = NEW.item_id);
+    END;
+
+    CREATE TRIGGER tr_tags_encrypted_delete AFTER DELETE ON tags_encrypted BEGIN
+        UPDATE item_revisions SET revision = (SELECT MAX(revision) + 1 FROM item_revisions)
+            WHERE type = (SELECT type FROM items WHERE id = OLD.item_id) AND name = (SELECT name FROM items WHERE id = OLD.item_id);
+    END;
+
+    CREATE TRIGGER tr_tags_plaintext_insert AFTER INSERT ON tags_plaintext BEGIN
+        UPDATE item_revisions SET revision = (SELECT MAX(revision) + 1 FROM item_revisions)
+            WHERE type = (SELECT type FROM items WHERE id = NEW.item_id) AND name = (SELECT name FROM items WHERE id = NEW.item_id);
+    END;
+
+    CREATE TRIGGER tr_tags_plaintext_update AFTER UPDATE ON tags_plaintext BEGIN
+        UPDATE item_revisions SET revision = (SELECT MAX(revision) + 1 FROM item_revisions)
+            WHERE type = (SELECT type FROM items WHERE id = NEW.item_id) AND name = (SELECT name FROM items WHERE id = NEW.item_id);
+    END;
+
+    CREATE TRIGGER tr_tags_plaintext_delete AFTER DELETE ON tags_plaintext BEGIN
+        UPDATE item_revisions SET revision = (SELECT MAX(revision) + 1 FROM item_revisions)
+            WHERE type = (SELECT type FROM items WHERE id = OLD.item_id) AND name = (SELECT name FROM items WHERE id = OLD.item_id);
+    END;
+";
+
+// Records that fail integrity check are moved here by repair, so they don't break
+// reading of the wallet but are still kept for investigation.
+const _CREATE_QUARANTINE_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS quarantine_items(
+        id INTEGER NOT NULL,
+        type NOT NULL,
+        name NOT NULL,
+        value NOT NULL,
+        key NOT NULL,
+        PRIMARY KEY(id)
+    );
+
+    CREATE TABLE IF NOT EXISTS quarantine_tags(
+        item_id INTEGER NOT NULL,
+        name NOT NULL,
+        value NOT NULL,
+        plaintext INTEGER NOT NULL
+    );
+";
+
+// Expiry time of the item as unix timestamp in seconds, items without expiry time never expire.
+// Expired items are hidden from all reads and removed by purge_expired.
+const _CREATE_EXPIRY_SCHEMA: &str = "
+    ALTER TABLE items ADD COLUMN expires_at INTEGER;
+
+    CREATE INDEX ix_items_expires_at ON items(expires_at);
+";
+
+// Profiles are independent logical wallets inside one database, each one with its own keys.
+// Items of the default profile created together with the database have profile id 0 and
+// its keys are kept in the metadata table, keys of other profiles are kept in the profiles table.
+// Revisions table is recreated as its primary key can't be altered, triggers are dropped
+// before that as renaming of the table validates all triggers referring it.
+const _CREATE_PROFILES_SCHEMA: &str = "
+    CREATE TABLE profiles(
+        id INTEGER NOT NULL,
+        name TEXT NOT NULL,
+        metadata NOT NULL,
+        PRIMARY KEY(id)
+    );
+
+    CREATE UNIQUE INDEX ux_profiles_name ON profiles(name);
+
+    ALTER TABLE items ADD COLUMN profile_id INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE quarantine_items ADD COLUMN profile_id INTEGER NOT NULL DEFAULT 0;
+
+    DROP INDEX ux_items_type_name;
+    CREATE UNIQUE INDEX ux_items_profile_id_type_name ON items(profile_id, type, name);
+
+    DROP TRIGGER tr_items_insert;
+    DROP TRIGGER tr_items_update;
+    DROP TRIGGER tr_items_delete;
+    DROP TRIGGER tr_tags_encrypted_insert;
+    DROP TRIGGER tr_tags_encrypted_update;
+    DROP TRIGGER tr_tags_encrypted_delete;
+    DROP TRIGGER tr_tags_plaintext_insert;
+    DROP TRIGGER tr_tags_plaintext_update;
+    DROP TRIGGER tr_tags_plaintext_delete;
+
+    CREATE TABLE item_revisions_profiles(
+        profile_id INTEGER NOT NULL,
+        type NOT NULL,
+        name NOT NULL,
+        revision INTEGER NOT NULL,
+        deleted INTEGER NOT NULL DEFAULT 0,
+        PRIMARY KEY(profile_id, type, name)
+    );
+
+    INSERT INTO item_revisions_profiles(profile_id, type, name, revision, deleted) SELECT 0, type, name, revision, deleted FROM item_revisions;
+
+    DROP TABLE item_revisions;
+    ALTER TABLE item_revisions_profiles RENAME TO item_revisions;
+
+    CREATE INDEX ix_item_revisions_revision ON item_revisions(profile_id, revision);
+
+    CREATE TRIGGER tr_items_insert AFTER INSERT ON items BEGIN
+        INSERT OR REPLACE INTO item_revisions(profile_id, type, name, revision, deleted)
+            VALUES (NEW.profile_id, NEW.type, NEW.name, (SELECT IFNULL(MAX(revision), 0) + 1 FROM item_revisions), 0);
+    END;
+
+    CREATE TRIGGER tr_items_update AFTER UPDATE ON items BEGIN
+        INSERT OR REPLACE INTO item_revisions(profile_id, type, name, revision, deleted)
+            VALUES (NEW.profile_id, NEW.type, NEW.name, (SELECT IFNULL(MAX(revision), 0) + 1 FROM item_revisions), 0);
+    END;
+
+    CREATE TRIGGER tr_items_delete AFTER DELETE ON items BEGIN
+        INSERT OR REPLACE INTO item_revisions(profile_id, type, name, revision, deleted)
+            VALUES (OLD.profile_id, OLD.type, OLD.name, (SELECT IFNULL(MAX(revision), 0) + 1 FROM item_revisions), 1);
+    END;
+
+    CREATE TRIGGER tr_tags_encrypted_insert AFTER INSERT ON tags_encrypted BEGIN
+        UPDATE item_revisions SET revision = (SELECT MAX(revision) + 1 FROM item_revisions)
+            WHERE (profile_id, type, name) = (SELECT profile_id, type, name FROM items WHERE id = NEW.item_id);
+    END;
+
+    CREATE TRIGGER tr_tags_encrypted_update AFTER UPDATE ON tags_encrypted BEGIN
+        UPDATE item_revisions SET revision = (SELECT MAX(revision) + 1 FROM item_revisions)
+            WHERE (profile_id, type, name) = (SELECT profile_id, type, name FROM items WHERE id = NEW.item_id);
+    END;
+
+    CREATE TRIGGER tr_tags_encrypted_delete AFTER DELETE ON tags_encrypted BEGIN
+        UPDATE item_revisions SET revision = (SELECT MAX(revision) + 1 FROM item_revisions)
+            WHERE (profile_id, type, name) = (SELECT profile_id, type, name FROM items WHERE id = OLD.item_id);
+    END;
+
+    CREATE TRIGGER tr_tags_plaintext_insert AFTER INSERT ON tags_plaintext BEGIN
+        UPDATE item_revisions SET revision = (SELECT MAX(revision) + 1 FROM item_revisions)
+            WHERE (profile_id, type, name) = (SELECT profile_id, type, name FROM items WHERE id = NEW.item_id);
+    END;
+
+    CREATE TRIGGER tr_tags_plaintext_update AFTER UPDATE ON tags_plaintext BEGIN
+        UPDATE item_revisions SET revision = (SELECT MAX(revision) + 1 FROM item_revisions)
+            WHERE (profile_id, type, name) = (SELECT profile_id, type, name FROM items WHERE id = NEW.item_id);
+    END;
+
+    CREATE TRIGGER tr_tags_plaintext_delete AFTER DELETE ON tags_plaintext BEGIN
+        UPDATE item_revisions SET revision = (SELECT MAX(revision) + 1 FROM item_revisions)
+            WHERE (profile_id, type, name) = (SELECT profile_id, type, name FROM items WHERE id = OLD.item_id);
+    END;
+";
+
+// Id of the profile created together with the database
+const _DEFAULT_PROFILE_ID: i64 = 0;
+
+// Ordered list of schema migrations. Each migration is applied in a separate transaction
+// together with the update of the schema version, so a failed migration leaves the wallet
+// on the previous version. Version 1 is the initial layout created by _CREATE_SCHEMA.
+// New migrations must be appended to the end of the list and never changed once released.
+const _MIGRATIONS: [(i64, &str); 4] = [
+    (2, _CREATE_REVISIONS_SCHEMA),
+    (3, _CREATE_QUARANTINE_SCHEMA),
+    (4, _CREATE_EXPIRY_SCHEMA),
+    (5, _CREATE_PROFILES_SCHEMA),
+];
+
+// Schema version is kept in user_version field of the database header instead of the metadata table,
+// as older versions of libindy read and overwrite all rows of the metadata table.
+const _SCHEMA_VERSION: i64 = 5;
 
 #[derive(Debug)]
 struct TagRetriever<'a> {
@@ -141,6 +343,7 @@ struct SQLiteStorageIterator {
     tag_retriever: Option<TagRetrieverOwned>,
     options: RecordOptions,
     total_count: Option<usize>,
+    retrieve_expiry: bool, // statement returns expires_at as 6th column
 }
 
 
@@ -155,6 +358,7 @@ impl SQLiteStorageIterator {
             tag_retriever,
             options,
             total_count,
+            retrieve_expiry: false,
         };
 
         if let Some(stmt) = stmt {
@@ -204,7 +408,13 @@ impl StorageIterator for SQLiteStorageIterator {
                     None
                 };
 
-                Ok(Some(StorageRecord::new(name, value, type_, tags)))
+                let expires_at = if self.retrieve_expiry {
+                    row.get::<_, Option<i64>>(5)?.map(|expires_at| expires_at as u64)
+                } else {
+                    None
+                };
+
+                Ok(Some(StorageRecord::new(name, value, type_, tags).with_expiry(expires_at)))
             }
             Err(err) => Err(err.into()),
         }
@@ -223,6 +433,7 @@ struct Config {
 #[derive(Debug)]
 struct SQLiteStorage {
     conn: Rc<rusqlite::Connection>,
+    profile_id: i64,
 }
 
 pub struct SQLiteStorageType {}
@@ -235,7 +446,7 @@ impl SQLiteStorageType {
 
     fn _db_path(id: &str, config: Option<&Config>) -> std::path::PathBuf {
         let mut path = match config {
-            Some(Config { path: Some(ref path) }) => std::path::PathBuf::from(path),
+            Some(Config { path: Some(ref path), .. }) => std::path::PathBuf::from(path),
             _ => environment::wallet_home_path()
         };
 
@@ -243,6 +454,125 @@ impl SQLiteStorageType {
         path.push(_SQLITE_DB);
         path
     }
+
+    // Wallets created before schema versioning was introduced don't have version record,
+    // so their version is detected by the set of existing tables.
+    fn _schema_version(conn: &rusqlite::Connection) -> IndyResult<i64> {
+        let version: i64 = conn.query_row(
+            "PRAGMA user_version",
+            [],
+            |row| { row.get(0) },
+        )?;
+
+        if version != 0 {
+            return Ok(version);
+        }
+
+        let has_revisions: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'item_revisions'",
+            [],
+            |row| { row.get(0) },
+        )?;
+
+        Ok(if has_revisions == 0 { 1 } else { 2 })
+    }
+
+    ///
+    /// Applies all migrations that are newer than the schema version of the wallet database.
+    ///
+    /// # Errors
+    ///
+    ///  * `InvalidState` - Wallet database was created by a newer version of libindy
+    ///  * `IOError("IO error during storage operation:...")` - Migration failed, the wallet is left on the last applied version
+    ///
+    fn _migrate(conn: &rusqlite::Connection) -> IndyResult<()> {
+        let version = SQLiteStorageType::_schema_version(conn)?;
+
+        if version > _SCHEMA_VERSION {
+            return Err(err_msg(IndyErrorKind::InvalidState,
+                               format!("Wallet schema version {} is newer than supported version {}", version, _SCHEMA_VERSION)));
+        }
+
+        for &(migration_version, migration) in _MIGRATIONS.iter().filter(|&&(migration_version, _)| migration_version > version) {
+            let tx: transaction::Transaction = transaction::Transaction::new(conn, rusqlite::TransactionBehavior::Exclusive)?;
+
+            tx.execute_batch(migration)?;
+            tx.execute_batch(&format!("PRAGMA user_version = {}", migration_version))?;
+
+            tx.commit()?;
+        }
+
+        Ok(())
+    }
+
+    fn _parse_config(config: Option<&str>) -> IndyResult<Option<Config>> {
+        config
+            .map(serde_json::from_str::<Config>)
+            .map_or(Ok(None), |v| v.map(Some))
+            .to_indy(IndyErrorKind::InvalidStructure, "Malformed config json")
+    }
+
+    fn _open_connection(id: &str, config: Option<&str>) -> IndyResult<rusqlite::Connection> {
+        let config = SQLiteStorageType::_parse_config(config)?;
+
+        let db_file_path = SQLiteStorageType::_db_path(id, config.as_ref());
+
+        if !db_file_path.exists() {
+            return Err(err_msg(IndyErrorKind::WalletNotFound, "No wallet database exists"));
+        }
+
+        let conn = rusqlite::Connection::open(db_file_path.as_path())?;
+
+        // set journal mode to WAL, because it provides better performance.
+        let journal_mode: String = conn.query_row(
+            "PRAGMA journal_mode = WAL",
+            [],
+            |row| { row.get(0) },
+        )?;
+
+        // if journal mode is set to WAL, set synchronous to FULL for safety reasons.
+        // (synchronous = NORMAL with journal_mode = WAL does not guaranties durability).
+        if journal_mode.to_lowercase() == "wal" {
+            conn.execute("PRAGMA synchronous = FULL", [])?;
+        }
+
+        SQLiteStorageType::_migrate(&conn)?;
+
+        Ok(conn)
+    }
+
+    fn _open_connection_read_only(id: &str, config: Option<&str>) -> IndyResult<rusqlite::Connection> {
+        let config = SQLiteStorageType::_parse_config(config)?;
+
+        let db_file_path = SQLiteStorageType::_db_path(id, config.as_ref());
+
+        if !db_file_path.exists() {
+            return Err(err_msg(IndyErrorKind::WalletNotFound, "No wallet database exists"));
+        }
+
+        let conn = rusqlite::Connection::open_with_flags(
+            db_file_path.as_path(),
+            rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+
+        // migrations can't be applied through read-only connection.
+        let version = SQLiteStorageType::_schema_version(&conn)?;
+
+        if version != _SCHEMA_VERSION {
+            return Err(err_msg(IndyErrorKind::InvalidState,
+                               format!("Wallet schema version {} differs from supported version {}, wallet has to be opened in read-write mode first", version, _SCHEMA_VERSION)));
+        }
+
+        Ok(conn)
+    }
+
+    fn _profile_id(conn: &rusqlite::Connection, profile: &str) -> IndyResult<Option<i64>> {
+        conn.query_row(
+            "SELECT id FROM profiles WHERE name = ?1",
+            &[&profile],
+            |row| { row.get(0) },
+        ).optional().map_err(IndyError::from)
+    }
 }
 
 impl WalletStorage for SQLiteStorage {
@@ -283,11 +613,11 @@ impl WalletStorage for SQLiteStorage {
         };
 
 
-        let item: (i64, Vec<u8>, Vec<u8>) = self.conn.query_row(
-            "SELECT id, value, key FROM items where type = ?1 AND name = ?2",
-            &[&type_.to_vec(), &id.to_vec()],
+        let item: (i64, Vec<u8>, Vec<u8>, Option<i64>) = self.conn.query_row(
+            &format!("SELECT i.id, i.value, i.key, i.expires_at FROM items as i where i.type = ?1 AND i.name = ?2 AND i.profile_id = ?3 AND {}", query::NOT_EXPIRED),
+            rusqlite::params![&type_.to_vec(), &id.to_vec(), &self.profile_id],
             |row| {
-                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
             },
         )?;
 
@@ -315,7 +645,7 @@ impl WalletStorage for SQLiteStorage {
             Some(tags)
         } else { None };
 
-        Ok(StorageRecord::new(id.to_vec(), value, type_, tags))
+        Ok(StorageRecord::new(id.to_vec(), value, type_, tags).with_expiry(item.3.map(|expires_at| expires_at as u64)))
     }
 
     ///
@@ -349,106 +679,32 @@ impl WalletStorage for SQLiteStorage {
     ///
     fn add(&self, type_: &[u8], id: &[u8], value: &EncryptedValue, tags: &[Tag]) -> IndyResult<()> {
         let tx: transaction::Transaction = transaction::Transaction::new(&self.conn, rusqlite::TransactionBehavior::Deferred)?;
-        let res = tx.prepare_cached("INSERT INTO items (type, name, value, key) VALUES (?1, ?2, ?3, ?4)")?
-            .insert(&[&type_.to_vec(), &id.to_vec(), &value.data, &value.key]);
-
-        let id = match res {
-            Ok(entity) => entity,
-            Err(err) => return Err(IndyError::from(err))
-        };
-
-        if !tags.is_empty() {
-            let mut stmt_e = tx.prepare_cached("INSERT INTO tags_encrypted (item_id, name, value) VALUES (?1, ?2, ?3)")?;
-            let mut stmt_p = tx.prepare_cached("INSERT INTO tags_plaintext (item_id, name, value) VALUES (?1, ?2, ?3)")?;
-
-            for tag in tags {
-                match *tag {
-                    Tag::Encrypted(ref tag_name, ref tag_data) => stmt_e.execute(rusqlite::params![&id, tag_name, tag_data])?,
-                    Tag::PlainText(ref tag_name, ref tag_data) => stmt_p.execute(rusqlite::params![&id, tag_name, tag_data])?
-                };
-            }
-        }
-
+        SQLiteStorage::_add(&tx, self.profile_id, type_, id, value, tags)?;
         tx.commit()?;
         Ok(())
     }
 
     fn update(&self, type_: &[u8], id: &[u8], value: &EncryptedValue) -> IndyResult<()> {
-        let res = self.conn.prepare_cached("UPDATE items SET value = ?1, key = ?2 WHERE type = ?3 AND name = ?4")?
-            .execute(rusqlite::params![&value.data, &value.key, &type_.to_vec(), &id.to_vec()]);
-
-        match res {
-            Ok(1) => Ok(()),
-            Ok(0) => Err(err_msg(IndyErrorKind::WalletItemNotFound, "Item to update not found")),
-            Ok(_) => Err(err_msg(IndyErrorKind::InvalidState, "More than one row update. Seems wallet structure is inconsistent")),
-            Err(err) => Err(err.into()),
-        }
+        SQLiteStorage::_update(&self.conn, self.profile_id, type_, id, value)
     }
 
     fn add_tags(&self, type_: &[u8], id: &[u8], tags: &[Tag]) -> IndyResult<()> {
         let tx: transaction::Transaction = transaction::Transaction::new(&self.conn, rusqlite::TransactionBehavior::Deferred)?;
-
-        let item_id: i64 = tx.prepare_cached("SELECT id FROM items WHERE type = ?1 AND name = ?2")?
-            .query_row(&[&type_.to_vec(), &id.to_vec()], |row| row.get(0))?;
-
-        if !tags.is_empty() {
-            let mut enc_tag_insert_stmt = tx.prepare_cached("INSERT OR REPLACE INTO tags_encrypted (item_id, name, value) VALUES (?1, ?2, ?3)")?;
-            let mut plain_tag_insert_stmt = tx.prepare_cached("INSERT OR REPLACE INTO tags_plaintext (item_id, name, value) VALUES (?1, ?2, ?3)")?;
-
-            for tag in tags {
-                match *tag {
-                    Tag::Encrypted(ref tag_name, ref tag_data) => enc_tag_insert_stmt.execute(rusqlite::params![&item_id, tag_name, tag_data])?,
-                    Tag::PlainText(ref tag_name, ref tag_data) => plain_tag_insert_stmt.execute(rusqlite::params![&item_id, tag_name, tag_data])?
-                };
-            }
-        }
+        SQLiteStorage::_add_tags(&tx, self.profile_id, type_, id, tags)?;
         tx.commit()?;
-
         Ok(())
     }
 
     fn update_tags(&self, type_: &[u8], id: &[u8], tags: &[Tag]) -> IndyResult<()> {
         let tx: transaction::Transaction = transaction::Transaction::new(&self.conn, rusqlite::TransactionBehavior::Deferred)?;
-
-        let item_id: i64 = tx.prepare_cached("SELECT id FROM items WHERE type = ?1 AND name = ?2")?
-            .query_row(&[&type_.to_vec(), &id.to_vec()], |row| row.get(0))?;
-
-        tx.execute("DELETE FROM tags_encrypted WHERE item_id = ?1", &[&item_id])?;
-        tx.execute("DELETE FROM tags_plaintext WHERE item_id = ?1", &[&item_id])?;
-
-        if !tags.is_empty() {
-            let mut enc_tag_insert_stmt = tx.prepare_cached("INSERT INTO tags_encrypted (item_id, name, value) VALUES (?1, ?2, ?3)")?;
-            let mut plain_tag_insert_stmt = tx.prepare_cached("INSERT INTO tags_plaintext (item_id, name, value) VALUES (?1, ?2, ?3)")?;
-
-            for tag in tags {
-                match *tag {
-                    Tag::Encrypted(ref tag_name, ref tag_data) => enc_tag_insert_stmt.execute(rusqlite::params![&item_id, tag_name, tag_data])?,
-                    Tag::PlainText(ref tag_name, ref tag_data) => plain_tag_insert_stmt.execute(rusqlite::params![&item_id, tag_name, tag_data])?
-                };
-            }
-        }
+        SQLiteStorage::_update_tags(&tx, self.profile_id, type_, id, tags)?;
         tx.commit()?;
-
         Ok(())
     }
 
     fn delete_tags(&self, type_: &[u8], id: &[u8], tag_names: &[TagName]) -> IndyResult<()> {
-        let item_id: i64 = self.conn.prepare_cached("SELECT id FROM items WHERE type =?1 AND name = ?2")?
-            .query_row(&[&type_.to_vec(), &id.to_vec()], |row| row.get(0))?;
-
         let tx: transaction::Transaction = transaction::Transaction::new(&self.conn, rusqlite::TransactionBehavior::Deferred)?;
-        {
-            let mut enc_tag_delete_stmt = tx.prepare_cached("DELETE FROM tags_encrypted WHERE item_id = ?1 AND name = ?2")?;
-            let mut plain_tag_delete_stmt = tx.prepare_cached("DELETE FROM tags_plaintext WHERE item_id = ?1 AND name = ?2")?;
-
-            for tag_name in tag_names {
-                match *tag_name {
-                    TagName::OfEncrypted(ref tag_name) => enc_tag_delete_stmt.execute(rusqlite::params![&item_id, tag_name])?,
-                    TagName::OfPlain(ref tag_name) => plain_tag_delete_stmt.execute(rusqlite::params![&item_id, tag_name])?,
-                };
-            }
-        }
-
+        SQLiteStorage::_delete_tags(&tx, self.profile_id, type_, id, tag_names)?;
         tx.commit()?;
         Ok(())
     }
@@ -480,19 +736,46 @@ impl WalletStorage for SQLiteStorage {
     ///  * `IOError("IO error during storage operation:...")` - Failed connection or SQL query
     ///
     fn delete(&self, type_: &[u8], id: &[u8]) -> IndyResult<()> {
-        let row_count = self.conn.execute(
-            "DELETE FROM items where type = ?1 AND name = ?2",
-            &[&type_.to_vec(), &id.to_vec()],
-        )?;
+        SQLiteStorage::_delete(&self.conn, self.profile_id, type_, id)
+    }
 
-        if row_count == 1 {
-            Ok(())
-        } else {
-            Err(err_msg(IndyErrorKind::WalletItemNotFound, "Item to delete not found"))
+    ///
+    /// Applies a list of operations as a single SQLite transaction.
+    /// Either all operations are committed or, if any of them fails,
+    /// the whole transaction is rolled back and the error of the failed operation is returned.
+    ///
+    /// # Arguments
+    ///
+    ///  * `operations` - operations to apply in order
+    ///
+    fn batch(&self, operations: &[StorageOperation]) -> IndyResult<()> {
+        let tx: transaction::Transaction = transaction::Transaction::new(&self.conn, rusqlite::TransactionBehavior::Deferred)?;
+
+        for operation in operations {
+            match *operation {
+                StorageOperation::Add(ref type_, ref id, ref value, ref tags) => SQLiteStorage::_add(&tx, self.profile_id, type_, id, value, tags)?,
+                StorageOperation::Update(ref type_, ref id, ref value) => SQLiteStorage::_update(&tx, self.profile_id, type_, id, value)?,
+                StorageOperation::AddTags(ref type_, ref id, ref tags) => SQLiteStorage::_add_tags(&tx, self.profile_id, type_, id, tags)?,
+                StorageOperation::UpdateTags(ref type_, ref id, ref tags) => SQLiteStorage::_update_tags(&tx, self.profile_id, type_, id, tags)?,
+                StorageOperation::DeleteTags(ref type_, ref id, ref tag_names) => SQLiteStorage::_delete_tags(&tx, self.profile_id, type_, id, tag_names)?,
+                StorageOperation::Delete(ref type_, ref id) => SQLiteStorage::_delete(&tx, self.profile_id, type_, id)?,
+                StorageOperation::SetExpiry(ref type_, ref id, expires_at) => SQLiteStorage::_set_expiry(&tx, self.profile_id, type_, id, expires_at)?,
+            }
         }
+
+        tx.commit()?;
+        Ok(())
     }
 
     fn get_storage_metadata(&self) -> IndyResult<Vec<u8>> {
+        if self.profile_id != _DEFAULT_PROFILE_ID {
+            return self.conn.query_row(
+                "SELECT metadata FROM profiles WHERE id = ?1",
+                &[&self.profile_id],
+                |row| { row.get(0) },
+            ).map_err(IndyError::from);
+        }
+
         self.conn.query_row(
             "SELECT value FROM metadata",
             [],
@@ -501,12 +784,17 @@ impl WalletStorage for SQLiteStorage {
     }
 
     fn set_storage_metadata(&self, metadata: &[u8]) -> IndyResult<()> {
+        if self.profile_id != _DEFAULT_PROFILE_ID {
+            self.conn.execute("UPDATE profiles SET metadata = ?1 WHERE id = ?2", rusqlite::params![metadata.to_vec(), self.profile_id])?;
+            return Ok(());
+        }
+
         self.conn.execute("UPDATE metadata SET value = ?1", &[&metadata.to_vec()])?;
         Ok(())
     }
 
     fn get_all(&self) -> IndyResult<Box<dyn StorageIterator>> {
-        let statement = self._prepare_statement("SELECT id, name, value, key, type FROM items;")?;
+        let statement = self._prepare_statement(&format!("SELECT i.id, i.name, i.value, i.key, i.type, i.expires_at FROM items as i WHERE i.profile_id = ?1 AND {};", query::NOT_EXPIRED))?;
 
         let fetch_options = RecordOptions {
             retrieve_type: true,
@@ -515,74 +803,360 @@ impl WalletStorage for SQLiteStorage {
         };
 
         let tag_retriever = Some(TagRetriever::new_owned(self.conn.clone())?);
-        let storage_iterator = SQLiteStorageIterator::new(Some(statement), &[], fetch_options, tag_retriever, None)?;
+        let mut storage_iterator = SQLiteStorageIterator::new(Some(statement), &[&self.profile_id], fetch_options, tag_retriever, None)?;
+        storage_iterator.retrieve_expiry = true;
 
         Ok(Box::new(storage_iterator))
     }
 
-    fn search(&self, type_: &[u8], query: &language::Operator, options: Option<&str>) -> IndyResult<Box<dyn StorageIterator>> {
-        let type_ = type_.to_vec(); // FIXME
+    fn get_revision(&self) -> IndyResult<u64> {
+        let revision: i64 = self.conn.query_row(
+            "SELECT IFNULL(MAX(revision), 0) FROM item_revisions WHERE profile_id = ?1",
+            &[&self.profile_id],
+            |row| { row.get(0) },
+        )?;
 
-        let search_options = match options {
-            None => SearchOptions::default(),
-            Some(option_str) => serde_json::from_str(option_str)
-                .to_indy(IndyErrorKind::InvalidStructure, "Search options is malformed json")?
-        };
+        Ok(revision as u64)
+    }
 
-        let total_count: Option<usize> = if search_options.retrieve_total_count {
-            let (query_string, query_arguments) = query::wql_to_sql_count(&type_, query)?;
+    fn get_changed(&self, since_revision: u64) -> IndyResult<Box<dyn StorageIterator>> {
+        let statement = self._prepare_statement(
+            &format!("SELECT i.id, i.name, i.value, i.key, i.type, i.expires_at FROM items as i \
+                      INNER JOIN item_revisions as r ON r.profile_id = i.profile_id AND r.type = i.type AND r.name = i.name \
+                      WHERE i.profile_id = ?1 AND r.revision > ?2 AND r.deleted = 0 AND {};", query::NOT_EXPIRED))?;
 
-            let res: Option<usize> = Option::from(self.conn.query_row(
-                &query_string,
-                &*query_arguments,
-                |row| {
-                    let x: i64 = row.get(0)?;
-                    Ok(x as usize)
-                },
-            )?);
-            res
-        } else { None };
+        let fetch_options = RecordOptions {
+            retrieve_type: true,
+            retrieve_value: true,
+            retrieve_tags: true,
+        };
 
+        let since_revision = since_revision as i64;
+        let tag_retriever = Some(TagRetriever::new_owned(self.conn.clone())?);
+        let mut storage_iterator = SQLiteStorageIterator::new(Some(statement), &[&self.profile_id, &since_revision], fetch_options, tag_retriever, None)?;
+        storage_iterator.retrieve_expiry = true;
 
-        if search_options.retrieve_records {
-            let fetch_options = RecordOptions {
-                retrieve_value: search_options.retrieve_value,
-                retrieve_tags: search_options.retrieve_tags,
-                retrieve_type: search_options.retrieve_type,
-            };
+        Ok(Box::new(storage_iterator))
+    }
 
-            let (query_string, query_arguments) = query::wql_to_sql(&type_, query, options)?;
+    fn get_deleted(&self, since_revision: u64) -> IndyResult<Vec<(Vec<u8>, Vec<u8>)>> {
+        let mut stmt = self.conn.prepare_cached("SELECT type, name FROM item_revisions WHERE profile_id = ?1 AND revision > ?2 AND deleted = 1")?;
+        let mut rows = stmt.query(&[&self.profile_id, &(since_revision as i64)])?;
 
-            let statement = self._prepare_statement(&query_string)?;
-            let tag_retriever = if fetch_options.retrieve_tags {
-                Some(TagRetriever::new_owned(self.conn.clone())?)
-            } else {
-                None
-            };
-            let storage_iterator = SQLiteStorageIterator::new(Some(statement), &query_arguments, fetch_options, tag_retriever, total_count)?;
-            Ok(Box::new(storage_iterator))
-        } else {
-            let storage_iterator = SQLiteStorageIterator::new(None, &[], RecordOptions::default(), None, total_count)?;
-            Ok(Box::new(storage_iterator))
+        let mut deleted = Vec::new();
+
+        while let Some(row) = rows.next()? {
+            deleted.push((row.get(0)?, row.get(1)?));
         }
-    }
 
-    fn close(&mut self) -> IndyResult<()> {
-        Ok(())
+        Ok(deleted)
     }
-}
 
-impl SQLiteStorage {
-    fn _prepare_statement(&self, sql: &str) -> IndyResult<OwningHandle<Rc<rusqlite::Connection>, Box<rusqlite::Statement<'static>>>> {
-        OwningHandle::try_new(self.conn.clone(), |conn| {
-            unsafe { (*conn).prepare(sql) }.map(Box::new).map_err(IndyError::from)
-        })
-    }
-}
+    fn rekey_deleted(&self, marks: &[((Vec<u8>, Vec<u8>), (Vec<u8>, Vec<u8>))]) -> IndyResult<usize> {
+        let tx: transaction::Transaction = transaction::Transaction::new(&self.conn, rusqlite::TransactionBehavior::Deferred)?;
 
+        let mut moved = 0;
 
-impl WalletStorageType for SQLiteStorageType {
-    ///
+        for &((ref type_, ref id), (ref new_type, ref new_id)) in marks {
+            tx.execute(
+                "DELETE FROM item_revisions WHERE profile_id = ?1 AND type = ?2 AND name = ?3 AND deleted = 1 \
+                 AND EXISTS (SELECT 1 FROM item_revisions WHERE profile_id = ?1 AND type = ?4 AND name = ?5)",
+                rusqlite::params![self.profile_id, type_, id, new_type, new_id],
+            )?;
+
+            moved += tx.execute(
+                "UPDATE item_revisions SET type = ?4, name = ?5 WHERE profile_id = ?1 AND type = ?2 AND name = ?3 AND deleted = 1",
+                rusqlite::params![self.profile_id, type_, id, new_type, new_id],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(moved)
+    }
+
+    fn check_orphan_tags(&self, repair: bool) -> IndyResult<usize> {
+        let count: i64 = self.conn.query_row(
+            "SELECT (SELECT COUNT(*) FROM tags_encrypted WHERE item_id NOT IN (SELECT id FROM items)) + \
+                    (SELECT COUNT(*) FROM tags_plaintext WHERE item_id NOT IN (SELECT id FROM items))",
+            [],
+            |row| { row.get(0) },
+        )?;
+
+        if repair && count > 0 {
+            let tx: transaction::Transaction = transaction::Transaction::new(&self.conn, rusqlite::TransactionBehavior::Deferred)?;
+            tx.execute("DELETE FROM tags_encrypted WHERE item_id NOT IN (SELECT id FROM items)", [])?;
+            tx.execute("DELETE FROM tags_plaintext WHERE item_id NOT IN (SELECT id FROM items)", [])?;
+            tx.commit()?;
+        }
+
+        Ok(count as usize)
+    }
+
+    ///
+    /// Moves the item with its tags from the items tables to the quarantine tables
+    /// as a single SQLite transaction.
+    ///
+    fn quarantine(&self, type_: &[u8], id: &[u8]) -> IndyResult<()> {
+        let tx: transaction::Transaction = transaction::Transaction::new(&self.conn, rusqlite::TransactionBehavior::Deferred)?;
+
+        let item_id: i64 = tx.prepare_cached("SELECT id FROM items WHERE type = ?1 AND name = ?2 AND profile_id = ?3")?
+            .query_row(rusqlite::params![&type_.to_vec(), &id.to_vec(), &self.profile_id], |row| row.get(0))?;
+
+        tx.execute("INSERT INTO quarantine_items (type, name, value, key, profile_id) SELECT type, name, value, key, profile_id FROM items WHERE id = ?1", &[&item_id])?;
+        let quarantine_id = tx.last_insert_rowid();
+
+        tx.execute("INSERT INTO quarantine_tags (item_id, name, value, plaintext) SELECT ?1, name, value, 0 FROM tags_encrypted WHERE item_id = ?2", &[&quarantine_id, &item_id])?;
+        tx.execute("INSERT INTO quarantine_tags (item_id, name, value, plaintext) SELECT ?1, name, value, 1 FROM tags_plaintext WHERE item_id = ?2", &[&quarantine_id, &item_id])?;
+
+        tx.execute("DELETE FROM tags_encrypted WHERE item_id = ?1", &[&item_id])?;
+        tx.execute("DELETE FROM tags_plaintext WHERE item_id = ?1", &[&item_id])?;
+        tx.execute("DELETE FROM items WHERE id = ?1", &[&item_id])?;
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    ///
+    /// Deletes all expired items with their tags as a single SQLite transaction.
+    /// Tags are deleted explicitly as foreign keys enforcement is enabled only for the connection
+    /// that created the wallet.
+    ///
+    fn purge_expired(&self) -> IndyResult<usize> {
+        let tx: transaction::Transaction = transaction::Transaction::new(&self.conn, rusqlite::TransactionBehavior::Deferred)?;
+
+        tx.execute(&format!("DELETE FROM tags_encrypted WHERE item_id IN (SELECT i.id FROM items as i WHERE i.profile_id = ?1 AND {})", query::EXPIRED), &[&self.profile_id])?;
+        tx.execute(&format!("DELETE FROM tags_plaintext WHERE item_id IN (SELECT i.id FROM items as i WHERE i.profile_id = ?1 AND {})", query::EXPIRED), &[&self.profile_id])?;
+        let count = tx.execute(&format!("DELETE FROM items WHERE id IN (SELECT i.id FROM items as i WHERE i.profile_id = ?1 AND {})", query::EXPIRED), &[&self.profile_id])?;
+
+        tx.commit()?;
+        Ok(count)
+    }
+
+    fn search(&self, type_: &[u8], query: &language::Operator, options: Option<&str>) -> IndyResult<Box<dyn StorageIterator>> {
+        let type_ = type_.to_vec(); // FIXME
+
+        let search_options = match options {
+            None => SearchOptions::default(),
+            Some(option_str) => serde_json::from_str(option_str)
+                .to_indy(IndyErrorKind::InvalidStructure, "Search options is malformed json")?
+        };
+
+        let total_count: Option<usize> = if search_options.retrieve_total_count {
+            let (query_string, query_arguments) = query::wql_to_sql_count(&type_, query, &self.profile_id)?;
+
+            let res: Option<usize> = Option::from(self.conn.query_row(
+                &query_string,
+                &*query_arguments,
+                |row| {
+                    let x: i64 = row.get(0)?;
+                    Ok(x as usize)
+                },
+            )?);
+            res
+        } else { None };
+
+
+        if search_options.retrieve_records {
+            let fetch_options = RecordOptions {
+                retrieve_value: search_options.retrieve_value,
+                retrieve_tags: search_options.retrieve_tags,
+                retrieve_type: search_options.retrieve_type,
+            };
+
+            let order = query::SearchOrder::from_options(&search_options)?;
+            let (query_string, query_arguments) = query::wql_to_sql(&type_, query, &order, &self.profile_id)?;
+
+            let statement = self._prepare_statement(&query_string)?;
+            let tag_retriever = if fetch_options.retrieve_tags {
+                Some(TagRetriever::new_owned(self.conn.clone())?)
+            } else {
+                None
+            };
+            let storage_iterator = SQLiteStorageIterator::new(Some(statement), &query_arguments, fetch_options, tag_retriever, total_count)?;
+            Ok(Box::new(storage_iterator))
+        } else {
+            let storage_iterator = SQLiteStorageIterator::new(None, &[], RecordOptions::default(), None, total_count)?;
+            Ok(Box::new(storage_iterator))
+        }
+    }
+
+    fn count_by_tag(&self, type_: &[u8], query: &language::Operator, tag_name: &TagName) -> IndyResult<Vec<(Vec<u8>, usize)>> {
+        let type_ = type_.to_vec();
+
+        // values of plaintext tags are stored as text
+        let (tags_table, name, plaintext) = match *tag_name {
+            TagName::OfPlain(ref name) => ("tags_plaintext", name, true),
+            TagName::OfEncrypted(ref name) => ("tags_encrypted", name, false),
+        };
+
+        let (query_string, query_arguments) = query::wql_to_sql_count_by_tag(&type_, query, tags_table, name, &self.profile_id)?;
+
+        let mut stmt = self.conn.prepare(&query_string)?;
+        let rows = stmt.query_map(&*query_arguments, |row| {
+            let value: Vec<u8> = if plaintext { row.get::<_, String>(0)?.into_bytes() } else { row.get(0)? };
+            let count: i64 = row.get(1)?;
+            Ok((value, count as usize))
+        })?;
+
+        let mut counts = Vec::new();
+        for row in rows {
+            counts.push(row?);
+        }
+
+        Ok(counts)
+    }
+
+    fn get_distinct_tag_values(&self, type_: &[u8], query: &language::Operator, tag_name: &TagName) -> IndyResult<Vec<Vec<u8>>> {
+        let counts = self.count_by_tag(type_, query, tag_name)?;
+        Ok(counts.into_iter().map(|(value, _)| value).collect())
+    }
+
+    fn close(&mut self) -> IndyResult<()> {
+        Ok(())
+    }
+}
+
+impl SQLiteStorage {
+    fn _add(conn: &rusqlite::Connection, profile_id: i64, type_: &[u8], id: &[u8], value: &EncryptedValue, tags: &[Tag]) -> IndyResult<()> {
+        // expired item that isn't purged yet must not prevent adding of the item with the same name
+        let expired_id: Option<i64> = conn.prepare_cached(&format!("SELECT i.id FROM items as i WHERE i.type = ?1 AND i.name = ?2 AND i.profile_id = ?3 AND {}", query::EXPIRED))?
+            .query_row(rusqlite::params![&type_.to_vec(), &id.to_vec(), &profile_id], |row| row.get(0))
+            .optional()?;
+
+        if let Some(expired_id) = expired_id {
+            conn.execute("DELETE FROM tags_encrypted WHERE item_id = ?1", &[&expired_id])?;
+            conn.execute("DELETE FROM tags_plaintext WHERE item_id = ?1", &[&expired_id])?;
+            conn.execute("DELETE FROM items WHERE id = ?1", &[&expired_id])?;
+        }
+
+        let res = conn.prepare_cached("INSERT INTO items (type, name, value, key, profile_id) VALUES (?1, ?2, ?3, ?4, ?5)")?
+            .insert(rusqlite::params![&type_.to_vec(), &id.to_vec(), &value.data, &value.key, &profile_id]);
+
+        let id = match res {
+            Ok(entity) => entity,
+            Err(err) => return Err(IndyError::from(err))
+        };
+
+        if !tags.is_empty() {
+            let mut stmt_e = conn.prepare_cached("INSERT INTO tags_encrypted (item_id, name, value) VALUES (?1, ?2, ?3)")?;
+            let mut stmt_p = conn.prepare_cached("INSERT INTO tags_plaintext (item_id, name, value) VALUES (?1, ?2, ?3)")?;
+
+            for tag in tags {
+                match *tag {
+                    Tag::Encrypted(ref tag_name, ref tag_data) => stmt_e.execute(rusqlite::params![&id, tag_name, tag_data])?,
+                    Tag::PlainText(ref tag_name, ref tag_data) => stmt_p.execute(rusqlite::params![&id, tag_name, tag_data])?
+                };
+            }
+        }
+
+        Ok(())
+    }
+
+    fn _update(conn: &rusqlite::Connection, profile_id: i64, type_: &[u8], id: &[u8], value: &EncryptedValue) -> IndyResult<()> {
+        let res = conn.prepare_cached("UPDATE items SET value = ?1, key = ?2 WHERE type = ?3 AND name = ?4 AND profile_id = ?5")?
+            .execute(rusqlite::params![&value.data, &value.key, &type_.to_vec(), &id.to_vec(), &profile_id]);
+
+        match res {
+            Ok(1) => Ok(()),
+            Ok(0) => Err(err_msg(IndyErrorKind::WalletItemNotFound, "Item to update not found")),
+            Ok(_) => Err(err_msg(IndyErrorKind::InvalidState, "More than one row update. Seems wallet structure is inconsistent")),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn _set_expiry(conn: &rusqlite::Connection, profile_id: i64, type_: &[u8], id: &[u8], expires_at: Option<u64>) -> IndyResult<()> {
+        let res = conn.prepare_cached("UPDATE items SET expires_at = ?1 WHERE type = ?2 AND name = ?3 AND profile_id = ?4")?
+            .execute(rusqlite::params![expires_at.map(|expires_at| expires_at as i64), &type_.to_vec(), &id.to_vec(), &profile_id]);
+
+        match res {
+            Ok(1) => Ok(()),
+            Ok(0) => Err(err_msg(IndyErrorKind::WalletItemNotFound, "Item to set expiry not found")),
+            Ok(_) => Err(err_msg(IndyErrorKind::InvalidState, "More than one row update. Seems wallet structure is inconsistent")),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn _add_tags(conn: &rusqlite::Connection, profile_id: i64, type_: &[u8], id: &[u8], tags: &[Tag]) -> IndyResult<()> {
+        let item_id: i64 = conn.prepare_cached("SELECT id FROM items WHERE type = ?1 AND name = ?2 AND profile_id = ?3")?
+            .query_row(rusqlite::params![&type_.to_vec(), &id.to_vec(), &profile_id], |row| row.get(0))?;
+
+        if !tags.is_empty() {
+            let mut enc_tag_insert_stmt = conn.prepare_cached("INSERT OR REPLACE INTO tags_encrypted (item_id, name, value) VALUES (?1, ?2, ?3)")?;
+            let mut plain_tag_insert_stmt = conn.prepare_cached("INSERT OR REPLACE INTO tags_plaintext (item_id, name, value) VALUES (?1, ?2, ?3)")?;
+
+            for tag in tags {
+                match *tag {
+                    Tag::Encrypted(ref tag_name, ref tag_data) => enc_tag_insert_stmt.execute(rusqlite::params![&item_id, tag_name, tag_data])?,
+                    Tag::PlainText(ref tag_name, ref tag_data) => plain_tag_insert_stmt.execute(rusqlite::params![&item_id, tag_name, tag_data])?
+                };
+            }
+        }
+
+        Ok(())
+    }
+
+    fn _update_tags(conn: &rusqlite::Connection, profile_id: i64, type_: &[u8], id: &[u8], tags: &[Tag]) -> IndyResult<()> {
+        let item_id: i64 = conn.prepare_cached("SELECT id FROM items WHERE type = ?1 AND name = ?2 AND profile_id = ?3")?
+            .query_row(rusqlite::params![&type_.to_vec(), &id.to_vec(), &profile_id], |row| row.get(0))?;
+
+        conn.execute("DELETE FROM tags_encrypted WHERE item_id = ?1", &[&item_id])?;
+        conn.execute("DELETE FROM tags_plaintext WHERE item_id = ?1", &[&item_id])?;
+
+        if !tags.is_empty() {
+            let mut enc_tag_insert_stmt = conn.prepare_cached("INSERT INTO tags_encrypted (item_id, name, value) VALUES (?1, ?2, ?3)")?;
+            let mut plain_tag_insert_stmt = conn.prepare_cached("INSERT INTO tags_plaintext (item_id, name, value) VALUES (?1, ?2, ?3)")?;
+
+            for tag in tags {
+                match *tag {
+                    Tag::Encrypted(ref tag_name, ref tag_data) => enc_tag_insert_stmt.execute(rusqlite::params![&item_id, tag_name, tag_data])?,
+                    Tag::PlainText(ref tag_name, ref tag_data) => plain_tag_insert_stmt.execute(rusqlite::params![&item_id, tag_name, tag_data])?
+                };
+            }
+        }
+
+        Ok(())
+    }
+
+    fn _delete_tags(conn: &rusqlite::Connection, profile_id: i64, type_: &[u8], id: &[u8], tag_names: &[TagName]) -> IndyResult<()> {
+        let item_id: i64 = conn.prepare_cached("SELECT id FROM items WHERE type = ?1 AND name = ?2 AND profile_id = ?3")?
+            .query_row(rusqlite::params![&type_.to_vec(), &id.to_vec(), &profile_id], |row| row.get(0))?;
+
+        let mut enc_tag_delete_stmt = conn.prepare_cached("DELETE FROM tags_encrypted WHERE item_id = ?1 AND name = ?2")?;
+        let mut plain_tag_delete_stmt = conn.prepare_cached("DELETE FROM tags_plaintext WHERE item_id = ?1 AND name = ?2")?;
+
+        for tag_name in tag_names {
+            match *tag_name {
+                TagName::OfEncrypted(ref tag_name) => enc_tag_delete_stmt.execute(rusqlite::params![&item_id, tag_name])?,
+                TagName::OfPlain(ref tag_name) => plain_tag_delete_stmt.execute(rusqlite::params![&item_id, tag_name])?,
+            };
+        }
+
+        Ok(())
+    }
+
+    fn _delete(conn: &rusqlite::Connection, profile_id: i64, type_: &[u8], id: &[u8]) -> IndyResult<()> {
+        let row_count = conn.execute(
+            "DELETE FROM items where type = ?1 AND name = ?2 AND profile_id = ?3",
+            rusqlite::params![&type_.to_vec(), &id.to_vec(), &profile_id],
+        )?;
+
+        if row_count == 1 {
+            Ok(())
+        } else {
+            Err(err_msg(IndyErrorKind::WalletItemNotFound, "Item to delete not found"))
+        }
+    }
+
+    fn _prepare_statement(&self, sql: &str) -> IndyResult<OwningHandle<Rc<rusqlite::Connection>, Box<rusqlite::Statement<'static>>>> {
+        OwningHandle::try_new(self.conn.clone(), |conn| {
+            unsafe { (*conn).prepare(sql) }.map(Box::new).map_err(IndyError::from)
+        })
+    }
+}
+
+
+impl WalletStorageType for SQLiteStorageType {
+    ///
     /// Deletes the SQLite database file with the provided id from the path specified in the
     /// config file.
     ///
@@ -668,17 +1242,16 @@ impl WalletStorageType for SQLiteStorageType {
 
         let conn = rusqlite::Connection::open(db_path.as_path())?;
 
-        match conn.execute_batch(_CREATE_SCHEMA) {
-            Ok(_) => match conn.execute("INSERT OR REPLACE INTO metadata(value) VALUES(?1)", &[&metadata.to_vec()]) {
-                Ok(_) => Ok(()),
-                Err(error) => {
-                    std::fs::remove_file(db_path)?;
-                    Err(error.into())
-                }
-            },
+        let res = conn.execute_batch(_CREATE_SCHEMA)
+            .and_then(|_| conn.execute("INSERT OR REPLACE INTO metadata(id, value) VALUES(1, ?1)", &[&metadata.to_vec()]))
+            .map_err(IndyError::from)
+            .and_then(|_| SQLiteStorageType::_migrate(&conn));
+
+        match res {
+            Ok(()) => Ok(()),
             Err(error) => {
                 std::fs::remove_file(db_path)?;
-                Err(error.into())
+                Err(error)
             }
         }
     }
@@ -711,42 +1284,113 @@ impl WalletStorageType for SQLiteStorageType {
     ///  * `IOError("IO error during storage operation:...")` - Failed connection or SQL query
     ///
     fn open_storage(&self, id: &str, config: Option<&str>, _credentials: Option<&str>) -> IndyResult<Box<dyn WalletStorage>> {
-        let config = config
-            .map(serde_json::from_str::<Config>)
-            .map_or(Ok(None), |v| v.map(Some))
-            .to_indy(IndyErrorKind::InvalidStructure, "Malformed config json")?;
+        let conn = SQLiteStorageType::_open_connection(id, config)?;
+        Ok(Box::new(SQLiteStorage { conn: Rc::new(conn), profile_id: _DEFAULT_PROFILE_ID }))
+    }
 
-        let db_file_path = SQLiteStorageType::_db_path(id, config.as_ref());
+    ///
+    /// Opens the SQLite DB with read-only connection, so the wallet can be read by several
+    /// processes at the same time while another process writes to it. Readers don't block
+    /// the writer as journal mode of the DB is set to WAL by read-write open.
+    ///
+    /// # Errors
+    ///
+    ///  * `IndyError::NotFound` - File with the provided id not found
+    ///  * `InvalidState` - Schema of the DB is outdated, the wallet has to be opened in read-write mode first
+    ///  * `IOError("IO error during storage operation:...")` - Failed connection or SQL query
+    ///
+    fn open_storage_read_only(&self, id: &str, config: Option<&str>, _credentials: Option<&str>) -> IndyResult<Box<dyn WalletStorage>> {
+        let conn = SQLiteStorageType::_open_connection_read_only(id, config)?;
+        Ok(Box::new(SQLiteStorage { conn: Rc::new(conn), profile_id: _DEFAULT_PROFILE_ID }))
+    }
 
-        if !db_file_path.exists() {
-            return Err(err_msg(IndyErrorKind::WalletNotFound, "No wallet database exists"));
+    ///
+    /// Adds the profile with the provided name and keys to the SQLite DB created before
+    /// by create_storage. Items of the profile are kept in the same tables as items of
+    /// the default profile and are distinguished by profile id.
+    ///
+    /// # Errors
+    ///
+    ///  * `IndyError::NotFound` - File with the provided id not found
+    ///  * `WalletAlreadyExists` - Profile with the provided name already exists
+    ///  * `IOError("IO error during storage operation:...")` - Failed connection or SQL query
+    ///
+    fn create_profile(&self, id: &str, config: Option<&str>, _credentials: Option<&str>, profile: &str, metadata: &[u8]) -> IndyResult<()> {
+        let conn = SQLiteStorageType::_open_connection(id, config)?;
+
+        if SQLiteStorageType::_profile_id(&conn, profile)?.is_some() {
+            return Err(err_msg(IndyErrorKind::WalletAlreadyExists, format!("Wallet profile already exists: {}", profile)));
         }
 
-        let conn = rusqlite::Connection::open(db_file_path.as_path())?;
+        conn.execute("INSERT INTO profiles(name, metadata) VALUES(?1, ?2)", rusqlite::params![profile, metadata.to_vec()])?;
+        Ok(())
+    }
 
-        // set journal mode to WAL, because it provides better performance.
-        let journal_mode: String = conn.query_row(
-            "PRAGMA journal_mode = WAL",
-            [],
-            |row| { row.get(0) },
-        )?;
+    fn open_profile(&self, id: &str, config: Option<&str>, _credentials: Option<&str>, profile: &str, read_only: bool) -> IndyResult<Box<dyn WalletStorage>> {
+        let conn = if read_only {
+            SQLiteStorageType::_open_connection_read_only(id, config)?
+        } else {
+            SQLiteStorageType::_open_connection(id, config)?
+        };
 
-        // if journal mode is set to WAL, set synchronous to FULL for safety reasons.
-        // (synchronous = NORMAL with journal_mode = WAL does not guaranties durability).
-        if journal_mode.to_lowercase() == "wal" {
-            conn.execute("PRAGMA synchronous = FULL", [])?;
+        let profile_id = SQLiteStorageType::_profile_id(&conn, profile)?
+            .ok_or_else(|| err_msg(IndyErrorKind::WalletNotFound, format!("Wallet profile isn't found: {}", profile)))?;
+
+        Ok(Box::new(SQLiteStorage { conn: Rc::new(conn), profile_id }))
+    }
+
+    ///
+    /// Deletes the profile with all its items as a single SQLite transaction.
+    /// Revisions and quarantined items of the profile are deleted too, as nobody can read them anymore.
+    ///
+    /// # Errors
+    ///
+    ///  * `IndyError::NotFound` - File with the provided id or profile with the provided name not found
+    ///  * `IOError("IO error during storage operation:...")` - Failed connection or SQL query
+    ///
+    fn delete_profile(&self, id: &str, config: Option<&str>, _credentials: Option<&str>, profile: &str) -> IndyResult<()> {
+        let conn = SQLiteStorageType::_open_connection(id, config)?;
+
+        let profile_id = SQLiteStorageType::_profile_id(&conn, profile)?
+            .ok_or_else(|| err_msg(IndyErrorKind::WalletNotFound, format!("Wallet profile isn't found: {}", profile)))?;
+
+        let tx: transaction::Transaction = transaction::Transaction::new(&conn, rusqlite::TransactionBehavior::Exclusive)?;
+
+        tx.execute("DELETE FROM tags_encrypted WHERE item_id IN (SELECT id FROM items WHERE profile_id = ?1)", &[&profile_id])?;
+        tx.execute("DELETE FROM tags_plaintext WHERE item_id IN (SELECT id FROM items WHERE profile_id = ?1)", &[&profile_id])?;
+        tx.execute("DELETE FROM items WHERE profile_id = ?1", &[&profile_id])?;
+        tx.execute("DELETE FROM item_revisions WHERE profile_id = ?1", &[&profile_id])?;
+        tx.execute("DELETE FROM quarantine_tags WHERE item_id IN (SELECT id FROM quarantine_items WHERE profile_id = ?1)", &[&profile_id])?;
+        tx.execute("DELETE FROM quarantine_items WHERE profile_id = ?1", &[&profile_id])?;
+        tx.execute("DELETE FROM profiles WHERE id = ?1", &[&profile_id])?;
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn list_profiles(&self, id: &str, config: Option<&str>, _credentials: Option<&str>) -> IndyResult<Vec<String>> {
+        let conn = SQLiteStorageType::_open_connection(id, config)?;
+
+        let mut stmt = conn.prepare("SELECT name FROM profiles ORDER BY name")?;
+        let mut rows = stmt.query([])?;
+
+        let mut profiles = Vec::new();
+
+        while let Some(row) = rows.next()? {
+            profiles.push(row.get(0)?);
         }
 
-        Ok(Box::new(SQLiteStorage { conn: Rc::new(conn) }))
+        Ok(profiles)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use indy_utils::crypto::base64;
     use indy_utils::test;
 
     use super::*;
-    use super::super::Tag;
+    use super::super::{Tag, unix_time};
     use std::path::Path;
 
     #[test]
@@ -805,65 +1449,226 @@ mod tests {
             let storage = _storage("sqlite_storage_get_storage_metadata_works");
             let metadata = storage.get_storage_metadata().unwrap();
 
-            assert_eq!(metadata, _metadata());
+            assert_eq!(metadata, _metadata());
+        }
+        _cleanup("sqlite_storage_get_storage_metadata_works");
+    }
+
+    #[test]
+    fn sqlite_storage_type_delete_works() {
+        _cleanup("sqlite_storage_type_delete_works");
+
+        let storage_type = SQLiteStorageType::new();
+        storage_type.create_storage("sqlite_storage_type_delete_works", None, None, &_metadata()).unwrap();
+
+        storage_type.delete_storage("sqlite_storage_type_delete_works", None, None).unwrap();
+    }
+
+
+    #[test]
+    fn sqlite_storage_type_delete_works_for_non_existing() {
+        _cleanup("sqlite_storage_type_delete_works_for_non_existing");
+
+        let storage_type = SQLiteStorageType::new();
+        storage_type.create_storage("sqlite_storage_type_delete_works_for_non_existing", None, None, &_metadata()).unwrap();
+
+        let res = storage_type.delete_storage("unknown", None, None);
+        assert_kind!(IndyErrorKind::WalletNotFound, res);
+
+        storage_type.delete_storage("sqlite_storage_type_delete_works_for_non_existing", None, None).unwrap();
+    }
+
+    #[test]
+    fn sqlite_storage_type_open_works() {
+        _cleanup("sqlite_storage_type_open_works");
+        _storage("sqlite_storage_type_open_works");
+        _cleanup("sqlite_storage_type_open_works");
+    }
+
+    #[test]
+    fn sqlite_storage_type_open_works_for_custom() {
+        _cleanup("sqlite_storage_type_open_works_for_custom");
+
+        let my_path = _custom_path("sqlite_storage_type_open_works_for_custom");
+        let path = Path::new(&my_path);
+        if path.exists() && path.is_dir() {
+            fs::remove_dir_all(path).unwrap();
+        }
+
+        _storage_custom("sqlite_storage_type_open_works_for_custom");
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn sqlite_storage_type_open_works_for_not_created() {
+        _cleanup("sqlite_storage_type_open_works_for_not_created");
+
+        let storage_type = SQLiteStorageType::new();
+
+        let res = storage_type.open_storage("unknown", Some("{}"), Some("{}"));
+        assert_kind!(IndyErrorKind::WalletNotFound, res);
+    }
+
+    #[test]
+    fn sqlite_storage_type_open_read_only_works() {
+        _cleanup("sqlite_storage_type_open_read_only_works");
+
+        let storage_type = SQLiteStorageType::new();
+        storage_type.create_storage("sqlite_storage_type_open_read_only_works", None, None, &_metadata()).unwrap();
+
+        let writer = storage_type.open_storage("sqlite_storage_type_open_read_only_works", None, None).unwrap();
+        writer.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+
+        let reader_1 = storage_type.open_storage_read_only("sqlite_storage_type_open_read_only_works", None, None).unwrap();
+        let reader_2 = storage_type.open_storage_read_only("sqlite_storage_type_open_read_only_works", None, None).unwrap();
+
+        assert_eq!(reader_1.get_storage_metadata().unwrap(), _metadata());
+        assert_eq!(reader_2.get(&_type1(), &_id1(), r##"{"retrieveValue": true}"##).unwrap().value.unwrap(), _value1());
+
+        // changes of the writer are visible to readers
+        writer.update(&_type1(), &_id1(), &_value2()).unwrap();
+        assert_eq!(reader_1.get(&_type1(), &_id1(), r##"{"retrieveValue": true}"##).unwrap().value.unwrap(), _value2());
+
+        // connection itself rejects writes
+        assert!(reader_1.add(&_type2(), &_id2(), &_value2(), &_tags()).is_err());
+
+        _cleanup("sqlite_storage_type_open_read_only_works");
+    }
+
+    #[test]
+    fn sqlite_storage_type_open_read_only_works_for_outdated_schema() {
+        _cleanup("sqlite_storage_type_open_read_only_works_for_outdated_schema");
+        _create_legacy_storage("sqlite_storage_type_open_read_only_works_for_outdated_schema", &[_CREATE_SCHEMA]);
+
+        let storage_type = SQLiteStorageType::new();
+
+        let res = storage_type.open_storage_read_only("sqlite_storage_type_open_read_only_works_for_outdated_schema", None, None);
+        assert_kind!(IndyErrorKind::InvalidState, res);
+
+        storage_type.open_storage("sqlite_storage_type_open_read_only_works_for_outdated_schema", None, None).unwrap();
+        storage_type.open_storage_read_only("sqlite_storage_type_open_read_only_works_for_outdated_schema", None, None).unwrap();
+
+        _cleanup("sqlite_storage_type_open_read_only_works_for_outdated_schema");
+    }
+
+    #[test]
+    fn sqlite_storage_type_open_read_only_works_for_not_created() {
+        let storage_type = SQLiteStorageType::new();
+
+        let res = storage_type.open_storage_read_only("unknown", None, None);
+        assert_kind!(IndyErrorKind::WalletNotFound, res);
+    }
+
+    #[test]
+    fn sqlite_storage_type_create_works_for_schema_version() {
+        _cleanup("sqlite_storage_type_create_works_for_schema_version");
+
+        let storage_type = SQLiteStorageType::new();
+        storage_type.create_storage("sqlite_storage_type_create_works_for_schema_version", None, None, &_metadata()).unwrap();
+
+        {
+            let conn = _connection("sqlite_storage_type_create_works_for_schema_version");
+            assert_eq!(_SCHEMA_VERSION, SQLiteStorageType::_schema_version(&conn).unwrap());
+
+            // older versions of libindy expect the only row of wallet keys in the metadata table
+            let rows: i64 = conn.query_row("SELECT COUNT(*) FROM metadata", [], |row| row.get(0)).unwrap();
+            assert_eq!(1, rows);
+        }
+
+        let storage = storage_type.open_storage("sqlite_storage_type_create_works_for_schema_version", None, None).unwrap();
+        assert_eq!(storage.get_storage_metadata().unwrap(), _metadata());
+
+        storage.set_storage_metadata(&_value1().data).unwrap();
+        assert_eq!(storage.get_storage_metadata().unwrap(), _value1().data);
+
+        {
+            let conn = _connection("sqlite_storage_type_create_works_for_schema_version");
+            assert_eq!(_SCHEMA_VERSION, SQLiteStorageType::_schema_version(&conn).unwrap());
+        }
+
+        _cleanup("sqlite_storage_type_create_works_for_schema_version");
+    }
+
+    #[test]
+    fn sqlite_storage_type_open_works_for_initial_schema() {
+        _cleanup("sqlite_storage_type_open_works_for_initial_schema");
+        _create_legacy_storage("sqlite_storage_type_open_works_for_initial_schema", &[_CREATE_SCHEMA]);
+
+        {
+            let storage_type = SQLiteStorageType::new();
+            let storage = storage_type.open_storage("sqlite_storage_type_open_works_for_initial_schema", None, None).unwrap();
+
+            assert_eq!(storage.get_storage_metadata().unwrap(), _metadata());
+
+            let record = storage.get(&_type1(), &_id1(), r##"{"retrieveType": false, "retrieveValue": true, "retrieveTags": true}"##).unwrap();
+            assert_eq!(record.value.unwrap(), _value1());
+            assert_eq!(_sort(record.tags.unwrap()), _sort(_tags()));
+
+            // items existing before migration have zero revision
+            assert_eq!(0, storage.get_revision().unwrap());
+
+            storage.update(&_type1(), &_id1(), &_value2()).unwrap();
+            assert_eq!(1, storage.get_revision().unwrap());
+
+            storage.quarantine(&_type1(), &_id1()).unwrap();
+            assert_eq!(vec![(_type1(), _id1())], storage.get_deleted(1).unwrap());
         }
-        _cleanup("sqlite_storage_get_storage_metadata_works");
-    }
 
-    #[test]
-    fn sqlite_storage_type_delete_works() {
-        _cleanup("sqlite_storage_type_delete_works");
+        let conn = _connection("sqlite_storage_type_open_works_for_initial_schema");
+        assert_eq!(_SCHEMA_VERSION, SQLiteStorageType::_schema_version(&conn).unwrap());
 
-        let storage_type = SQLiteStorageType::new();
-        storage_type.create_storage("sqlite_storage_type_delete_works", None, None, &_metadata()).unwrap();
+        let rows: i64 = conn.query_row("SELECT COUNT(*) FROM metadata", [], |row| row.get(0)).unwrap();
+        assert_eq!(1, rows);
 
-        storage_type.delete_storage("sqlite_storage_type_delete_works", None, None).unwrap();
+        _cleanup("sqlite_storage_type_open_works_for_initial_schema");
     }
 
-
     #[test]
-    fn sqlite_storage_type_delete_works_for_non_existing() {
-        _cleanup("sqlite_storage_type_delete_works_for_non_existing");
-
-        let storage_type = SQLiteStorageType::new();
-        storage_type.create_storage("sqlite_storage_type_delete_works_for_non_existing", None, None, &_metadata()).unwrap();
+    fn sqlite_storage_type_open_works_for_revisions_schema_without_version() {
+        _cleanup("sqlite_storage_type_open_works_for_revisions_schema_without_version");
+        _create_legacy_storage("sqlite_storage_type_open_works_for_revisions_schema_without_version", &[_CREATE_SCHEMA, _CREATE_REVISIONS_SCHEMA]);
 
-        let res = storage_type.delete_storage("unknown", None, None);
-        assert_kind!(IndyErrorKind::WalletNotFound, res);
+        {
+            let conn = _connection("sqlite_storage_type_open_works_for_revisions_schema_without_version");
+            assert_eq!(2, SQLiteStorageType::_schema_version(&conn).unwrap());
+        }
 
-        storage_type.delete_storage("sqlite_storage_type_delete_works_for_non_existing", None, None).unwrap();
-    }
+        {
+            let storage_type = SQLiteStorageType::new();
+            let storage = storage_type.open_storage("sqlite_storage_type_open_works_for_revisions_schema_without_version", None, None).unwrap();
 
-    #[test]
-    fn sqlite_storage_type_open_works() {
-        _cleanup("sqlite_storage_type_open_works");
-        _storage("sqlite_storage_type_open_works");
-        _cleanup("sqlite_storage_type_open_works");
-    }
+            assert_eq!(storage.get_storage_metadata().unwrap(), _metadata());
+            assert_eq!(0, storage.get_revision().unwrap());
 
-    #[test]
-    fn sqlite_storage_type_open_works_for_custom() {
-        _cleanup("sqlite_storage_type_open_works_for_custom");
+            storage.quarantine(&_type1(), &_id1()).unwrap();
 
-        let my_path = _custom_path("sqlite_storage_type_open_works_for_custom");
-        let path = Path::new(&my_path);
-        if path.exists() && path.is_dir() {
-            fs::remove_dir_all(path).unwrap();
+            let res = storage.get(&_type1(), &_id1(), "{}");
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
         }
 
-        _storage_custom("sqlite_storage_type_open_works_for_custom");
+        let conn = _connection("sqlite_storage_type_open_works_for_revisions_schema_without_version");
+        assert_eq!(_SCHEMA_VERSION, SQLiteStorageType::_schema_version(&conn).unwrap());
 
-        fs::remove_dir_all(path).unwrap();
+        _cleanup("sqlite_storage_type_open_works_for_revisions_schema_without_version");
     }
 
     #[test]
-    fn sqlite_storage_type_open_works_for_not_created() {
-        _cleanup("sqlite_storage_type_open_works_for_not_created");
+    fn sqlite_storage_type_open_works_for_newer_schema_version() {
+        _cleanup("sqlite_storage_type_open_works_for_newer_schema_version");
 
         let storage_type = SQLiteStorageType::new();
+        storage_type.create_storage("sqlite_storage_type_open_works_for_newer_schema_version", None, None, &_metadata()).unwrap();
 
-        let res = storage_type.open_storage("unknown", Some("{}"), Some("{}"));
-        assert_kind!(IndyErrorKind::WalletNotFound, res);
+        {
+            let conn = _connection("sqlite_storage_type_open_works_for_newer_schema_version");
+            conn.execute_batch(&format!("PRAGMA user_version = {}", _SCHEMA_VERSION + 1)).unwrap();
+        }
+
+        let res = storage_type.open_storage("sqlite_storage_type_open_works_for_newer_schema_version", None, None);
+        assert_kind!(IndyErrorKind::InvalidState, res);
+
+        _cleanup("sqlite_storage_type_open_works_for_newer_schema_version");
     }
 
     #[test]
@@ -1313,10 +2118,497 @@ mod tests {
         _cleanup("sqlite_storage_delete_tags_works_for_non_existing_id");
     }
 
+    #[test]
+    fn sqlite_storage_batch_works() {
+        _cleanup("sqlite_storage_batch_works");
+        {
+            let storage = _storage("sqlite_storage_batch_works");
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+
+            let operations = vec![
+                StorageOperation::Add(_type1(), _id2(), _value2(), _new_tags()),
+                StorageOperation::Update(_type1(), _id1(), _value2()),
+                StorageOperation::UpdateTags(_type1(), _id2(), _tags()),
+                StorageOperation::Delete(_type1(), _id1()),
+            ];
+            storage.batch(&operations).unwrap();
+
+            let res = storage.get(&_type1(), &_id1(), r##"{"retrieveType": false, "retrieveValue": true, "retrieveTags": true}"##);
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+            let record = storage.get(&_type1(), &_id2(), r##"{"retrieveType": false, "retrieveValue": true, "retrieveTags": true}"##).unwrap();
+            assert_eq!(record.value.unwrap(), _value2());
+            assert_eq!(_sort(record.tags.unwrap()), _sort(_tags()));
+        }
+        _cleanup("sqlite_storage_batch_works");
+    }
+
+    #[test]
+    fn sqlite_storage_batch_works_for_rollback() {
+        _cleanup("sqlite_storage_batch_works_for_rollback");
+        {
+            let storage = _storage("sqlite_storage_batch_works_for_rollback");
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+
+            let operations = vec![
+                StorageOperation::Update(_type1(), _id1(), _value2()),
+                StorageOperation::Add(_type1(), _id2(), _value2(), _new_tags()),
+                StorageOperation::Delete(_type2(), _id1()),
+            ];
+            let res = storage.batch(&operations);
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+            let record = storage.get(&_type1(), &_id1(), r##"{"retrieveType": false, "retrieveValue": true, "retrieveTags": true}"##).unwrap();
+            assert_eq!(record.value.unwrap(), _value1());
+            assert_eq!(_sort(record.tags.unwrap()), _sort(_tags()));
+
+            let res = storage.get(&_type1(), &_id2(), r##"{"retrieveType": false, "retrieveValue": true, "retrieveTags": true}"##);
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+        }
+        _cleanup("sqlite_storage_batch_works_for_rollback");
+    }
+
+    #[test]
+    fn sqlite_storage_search_works_for_sort_and_page() {
+        _cleanup("sqlite_storage_search_works_for_sort_and_page");
+        {
+            let storage = _storage("sqlite_storage_search_works_for_sort_and_page");
+
+            for (i, value) in ["b", "c", "a"].iter().enumerate() {
+                storage.add(&_type1(), &_id(i as u8), &_value1(), &[Tag::PlainText(vec![1, 2, 3], value.to_string())]).unwrap();
+            }
+            storage.add(&_type1(), &_id(3), &_value1(), &[]).unwrap();
+
+            let query = language::Operator::And(vec![]);
+
+            let options = json!({
+                "retrieveTotalCount": true,
+                "sortBy": {"tagName": format!("~{}", base64::encode(&[1, 2, 3])), "order": "desc"},
+            }).to_string();
+            let mut iterator = storage.search(&_type1(), &query, Some(&options)).unwrap();
+            assert_eq!(iterator.get_total_count().unwrap(), Some(4));
+            assert_eq!(iterator.next().unwrap().unwrap().id, _id(1));
+            assert_eq!(iterator.next().unwrap().unwrap().id, _id(0));
+            assert_eq!(iterator.next().unwrap().unwrap().id, _id(2));
+            assert_eq!(iterator.next().unwrap().unwrap().id, _id(3));
+            assert!(iterator.next().unwrap().is_none());
+
+            let options = json!({
+                "retrieveTotalCount": true,
+                "sortBy": {"tagName": format!("~{}", base64::encode(&[1, 2, 3]))},
+                "offset": 1,
+                "limit": 2,
+            }).to_string();
+            let mut iterator = storage.search(&_type1(), &query, Some(&options)).unwrap();
+            assert_eq!(iterator.get_total_count().unwrap(), Some(4));
+            assert_eq!(iterator.next().unwrap().unwrap().id, _id(0));
+            assert_eq!(iterator.next().unwrap().unwrap().id, _id(1));
+            assert!(iterator.next().unwrap().is_none());
+        }
+        _cleanup("sqlite_storage_search_works_for_sort_and_page");
+    }
+
+    #[test]
+    fn sqlite_storage_search_works_for_numeric_values() {
+        _cleanup("sqlite_storage_search_works_for_numeric_values");
+        {
+            let storage = _storage("sqlite_storage_search_works_for_numeric_values");
+
+            for (i, value) in ["5", "1.2.3", "1-2", "-1.5e1", ".5", "1e", "+2E+1", "--3"].iter().enumerate() {
+                storage.add(&_type1(), &_id(i as u8), &_value1(), &[Tag::PlainText(vec![1, 2, 3], value.to_string())]).unwrap();
+            }
+
+            let query = language::Operator::NumGt(language::TagName::PlainTagName(vec![1, 2, 3]),
+                                                  language::TargetValue::Unencrypted("0".to_string()));
+
+            let options = json!({"retrieveTotalCount": true, "sortBy": {"tagName": format!("~{}", base64::encode(&[1, 2, 3]))}}).to_string();
+            let mut iterator = storage.search(&_type1(), &query, Some(&options)).unwrap();
+            assert_eq!(iterator.get_total_count().unwrap(), Some(3));
+            assert_eq!(iterator.next().unwrap().unwrap().id, _id(6));
+            assert_eq!(iterator.next().unwrap().unwrap().id, _id(4));
+            assert_eq!(iterator.next().unwrap().unwrap().id, _id(0));
+            assert!(iterator.next().unwrap().is_none());
+        }
+        _cleanup("sqlite_storage_search_works_for_numeric_values");
+    }
+
+    #[test]
+    fn sqlite_storage_check_orphan_tags_works() {
+        _cleanup("sqlite_storage_check_orphan_tags_works");
+        {
+            let storage = _storage("sqlite_storage_check_orphan_tags_works");
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+            assert_eq!(0, storage.check_orphan_tags(false).unwrap());
+        }
+        {
+            let conn = rusqlite::Connection::open(SQLiteStorageType::_db_path("sqlite_storage_check_orphan_tags_works", None)).unwrap();
+            conn.execute_batch("
+                INSERT INTO tags_encrypted(name, value, item_id) VALUES (x'01', x'02', 100);
+                INSERT INTO tags_plaintext(name, value, item_id) VALUES (x'01', 'value', 100);
+            ").unwrap();
+        }
+        {
+            let storage_type = SQLiteStorageType::new();
+            let storage = storage_type.open_storage("sqlite_storage_check_orphan_tags_works", None, None).unwrap();
+
+            assert_eq!(2, storage.check_orphan_tags(false).unwrap());
+            assert_eq!(2, storage.check_orphan_tags(true).unwrap());
+            assert_eq!(0, storage.check_orphan_tags(false).unwrap());
+
+            let record = storage.get(&_type1(), &_id1(), r##"{"retrieveType": false, "retrieveValue": false, "retrieveTags": true}"##).unwrap();
+            assert_eq!(_sort(record.tags.unwrap()), _sort(_tags()));
+        }
+        _cleanup("sqlite_storage_check_orphan_tags_works");
+    }
+
+    #[test]
+    fn sqlite_storage_quarantine_works() {
+        _cleanup("sqlite_storage_quarantine_works");
+        {
+            let storage = _storage("sqlite_storage_quarantine_works");
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+            storage.add(&_type2(), &_id2(), &_value2(), &_tags()).unwrap();
+
+            storage.quarantine(&_type1(), &_id1()).unwrap();
+
+            let res = storage.get(&_type1(), &_id1(), r##"{"retrieveType": false, "retrieveValue": true, "retrieveTags": true}"##);
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+            let mut storage_iterator = storage.get_all().unwrap();
+            assert_eq!(storage_iterator.next().unwrap().unwrap().id, _id2());
+            assert!(storage_iterator.next().unwrap().is_none());
+
+            let res = storage.quarantine(&_type1(), &_id1());
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+        }
+        {
+            let conn = rusqlite::Connection::open(SQLiteStorageType::_db_path("sqlite_storage_quarantine_works", None)).unwrap();
+
+            let items: i64 = conn.query_row("SELECT COUNT(*) FROM quarantine_items", [], |row| row.get(0)).unwrap();
+            assert_eq!(1, items);
+
+            let tags: i64 = conn.query_row("SELECT COUNT(*) FROM quarantine_tags", [], |row| row.get(0)).unwrap();
+            assert_eq!(2, tags);
+
+            let orphan_tags: i64 = conn.query_row("SELECT COUNT(*) FROM tags_encrypted WHERE item_id NOT IN (SELECT id FROM items)", [], |row| row.get(0)).unwrap();
+            assert_eq!(0, orphan_tags);
+        }
+        _cleanup("sqlite_storage_quarantine_works");
+    }
+
+    #[test]
+    fn sqlite_storage_expiry_works() {
+        _cleanup("sqlite_storage_expiry_works");
+        {
+            let storage = _storage("sqlite_storage_expiry_works");
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+            storage.add(&_type1(), &_id2(), &_value2(), &_tags()).unwrap();
+
+            storage.batch(&[
+                StorageOperation::SetExpiry(_type1(), _id1(), Some(1)),
+                StorageOperation::SetExpiry(_type1(), _id2(), Some(unix_time() + 3600)),
+            ]).unwrap();
+
+            let res = storage.get(&_type1(), &_id1(), "{}");
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+            storage.get(&_type1(), &_id2(), "{}").unwrap();
+
+            let mut storage_iterator = storage.search(&_type1(), &language::Operator::And(vec![]), Some(r##"{"retrieveTotalCount": true}"##)).unwrap();
+            assert_eq!(storage_iterator.get_total_count().unwrap(), Some(1));
+            assert_eq!(storage_iterator.next().unwrap().unwrap().id, _id2());
+            assert!(storage_iterator.next().unwrap().is_none());
+
+            let mut storage_iterator = storage.get_all().unwrap();
+            assert_eq!(storage_iterator.next().unwrap().unwrap().id, _id2());
+            assert!(storage_iterator.next().unwrap().is_none());
+
+            // expired item doesn't prevent adding of the item with the same name
+            storage.add(&_type1(), &_id1(), &_value2(), &[]).unwrap();
+            storage.batch(&[StorageOperation::SetExpiry(_type1(), _id1(), Some(1))]).unwrap();
+
+            assert_eq!(1, storage.purge_expired().unwrap());
+            assert_eq!(0, storage.purge_expired().unwrap());
+
+            let res = storage.batch(&[StorageOperation::SetExpiry(_type1(), _id1(), None)]);
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+        }
+        {
+            let conn = _connection("sqlite_storage_expiry_works");
+
+            let orphan_tags: i64 = conn.query_row("SELECT COUNT(*) FROM tags_encrypted WHERE item_id NOT IN (SELECT id FROM items)", [], |row| row.get(0)).unwrap();
+            assert_eq!(0, orphan_tags);
+        }
+        _cleanup("sqlite_storage_expiry_works");
+    }
+
+    #[test]
+    fn sqlite_storage_count_by_tag_works() {
+        _cleanup("sqlite_storage_count_by_tag_works");
+        {
+            let storage = _storage("sqlite_storage_count_by_tag_works");
+            storage.add(&_type1(), &_id(1), &_value1(), &[Tag::Encrypted(vec![1], vec![20]), Tag::PlainText(vec![2], "b".to_string())]).unwrap();
+            storage.add(&_type1(), &_id(2), &_value1(), &[Tag::Encrypted(vec![1], vec![10]), Tag::PlainText(vec![2], "a".to_string())]).unwrap();
+            storage.add(&_type1(), &_id(3), &_value1(), &[Tag::Encrypted(vec![1], vec![20])]).unwrap();
+            storage.add(&_type1(), &_id(4), &_value1(), &[Tag::PlainText(vec![2], "b".to_string())]).unwrap();
+            storage.add(&_type2(), &_id(5), &_value1(), &[Tag::Encrypted(vec![1], vec![30])]).unwrap();
+            storage.add(&_type1(), &_id(6), &_value1(), &[Tag::Encrypted(vec![1], vec![40])]).unwrap();
+            storage.batch(&[StorageOperation::SetExpiry(_type1(), _id(6), Some(1))]).unwrap();
+
+            let counts = storage.count_by_tag(&_type1(), &language::Operator::And(vec![]), &TagName::OfEncrypted(vec![1])).unwrap();
+            assert_eq!(counts, vec![(vec![10], 1), (vec![20], 2)]);
+
+            let counts = storage.count_by_tag(&_type1(), &language::Operator::And(vec![]), &TagName::OfPlain(vec![2])).unwrap();
+            assert_eq!(counts, vec![(b"a".to_vec(), 1), (b"b".to_vec(), 2)]);
+
+            let query = language::Operator::Eq(language::TagName::PlainTagName(vec![2]), language::TargetValue::Unencrypted("b".to_string()));
+            let counts = storage.count_by_tag(&_type1(), &query, &TagName::OfEncrypted(vec![1])).unwrap();
+            assert_eq!(counts, vec![(vec![20], 1)]);
+
+            let values = storage.get_distinct_tag_values(&_type1(), &language::Operator::And(vec![]), &TagName::OfEncrypted(vec![1])).unwrap();
+            assert_eq!(values, vec![vec![10], vec![20]]);
+
+            let values = storage.get_distinct_tag_values(&_type1(), &language::Operator::And(vec![]), &TagName::OfEncrypted(vec![3])).unwrap();
+            assert!(values.is_empty());
+        }
+        _cleanup("sqlite_storage_count_by_tag_works");
+    }
+
+    #[test]
+    fn sqlite_storage_revisions_work() {
+        _cleanup("sqlite_storage_revisions_work");
+        {
+            let storage = _storage("sqlite_storage_revisions_work");
+            assert_eq!(0, storage.get_revision().unwrap());
+
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+            storage.add(&_type2(), &_id2(), &_value2(), &_tags()).unwrap();
+
+            let revision = storage.get_revision().unwrap();
+            assert!(revision > 0);
+
+            let mut storage_iterator = storage.get_changed(revision).unwrap();
+            assert!(storage_iterator.next().unwrap().is_none());
+
+            storage.update(&_type1(), &_id1(), &_value2()).unwrap();
+            storage.delete(&_type2(), &_id2()).unwrap();
+            assert!(storage.get_revision().unwrap() > revision);
+
+            let mut storage_iterator = storage.get_changed(revision).unwrap();
+
+            let record = storage_iterator.next().unwrap().unwrap();
+            assert_eq!(record.id, _id1());
+            assert_eq!(record.type_.unwrap(), _type1());
+            assert_eq!(record.value.unwrap(), _value2());
+            assert_eq!(_sort(record.tags.unwrap()), _sort(_tags()));
+            assert!(storage_iterator.next().unwrap().is_none());
+
+            let deleted = storage.get_deleted(revision).unwrap();
+            assert_eq!(vec![(_type2(), _id2())], deleted);
+        }
+        _cleanup("sqlite_storage_revisions_work");
+    }
+
+    #[test]
+    fn sqlite_storage_rekey_deleted_works() {
+        _cleanup("sqlite_storage_rekey_deleted_works");
+        {
+            let storage = _storage("sqlite_storage_rekey_deleted_works");
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+            storage.add(&_type2(), &_id2(), &_value2(), &_tags()).unwrap();
+            storage.delete(&_type1(), &_id1()).unwrap();
+            storage.delete(&_type2(), &_id2()).unwrap();
+            storage.add(&_type1(), b"rekeyed1", &_value1(), &_tags()).unwrap();
+
+            let revision = storage.get_revision().unwrap();
+
+            let marks = vec![
+                ((_type1(), _id1()), (_type1(), b"rekeyed1".to_vec())),
+                ((_type2(), _id2()), (_type2(), b"rekeyed2".to_vec())),
+            ];
+            assert_eq!(1, storage.rekey_deleted(&marks).unwrap());
+
+            assert_eq!(vec![(_type2(), b"rekeyed2".to_vec())], storage.get_deleted(0).unwrap());
+            assert_eq!(revision, storage.get_revision().unwrap());
+        }
+        _cleanup("sqlite_storage_rekey_deleted_works");
+    }
+
+    #[test]
+    fn sqlite_storage_revisions_work_for_tags_change() {
+        _cleanup("sqlite_storage_revisions_work_for_tags_change");
+        {
+            let storage = _storage("sqlite_storage_revisions_work_for_tags_change");
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+            storage.add(&_type2(), &_id2(), &_value2(), &_tags()).unwrap();
+
+            let revision = storage.get_revision().unwrap();
+            storage.update_tags(&_type2(), &_id2(), &_new_tags()).unwrap();
+
+            let mut storage_iterator = storage.get_changed(revision).unwrap();
+
+            let record = storage_iterator.next().unwrap().unwrap();
+            assert_eq!(record.id, _id2());
+            assert_eq!(_sort(record.tags.unwrap()), _sort(_new_tags()));
+            assert!(storage_iterator.next().unwrap().is_none());
+        }
+        _cleanup("sqlite_storage_revisions_work_for_tags_change");
+    }
+
+    #[test]
+    fn sqlite_storage_profiles_work() {
+        _cleanup("sqlite_storage_profiles_work");
+        {
+            let storage_type = SQLiteStorageType::new();
+            let storage = _storage("sqlite_storage_profiles_work");
+
+            storage_type.create_profile("sqlite_storage_profiles_work", None, None, "profile1", &_value1().data).unwrap();
+
+            let res = storage_type.create_profile("sqlite_storage_profiles_work", None, None, "profile1", &_value1().data);
+            assert_kind!(IndyErrorKind::WalletAlreadyExists, res);
+
+            assert_eq!(vec!["profile1".to_string()], storage_type.list_profiles("sqlite_storage_profiles_work", None, None).unwrap());
+
+            let profile = storage_type.open_profile("sqlite_storage_profiles_work", None, None, "profile1", false).unwrap();
+            assert_eq!(storage.get_storage_metadata().unwrap(), _metadata());
+            assert_eq!(profile.get_storage_metadata().unwrap(), _value1().data);
+
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+            profile.add(&_type1(), &_id1(), &_value2(), &_tags()).unwrap();
+            profile.add(&_type2(), &_id2(), &_value2(), &_tags()).unwrap();
+
+            let record = storage.get(&_type1(), &_id1(), r##"{"retrieveType": false, "retrieveValue": true, "retrieveTags": false}"##).unwrap();
+            assert_eq!(record.value.unwrap(), _value1());
+
+            let record = profile.get(&_type1(), &_id1(), r##"{"retrieveType": false, "retrieveValue": true, "retrieveTags": false}"##).unwrap();
+            assert_eq!(record.value.unwrap(), _value2());
+
+            let res = storage.get(&_type2(), &_id2(), "{}");
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+            let res = storage.delete(&_type2(), &_id2());
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+            let mut storage_iterator = profile.get_all().unwrap();
+            let mut count = 0;
+            while let Some(_) = storage_iterator.next().unwrap() {
+                count += 1;
+            }
+            assert_eq!(2, count);
+
+            let revision = storage.get_revision().unwrap();
+            profile.delete(&_type1(), &_id1()).unwrap();
+            assert!(storage.get_deleted(0).unwrap().is_empty());
+            assert_eq!(vec![(_type1(), _id1())], profile.get_deleted(revision).unwrap());
+
+            storage.add(&_type2(), &_id2(), &_value1(), &_tags()).unwrap();
+            storage.quarantine(&_type2(), &_id2()).unwrap();
+            profile.quarantine(&_type2(), &_id2()).unwrap();
+
+            storage_type.delete_profile("sqlite_storage_profiles_work", None, None, "profile1").unwrap();
+
+            // only quarantined item of the default profile is left
+            let conn = rusqlite::Connection::open(SQLiteStorageType::_db_path("sqlite_storage_profiles_work", None)).unwrap();
+            let items: i64 = conn.query_row("SELECT COUNT(*) FROM quarantine_items WHERE profile_id = 0", [], |row| row.get(0)).unwrap();
+            assert_eq!(1, items);
+            let items: i64 = conn.query_row("SELECT COUNT(*) FROM quarantine_items", [], |row| row.get(0)).unwrap();
+            assert_eq!(1, items);
+            let tags: i64 = conn.query_row("SELECT COUNT(*) FROM quarantine_tags", [], |row| row.get(0)).unwrap();
+            assert_eq!(_tags().len() as i64, tags);
+            assert!(storage_type.list_profiles("sqlite_storage_profiles_work", None, None).unwrap().is_empty());
+
+            let res = storage_type.open_profile("sqlite_storage_profiles_work", None, None, "profile1", false);
+            assert_kind!(IndyErrorKind::WalletNotFound, res);
+
+            let record = storage.get(&_type1(), &_id1(), r##"{"retrieveType": false, "retrieveValue": true, "retrieveTags": false}"##).unwrap();
+            assert_eq!(record.value.unwrap(), _value1());
+
+            let orphan_tags = storage.check_orphan_tags(false).unwrap();
+            assert_eq!(0, orphan_tags);
+        }
+        _cleanup("sqlite_storage_profiles_work");
+    }
+
+    #[test]
+    fn sqlite_storage_type_create_profile_works_for_not_created() {
+        let storage_type = SQLiteStorageType::new();
+
+        let res = storage_type.create_profile("unknown", None, None, "profile1", &_metadata());
+        assert_kind!(IndyErrorKind::WalletNotFound, res);
+    }
+
+    #[test]
+    fn sqlite_storage_type_open_works_for_wallet_without_revisions() {
+        _cleanup("sqlite_storage_type_open_works_for_wallet_without_revisions");
+        {
+            let storage = _storage("sqlite_storage_type_open_works_for_wallet_without_revisions");
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+        }
+        {
+            let conn = rusqlite::Connection::open(SQLiteStorageType::_db_path("sqlite_storage_type_open_works_for_wallet_without_revisions", None)).unwrap();
+            conn.execute_batch("
+                DROP TRIGGER tr_items_insert;
+                DROP TRIGGER tr_items_update;
+                DROP TRIGGER tr_items_delete;
+                DROP TRIGGER tr_tags_encrypted_insert;
+                DROP TRIGGER tr_tags_encrypted_update;
+                DROP TRIGGER tr_tags_encrypted_delete;
+                DROP TRIGGER tr_tags_plaintext_insert;
+                DROP TRIGGER tr_tags_plaintext_update;
+                DROP TRIGGER tr_tags_plaintext_delete;
+                DROP TABLE item_revisions;
+            ").unwrap();
+        }
+        {
+            let storage_type = SQLiteStorageType::new();
+            let storage = storage_type.open_storage("sqlite_storage_type_open_works_for_wallet_without_revisions", None, None).unwrap();
+
+            assert_eq!(0, storage.get_revision().unwrap());
+            assert!(storage.get_changed(0).unwrap().next().unwrap().is_none());
+
+            storage.delete(&_type1(), &_id1()).unwrap();
+            assert_eq!(vec![(_type1(), _id1())], storage.get_deleted(0).unwrap());
+        }
+        _cleanup("sqlite_storage_type_open_works_for_wallet_without_revisions");
+    }
+
     fn _cleanup(name: &str) {
         test::cleanup_storage(name)
     }
 
+    fn _connection(name: &str) -> rusqlite::Connection {
+        rusqlite::Connection::open(SQLiteStorageType::_db_path(name, None)).unwrap()
+    }
+
+    // Creates wallet database the way older versions of libindy did, without schema version record.
+    fn _create_legacy_storage(name: &str, schemas: &[&str]) {
+        let db_path = SQLiteStorageType::_db_path(name, None);
+        fs::DirBuilder::new().recursive(true).create(db_path.parent().unwrap()).unwrap();
+
+        let conn = rusqlite::Connection::open(db_path).unwrap();
+
+        conn.execute_batch(schemas[0]).unwrap();
+        conn.execute("INSERT OR REPLACE INTO metadata(value) VALUES(?1)", &[&_metadata()]).unwrap();
+
+        // items are inserted directly as SQL of the storage refers columns added by later migrations
+        let value = _value1();
+        conn.execute("INSERT INTO items (type, name, value, key) VALUES (?1, ?2, ?3, ?4)",
+                     rusqlite::params![_type1(), _id1(), value.data, value.key]).unwrap();
+        let item_id = conn.last_insert_rowid();
+
+        for tag in _tags() {
+            match tag {
+                Tag::Encrypted(name, value) =>
+                    conn.execute("INSERT INTO tags_encrypted (item_id, name, value) VALUES (?1, ?2, ?3)", rusqlite::params![item_id, name, value]).unwrap(),
+                Tag::PlainText(name, value) =>
+                    conn.execute("INSERT INTO tags_plaintext (item_id, name, value) VALUES (?1, ?2, ?3)", rusqlite::params![item_id, name, value]).unwrap(),
+            };
+        }
+
+        for schema in &schemas[1..] {
+            conn.execute_batch(schema).unwrap();
+        }
+    }
+
     fn _storage(name: &str) -> Box<dyn WalletStorage> {
         let storage_type = SQLiteStorageType::new();
         storage_type.create_storage(name, None, None, &_metadata()).unwrap();
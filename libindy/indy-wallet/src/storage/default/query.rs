@@ -1,34 +1,95 @@
 use indy_api_types::errors::prelude::*;
 use rusqlite::types::ToSql;
 use crate::language::{Operator, TagName, TargetValue};
+use crate::{SearchOptions, SortBy};
+
+
+// Sorting and paging of search results prepared for binding as query arguments
+pub struct SearchOrder {
+    sort_tag_name: Option<Vec<u8>>,
+    descending: bool,
+    limit: i64,
+    offset: i64,
+}
+
+impl SearchOrder {
+    pub fn from_options(options: &SearchOptions) -> IndyResult<SearchOrder> {
+        Ok(SearchOrder {
+            sort_tag_name: options.sort_by.as_ref().map(SortBy::encrypted_tag_name).transpose()?,
+            descending: options.sort_by.as_ref().map(SortBy::is_descending).unwrap_or(false),
+            // negative LIMIT means no upper bound for SQLite
+            limit: options.limit.map(|limit| limit as i64).unwrap_or(-1),
+            offset: options.offset.unwrap_or(0) as i64,
+        })
+    }
+
+    fn is_paged(&self) -> bool {
+        self.limit >= 0 || self.offset > 0
+    }
+}
+
+
+const SORT_VALUE: &str = "SELECT value FROM tags_plaintext WHERE item_id = i.id AND name = ?";
+
+// Conditions on expiry time of item aliased as `i` against current unix time of SQLite
+pub const EXPIRED: &str = "i.expires_at <= CAST(strftime('%s', 'now') AS INTEGER)";
+pub const NOT_EXPIRED: &str = "(i.expires_at IS NULL OR i.expires_at > CAST(strftime('%s', 'now') AS INTEGER))";
+
+// Tag values of the form ^[+-]?([0-9]+\.?[0-9]*|\.[0-9]+)([eE][+-]?[0-9]+)?$, anything else never
+// matches numeric comparisons. SQLite has no regexps, so the sign is trimmed and the mantissa alone
+// or the value without exponent digits is checked by GLOB patterns.
+const NUMERIC_VALUE: &str = "(value NOT GLOB '[+-][+-]*' AND (\
+(ltrim(value, '+-') GLOB '*[0-9]*' AND ltrim(value, '+-') NOT GLOB '*[^0-9.]*' AND ltrim(value, '+-') NOT GLOB '*.*.*') OR \
+(length(rtrim(ltrim(value, '+-'), '0123456789')) < length(ltrim(value, '+-')) AND \
+(rtrim(ltrim(value, '+-'), '0123456789') GLOB '*[eE]' OR rtrim(ltrim(value, '+-'), '0123456789') GLOB '*[eE][+-]') AND \
+rtrim(ltrim(value, '+-'), '0123456789') NOT GLOB '*[^0-9.eE+-]*' AND \
+rtrim(ltrim(value, '+-'), '0123456789') NOT GLOB '*[eE]*[eE]*' AND \
+rtrim(ltrim(value, '+-'), '0123456789') NOT GLOB '*[+-]*[eE]*' AND \
+rtrim(ltrim(value, '+-'), '0123456789') NOT GLOB '*.*.*' AND \
+rtrim(ltrim(value, '+-'), '0123456789') GLOB '*[0-9]*[eE]*')))";
 
 
 // Translates Wallet Query Language to SQL
 // WQL input is provided as a reference to a top level Operator
+// Profile id restricts the query to the items of the wallet profile
 // Result is a tuple of query string and query arguments
-pub fn wql_to_sql<'a>(class: &'a Vec<u8>, op: &'a Operator, _options: Option<&str>) -> Result<(String, Vec<&'a dyn ToSql>), IndyError> {
+pub fn wql_to_sql<'a>(class: &'a Vec<u8>, op: &'a Operator, order: &'a SearchOrder, profile_id: &'a i64) -> Result<(String, Vec<&'a dyn ToSql>), IndyError> {
     let mut arguments: Vec<&dyn ToSql> = Vec::new();
     arguments.push(class);
+    arguments.push(profile_id);
     let clause_string = operator_to_sql(op, &mut arguments)?;
-    const BASE: &str = "SELECT i.id, i.name, i.value, i.key, i.type FROM items as i WHERE i.type = ?";
+    let mut query_string = format!("SELECT i.id, i.name, i.value, i.key, i.type FROM items as i WHERE i.type = ? AND i.profile_id = ? AND {}", NOT_EXPIRED);
     if !clause_string.is_empty() {
-        let mut query_string = String::with_capacity(BASE.len() + 5 + clause_string.len());
-        query_string.push_str(BASE);
         query_string.push_str(" AND ");
         query_string.push_str(&clause_string);
-        Ok((query_string, arguments))
-    } else {
-        Ok((BASE.to_string(), arguments))
     }
 
+    // records without the tag go last in both directions, item id keeps pages stable
+    if let Some(ref sort_tag_name) = order.sort_tag_name {
+        arguments.push(sort_tag_name);
+        arguments.push(sort_tag_name);
+        query_string.push_str(&format!(" ORDER BY ({0}) IS NULL, ({0}) {1}, i.id",
+                                       SORT_VALUE, if order.descending { "DESC" } else { "ASC" }));
+    } else if order.is_paged() {
+        query_string.push_str(" ORDER BY i.id");
+    }
+
+    if order.is_paged() {
+        arguments.push(&order.limit);
+        arguments.push(&order.offset);
+        query_string.push_str(" LIMIT ? OFFSET ?");
+    }
+
+    Ok((query_string, arguments))
 }
 
 
-pub fn wql_to_sql_count<'a>(class: &'a Vec<u8>, op: &'a Operator) -> Result<(String, Vec<&'a dyn ToSql>), IndyError> {
+pub fn wql_to_sql_count<'a>(class: &'a Vec<u8>, op: &'a Operator, profile_id: &'a i64) -> Result<(String, Vec<&'a dyn ToSql>), IndyError> {
     let mut arguments: Vec<&dyn ToSql> = Vec::new();
     arguments.push(class);
+    arguments.push(profile_id);
     let clause_string = operator_to_sql(op, &mut arguments)?;
-    let mut query_string = "SELECT count(*) FROM items as i WHERE i.type = ?".to_string();
+    let mut query_string = format!("SELECT count(*) FROM items as i WHERE i.type = ? AND i.profile_id = ? AND {}", NOT_EXPIRED);
     if !clause_string.is_empty() {
         query_string.push_str(" AND ");
         query_string.push_str(&clause_string);
@@ -37,6 +98,25 @@ pub fn wql_to_sql_count<'a>(class: &'a Vec<u8>, op: &'a Operator) -> Result<(Str
 }
 
 
+// Count of items matching the query per value of the tag.
+// `tags_table` is tags_plaintext or tags_encrypted depending on the kind of the tag.
+pub fn wql_to_sql_count_by_tag<'a>(class: &'a Vec<u8>, op: &'a Operator, tags_table: &str, tag_name: &'a Vec<u8>, profile_id: &'a i64) -> Result<(String, Vec<&'a dyn ToSql>), IndyError> {
+    let mut arguments: Vec<&dyn ToSql> = Vec::new();
+    arguments.push(tag_name);
+    arguments.push(class);
+    arguments.push(profile_id);
+    let clause_string = operator_to_sql(op, &mut arguments)?;
+    let mut query_string = format!("SELECT t.value, count(*) FROM items as i INNER JOIN {} as t ON t.item_id = i.id AND t.name = ? WHERE i.type = ? AND i.profile_id = ? AND {}",
+                                   tags_table, NOT_EXPIRED);
+    if !clause_string.is_empty() {
+        query_string.push_str(" AND ");
+        query_string.push_str(&clause_string);
+    }
+    query_string.push_str(" GROUP BY t.value ORDER BY t.value");
+    Ok((query_string, arguments))
+}
+
+
 fn operator_to_sql<'a>(op: &'a Operator, arguments: &mut Vec<&'a dyn ToSql>) -> IndyResult<String> {
     match *op {
         Operator::Eq(ref tag_name, ref target_value) => eq_to_sql(tag_name, target_value, arguments),
@@ -47,6 +127,12 @@ fn operator_to_sql<'a>(op: &'a Operator, arguments: &mut Vec<&'a dyn ToSql>) ->
         Operator::Lte(ref tag_name, ref target_value) => lte_to_sql(tag_name, target_value, arguments),
         Operator::Like(ref tag_name, ref target_value) => like_to_sql(tag_name, target_value, arguments),
         Operator::In(ref tag_name, ref target_values) => in_to_sql(tag_name, target_values, arguments),
+        Operator::Exist(ref tag_name) => exist_to_sql(tag_name, arguments),
+        Operator::NumGt(ref tag_name, ref target_value) => num_cmp_to_sql(tag_name, target_value, ">", "$gt", arguments),
+        Operator::NumGte(ref tag_name, ref target_value) => num_cmp_to_sql(tag_name, target_value, ">=", "$gte", arguments),
+        Operator::NumLt(ref tag_name, ref target_value) => num_cmp_to_sql(tag_name, target_value, "<", "$lt", arguments),
+        Operator::NumLte(ref tag_name, ref target_value) => num_cmp_to_sql(tag_name, target_value, "<=", "$lte", arguments),
+        Operator::Prefix(ref tag_name, ref target_value) => prefix_to_sql(tag_name, target_value, arguments),
         Operator::And(ref suboperators) => and_to_sql(suboperators, arguments),
         Operator::Or(ref suboperators) => or_to_sql(suboperators, arguments),
         Operator::Not(ref suboperator) => not_to_sql(suboperator, arguments),
@@ -192,6 +278,47 @@ fn in_to_sql<'a>(name: &'a TagName, values: &'a Vec<TargetValue>, arguments: &mu
 }
 
 
+fn exist_to_sql<'a>(name: &'a TagName, arguments: &mut Vec<&'a dyn ToSql>) -> IndyResult<String> {
+    match *name {
+        TagName::PlainTagName(ref queried_name) => {
+            arguments.push(queried_name);
+            Ok("(i.id in (SELECT item_id FROM tags_plaintext WHERE name = ?))".to_string())
+        },
+        TagName::EncryptedTagName(ref queried_name) => {
+            arguments.push(queried_name);
+            Ok("(i.id in (SELECT item_id FROM tags_encrypted WHERE name = ?))".to_string())
+        },
+    }
+}
+
+
+fn num_cmp_to_sql<'a>(name: &'a TagName, value: &'a TargetValue, sql_op: &str, wql_op: &str, arguments: &mut Vec<&'a dyn ToSql>) -> IndyResult<String> {
+    match (name, value) {
+        (&TagName::PlainTagName(ref queried_name), &TargetValue::Unencrypted(ref queried_value)) => {
+            arguments.push(queried_name);
+            arguments.push(queried_value);
+            Ok(format!("(i.id in (SELECT item_id FROM tags_plaintext WHERE name = ? AND {} AND CAST(value AS REAL) {} CAST(? AS REAL)))",
+                       NUMERIC_VALUE, sql_op))
+        },
+        _ => Err(err_msg(IndyErrorKind::WalletQueryError, format!("Invalid combination of tag name and value for numeric {} operator", wql_op)))
+    }
+}
+
+
+// substr and length count characters, so multibyte prefixes are compared as a whole
+fn prefix_to_sql<'a>(name: &'a TagName, value: &'a TargetValue, arguments: &mut Vec<&'a dyn ToSql>) -> IndyResult<String> {
+    match (name, value) {
+        (&TagName::PlainTagName(ref queried_name), &TargetValue::Unencrypted(ref queried_value)) => {
+            arguments.push(queried_name);
+            arguments.push(queried_value);
+            arguments.push(queried_value);
+            Ok("(i.id in (SELECT item_id FROM tags_plaintext WHERE name = ? AND substr(value, 1, length(?)) = ?))".to_string())
+        },
+        _ => Err(err_msg(IndyErrorKind::WalletQueryError, "Invalid combination of tag name and value for $prefix operator"))
+    }
+}
+
+
 fn and_to_sql<'a>(suboperators: &'a [Operator], arguments: &mut Vec<&'a dyn ToSql>) -> IndyResult<String> {
     join_operators(suboperators, " AND ", arguments)
 }
@@ -241,6 +368,65 @@ mod tests {
         ]);
         let query = Operator::Or(vec![condition_1, condition_2]);
         let class = vec![100,100,100];
-        let (_query, _arguments) = wql_to_sql(&class, &query, None).unwrap();
+        let order = SearchOrder::from_options(&SearchOptions::default()).unwrap();
+        let (_query, _arguments) = wql_to_sql(&class, &query, &order, &0).unwrap();
+    }
+
+    #[test]
+    fn sort_and_page() {
+        let query = Operator::Eq(TagName::PlainTagName(vec![7, 8, 9]), TargetValue::Unencrypted("spam".to_string()));
+        let class = vec![100, 100, 100];
+        let options: SearchOptions = serde_json::from_str(r#"{"sortBy": {"tagName": "~AQID", "order": "desc"}, "offset": 10, "limit": 5}"#).unwrap();
+        let order = SearchOrder::from_options(&options).unwrap();
+
+        let (query, arguments) = wql_to_sql(&class, &query, &order, &0).unwrap();
+
+        assert!(query.ends_with(&format!(" ORDER BY ({0}) IS NULL, ({0}) DESC, i.id LIMIT ? OFFSET ?", SORT_VALUE)));
+        assert_eq!(arguments.len(), 8);
+        assert_eq!(order.sort_tag_name, Some(vec![1, 2, 3]));
+        assert_eq!(order.limit, 5);
+        assert_eq!(order.offset, 10);
+    }
+
+    #[test]
+    fn exist_and_numeric() {
+        let query = Operator::And(vec![
+            Operator::Exist(TagName::EncryptedTagName(vec![1, 2, 3])),
+            Operator::NumGt(TagName::PlainTagName(vec![4, 5, 6]), TargetValue::Unencrypted("10".to_string())),
+        ]);
+        let class = vec![100, 100, 100];
+        let order = SearchOrder::from_options(&SearchOptions::default()).unwrap();
+
+        let (query, arguments) = wql_to_sql(&class, &query, &order, &0).unwrap();
+
+        let expected = format!("SELECT i.id, i.name, i.value, i.key, i.type FROM items as i WHERE i.type = ? AND i.profile_id = ? AND {} AND \
+((i.id in (SELECT item_id FROM tags_encrypted WHERE name = ?)) AND \
+(i.id in (SELECT item_id FROM tags_plaintext WHERE name = ? AND {} AND CAST(value AS REAL) > CAST(? AS REAL))))",
+                               NOT_EXPIRED, NUMERIC_VALUE);
+        assert_eq!(query, expected);
+        assert_eq!(arguments.len(), 5);
+    }
+
+    #[test]
+    fn numeric_for_encrypted_tag_fails() {
+        let query = Operator::NumLt(TagName::EncryptedTagName(vec![1, 2, 3]), TargetValue::Encrypted(vec![4, 5, 6]));
+        let class = vec![100, 100, 100];
+        let order = SearchOrder::from_options(&SearchOptions::default()).unwrap();
+
+        let res = wql_to_sql(&class, &query, &order, &0);
+
+        assert_kind!(IndyErrorKind::WalletQueryError, res);
+    }
+
+    #[test]
+    fn prefix_works() {
+        let query = Operator::Prefix(TagName::PlainTagName(vec![4, 5, 6]), TargetValue::Unencrypted("ab".to_string()));
+        let class = vec![100, 100, 100];
+        let order = SearchOrder::from_options(&SearchOptions::default()).unwrap();
+
+        let (query, arguments) = wql_to_sql(&class, &query, &order, &0).unwrap();
+
+        assert!(query.contains("(i.id in (SELECT item_id FROM tags_plaintext WHERE name = ? AND substr(value, 1, length(?)) = ?))"));
+        assert_eq!(arguments.len(), 5);
     }
 }
\ No newline at end of file
@@ -0,0 +1,1051 @@
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+
+use indy_api_types::errors::prelude::*;
+use crate::language::{Operator, TagName as QueryTagName, TargetValue};
+
+use super::{EncryptedValue, StorageIterator, StorageOperation, StorageRecord, Tag, TagName, WalletStorage, WalletStorageType, unix_time};
+use super::super::{RecordOptions, SearchOptions};
+
+#[derive(Clone, Debug)]
+struct InmemItem {
+    type_: Vec<u8>,
+    name: Vec<u8>,
+    value: EncryptedValue,
+    tags: Vec<Tag>,
+    expires_at: Option<u64>,
+}
+
+impl InmemItem {
+    fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.map(|expires_at| expires_at <= now).unwrap_or(false)
+    }
+
+    fn to_record(&self, options: &RecordOptions) -> StorageRecord {
+        StorageRecord::new(
+            self.name.clone(),
+            if options.retrieve_value { Some(self.value.clone()) } else { None },
+            if options.retrieve_type { Some(self.type_.clone()) } else { None },
+            if options.retrieve_tags { Some(self.tags.clone()) } else { None },
+        ).with_expiry(self.expires_at)
+    }
+
+    fn plain_tag(&self, name: &[u8]) -> Option<&String> {
+        self.tags.iter().find_map(|tag| match *tag {
+            Tag::PlainText(ref tag_name, ref tag_value) if tag_name.as_slice() == name => Some(tag_value),
+            _ => None
+        })
+    }
+
+    fn encrypted_tag(&self, name: &[u8]) -> Option<&Vec<u8>> {
+        self.tags.iter().find_map(|tag| match *tag {
+            Tag::Encrypted(ref tag_name, ref tag_value) if tag_name.as_slice() == name => Some(tag_value),
+            _ => None
+        })
+    }
+
+    fn set_tag(&mut self, tag: &Tag) {
+        let same_name = |t: &Tag| match (t, tag) {
+            (&Tag::Encrypted(ref a, _), &Tag::Encrypted(ref b, _)) => a == b,
+            (&Tag::PlainText(ref a, _), &Tag::PlainText(ref b, _)) => a == b,
+            _ => false
+        };
+
+        self.tags.retain(|t| !same_name(t));
+        self.tags.push(tag.clone());
+    }
+}
+
+// Items are keyed by sequential id, so iteration order matches insertion order
+// the same way as SQLite rowid does for the default storage.
+#[derive(Clone, Debug, Default)]
+struct InmemWallet {
+    metadata: Vec<u8>,
+    items: BTreeMap<u64, InmemItem>,
+    ids: HashMap<(Vec<u8>, Vec<u8>), u64>,
+    revisions: HashMap<(Vec<u8>, Vec<u8>), (u64, bool /* deleted */)>,
+    quarantine: Vec<InmemItem>,
+    next_id: u64,
+    revision: u64,
+}
+
+impl InmemWallet {
+    fn _item_id(&self, type_: &[u8], id: &[u8]) -> IndyResult<u64> {
+        self.ids.get(&(type_.to_vec(), id.to_vec()))
+            .cloned()
+            .ok_or_else(|| err_msg(IndyErrorKind::WalletItemNotFound, "Item not found"))
+    }
+
+    fn _bump_revision(&mut self, type_: &[u8], id: &[u8], deleted: bool) {
+        self.revision += 1;
+        self.revisions.insert((type_.to_vec(), id.to_vec()), (self.revision, deleted));
+    }
+
+    fn _add(&mut self, type_: &[u8], id: &[u8], value: &EncryptedValue, tags: &[Tag]) -> IndyResult<()> {
+        let key = (type_.to_vec(), id.to_vec());
+
+        if let Some(item_id) = self.ids.get(&key).cloned() {
+            // expired item that isn't purged yet must not prevent adding of the item with the same name
+            if !self.items[&item_id].is_expired(unix_time()) {
+                return Err(err_msg(IndyErrorKind::WalletItemAlreadyExists, "Item already exists"));
+            }
+
+            self._delete(type_, id)?;
+        }
+
+        let mut item = InmemItem {
+            type_: type_.to_vec(),
+            name: id.to_vec(),
+            value: value.clone(),
+            tags: Vec::new(),
+            expires_at: None,
+        };
+
+        for tag in tags {
+            item.set_tag(tag);
+        }
+
+        self.next_id += 1;
+        self.items.insert(self.next_id, item);
+        self.ids.insert(key, self.next_id);
+        self._bump_revision(type_, id, false);
+        Ok(())
+    }
+
+    fn _update(&mut self, type_: &[u8], id: &[u8], value: &EncryptedValue) -> IndyResult<()> {
+        let item_id = self._item_id(type_, id)
+            .map_err(|_| err_msg(IndyErrorKind::WalletItemNotFound, "Item to update not found"))?;
+
+        self.items.get_mut(&item_id).unwrap().value = value.clone();
+        self._bump_revision(type_, id, false);
+        Ok(())
+    }
+
+    fn _add_tags(&mut self, type_: &[u8], id: &[u8], tags: &[Tag]) -> IndyResult<()> {
+        let item_id = self._item_id(type_, id)?;
+        let item = self.items.get_mut(&item_id).unwrap();
+
+        for tag in tags {
+            item.set_tag(tag);
+        }
+
+        self._bump_revision(type_, id, false);
+        Ok(())
+    }
+
+    fn _update_tags(&mut self, type_: &[u8], id: &[u8], tags: &[Tag]) -> IndyResult<()> {
+        let item_id = self._item_id(type_, id)?;
+        let item = self.items.get_mut(&item_id).unwrap();
+
+        item.tags.clear();
+
+        for tag in tags {
+            item.set_tag(tag);
+        }
+
+        self._bump_revision(type_, id, false);
+        Ok(())
+    }
+
+    fn _delete_tags(&mut self, type_: &[u8], id: &[u8], tag_names: &[TagName]) -> IndyResult<()> {
+        let item_id = self._item_id(type_, id)?;
+        let item = self.items.get_mut(&item_id).unwrap();
+
+        for tag_name in tag_names {
+            item.tags.retain(|tag| match (tag, tag_name) {
+                (&Tag::Encrypted(ref name, _), &TagName::OfEncrypted(ref deleted)) => name != deleted,
+                (&Tag::PlainText(ref name, _), &TagName::OfPlain(ref deleted)) => name != deleted,
+                _ => true
+            });
+        }
+
+        self._bump_revision(type_, id, false);
+        Ok(())
+    }
+
+    fn _set_expiry(&mut self, type_: &[u8], id: &[u8], expires_at: Option<u64>) -> IndyResult<()> {
+        let item_id = self._item_id(type_, id)?;
+
+        self.items.get_mut(&item_id).unwrap().expires_at = expires_at;
+        self._bump_revision(type_, id, false);
+        Ok(())
+    }
+
+    fn _delete(&mut self, type_: &[u8], id: &[u8]) -> IndyResult<()> {
+        let item_id = self.ids.remove(&(type_.to_vec(), id.to_vec()))
+            .ok_or_else(|| err_msg(IndyErrorKind::WalletItemNotFound, "Item to delete not found"))?;
+
+        self.items.remove(&item_id);
+        self._bump_revision(type_, id, true);
+        Ok(())
+    }
+
+    fn _apply(&mut self, operation: &StorageOperation) -> IndyResult<()> {
+        match *operation {
+            StorageOperation::Add(ref type_, ref id, ref value, ref tags) => self._add(type_, id, value, tags),
+            StorageOperation::Update(ref type_, ref id, ref value) => self._update(type_, id, value),
+            StorageOperation::AddTags(ref type_, ref id, ref tags) => self._add_tags(type_, id, tags),
+            StorageOperation::UpdateTags(ref type_, ref id, ref tags) => self._update_tags(type_, id, tags),
+            StorageOperation::DeleteTags(ref type_, ref id, ref tag_names) => self._delete_tags(type_, id, tag_names),
+            StorageOperation::Delete(ref type_, ref id) => self._delete(type_, id),
+            StorageOperation::SetExpiry(ref type_, ref id, expires_at) => self._set_expiry(type_, id, expires_at),
+        }
+    }
+}
+
+struct InmemStorageIterator {
+    records: std::vec::IntoIter<StorageRecord>,
+    total_count: Option<usize>,
+}
+
+impl InmemStorageIterator {
+    fn new(records: Vec<StorageRecord>, total_count: Option<usize>) -> InmemStorageIterator {
+        InmemStorageIterator {
+            records: records.into_iter(),
+            total_count,
+        }
+    }
+}
+
+impl StorageIterator for InmemStorageIterator {
+    fn next(&mut self) -> IndyResult<Option<StorageRecord>> {
+        Ok(self.records.next())
+    }
+
+    fn get_total_count(&self) -> IndyResult<Option<usize>> {
+        Ok(self.total_count)
+    }
+}
+
+struct InmemStorage {
+    wallet: Rc<RefCell<InmemWallet>>,
+}
+
+///
+/// Storage type that keeps wallets in process memory only.
+/// Data of a wallet lives until the wallet is deleted or the process exits,
+/// so it fits tests and ephemeral agents that don't need persistence.
+///
+pub struct InmemStorageType {
+    wallets: RefCell<HashMap<String, Rc<RefCell<InmemWallet>>>>,
+}
+
+impl InmemStorageType {
+    pub fn new() -> InmemStorageType {
+        InmemStorageType {
+            wallets: RefCell::new(HashMap::new()),
+        }
+    }
+}
+
+impl WalletStorage for InmemStorage {
+    fn get(&self, type_: &[u8], id: &[u8], options: &str) -> IndyResult<StorageRecord> {
+        let options: RecordOptions = if options == "{}" {
+            RecordOptions::default()
+        } else {
+            serde_json::from_str(options)
+                .to_indy(IndyErrorKind::InvalidStructure, "RecordOptions is malformed json")?
+        };
+
+        let wallet = self.wallet.borrow();
+        let item = &wallet.items[&wallet._item_id(type_, id)?];
+
+        if item.is_expired(unix_time()) {
+            return Err(err_msg(IndyErrorKind::WalletItemNotFound, "Item not found"));
+        }
+
+        Ok(item.to_record(&options))
+    }
+
+    fn add(&self, type_: &[u8], id: &[u8], value: &EncryptedValue, tags: &[Tag]) -> IndyResult<()> {
+        self.wallet.borrow_mut()._add(type_, id, value, tags)
+    }
+
+    fn update(&self, type_: &[u8], id: &[u8], value: &EncryptedValue) -> IndyResult<()> {
+        self.wallet.borrow_mut()._update(type_, id, value)
+    }
+
+    fn add_tags(&self, type_: &[u8], id: &[u8], tags: &[Tag]) -> IndyResult<()> {
+        self.wallet.borrow_mut()._add_tags(type_, id, tags)
+    }
+
+    fn update_tags(&self, type_: &[u8], id: &[u8], tags: &[Tag]) -> IndyResult<()> {
+        self.wallet.borrow_mut()._update_tags(type_, id, tags)
+    }
+
+    fn delete_tags(&self, type_: &[u8], id: &[u8], tag_names: &[TagName]) -> IndyResult<()> {
+        self.wallet.borrow_mut()._delete_tags(type_, id, tag_names)
+    }
+
+    fn delete(&self, type_: &[u8], id: &[u8]) -> IndyResult<()> {
+        self.wallet.borrow_mut()._delete(type_, id)
+    }
+
+    ///
+    /// Applies operations to a copy of the wallet and replaces the wallet with the copy
+    /// only if all of them succeeded.
+    ///
+    fn batch(&self, operations: &[StorageOperation]) -> IndyResult<()> {
+        let mut wallet = self.wallet.borrow().clone();
+
+        for operation in operations {
+            wallet._apply(operation)?;
+        }
+
+        *self.wallet.borrow_mut() = wallet;
+        Ok(())
+    }
+
+    fn get_storage_metadata(&self) -> IndyResult<Vec<u8>> {
+        Ok(self.wallet.borrow().metadata.clone())
+    }
+
+    fn set_storage_metadata(&self, metadata: &[u8]) -> IndyResult<()> {
+        self.wallet.borrow_mut().metadata = metadata.to_vec();
+        Ok(())
+    }
+
+    fn get_all(&self) -> IndyResult<Box<dyn StorageIterator>> {
+        let options = RecordOptions {
+            retrieve_type: true,
+            retrieve_value: true,
+            retrieve_tags: true,
+        };
+
+        let now = unix_time();
+
+        let records = self.wallet.borrow().items.values()
+            .filter(|item| !item.is_expired(now))
+            .map(|item| item.to_record(&options))
+            .collect();
+
+        Ok(Box::new(InmemStorageIterator::new(records, None)))
+    }
+
+    fn get_revision(&self) -> IndyResult<u64> {
+        Ok(self.wallet.borrow().revision)
+    }
+
+    fn get_changed(&self, since_revision: u64) -> IndyResult<Box<dyn StorageIterator>> {
+        let options = RecordOptions {
+            retrieve_type: true,
+            retrieve_value: true,
+            retrieve_tags: true,
+        };
+
+        let wallet = self.wallet.borrow();
+        let now = unix_time();
+
+        let records = wallet.items.values()
+            .filter(|item| !item.is_expired(now))
+            .filter(|item| {
+                wallet.revisions.get(&(item.type_.clone(), item.name.clone()))
+                    .map(|&(revision, _)| revision > since_revision)
+                    .unwrap_or(false)
+            })
+            .map(|item| item.to_record(&options))
+            .collect();
+
+        Ok(Box::new(InmemStorageIterator::new(records, None)))
+    }
+
+    fn get_deleted(&self, since_revision: u64) -> IndyResult<Vec<(Vec<u8>, Vec<u8>)>> {
+        Ok(self.wallet.borrow().revisions.iter()
+            .filter(|&(_, &(revision, deleted))| deleted && revision > since_revision)
+            .map(|(key, _)| key.clone())
+            .collect())
+    }
+
+    fn rekey_deleted(&self, marks: &[((Vec<u8>, Vec<u8>), (Vec<u8>, Vec<u8>))]) -> IndyResult<usize> {
+        let mut wallet = self.wallet.borrow_mut();
+        let mut moved = 0;
+
+        for &(ref key, ref new_key) in marks {
+            let mark = match wallet.revisions.get(key) {
+                Some(&mark @ (_, true)) => mark,
+                _ => continue
+            };
+
+            wallet.revisions.remove(key);
+
+            if !wallet.revisions.contains_key(new_key) {
+                wallet.revisions.insert(new_key.clone(), mark);
+                moved += 1;
+            }
+        }
+
+        Ok(moved)
+    }
+
+    fn check_orphan_tags(&self, _repair: bool) -> IndyResult<usize> {
+        // tags are owned by items here, so they can't outlive them.
+        Ok(0)
+    }
+
+    fn quarantine(&self, type_: &[u8], id: &[u8]) -> IndyResult<()> {
+        let mut wallet = self.wallet.borrow_mut();
+
+        let item_id = wallet._item_id(type_, id)?;
+        let item = wallet.items.remove(&item_id).unwrap();
+
+        wallet.ids.remove(&(type_.to_vec(), id.to_vec()));
+        wallet.quarantine.push(item);
+        wallet._bump_revision(type_, id, true);
+        Ok(())
+    }
+
+    fn purge_expired(&self) -> IndyResult<usize> {
+        let mut wallet = self.wallet.borrow_mut();
+        let now = unix_time();
+
+        let expired: Vec<(Vec<u8>, Vec<u8>)> = wallet.items.values()
+            .filter(|item| item.is_expired(now))
+            .map(|item| (item.type_.clone(), item.name.clone()))
+            .collect();
+
+        for &(ref type_, ref id) in expired.iter() {
+            wallet._delete(type_, id)?;
+        }
+
+        Ok(expired.len())
+    }
+
+    fn search(&self, type_: &[u8], query: &Operator, options: Option<&str>) -> IndyResult<Box<dyn StorageIterator>> {
+        let search_options: SearchOptions = match options {
+            None => SearchOptions::default(),
+            Some(option_str) => serde_json::from_str(option_str)
+                .to_indy(IndyErrorKind::InvalidStructure, "Search options is malformed json")?
+        };
+
+        let sort_tag_name = search_options.sort_by.as_ref().map(|sort_by| sort_by.encrypted_tag_name()).transpose()?;
+        let descending = search_options.sort_by.as_ref().map(|sort_by| sort_by.is_descending()).unwrap_or(false);
+
+        // evaluation of empty item checks the query the same way as translation to SQL does,
+        // so invalid query fails even if there are no items of the type.
+        _matches(query, None)?;
+
+        let wallet = self.wallet.borrow();
+        let now = unix_time();
+
+        let mut found: Vec<(&u64, &InmemItem)> = Vec::new();
+
+        for (item_id, item) in wallet.items.iter() {
+            if item.type_.as_slice() == type_ && !item.is_expired(now) && _matches(query, Some(item))? {
+                found.push((item_id, item));
+            }
+        }
+
+        let total_count = if search_options.retrieve_total_count { Some(found.len()) } else { None };
+
+        if !search_options.retrieve_records {
+            return Ok(Box::new(InmemStorageIterator::new(Vec::new(), total_count)));
+        }
+
+        // records without the tag go last in both directions, item id keeps pages stable
+        if let Some(ref sort_tag_name) = sort_tag_name {
+            found.sort_by(|&(a_id, a), &(b_id, b)| {
+                let order = match (a.plain_tag(sort_tag_name), b.plain_tag(sort_tag_name)) {
+                    (Some(a), Some(b)) if descending => b.cmp(a),
+                    (Some(a), Some(b)) => a.cmp(b),
+                    (Some(_), None) => Ordering::Less,
+                    (None, Some(_)) => Ordering::Greater,
+                    (None, None) => Ordering::Equal,
+                };
+
+                order.then(a_id.cmp(b_id))
+            });
+        }
+
+        let options = RecordOptions {
+            retrieve_type: search_options.retrieve_type,
+            retrieve_value: search_options.retrieve_value,
+            retrieve_tags: search_options.retrieve_tags,
+        };
+
+        let records = found.into_iter()
+            .skip(search_options.offset.unwrap_or(0))
+            .take(search_options.limit.unwrap_or(usize::max_value()))
+            .map(|(_, item)| item.to_record(&options))
+            .collect();
+
+        Ok(Box::new(InmemStorageIterator::new(records, total_count)))
+    }
+
+    fn count_by_tag(&self, type_: &[u8], query: &Operator, tag_name: &TagName) -> IndyResult<Vec<(Vec<u8>, usize)>> {
+        _matches(query, None)?;
+
+        let wallet = self.wallet.borrow();
+        let now = unix_time();
+
+        let mut counts: BTreeMap<Vec<u8>, usize> = BTreeMap::new();
+
+        for item in wallet.items.values() {
+            if item.type_.as_slice() != type_ || item.is_expired(now) || !_matches(query, Some(item))? {
+                continue;
+            }
+
+            let value = match *tag_name {
+                TagName::OfPlain(ref name) => item.plain_tag(name).map(|value| value.as_bytes().to_vec()),
+                TagName::OfEncrypted(ref name) => item.encrypted_tag(name).cloned(),
+            };
+
+            if let Some(value) = value {
+                *counts.entry(value).or_insert(0) += 1;
+            }
+        }
+
+        Ok(counts.into_iter().collect())
+    }
+
+    fn get_distinct_tag_values(&self, type_: &[u8], query: &Operator, tag_name: &TagName) -> IndyResult<Vec<Vec<u8>>> {
+        self.count_by_tag(type_, query, tag_name)
+            .map(|counts| counts.into_iter().map(|(value, _)| value).collect())
+    }
+
+    fn close(&mut self) -> IndyResult<()> {
+        Ok(())
+    }
+}
+
+// Evaluates WQL operator for the item with the same semantics as SQL translation of the default storage.
+// Item is None for validation of the query only.
+fn _matches(op: &Operator, item: Option<&InmemItem>) -> IndyResult<bool> {
+    match *op {
+        Operator::And(ref suboperators) => {
+            let mut res = true;
+            for suboperator in suboperators {
+                res &= _matches(suboperator, item)?;
+            }
+            Ok(res)
+        }
+        Operator::Or(ref suboperators) => {
+            let mut res = suboperators.is_empty();
+            for suboperator in suboperators {
+                res |= _matches(suboperator, item)?;
+            }
+            Ok(res)
+        }
+        Operator::Not(ref suboperator) => _matches(suboperator, item).map(|res| !res),
+        Operator::Eq(ref name, ref value) => _compare(name, value, item, "equality", true, |a, b| a == b),
+        Operator::Neq(ref name, ref value) => _compare(name, value, item, "inequality", true, |a, b| a != b),
+        Operator::Gt(ref name, ref value) => _compare(name, value, item, "$gt", false, |a, b| a > b),
+        Operator::Gte(ref name, ref value) => _compare(name, value, item, "$gte", false, |a, b| a >= b),
+        Operator::Lt(ref name, ref value) => _compare(name, value, item, "$lt", false, |a, b| a < b),
+        Operator::Lte(ref name, ref value) => _compare(name, value, item, "$lte", false, |a, b| a <= b),
+        Operator::Like(ref name, ref value) => match (name, value) {
+            (&QueryTagName::PlainTagName(ref name), &TargetValue::Unencrypted(ref pattern)) =>
+                Ok(item.and_then(|item| item.plain_tag(name)).map(|value| _like(value, pattern)).unwrap_or(false)),
+            _ => Err(err_msg(IndyErrorKind::WalletQueryError, "Invalid combination of tag name and value for $like operator"))
+        },
+        Operator::Exist(ref name) => Ok(item.map(|item| match *name {
+            QueryTagName::PlainTagName(ref name) => item.plain_tag(name).is_some(),
+            QueryTagName::EncryptedTagName(ref name) => item.encrypted_tag(name).is_some(),
+        }).unwrap_or(false)),
+        Operator::NumGt(ref name, ref value) => _compare_numbers(name, value, item, "$gt", |a, b| a > b),
+        Operator::NumGte(ref name, ref value) => _compare_numbers(name, value, item, "$gte", |a, b| a >= b),
+        Operator::NumLt(ref name, ref value) => _compare_numbers(name, value, item, "$lt", |a, b| a < b),
+        Operator::NumLte(ref name, ref value) => _compare_numbers(name, value, item, "$lte", |a, b| a <= b),
+        Operator::Prefix(ref name, ref value) => _compare(name, value, item, "$prefix", false, |a, b| a.starts_with(b)),
+        Operator::In(ref name, ref values) => {
+            let mut res = false;
+            for value in values {
+                match (name, value) {
+                    (&QueryTagName::PlainTagName(_), &TargetValue::Encrypted(_)) =>
+                        return Err(err_msg(IndyErrorKind::WalletQueryError, "Encrypted tag value in $in for nonencrypted tag name")),
+                    (&QueryTagName::EncryptedTagName(_), &TargetValue::Unencrypted(_)) =>
+                        return Err(err_msg(IndyErrorKind::WalletQueryError, "Unencrypted tag value in $in for encrypted tag name")),
+                    _ => res |= _compare(name, value, item, "$in", true, |a, b| a == b)?
+                }
+            }
+            Ok(res)
+        }
+    }
+}
+
+// Compares tag value of the item with the target one. Items without the tag never match.
+fn _compare<F>(name: &QueryTagName, value: &TargetValue, item: Option<&InmemItem>, op_name: &str, encrypted_allowed: bool, cmp: F) -> IndyResult<bool>
+    where F: Fn(&[u8], &[u8]) -> bool {
+    match (name, value) {
+        (&QueryTagName::PlainTagName(ref name), &TargetValue::Unencrypted(ref target)) =>
+            Ok(item.and_then(|item| item.plain_tag(name)).map(|value| cmp(value.as_bytes(), target.as_bytes())).unwrap_or(false)),
+        (&QueryTagName::EncryptedTagName(ref name), &TargetValue::Encrypted(ref target)) if encrypted_allowed =>
+            Ok(item.and_then(|item| item.encrypted_tag(name)).map(|value| cmp(value, target)).unwrap_or(false)),
+        _ => Err(err_msg(IndyErrorKind::WalletQueryError, format!("Invalid combination of tag name and value for {} operator", op_name)))
+    }
+}
+
+// Numeric comparison of plain tag value. Items without the tag or with non-numeric value never match.
+fn _compare_numbers<F>(name: &QueryTagName, value: &TargetValue, item: Option<&InmemItem>, op_name: &str, cmp: F) -> IndyResult<bool>
+    where F: Fn(f64, f64) -> bool {
+    match (name, value) {
+        (&QueryTagName::PlainTagName(ref name), &TargetValue::Unencrypted(ref target)) => {
+            let target = _parse_number(target)
+                .ok_or_else(|| err_msg(IndyErrorKind::WalletQueryError, format!("Invalid number for numeric {} operator", op_name)))?;
+            Ok(item.and_then(|item| item.plain_tag(name)).and_then(|value| _parse_number(value)).map(|value| cmp(value, target)).unwrap_or(false))
+        }
+        _ => Err(err_msg(IndyErrorKind::WalletQueryError, format!("Invalid combination of tag name and value for numeric {} operator", op_name)))
+    }
+}
+
+// Only plain decimal notation is treated as number, same as in SQL storages
+fn _parse_number(value: &str) -> Option<f64> {
+    if !value.chars().any(|c| c.is_ascii_digit()) || !value.chars().all(|c| c.is_ascii_digit() || "+-.eE".contains(c)) {
+        return None;
+    }
+    value.parse::<f64>().ok()
+}
+
+// SQL LIKE: `%` matches any sequence, `_` matches any single character, ASCII letters are case insensitive.
+fn _like(value: &str, pattern: &str) -> bool {
+    let value: Vec<char> = value.chars().collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+
+    fn _like_from(value: &[char], pattern: &[char]) -> bool {
+        match pattern.split_first() {
+            None => value.is_empty(),
+            Some((&'%', rest)) => (0..=value.len()).any(|i| _like_from(&value[i..], rest)),
+            Some((&'_', rest)) => !value.is_empty() && _like_from(&value[1..], rest),
+            Some((c, rest)) => !value.is_empty() && value[0].eq_ignore_ascii_case(c) && _like_from(&value[1..], rest),
+        }
+    }
+
+    _like_from(&value, &pattern)
+}
+
+impl WalletStorageType for InmemStorageType {
+    fn create_storage(&self, id: &str, _config: Option<&str>, _credentials: Option<&str>, metadata: &[u8]) -> IndyResult<()> {
+        let mut wallets = self.wallets.borrow_mut();
+
+        if wallets.contains_key(id) {
+            return Err(err_msg(IndyErrorKind::WalletAlreadyExists, format!("In-memory wallet already exists: {}", id)));
+        }
+
+        let wallet = InmemWallet {
+            metadata: metadata.to_vec(),
+            ..InmemWallet::default()
+        };
+
+        wallets.insert(id.to_string(), Rc::new(RefCell::new(wallet)));
+        Ok(())
+    }
+
+    fn open_storage(&self, id: &str, _config: Option<&str>, _credentials: Option<&str>) -> IndyResult<Box<dyn WalletStorage>> {
+        let wallet = self.wallets.borrow().get(id).cloned()
+            .ok_or_else(|| err_msg(IndyErrorKind::WalletNotFound, format!("In-memory wallet not found: {}", id)))?;
+
+        Ok(Box::new(InmemStorage { wallet }))
+    }
+
+    fn delete_storage(&self, id: &str, _config: Option<&str>, _credentials: Option<&str>) -> IndyResult<()> {
+        self.wallets.borrow_mut().remove(id)
+            .map(|_| ())
+            .ok_or_else(|| err_msg(IndyErrorKind::WalletNotFound, format!("In-memory wallet not found: {}", id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indy_utils::crypto::base64;
+
+    use super::*;
+
+    #[test]
+    fn inmem_storage_type_create_works_for_twice() {
+        let storage_type = InmemStorageType::new();
+        storage_type.create_storage("inmem_storage_type_create_works_for_twice", None, None, &_metadata()).unwrap();
+
+        let res = storage_type.create_storage("inmem_storage_type_create_works_for_twice", None, None, &_metadata());
+        assert_kind!(IndyErrorKind::WalletAlreadyExists, res);
+    }
+
+    #[test]
+    fn inmem_storage_type_open_works_for_not_created() {
+        let storage_type = InmemStorageType::new();
+
+        let res = storage_type.open_storage("unknown", None, None);
+        assert_kind!(IndyErrorKind::WalletNotFound, res);
+    }
+
+    #[test]
+    fn inmem_storage_type_delete_works() {
+        let storage_type = InmemStorageType::new();
+        storage_type.create_storage("inmem_storage_type_delete_works", None, None, &_metadata()).unwrap();
+
+        storage_type.delete_storage("inmem_storage_type_delete_works", None, None).unwrap();
+
+        let res = storage_type.open_storage("inmem_storage_type_delete_works", None, None);
+        assert_kind!(IndyErrorKind::WalletNotFound, res);
+
+        let res = storage_type.delete_storage("inmem_storage_type_delete_works", None, None);
+        assert_kind!(IndyErrorKind::WalletNotFound, res);
+    }
+
+    #[test]
+    fn inmem_storage_set_get_works_for_reopen() {
+        let storage_type = InmemStorageType::new();
+        storage_type.create_storage("inmem_storage_set_get_works_for_reopen", None, None, &_metadata()).unwrap();
+
+        {
+            let storage = storage_type.open_storage("inmem_storage_set_get_works_for_reopen", None, None).unwrap();
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+
+            let res = storage.add(&_type1(), &_id1(), &_value1(), &_tags());
+            assert_kind!(IndyErrorKind::WalletItemAlreadyExists, res);
+        }
+
+        let storage = storage_type.open_storage("inmem_storage_set_get_works_for_reopen", None, None).unwrap();
+        assert_eq!(storage.get_storage_metadata().unwrap(), _metadata());
+
+        let record = storage.get(&_type1(), &_id1(), r##"{"retrieveType": false, "retrieveValue": true, "retrieveTags": true}"##).unwrap();
+        assert_eq!(record.value.unwrap(), _value1());
+        assert_eq!(_sort(record.tags.unwrap()), _sort(_tags()));
+    }
+
+    #[test]
+    fn inmem_storage_update_and_tags_work() {
+        let storage = _storage("inmem_storage_update_and_tags_work");
+        storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+
+        storage.update(&_type1(), &_id1(), &_value2()).unwrap();
+        storage.add_tags(&_type1(), &_id1(), &[Tag::PlainText(vec![1, 5, 8, 1], "New value".to_string())]).unwrap();
+        storage.delete_tags(&_type1(), &_id1(), &[TagName::OfEncrypted(vec![1, 5, 8])]).unwrap();
+
+        let record = storage.get(&_type1(), &_id1(), r##"{"retrieveType": false, "retrieveValue": true, "retrieveTags": true}"##).unwrap();
+        assert_eq!(record.value.unwrap(), _value2());
+        assert_eq!(record.tags.unwrap(), vec![Tag::PlainText(vec![1, 5, 8, 1], "New value".to_string())]);
+
+        storage.update_tags(&_type1(), &_id1(), &_new_tags()).unwrap();
+
+        let record = storage.get(&_type1(), &_id1(), r##"{"retrieveType": false, "retrieveValue": false, "retrieveTags": true}"##).unwrap();
+        assert_eq!(_sort(record.tags.unwrap()), _sort(_new_tags()));
+
+        let res = storage.update(&_type2(), &_id1(), &_value2());
+        assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+        let res = storage.add_tags(&_type1(), &_id2(), &_tags());
+        assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+        storage.delete(&_type1(), &_id1()).unwrap();
+
+        let res = storage.delete(&_type1(), &_id1());
+        assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+    }
+
+    #[test]
+    fn inmem_storage_batch_works_for_rollback() {
+        let storage = _storage("inmem_storage_batch_works_for_rollback");
+        storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+
+        let operations = vec![
+            StorageOperation::Update(_type1(), _id1(), _value2()),
+            StorageOperation::Add(_type1(), _id2(), _value2(), _new_tags()),
+            StorageOperation::Delete(_type2(), _id1()),
+        ];
+        let res = storage.batch(&operations);
+        assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+        let record = storage.get(&_type1(), &_id1(), r##"{"retrieveType": false, "retrieveValue": true, "retrieveTags": true}"##).unwrap();
+        assert_eq!(record.value.unwrap(), _value1());
+
+        let res = storage.get(&_type1(), &_id2(), r##"{"retrieveType": false, "retrieveValue": true, "retrieveTags": true}"##);
+        assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+        storage.batch(&operations[..2]).unwrap();
+
+        let record = storage.get(&_type1(), &_id2(), r##"{"retrieveType": false, "retrieveValue": true, "retrieveTags": true}"##).unwrap();
+        assert_eq!(record.value.unwrap(), _value2());
+    }
+
+    #[test]
+    fn inmem_storage_search_works() {
+        let storage = _storage("inmem_storage_search_works");
+
+        storage.add(&_type1(), &_id(1), &_value1(), &[Tag::PlainText(vec![1], "1".to_string()), Tag::Encrypted(vec![2], vec![1])]).unwrap();
+        storage.add(&_type1(), &_id(2), &_value1(), &[Tag::PlainText(vec![1], "2".to_string()), Tag::Encrypted(vec![2], vec![2])]).unwrap();
+        storage.add(&_type1(), &_id(3), &_value1(), &[Tag::PlainText(vec![1], "Abc".to_string())]).unwrap();
+        storage.add(&_type2(), &_id(4), &_value1(), &[Tag::PlainText(vec![1], "1".to_string())]).unwrap();
+
+        let search = |query: Operator| -> Vec<Vec<u8>> {
+            let mut iterator = storage.search(&_type1(), &query, None).unwrap();
+            let mut ids = Vec::new();
+            while let Some(record) = iterator.next().unwrap() {
+                ids.push(record.id);
+            }
+            ids
+        };
+
+        assert_eq!(search(Operator::And(vec![])), vec![_id(1), _id(2), _id(3)]);
+        assert_eq!(search(Operator::Eq(QueryTagName::PlainTagName(vec![1]), TargetValue::Unencrypted("1".to_string()))), vec![_id(1)]);
+        assert_eq!(search(Operator::Eq(QueryTagName::EncryptedTagName(vec![2]), TargetValue::Encrypted(vec![2]))), vec![_id(2)]);
+        assert_eq!(search(Operator::Neq(QueryTagName::EncryptedTagName(vec![2]), TargetValue::Encrypted(vec![2]))), vec![_id(1)]);
+        assert_eq!(search(Operator::Gt(QueryTagName::PlainTagName(vec![1]), TargetValue::Unencrypted("1".to_string()))), vec![_id(2), _id(3)]);
+        assert_eq!(search(Operator::Lte(QueryTagName::PlainTagName(vec![1]), TargetValue::Unencrypted("2".to_string()))), vec![_id(1), _id(2)]);
+        assert_eq!(search(Operator::Like(QueryTagName::PlainTagName(vec![1]), TargetValue::Unencrypted("a_C%".to_string()))), vec![_id(3)]);
+        assert_eq!(search(Operator::Prefix(QueryTagName::PlainTagName(vec![1]), TargetValue::Unencrypted("Ab".to_string()))), vec![_id(3)]);
+        assert_eq!(search(Operator::In(QueryTagName::EncryptedTagName(vec![2]), vec![TargetValue::Encrypted(vec![1]), TargetValue::Encrypted(vec![2])])), vec![_id(1), _id(2)]);
+        assert_eq!(search(Operator::Not(Box::new(Operator::Eq(QueryTagName::EncryptedTagName(vec![2]), TargetValue::Encrypted(vec![1]))))), vec![_id(2), _id(3)]);
+        assert_eq!(search(Operator::Or(vec![
+            Operator::Eq(QueryTagName::PlainTagName(vec![1]), TargetValue::Unencrypted("Abc".to_string())),
+            Operator::Eq(QueryTagName::EncryptedTagName(vec![2]), TargetValue::Encrypted(vec![1])),
+        ])), vec![_id(1), _id(3)]);
+
+        let res = storage.search(&_type1(), &Operator::Gt(QueryTagName::EncryptedTagName(vec![2]), TargetValue::Encrypted(vec![1])), None);
+        assert_kind!(IndyErrorKind::WalletQueryError, res);
+
+        let res = storage.search(&_type2(), &Operator::Eq(QueryTagName::PlainTagName(vec![1]), TargetValue::Encrypted(vec![1])), None);
+        assert_kind!(IndyErrorKind::WalletQueryError, res);
+    }
+
+    #[test]
+    fn inmem_storage_search_works_for_sort_and_page() {
+        let storage = _storage("inmem_storage_search_works_for_sort_and_page");
+
+        for (i, value) in ["b", "c", "a"].iter().enumerate() {
+            storage.add(&_type1(), &_id(i as u8), &_value1(), &[Tag::PlainText(vec![1, 2, 3], value.to_string())]).unwrap();
+        }
+        storage.add(&_type1(), &_id(3), &_value1(), &[]).unwrap();
+
+        let query = Operator::And(vec![]);
+
+        let options = json!({
+            "retrieveTotalCount": true,
+            "sortBy": {"tagName": format!("~{}", base64::encode(&[1, 2, 3])), "order": "desc"},
+        }).to_string();
+        let mut iterator = storage.search(&_type1(), &query, Some(&options)).unwrap();
+        assert_eq!(iterator.get_total_count().unwrap(), Some(4));
+        assert_eq!(iterator.next().unwrap().unwrap().id, _id(1));
+        assert_eq!(iterator.next().unwrap().unwrap().id, _id(0));
+        assert_eq!(iterator.next().unwrap().unwrap().id, _id(2));
+        assert_eq!(iterator.next().unwrap().unwrap().id, _id(3));
+        assert!(iterator.next().unwrap().is_none());
+
+        let options = json!({
+            "retrieveTotalCount": true,
+            "sortBy": {"tagName": format!("~{}", base64::encode(&[1, 2, 3]))},
+            "offset": 1,
+            "limit": 2,
+        }).to_string();
+        let mut iterator = storage.search(&_type1(), &query, Some(&options)).unwrap();
+        assert_eq!(iterator.get_total_count().unwrap(), Some(4));
+        assert_eq!(iterator.next().unwrap().unwrap().id, _id(0));
+        assert_eq!(iterator.next().unwrap().unwrap().id, _id(1));
+        assert!(iterator.next().unwrap().is_none());
+    }
+
+    #[test]
+    fn inmem_storage_revisions_work() {
+        let storage = _storage("inmem_storage_revisions_work");
+        assert_eq!(0, storage.get_revision().unwrap());
+
+        storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+        storage.add(&_type2(), &_id2(), &_value2(), &_tags()).unwrap();
+
+        let revision = storage.get_revision().unwrap();
+        assert!(storage.get_changed(revision).unwrap().next().unwrap().is_none());
+
+        storage.update(&_type1(), &_id1(), &_value2()).unwrap();
+        storage.delete(&_type2(), &_id2()).unwrap();
+
+        let mut storage_iterator = storage.get_changed(revision).unwrap();
+
+        let record = storage_iterator.next().unwrap().unwrap();
+        assert_eq!(record.id, _id1());
+        assert_eq!(record.value.unwrap(), _value2());
+        assert!(storage_iterator.next().unwrap().is_none());
+
+        assert_eq!(vec![(_type2(), _id2())], storage.get_deleted(revision).unwrap());
+    }
+
+    #[test]
+    fn inmem_storage_rekey_deleted_works() {
+        let storage = _storage("inmem_storage_rekey_deleted_works");
+        storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+        storage.add(&_type2(), &_id2(), &_value2(), &_tags()).unwrap();
+        storage.delete(&_type1(), &_id1()).unwrap();
+        storage.delete(&_type2(), &_id2()).unwrap();
+        storage.add(&_type1(), b"rekeyed1", &_value1(), &_tags()).unwrap();
+
+        let marks = vec![
+            ((_type1(), _id1()), (_type1(), b"rekeyed1".to_vec())),
+            ((_type2(), _id2()), (_type2(), b"rekeyed2".to_vec())),
+        ];
+        assert_eq!(1, storage.rekey_deleted(&marks).unwrap());
+
+        assert_eq!(vec![(_type2(), b"rekeyed2".to_vec())], storage.get_deleted(0).unwrap());
+    }
+
+    #[test]
+    fn inmem_storage_quarantine_works() {
+        let storage = _storage("inmem_storage_quarantine_works");
+        storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+        storage.add(&_type2(), &_id2(), &_value2(), &_tags()).unwrap();
+
+        storage.quarantine(&_type1(), &_id1()).unwrap();
+
+        let res = storage.get(&_type1(), &_id1(), "{}");
+        assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+        let mut storage_iterator = storage.get_all().unwrap();
+        assert_eq!(storage_iterator.next().unwrap().unwrap().id, _id2());
+        assert!(storage_iterator.next().unwrap().is_none());
+
+        let res = storage.quarantine(&_type1(), &_id1());
+        assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+    }
+
+    #[test]
+    fn inmem_storage_expiry_works() {
+        let storage = _storage("inmem_storage_expiry_works");
+        storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+        storage.add(&_type1(), &_id2(), &_value2(), &_tags()).unwrap();
+
+        storage.batch(&[
+            StorageOperation::SetExpiry(_type1(), _id1(), Some(1)),
+            StorageOperation::SetExpiry(_type1(), _id2(), Some(unix_time() + 3600)),
+        ]).unwrap();
+
+        let res = storage.get(&_type1(), &_id1(), "{}");
+        assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+        storage.get(&_type1(), &_id2(), "{}").unwrap();
+
+        let mut storage_iterator = storage.search(&_type1(), &Operator::And(vec![]), Some(r##"{"retrieveTotalCount": true}"##)).unwrap();
+        assert_eq!(storage_iterator.get_total_count().unwrap(), Some(1));
+        assert_eq!(storage_iterator.next().unwrap().unwrap().id, _id2());
+        assert!(storage_iterator.next().unwrap().is_none());
+
+        // expired item doesn't prevent adding of the item with the same name
+        storage.add(&_type1(), &_id1(), &_value2(), &[]).unwrap();
+        storage.batch(&[StorageOperation::SetExpiry(_type1(), _id1(), Some(1))]).unwrap();
+
+        assert_eq!(1, storage.purge_expired().unwrap());
+        assert_eq!(0, storage.purge_expired().unwrap());
+
+        let revision = storage.get_revision().unwrap();
+        assert_eq!(vec![(_type1(), _id1())], storage.get_deleted(revision - 1).unwrap());
+    }
+
+    #[test]
+    fn inmem_storage_count_by_tag_works() {
+        let storage = _storage("inmem_storage_count_by_tag_works");
+        storage.add(&_type1(), &_id(1), &_value1(), &[Tag::Encrypted(vec![1], vec![20]), Tag::PlainText(vec![2], "b".to_string())]).unwrap();
+        storage.add(&_type1(), &_id(2), &_value1(), &[Tag::Encrypted(vec![1], vec![10]), Tag::PlainText(vec![2], "a".to_string())]).unwrap();
+        storage.add(&_type1(), &_id(3), &_value1(), &[Tag::Encrypted(vec![1], vec![20])]).unwrap();
+        storage.add(&_type1(), &_id(4), &_value1(), &[Tag::PlainText(vec![2], "b".to_string())]).unwrap();
+        storage.add(&_type2(), &_id(5), &_value1(), &[Tag::Encrypted(vec![1], vec![30])]).unwrap();
+        storage.add(&_type1(), &_id(6), &_value1(), &[Tag::Encrypted(vec![1], vec![40])]).unwrap();
+        storage.batch(&[StorageOperation::SetExpiry(_type1(), _id(6), Some(1))]).unwrap();
+
+        let counts = storage.count_by_tag(&_type1(), &Operator::And(vec![]), &TagName::OfEncrypted(vec![1])).unwrap();
+        assert_eq!(counts, vec![(vec![10], 1), (vec![20], 2)]);
+
+        let counts = storage.count_by_tag(&_type1(), &Operator::And(vec![]), &TagName::OfPlain(vec![2])).unwrap();
+        assert_eq!(counts, vec![(b"a".to_vec(), 1), (b"b".to_vec(), 2)]);
+
+        let query = Operator::Eq(QueryTagName::PlainTagName(vec![2]), TargetValue::Unencrypted("b".to_string()));
+        let counts = storage.count_by_tag(&_type1(), &query, &TagName::OfEncrypted(vec![1])).unwrap();
+        assert_eq!(counts, vec![(vec![20], 1)]);
+
+        let values = storage.get_distinct_tag_values(&_type1(), &Operator::And(vec![]), &TagName::OfEncrypted(vec![1])).unwrap();
+        assert_eq!(values, vec![vec![10], vec![20]]);
+
+        let values = storage.get_distinct_tag_values(&_type1(), &Operator::And(vec![]), &TagName::OfEncrypted(vec![3])).unwrap();
+        assert!(values.is_empty());
+    }
+
+    #[test]
+    fn inmem_storage_search_works_for_exist_and_numeric() {
+        let storage = _storage("inmem_storage_search_works_for_exist_and_numeric");
+        storage.add(&_type1(), &_id(1), &_value1(), &[Tag::PlainText(vec![1], "9".to_string())]).unwrap();
+        storage.add(&_type1(), &_id(2), &_value1(), &[Tag::PlainText(vec![1], "10".to_string()), Tag::Encrypted(vec![2], vec![3])]).unwrap();
+        storage.add(&_type1(), &_id(3), &_value1(), &[Tag::PlainText(vec![1], "ten".to_string())]).unwrap();
+
+        let search_ids = |query: &Operator| {
+            let mut iterator = storage.search(&_type1(), query, None).unwrap();
+            let mut ids = Vec::new();
+            while let Some(record) = iterator.next().unwrap() {
+                ids.push(record.id);
+            }
+            ids.sort();
+            ids
+        };
+
+        // string comparison puts "10" before "9", numeric one doesn't
+        assert_eq!(search_ids(&Operator::Gt(QueryTagName::PlainTagName(vec![1]), TargetValue::Unencrypted("9".to_string()))), vec![_id(3)]);
+        assert_eq!(search_ids(&Operator::NumGt(QueryTagName::PlainTagName(vec![1]), TargetValue::Unencrypted("9".to_string()))), vec![_id(2)]);
+        assert_eq!(search_ids(&Operator::NumLte(QueryTagName::PlainTagName(vec![1]), TargetValue::Unencrypted("1e1".to_string()))), vec![_id(1), _id(2)]);
+
+        assert_eq!(search_ids(&Operator::Exist(QueryTagName::EncryptedTagName(vec![2]))), vec![_id(2)]);
+        assert_eq!(search_ids(&Operator::Not(Box::new(Operator::Exist(QueryTagName::EncryptedTagName(vec![2]))))), vec![_id(1), _id(3)]);
+        assert_eq!(search_ids(&Operator::Exist(QueryTagName::PlainTagName(vec![2]))), Vec::<Vec<u8>>::new());
+
+        let res = storage.search(&_type1(), &Operator::NumGt(QueryTagName::EncryptedTagName(vec![2]), TargetValue::Encrypted(vec![1])), None);
+        assert_kind!(IndyErrorKind::WalletQueryError, res);
+    }
+
+    fn _storage(name: &str) -> Box<dyn WalletStorage> {
+        let storage_type = InmemStorageType::new();
+        storage_type.create_storage(name, None, None, &_metadata()).unwrap();
+        storage_type.open_storage(name, None, None).unwrap()
+    }
+
+    fn _metadata() -> Vec<u8> {
+        vec![1, 2, 3, 4, 5, 6, 7, 8]
+    }
+
+    fn _type(i: u8) -> Vec<u8> {
+        vec![i, 1 + i, 2 + i]
+    }
+
+    fn _type1() -> Vec<u8> {
+        _type(1)
+    }
+
+    fn _type2() -> Vec<u8> {
+        _type(2)
+    }
+
+    fn _id(i: u8) -> Vec<u8> {
+        vec![3 + i, 4 + i, 5 + i]
+    }
+
+    fn _id1() -> Vec<u8> {
+        _id(1)
+    }
+
+    fn _id2() -> Vec<u8> {
+        _id(2)
+    }
+
+    fn _value(i: u8) -> EncryptedValue {
+        EncryptedValue { data: vec![6 + i, 7 + i, 8 + i], key: vec![9 + i, 10 + i, 11 + i] }
+    }
+
+    fn _value1() -> EncryptedValue {
+        _value(1)
+    }
+
+    fn _value2() -> EncryptedValue {
+        _value(2)
+    }
+
+    fn _tags() -> Vec<Tag> {
+        vec![
+            Tag::Encrypted(vec![1, 5, 8], vec![3, 5, 6]),
+            Tag::PlainText(vec![1, 5, 8, 1], "Plain value".to_string())
+        ]
+    }
+
+    fn _new_tags() -> Vec<Tag> {
+        vec![
+            Tag::Encrypted(vec![1, 1, 1], vec![2, 2, 2]),
+            Tag::PlainText(vec![1, 1, 1], String::from("tag_value_3"))
+        ]
+    }
+
+    fn _sort(mut v: Vec<Tag>) -> Vec<Tag> {
+        v.sort();
+        v
+    }
+}
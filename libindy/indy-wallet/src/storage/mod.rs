@@ -1,5 +1,11 @@
 pub mod default;
+pub mod inmem;
+#[cfg(feature = "postgres_storage")]
+pub mod postgres;
 pub mod plugged;
+pub mod read_only;
+
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use indy_api_types::errors::prelude::*;
 use crate::language;
@@ -11,7 +17,7 @@ pub enum Tag {
     PlainText(Vec<u8>, String)
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum TagName {
     OfEncrypted(Vec<u8>),
     OfPlain(Vec<u8>),
@@ -23,6 +29,7 @@ pub struct StorageRecord {
     pub value: Option<EncryptedValue>,
     pub type_: Option<Vec<u8>>,
     pub tags: Option<Vec<Tag>>,
+    pub expires_at: Option<u64>, // filled only by storages that returned it along with the record
 }
 
 impl StorageRecord {
@@ -32,8 +39,47 @@ impl StorageRecord {
             value,
             type_,
             tags,
+            expires_at: None,
         }
     }
+
+    fn with_expiry(mut self, expires_at: Option<u64>) -> Self {
+        self.expires_at = expires_at;
+        self
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum StorageOperation {
+    Add(Vec<u8>, Vec<u8>, EncryptedValue, Vec<Tag>),
+    Update(Vec<u8>, Vec<u8>, EncryptedValue),
+    AddTags(Vec<u8>, Vec<u8>, Vec<Tag>),
+    UpdateTags(Vec<u8>, Vec<u8>, Vec<Tag>),
+    DeleteTags(Vec<u8>, Vec<u8>, Vec<TagName>),
+    Delete(Vec<u8>, Vec<u8>),
+    SetExpiry(Vec<u8>, Vec<u8>, Option<u64>), // expiry time as unix timestamp in seconds
+}
+
+// Operations staged for atomic apply with WalletStorage::commit. Nothing is written
+// to the storage before commit, so dropping the transaction rolls it back.
+#[derive(Debug, Default)]
+pub struct StorageTransaction {
+    operations: Vec<StorageOperation>,
+}
+
+impl StorageTransaction {
+    pub fn stage(&mut self, operation: StorageOperation) {
+        self.operations.push(operation);
+    }
+
+    pub fn operations(&self) -> &[StorageOperation] {
+        &self.operations
+    }
+}
+
+// Current unix time in seconds to check expiry of items against
+fn unix_time() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0)
 }
 
 pub trait StorageIterator {
@@ -49,15 +95,56 @@ pub trait WalletStorage {
     fn update_tags(&self, type_: &[u8], id: &[u8], tags: &[Tag]) -> Result<(), IndyError>;
     fn delete_tags(&self, type_: &[u8], id: &[u8], tag_names: &[TagName]) -> Result<(), IndyError>;
     fn delete(&self, type_: &[u8], id: &[u8]) -> Result<(), IndyError>;
+    fn batch(&self, operations: &[StorageOperation]) -> Result<(), IndyError>;
+    fn begin(&self) -> StorageTransaction {
+        StorageTransaction::default()
+    }
+    fn commit(&self, transaction: StorageTransaction) -> Result<(), IndyError> {
+        self.batch(transaction.operations())
+    }
+    fn rollback(&self, _transaction: StorageTransaction) {}
     fn get_storage_metadata(&self) -> Result<Vec<u8>, IndyError>;
     fn set_storage_metadata(&self, metadata: &[u8]) -> Result<(), IndyError>;
     fn get_all(&self) -> Result<Box<dyn StorageIterator>, IndyError>;
+    fn get_revision(&self) -> Result<u64, IndyError>;
+    fn get_changed(&self, since_revision: u64) -> Result<Box<dyn StorageIterator>, IndyError>;
+    fn get_deleted(&self, since_revision: u64) -> Result<Vec<(Vec<u8>, Vec<u8>)>, IndyError>;
+    // Moves deletion marks from old to new type and name keeping their revisions, atomically.
+    // Marks whose new type and name are already tracked are dropped. Returns count of moved marks.
+    fn rekey_deleted(&self, marks: &[((Vec<u8>, Vec<u8>), (Vec<u8>, Vec<u8>))]) -> Result<usize, IndyError>;
+    fn check_orphan_tags(&self, repair: bool) -> Result<usize, IndyError>;
+    fn quarantine(&self, type_: &[u8], id: &[u8]) -> Result<(), IndyError>;
+    // Expired items must be hidden by get, get_all, get_changed and search until they are purged
+    fn purge_expired(&self) -> Result<usize, IndyError>;
     fn search(&self, type_: &[u8], query: &language::Operator, options: Option<&str>) -> Result<Box<dyn StorageIterator>, IndyError>;
+    // Tag values of items matching the query ordered by value, items without the tag are skipped.
+    // Values of plaintext tags are returned as utf8 bytes, values of encrypted tags as stored.
+    fn count_by_tag(&self, type_: &[u8], query: &language::Operator, tag_name: &TagName) -> Result<Vec<(Vec<u8>, usize)>, IndyError>;
+    fn get_distinct_tag_values(&self, type_: &[u8], query: &language::Operator, tag_name: &TagName) -> Result<Vec<Vec<u8>>, IndyError>;
     fn close(&mut self) -> Result<(), IndyError>;
 }
 
 pub trait WalletStorageType {
     fn create_storage(&self, id: &str, config: Option<&str>, credentials: Option<&str>, metadata: &[u8]) -> Result<(), IndyError>;
     fn open_storage(&self, id: &str, config: Option<&str>, credentials: Option<&str>) -> Result<Box<dyn WalletStorage>, IndyError>;
+    // Storage types without dedicated read-only mode open the storage as usual,
+    // mutating calls are rejected by ReadOnlyStorage wrapper anyway.
+    fn open_storage_read_only(&self, id: &str, config: Option<&str>, credentials: Option<&str>) -> Result<Box<dyn WalletStorage>, IndyError> {
+        self.open_storage(id, config, credentials)
+    }
     fn delete_storage(&self, id: &str, config: Option<&str>, credentials: Option<&str>) -> Result<(), IndyError>;
+    // Profiles are independent logical wallets with own metadata inside one storage
+    // created by create_storage. Storage types without profiles support reject profile calls.
+    fn create_profile(&self, _id: &str, _config: Option<&str>, _credentials: Option<&str>, _profile: &str, _metadata: &[u8]) -> Result<(), IndyError> {
+        Err(err_msg(IndyErrorKind::InvalidState, "Wallet storage type doesn't support profiles"))
+    }
+    fn open_profile(&self, _id: &str, _config: Option<&str>, _credentials: Option<&str>, _profile: &str, _read_only: bool) -> Result<Box<dyn WalletStorage>, IndyError> {
+        Err(err_msg(IndyErrorKind::InvalidState, "Wallet storage type doesn't support profiles"))
+    }
+    fn delete_profile(&self, _id: &str, _config: Option<&str>, _credentials: Option<&str>, _profile: &str) -> Result<(), IndyError> {
+        Err(err_msg(IndyErrorKind::InvalidState, "Wallet storage type doesn't support profiles"))
+    }
+    fn list_profiles(&self, _id: &str, _config: Option<&str>, _credentials: Option<&str>) -> Result<Vec<String>, IndyError> {
+        Err(err_msg(IndyErrorKind::InvalidState, "Wallet storage type doesn't support profiles"))
+    }
 }
\ No newline at end of file
@@ -1,5 +1,6 @@
 use std::{slice, str};
-use std::collections::HashMap;
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashMap};
 use std::ffi::{CStr, CString};
 use std::ptr;
 
@@ -12,9 +13,21 @@ use indy_api_types::errors::prelude::*;
 use crate::language;
 use indy_utils::crypto::base64;
 
-use super::{EncryptedValue, StorageIterator, StorageRecord, Tag, TagName, WalletStorage, WalletStorageType};
+use super::{EncryptedValue, StorageIterator, StorageOperation, StorageRecord, Tag, TagName, WalletStorage, WalletStorageType, unix_time};
 use super::super::{RecordOptions, SearchOptions};
 
+const _VALUE_TAGS_OPTIONS: &str = r#"{"retrieveType":false,"retrieveValue":true,"retrieveTags":true}"#;
+const _TAGS_OPTIONS: &str = r#"{"retrieveType":false,"retrieveValue":false,"retrieveTags":true}"#;
+const _SEARCH_TAGS_OPTIONS: &str = r#"{"retrieveRecords":true,"retrieveTotalCount":false,"retrieveType":false,"retrieveValue":false,"retrieveTags":true}"#;
+
+// Plugged storages have no notion of item expiry, so the expiry time is kept as a plaintext tag
+// with this reserved name. Names of regular tags are encrypted, so they can't clash with it.
+const _EXPIRES_AT_TAG: &[u8] = b"__indy_expires_at";
+
+// Storage metadata field that marks storages with expiry tags. Expiry tags are looked for
+// only in such storages, so storages without expiring records don't pay for them.
+const _EXPIRY_METADATA_FIELD: &str = "indy_expiry_tags";
+
 #[derive(Debug, Deserialize)]
 pub struct PluggedWalletJSONValue {
     pub key: String,
@@ -59,6 +72,11 @@ struct PluggedStorageIterator {
     storage_handle: i32,
     search_handle: SearchHandle,
     options: SearchOptions,
+    return_tags: bool,
+    filter_expired: bool,
+    skip: usize,
+    limit: Option<usize>,
+    total_count: Option<usize>,
     fetch_search_next_record_handler: WalletFetchSearchNextRecord,
     get_search_total_count_handler: WalletGetSearchTotalCount,
     get_record_type_handler: WalletGetRecordType,
@@ -70,11 +88,28 @@ struct PluggedStorageIterator {
 }
 
 impl PluggedStorageIterator {
-    fn new(storage: &PluggedStorage, search_handle: SearchHandle, options: SearchOptions) -> Self {
+    // If `filter_expired` is set tags are always fetched from plugin as they contain expiry time of the record,
+    // expired records are skipped and expiry tag is stripped. Offset and limit of the search are always applied here,
+    // as plugins can't skip expired records and aren't required to support paging.
+    fn new(storage: &PluggedStorage, search_handle: SearchHandle, mut options: SearchOptions, filter_expired: bool) -> Self {
+        let return_tags = options.retrieve_tags;
+
+        if filter_expired {
+            options.retrieve_tags = true;
+        }
+
+        let skip = options.offset.take().unwrap_or(0);
+        let limit = options.limit.take();
+
         Self {
             storage_handle: storage.handle,
             search_handle,
             options,
+            return_tags,
+            filter_expired,
+            skip,
+            limit,
+            total_count: None,
             fetch_search_next_record_handler: storage.fetch_search_next_record_handler,
             get_search_total_count_handler: storage.get_search_total_count_handler,
             get_record_type_handler: storage.get_record_type_handler,
@@ -87,8 +122,8 @@ impl PluggedStorageIterator {
     }
 }
 
-impl StorageIterator for PluggedStorageIterator {
-    fn next(&mut self) -> IndyResult<Option<StorageRecord>> {
+impl PluggedStorageIterator {
+    fn _next(&mut self) -> IndyResult<Option<StorageRecord>> {
         let mut record_handle = -1;
 
         let err = (self.fetch_search_next_record_handler)(self.storage_handle,
@@ -183,10 +218,56 @@ impl StorageIterator for PluggedStorageIterator {
             id,
             value,
             tags,
+            expires_at: None,
         }))
     }
+}
+
+impl StorageIterator for PluggedStorageIterator {
+    fn next(&mut self) -> IndyResult<Option<StorageRecord>> {
+        loop {
+            if self.limit == Some(0) {
+                return Ok(None);
+            }
+
+            let mut record = match self._next()? {
+                Some(record) => record,
+                None => return Ok(None),
+            };
+
+            let expires_at = if self.filter_expired {
+                record.tags.as_mut().and_then(|tags| _take_expiry(tags))
+            } else {
+                None
+            };
+
+            if _is_expired(expires_at) {
+                continue;
+            }
+
+            if self.skip > 0 {
+                self.skip -= 1;
+                continue;
+            }
+
+            if let Some(ref mut limit) = self.limit {
+                *limit -= 1;
+            }
+
+            if !self.return_tags {
+                record.tags = None;
+            }
+
+            return Ok(Some(record.with_expiry(expires_at)));
+        }
+    }
 
     fn get_total_count(&self) -> IndyResult<Option<usize>> {
+        // plugin counts expired records as well, so searches that filter them are counted by PluggedStorage::search
+        if self.total_count.is_some() {
+            return Ok(self.total_count);
+        }
+
         let mut total_count = 0;
 
         if self.options.retrieve_total_count {
@@ -235,6 +316,7 @@ struct PluggedStorage {
     fetch_search_next_record_handler: WalletFetchSearchNextRecord,
     free_search_handler: WalletFreeSearch,
     close_handler: WalletClose,
+    expiry: Cell<bool>, // storage contains expiry tags, read from storage metadata
 }
 
 impl PluggedStorage {
@@ -283,8 +365,176 @@ impl PluggedStorage {
             fetch_search_next_record_handler,
             free_search_handler,
             close_handler,
+            expiry: Cell::new(false),
         }
     }
+
+    fn _apply_operation(&self, operation: &StorageOperation) -> IndyResult<()> {
+        match *operation {
+            StorageOperation::Add(ref type_, ref id, ref value, ref tags) => self.add(type_, id, value, tags),
+            StorageOperation::Update(ref type_, ref id, ref value) => self.update(type_, id, value),
+            StorageOperation::AddTags(ref type_, ref id, ref tags) => self.add_tags(type_, id, tags),
+            StorageOperation::UpdateTags(ref type_, ref id, ref tags) => self.update_tags(type_, id, tags),
+            StorageOperation::DeleteTags(ref type_, ref id, ref tag_names) => self.delete_tags(type_, id, tag_names),
+            StorageOperation::Delete(ref type_, ref id) => self.delete(type_, id),
+            StorageOperation::SetExpiry(ref type_, ref id, expires_at) => self._set_expiry(type_, id, expires_at),
+        }
+    }
+
+    // Builds the operation that restores the current state of the record touched by `operation`.
+    fn _undo_operation(&self, operation: &StorageOperation) -> IndyResult<StorageOperation> {
+        let undo = match *operation {
+            StorageOperation::Add(ref type_, ref id, _, _) =>
+                StorageOperation::Delete(type_.clone(), id.clone()),
+            StorageOperation::Update(ref type_, ref id, _) => {
+                let record = self._get(type_, id, _VALUE_TAGS_OPTIONS)?;
+                let value = record.value
+                    .ok_or_else(|| err_msg(IndyErrorKind::InvalidState, "Plugged storage returned record without value"))?;
+                StorageOperation::Update(type_.clone(), id.clone(), value)
+            }
+            StorageOperation::AddTags(ref type_, ref id, _) |
+            StorageOperation::UpdateTags(ref type_, ref id, _) |
+            StorageOperation::DeleteTags(ref type_, ref id, _) => {
+                let record = self._get(type_, id, _VALUE_TAGS_OPTIONS)?;
+                StorageOperation::UpdateTags(type_.clone(), id.clone(), record.tags.unwrap_or_default())
+            }
+            StorageOperation::Delete(ref type_, ref id) => {
+                let record = self._get(type_, id, _VALUE_TAGS_OPTIONS)?;
+                let value = record.value
+                    .ok_or_else(|| err_msg(IndyErrorKind::InvalidState, "Plugged storage returned record without value"))?;
+                StorageOperation::Add(type_.clone(), id.clone(), value, record.tags.unwrap_or_default())
+            }
+            StorageOperation::SetExpiry(ref type_, ref id, _) =>
+                StorageOperation::SetExpiry(type_.clone(), id.clone(), self._expires_at(type_, id)?),
+        };
+
+        Ok(undo)
+    }
+
+    fn _set_expiry(&self, type_: &[u8], id: &[u8], expires_at: Option<u64>) -> IndyResult<()> {
+        match expires_at {
+            Some(expires_at) => {
+                self._enable_expiry()?;
+                self.add_tags(type_, id, &[Tag::PlainText(_EXPIRES_AT_TAG.to_vec(), expires_at.to_string())])
+            }
+            None if self.expiry.get() => self.delete_tags(type_, id, &[TagName::OfPlain(_EXPIRES_AT_TAG.to_vec())]),
+            None => Ok(()),
+        }
+    }
+
+    // Marks the storage in its metadata as the one that contains expiry tags
+    fn _enable_expiry(&self) -> IndyResult<()> {
+        if self.expiry.get() {
+            return Ok(());
+        }
+
+        let metadata = self.get_storage_metadata()?;
+        self.expiry.set(true);
+
+        self.set_storage_metadata(&metadata)
+            .map_err(|err| {
+                self.expiry.set(false);
+                err
+            })
+    }
+
+    fn _expires_at(&self, type_: &[u8], id: &[u8]) -> IndyResult<Option<u64>> {
+        let record = self._get(type_, id, _TAGS_OPTIONS)?;
+        Ok(record.tags.and_then(|mut tags| _take_expiry(&mut tags)))
+    }
+
+    // Plugin counts expired records as well, so not expired ones are counted by fetching their tags
+    fn _count_not_expired(&self, type_: &CString, query: &CString) -> IndyResult<usize> {
+        let options_cstr = CString::new(_SEARCH_TAGS_OPTIONS)?;
+        let mut search_handle: SearchHandle = INVALID_SEARCH_HANDLE;
+
+        let err = (self.search_records_handler)(self.handle,
+                                                type_.as_ptr(),
+                                                query.as_ptr(),
+                                                options_cstr.as_ptr(),
+                                                &mut search_handle.0);
+
+        if err != ErrorCode::Success {
+            return Err(err.into());
+        }
+
+        let options: SearchOptions = serde_json::from_str(_SEARCH_TAGS_OPTIONS)
+            .to_indy(IndyErrorKind::InvalidState, "Search options is malformed json")?;
+
+        let mut iterator = PluggedStorageIterator::new(&self, search_handle, options, true);
+        let mut count = 0;
+
+        while iterator.next()?.is_some() {
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    fn _get_all(&self, filter_expired: bool) -> IndyResult<PluggedStorageIterator> {
+        let mut search_handle: SearchHandle = INVALID_SEARCH_HANDLE;
+
+        let err = (self.search_all_records_handler)(self.handle, &mut search_handle.0);
+
+        if err != ErrorCode::Success {
+            return Err(err.into());
+        }
+
+        Ok(PluggedStorageIterator::new(
+            &self,
+            search_handle,
+            SearchOptions {
+                retrieve_records: true,
+                retrieve_total_count: false,
+                retrieve_type: true,
+                retrieve_value: true,
+                retrieve_tags: true,
+                sort_by: None,
+                offset: None,
+                limit: None,
+            },
+            filter_expired,
+        ))
+    }
+}
+
+fn _is_expiry_tag(tag: &Tag) -> bool {
+    match *tag {
+        Tag::PlainText(ref name, _) => name.as_slice() == _EXPIRES_AT_TAG,
+        Tag::Encrypted(..) => false,
+    }
+}
+
+// Removes expiry tag from the tags and returns expiry time it contains
+fn _take_expiry(tags: &mut Vec<Tag>) -> Option<u64> {
+    let position = tags.iter().position(_is_expiry_tag)?;
+
+    match tags.remove(position) {
+        Tag::PlainText(_, value) => value.parse().ok(),
+        Tag::Encrypted(..) => None,
+    }
+}
+
+fn _is_expired(expires_at: Option<u64>) -> bool {
+    expires_at.map(|expires_at| expires_at <= unix_time()).unwrap_or(false)
+}
+
+fn _has_expiry_mark(metadata: &[u8]) -> bool {
+    serde_json::from_slice::<serde_json::Value>(metadata)
+        .ok()
+        .and_then(|metadata| metadata.get(_EXPIRY_METADATA_FIELD).and_then(serde_json::Value::as_bool))
+        .unwrap_or(false)
+}
+
+// Storage metadata is wallet metadata json, unknown fields of which are ignored on parsing
+fn _with_expiry_mark(metadata: &[u8]) -> IndyResult<Vec<u8>> {
+    let mut metadata: serde_json::Map<String, serde_json::Value> = serde_json::from_slice(metadata)
+        .to_indy(IndyErrorKind::InvalidState, "Storage metadata isn't json object, expiry of records can't be kept")?;
+
+    metadata.insert(_EXPIRY_METADATA_FIELD.to_string(), serde_json::Value::Bool(true));
+
+    serde_json::to_vec(&metadata)
+        .to_indy(IndyErrorKind::InvalidState, "Unable to serialize storage metadata")
 }
 
 fn _tags_to_json(tags: &[Tag]) -> IndyResult<String> {
@@ -340,8 +590,8 @@ fn _tags_names_to_json(tag_names: &[TagName]) -> IndyResult<String> {
         .to_indy(IndyErrorKind::InvalidState, "Unable to serialize tag names as json")
 }
 
-impl WalletStorage for PluggedStorage {
-    fn get(&self, type_: &[u8], id: &[u8], options: &str) -> IndyResult<StorageRecord> {
+impl PluggedStorage {
+    fn _get(&self, type_: &[u8], id: &[u8], options: &str) -> IndyResult<StorageRecord> {
         let type_cstr = CString::new(base64::encode(type_))?;
         let id_cstr = CString::new(base64::encode(id))?;
         let options_cstr = CString::new(options)?;
@@ -403,6 +653,7 @@ impl WalletStorage for PluggedStorage {
             type_: if options.retrieve_type { Some(type_.to_vec()) } else { None },
             value,
             tags,
+            expires_at: None,
         };
 
         if err != ErrorCode::Success {
@@ -412,7 +663,7 @@ impl WalletStorage for PluggedStorage {
         Ok(result)
     }
 
-    fn add(&self, type_: &[u8], id: &[u8], value: &EncryptedValue, tags: &[Tag]) -> IndyResult<()> {
+    fn _add(&self, type_: &[u8], id: &[u8], value: &EncryptedValue, tags: &[Tag]) -> IndyResult<()> {
         let type_ = CString::new(base64::encode(type_))?;
         let id = CString::new(base64::encode(id))?;
         let joined_value = value.to_bytes();
@@ -433,6 +684,60 @@ impl WalletStorage for PluggedStorage {
 
         Ok(())
     }
+}
+
+impl WalletStorage for PluggedStorage {
+    fn get(&self, type_: &[u8], id: &[u8], options_json: &str) -> IndyResult<StorageRecord> {
+        if !self.expiry.get() {
+            return self._get(type_, id, options_json);
+        }
+
+        let options: RecordOptions = serde_json::from_str(options_json)
+            .to_indy(IndyErrorKind::InvalidStructure, "RecordRetrieveOptions is malformed json")?;
+
+        // tags are required to get expiry time of the record
+        let mut record = if options.retrieve_tags {
+            self._get(type_, id, options_json)?
+        } else {
+            let plugin_options = RecordOptions {
+                retrieve_type: options.retrieve_type,
+                retrieve_value: options.retrieve_value,
+                retrieve_tags: true,
+            };
+
+            let plugin_options = serde_json::to_string(&plugin_options)
+                .to_indy(IndyErrorKind::InvalidState, "Unable to serialize RecordRetrieveOptions")?;
+
+            self._get(type_, id, &plugin_options)?
+        };
+
+        let expires_at = record.tags.as_mut().and_then(|tags| _take_expiry(tags));
+
+        if _is_expired(expires_at) {
+            return Err(err_msg(IndyErrorKind::WalletItemNotFound, "Wallet item not found"));
+        }
+
+        if !options.retrieve_tags {
+            record.tags = None;
+        }
+
+        Ok(record.with_expiry(expires_at))
+    }
+
+    fn add(&self, type_: &[u8], id: &[u8], value: &EncryptedValue, tags: &[Tag]) -> IndyResult<()> {
+        match self._add(type_, id, value, tags) {
+            Err(ref err) if err.kind() == IndyErrorKind::WalletItemAlreadyExists && self.expiry.get() => {
+                // expired item that isn't purged yet must not prevent adding of the item with the same name
+                if !_is_expired(self._expires_at(type_, id)?) {
+                    return Err(err_msg(IndyErrorKind::WalletItemAlreadyExists, "Wallet item already exists"));
+                }
+
+                self.delete(type_, id)?;
+                self._add(type_, id, value, tags)
+            }
+            res => res,
+        }
+    }
 
     fn add_tags(&self, type_: &[u8], id: &[u8], tags: &[Tag]) -> IndyResult<()> {
         let type_ = CString::new(base64::encode(type_))?;
@@ -452,6 +757,15 @@ impl WalletStorage for PluggedStorage {
     }
 
     fn update_tags(&self, type_: &[u8], id: &[u8], tags: &[Tag]) -> IndyResult<()> {
+        // replacement of tags must keep the expiry time of the record
+        let mut tags = tags.to_vec();
+
+        if self.expiry.get() && !tags.iter().any(_is_expiry_tag) {
+            if let Some(expires_at) = self._expires_at(type_, id)? {
+                tags.push(Tag::PlainText(_EXPIRES_AT_TAG.to_vec(), expires_at.to_string()));
+            }
+        }
+
         let type_ = CString::new(base64::encode(type_))?;
         let id = CString::new(base64::encode(id))?;
         let tags = CString::new(_tags_to_json(&tags)?)?;
@@ -518,6 +832,32 @@ impl WalletStorage for PluggedStorage {
         Ok(())
     }
 
+    // Plugged storages don't expose transactions, so the state of every record is captured
+    // before it is changed and already applied operations are reverted if a later one fails.
+    fn batch(&self, operations: &[StorageOperation]) -> IndyResult<()> {
+        let mut undo_log: Vec<StorageOperation> = Vec::with_capacity(operations.len());
+
+        for operation in operations {
+            let res = self._undo_operation(operation)
+                .and_then(|undo| self._apply_operation(operation).map(|_| undo));
+
+            match res {
+                Ok(undo) => undo_log.push(undo),
+                Err(err) => {
+                    for undo in undo_log.iter().rev() {
+                        if let Err(undo_err) = self._apply_operation(undo) {
+                            warn!("Unable to revert plugged storage operation: {:?}", undo_err);
+                        }
+                    }
+
+                    return Err(err);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     fn get_storage_metadata(&self) -> IndyResult<Vec<u8>> {
         let mut metadata_ptr: *const c_char = ptr::null_mut();
         let mut metadata_handle = -1;
@@ -538,11 +878,20 @@ impl WalletStorage for PluggedStorage {
                 .to_indy(IndyErrorKind::InvalidState, "Metadata contains non-utf8 symbol")?
         })?;
 
+        // wallet reads metadata right after the storage is opened
+        self.expiry.set(_has_expiry_mark(&metadata));
+
         Ok(metadata)
     }
 
     fn set_storage_metadata(&self, metadata: &[u8]) -> IndyResult<()> {
-        let metadata = CString::new(base64::encode(metadata))?;
+        let metadata = if self.expiry.get() {
+            _with_expiry_mark(metadata)?
+        } else {
+            metadata.to_vec()
+        };
+
+        let metadata = CString::new(base64::encode(&metadata))?;
 
         let err = (self.set_storage_metadata_handler)(self.handle, metadata.as_ptr());
 
@@ -554,37 +903,111 @@ impl WalletStorage for PluggedStorage {
     }
 
     fn get_all(&self) -> IndyResult<Box<dyn StorageIterator>> {
-        let mut search_handle: SearchHandle = INVALID_SEARCH_HANDLE;
+        Ok(Box::new(self._get_all(self.expiry.get())?))
+    }
 
-        let err = (self.search_all_records_handler)(self.handle, &mut search_handle.0);
+    fn get_revision(&self) -> IndyResult<u64> {
+        Err(err_msg(IndyErrorKind::InvalidState, "Plugged storage doesn't support change tracking"))
+    }
 
-        if err != ErrorCode::Success {
-            return Err(err.into());
+    fn get_changed(&self, _since_revision: u64) -> IndyResult<Box<dyn StorageIterator>> {
+        Err(err_msg(IndyErrorKind::InvalidState, "Plugged storage doesn't support change tracking"))
+    }
+
+    fn get_deleted(&self, _since_revision: u64) -> IndyResult<Vec<(Vec<u8>, Vec<u8>)>> {
+        Err(err_msg(IndyErrorKind::InvalidState, "Plugged storage doesn't support change tracking"))
+    }
+
+    fn rekey_deleted(&self, _marks: &[((Vec<u8>, Vec<u8>), (Vec<u8>, Vec<u8>))]) -> IndyResult<usize> {
+        // there are no deletion marks without change tracking
+        Ok(0)
+    }
+
+    fn check_orphan_tags(&self, _repair: bool) -> IndyResult<usize> {
+        // tags are kept by plugin as a part of the record, so they can't reference missing records
+        Ok(0)
+    }
+
+    fn quarantine(&self, _type_: &[u8], _id: &[u8]) -> IndyResult<()> {
+        Err(err_msg(IndyErrorKind::InvalidState, "Plugged storage doesn't support quarantine of records"))
+    }
+
+    ///
+    /// Plugged storage can't filter by expiry time, so all records are scanned
+    /// and expired ones are deleted one by one.
+    ///
+    fn purge_expired(&self) -> IndyResult<usize> {
+        if !self.expiry.get() {
+            return Ok(0);
         }
 
-        Ok(Box::new(
-            PluggedStorageIterator::new(
-                &self,
-                search_handle,
-                SearchOptions {
-                    retrieve_records: true,
-                    retrieve_total_count: false,
-                    retrieve_type: true,
-                    retrieve_value: true,
-                    retrieve_tags: true,
-                },
-            )
-        ))
+        let mut expired = Vec::new();
+
+        {
+            let mut iterator = self._get_all(false)?;
+
+            while let Some(mut record) = iterator.next()? {
+                let expires_at = record.tags.as_mut().and_then(|tags| _take_expiry(tags));
+
+                if _is_expired(expires_at) {
+                    let type_ = record.type_
+                        .ok_or_else(|| err_msg(IndyErrorKind::InvalidState, "Plugged storage returned record without type"))?;
+                    expired.push((type_, record.id));
+                }
+            }
+        }
+
+        for &(ref type_, ref id) in expired.iter() {
+            self.delete(type_, id)?;
+        }
+
+        Ok(expired.len())
     }
 
     fn search(&self, type_: &[u8], query: &language::Operator, options: Option<&str>) -> IndyResult<Box<dyn StorageIterator>> {
         let type_ = CString::new(base64::encode(type_))?;
         let query = CString::new(query.to_string())?;
-        let options_cstr = CString::new(options.unwrap_or("{}"))?;
 
-        let options: SearchOptions = serde_json::from_str(options.unwrap_or("{}"))
+        let options_json = options.unwrap_or("{}");
+
+        let options: SearchOptions = serde_json::from_str(options_json)
             .to_indy(IndyErrorKind::InvalidStructure, "Search options is malformed json")?;
 
+        // plugin interface doesn't require sorting, so plugin could return records in any order
+        if options.sort_by.is_some() {
+            return Err(err_msg(IndyErrorKind::WalletQueryError, "Sorting of search results isn't supported by plugged storage"));
+        }
+
+        let filter_expired = self.expiry.get();
+
+        // tags are required to get expiry time of the records, expired records are skipped and counted here.
+        // Offset and limit aren't passed to plugin as they are applied by the iterator.
+        let options_cstr = if filter_expired || options.offset.is_some() || options.limit.is_some() {
+            let plugin_options = SearchOptions {
+                retrieve_records: options.retrieve_records,
+                retrieve_total_count: options.retrieve_total_count && !filter_expired,
+                retrieve_type: options.retrieve_type,
+                retrieve_value: options.retrieve_value,
+                retrieve_tags: options.retrieve_tags || filter_expired,
+                sort_by: None,
+                offset: None,
+                limit: None,
+            };
+
+            let plugin_options = serde_json::to_string(&plugin_options)
+                .to_indy(IndyErrorKind::InvalidState, "Unable to serialize search options")?;
+
+            CString::new(plugin_options)?
+        } else {
+            CString::new(options_json)?
+        };
+
+        let total_count = if filter_expired && options.retrieve_total_count {
+            Some(self._count_not_expired(&type_, &query)?)
+        } else {
+            None
+        };
+
         let mut search_handle: SearchHandle = INVALID_SEARCH_HANDLE;
 
         let err = (self.search_records_handler)(self.handle,
@@ -597,13 +1020,39 @@ impl WalletStorage for PluggedStorage {
             return Err(err.into());
         }
 
-        Ok(Box::new(
-            PluggedStorageIterator::new(
-                &self,
-                search_handle,
-                options,
-            )
-        ))
+        let mut iterator = PluggedStorageIterator::new(&self, search_handle, options, filter_expired);
+        iterator.total_count = total_count;
+
+        Ok(Box::new(iterator))
+    }
+
+    ///
+    /// Plugin interface has no aggregate queries, so matching records are fetched
+    /// with tags only and tag values are counted here.
+    ///
+    fn count_by_tag(&self, type_: &[u8], query: &language::Operator, tag_name: &TagName) -> IndyResult<Vec<(Vec<u8>, usize)>> {
+        let mut counts: BTreeMap<Vec<u8>, usize> = BTreeMap::new();
+
+        let mut iterator = self.search(type_, query, Some(_SEARCH_TAGS_OPTIONS))?;
+
+        while let Some(record) = iterator.next()? {
+            let value = record.tags.unwrap_or_default().iter().find_map(|tag| match (tag, tag_name) {
+                (&Tag::Encrypted(ref name, ref value), &TagName::OfEncrypted(ref tag_name)) if name == tag_name => Some(value.clone()),
+                (&Tag::PlainText(ref name, ref value), &TagName::OfPlain(ref tag_name)) if name == tag_name => Some(value.as_bytes().to_vec()),
+                _ => None
+            });
+
+            if let Some(value) = value {
+                *counts.entry(value).or_insert(0) += 1;
+            }
+        }
+
+        Ok(counts.into_iter().collect())
+    }
+
+    fn get_distinct_tag_values(&self, type_: &[u8], query: &language::Operator, tag_name: &TagName) -> IndyResult<Vec<Vec<u8>>> {
+        let counts = self.count_by_tag(type_, query, tag_name)?;
+        Ok(counts.into_iter().map(|(value, _)| value).collect())
     }
 
     fn close(&mut self) -> IndyResult<()> {
@@ -1495,6 +1944,37 @@ mod tests {
         serde_json::to_string(&map).unwrap()
     }
 
+    #[test]
+    fn plugged_storage_take_expiry_works() {
+        let mut tags = vec![
+            Tag::Encrypted(_random_vector(32), _random_vector(64)),
+            Tag::PlainText(_EXPIRES_AT_TAG.to_vec(), "1".to_string()),
+        ];
+
+        assert_eq!(Some(1), _take_expiry(&mut tags));
+        assert_eq!(1, tags.len());
+        assert_eq!(None, _take_expiry(&mut tags));
+
+        assert!(_is_expired(Some(1)));
+        assert!(!_is_expired(Some(unix_time() + 3600)));
+        assert!(!_is_expired(None));
+    }
+
+    #[test]
+    fn plugged_storage_expiry_mark_works() {
+        let metadata = br#"{"keys":[1,2,3]}"#;
+        assert!(!_has_expiry_mark(metadata));
+
+        let marked = _with_expiry_mark(metadata).unwrap();
+        assert!(_has_expiry_mark(&marked));
+
+        let marked: serde_json::Value = serde_json::from_slice(&marked).unwrap();
+        assert_eq!(json!([1, 2, 3]), marked["keys"]);
+
+        assert!(!_has_expiry_mark(&_random_vector(512)));
+        assert_kind!(IndyErrorKind::InvalidState, _with_expiry_mark(&_random_vector(512)));
+    }
+
     #[test]
     fn plugged_storage_add_works() {
         DEBUG_VEC.write().unwrap().clear();
@@ -1635,6 +2115,7 @@ mod tests {
             id: id.clone(),
             value: Some(RETURN_VALUE.read().unwrap().1.clone()),
             tags: Some(RETURN_TAGS.read().unwrap().1.clone()),
+            expires_at: None,
         };
 
         assert_eq!(expected_storage_entity, storage_entity);
@@ -1687,6 +2168,7 @@ mod tests {
             id: id.clone(),
             value: Some(RETURN_VALUE.read().unwrap().1.clone()),
             tags: Some(RETURN_TAGS.read().unwrap().1.clone()),
+            expires_at: None,
         };
 
         assert_eq!(expected_storage_entity, storage_entity);
@@ -1738,6 +2220,7 @@ mod tests {
             id: id.clone(),
             value: Some(RETURN_VALUE.read().unwrap().1.clone()),
             tags: None,
+            expires_at: None,
         };
 
         assert_eq!(expected_storage_entity, storage_entity);
@@ -1784,6 +2267,7 @@ mod tests {
             id: id.clone(),
             value: None,
             tags: Some(RETURN_TAGS.read().unwrap().1.clone()),
+            expires_at: None,
         };
 
         assert_eq!(expected_storage_entity, storage_entity);
@@ -1831,6 +2315,7 @@ mod tests {
             id: id.clone(),
             value: None,
             tags: None,
+            expires_at: None,
         };
 
         assert_eq!(expected_storage_entity, storage_entity);
@@ -1951,6 +2436,7 @@ mod tests {
                 id: RETURN_ID.read().unwrap().1.clone(),
                 value: Some(RETURN_VALUE.read().unwrap().1.clone()),
                 tags: Some(RETURN_TAGS.read().unwrap().1.clone()),
+                expires_at: None,
             };
 
             assert_eq!(expected_storage_entity, storage_entity.unwrap());
@@ -2051,6 +2537,7 @@ mod tests {
                 id: RETURN_ID.read().unwrap().1.clone(),
                 value: Some(RETURN_VALUE.read().unwrap().1.clone()),
                 tags: Some(RETURN_TAGS.read().unwrap().1.clone()),
+                expires_at: None,
             };
 
             assert_eq!(expected_storage_entity, storage_entity.unwrap());
@@ -2100,6 +2587,55 @@ mod tests {
         assert_eq!(&expected_free_search_call, debug.get(6).unwrap());
     }
 
+    #[test]
+    fn plugged_storage_search_works_for_sort_by() {
+        DEBUG_VEC.write().unwrap().clear();
+
+        let storage = _open_storage();
+
+        DEBUG_VEC.write().unwrap().clear();
+
+        let query = language::Operator::And(vec![]);
+        let options = json!({"sortBy": {"tagName": "~dGFn", "order": "desc"}}).to_string();
+
+        let res = storage.search(&_random_vector(32), &query, Some(&options));
+        assert_kind!(IndyErrorKind::WalletQueryError, res);
+
+        // plugin isn't called
+        assert!(DEBUG_VEC.read().unwrap().is_empty());
+    }
+
+    #[test]
+    fn plugged_storage_search_works_for_paging() {
+        DEBUG_VEC.write().unwrap().clear();
+
+        let storage = _open_storage();
+
+        DEBUG_VEC.write().unwrap().clear();
+
+        let type_ = _random_vector(32);
+        let query = language::Operator::And(vec![]);
+        let options = json!({"retrieveTotalCount": true, "retrieveTags": true, "offset": 1, "limit": 1}).to_string();
+
+        {
+            let mut storage_iterator = storage.search(&type_, &query, Some(&options)).unwrap();
+
+            // the only record returned by plugin is skipped by offset
+            assert!(storage_iterator.next().unwrap().is_none());
+        }
+
+        // paging isn't passed to plugin
+        let expected_search_call = Call::SearchRecordsHandler(
+            RETURN_STORAGE_HANDLE,
+            Some(base64::encode(&type_)),
+            Some(query.to_string()),
+            Some(r#"{"retrieveRecords":true,"retrieveTotalCount":true,"retrieveType":false,"retrieveValue":true,"retrieveTags":true}"#.to_string()),
+        );
+
+        let debug = DEBUG_VEC.read().unwrap();
+        assert_eq!(&expected_search_call, debug.get(0).unwrap());
+    }
+
     #[test]
     fn plugged_storage_get_all_works() {
         DEBUG_VEC.write().unwrap().clear();
@@ -2132,6 +2668,7 @@ mod tests {
                 id: RETURN_ID.read().unwrap().1.clone(),
                 value: Some(RETURN_VALUE.read().unwrap().1.clone()),
                 tags: Some(RETURN_TAGS.read().unwrap().1.clone()),
+                expires_at: None,
             };
 
             assert_eq!(expected_storage_entity, storage_entity.unwrap());
@@ -0,0 +1,2026 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use postgres::GenericConnection;
+use postgres::rows::Rows;
+use postgres::tls::openssl::OpenSsl;
+use postgres::types::ToSql;
+use r2d2_postgres::{PostgresConnectionManager, TlsMode};
+
+use indy_api_types::errors::prelude::*;
+use crate::language;
+
+use super::{EncryptedValue, StorageIterator, StorageOperation, StorageRecord, Tag, TagName, WalletStorage, WalletStorageType};
+use super::super::{RecordOptions, SearchOptions};
+
+mod query;
+
+const _POSTGRES_DB: &str = "postgres";
+const _WALLETS_DB: &str = "wallets";
+const _SEARCH_PAGE_SIZE: i64 = 100;
+
+// Schema is compatible with the one of experimental postgres storage plug-in,
+// so wallets created by the plug-in can be opened with this storage type.
+// Note: wallet id length is constrained by postgres database name length to 64 characters
+const _CREATE_SCHEMA: [&str; 20] = [
+    "CREATE TABLE IF NOT EXISTS metadata (
+        id BIGSERIAL PRIMARY KEY,
+        value BYTEA NOT NULL
+    )",
+    "CREATE UNIQUE INDEX IF NOT EXISTS ux_metadata_values ON metadata(value)",
+    "CREATE TABLE IF NOT EXISTS items(
+        id BIGSERIAL PRIMARY KEY,
+        type BYTEA NOT NULL,
+        name BYTEA NOT NULL,
+        value BYTEA NOT NULL,
+        key BYTEA NOT NULL
+    )",
+    "CREATE UNIQUE INDEX IF NOT EXISTS ux_items_type_name ON items(type, name)",
+    "CREATE TABLE IF NOT EXISTS tags_encrypted(
+        name BYTEA NOT NULL,
+        value BYTEA NOT NULL,
+        item_id BIGINT NOT NULL,
+        PRIMARY KEY(name, item_id),
+        FOREIGN KEY(item_id)
+            REFERENCES items(id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+    )",
+    "CREATE INDEX IF NOT EXISTS ix_tags_encrypted_name ON tags_encrypted(name)",
+    "CREATE INDEX IF NOT EXISTS ix_tags_encrypted_value ON tags_encrypted(md5(value))",
+    "CREATE INDEX IF NOT EXISTS ix_tags_encrypted_item_id ON tags_encrypted(item_id)",
+    "CREATE TABLE IF NOT EXISTS tags_plaintext(
+        name BYTEA NOT NULL,
+        value TEXT NOT NULL,
+        item_id BIGINT NOT NULL,
+        PRIMARY KEY(name, item_id),
+        FOREIGN KEY(item_id)
+            REFERENCES items(id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+    )",
+    "CREATE INDEX IF NOT EXISTS ix_tags_plaintext_name ON tags_plaintext(name)",
+    "CREATE INDEX IF NOT EXISTS ix_tags_plaintext_value ON tags_plaintext(value)",
+    "CREATE INDEX IF NOT EXISTS ix_tags_plaintext_item_id ON tags_plaintext(item_id)",
+    "CREATE TABLE IF NOT EXISTS item_revisions(
+        type BYTEA NOT NULL,
+        name BYTEA NOT NULL,
+        revision BIGINT NOT NULL,
+        deleted BOOLEAN NOT NULL DEFAULT FALSE,
+        PRIMARY KEY(type, name)
+    )",
+    "CREATE INDEX IF NOT EXISTS ix_item_revisions_revision ON item_revisions(revision)",
+    "CREATE SEQUENCE IF NOT EXISTS item_revisions_seq",
+    "CREATE TABLE IF NOT EXISTS quarantine_items(
+        id BIGSERIAL PRIMARY KEY,
+        type BYTEA NOT NULL,
+        name BYTEA NOT NULL,
+        value BYTEA NOT NULL,
+        key BYTEA NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS quarantine_tags(
+        item_id BIGINT NOT NULL,
+        name BYTEA NOT NULL,
+        value BYTEA NOT NULL,
+        plaintext BOOLEAN NOT NULL
+    )",
+    "CREATE INDEX IF NOT EXISTS ix_quarantine_tags_item_id ON quarantine_tags(item_id)",
+    "ALTER TABLE items ADD COLUMN IF NOT EXISTS expires_at BIGINT",
+    "CREATE INDEX IF NOT EXISTS ix_items_expires_at ON items(expires_at)",
+];
+
+const _CREATE_SCHEMA_MULTI: [&str; 22] = [
+    "CREATE TABLE IF NOT EXISTS metadata (
+        wallet_id VARCHAR(64) NOT NULL,
+        value BYTEA NOT NULL,
+        PRIMARY KEY(wallet_id)
+    )",
+    "CREATE UNIQUE INDEX IF NOT EXISTS ux_metadata_wallet_id_id ON metadata(wallet_id)",
+    "CREATE UNIQUE INDEX IF NOT EXISTS ux_metadata_values ON metadata(wallet_id, value)",
+    "CREATE TABLE IF NOT EXISTS items(
+        wallet_id VARCHAR(64) NOT NULL,
+        id BIGSERIAL NOT NULL,
+        type BYTEA NOT NULL,
+        name BYTEA NOT NULL,
+        value BYTEA NOT NULL,
+        key BYTEA NOT NULL,
+        PRIMARY KEY(wallet_id, id)
+    )",
+    "CREATE UNIQUE INDEX IF NOT EXISTS ux_items_wallet_id_id ON items(wallet_id, id)",
+    "CREATE UNIQUE INDEX IF NOT EXISTS ux_items_type_name ON items(wallet_id, type, name)",
+    "CREATE TABLE IF NOT EXISTS tags_encrypted(
+        wallet_id VARCHAR(64) NOT NULL,
+        name BYTEA NOT NULL,
+        value BYTEA NOT NULL,
+        item_id BIGINT NOT NULL,
+        PRIMARY KEY(wallet_id, name, item_id),
+        FOREIGN KEY(wallet_id, item_id)
+            REFERENCES items(wallet_id, id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+    )",
+    "CREATE INDEX IF NOT EXISTS ix_tags_encrypted_name ON tags_encrypted(wallet_id, name)",
+    "CREATE INDEX IF NOT EXISTS ix_tags_encrypted_value ON tags_encrypted(wallet_id, md5(value))",
+    "CREATE INDEX IF NOT EXISTS ix_tags_encrypted_wallet_id_item_id ON tags_encrypted(wallet_id, item_id)",
+    "CREATE TABLE IF NOT EXISTS tags_plaintext(
+        wallet_id VARCHAR(64) NOT NULL,
+        name BYTEA NOT NULL,
+        value TEXT NOT NULL,
+        item_id BIGINT NOT NULL,
+        PRIMARY KEY(wallet_id, name, item_id),
+        FOREIGN KEY(wallet_id, item_id)
+            REFERENCES items(wallet_id, id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+    )",
+    "CREATE INDEX IF NOT EXISTS ix_tags_plaintext_name ON tags_plaintext(wallet_id, name)",
+    "CREATE INDEX IF NOT EXISTS ix_tags_plaintext_value ON tags_plaintext(wallet_id, value)",
+    "CREATE INDEX IF NOT EXISTS ix_tags_plaintext_wallet_id_item_id ON tags_plaintext(wallet_id, item_id)",
+    "CREATE TABLE IF NOT EXISTS item_revisions(
+        wallet_id VARCHAR(64) NOT NULL,
+        type BYTEA NOT NULL,
+        name BYTEA NOT NULL,
+        revision BIGINT NOT NULL,
+        deleted BOOLEAN NOT NULL DEFAULT FALSE,
+        PRIMARY KEY(wallet_id, type, name)
+    )",
+    "CREATE INDEX IF NOT EXISTS ix_item_revisions_revision ON item_revisions(wallet_id, revision)",
+    "CREATE SEQUENCE IF NOT EXISTS item_revisions_seq",
+    "CREATE TABLE IF NOT EXISTS quarantine_items(
+        wallet_id VARCHAR(64) NOT NULL,
+        id BIGSERIAL NOT NULL,
+        type BYTEA NOT NULL,
+        name BYTEA NOT NULL,
+        value BYTEA NOT NULL,
+        key BYTEA NOT NULL,
+        PRIMARY KEY(wallet_id, id)
+    )",
+    "CREATE TABLE IF NOT EXISTS quarantine_tags(
+        wallet_id VARCHAR(64) NOT NULL,
+        item_id BIGINT NOT NULL,
+        name BYTEA NOT NULL,
+        value BYTEA NOT NULL,
+        plaintext BOOLEAN NOT NULL
+    )",
+    "CREATE INDEX IF NOT EXISTS ix_quarantine_tags_item_id ON quarantine_tags(wallet_id, item_id)",
+    "ALTER TABLE items ADD COLUMN IF NOT EXISTS expires_at BIGINT",
+    "CREATE INDEX IF NOT EXISTS ix_items_expires_at ON items(wallet_id, expires_at)",
+];
+
+const _DROP_SCHEMA: [&str; 8] = [
+    "DROP TABLE IF EXISTS quarantine_tags",
+    "DROP TABLE IF EXISTS quarantine_items",
+    "DROP SEQUENCE IF EXISTS item_revisions_seq",
+    "DROP TABLE IF EXISTS item_revisions",
+    "DROP TABLE IF EXISTS tags_plaintext",
+    "DROP TABLE IF EXISTS tags_encrypted",
+    "DROP TABLE IF EXISTS items",
+    "DROP TABLE IF EXISTS metadata",
+];
+
+const _DELETE_WALLET_MULTI: [&str; 7] = [
+    "DELETE FROM quarantine_tags WHERE wallet_id = $1",
+    "DELETE FROM quarantine_items WHERE wallet_id = $1",
+    "DELETE FROM item_revisions WHERE wallet_id = $1",
+    "DELETE FROM tags_plaintext WHERE wallet_id = $1",
+    "DELETE FROM tags_encrypted WHERE wallet_id = $1",
+    "DELETE FROM items WHERE wallet_id = $1",
+    "DELETE FROM metadata WHERE wallet_id = $1",
+];
+
+// Storage doesn't keep a cursor open between calls, so rows are read at once.
+struct PostgresStorageIterator {
+    records: std::vec::IntoIter<StorageRecord>,
+    total_count: Option<usize>,
+}
+
+impl PostgresStorageIterator {
+    fn new(records: Vec<StorageRecord>, total_count: Option<usize>) -> PostgresStorageIterator {
+        PostgresStorageIterator {
+            records: records.into_iter(),
+            total_count,
+        }
+    }
+}
+
+impl StorageIterator for PostgresStorageIterator {
+    fn next(&mut self) -> IndyResult<Option<StorageRecord>> {
+        Ok(self.records.next())
+    }
+
+    fn get_total_count(&self) -> IndyResult<Option<usize>> {
+        Ok(self.total_count)
+    }
+}
+
+// Search results are read by pages of _SEARCH_PAGE_SIZE records, each page is a separate query.
+struct PostgresSearchIterator {
+    storage: PostgresStorage,
+    type_: Vec<u8>,
+    query: language::Operator,
+    order: query::SearchOrder,
+    options: RecordOptions,
+    records: std::vec::IntoIter<StorageRecord>,
+    fetched: i64,
+    done: bool,
+    total_count: Option<usize>,
+}
+
+impl PostgresSearchIterator {
+    fn new(storage: PostgresStorage, type_: Vec<u8>, query: language::Operator, order: query::SearchOrder,
+           options: RecordOptions, total_count: Option<usize>) -> PostgresSearchIterator {
+        PostgresSearchIterator {
+            storage,
+            type_,
+            query,
+            order,
+            options,
+            records: Vec::new().into_iter(),
+            fetched: 0,
+            done: false,
+            total_count,
+        }
+    }
+}
+
+impl StorageIterator for PostgresSearchIterator {
+    fn next(&mut self) -> IndyResult<Option<StorageRecord>> {
+        if let Some(record) = self.records.next() {
+            return Ok(Some(record));
+        }
+
+        if self.done {
+            return Ok(None);
+        }
+
+        let order = match self.order.page(self.fetched, _SEARCH_PAGE_SIZE) {
+            Some(order) => order,
+            None => return Ok(None)
+        };
+
+        let records = self.storage._search(&self.type_, &self.query, &order, &self.options)?;
+
+        self.fetched += records.len() as i64;
+        self.done = (records.len() as i64) < _SEARCH_PAGE_SIZE;
+        self.records = records.into_iter();
+
+        Ok(self.records.next())
+    }
+
+    fn get_total_count(&self) -> IndyResult<Option<usize>> {
+        Ok(self.total_count)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+enum WalletScheme {
+    // each wallet is stored in a separate database named by wallet id
+    DatabasePerWallet,
+    // all wallets share the tables of one database, rows are distinguished by wallet_id column
+    MultiWalletSingleTable,
+}
+
+impl Default for WalletScheme {
+    fn default() -> WalletScheme {
+        WalletScheme::DatabasePerWallet
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+enum Tls {
+    // plain connection
+    None,
+    // TLS connection if the server supports it, plain connection otherwise
+    Prefer,
+    // connection fails if the server doesn't support TLS
+    Require,
+}
+
+impl Default for Tls {
+    fn default() -> Tls {
+        Tls::None
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct Config {
+    url: String,
+    #[serde(default)]
+    wallet_scheme: WalletScheme,
+    // server certificate is verified against system trusted certificates
+    #[serde(default)]
+    tls: Tls,
+    // database for MultiWalletSingleTable scheme, defaults to "wallets"
+    database_name: Option<String>,
+    max_connections: Option<u32>,
+    min_idle_count: Option<u32>,
+    connection_timeout: Option<u64>,
+}
+
+impl Config {
+    fn database_name<'a>(&'a self, id: &'a str) -> &'a str {
+        match self.wallet_scheme {
+            WalletScheme::DatabasePerWallet => id,
+            WalletScheme::MultiWalletSingleTable => match self.database_name {
+                Some(ref database_name) if !database_name.is_empty() => database_name,
+                _ => _WALLETS_DB
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Credentials {
+    account: String,
+    password: String,
+    admin_account: Option<String>,
+    admin_password: Option<String>,
+}
+
+impl Credentials {
+    fn admin(&self) -> Option<(&str, &str)> {
+        match (&self.admin_account, &self.admin_password) {
+            (Some(account), Some(password)) => Some((account, password)),
+            _ => None
+        }
+    }
+}
+
+#[derive(Clone)]
+struct PostgresStorage {
+    pool: r2d2::Pool<PostgresConnectionManager>,
+    // set for MultiWalletSingleTable scheme only
+    wallet_id: Option<String>,
+}
+
+pub struct PostgresStorageType {}
+
+impl PostgresStorageType {
+    pub fn new() -> PostgresStorageType {
+        PostgresStorageType {}
+    }
+
+    fn _parse(config: Option<&str>, credentials: Option<&str>) -> IndyResult<(Config, Credentials)> {
+        let config = config
+            .ok_or_else(|| err_msg(IndyErrorKind::InvalidStructure, "Storage config is required for postgres storage"))
+            .and_then(|config| serde_json::from_str::<Config>(config)
+                .to_indy(IndyErrorKind::InvalidStructure, "Malformed postgres storage config json"))?;
+
+        let credentials = credentials
+            .ok_or_else(|| err_msg(IndyErrorKind::InvalidStructure, "Storage credentials are required for postgres storage"))
+            .and_then(|credentials| serde_json::from_str::<Credentials>(credentials)
+                .to_indy(IndyErrorKind::InvalidStructure, "Malformed postgres storage credentials json"))?;
+
+        Ok((config, credentials))
+    }
+
+    fn _url(account: &str, password: &str, config: &Config, database: &str) -> String {
+        format!("postgresql://{}:{}@{}/{}",
+                utf8_percent_encode(account, NON_ALPHANUMERIC),
+                utf8_percent_encode(password, NON_ALPHANUMERIC),
+                config.url,
+                database)
+    }
+
+    fn _tls(config: &Config) -> IndyResult<Option<OpenSsl>> {
+        match config.tls {
+            Tls::None => Ok(None),
+            Tls::Prefer | Tls::Require => OpenSsl::new()
+                .map(Some)
+                .to_indy(IndyErrorKind::InvalidState, "Unable to initialize TLS for postgres storage")
+        }
+    }
+
+    fn _connect_url(config: &Config, url: &str) -> IndyResult<postgres::Connection> {
+        let tls = PostgresStorageType::_tls(config)?;
+
+        let tls_mode = match (config.tls, tls.as_ref()) {
+            (Tls::Prefer, Some(tls)) => postgres::TlsMode::Prefer(tls),
+            (Tls::Require, Some(tls)) => postgres::TlsMode::Require(tls),
+            _ => postgres::TlsMode::None,
+        };
+
+        Ok(postgres::Connection::connect(url, tls_mode)?)
+    }
+
+    fn _connect(config: &Config, credentials: &Credentials, database: &str) -> IndyResult<postgres::Connection> {
+        let url = PostgresStorageType::_url(&credentials.account, &credentials.password, config, database);
+
+        PostgresStorageType::_connect_url(config, &url)
+            .map_err(|err| err.to_indy(IndyErrorKind::WalletNotFound, format!("Unable to connect to postgres database: {}", database)))
+    }
+
+    // Database can be created beforehand by DBA, so it's created only if admin credentials are provided.
+    fn _create_database(config: &Config, credentials: &Credentials, database: &str) -> IndyResult<()> {
+        if let Some((account, password)) = credentials.admin() {
+            let url = PostgresStorageType::_url(account, password, config, _POSTGRES_DB);
+            let conn = PostgresStorageType::_connect_url(config, &url)?;
+
+            if let Err(err) = conn.execute(&format!("CREATE DATABASE \"{}\"", database.replace('"', "\"\"")), &[]) {
+                if err.code() != Some(&postgres::error::DUPLICATE_DATABASE) {
+                    return Err(err.into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl WalletStorage for PostgresStorage {
+    fn get(&self, type_: &[u8], id: &[u8], options: &str) -> IndyResult<StorageRecord> {
+        let options: RecordOptions = if options == "{}" {
+            RecordOptions::default()
+        } else {
+            serde_json::from_str(options)
+                .to_indy(IndyErrorKind::InvalidStructure, "RecordOptions is malformed json")?
+        };
+
+        let conn = self.pool.get()?;
+
+        let rows = conn.query(
+            &format!("SELECT i.id, i.name, i.value, i.key, i.type, i.expires_at FROM items as i WHERE i.type = $1 AND i.name = $2 AND {}{}", query::NOT_EXPIRED, self._filter_as("i", 3)),
+            &self._args(&[&type_.to_vec(), &id.to_vec()]),
+        )?;
+
+        self._records(&*conn, &rows, &options)?
+            .pop()
+            .ok_or_else(|| err_msg(IndyErrorKind::WalletItemNotFound, "Item not found"))
+    }
+
+    fn add(&self, type_: &[u8], id: &[u8], value: &EncryptedValue, tags: &[Tag]) -> IndyResult<()> {
+        self._in_transaction(|conn| self._add(conn, type_, id, value, tags))
+    }
+
+    fn update(&self, type_: &[u8], id: &[u8], value: &EncryptedValue) -> IndyResult<()> {
+        self._in_transaction(|conn| self._update(conn, type_, id, value))
+    }
+
+    fn add_tags(&self, type_: &[u8], id: &[u8], tags: &[Tag]) -> IndyResult<()> {
+        self._in_transaction(|conn| self._add_tags(conn, type_, id, tags))
+    }
+
+    fn update_tags(&self, type_: &[u8], id: &[u8], tags: &[Tag]) -> IndyResult<()> {
+        self._in_transaction(|conn| self._update_tags(conn, type_, id, tags))
+    }
+
+    fn delete_tags(&self, type_: &[u8], id: &[u8], tag_names: &[TagName]) -> IndyResult<()> {
+        self._in_transaction(|conn| self._delete_tags(conn, type_, id, tag_names))
+    }
+
+    fn delete(&self, type_: &[u8], id: &[u8]) -> IndyResult<()> {
+        self._in_transaction(|conn| self._delete(conn, type_, id))
+    }
+
+    ///
+    /// Applies a list of operations as a single Postgres transaction.
+    /// Either all operations are committed or, if any of them fails,
+    /// the whole transaction is rolled back and the error of the failed operation is returned.
+    ///
+    fn batch(&self, operations: &[StorageOperation]) -> IndyResult<()> {
+        self._in_transaction(|conn| {
+            for operation in operations {
+                match *operation {
+                    StorageOperation::Add(ref type_, ref id, ref value, ref tags) => self._add(conn, type_, id, value, tags)?,
+                    StorageOperation::Update(ref type_, ref id, ref value) => self._update(conn, type_, id, value)?,
+                    StorageOperation::AddTags(ref type_, ref id, ref tags) => self._add_tags(conn, type_, id, tags)?,
+                    StorageOperation::UpdateTags(ref type_, ref id, ref tags) => self._update_tags(conn, type_, id, tags)?,
+                    StorageOperation::DeleteTags(ref type_, ref id, ref tag_names) => self._delete_tags(conn, type_, id, tag_names)?,
+                    StorageOperation::Delete(ref type_, ref id) => self._delete(conn, type_, id)?,
+                    StorageOperation::SetExpiry(ref type_, ref id, expires_at) => self._set_expiry(conn, type_, id, expires_at)?,
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn get_storage_metadata(&self) -> IndyResult<Vec<u8>> {
+        let conn = self.pool.get()?;
+
+        let rows = conn.query(&format!("SELECT value FROM metadata{}", self._where(1)), &self._args(&[]))?;
+
+        rows.iter()
+            .next()
+            .map(|row| row.get(0))
+            .ok_or_else(|| err_msg(IndyErrorKind::WalletItemNotFound, "Wallet metadata not found"))
+    }
+
+    fn set_storage_metadata(&self, metadata: &[u8]) -> IndyResult<()> {
+        let conn = self.pool.get()?;
+        conn.execute(&format!("UPDATE metadata SET value = $1{}", self._where(2)), &self._args(&[&metadata.to_vec()]))?;
+        Ok(())
+    }
+
+    fn get_all(&self) -> IndyResult<Box<dyn StorageIterator>> {
+        let conn = self.pool.get()?;
+
+        let rows = conn.query(
+            &format!("SELECT i.id, i.name, i.value, i.key, i.type, i.expires_at FROM items as i WHERE {}{} ORDER BY i.id", query::NOT_EXPIRED, self._filter_as("i", 1)),
+            &self._args(&[]),
+        )?;
+
+        let options = RecordOptions {
+            retrieve_type: true,
+            retrieve_value: true,
+            retrieve_tags: true,
+        };
+
+        let records = self._records(&*conn, &rows, &options)?;
+        Ok(Box::new(PostgresStorageIterator::new(records, None)))
+    }
+
+    fn get_revision(&self) -> IndyResult<u64> {
+        let conn = self.pool.get()?;
+
+        let rows = conn.query(&format!("SELECT COALESCE(MAX(revision), 0) FROM item_revisions{}", self._where(1)), &self._args(&[]))?;
+        let revision: i64 = rows.get(0).get(0);
+
+        Ok(revision as u64)
+    }
+
+    fn get_changed(&self, since_revision: u64) -> IndyResult<Box<dyn StorageIterator>> {
+        let conn = self.pool.get()?;
+
+        let join = if self.wallet_id.is_some() { " AND r.wallet_id = i.wallet_id" } else { "" };
+        let rows = conn.query(
+            &format!("SELECT i.id, i.name, i.value, i.key, i.type, i.expires_at FROM items as i \
+                      INNER JOIN item_revisions as r ON r.type = i.type AND r.name = i.name{} \
+                      WHERE r.revision > $1 AND NOT r.deleted AND {}{} ORDER BY i.id", join, query::NOT_EXPIRED, self._filter_as("i", 2)),
+            &self._args(&[&(since_revision as i64)]),
+        )?;
+
+        let options = RecordOptions {
+            retrieve_type: true,
+            retrieve_value: true,
+            retrieve_tags: true,
+        };
+
+        let records = self._records(&*conn, &rows, &options)?;
+        Ok(Box::new(PostgresStorageIterator::new(records, None)))
+    }
+
+    fn get_deleted(&self, since_revision: u64) -> IndyResult<Vec<(Vec<u8>, Vec<u8>)>> {
+        let conn = self.pool.get()?;
+
+        let rows = conn.query(
+            &format!("SELECT type, name FROM item_revisions WHERE revision > $1 AND deleted{}", self._filter(2)),
+            &self._args(&[&(since_revision as i64)]),
+        )?;
+
+        Ok(rows.iter().map(|row| (row.get(0), row.get(1))).collect())
+    }
+
+    fn rekey_deleted(&self, marks: &[((Vec<u8>, Vec<u8>), (Vec<u8>, Vec<u8>))]) -> IndyResult<usize> {
+        let mut moved = 0;
+
+        self._in_transaction(|conn| {
+            for &((ref type_, ref id), (ref new_type, ref new_id)) in marks {
+                conn.execute(
+                    &format!("DELETE FROM item_revisions WHERE type = $1 AND name = $2 AND deleted{0} \
+                              AND EXISTS (SELECT 1 FROM item_revisions WHERE type = $3 AND name = $4{0})", self._filter(5)),
+                    &self._args(&[type_, id, new_type, new_id]),
+                )?;
+
+                moved += conn.execute(
+                    &format!("UPDATE item_revisions SET type = $3, name = $4 WHERE type = $1 AND name = $2 AND deleted{}", self._filter(5)),
+                    &self._args(&[type_, id, new_type, new_id]),
+                )? as usize;
+            }
+            Ok(())
+        })?;
+
+        Ok(moved)
+    }
+
+    fn check_orphan_tags(&self, repair: bool) -> IndyResult<usize> {
+        let conn = self.pool.get()?;
+
+        let orphans = |table: &str| format!("FROM {} WHERE item_id NOT IN (SELECT id FROM items{}){}", table, self._where(1), self._filter(1));
+
+        let rows = conn.query(
+            &format!("SELECT (SELECT COUNT(*) {}) + (SELECT COUNT(*) {})", orphans("tags_encrypted"), orphans("tags_plaintext")),
+            &self._args(&[]),
+        )?;
+        let count: i64 = rows.get(0).get(0);
+
+        if repair && count > 0 {
+            self._in_transaction(|conn| {
+                conn.execute(&format!("DELETE {}", orphans("tags_encrypted")), &self._args(&[]))?;
+                conn.execute(&format!("DELETE {}", orphans("tags_plaintext")), &self._args(&[]))?;
+                Ok(())
+            })?;
+        }
+
+        Ok(count as usize)
+    }
+
+    ///
+    /// Moves the item with its tags from the items tables to the quarantine tables
+    /// as a single Postgres transaction.
+    ///
+    fn quarantine(&self, type_: &[u8], id: &[u8]) -> IndyResult<()> {
+        self._in_transaction(|conn| {
+            let item_id = self._item_id(conn, type_, id)?;
+
+            let rows = conn.query(
+                &format!("INSERT INTO quarantine_items (type, name, value, key{0}) \
+                          SELECT type, name, value, key{0} FROM items WHERE id = $1{1} RETURNING id", self._column(), self._filter(2)),
+                &self._args(&[&item_id]),
+            )?;
+            let quarantine_id: i64 = rows.get(0).get(0);
+
+            conn.execute(
+                &format!("INSERT INTO quarantine_tags (item_id, name, value, plaintext{0}) \
+                          SELECT $1, name, value, FALSE{0} FROM tags_encrypted WHERE item_id = $2{1}", self._column(), self._filter(3)),
+                &self._args(&[&quarantine_id, &item_id]),
+            )?;
+            conn.execute(
+                &format!("INSERT INTO quarantine_tags (item_id, name, value, plaintext{0}) \
+                          SELECT $1, name, convert_to(value, 'UTF8'), TRUE{0} FROM tags_plaintext WHERE item_id = $2{1}", self._column(), self._filter(3)),
+                &self._args(&[&quarantine_id, &item_id]),
+            )?;
+
+            // tags are removed by cascade
+            conn.execute(&format!("DELETE FROM items WHERE id = $1{}", self._filter(2)), &self._args(&[&item_id]))?;
+            self._bump_revision(conn, type_, id, true)
+        })
+    }
+
+    ///
+    /// Deletes all expired items as a single Postgres transaction,
+    /// deletion of each item is recorded in item revisions.
+    ///
+    fn purge_expired(&self) -> IndyResult<usize> {
+        let mut count = 0;
+
+        self._in_transaction(|conn| {
+            let rows = conn.query(
+                &format!("SELECT i.type, i.name FROM items as i WHERE {}{}", query::EXPIRED, self._filter_as("i", 1)),
+                &self._args(&[]),
+            )?;
+
+            for row in rows.iter() {
+                let (type_, id): (Vec<u8>, Vec<u8>) = (row.get(0), row.get(1));
+                self._delete(conn, &type_, &id)?;
+                count += 1;
+            }
+
+            Ok(())
+        })?;
+
+        Ok(count)
+    }
+
+    fn search(&self, type_: &[u8], query: &language::Operator, options: Option<&str>) -> IndyResult<Box<dyn StorageIterator>> {
+        let type_ = type_.to_vec();
+
+        let search_options = match options {
+            None => SearchOptions::default(),
+            Some(option_str) => serde_json::from_str(option_str)
+                .to_indy(IndyErrorKind::InvalidStructure, "Search options is malformed json")?
+        };
+
+        let conn = self.pool.get()?;
+
+        let total_count: Option<usize> = if search_options.retrieve_total_count {
+            let (query_string, query_arguments) = query::wql_to_sql_count(&type_, query, self.wallet_id.as_ref())?;
+
+            let rows = conn.query(&query_string, &query_arguments)?;
+            let count: i64 = rows.get(0).get(0);
+            Some(count as usize)
+        } else { None };
+
+        if search_options.retrieve_records {
+            let fetch_options = RecordOptions {
+                retrieve_value: search_options.retrieve_value,
+                retrieve_tags: search_options.retrieve_tags,
+                retrieve_type: search_options.retrieve_type,
+            };
+
+            let order = query::SearchOrder::from_options(&search_options)?;
+
+            Ok(Box::new(PostgresSearchIterator::new(self.clone(), type_, query.clone(), order, fetch_options, total_count)))
+        } else {
+            Ok(Box::new(PostgresStorageIterator::new(Vec::new(), total_count)))
+        }
+    }
+
+    fn count_by_tag(&self, type_: &[u8], query: &language::Operator, tag_name: &TagName) -> IndyResult<Vec<(Vec<u8>, usize)>> {
+        let type_ = type_.to_vec();
+
+        // values of plaintext tags are stored as text
+        let (tags_table, name, plaintext) = match *tag_name {
+            TagName::OfEncrypted(ref name) => ("tags_encrypted", name, false),
+            TagName::OfPlain(ref name) => ("tags_plaintext", name, true),
+        };
+
+        let conn = self.pool.get()?;
+
+        let (query_string, query_arguments) = query::wql_to_sql_count_by_tag(&type_, query, tags_table, name, self.wallet_id.as_ref())?;
+
+        let mut counts = Vec::new();
+
+        for row in conn.query(&query_string, &query_arguments)?.iter() {
+            let value: Vec<u8> = if plaintext {
+                row.get::<_, String>(0).into_bytes()
+            } else {
+                row.get(0)
+            };
+            let count: i64 = row.get(1);
+            counts.push((value, count as usize));
+        }
+
+        Ok(counts)
+    }
+
+    fn get_distinct_tag_values(&self, type_: &[u8], query: &language::Operator, tag_name: &TagName) -> IndyResult<Vec<Vec<u8>>> {
+        let counts = self.count_by_tag(type_, query, tag_name)?;
+        Ok(counts.into_iter().map(|(value, _)| value).collect())
+    }
+
+    fn close(&mut self) -> IndyResult<()> {
+        // connections are closed when the pool is dropped
+        Ok(())
+    }
+}
+
+impl PostgresStorage {
+    // For MultiWalletSingleTable scheme every table has wallet_id column
+    // and wallet id is always bound as the last argument of a statement.
+
+    fn _column(&self) -> &'static str {
+        if self.wallet_id.is_some() { ", wallet_id" } else { "" }
+    }
+
+    fn _value(&self, index: usize) -> String {
+        if self.wallet_id.is_some() { format!(", ${}", index) } else { String::new() }
+    }
+
+    fn _where(&self, index: usize) -> String {
+        if self.wallet_id.is_some() { format!(" WHERE wallet_id = ${}", index) } else { String::new() }
+    }
+
+    fn _filter(&self, index: usize) -> String {
+        if self.wallet_id.is_some() { format!(" AND wallet_id = ${}", index) } else { String::new() }
+    }
+
+    fn _filter_as(&self, alias: &str, index: usize) -> String {
+        if self.wallet_id.is_some() { format!(" AND {}.wallet_id = ${}", alias, index) } else { String::new() }
+    }
+
+    fn _args<'a>(&'a self, args: &[&'a dyn ToSql]) -> Vec<&'a dyn ToSql> {
+        let mut args = args.to_vec();
+        if let Some(ref wallet_id) = self.wallet_id {
+            args.push(wallet_id);
+        }
+        args
+    }
+
+    fn _in_transaction<F>(&self, f: F) -> IndyResult<()> where F: FnOnce(&dyn GenericConnection) -> IndyResult<()> {
+        let conn = self.pool.get()?;
+        let tx = conn.transaction()?;
+        f(&tx)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn _records(&self, conn: &dyn GenericConnection, rows: &Rows, options: &RecordOptions) -> IndyResult<Vec<StorageRecord>> {
+        let mut records = Vec::with_capacity(rows.len());
+
+        // get, get_all and get_changed select expires_at as 6th column to keep it in rotation, export and migration
+        let retrieve_expiry = rows.columns().len() > 5;
+
+        let mut tags = if options.retrieve_tags {
+            self._tags(conn, &rows.iter().map(|row| row.get(0)).collect::<Vec<i64>>())?
+        } else {
+            HashMap::new()
+        };
+
+        for row in rows.iter() {
+            let value = if options.retrieve_value {
+                Some(EncryptedValue::new(row.get(2), row.get(3)))
+            } else {
+                None
+            };
+
+            let tags = if options.retrieve_tags {
+                Some(tags.remove(&row.get::<_, i64>(0)).unwrap_or_default())
+            } else {
+                None
+            };
+
+            let type_ = if options.retrieve_type {
+                Some(row.get(4))
+            } else {
+                None
+            };
+
+            let expires_at = if retrieve_expiry {
+                row.get::<_, Option<i64>>(5).map(|expires_at| expires_at as u64)
+            } else {
+                None
+            };
+
+            records.push(StorageRecord::new(row.get(1), value, type_, tags).with_expiry(expires_at));
+        }
+
+        Ok(records)
+    }
+
+    // Tags of all the items are read by one query per tags table
+    fn _tags(&self, conn: &dyn GenericConnection, item_ids: &[i64]) -> IndyResult<HashMap<i64, Vec<Tag>>> {
+        let mut tags: HashMap<i64, Vec<Tag>> = HashMap::new();
+
+        if item_ids.is_empty() {
+            return Ok(tags);
+        }
+
+        let item_ids = item_ids.to_vec();
+
+        let rows = conn.query(&format!("SELECT item_id, name, value FROM tags_encrypted WHERE item_id = ANY($1){}", self._filter(2)), &self._args(&[&item_ids]))?;
+
+        for row in rows.iter() {
+            tags.entry(row.get(0)).or_insert_with(Vec::new).push(Tag::Encrypted(row.get(1), row.get(2)));
+        }
+
+        let rows = conn.query(&format!("SELECT item_id, name, value FROM tags_plaintext WHERE item_id = ANY($1){}", self._filter(2)), &self._args(&[&item_ids]))?;
+
+        for row in rows.iter() {
+            tags.entry(row.get(0)).or_insert_with(Vec::new).push(Tag::PlainText(row.get(1), row.get(2)));
+        }
+
+        Ok(tags)
+    }
+
+    fn _search(&self, type_: &Vec<u8>, wql: &language::Operator, order: &query::SearchOrder, options: &RecordOptions) -> IndyResult<Vec<StorageRecord>> {
+        let conn = self.pool.get()?;
+
+        let (query_string, query_arguments) = query::wql_to_sql(type_, wql, order, self.wallet_id.as_ref())?;
+
+        let rows = conn.query(&query_string, &query_arguments)?;
+        self._records(&*conn, &rows, options)
+    }
+
+    fn _item_id(&self, conn: &dyn GenericConnection, type_: &[u8], id: &[u8]) -> IndyResult<i64> {
+        let rows = conn.query(
+            &format!("SELECT id FROM items WHERE type = $1 AND name = $2{}", self._filter(3)),
+            &self._args(&[&type_.to_vec(), &id.to_vec()]),
+        )?;
+
+        rows.iter()
+            .next()
+            .map(|row| row.get(0))
+            .ok_or_else(|| err_msg(IndyErrorKind::WalletItemNotFound, "Item not found"))
+    }
+
+    // Revision of item is bumped on each mutation of the item or its tags.
+    // Deleted items are kept as tombstones to be able to export deletions.
+    // Revisions are taken from the sequence, so concurrent transactions never get the same revision.
+    fn _bump_revision(&self, conn: &dyn GenericConnection, type_: &[u8], id: &[u8], deleted: bool) -> IndyResult<()> {
+        conn.execute(
+            &format!("INSERT INTO item_revisions (type, name, revision, deleted{0}) \
+                      VALUES ($1, $2, nextval('item_revisions_seq'), $3{1}) \
+                      ON CONFLICT (type, name{0}) DO UPDATE SET revision = excluded.revision, deleted = excluded.deleted",
+                     self._column(), self._value(4)),
+            &self._args(&[&type_.to_vec(), &id.to_vec(), &deleted]),
+        )?;
+
+        Ok(())
+    }
+
+    fn _insert_tags(&self, conn: &dyn GenericConnection, item_id: i64, tags: &[Tag], replace: bool) -> IndyResult<()> {
+        let on_conflict = if replace {
+            format!(" ON CONFLICT (name, item_id{}) DO UPDATE SET value = excluded.value", self._column())
+        } else {
+            String::new()
+        };
+
+        let enc_tag_insert = format!("INSERT INTO tags_encrypted (item_id, name, value{}) VALUES ($1, $2, $3{}){}", self._column(), self._value(4), on_conflict);
+        let plain_tag_insert = format!("INSERT INTO tags_plaintext (item_id, name, value{}) VALUES ($1, $2, $3{}){}", self._column(), self._value(4), on_conflict);
+
+        for tag in tags {
+            match *tag {
+                Tag::Encrypted(ref tag_name, ref tag_data) => conn.execute(&enc_tag_insert, &self._args(&[&item_id, tag_name, tag_data]))?,
+                Tag::PlainText(ref tag_name, ref tag_data) => conn.execute(&plain_tag_insert, &self._args(&[&item_id, tag_name, tag_data]))?,
+            };
+        }
+
+        Ok(())
+    }
+
+    fn _add(&self, conn: &dyn GenericConnection, type_: &[u8], id: &[u8], value: &EncryptedValue, tags: &[Tag]) -> IndyResult<()> {
+        // expired item that isn't purged yet must not prevent adding of the item with the same name
+        conn.execute(
+            &format!("DELETE FROM items as i WHERE i.type = $1 AND i.name = $2 AND {}{}", query::EXPIRED, self._filter_as("i", 3)),
+            &self._args(&[&type_.to_vec(), &id.to_vec()]),
+        )?;
+
+        let rows = conn.query(
+            &format!("INSERT INTO items (type, name, value, key{}) VALUES ($1, $2, $3, $4{}) RETURNING id", self._column(), self._value(5)),
+            &self._args(&[&type_.to_vec(), &id.to_vec(), &value.data, &value.key]),
+        )?;
+        let item_id: i64 = rows.get(0).get(0);
+
+        self._insert_tags(conn, item_id, tags, false)?;
+        self._bump_revision(conn, type_, id, false)
+    }
+
+    fn _update(&self, conn: &dyn GenericConnection, type_: &[u8], id: &[u8], value: &EncryptedValue) -> IndyResult<()> {
+        let row_count = conn.execute(
+            &format!("UPDATE items SET value = $1, key = $2 WHERE type = $3 AND name = $4{}", self._filter(5)),
+            &self._args(&[&value.data, &value.key, &type_.to_vec(), &id.to_vec()]),
+        )?;
+
+        match row_count {
+            1 => self._bump_revision(conn, type_, id, false),
+            0 => Err(err_msg(IndyErrorKind::WalletItemNotFound, "Item to update not found")),
+            _ => Err(err_msg(IndyErrorKind::InvalidState, "More than one row update. Seems wallet structure is inconsistent")),
+        }
+    }
+
+    fn _set_expiry(&self, conn: &dyn GenericConnection, type_: &[u8], id: &[u8], expires_at: Option<u64>) -> IndyResult<()> {
+        let row_count = conn.execute(
+            &format!("UPDATE items SET expires_at = $1 WHERE type = $2 AND name = $3{}", self._filter(4)),
+            &self._args(&[&expires_at.map(|expires_at| expires_at as i64), &type_.to_vec(), &id.to_vec()]),
+        )?;
+
+        match row_count {
+            1 => self._bump_revision(conn, type_, id, false),
+            0 => Err(err_msg(IndyErrorKind::WalletItemNotFound, "Item to set expiry not found")),
+            _ => Err(err_msg(IndyErrorKind::InvalidState, "More than one row update. Seems wallet structure is inconsistent")),
+        }
+    }
+
+    fn _add_tags(&self, conn: &dyn GenericConnection, type_: &[u8], id: &[u8], tags: &[Tag]) -> IndyResult<()> {
+        let item_id = self._item_id(conn, type_, id)?;
+
+        self._insert_tags(conn, item_id, tags, true)?;
+        self._bump_revision(conn, type_, id, false)
+    }
+
+    fn _update_tags(&self, conn: &dyn GenericConnection, type_: &[u8], id: &[u8], tags: &[Tag]) -> IndyResult<()> {
+        let item_id = self._item_id(conn, type_, id)?;
+
+        conn.execute(&format!("DELETE FROM tags_encrypted WHERE item_id = $1{}", self._filter(2)), &self._args(&[&item_id]))?;
+        conn.execute(&format!("DELETE FROM tags_plaintext WHERE item_id = $1{}", self._filter(2)), &self._args(&[&item_id]))?;
+
+        self._insert_tags(conn, item_id, tags, false)?;
+        self._bump_revision(conn, type_, id, false)
+    }
+
+    fn _delete_tags(&self, conn: &dyn GenericConnection, type_: &[u8], id: &[u8], tag_names: &[TagName]) -> IndyResult<()> {
+        let item_id = self._item_id(conn, type_, id)?;
+
+        let enc_tag_delete = format!("DELETE FROM tags_encrypted WHERE item_id = $1 AND name = $2{}", self._filter(3));
+        let plain_tag_delete = format!("DELETE FROM tags_plaintext WHERE item_id = $1 AND name = $2{}", self._filter(3));
+
+        for tag_name in tag_names {
+            match *tag_name {
+                TagName::OfEncrypted(ref tag_name) => conn.execute(&enc_tag_delete, &self._args(&[&item_id, tag_name]))?,
+                TagName::OfPlain(ref tag_name) => conn.execute(&plain_tag_delete, &self._args(&[&item_id, tag_name]))?,
+            };
+        }
+
+        self._bump_revision(conn, type_, id, false)
+    }
+
+    fn _delete(&self, conn: &dyn GenericConnection, type_: &[u8], id: &[u8]) -> IndyResult<()> {
+        let row_count = conn.execute(
+            &format!("DELETE FROM items WHERE type = $1 AND name = $2{}", self._filter(3)),
+            &self._args(&[&type_.to_vec(), &id.to_vec()]),
+        )?;
+
+        if row_count == 1 {
+            self._bump_revision(conn, type_, id, true)
+        } else {
+            Err(err_msg(IndyErrorKind::WalletItemNotFound, "Item to delete not found"))
+        }
+    }
+}
+
+impl WalletStorageType for PostgresStorageType {
+    ///
+    /// Creates the wallet in Postgres database according to the wallet scheme of the config
+    /// and stores the wallet metadata there.
+    ///
+    /// # Arguments
+    ///
+    ///  * `id` - id of the wallet
+    ///  * `config` - config containing the url of Postgres server and wallet scheme
+    ///  * `credentials` - DB credentials, admin credentials allow to create databases
+    ///  * `metadata` - encryption keys that need to be stored in the newly created wallet
+    ///
+    /// # Errors
+    ///
+    ///  * `WalletAlreadyExists` - Wallet with a given id already exists
+    ///  * `WalletNotFound` - Unable to connect to the database of the wallet
+    ///
+    fn create_storage(&self, id: &str, config: Option<&str>, credentials: Option<&str>, metadata: &[u8]) -> IndyResult<()> {
+        let (config, credentials) = PostgresStorageType::_parse(config, credentials)?;
+        let database = config.database_name(id);
+
+        PostgresStorageType::_create_database(&config, &credentials, database)?;
+        let conn = PostgresStorageType::_connect(&config, &credentials, database)?;
+
+        match config.wallet_scheme {
+            WalletScheme::DatabasePerWallet => {
+                // metadata table is missing in a new database
+                let exists = conn.query("SELECT value FROM metadata", &[])
+                    .map(|rows| !rows.is_empty())
+                    .unwrap_or(false);
+
+                if exists {
+                    return Err(err_msg(IndyErrorKind::WalletAlreadyExists, format!("Wallet database already exists: {}", id)));
+                }
+
+                for sql in _CREATE_SCHEMA.iter() {
+                    conn.execute(sql, &[])?;
+                }
+
+                conn.execute("INSERT INTO metadata(value) VALUES($1)", &[&metadata.to_vec()])?;
+            }
+            WalletScheme::MultiWalletSingleTable => {
+                for sql in _CREATE_SCHEMA_MULTI.iter() {
+                    conn.execute(sql, &[])?;
+                }
+
+                if let Err(err) = conn.execute("INSERT INTO metadata(wallet_id, value) VALUES($1, $2)", &[&id, &metadata.to_vec()]) {
+                    return if err.code() == Some(&postgres::error::UNIQUE_VIOLATION) {
+                        Err(err_msg(IndyErrorKind::WalletAlreadyExists, format!("Wallet already exists: {}", id)))
+                    } else {
+                        Err(err.into())
+                    };
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Opens the pool of connections to the database of the wallet.
+    /// Missing tables are created, so wallets of the postgres storage plug-in can be opened too.
+    ///
+    fn open_storage(&self, id: &str, config: Option<&str>, credentials: Option<&str>) -> IndyResult<Box<dyn WalletStorage>> {
+        let (config, credentials) = PostgresStorageType::_parse(config, credentials)?;
+        let database = config.database_name(id);
+
+        let url = PostgresStorageType::_url(&credentials.account, &credentials.password, &config, database);
+        let tls_mode = match (config.tls, PostgresStorageType::_tls(&config)?) {
+            (Tls::Prefer, Some(tls)) => TlsMode::Prefer(Box::new(tls)),
+            (Tls::Require, Some(tls)) => TlsMode::Require(Box::new(tls)),
+            _ => TlsMode::None,
+        };
+        let manager = PostgresConnectionManager::new(url.as_str(), tls_mode)?;
+
+        let pool = r2d2::Pool::builder()
+            .min_idle(Some(config.min_idle_count.unwrap_or(0)))
+            .max_size(config.max_connections.unwrap_or(5))
+            .idle_timeout(Some(Duration::new(config.connection_timeout.unwrap_or(5), 0)))
+            .build(manager)?;
+
+        let storage = PostgresStorage {
+            pool,
+            wallet_id: match config.wallet_scheme {
+                WalletScheme::DatabasePerWallet => None,
+                WalletScheme::MultiWalletSingleTable => Some(id.to_string()),
+            },
+        };
+
+        let metadata = storage.get_storage_metadata()
+            .map_err(|err| err.to_indy(IndyErrorKind::WalletNotFound, format!("Wallet not found: {}", id)))?;
+
+        let schema = match config.wallet_scheme {
+            WalletScheme::DatabasePerWallet => &_CREATE_SCHEMA[..],
+            WalletScheme::MultiWalletSingleTable => &_CREATE_SCHEMA_MULTI[..],
+        };
+
+        let conn = storage.pool.get()?;
+
+        let rows = conn.query("SELECT to_regclass('item_revisions_seq') IS NOT NULL", &[])?;
+        let has_revisions_seq: bool = rows.get(0).get(0);
+
+        for sql in schema {
+            conn.execute(sql, &[])?;
+        }
+
+        // revisions of wallets created before the sequence was introduced must keep growing
+        if !has_revisions_seq {
+            conn.query("SELECT setval('item_revisions_seq', (SELECT COALESCE(MAX(revision), 0) + 1 FROM item_revisions), false)", &[])?;
+        }
+
+        trace!("open_storage >> wallet: {}, metadata length: {}", id, metadata.len());
+
+        Ok(Box::new(storage))
+    }
+
+    ///
+    /// Deletes the wallet. For DatabasePerWallet scheme the database is dropped if admin credentials
+    /// are provided, otherwise only wallet tables are dropped.
+    ///
+    fn delete_storage(&self, id: &str, config: Option<&str>, credentials: Option<&str>) -> IndyResult<()> {
+        let (config, credentials) = PostgresStorageType::_parse(config, credentials)?;
+        let database = config.database_name(id);
+
+        match config.wallet_scheme {
+            WalletScheme::DatabasePerWallet => {
+                {
+                    let conn = PostgresStorageType::_connect(&config, &credentials, database)?;
+
+                    for sql in _DROP_SCHEMA.iter() {
+                        conn.execute(sql, &[])?;
+                    }
+                }
+
+                if let Some((account, password)) = credentials.admin() {
+                    let url = PostgresStorageType::_url(account, password, &config, _POSTGRES_DB);
+                    let conn = PostgresStorageType::_connect_url(&config, &url)?;
+                    conn.execute(&format!("DROP DATABASE \"{}\"", database.replace('"', "\"\"")), &[])?;
+                }
+            }
+            WalletScheme::MultiWalletSingleTable => {
+                let conn = PostgresStorageType::_connect(&config, &credentials, database)?;
+                let tx = conn.transaction()?;
+
+                let mut row_count = 0;
+
+                for sql in _DELETE_WALLET_MULTI.iter() {
+                    row_count = tx.execute(sql, &[&id])?;
+                }
+
+                // metadata is deleted last
+                if row_count == 0 {
+                    return Err(err_msg(IndyErrorKind::WalletNotFound, format!("Wallet not found: {}", id)));
+                }
+
+                tx.commit()?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use indy_utils::crypto::base64;
+
+    use super::*;
+    use super::super::unix_time;
+
+    // Tests that need running Postgres server are ignored by default, they are run by:
+    // POSTGRES_PASSWORD=<password> cargo test --features postgres_storage -- --ignored
+    // Server address and account are set by POSTGRES_URL and POSTGRES_ACCOUNT environment variables,
+    // localhost:5432 and postgres by default. The account is used as admin account too.
+    // Wallet scheme is selected by WALLET_SCHEME environment variable, DatabasePerWallet by default.
+
+    #[test]
+    #[ignore]
+    fn postgres_storage_type_create_works() {
+        _cleanup("postgres_storage_type_create_works");
+
+        let storage_type = PostgresStorageType::new();
+        storage_type.create_storage("postgres_storage_type_create_works", Some(&_config()), Some(&_credentials()), &_metadata()).unwrap();
+
+        _cleanup("postgres_storage_type_create_works");
+    }
+
+    #[test]
+    #[ignore]
+    fn postgres_storage_type_create_works_for_twice() {
+        _cleanup("postgres_storage_type_create_works_for_twice");
+
+        let storage_type = PostgresStorageType::new();
+        storage_type.create_storage("postgres_storage_type_create_works_for_twice", Some(&_config()), Some(&_credentials()), &_metadata()).unwrap();
+
+        let res = storage_type.create_storage("postgres_storage_type_create_works_for_twice", Some(&_config()), Some(&_credentials()), &_metadata());
+        assert_kind!(IndyErrorKind::WalletAlreadyExists, res);
+
+        _cleanup("postgres_storage_type_create_works_for_twice");
+    }
+
+    #[test]
+    fn postgres_storage_type_create_works_for_missed_config() {
+        let storage_type = PostgresStorageType::new();
+
+        let res = storage_type.create_storage("postgres_storage_type_create_works_for_missed_config", None, Some(&_credentials()), &_metadata());
+        assert_kind!(IndyErrorKind::InvalidStructure, res);
+    }
+
+    #[test]
+    #[ignore]
+    fn postgres_storage_get_storage_metadata_works() {
+        _cleanup("postgres_storage_get_storage_metadata_works");
+        {
+            let storage = _storage("postgres_storage_get_storage_metadata_works");
+            assert_eq!(storage.get_storage_metadata().unwrap(), _metadata());
+
+            storage.set_storage_metadata(&_value1().data).unwrap();
+            assert_eq!(storage.get_storage_metadata().unwrap(), _value1().data);
+        }
+        _cleanup("postgres_storage_get_storage_metadata_works");
+    }
+
+    #[test]
+    #[ignore]
+    fn postgres_storage_type_delete_works_for_non_existing() {
+        _cleanup("postgres_storage_type_delete_works_for_non_existing");
+
+        let storage_type = PostgresStorageType::new();
+        let res = storage_type.delete_storage("postgres_storage_type_delete_works_for_non_existing", Some(&_config()), Some(&_credentials()));
+        assert_kind!(IndyErrorKind::WalletNotFound, res);
+    }
+
+    #[test]
+    #[ignore]
+    fn postgres_storage_type_open_works_for_not_created() {
+        _cleanup("postgres_storage_type_open_works_for_not_created");
+
+        let storage_type = PostgresStorageType::new();
+        let res = storage_type.open_storage("postgres_storage_type_open_works_for_not_created", Some(&_config()), Some(&_credentials()));
+        assert_kind!(IndyErrorKind::WalletNotFound, res);
+    }
+
+    #[test]
+    #[ignore]
+    fn postgres_storage_add_works_for_is_802() {
+        _cleanup("postgres_storage_add_works_for_is_802");
+        {
+            let storage = _storage("postgres_storage_add_works_for_is_802");
+
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+
+            let res = storage.add(&_type1(), &_id1(), &_value1(), &_tags());
+            assert_kind!(IndyErrorKind::WalletItemAlreadyExists, res);
+        }
+        _cleanup("postgres_storage_add_works_for_is_802");
+    }
+
+    #[test]
+    #[ignore]
+    fn postgres_storage_set_get_works_for_reopen() {
+        _cleanup("postgres_storage_set_get_works_for_reopen");
+        {
+            _storage("postgres_storage_set_get_works_for_reopen").add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+        }
+        {
+            let storage_type = PostgresStorageType::new();
+            let storage = storage_type.open_storage("postgres_storage_set_get_works_for_reopen", Some(&_config()), Some(&_credentials())).unwrap();
+
+            let record = storage.get(&_type1(), &_id1(), r##"{"retrieveType": false, "retrieveValue": true, "retrieveTags": true}"##).unwrap();
+            assert_eq!(record.value.unwrap(), _value1());
+            assert_eq!(_sort(record.tags.unwrap()), _sort(_tags()));
+
+            let res = storage.get(&_type2(), &_id1(), r##"{"retrieveType": false, "retrieveValue": true, "retrieveTags": true}"##);
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+        }
+        _cleanup("postgres_storage_set_get_works_for_reopen");
+    }
+
+    #[test]
+    #[ignore]
+    fn postgres_storage_delete_works() {
+        _cleanup("postgres_storage_delete_works");
+        {
+            let storage = _storage("postgres_storage_delete_works");
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+
+            storage.delete(&_type1(), &_id1()).unwrap();
+
+            let res = storage.get(&_type1(), &_id1(), r##"{"retrieveType": false, "retrieveValue": true, "retrieveTags": true}"##);
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+            let res = storage.delete(&_type1(), &_id1());
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+        }
+        _cleanup("postgres_storage_delete_works");
+    }
+
+    #[test]
+    #[ignore]
+    fn postgres_storage_get_all_works() {
+        _cleanup("postgres_storage_get_all_works");
+        {
+            let storage = _storage("postgres_storage_get_all_works");
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+            storage.add(&_type2(), &_id2(), &_value2(), &_tags()).unwrap();
+
+            let mut storage_iterator = storage.get_all().unwrap();
+
+            let record = storage_iterator.next().unwrap().unwrap();
+            assert_eq!(record.type_.unwrap(), _type1());
+            assert_eq!(record.value.unwrap(), _value1());
+            assert_eq!(_sort(record.tags.unwrap()), _sort(_tags()));
+
+            let record = storage_iterator.next().unwrap().unwrap();
+            assert_eq!(record.type_.unwrap(), _type2());
+            assert_eq!(record.value.unwrap(), _value2());
+
+            assert!(storage_iterator.next().unwrap().is_none());
+        }
+        _cleanup("postgres_storage_get_all_works");
+    }
+
+    #[test]
+    #[ignore]
+    fn postgres_storage_update_works() {
+        _cleanup("postgres_storage_update_works");
+        {
+            let storage = _storage("postgres_storage_update_works");
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+
+            storage.update(&_type1(), &_id1(), &_value2()).unwrap();
+
+            let record = storage.get(&_type1(), &_id1(), r##"{"retrieveType": false, "retrieveValue": true, "retrieveTags": true}"##).unwrap();
+            assert_eq!(record.value.unwrap(), _value2());
+
+            let res = storage.update(&_type2(), &_id1(), &_value2());
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+        }
+        _cleanup("postgres_storage_update_works");
+    }
+
+    #[test]
+    #[ignore]
+    fn postgres_storage_tags_work() {
+        _cleanup("postgres_storage_tags_work");
+        {
+            let storage = _storage("postgres_storage_tags_work");
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+
+            let tags = vec![Tag::PlainText(vec![1, 5, 8, 1], "New value".to_string()), Tag::Encrypted(vec![1, 1, 1], vec![2, 2, 2])];
+            storage.add_tags(&_type1(), &_id1(), &tags).unwrap();
+
+            let record = storage.get(&_type1(), &_id1(), r##"{"retrieveType": false, "retrieveValue": false, "retrieveTags": true}"##).unwrap();
+            assert_eq!(_sort(record.tags.unwrap()), _sort(vec![
+                Tag::Encrypted(vec![1, 5, 8], vec![3, 5, 6]),
+                Tag::PlainText(vec![1, 5, 8, 1], "New value".to_string()),
+                Tag::Encrypted(vec![1, 1, 1], vec![2, 2, 2]),
+            ]));
+
+            storage.delete_tags(&_type1(), &_id1(), &[TagName::OfEncrypted(vec![1, 5, 8]), TagName::OfPlain(vec![1, 5, 8, 1])]).unwrap();
+
+            let record = storage.get(&_type1(), &_id1(), r##"{"retrieveType": false, "retrieveValue": false, "retrieveTags": true}"##).unwrap();
+            assert_eq!(record.tags.unwrap(), vec![Tag::Encrypted(vec![1, 1, 1], vec![2, 2, 2])]);
+
+            storage.update_tags(&_type1(), &_id1(), &_new_tags()).unwrap();
+
+            let record = storage.get(&_type1(), &_id1(), r##"{"retrieveType": false, "retrieveValue": false, "retrieveTags": true}"##).unwrap();
+            assert_eq!(_sort(record.tags.unwrap()), _sort(_new_tags()));
+
+            let res = storage.add_tags(&_type1(), &_id2(), &_tags());
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+            let res = storage.update_tags(&_type2(), &_id1(), &_tags());
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+            let res = storage.delete_tags(&_type1(), &_id2(), &[TagName::OfEncrypted(vec![1, 5, 8])]);
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+        }
+        _cleanup("postgres_storage_tags_work");
+    }
+
+    #[test]
+    #[ignore]
+    fn postgres_storage_batch_works_for_rollback() {
+        _cleanup("postgres_storage_batch_works_for_rollback");
+        {
+            let storage = _storage("postgres_storage_batch_works_for_rollback");
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+
+            let operations = vec![
+                StorageOperation::Update(_type1(), _id1(), _value2()),
+                StorageOperation::Add(_type1(), _id2(), _value2(), _new_tags()),
+                StorageOperation::Delete(_type2(), _id1()),
+            ];
+            let res = storage.batch(&operations);
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+            let record = storage.get(&_type1(), &_id1(), r##"{"retrieveType": false, "retrieveValue": true, "retrieveTags": true}"##).unwrap();
+            assert_eq!(record.value.unwrap(), _value1());
+
+            let res = storage.get(&_type1(), &_id2(), r##"{"retrieveType": false, "retrieveValue": true, "retrieveTags": true}"##);
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+            storage.batch(&operations[..2]).unwrap();
+
+            let record = storage.get(&_type1(), &_id2(), r##"{"retrieveType": false, "retrieveValue": true, "retrieveTags": true}"##).unwrap();
+            assert_eq!(record.value.unwrap(), _value2());
+            assert_eq!(_sort(record.tags.unwrap()), _sort(_new_tags()));
+        }
+        _cleanup("postgres_storage_batch_works_for_rollback");
+    }
+
+    #[test]
+    #[ignore]
+    fn postgres_storage_search_works() {
+        _cleanup("postgres_storage_search_works");
+        {
+            let storage = _storage("postgres_storage_search_works");
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+            storage.add(&_type1(), &_id2(), &_value2(), &_new_tags()).unwrap();
+
+            let query = language::Operator::Or(vec![
+                language::Operator::Eq(language::TagName::EncryptedTagName(vec![1, 5, 8]), language::TargetValue::Encrypted(vec![3, 5, 6])),
+                language::Operator::Like(language::TagName::PlainTagName(vec![1, 1, 1]), language::TargetValue::Unencrypted("tag_%".to_string())),
+            ]);
+
+            let mut iterator = storage.search(&_type1(), &query, Some(r##"{"retrieveTotalCount": true, "retrieveTags": true}"##)).unwrap();
+            assert_eq!(iterator.get_total_count().unwrap(), Some(2));
+
+            let record = iterator.next().unwrap().unwrap();
+            assert_eq!(record.id, _id1());
+            assert_eq!(_sort(record.tags.unwrap()), _sort(_tags()));
+            assert_eq!(iterator.next().unwrap().unwrap().id, _id2());
+            assert!(iterator.next().unwrap().is_none());
+
+            let query = language::Operator::Not(Box::new(
+                language::Operator::Eq(language::TagName::PlainTagName(vec![1, 5, 8, 1]), language::TargetValue::Unencrypted("Plain value".to_string()))
+            ));
+
+            let mut iterator = storage.search(&_type1(), &query, None).unwrap();
+            assert_eq!(iterator.next().unwrap().unwrap().id, _id2());
+            assert!(iterator.next().unwrap().is_none());
+
+            let mut iterator = storage.search(&_type2(), &query, None).unwrap();
+            assert!(iterator.next().unwrap().is_none());
+        }
+        _cleanup("postgres_storage_search_works");
+    }
+
+    #[test]
+    #[ignore]
+    fn postgres_storage_search_works_for_sort_and_page() {
+        _cleanup("postgres_storage_search_works_for_sort_and_page");
+        {
+            let storage = _storage("postgres_storage_search_works_for_sort_and_page");
+
+            for (i, value) in ["b", "c", "a"].iter().enumerate() {
+                storage.add(&_type1(), &_id(i as u8), &_value1(), &[Tag::PlainText(vec![1, 2, 3], value.to_string())]).unwrap();
+            }
+            storage.add(&_type1(), &_id(3), &_value1(), &[]).unwrap();
+
+            let query = language::Operator::And(vec![]);
+
+            let options = json!({
+                "retrieveTotalCount": true,
+                "sortBy": {"tagName": format!("~{}", base64::encode(&[1, 2, 3])), "order": "desc"},
+            }).to_string();
+            let mut iterator = storage.search(&_type1(), &query, Some(&options)).unwrap();
+            assert_eq!(iterator.get_total_count().unwrap(), Some(4));
+            assert_eq!(iterator.next().unwrap().unwrap().id, _id(1));
+            assert_eq!(iterator.next().unwrap().unwrap().id, _id(0));
+            assert_eq!(iterator.next().unwrap().unwrap().id, _id(2));
+            assert_eq!(iterator.next().unwrap().unwrap().id, _id(3));
+            assert!(iterator.next().unwrap().is_none());
+
+            let options = json!({
+                "retrieveTotalCount": true,
+                "sortBy": {"tagName": format!("~{}", base64::encode(&[1, 2, 3]))},
+                "offset": 1,
+                "limit": 2,
+            }).to_string();
+            let mut iterator = storage.search(&_type1(), &query, Some(&options)).unwrap();
+            assert_eq!(iterator.get_total_count().unwrap(), Some(4));
+            assert_eq!(iterator.next().unwrap().unwrap().id, _id(0));
+            assert_eq!(iterator.next().unwrap().unwrap().id, _id(1));
+            assert!(iterator.next().unwrap().is_none());
+        }
+        _cleanup("postgres_storage_search_works_for_sort_and_page");
+    }
+
+    #[test]
+    #[ignore]
+    fn postgres_storage_quarantine_works() {
+        _cleanup("postgres_storage_quarantine_works");
+        {
+            let storage = _storage("postgres_storage_quarantine_works");
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+            storage.add(&_type2(), &_id2(), &_value2(), &_tags()).unwrap();
+
+            storage.quarantine(&_type1(), &_id1()).unwrap();
+
+            let res = storage.get(&_type1(), &_id1(), "{}");
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+            let mut storage_iterator = storage.get_all().unwrap();
+            assert_eq!(storage_iterator.next().unwrap().unwrap().id, _id2());
+            assert!(storage_iterator.next().unwrap().is_none());
+
+            assert_eq!(0, storage.check_orphan_tags(false).unwrap());
+
+            let res = storage.quarantine(&_type1(), &_id1());
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+        }
+        _cleanup("postgres_storage_quarantine_works");
+    }
+
+    #[test]
+    #[ignore]
+    fn postgres_storage_revisions_work() {
+        _cleanup("postgres_storage_revisions_work");
+        {
+            let storage = _storage("postgres_storage_revisions_work");
+            assert_eq!(0, storage.get_revision().unwrap());
+
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+            storage.add(&_type2(), &_id2(), &_value2(), &_tags()).unwrap();
+
+            let revision = storage.get_revision().unwrap();
+            assert!(storage.get_changed(revision).unwrap().next().unwrap().is_none());
+
+            storage.update_tags(&_type1(), &_id1(), &_new_tags()).unwrap();
+            storage.delete(&_type2(), &_id2()).unwrap();
+            assert!(storage.get_revision().unwrap() > revision);
+
+            let mut storage_iterator = storage.get_changed(revision).unwrap();
+
+            let record = storage_iterator.next().unwrap().unwrap();
+            assert_eq!(record.id, _id1());
+            assert_eq!(_sort(record.tags.unwrap()), _sort(_new_tags()));
+            assert!(storage_iterator.next().unwrap().is_none());
+
+            assert_eq!(vec![(_type2(), _id2())], storage.get_deleted(revision).unwrap());
+        }
+        _cleanup("postgres_storage_revisions_work");
+    }
+
+    #[test]
+    #[ignore]
+    fn postgres_storage_expiry_works() {
+        _cleanup("postgres_storage_expiry_works");
+        {
+            let storage = _storage("postgres_storage_expiry_works");
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+            storage.add(&_type1(), &_id2(), &_value2(), &_tags()).unwrap();
+
+            storage.batch(&[
+                StorageOperation::SetExpiry(_type1(), _id1(), Some(1)),
+                StorageOperation::SetExpiry(_type1(), _id2(), Some(unix_time() + 3600)),
+            ]).unwrap();
+
+            let res = storage.get(&_type1(), &_id1(), "{}");
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+            storage.get(&_type1(), &_id2(), "{}").unwrap();
+
+            let mut storage_iterator = storage.search(&_type1(), &language::Operator::And(vec![]), Some(r##"{"retrieveTotalCount": true}"##)).unwrap();
+            assert_eq!(storage_iterator.get_total_count().unwrap(), Some(1));
+            assert_eq!(storage_iterator.next().unwrap().unwrap().id, _id2());
+            assert!(storage_iterator.next().unwrap().is_none());
+
+            // expired item doesn't prevent adding of the item with the same name
+            storage.add(&_type1(), &_id1(), &_value2(), &[]).unwrap();
+            storage.batch(&[StorageOperation::SetExpiry(_type1(), _id1(), Some(1))]).unwrap();
+
+            let revision = storage.get_revision().unwrap();
+            assert_eq!(1, storage.purge_expired().unwrap());
+            assert_eq!(0, storage.purge_expired().unwrap());
+            assert_eq!(vec![(_type1(), _id1())], storage.get_deleted(revision).unwrap());
+
+            let res = storage.batch(&[StorageOperation::SetExpiry(_type1(), _id1(), None)]);
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+        }
+        _cleanup("postgres_storage_expiry_works");
+    }
+
+    #[test]
+    fn postgres_storage_type_parse_works_for_tls() {
+        let (config, _) = PostgresStorageType::_parse(Some(r#"{"url": "localhost:5432"}"#), Some(r#"{"account": "a", "password": "p"}"#)).unwrap();
+        assert_eq!(Tls::None, config.tls);
+
+        let (config, _) = PostgresStorageType::_parse(Some(r#"{"url": "localhost:5432", "tls": "Require"}"#), Some(r#"{"account": "a", "password": "p"}"#)).unwrap();
+        assert_eq!(Tls::Require, config.tls);
+
+        let res = PostgresStorageType::_parse(Some(r#"{"url": "localhost:5432", "tls": "Sometimes"}"#), Some(r#"{"account": "a", "password": "p"}"#));
+        assert_kind!(IndyErrorKind::InvalidStructure, res);
+    }
+
+    #[test]
+    fn postgres_storage_type_create_works_for_missed_credentials() {
+        let storage_type = PostgresStorageType::new();
+
+        let res = storage_type.create_storage("postgres_storage_type_create_works_for_missed_credentials", Some(&_config()), None, &_metadata());
+        assert_kind!(IndyErrorKind::InvalidStructure, res);
+    }
+
+    #[test]
+    #[ignore]
+    fn postgres_storage_type_delete_works() {
+        _cleanup("postgres_storage_type_delete_works");
+
+        let storage_type = PostgresStorageType::new();
+        storage_type.create_storage("postgres_storage_type_delete_works", Some(&_config()), Some(&_credentials()), &_metadata()).unwrap();
+        storage_type.delete_storage("postgres_storage_type_delete_works", Some(&_config()), Some(&_credentials())).unwrap();
+
+        let res = storage_type.open_storage("postgres_storage_type_delete_works", Some(&_config()), Some(&_credentials()));
+        assert_kind!(IndyErrorKind::WalletNotFound, res);
+    }
+
+    #[test]
+    #[ignore]
+    fn postgres_storage_type_open_works_for_existing_revisions() {
+        _cleanup("postgres_storage_type_open_works_for_existing_revisions");
+        {
+            let storage = _storage("postgres_storage_type_open_works_for_existing_revisions");
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+            let revision = storage.get_revision().unwrap();
+
+            // wallet created before revisions sequence was introduced
+            _connect("postgres_storage_type_open_works_for_existing_revisions").execute("DROP SEQUENCE item_revisions_seq", &[]).unwrap();
+
+            let storage_type = PostgresStorageType::new();
+            let storage = storage_type.open_storage("postgres_storage_type_open_works_for_existing_revisions", Some(&_config()), Some(&_credentials())).unwrap();
+
+            storage.add(&_type2(), &_id2(), &_value2(), &_tags()).unwrap();
+            assert!(storage.get_revision().unwrap() > revision);
+            assert_eq!(_id2(), storage.get_changed(revision).unwrap().next().unwrap().unwrap().id);
+        }
+        _cleanup("postgres_storage_type_open_works_for_existing_revisions");
+    }
+
+    #[test]
+    #[ignore]
+    fn postgres_storage_get_works_for_options() {
+        _cleanup("postgres_storage_get_works_for_options");
+        {
+            let storage = _storage("postgres_storage_get_works_for_options");
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+
+            let record = storage.get(&_type1(), &_id1(), r##"{"retrieveType": false, "retrieveValue": false, "retrieveTags": false}"##).unwrap();
+            assert_eq!(record.id, _id1());
+            assert!(record.type_.is_none());
+            assert!(record.value.is_none());
+            assert!(record.tags.is_none());
+
+            let record = storage.get(&_type1(), &_id1(), r##"{"retrieveType": true, "retrieveValue": true, "retrieveTags": false}"##).unwrap();
+            assert_eq!(record.type_.unwrap(), _type1());
+            assert_eq!(record.value.unwrap(), _value1());
+            assert!(record.tags.is_none());
+
+            let res = storage.get(&_type1(), &_id1(), "not json");
+            assert_kind!(IndyErrorKind::InvalidStructure, res);
+        }
+        _cleanup("postgres_storage_get_works_for_options");
+    }
+
+    #[test]
+    #[ignore]
+    fn postgres_storage_add_works_for_empty_tags() {
+        _cleanup("postgres_storage_add_works_for_empty_tags");
+        {
+            let storage = _storage("postgres_storage_add_works_for_empty_tags");
+            storage.add(&_type1(), &_id1(), &_value1(), &[]).unwrap();
+
+            let record = storage.get(&_type1(), &_id1(), r##"{"retrieveType": false, "retrieveValue": true, "retrieveTags": true}"##).unwrap();
+            assert_eq!(record.value.unwrap(), _value1());
+            assert_eq!(record.tags.unwrap(), vec![]);
+        }
+        _cleanup("postgres_storage_add_works_for_empty_tags");
+    }
+
+    #[test]
+    #[ignore]
+    fn postgres_storage_get_all_works_for_empty() {
+        _cleanup("postgres_storage_get_all_works_for_empty");
+        {
+            let storage = _storage("postgres_storage_get_all_works_for_empty");
+
+            let mut storage_iterator = storage.get_all().unwrap();
+            assert!(storage_iterator.next().unwrap().is_none());
+        }
+        _cleanup("postgres_storage_get_all_works_for_empty");
+    }
+
+    #[test]
+    #[ignore]
+    fn postgres_storage_batch_works() {
+        _cleanup("postgres_storage_batch_works");
+        {
+            let storage = _storage("postgres_storage_batch_works");
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+            storage.add(&_type2(), &_id2(), &_value2(), &_tags()).unwrap();
+
+            storage.batch(&[
+                StorageOperation::Update(_type1(), _id1(), _value2()),
+                StorageOperation::AddTags(_type1(), _id1(), vec![Tag::PlainText(vec![9, 9, 9], "added".to_string())]),
+                StorageOperation::DeleteTags(_type1(), _id1(), vec![TagName::OfEncrypted(vec![1, 5, 8])]),
+                StorageOperation::UpdateTags(_type2(), _id2(), _new_tags()),
+                StorageOperation::Delete(_type2(), _id2()),
+                StorageOperation::Add(_type2(), _id2(), _value1(), _new_tags()),
+            ]).unwrap();
+
+            let record = storage.get(&_type1(), &_id1(), r##"{"retrieveType": false, "retrieveValue": true, "retrieveTags": true}"##).unwrap();
+            assert_eq!(record.value.unwrap(), _value2());
+            assert_eq!(_sort(record.tags.unwrap()), _sort(vec![
+                Tag::PlainText(vec![1, 5, 8, 1], "Plain value".to_string()),
+                Tag::PlainText(vec![9, 9, 9], "added".to_string()),
+            ]));
+
+            let record = storage.get(&_type2(), &_id2(), r##"{"retrieveType": false, "retrieveValue": true, "retrieveTags": true}"##).unwrap();
+            assert_eq!(record.value.unwrap(), _value1());
+            assert_eq!(_sort(record.tags.unwrap()), _sort(_new_tags()));
+        }
+        _cleanup("postgres_storage_batch_works");
+    }
+
+    #[test]
+    #[ignore]
+    fn postgres_storage_search_works_for_many_records() {
+        _cleanup("postgres_storage_search_works_for_many_records");
+        {
+            let storage = _storage("postgres_storage_search_works_for_many_records");
+
+            for i in 0..250u8 {
+                storage.add(&_type1(), &[i, 0, 0], &_value1(), &[Tag::PlainText(vec![1, 2, 3], i.to_string())]).unwrap();
+            }
+
+            let query = language::Operator::And(vec![]);
+
+            let mut iterator = storage.search(&_type1(), &query, Some(r##"{"retrieveTotalCount": true, "retrieveTags": true}"##)).unwrap();
+            assert_eq!(iterator.get_total_count().unwrap(), Some(250));
+
+            for i in 0..250u8 {
+                let record = iterator.next().unwrap().unwrap();
+                assert_eq!(record.id, vec![i, 0, 0]);
+                assert_eq!(record.tags.unwrap(), vec![Tag::PlainText(vec![1, 2, 3], i.to_string())]);
+            }
+            assert!(iterator.next().unwrap().is_none());
+
+            let mut iterator = storage.search(&_type1(), &query, Some(r##"{"offset": 20, "limit": 150}"##)).unwrap();
+            assert_eq!(_ids(&mut iterator), (20..170u8).map(|i| vec![i, 0, 0]).collect::<Vec<Vec<u8>>>());
+        }
+        _cleanup("postgres_storage_search_works_for_many_records");
+    }
+
+    #[test]
+    #[ignore]
+    fn postgres_storage_search_works_for_only_count() {
+        _cleanup("postgres_storage_search_works_for_only_count");
+        {
+            let storage = _storage("postgres_storage_search_works_for_only_count");
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+            storage.add(&_type1(), &_id2(), &_value2(), &_new_tags()).unwrap();
+
+            let query = language::Operator::Eq(language::TagName::EncryptedTagName(vec![1, 5, 8]), language::TargetValue::Encrypted(vec![3, 5, 6]));
+
+            let mut iterator = storage.search(&_type1(), &query, Some(r##"{"retrieveRecords": false, "retrieveTotalCount": true}"##)).unwrap();
+            assert_eq!(iterator.get_total_count().unwrap(), Some(1));
+            assert!(iterator.next().unwrap().is_none());
+        }
+        _cleanup("postgres_storage_search_works_for_only_count");
+    }
+
+    #[test]
+    #[ignore]
+    fn postgres_storage_search_works_for_options() {
+        _cleanup("postgres_storage_search_works_for_options");
+        {
+            let storage = _storage("postgres_storage_search_works_for_options");
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+
+            let query = language::Operator::And(vec![]);
+
+            let mut iterator = storage.search(&_type1(), &query, Some(r##"{"retrieveType": true, "retrieveValue": true, "retrieveTags": true}"##)).unwrap();
+            let record = iterator.next().unwrap().unwrap();
+            assert_eq!(record.type_.unwrap(), _type1());
+            assert_eq!(record.value.unwrap(), _value1());
+            assert_eq!(_sort(record.tags.unwrap()), _sort(_tags()));
+
+            let mut iterator = storage.search(&_type1(), &query, Some(r##"{"retrieveValue": false}"##)).unwrap();
+            let record = iterator.next().unwrap().unwrap();
+            assert!(record.type_.is_none());
+            assert!(record.value.is_none());
+            assert!(record.tags.is_none());
+
+            let res = storage.search(&_type1(), &query, Some("not json"));
+            assert_kind!(IndyErrorKind::InvalidStructure, res);
+        }
+        _cleanup("postgres_storage_search_works_for_options");
+    }
+
+    #[test]
+    #[ignore]
+    fn postgres_storage_count_by_tag_works() {
+        _cleanup("postgres_storage_count_by_tag_works");
+        {
+            let storage = _storage("postgres_storage_count_by_tag_works");
+            for (i, value) in ["b", "a", "b"].iter().enumerate() {
+                storage.add(&_type1(), &_id(i as u8), &_value1(), &[
+                    Tag::PlainText(vec![1, 2, 3], value.to_string()),
+                    Tag::Encrypted(vec![4, 5, 6], value.as_bytes().to_vec()),
+                ]).unwrap();
+            }
+            storage.add(&_type1(), &_id(3), &_value1(), &[]).unwrap();
+
+            let query = language::Operator::And(vec![]);
+
+            let counts = storage.count_by_tag(&_type1(), &query, &TagName::OfPlain(vec![1, 2, 3])).unwrap();
+            assert_eq!(vec![(b"a".to_vec(), 1), (b"b".to_vec(), 2)], counts);
+
+            let counts = storage.count_by_tag(&_type1(), &query, &TagName::OfEncrypted(vec![4, 5, 6])).unwrap();
+            assert_eq!(vec![(b"a".to_vec(), 1), (b"b".to_vec(), 2)], counts);
+
+            assert!(storage.count_by_tag(&_type2(), &query, &TagName::OfPlain(vec![1, 2, 3])).unwrap().is_empty());
+        }
+        _cleanup("postgres_storage_count_by_tag_works");
+    }
+
+    #[test]
+    #[ignore]
+    fn postgres_storage_get_distinct_tag_values_works() {
+        _cleanup("postgres_storage_get_distinct_tag_values_works");
+        {
+            let storage = _storage("postgres_storage_get_distinct_tag_values_works");
+            for (i, value) in ["b", "a", "b"].iter().enumerate() {
+                storage.add(&_type1(), &_id(i as u8), &_value1(), &[Tag::PlainText(vec![1, 2, 3], value.to_string())]).unwrap();
+            }
+
+            let query = language::Operator::Eq(language::TagName::PlainTagName(vec![1, 2, 3]), language::TargetValue::Unencrypted("b".to_string()));
+
+            let values = storage.get_distinct_tag_values(&_type1(), &query, &TagName::OfPlain(vec![1, 2, 3])).unwrap();
+            assert_eq!(vec![b"b".to_vec()], values);
+        }
+        _cleanup("postgres_storage_get_distinct_tag_values_works");
+    }
+
+    #[test]
+    #[ignore]
+    fn postgres_storage_check_orphan_tags_works() {
+        _cleanup("postgres_storage_check_orphan_tags_works");
+        {
+            let storage = _storage("postgres_storage_check_orphan_tags_works");
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+
+            assert_eq!(0, storage.check_orphan_tags(false).unwrap());
+            assert_eq!(0, storage.check_orphan_tags(true).unwrap());
+        }
+        _cleanup("postgres_storage_check_orphan_tags_works");
+    }
+
+    #[test]
+    #[ignore]
+    fn postgres_storage_rekey_deleted_works() {
+        _cleanup("postgres_storage_rekey_deleted_works");
+        {
+            let storage = _storage("postgres_storage_rekey_deleted_works");
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+            storage.add(&_type2(), &_id2(), &_value2(), &_tags()).unwrap();
+            storage.delete(&_type1(), &_id1()).unwrap();
+            storage.delete(&_type2(), &_id2()).unwrap();
+            storage.add(&_type1(), &_id(3), &_value1(), &_tags()).unwrap();
+
+            let revision = storage.get_revision().unwrap();
+
+            let marks = vec![
+                ((_type1(), _id1()), (_type1(), _id(3))),
+                ((_type2(), _id2()), (_type2(), _id(4))),
+            ];
+            assert_eq!(1, storage.rekey_deleted(&marks).unwrap());
+
+            assert_eq!(vec![(_type2(), _id(4))], storage.get_deleted(0).unwrap());
+            assert_eq!(revision, storage.get_revision().unwrap());
+        }
+        _cleanup("postgres_storage_rekey_deleted_works");
+    }
+
+    #[test]
+    #[ignore]
+    fn postgres_storage_revisions_work_for_concurrent_writes() {
+        _cleanup("postgres_storage_revisions_work_for_concurrent_writes");
+        {
+            let storage = _storage("postgres_storage_revisions_work_for_concurrent_writes");
+
+            let threads: Vec<_> = (0..4u8)
+                .map(|t| std::thread::spawn(move || {
+                    let storage_type = PostgresStorageType::new();
+                    let storage = storage_type.open_storage("postgres_storage_revisions_work_for_concurrent_writes", Some(&_config()), Some(&_credentials())).unwrap();
+
+                    for i in 0..25u8 {
+                        storage.add(&_type1(), &[t, i], &_value1(), &_tags()).unwrap();
+                    }
+                }))
+                .collect();
+
+            for thread in threads {
+                thread.join().unwrap();
+            }
+
+            let mut storage_iterator = storage.get_changed(0).unwrap();
+            assert_eq!(100, _ids(&mut storage_iterator).len());
+
+            let rows = _connect("postgres_storage_revisions_work_for_concurrent_writes")
+                .query("SELECT COUNT(*) - COUNT(DISTINCT revision) FROM item_revisions", &[])
+                .unwrap();
+            let duplicates: i64 = rows.get(0).get(0);
+            assert_eq!(0, duplicates);
+        }
+        _cleanup("postgres_storage_revisions_work_for_concurrent_writes");
+    }
+
+    fn _cleanup(name: &str) {
+        let storage_type = PostgresStorageType::new();
+        let _ = storage_type.delete_storage(name, Some(&_config()), Some(&_credentials()));
+    }
+
+    fn _storage(name: &str) -> Box<dyn WalletStorage> {
+        let storage_type = PostgresStorageType::new();
+        storage_type.create_storage(name, Some(&_config()), Some(&_credentials()), &_metadata()).unwrap();
+        storage_type.open_storage(name, Some(&_config()), Some(&_credentials())).unwrap()
+    }
+
+    fn _config() -> String {
+        let wallet_scheme = env::var("WALLET_SCHEME").unwrap_or_else(|_| "DatabasePerWallet".to_string());
+
+        json!({
+            "url": env::var("POSTGRES_URL").unwrap_or_else(|_| "localhost:5432".to_string()),
+            "wallet_scheme": wallet_scheme,
+        }).to_string()
+    }
+
+    fn _credentials() -> String {
+        let account = env::var("POSTGRES_ACCOUNT").unwrap_or_else(|_| "postgres".to_string());
+        let password = env::var("POSTGRES_PASSWORD").expect("POSTGRES_PASSWORD environment variable must be set");
+
+        json!({
+            "account": account,
+            "password": password,
+            "admin_account": account,
+            "admin_password": password,
+        }).to_string()
+    }
+
+    fn _connect(name: &str) -> postgres::Connection {
+        let (config, credentials) = PostgresStorageType::_parse(Some(&_config()), Some(&_credentials())).unwrap();
+        PostgresStorageType::_connect(&config, &credentials, config.database_name(name)).unwrap()
+    }
+
+    fn _ids(iterator: &mut Box<dyn StorageIterator>) -> Vec<Vec<u8>> {
+        let mut ids = Vec::new();
+        while let Some(record) = iterator.next().unwrap() {
+            ids.push(record.id);
+        }
+        ids
+    }
+
+    fn _metadata() -> Vec<u8> {
+        vec![1, 2, 3, 4, 5, 6, 7, 8]
+    }
+
+    fn _type(i: u8) -> Vec<u8> {
+        vec![i, 1 + i, 2 + i]
+    }
+
+    fn _type1() -> Vec<u8> {
+        _type(1)
+    }
+
+    fn _type2() -> Vec<u8> {
+        _type(2)
+    }
+
+    fn _id(i: u8) -> Vec<u8> {
+        vec![3 + i, 4 + i, 5 + i]
+    }
+
+    fn _id1() -> Vec<u8> {
+        _id(1)
+    }
+
+    fn _id2() -> Vec<u8> {
+        _id(2)
+    }
+
+    fn _value(i: u8) -> EncryptedValue {
+        EncryptedValue { data: vec![6 + i, 7 + i, 8 + i], key: vec![9 + i, 10 + i, 11 + i] }
+    }
+
+    fn _value1() -> EncryptedValue {
+        _value(1)
+    }
+
+    fn _value2() -> EncryptedValue {
+        _value(2)
+    }
+
+    fn _tags() -> Vec<Tag> {
+        vec![
+            Tag::Encrypted(vec![1, 5, 8], vec![3, 5, 6]),
+            Tag::PlainText(vec![1, 5, 8, 1], "Plain value".to_string())
+        ]
+    }
+
+    fn _new_tags() -> Vec<Tag> {
+        vec![
+            Tag::Encrypted(vec![1, 1, 1], vec![2, 2, 2]),
+            Tag::PlainText(vec![1, 1, 1], String::from("tag_value_3"))
+        ]
+    }
+
+    fn _sort(mut v: Vec<Tag>) -> Vec<Tag> {
+        v.sort();
+        v
+    }
+}
@@ -0,0 +1,449 @@
+use std::cmp;
+
+use postgres::types::ToSql;
+
+use indy_api_types::errors::prelude::*;
+use crate::language::{Operator, TagName, TargetValue};
+use crate::{SearchOptions, SortBy};
+
+// Sorting and paging of search results prepared for binding as query arguments
+#[derive(Clone)]
+pub struct SearchOrder {
+    sort_tag_name: Option<Vec<u8>>,
+    descending: bool,
+    limit: Option<i64>,
+    offset: i64,
+}
+
+impl SearchOrder {
+    pub fn from_options(options: &SearchOptions) -> IndyResult<SearchOrder> {
+        Ok(SearchOrder {
+            sort_tag_name: options.sort_by.as_ref().map(SortBy::encrypted_tag_name).transpose()?,
+            descending: options.sort_by.as_ref().map(SortBy::is_descending).unwrap_or(false),
+            limit: options.limit.map(|limit| limit as i64),
+            offset: options.offset.unwrap_or(0) as i64,
+        })
+    }
+
+    // Order of the next page of at most `size` records after `fetched` records are read,
+    // None if the requested limit is reached
+    pub fn page(&self, fetched: i64, size: i64) -> Option<SearchOrder> {
+        let limit = match self.limit {
+            Some(limit) if limit <= fetched => return None,
+            Some(limit) => cmp::min(limit - fetched, size),
+            None => size,
+        };
+
+        Some(SearchOrder {
+            sort_tag_name: self.sort_tag_name.clone(),
+            descending: self.descending,
+            limit: Some(limit),
+            offset: self.offset + fetched,
+        })
+    }
+
+    fn is_paged(&self) -> bool {
+        self.limit.is_some() || self.offset > 0
+    }
+}
+
+// Conditions on expiry time of item aliased as `i` against current unix time of Postgres
+pub const EXPIRED: &str = "i.expires_at <= EXTRACT(EPOCH FROM now())::BIGINT";
+pub const NOT_EXPIRED: &str = "(i.expires_at IS NULL OR i.expires_at > EXTRACT(EPOCH FROM now())::BIGINT)";
+
+// Tag values that look like numbers, anything else never matches numeric comparisons
+const NUMERIC_VALUE: &str = r"'^[+-]?([0-9]+\.?[0-9]*|\.[0-9]+)([eE][+-]?[0-9]+)?$'";
+
+
+// Translates Wallet Query Language to SQL
+// WQL input is provided as a reference to a top level Operator
+// Wallet id is set for MultiWalletSingleTable scheme and restricts the query to the rows of the wallet
+// Result is a tuple of query string and query arguments
+pub fn wql_to_sql<'a>(class: &'a Vec<u8>, op: &'a Operator, order: &'a SearchOrder, wallet_id: Option<&'a String>) -> IndyResult<(String, Vec<&'a dyn ToSql>)> {
+    let mut arguments: Vec<&dyn ToSql> = Vec::new();
+    arguments.push(class);
+    let clause_string = operator_to_sql(op, &mut arguments)?;
+    let mut query_string = format!("SELECT i.id, i.name, i.value, i.key, i.type FROM items as i WHERE i.type = $$ AND {}", NOT_EXPIRED);
+    if !clause_string.is_empty() {
+        query_string.push_str(" AND ");
+        query_string.push_str(&clause_string);
+    }
+
+    if let Some(wallet_id) = wallet_id {
+        arguments.push(wallet_id);
+        query_string.push_str(" AND i.wallet_id = $$");
+    }
+
+    // records without the tag go last in both directions, item id keeps pages stable
+    if let Some(ref sort_tag_name) = order.sort_tag_name {
+        arguments.push(sort_tag_name);
+        query_string.push_str(&format!(" ORDER BY (SELECT value FROM tags_plaintext WHERE item_id = i.id AND name = $$) {} NULLS LAST, i.id",
+                                       if order.descending { "DESC" } else { "ASC" }));
+    } else if order.is_paged() {
+        query_string.push_str(" ORDER BY i.id");
+    }
+
+    if let Some(ref limit) = order.limit {
+        arguments.push(limit);
+        query_string.push_str(" LIMIT $$");
+    }
+
+    if order.offset > 0 {
+        arguments.push(&order.offset);
+        query_string.push_str(" OFFSET $$");
+    }
+
+    let query_string = scope_to_wallet(convert_query_to_psql_args(&query_string), wallet_id, &mut arguments);
+    Ok((query_string, arguments))
+}
+
+
+pub fn wql_to_sql_count<'a>(class: &'a Vec<u8>, op: &'a Operator, wallet_id: Option<&'a String>) -> IndyResult<(String, Vec<&'a dyn ToSql>)> {
+    let mut arguments: Vec<&dyn ToSql> = Vec::new();
+    arguments.push(class);
+    let clause_string = operator_to_sql(op, &mut arguments)?;
+    let mut query_string = format!("SELECT count(*) FROM items as i WHERE i.type = $$ AND {}", NOT_EXPIRED);
+    if !clause_string.is_empty() {
+        query_string.push_str(" AND ");
+        query_string.push_str(&clause_string);
+    }
+
+    if let Some(wallet_id) = wallet_id {
+        arguments.push(wallet_id);
+        query_string.push_str(" AND i.wallet_id = $$");
+    }
+
+    let query_string = scope_to_wallet(convert_query_to_psql_args(&query_string), wallet_id, &mut arguments);
+    Ok((query_string, arguments))
+}
+
+
+pub fn wql_to_sql_count_by_tag<'a>(class: &'a Vec<u8>, op: &'a Operator, tags_table: &str, tag_name: &'a Vec<u8>, wallet_id: Option<&'a String>) -> IndyResult<(String, Vec<&'a dyn ToSql>)> {
+    let mut arguments: Vec<&dyn ToSql> = Vec::new();
+    arguments.push(tag_name);
+    arguments.push(class);
+    let clause_string = operator_to_sql(op, &mut arguments)?;
+    let mut query_string = format!("SELECT t.value, count(*) FROM items as i INNER JOIN {} as t ON t.item_id = i.id AND t.name = $$ \
+                                    WHERE i.type = $$ AND {}", tags_table, NOT_EXPIRED);
+    if !clause_string.is_empty() {
+        query_string.push_str(" AND ");
+        query_string.push_str(&clause_string);
+    }
+
+    if let Some(wallet_id) = wallet_id {
+        arguments.push(wallet_id);
+        query_string.push_str(" AND i.wallet_id = $$");
+    }
+
+    query_string.push_str(" GROUP BY t.value ORDER BY t.value");
+
+    let query_string = scope_to_wallet(convert_query_to_psql_args(&query_string), wallet_id, &mut arguments);
+    Ok((query_string, arguments))
+}
+
+
+// Tag sub-queries don't know about wallets, so for MultiWalletSingleTable scheme
+// tag tables are shadowed with the rows of the wallet only.
+fn scope_to_wallet<'a>(query_string: String, wallet_id: Option<&'a String>, arguments: &mut Vec<&'a dyn ToSql>) -> String {
+    match wallet_id {
+        Some(wallet_id) => {
+            arguments.push(wallet_id);
+            format!("WITH tags_plaintext AS (SELECT * FROM tags_plaintext WHERE wallet_id = ${0}), \
+                     tags_encrypted AS (SELECT * FROM tags_encrypted WHERE wallet_id = ${0}) {1}", arguments.len(), query_string)
+        }
+        None => query_string
+    }
+}
+
+
+fn convert_query_to_psql_args(query: &str) -> String {
+    let mut index = 1;
+    let mut s: String = query.to_owned();
+    while s.contains("$$") {
+        let arg_str = format!("${}", index);
+        s = s.replacen("$$", &arg_str, 1);
+        index += 1;
+    }
+    s
+}
+
+
+fn operator_to_sql<'a>(op: &'a Operator, arguments: &mut Vec<&'a dyn ToSql>) -> IndyResult<String> {
+    match *op {
+        Operator::Eq(ref tag_name, ref target_value) => eq_to_sql(tag_name, target_value, arguments),
+        Operator::Neq(ref tag_name, ref target_value) => neq_to_sql(tag_name, target_value, arguments),
+        Operator::Gt(ref tag_name, ref target_value) => plain_cmp_to_sql(tag_name, target_value, ">", "$gt", arguments),
+        Operator::Gte(ref tag_name, ref target_value) => plain_cmp_to_sql(tag_name, target_value, ">=", "$gte", arguments),
+        Operator::Lt(ref tag_name, ref target_value) => plain_cmp_to_sql(tag_name, target_value, "<", "$lt", arguments),
+        Operator::Lte(ref tag_name, ref target_value) => plain_cmp_to_sql(tag_name, target_value, "<=", "$lte", arguments),
+        Operator::Like(ref tag_name, ref target_value) => plain_cmp_to_sql(tag_name, target_value, "LIKE", "$like", arguments),
+        Operator::In(ref tag_name, ref target_values) => in_to_sql(tag_name, target_values, arguments),
+        Operator::Exist(ref tag_name) => exist_to_sql(tag_name, arguments),
+        Operator::NumGt(ref tag_name, ref target_value) => num_cmp_to_sql(tag_name, target_value, ">", "$gt", arguments),
+        Operator::NumGte(ref tag_name, ref target_value) => num_cmp_to_sql(tag_name, target_value, ">=", "$gte", arguments),
+        Operator::NumLt(ref tag_name, ref target_value) => num_cmp_to_sql(tag_name, target_value, "<", "$lt", arguments),
+        Operator::NumLte(ref tag_name, ref target_value) => num_cmp_to_sql(tag_name, target_value, "<=", "$lte", arguments),
+        Operator::Prefix(ref tag_name, ref target_value) => prefix_to_sql(tag_name, target_value, arguments),
+        Operator::And(ref suboperators) => and_to_sql(suboperators, arguments),
+        Operator::Or(ref suboperators) => or_to_sql(suboperators, arguments),
+        Operator::Not(ref suboperator) => not_to_sql(suboperator, arguments),
+    }
+}
+
+
+fn eq_to_sql<'a>(name: &'a TagName, value: &'a TargetValue, arguments: &mut Vec<&'a dyn ToSql>) -> IndyResult<String> {
+    match (name, value) {
+        (&TagName::PlainTagName(ref queried_name), &TargetValue::Unencrypted(ref queried_value)) => {
+            arguments.push(queried_name);
+            arguments.push(queried_value);
+            Ok("(i.id in (SELECT item_id FROM tags_plaintext WHERE name = $$ AND value = $$))".to_string())
+        },
+        (&TagName::EncryptedTagName(ref queried_name), &TargetValue::Encrypted(ref queried_value)) => {
+            arguments.push(queried_name);
+            arguments.push(queried_value);
+            Ok("(i.id in (SELECT item_id FROM tags_encrypted WHERE name = $$ AND value = $$))".to_string())
+        },
+        _ => Err(err_msg(IndyErrorKind::WalletQueryError, "Invalid combination of tag name and value for equality operator"))
+    }
+}
+
+
+fn neq_to_sql<'a>(name: &'a TagName, value: &'a TargetValue, arguments: &mut Vec<&'a dyn ToSql>) -> IndyResult<String> {
+    match (name, value) {
+        (&TagName::PlainTagName(ref queried_name), &TargetValue::Unencrypted(ref queried_value)) => {
+            arguments.push(queried_name);
+            arguments.push(queried_value);
+            Ok("(i.id in (SELECT item_id FROM tags_plaintext WHERE name = $$ AND value != $$))".to_string())
+        },
+        (&TagName::EncryptedTagName(ref queried_name), &TargetValue::Encrypted(ref queried_value)) => {
+            arguments.push(queried_name);
+            arguments.push(queried_value);
+            Ok("(i.id in (SELECT item_id FROM tags_encrypted WHERE name = $$ AND value != $$))".to_string())
+        },
+        _ => Err(err_msg(IndyErrorKind::WalletQueryError, "Invalid combination of tag name and value for inequality operator"))
+    }
+}
+
+
+// Comparison operators are supported for plain tags only
+fn plain_cmp_to_sql<'a>(name: &'a TagName, value: &'a TargetValue, sql_op: &str, wql_op: &str, arguments: &mut Vec<&'a dyn ToSql>) -> IndyResult<String> {
+    match (name, value) {
+        (&TagName::PlainTagName(ref queried_name), &TargetValue::Unencrypted(ref queried_value)) => {
+            arguments.push(queried_name);
+            arguments.push(queried_value);
+            Ok(format!("(i.id in (SELECT item_id FROM tags_plaintext WHERE name = $$ AND value {} $$))", sql_op))
+        },
+        _ => Err(err_msg(IndyErrorKind::WalletQueryError, format!("Invalid combination of tag name and value for {} operator", wql_op)))
+    }
+}
+
+
+// Numeric comparisons are supported for plain tags only
+fn num_cmp_to_sql<'a>(name: &'a TagName, value: &'a TargetValue, sql_op: &str, wql_op: &str, arguments: &mut Vec<&'a dyn ToSql>) -> IndyResult<String> {
+    match (name, value) {
+        (&TagName::PlainTagName(ref queried_name), &TargetValue::Unencrypted(ref queried_value)) => {
+            arguments.push(queried_name);
+            arguments.push(queried_value);
+            Ok(format!("(i.id in (SELECT item_id FROM tags_plaintext WHERE name = $$ AND CASE WHEN value ~ {} THEN value::numeric {} $$::text::numeric ELSE false END))",
+                       NUMERIC_VALUE, sql_op))
+        },
+        _ => Err(err_msg(IndyErrorKind::WalletQueryError, format!("Invalid combination of tag name and value for numeric {} operator", wql_op)))
+    }
+}
+
+
+// Prefix is compared as is, LIKE would need escaping of wildcards in the prefix
+fn prefix_to_sql<'a>(name: &'a TagName, value: &'a TargetValue, arguments: &mut Vec<&'a dyn ToSql>) -> IndyResult<String> {
+    match (name, value) {
+        (&TagName::PlainTagName(ref queried_name), &TargetValue::Unencrypted(ref queried_value)) => {
+            arguments.push(queried_name);
+            arguments.push(queried_value);
+            arguments.push(queried_value);
+            Ok("(i.id in (SELECT item_id FROM tags_plaintext WHERE name = $$ AND left(value, char_length($$::text)) = $$))".to_string())
+        },
+        _ => Err(err_msg(IndyErrorKind::WalletQueryError, "Invalid combination of tag name and value for $prefix operator"))
+    }
+}
+
+
+fn exist_to_sql<'a>(name: &'a TagName, arguments: &mut Vec<&'a dyn ToSql>) -> IndyResult<String> {
+    let (table, queried_name) = match *name {
+        TagName::PlainTagName(ref queried_name) => ("tags_plaintext", queried_name),
+        TagName::EncryptedTagName(ref queried_name) => ("tags_encrypted", queried_name),
+    };
+
+    arguments.push(queried_name);
+    Ok(format!("(i.id in (SELECT item_id FROM {} WHERE name = $$))", table))
+}
+
+
+fn in_to_sql<'a>(name: &'a TagName, values: &'a [TargetValue], arguments: &mut Vec<&'a dyn ToSql>) -> IndyResult<String> {
+    let (table, queried_name) = match *name {
+        TagName::PlainTagName(ref queried_name) => ("tags_plaintext", queried_name),
+        TagName::EncryptedTagName(ref queried_name) => ("tags_encrypted", queried_name),
+    };
+
+    arguments.push(queried_name);
+
+    let mut placeholders = Vec::new();
+
+    for value in values {
+        match (name, value) {
+            (&TagName::PlainTagName(_), &TargetValue::Unencrypted(ref target)) => arguments.push(target),
+            (&TagName::EncryptedTagName(_), &TargetValue::Encrypted(ref target)) => arguments.push(target),
+            (&TagName::PlainTagName(_), _) =>
+                return Err(err_msg(IndyErrorKind::WalletQueryError, "Encrypted tag value in $in for nonencrypted tag name")),
+            (&TagName::EncryptedTagName(_), _) =>
+                return Err(err_msg(IndyErrorKind::WalletQueryError, "Unencrypted tag value in $in for encrypted tag name")),
+        }
+        placeholders.push("$$");
+    }
+
+    Ok(format!("(i.id in (SELECT item_id FROM {} WHERE name = $$ AND value IN ({})))", table, placeholders.join(",")))
+}
+
+
+fn and_to_sql<'a>(suboperators: &'a [Operator], arguments: &mut Vec<&'a dyn ToSql>) -> IndyResult<String> {
+    join_operators(suboperators, " AND ", arguments)
+}
+
+
+fn or_to_sql<'a>(suboperators: &'a [Operator], arguments: &mut Vec<&'a dyn ToSql>) -> IndyResult<String> {
+    join_operators(suboperators, " OR ", arguments)
+}
+
+
+fn not_to_sql<'a>(suboperator: &'a Operator, arguments: &mut Vec<&'a dyn ToSql>) -> IndyResult<String> {
+    let suboperator_string = operator_to_sql(suboperator, arguments)?;
+    Ok("NOT (".to_string() + &suboperator_string + ")")
+}
+
+
+fn join_operators<'a>(operators: &'a [Operator], join_str: &str, arguments: &mut Vec<&'a dyn ToSql>) -> IndyResult<String> {
+    let mut s = String::new();
+    if !operators.is_empty() {
+        s.push('(');
+        for (index, operator) in operators.iter().enumerate() {
+            let operator_string = operator_to_sql(operator, arguments)?;
+            s.push_str(&operator_string);
+            if index < operators.len() - 1 {
+                s.push_str(join_str);
+            }
+        }
+        s.push(')');
+    }
+    Ok(s)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simple_and_convert_args_works() {
+        assert_eq!("This $1 is $2 a $3 string!", convert_query_to_psql_args("This $$ is $$ a $$ string!"));
+        assert_eq!("This is a string!", convert_query_to_psql_args("This is a string!"));
+    }
+
+    #[test]
+    fn simple_and() {
+        let condition_1 = Operator::And(vec![
+            Operator::Eq(TagName::EncryptedTagName(vec![1, 2, 3]), TargetValue::Encrypted(vec![4, 5, 6])),
+            Operator::Eq(TagName::PlainTagName(vec![7, 8, 9]), TargetValue::Unencrypted("spam".to_string())),
+        ]);
+        let condition_2 = Operator::And(vec![
+            Operator::Eq(TagName::EncryptedTagName(vec![10, 11, 12]), TargetValue::Encrypted(vec![13, 14, 15])),
+            Operator::Not(Box::new(Operator::Eq(TagName::PlainTagName(vec![16, 17, 18]), TargetValue::Unencrypted("eggs".to_string()))))
+        ]);
+        let query = Operator::Or(vec![condition_1, condition_2]);
+        let class = vec![100, 100, 100];
+        let order = SearchOrder::from_options(&SearchOptions::default()).unwrap();
+        let (query, arguments) = wql_to_sql(&class, &query, &order, None).unwrap();
+        assert_eq!(query, "SELECT i.id, i.name, i.value, i.key, i.type FROM items as i WHERE i.type = $1 AND (i.expires_at IS NULL OR i.expires_at > EXTRACT(EPOCH FROM now())::BIGINT) AND (((i.id in (SELECT item_id FROM tags_encrypted WHERE name = $2 AND value = $3)) AND (i.id in (SELECT item_id FROM tags_plaintext WHERE name = $4 AND value = $5))) OR ((i.id in (SELECT item_id FROM tags_encrypted WHERE name = $6 AND value = $7)) AND NOT ((i.id in (SELECT item_id FROM tags_plaintext WHERE name = $8 AND value = $9)))))");
+        assert_eq!(arguments.len(), 9);
+    }
+
+    #[test]
+    fn sort_and_page() {
+        let query = Operator::Eq(TagName::PlainTagName(vec![7, 8, 9]), TargetValue::Unencrypted("spam".to_string()));
+        let class = vec![100, 100, 100];
+        let options: SearchOptions = serde_json::from_str(r#"{"sortBy": {"tagName": "~AQID", "order": "desc"}, "offset": 10, "limit": 5}"#).unwrap();
+        let order = SearchOrder::from_options(&options).unwrap();
+        let (query, arguments) = wql_to_sql(&class, &query, &order, None).unwrap();
+        assert_eq!(query, "SELECT i.id, i.name, i.value, i.key, i.type FROM items as i WHERE i.type = $1 AND (i.expires_at IS NULL OR i.expires_at > EXTRACT(EPOCH FROM now())::BIGINT) AND (i.id in (SELECT item_id FROM tags_plaintext WHERE name = $2 AND value = $3)) ORDER BY (SELECT value FROM tags_plaintext WHERE item_id = i.id AND name = $4) DESC NULLS LAST, i.id LIMIT $5 OFFSET $6");
+        assert_eq!(arguments.len(), 6);
+    }
+
+    #[test]
+    fn search_order_page_works() {
+        let options: SearchOptions = serde_json::from_str(r#"{"offset": 10, "limit": 150}"#).unwrap();
+        let order = SearchOrder::from_options(&options).unwrap();
+
+        let page = order.page(0, 100).unwrap();
+        assert_eq!((Some(100), 10), (page.limit, page.offset));
+
+        let page = order.page(100, 100).unwrap();
+        assert_eq!((Some(50), 110), (page.limit, page.offset));
+
+        assert!(order.page(150, 100).is_none());
+
+        let order = SearchOrder::from_options(&SearchOptions::default()).unwrap();
+        let page = order.page(200, 100).unwrap();
+        assert_eq!((Some(100), 200), (page.limit, page.offset));
+    }
+
+    #[test]
+    fn scope_to_wallet_works() {
+        let query = Operator::In(TagName::EncryptedTagName(vec![1, 2, 3]), vec![TargetValue::Encrypted(vec![4, 5, 6]), TargetValue::Encrypted(vec![7, 8, 9])]);
+        let class = vec![100, 100, 100];
+        let wallet_id = "wallet".to_string();
+        let (query, arguments) = wql_to_sql_count(&class, &query, Some(&wallet_id)).unwrap();
+        assert_eq!(query, "WITH tags_plaintext AS (SELECT * FROM tags_plaintext WHERE wallet_id = $6), tags_encrypted AS (SELECT * FROM tags_encrypted WHERE wallet_id = $6) SELECT count(*) FROM items as i WHERE i.type = $1 AND (i.expires_at IS NULL OR i.expires_at > EXTRACT(EPOCH FROM now())::BIGINT) AND (i.id in (SELECT item_id FROM tags_encrypted WHERE name = $2 AND value IN ($3,$4))) AND i.wallet_id = $5");
+        assert_eq!(arguments.len(), 6);
+    }
+
+    #[test]
+    fn count_by_tag_works() {
+        let query = Operator::Eq(TagName::PlainTagName(vec![7, 8, 9]), TargetValue::Unencrypted("spam".to_string()));
+        let class = vec![100, 100, 100];
+        let tag_name = vec![1, 2, 3];
+        let (query, arguments) = wql_to_sql_count_by_tag(&class, &query, "tags_encrypted", &tag_name, None).unwrap();
+        assert_eq!(query, "SELECT t.value, count(*) FROM items as i INNER JOIN tags_encrypted as t ON t.item_id = i.id AND t.name = $1 WHERE i.type = $2 AND (i.expires_at IS NULL OR i.expires_at > EXTRACT(EPOCH FROM now())::BIGINT) AND (i.id in (SELECT item_id FROM tags_plaintext WHERE name = $3 AND value = $4)) GROUP BY t.value ORDER BY t.value");
+        assert_eq!(arguments.len(), 4);
+    }
+
+    #[test]
+    fn exist_and_numeric_works() {
+        let query = Operator::And(vec![
+            Operator::Exist(TagName::EncryptedTagName(vec![1, 2, 3])),
+            Operator::NumLte(TagName::PlainTagName(vec![4, 5, 6]), TargetValue::Unencrypted("9.5".to_string())),
+        ]);
+        let class = vec![100, 100, 100];
+        let (query, arguments) = wql_to_sql_count(&class, &query, None).unwrap();
+        assert_eq!(query, format!("SELECT count(*) FROM items as i WHERE i.type = $1 AND (i.expires_at IS NULL OR i.expires_at > EXTRACT(EPOCH FROM now())::BIGINT) AND ((i.id in (SELECT item_id FROM tags_encrypted WHERE name = $2)) AND (i.id in (SELECT item_id FROM tags_plaintext WHERE name = $3 AND CASE WHEN value ~ {} THEN value::numeric <= $4::text::numeric ELSE false END)))", NUMERIC_VALUE));
+        assert_eq!(arguments.len(), 4);
+    }
+
+    #[test]
+    fn numeric_for_encrypted_tag_fails() {
+        let query = Operator::NumGt(TagName::EncryptedTagName(vec![1, 2, 3]), TargetValue::Encrypted(vec![4, 5, 6]));
+        let class = vec![100, 100, 100];
+        assert!(wql_to_sql_count(&class, &query, None).is_err());
+    }
+
+    #[test]
+    fn sort_by_encrypted_tag_fails() {
+        let options: SearchOptions = serde_json::from_str(r#"{"sortBy": {"tagName": "AQID"}}"#).unwrap();
+        assert!(SearchOrder::from_options(&options).is_err());
+    }
+
+    #[test]
+    fn prefix_works() {
+        let query = Operator::Prefix(TagName::PlainTagName(vec![4, 5, 6]), TargetValue::Unencrypted("ab".to_string()));
+        let class = vec![100, 100, 100];
+        let (query, arguments) = wql_to_sql_count(&class, &query, None).unwrap();
+        assert_eq!(query, "SELECT count(*) FROM items as i WHERE i.type = $1 AND (i.expires_at IS NULL OR i.expires_at > EXTRACT(EPOCH FROM now())::BIGINT) AND (i.id in (SELECT item_id FROM tags_plaintext WHERE name = $2 AND left(value, char_length($3::text)) = $4))");
+        assert_eq!(arguments.len(), 4);
+    }
+}
@@ -0,0 +1,159 @@
+use indy_api_types::errors::prelude::*;
+use crate::language;
+
+use super::{EncryptedValue, StorageIterator, StorageOperation, StorageRecord, Tag, TagName, WalletStorage};
+
+///
+/// Wraps the storage of the wallet opened in read-only mode and rejects all calls
+/// that would mutate the wallet, so storage types without dedicated read-only
+/// mode behave the same way as the ones having it.
+///
+pub struct ReadOnlyStorage {
+    storage: Box<dyn WalletStorage>,
+}
+
+impl ReadOnlyStorage {
+    pub fn new(storage: Box<dyn WalletStorage>) -> ReadOnlyStorage {
+        ReadOnlyStorage { storage }
+    }
+
+    fn _read_only<T>() -> IndyResult<T> {
+        Err(err_msg(IndyErrorKind::WalletReadOnly, "Wallet is opened in read-only mode"))
+    }
+}
+
+impl WalletStorage for ReadOnlyStorage {
+    fn get(&self, type_: &[u8], id: &[u8], options: &str) -> IndyResult<StorageRecord> {
+        self.storage.get(type_, id, options)
+    }
+
+    fn add(&self, _type_: &[u8], _id: &[u8], _value: &EncryptedValue, _tags: &[Tag]) -> IndyResult<()> {
+        ReadOnlyStorage::_read_only()
+    }
+
+    fn update(&self, _type_: &[u8], _id: &[u8], _value: &EncryptedValue) -> IndyResult<()> {
+        ReadOnlyStorage::_read_only()
+    }
+
+    fn add_tags(&self, _type_: &[u8], _id: &[u8], _tags: &[Tag]) -> IndyResult<()> {
+        ReadOnlyStorage::_read_only()
+    }
+
+    fn update_tags(&self, _type_: &[u8], _id: &[u8], _tags: &[Tag]) -> IndyResult<()> {
+        ReadOnlyStorage::_read_only()
+    }
+
+    fn delete_tags(&self, _type_: &[u8], _id: &[u8], _tag_names: &[TagName]) -> IndyResult<()> {
+        ReadOnlyStorage::_read_only()
+    }
+
+    fn delete(&self, _type_: &[u8], _id: &[u8]) -> IndyResult<()> {
+        ReadOnlyStorage::_read_only()
+    }
+
+    fn batch(&self, _operations: &[StorageOperation]) -> IndyResult<()> {
+        ReadOnlyStorage::_read_only()
+    }
+
+    fn get_storage_metadata(&self) -> IndyResult<Vec<u8>> {
+        self.storage.get_storage_metadata()
+    }
+
+    fn set_storage_metadata(&self, _metadata: &[u8]) -> IndyResult<()> {
+        ReadOnlyStorage::_read_only()
+    }
+
+    fn get_all(&self) -> IndyResult<Box<dyn StorageIterator>> {
+        self.storage.get_all()
+    }
+
+    fn get_revision(&self) -> IndyResult<u64> {
+        self.storage.get_revision()
+    }
+
+    fn get_changed(&self, since_revision: u64) -> IndyResult<Box<dyn StorageIterator>> {
+        self.storage.get_changed(since_revision)
+    }
+
+    fn get_deleted(&self, since_revision: u64) -> IndyResult<Vec<(Vec<u8>, Vec<u8>)>> {
+        self.storage.get_deleted(since_revision)
+    }
+
+    fn rekey_deleted(&self, _marks: &[((Vec<u8>, Vec<u8>), (Vec<u8>, Vec<u8>))]) -> IndyResult<usize> {
+        ReadOnlyStorage::_read_only()
+    }
+
+    fn check_orphan_tags(&self, repair: bool) -> IndyResult<usize> {
+        if repair {
+            return ReadOnlyStorage::_read_only();
+        }
+
+        self.storage.check_orphan_tags(false)
+    }
+
+    fn quarantine(&self, _type_: &[u8], _id: &[u8]) -> IndyResult<()> {
+        ReadOnlyStorage::_read_only()
+    }
+
+    fn purge_expired(&self) -> IndyResult<usize> {
+        ReadOnlyStorage::_read_only()
+    }
+
+    fn search(&self, type_: &[u8], query: &language::Operator, options: Option<&str>) -> IndyResult<Box<dyn StorageIterator>> {
+        self.storage.search(type_, query, options)
+    }
+
+    fn count_by_tag(&self, type_: &[u8], query: &language::Operator, tag_name: &TagName) -> IndyResult<Vec<(Vec<u8>, usize)>> {
+        self.storage.count_by_tag(type_, query, tag_name)
+    }
+
+    fn get_distinct_tag_values(&self, type_: &[u8], query: &language::Operator, tag_name: &TagName) -> IndyResult<Vec<Vec<u8>>> {
+        self.storage.get_distinct_tag_values(type_, query, tag_name)
+    }
+
+    fn close(&mut self) -> IndyResult<()> {
+        self.storage.close()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::WalletStorageType;
+    use super::super::inmem::InmemStorageType;
+
+    #[test]
+    fn read_only_storage_rejects_mutations() {
+        let storage_type = InmemStorageType::new();
+        storage_type.create_storage("read_only_storage_rejects_mutations", None, None, &[1, 2, 3]).unwrap();
+
+        {
+            let storage = storage_type.open_storage("read_only_storage_rejects_mutations", None, None).unwrap();
+            storage.add(&[1], &[2], &_value(), &[]).unwrap();
+        }
+
+        let storage = ReadOnlyStorage::new(storage_type.open_storage("read_only_storage_rejects_mutations", None, None).unwrap());
+
+        assert_eq!(storage.get(&[1], &[2], r##"{"retrieveValue": true}"##).unwrap().value.unwrap(), _value());
+        assert_eq!(storage.get_storage_metadata().unwrap(), vec![1, 2, 3]);
+        assert_eq!(0, storage.check_orphan_tags(false).unwrap());
+
+        assert_kind!(IndyErrorKind::WalletReadOnly, storage.add(&[1], &[3], &_value(), &[]));
+        assert_kind!(IndyErrorKind::WalletReadOnly, storage.update(&[1], &[2], &_value()));
+        assert_kind!(IndyErrorKind::WalletReadOnly, storage.add_tags(&[1], &[2], &[]));
+        assert_kind!(IndyErrorKind::WalletReadOnly, storage.update_tags(&[1], &[2], &[]));
+        assert_kind!(IndyErrorKind::WalletReadOnly, storage.delete_tags(&[1], &[2], &[]));
+        assert_kind!(IndyErrorKind::WalletReadOnly, storage.delete(&[1], &[2]));
+        assert_kind!(IndyErrorKind::WalletReadOnly, storage.batch(&[StorageOperation::Delete(vec![1], vec![2])]));
+        assert_kind!(IndyErrorKind::WalletReadOnly, storage.set_storage_metadata(&[4, 5, 6]));
+        assert_kind!(IndyErrorKind::WalletReadOnly, storage.check_orphan_tags(true));
+        assert_kind!(IndyErrorKind::WalletReadOnly, storage.quarantine(&[1], &[2]));
+        assert_kind!(IndyErrorKind::WalletReadOnly, storage.purge_expired());
+
+        assert!(storage.get(&[1], &[2], "{}").is_ok());
+    }
+
+    fn _value() -> EncryptedValue {
+        EncryptedValue { data: vec![6, 7, 8], key: vec![9, 10, 11] }
+    }
+}
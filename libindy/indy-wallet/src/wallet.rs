@@ -1,18 +1,19 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::rc::Rc;
 
-use indy_utils::crypto::{hmacsha256, chacha20poly1305_ietf};
+use indy_utils::crypto::{base64, hmacsha256, chacha20poly1305_ietf};
 use indy_utils::wql::Query;
 
 use indy_api_types::errors::prelude::*;
 
 use zeroize::Zeroize;
 
+use super::language;
 use super::storage;
 use super::iterator::WalletIterator;
 use super::encryption::*;
 use super::query_encryption::encrypt_query;
-use super::WalletRecord;
+use super::{SearchOptions, WalletCheckProblem, WalletCheckProblemKind, WalletCheckReport, WalletOperation, WalletRecord};
 
 #[derive(Serialize, Deserialize)]
 pub(super) struct Keys {
@@ -112,30 +113,90 @@ impl EncryptedValue {
     }
 }
 
+/// Count of records re-encrypted within a single storage batch during keys rotation
+pub(crate) const KEYS_ROTATION_CHUNK_SIZE: usize = 100;
+
+/// Count of records reindexed within a single storage batch during prefix indexes rebuild
+pub(crate) const PREFIX_INDEXES_REBUILD_CHUNK_SIZE: usize = 100;
+
+///
+/// Types and ids of records pending for chunked processing. Records are read from storage
+/// lazily, so only ids of a single chunk are kept in memory.
+///
+pub(super) struct PendingRecords {
+    records: Box<dyn storage::StorageIterator>,
+    // records which names can be decrypted with this key are already processed
+    processed_name_key: Option<chacha20poly1305_ietf::Key>,
+}
+
+impl PendingRecords {
+    ///
+    /// Returns types and ids of up to `size` next pending records.
+    /// Empty result means that all pending records are returned.
+    ///
+    pub fn next_chunk(&mut self, size: usize) -> IndyResult<Vec<(Vec<u8>, Vec<u8>)>> {
+        let mut chunk = Vec::with_capacity(size);
+
+        while chunk.len() < size {
+            let record = match self.records.next()? {
+                Some(record) => record,
+                None => break
+            };
+
+            if let Some(ref name_key) = self.processed_name_key {
+                if decrypt_merged(&record.id, name_key).is_ok() {
+                    continue;
+                }
+            }
+
+            let type_ = record.type_
+                .ok_or_else(|| err_msg(IndyErrorKind::InvalidState, "Record type is missed in storage record"))?;
+
+            chunk.push((type_, record.id));
+        }
+
+        Ok(chunk)
+    }
+}
+
 pub(super) struct Wallet {
     id: String,
     storage: Box<dyn storage::WalletStorage>,
     keys: Rc<Keys>,
+    read_only: bool,
+    prefix_indexes: HashMap<String, usize>,
 }
 
 impl Wallet {
     pub fn new(id: String, storage: Box<dyn storage::WalletStorage>, keys: Rc<Keys>) -> Wallet {
-        Wallet { id, storage, keys }
+        Wallet { id, storage, keys, read_only: false, prefix_indexes: HashMap::new() }
+    }
+
+    pub fn new_read_only(id: String, storage: Box<dyn storage::WalletStorage>, keys: Rc<Keys>) -> Wallet {
+        Wallet { id, storage, keys, read_only: true, prefix_indexes: HashMap::new() }
     }
 
-    pub fn add(&self, type_: &str, name: &str, value: &str, tags: &HashMap<String, String>) -> IndyResult<()> {
+    pub fn add(&self, type_: &str, name: &str, value: &str, tags: &HashMap<String, String>, expires_at: Option<u64>) -> IndyResult<()> {
         let etype = encrypt_as_searchable(type_.as_bytes(), &self.keys.type_key, &self.keys.item_hmac_key);
         let ename = encrypt_as_searchable(name.as_bytes(), &self.keys.name_key, &self.keys.item_hmac_key);
         let evalue = EncryptedValue::encrypt(value, &self.keys.value_key);
-        let etags = encrypt_tags(tags, &self.keys.tag_name_key, &self.keys.tag_value_key, &self.keys.tags_hmac_key);
-        self.storage.add(&etype, &ename, &evalue, &etags)?;
+        let etags = self._encrypt_tags(tags, &self.keys);
+
+        match expires_at {
+            Some(expires_at) => self.storage.batch(&[
+                storage::StorageOperation::Add(etype.clone(), ename.clone(), evalue, etags),
+                storage::StorageOperation::SetExpiry(etype, ename, Some(expires_at)),
+            ])?,
+            None => self.storage.add(&etype, &ename, &evalue, &etags)?,
+        }
+
         Ok(())
     }
 
     pub fn add_tags(&self, type_: &str, name: &str, tags: &HashMap<String, String>) -> IndyResult<()> {
         let encrypted_type = encrypt_as_searchable(type_.as_bytes(), &self.keys.type_key, &self.keys.item_hmac_key);
         let encrypted_name = encrypt_as_searchable(name.as_bytes(), &self.keys.name_key, &self.keys.item_hmac_key);
-        let encrypted_tags = encrypt_tags(tags, &self.keys.tag_name_key, &self.keys.tag_value_key, &self.keys.tags_hmac_key);
+        let encrypted_tags = self._encrypt_tags(tags, &self.keys);
         self.storage.add_tags(&encrypted_type, &encrypted_name, &encrypted_tags)?;
         Ok(())
     }
@@ -143,7 +204,7 @@ impl Wallet {
     pub fn update_tags(&self, type_: &str, name: &str, tags: &HashMap<String, String>) -> IndyResult<()> {
         let encrypted_type = encrypt_as_searchable(type_.as_bytes(), &self.keys.type_key, &self.keys.item_hmac_key);
         let encrypted_name = encrypt_as_searchable(name.as_bytes(), &self.keys.name_key, &self.keys.item_hmac_key);
-        let encrypted_tags = encrypt_tags(tags, &self.keys.tag_name_key, &self.keys.tag_value_key, &self.keys.tags_hmac_key);
+        let encrypted_tags = self._encrypt_tags(tags, &self.keys);
         self.storage.update_tags(&encrypted_type, &encrypted_name, &encrypted_tags)?;
         Ok(())
     }
@@ -151,7 +212,7 @@ impl Wallet {
     pub fn delete_tags(&self, type_: &str, name: &str, tag_names: &[&str]) -> IndyResult<()> {
         let encrypted_type = encrypt_as_searchable(type_.as_bytes(), &self.keys.type_key, &self.keys.item_hmac_key);
         let encrypted_name = encrypt_as_searchable(name.as_bytes(), &self.keys.name_key, &self.keys.item_hmac_key);
-        let encrypted_tag_names = encrypt_tag_names(tag_names, &self.keys.tag_name_key, &self.keys.tags_hmac_key);
+        let encrypted_tag_names = self._encrypt_tag_names(tag_names);
         self.storage.delete_tags(&encrypted_type, &encrypted_name, &encrypted_tag_names[..])?;
         Ok(())
     }
@@ -177,7 +238,7 @@ impl Wallet {
 
         let tags = decrypt_tags(&result.tags, &self.keys.tag_name_key, &self.keys.tag_value_key)?;
 
-        Ok(WalletRecord::new(String::from(name), result.type_.map(|_| type_.to_string()), value, tags))
+        Ok(WalletRecord::new(String::from(name), result.type_.map(|_| type_.to_string()), value, tags).with_expiry(result.expires_at))
     }
 
     pub fn delete(&self, type_: &str, name: &str) -> IndyResult<()> {
@@ -188,19 +249,158 @@ impl Wallet {
         Ok(())
     }
 
+    pub fn batch(&self, operations: &[WalletOperation]) -> IndyResult<()> {
+        let mut transaction = self.storage.begin();
+
+        for operation in operations {
+            transaction.stage(self._encrypt_operation(operation));
+
+            // expiry time is set by separate storage operation as it is done by `add`
+            if let WalletOperation::Add { ref type_, ref id, expires_at: Some(expires_at), .. } = *operation {
+                let etype = encrypt_as_searchable(type_.as_bytes(), &self.keys.type_key, &self.keys.item_hmac_key);
+                transaction.stage(storage::StorageOperation::SetExpiry(etype, self._encrypt_name(id), Some(expires_at)));
+            }
+        }
+
+        self.storage.commit(transaction)?;
+        Ok(())
+    }
+
+    fn _encrypt_operation(&self, operation: &WalletOperation) -> storage::StorageOperation {
+        let etype = encrypt_as_searchable(operation.get_type().as_bytes(), &self.keys.type_key, &self.keys.item_hmac_key);
+
+        match *operation {
+            WalletOperation::Add { ref id, ref value, ref tags, .. } =>
+                storage::StorageOperation::Add(etype,
+                                               self._encrypt_name(id),
+                                               EncryptedValue::encrypt(value, &self.keys.value_key),
+                                               tags.as_ref().map(|tags| self._encrypt_tags(tags, &self.keys)).unwrap_or_default()),
+            WalletOperation::UpdateValue { ref id, ref value, .. } =>
+                storage::StorageOperation::Update(etype, self._encrypt_name(id), EncryptedValue::encrypt(value, &self.keys.value_key)),
+            WalletOperation::AddTags { ref id, ref tags, .. } =>
+                storage::StorageOperation::AddTags(etype, self._encrypt_name(id), self._encrypt_tags(tags, &self.keys)),
+            WalletOperation::UpdateTags { ref id, ref tags, .. } =>
+                storage::StorageOperation::UpdateTags(etype, self._encrypt_name(id), self._encrypt_tags(tags, &self.keys)),
+            WalletOperation::DeleteTags { ref id, ref tag_names, .. } => {
+                let tag_names = tag_names.iter().map(String::as_str).collect::<Vec<&str>>();
+                storage::StorageOperation::DeleteTags(etype, self._encrypt_name(id), self._encrypt_tag_names(&tag_names))
+            }
+            WalletOperation::Delete { ref id, .. } =>
+                storage::StorageOperation::Delete(etype, self._encrypt_name(id)),
+        }
+    }
+
+    fn _encrypt_name(&self, name: &str) -> Vec<u8> {
+        encrypt_as_searchable(name.as_bytes(), &self.keys.name_key, &self.keys.item_hmac_key)
+    }
+
+    // Encrypts tags with given keys adding blind prefix indexes of indexed tags
+    fn _encrypt_tags(&self, tags: &HashMap<String, String>, keys: &Keys) -> Vec<storage::Tag> {
+        let mut etags = encrypt_tags(tags, &keys.tag_name_key, &keys.tag_value_key, &keys.tags_hmac_key);
+        etags.extend(encrypt_tags(&prefix_index_tags(tags, &self.prefix_indexes), &keys.tag_name_key, &keys.tag_value_key, &keys.tags_hmac_key));
+        etags
+    }
+
+    // Encrypts tag names adding names of blind prefix indexes of indexed tags, so indexes are deleted with tags
+    fn _encrypt_tag_names(&self, tag_names: &[&str]) -> Vec<storage::TagName> {
+        let index_tag_names = prefix_index_tag_names(tag_names, &self.prefix_indexes);
+
+        let mut names = tag_names.to_vec();
+        names.extend(index_tag_names.iter().map(String::as_str));
+
+        encrypt_tag_names(&names, &self.keys.tag_name_key, &self.keys.tags_hmac_key)
+    }
+
+    fn _encrypt_search_options(&self, options: Option<&str>) -> IndyResult<Option<String>> {
+        let mut options: SearchOptions = match options {
+            Some(options) => ::serde_json::from_str(options)
+                .to_indy(IndyErrorKind::InvalidStructure, "Search options is malformed json")?,
+            None => return Ok(None)
+        };
+
+        if let Some(ref mut sort_by) = options.sort_by {
+            if !sort_by.tag_name.starts_with('~') || sort_by.tag_name.len() == 1 {
+                return Err(err_msg(IndyErrorKind::WalletQueryError,
+                                   format!("Only un-encrypted tags can be used for sorting, got: {}", sort_by.tag_name)));
+            }
+
+            let encrypted_name = encrypt_as_searchable(&sort_by.tag_name.as_bytes()[1..], &self.keys.tag_name_key, &self.keys.tags_hmac_key);
+            sort_by.tag_name = format!("~{}", base64::encode(&encrypted_name));
+        }
+
+        Ok(Some(::serde_json::to_string(&options)
+            .to_indy(IndyErrorKind::InvalidState, "Cannot serialize SearchOptions")?))
+    }
+
     pub fn search<'a>(&'a self, type_: &str, query: &str, options: Option<&str>) -> IndyResult<WalletIterator> {
         let parsed_query: Query = ::serde_json::from_str::<Query>(query)
             .map_err(|err| IndyError::from_msg(IndyErrorKind::WalletQueryError, err))?
             .optimise()
             .unwrap_or_default();
 
-        let encrypted_query = encrypt_query(parsed_query, &self.keys)?;
+        let encrypted_query = encrypt_query(parsed_query, &self.keys, &self.prefix_indexes)?;
         let encrypted_type_ = encrypt_as_searchable(type_.as_bytes(), &self.keys.type_key, &self.keys.item_hmac_key);
-        let storage_iterator = self.storage.search(&encrypted_type_, &encrypted_query, options)?;
+        let encrypted_options = self._encrypt_search_options(options)?;
+        let storage_iterator = self.storage.search(&encrypted_type_, &encrypted_query, encrypted_options.as_ref().map(String::as_str))?;
         let wallet_iterator = WalletIterator::new(storage_iterator, Rc::clone(&self.keys));
         Ok(wallet_iterator)
     }
 
+    pub fn count_by_tag(&self, type_: &str, query: &str, tag_name: &str) -> IndyResult<BTreeMap<String, usize>> {
+        let (encrypted_type_, encrypted_query, encrypted_tag_name) = self._encrypt_tag_aggregation(type_, query, tag_name)?;
+
+        let counts = self.storage.count_by_tag(&encrypted_type_, &encrypted_query, &encrypted_tag_name)?;
+
+        let mut res = BTreeMap::new();
+
+        for (value, count) in counts {
+            let value = self._decrypt_tag_value(&encrypted_tag_name, value)?;
+            *res.entry(value).or_insert(0) += count;
+        }
+
+        Ok(res)
+    }
+
+    pub fn get_distinct_tag_values(&self, type_: &str, query: &str, tag_name: &str) -> IndyResult<Vec<String>> {
+        let (encrypted_type_, encrypted_query, encrypted_tag_name) = self._encrypt_tag_aggregation(type_, query, tag_name)?;
+
+        let values = self.storage.get_distinct_tag_values(&encrypted_type_, &encrypted_query, &encrypted_tag_name)?;
+
+        let mut res = values.into_iter()
+            .map(|value| self._decrypt_tag_value(&encrypted_tag_name, value))
+            .collect::<IndyResult<Vec<String>>>()?;
+
+        // encrypted values are ordered by ciphertext in the storage
+        res.sort();
+        Ok(res)
+    }
+
+    fn _encrypt_tag_aggregation(&self, type_: &str, query: &str, tag_name: &str) -> IndyResult<(Vec<u8>, language::Operator, storage::TagName)> {
+        if tag_name.is_empty() || tag_name == "~" {
+            return Err(err_msg(IndyErrorKind::InvalidStructure, "Tag name is empty"));
+        }
+
+        let parsed_query: Query = ::serde_json::from_str::<Query>(query)
+            .map_err(|err| IndyError::from_msg(IndyErrorKind::WalletQueryError, err))?
+            .optimise()
+            .unwrap_or_default();
+
+        let encrypted_query = encrypt_query(parsed_query, &self.keys, &self.prefix_indexes)?;
+        let encrypted_type_ = encrypt_as_searchable(type_.as_bytes(), &self.keys.type_key, &self.keys.item_hmac_key);
+        let encrypted_tag_name = encrypt_tag_names(&[tag_name], &self.keys.tag_name_key, &self.keys.tags_hmac_key).remove(0);
+
+        Ok((encrypted_type_, encrypted_query, encrypted_tag_name))
+    }
+
+    fn _decrypt_tag_value(&self, tag_name: &storage::TagName, value: Vec<u8>) -> IndyResult<String> {
+        let value = match *tag_name {
+            storage::TagName::OfPlain(_) => value,
+            storage::TagName::OfEncrypted(_) => decrypt_merged(&value, &self.keys.tag_value_key)?,
+        };
+
+        String::from_utf8(value).to_indy(IndyErrorKind::WalletEncryptionError, "Tag value is invalid utf8")
+    }
+
     pub fn close(&mut self) -> IndyResult<()> {
         self.storage.close()
             .map_err(IndyError::from)
@@ -211,9 +411,271 @@ impl Wallet {
         Ok(WalletIterator::new(all_items, Rc::clone(&self.keys)))
     }
 
+    pub fn get_revision(&self) -> IndyResult<u64> {
+        self.storage.get_revision()
+    }
+
+    pub fn purge_expired(&self) -> IndyResult<usize> {
+        self.storage.purge_expired()
+    }
+
+    pub fn get_changed(&self, since_revision: u64) -> IndyResult<WalletIterator> {
+        let changed_items = self.storage.get_changed(since_revision)?;
+        Ok(WalletIterator::new(changed_items, Rc::clone(&self.keys)))
+    }
+
+    ///
+    /// Returns types and ids of records deleted after the given revision.
+    ///
+    pub fn get_deleted(&self, since_revision: u64) -> IndyResult<Vec<(String, String)>> {
+        let mut deleted = Vec::new();
+
+        for (etype, ename) in self.storage.get_deleted(since_revision)? {
+            let type_ = decrypt_merged(&etype, &self.keys.type_key)
+                .to_indy(IndyErrorKind::WalletEncryptionError, "Unable to decrypt type of deleted record")?;
+            let name = decrypt_merged(&ename, &self.keys.name_key)
+                .to_indy(IndyErrorKind::WalletEncryptionError, "Unable to decrypt name of deleted record")?;
+
+            let type_ = String::from_utf8(type_)
+                .to_indy(IndyErrorKind::WalletEncryptionError, "Record type is invalid utf8")?;
+
+            let name = String::from_utf8(name)
+                .to_indy(IndyErrorKind::WalletEncryptionError, "Record is invalid utf8")?;
+
+            deleted.push((type_, name));
+        }
+
+        Ok(deleted)
+    }
+
     pub fn get_id<'a>(&'a self) -> &'a str {
         &self.id
     }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    pub fn get_keys(&self) -> &Keys {
+        &self.keys
+    }
+
+    pub fn get_storage_metadata(&self) -> IndyResult<Vec<u8>> {
+        self.storage.get_storage_metadata()
+    }
+
+    pub fn set_storage_metadata(&self, metadata: &[u8]) -> IndyResult<()> {
+        self.storage.set_storage_metadata(metadata)
+    }
+
+    ///
+    /// Returns records that are still encrypted with the current keys.
+    /// Records already re-encrypted are recognized by their names that can be decrypted
+    /// with the new keys, so rotation can be continued after interruption.
+    /// Expired records are hidden from storage reads, so they are skipped.
+    ///
+    pub fn get_keys_rotation_pending(&self, new_keys: &Keys) -> IndyResult<PendingRecords> {
+        Ok(PendingRecords {
+            records: self.storage.get_all()?,
+            processed_name_key: Some(new_keys.name_key.clone()),
+        })
+    }
+
+    ///
+    /// Re-encrypts the given records with the new keys and stores them atomically.
+    /// Records that were removed after they were listed are skipped.
+    ///
+    /// Returns count of re-encrypted records.
+    ///
+    pub fn rotate_keys_chunk(&self, new_keys: &Keys, records: &[(Vec<u8>, Vec<u8>)]) -> IndyResult<usize> {
+        let mut operations: Vec<storage::StorageOperation> = Vec::with_capacity(2 * records.len());
+        let mut rotated = 0;
+
+        for &(ref type_, ref id) in records {
+            let mut record = match self.storage.get(type_, id, r##"{"retrieveType": false, "retrieveValue": true, "retrieveTags": true}"##) {
+                Ok(record) => record,
+                Err(ref err) if err.kind() == IndyErrorKind::WalletItemNotFound => continue,
+                Err(err) => return Err(err)
+            };
+            record.type_ = Some(type_.clone());
+
+            let decrypted = decrypt_storage_record(&record, &self.keys)?;
+
+            let type_name = decrypted.get_type()
+                .ok_or_else(|| err_msg(IndyErrorKind::InvalidState, "Record type is missed in wallet record"))?;
+            let value = decrypted.get_value()
+                .ok_or_else(|| err_msg(IndyErrorKind::InvalidState, "Record value is missed in wallet record"))?;
+
+            let etype = encrypt_as_searchable(type_name.as_bytes(), &new_keys.type_key, &new_keys.item_hmac_key);
+            let ename = encrypt_as_searchable(decrypted.get_id().as_bytes(), &new_keys.name_key, &new_keys.item_hmac_key);
+
+            operations.push(storage::StorageOperation::Delete(type_.clone(), id.clone()));
+            operations.push(storage::StorageOperation::Add(
+                etype.clone(),
+                ename.clone(),
+                EncryptedValue::encrypt(value, &new_keys.value_key),
+                self._encrypt_tags(decrypted.get_tags().unwrap_or(&HashMap::new()), &new_keys),
+            ));
+
+            if let Some(expires_at) = decrypted.get_expires_at() {
+                operations.push(storage::StorageOperation::SetExpiry(etype, ename, Some(expires_at)));
+            }
+
+            rotated += 1;
+        }
+
+        if !operations.is_empty() {
+            self.storage.batch(&operations)?;
+        }
+
+        Ok(rotated)
+    }
+
+    ///
+    /// Returns all records, so their blind prefix indexes can be rebuilt
+    /// for changed prefix indexes config.
+    /// Expired records are hidden from storage reads, so they are skipped.
+    ///
+    pub fn get_prefix_indexes_rebuild_pending(&self) -> IndyResult<PendingRecords> {
+        Ok(PendingRecords {
+            records: self.storage.get_all()?,
+            processed_name_key: None,
+        })
+    }
+
+    ///
+    /// Rewrites all tags of the given records with blind prefix indexes built for the current
+    /// prefix indexes config, so indexes of tags that aren't indexed anymore are removed.
+    /// Rebuild is idempotent, so it can be repeated after interruption.
+    /// Records that were removed after they were listed are skipped.
+    ///
+    /// Returns count of reindexed records.
+    ///
+    pub fn rebuild_prefix_indexes_chunk(&self, records: &[(Vec<u8>, Vec<u8>)]) -> IndyResult<usize> {
+        let mut operations: Vec<storage::StorageOperation> = Vec::with_capacity(records.len());
+
+        for &(ref type_, ref id) in records {
+            let record = match self.storage.get(type_, id, r##"{"retrieveType": false, "retrieveValue": false, "retrieveTags": true}"##) {
+                Ok(record) => record,
+                Err(ref err) if err.kind() == IndyErrorKind::WalletItemNotFound => continue,
+                Err(err) => return Err(err)
+            };
+
+            // stale index tags are hidden on decryption
+            let tags = decrypt_tags(&record.tags, &self.keys.tag_name_key, &self.keys.tag_value_key)?.unwrap_or_default();
+
+            operations.push(storage::StorageOperation::UpdateTags(type_.clone(), id.clone(), self._encrypt_tags(&tags, &self.keys)));
+        }
+
+        if !operations.is_empty() {
+            self.storage.batch(&operations)?;
+        }
+
+        Ok(operations.len())
+    }
+
+    ///
+    /// Moves deletion marks to types and names encrypted with the new keys, so deleted records
+    /// are still reported by `get_deleted` after rotation. Marks left by re-encryption of
+    /// existing records are dropped. Should be called after all records are re-encrypted.
+    ///
+    pub fn rotate_deleted_keys(&self, new_keys: &Keys) -> IndyResult<usize> {
+        let mut marks = Vec::new();
+
+        for (etype, ename) in self.storage.get_deleted(0)? {
+            // marks that can't be decrypted with the current keys are already moved
+            let (type_, name) = match (decrypt_merged(&etype, &self.keys.type_key), decrypt_merged(&ename, &self.keys.name_key)) {
+                (Ok(type_), Ok(name)) => (type_, name),
+                _ => continue
+            };
+
+            marks.push(((etype, ename), (
+                encrypt_as_searchable(&type_, &new_keys.type_key, &new_keys.item_hmac_key),
+                encrypt_as_searchable(&name, &new_keys.name_key, &new_keys.item_hmac_key),
+            )));
+        }
+
+        self.storage.rekey_deleted(&marks)
+    }
+
+    ///
+    /// Checks that every record can be decrypted with the wallet keys, that its value is
+    /// accepted by `validate_value` (called with decrypted type and value) and that tags
+    /// don't reference missing records. In repair mode records that failed the check
+    /// are moved to the storage quarantine and orphan tags are removed.
+    ///
+    pub fn check(&self, repair: bool, validate_value: &dyn Fn(&str, &str) -> Result<(), String>) -> IndyResult<WalletCheckReport> {
+        let mut report = WalletCheckReport::default();
+        let mut bad_records: Vec<(Vec<u8>, Vec<u8>)> = Vec::new();
+
+        {
+            let mut records = self.storage.get_all()?;
+
+            while let Some(record) = records.next()? {
+                report.records += 1;
+
+                if let Err(problem) = self._check_record(&record, validate_value) {
+                    bad_records.push((record.type_.clone().unwrap_or_default(), record.id.clone()));
+                    report.problems.push(problem);
+                }
+            }
+        }
+
+        // bad records are quarantined before orphan tags are removed, so storage that doesn't
+        // support quarantine fails the repair before anything is changed
+        if repair {
+            for ((type_, id), problem) in bad_records.iter().zip(report.problems.iter_mut()) {
+                self.storage.quarantine(type_, id)?;
+                problem.quarantined = true;
+            }
+        }
+
+        report.orphan_tags = self.storage.check_orphan_tags(repair)?;
+        report.repaired = repair;
+
+        Ok(report)
+    }
+
+    fn _check_record(&self, record: &storage::StorageRecord, validate_value: &dyn Fn(&str, &str) -> Result<(), String>) -> Result<(), WalletCheckProblem> {
+        let problem = |type_: Option<&str>, id: Option<&str>, kind: WalletCheckProblemKind, message: &str| WalletCheckProblem {
+            type_: type_.map(String::from),
+            id: id.map(String::from),
+            kind,
+            message: message.to_string(),
+            quarantined: false,
+        };
+
+        let type_ = record.type_.as_ref()
+            .and_then(|type_| decrypt_merged(type_, &self.keys.type_key).ok())
+            .and_then(|type_| String::from_utf8(type_).ok())
+            .ok_or_else(|| problem(None, None, WalletCheckProblemKind::TypeDecryption, "Unable to decrypt record type"))?;
+
+        let id = decrypt_merged(&record.id, &self.keys.name_key).ok()
+            .and_then(|id| String::from_utf8(id).ok())
+            .ok_or_else(|| problem(Some(&type_), None, WalletCheckProblemKind::IdDecryption, "Unable to decrypt record id"))?;
+
+        let value = record.value.as_ref()
+            .and_then(|value| value.decrypt(&self.keys.value_key).ok())
+            .ok_or_else(|| problem(Some(&type_), Some(&id), WalletCheckProblemKind::ValueDecryption, "Unable to decrypt record value"))?;
+
+        decrypt_tags(&record.tags, &self.keys.tag_name_key, &self.keys.tag_value_key)
+            .map_err(|_| problem(Some(&type_), Some(&id), WalletCheckProblemKind::TagsDecryption, "Unable to decrypt record tags"))?;
+
+        validate_value(&type_, &value)
+            .map_err(|err| problem(Some(&type_), Some(&id), WalletCheckProblemKind::InvalidValue, &err))
+    }
+
+    pub fn set_keys(&mut self, keys: Keys) {
+        self.keys = Rc::new(keys);
+    }
+
+    pub fn get_prefix_indexes(&self) -> &HashMap<String, usize> {
+        &self.prefix_indexes
+    }
+
+    pub fn set_prefix_indexes(&mut self, prefix_indexes: HashMap<String, usize>) {
+        self.prefix_indexes = prefix_indexes;
+    }
 }
 
 #[cfg(test)]
@@ -264,7 +726,7 @@ mod tests {
         test::cleanup_wallet("wallet_add_get_works");
         {
             let mut wallet = _wallet("wallet_add_get_works");
-            wallet.add(_type1(), _id1(), _value1(), &_tags()).unwrap();
+            wallet.add(_type1(), _id1(), _value1(), &_tags(), None).unwrap();
 
             let record = wallet.get(_type1(), _id1(), &_fetch_options(false, true, true)).unwrap();
             assert_eq!(record.id, _id1());
@@ -281,7 +743,7 @@ mod tests {
         test::cleanup_wallet("wallet_add_get_works_for_reopen");
         {
             let mut wallet = _wallet("wallet_add_get_works_for_reopen");
-            wallet.add(_type1(), _id1(), _value1(), &_tags()).unwrap();
+            wallet.add(_type1(), _id1(), _value1(), &_tags(), None).unwrap();
             wallet.close().unwrap();
 
             let mut wallet = _exists_wallet("wallet_add_get_works_for_reopen");
@@ -301,7 +763,7 @@ mod tests {
         test::cleanup_wallet("wallet_get_works_for_non_existing");
         {
             let mut wallet = _wallet("wallet_get_works_for_non_existing");
-            wallet.add(_type1(), _id1(), _value1(), &_tags()).unwrap();
+            wallet.add(_type1(), _id1(), _value1(), &_tags(), None).unwrap();
 
             let res = wallet.get(_type1(), _id2(), &_fetch_options(false, true, true));
             assert_kind!(IndyErrorKind::WalletItemNotFound, res);
@@ -316,9 +778,9 @@ mod tests {
         test::cleanup_wallet("wallet_add_works_for_already_existing");
         {
             let mut wallet = _wallet("wallet_add_works_for_already_existing");
-            wallet.add(_type1(), _id1(), _value1(), &_tags()).unwrap();
+            wallet.add(_type1(), _id1(), _value1(), &_tags(), None).unwrap();
 
-            let res = wallet.add(_type1(), _id1(), _value2(), &_tags());
+            let res = wallet.add(_type1(), _id1(), _value2(), &_tags(), None);
             assert_kind!(IndyErrorKind::WalletItemAlreadyExists, res);
 
             wallet.close().unwrap();
@@ -326,12 +788,33 @@ mod tests {
         test::cleanup_wallet("wallet_add_works_for_already_existing");
     }
 
+    #[test]
+    fn wallet_add_works_for_expiry() {
+        test::cleanup_wallet("wallet_add_works_for_expiry");
+        {
+            let mut wallet = _wallet("wallet_add_works_for_expiry");
+            wallet.add(_type1(), _id1(), _value1(), &_tags(), Some(1)).unwrap();
+            wallet.add(_type1(), _id2(), _value2(), &_tags(), Some(u64::max_value() >> 1)).unwrap();
+
+            let res = wallet.get(_type1(), _id1(), &_fetch_options(false, true, true));
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+            let record = wallet.get(_type1(), _id2(), &_fetch_options(false, true, true)).unwrap();
+            assert_eq!(record.value.unwrap(), _value2());
+
+            assert_eq!(1, wallet.purge_expired().unwrap());
+
+            wallet.close().unwrap();
+        }
+        test::cleanup_wallet("wallet_add_works_for_expiry");
+    }
+
     #[test]
     fn wallet_update_works() {
         test::cleanup_wallet("wallet_update_works");
         {
             let mut wallet = _wallet("wallet_update_works");
-            wallet.add(_type1(), _id1(), _value1(), &_tags()).unwrap();
+            wallet.add(_type1(), _id1(), _value1(), &_tags(), None).unwrap();
 
             let record = wallet.get(_type1(), _id1(), &_fetch_options(false, true, true)).unwrap();
             assert_eq!(record.id, _id1());
@@ -355,7 +838,7 @@ mod tests {
         test::cleanup_wallet("wallet_update_works_for_non_existing_id");
         {
             let mut wallet = _wallet("wallet_update_works_for_non_existing_id");
-            wallet.add(_type1(), _id1(), _value1(), &_tags()).unwrap();
+            wallet.add(_type1(), _id1(), _value1(), &_tags(), None).unwrap();
 
             let res = wallet.update(_type1(), _id2(), _value2());
             assert_kind!(IndyErrorKind::WalletItemNotFound, res);
@@ -371,7 +854,7 @@ mod tests {
         {
             let mut wallet = _wallet("wallet_update_works_for_non_existing_type");
 
-            wallet.add(_type1(), _id1(), _value1(), &_tags()).unwrap();
+            wallet.add(_type1(), _id1(), _value1(), &_tags(), None).unwrap();
 
             let res = wallet.update(_type2(), _id1(), _value2());
             assert_kind!(IndyErrorKind::WalletItemNotFound, res);
@@ -394,7 +877,7 @@ mod tests {
              });
 
             let mut wallet = _wallet("wallet_add_tags_works");
-            wallet.add(_type1(), _id1(), _value1(), &tags).unwrap();
+            wallet.add(_type1(), _id1(), _value1(), &tags, None).unwrap();
 
             let new_tags = jsonmap!({
                 "tag_name_2": "tag_value_2",
@@ -427,7 +910,7 @@ mod tests {
              });
 
             let mut wallet = _wallet("wallet_update_tags_works");
-            wallet.add(_type1(), _id1(), _value1(), &tags).unwrap();
+            wallet.add(_type1(), _id1(), _value1(), &tags, None).unwrap();
 
             let new_tags = jsonmap!({
                 "tag_name_2": "tag_value_2",
@@ -456,7 +939,7 @@ mod tests {
              });
 
             let mut wallet = _wallet("wallet_delete_tags_works");
-            wallet.add(_type1(), _id1(), _value1(), &tags).unwrap();
+            wallet.add(_type1(), _id1(), _value1(), &tags, None).unwrap();
 
             wallet.delete_tags(_type1(), _id1(), &vec!["tag_name_1", "~tag_name_3", "tag_name_5", "~tag_name_6"]).unwrap();
 
@@ -478,7 +961,7 @@ mod tests {
         test::cleanup_wallet("wallet_delete_works");
         {
             let mut wallet = _wallet("wallet_delete_works");
-            wallet.add(_type1(), _id1(), _value1(), &_tags()).unwrap();
+            wallet.add(_type1(), _id1(), _value1(), &_tags(), None).unwrap();
 
             let record = wallet.get(_type1(), _id1(), &_fetch_options(false, true, true)).unwrap();
             assert_eq!(record.id, _id1());
@@ -500,7 +983,7 @@ mod tests {
         test::cleanup_wallet("wallet_delete_works_for_non_existing_id");
         {
             let mut wallet = _wallet("wallet_delete_works_for_non_existing_id");
-            wallet.add(_type1(), _id1(), _value1(), &_tags()).unwrap();
+            wallet.add(_type1(), _id1(), _value1(), &_tags(), None).unwrap();
 
             let res = wallet.delete(_type1(), _id2());
             assert_kind!(IndyErrorKind::WalletItemNotFound, res);
@@ -515,7 +998,7 @@ mod tests {
         test::cleanup_wallet("wallet_delete_works_for_non_existing_type");
         {
             let mut wallet = _wallet("wallet_delete_works_for_non_existing_type");
-            wallet.add(_type1(), _id1(), _value1(), &_tags()).unwrap();
+            wallet.add(_type1(), _id1(), _value1(), &_tags(), None).unwrap();
 
             let res = wallet.delete(_type2(), _id1());
             assert_kind!(IndyErrorKind::WalletItemNotFound, res);
@@ -525,6 +1008,75 @@ mod tests {
         test::cleanup_wallet("wallet_delete_works_for_non_existing_type");
     }
 
+    #[test]
+    fn wallet_check_works() {
+        test::cleanup_wallet("wallet_check_works");
+        {
+            let wallet = _wallet("wallet_check_works");
+            wallet.add(_type1(), _id1(), _value1(), &_tags(), None).unwrap();
+            wallet.add(_type2(), _id2(), _value2(), &_tags(), None).unwrap();
+
+            let report = wallet.check(false, &|_, _| Ok(())).unwrap();
+            assert_eq!(2, report.records);
+            assert_eq!(0, report.orphan_tags);
+            assert!(report.problems.is_empty());
+            assert!(!report.repaired);
+        }
+        test::cleanup_wallet("wallet_check_works");
+    }
+
+    #[test]
+    fn wallet_check_works_for_undecryptable_value_and_repair() {
+        test::cleanup_wallet("wallet_check_works_for_undecryptable_value_and_repair");
+        {
+            let wallet = _wallet("wallet_check_works_for_undecryptable_value_and_repair");
+            wallet.add(_type1(), _id1(), _value1(), &_tags(), None).unwrap();
+
+            let keys = Keys::new();
+            wallet.storage.add(&encrypt_as_searchable(_type2().as_bytes(), &wallet.keys.type_key, &wallet.keys.item_hmac_key),
+                               &encrypt_as_searchable(_id2().as_bytes(), &wallet.keys.name_key, &wallet.keys.item_hmac_key),
+                               &EncryptedValue::encrypt(_value2(), &keys.value_key),
+                               &[]).unwrap();
+
+            let report = wallet.check(false, &|_, _| Ok(())).unwrap();
+            assert_eq!(2, report.records);
+            assert_eq!(1, report.problems.len());
+            assert_eq!(WalletCheckProblemKind::ValueDecryption, report.problems[0].kind);
+            assert_eq!(Some(_type2().to_string()), report.problems[0].type_);
+            assert_eq!(Some(_id2().to_string()), report.problems[0].id);
+            assert!(!report.problems[0].quarantined);
+
+            let report = wallet.check(true, &|_, _| Ok(())).unwrap();
+            assert!(report.problems[0].quarantined);
+            assert!(report.repaired);
+
+            let res = wallet.get(_type2(), _id2(), &_fetch_options(false, false, false));
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+            let report = wallet.check(false, &|_, _| Ok(())).unwrap();
+            assert_eq!(1, report.records);
+            assert!(report.problems.is_empty());
+        }
+        test::cleanup_wallet("wallet_check_works_for_undecryptable_value_and_repair");
+    }
+
+    #[test]
+    fn wallet_check_works_for_invalid_value() {
+        test::cleanup_wallet("wallet_check_works_for_invalid_value");
+        {
+            let wallet = _wallet("wallet_check_works_for_invalid_value");
+            wallet.add(_type1(), _id1(), _value1(), &_tags(), None).unwrap();
+            wallet.add(_type2(), _id2(), _value2(), &_tags(), None).unwrap();
+
+            let report = wallet.check(false, &|type_, _| if type_ == _type2() { Err("Invalid value".to_string()) } else { Ok(()) }).unwrap();
+            assert_eq!(1, report.problems.len());
+            assert_eq!(WalletCheckProblemKind::InvalidValue, report.problems[0].kind);
+            assert_eq!(Some(_id2().to_string()), report.problems[0].id);
+            assert_eq!("Invalid value", report.problems[0].message);
+        }
+        test::cleanup_wallet("wallet_check_works_for_invalid_value");
+    }
+
     #[test]
     fn language_parse_from_json_ecrypt_query_works() {
         test::cleanup_wallet("language_parse_from_json_ecrypt_query_works");
@@ -561,7 +1113,7 @@ mod tests {
             });
 
             let query = serde_json::from_str(&query).unwrap();
-            let encrypted_query = encrypt_query(query, &Keys::new()).unwrap();
+            let encrypted_query = encrypt_query(query, &Keys::new(), &HashMap::new()).unwrap();
 
             assert_match!(Operator::And(_), encrypted_query);
         }
@@ -573,8 +1125,8 @@ mod tests {
         test::cleanup_wallet("wallet_search_works_for_empty_query");
         {
             let mut wallet = _wallet("wallet_search_works_for_empty_query");
-            wallet.add(_type1(), _id1(), _value1(), &_tags()).unwrap();
-            wallet.add(_type1(), _id2(), _value2(), &_tags()).unwrap();
+            wallet.add(_type1(), _id1(), _value1(), &_tags(), None).unwrap();
+            wallet.add(_type1(), _id2(), _value2(), &_tags(), None).unwrap();
 
             let mut iterator = wallet.search(
                 _type1(),
@@ -587,12 +1139,14 @@ mod tests {
                     value: Some(_value1().to_string()),
                     tags: None,
                     type_: None,
+                    expires_at: None,
                 },
                 WalletRecord {
                     id: _id2().to_string(),
                     value: Some(_value2().to_string()),
                     tags: None,
                     type_: None,
+                    expires_at: None,
                 },
             ]);
 
@@ -609,8 +1163,8 @@ mod tests {
         test::cleanup_wallet("wallet_search_works_for_empty_query_with_count");
         {
             let mut wallet = _wallet("wallet_search_works_for_empty_query_with_count");
-            wallet.add(_type1(), _id1(), _value1(), &_tags()).unwrap();
-            wallet.add(_type1(), _id2(), _value2(), &_tags()).unwrap();
+            wallet.add(_type1(), _id1(), _value1(), &_tags(), None).unwrap();
+            wallet.add(_type1(), _id2(), _value2(), &_tags(), None).unwrap();
 
             let mut iterator = wallet.search(
                 _type1(),
@@ -623,12 +1177,14 @@ mod tests {
                     value: Some(_value1().to_string()),
                     tags: Some(_tags()),
                     type_: Some(_type1().to_string()),
+                    expires_at: None,
                 },
                 WalletRecord {
                     id: _id2().to_string(),
                     value: Some(_value2().to_string()),
                     tags: Some(_tags()),
                     type_: Some(_type1().to_string()),
+                    expires_at: None,
                 },
             ]);
 
@@ -645,8 +1201,8 @@ mod tests {
         test::cleanup_wallet("wallet_search_works_for_empty_query_with_only_count");
         {
             let mut wallet = _wallet("wallet_search_works_for_empty_query_with_only_count");
-            wallet.add(_type1(), _id1(), _value1(), &_tags()).unwrap();
-            wallet.add(_type1(), _id2(), _value2(), &_tags()).unwrap();
+            wallet.add(_type1(), _id1(), _value1(), &_tags(), None).unwrap();
+            wallet.add(_type1(), _id2(), _value2(), &_tags(), None).unwrap();
 
             let mut iterator = wallet.search(
                 _type1(),
@@ -672,7 +1228,7 @@ mod tests {
                 "~tag_name_2": "tag_value_2",
              });
 
-            wallet.add(_type1(), _id1(), _value1(), &tags).unwrap();
+            wallet.add(_type1(), _id1(), _value1(), &tags, None).unwrap();
 
             let mut iterator = wallet.search(_type1(),
                                              &jsonstr!({"tag_name_1": "tag_value_1"}),
@@ -684,6 +1240,7 @@ mod tests {
                     id: _id1().to_string(),
                     value: Some(_value1().to_string()),
                     tags: None,
+                    expires_at: None,
                 }
             ];
 
@@ -727,12 +1284,48 @@ mod tests {
         test::cleanup_wallet("wallet_search_works_for_eq_encrypted");
     }
 
+    #[test]
+    fn wallet_count_by_tag_works() {
+        test::cleanup_wallet("wallet_count_by_tag_works");
+        {
+            let mut wallet = _wallet("wallet_count_by_tag_works");
+
+            wallet.add(_type1(), _id1(), _value1(), &jsonmap!({"tag_name_1": "b", "~tag_name_2": "x"}), None).unwrap();
+            wallet.add(_type1(), _id2(), _value1(), &jsonmap!({"tag_name_1": "a", "~tag_name_2": "x"}), None).unwrap();
+            wallet.add(_type1(), _id3(), _value1(), &jsonmap!({"tag_name_1": "b"}), None).unwrap();
+            wallet.add(_type2(), _id1(), _value1(), &jsonmap!({"tag_name_1": "c", "~tag_name_2": "y"}), None).unwrap();
+
+            let counts = wallet.count_by_tag(_type1(), "{}", "tag_name_1").unwrap();
+            assert_eq!(counts.into_iter().collect::<Vec<(String, usize)>>(),
+                       vec![("a".to_string(), 1), ("b".to_string(), 2)]);
+
+            let counts = wallet.count_by_tag(_type1(), "{}", "~tag_name_2").unwrap();
+            assert_eq!(counts.into_iter().collect::<Vec<(String, usize)>>(), vec![("x".to_string(), 2)]);
+
+            let counts = wallet.count_by_tag(_type1(), &jsonstr!({"~tag_name_2": "x"}), "tag_name_1").unwrap();
+            assert_eq!(counts.into_iter().collect::<Vec<(String, usize)>>(),
+                       vec![("a".to_string(), 1), ("b".to_string(), 1)]);
+
+            let values = wallet.get_distinct_tag_values(_type1(), "{}", "tag_name_1").unwrap();
+            assert_eq!(values, vec!["a".to_string(), "b".to_string()]);
+
+            let values = wallet.get_distinct_tag_values(_type1(), "{}", "tag_name_3").unwrap();
+            assert!(values.is_empty());
+
+            let res = wallet.count_by_tag(_type1(), "{}", "");
+            assert_kind!(IndyErrorKind::InvalidStructure, res);
+
+            wallet.close().unwrap();
+        }
+        test::cleanup_wallet("wallet_count_by_tag_works");
+    }
+
     #[test]
     fn wallet_search_works_for_empty_tag_plain() {
         test::cleanup_wallet("wallet_search_works_for_empty_tag_plain");
         {
             let mut wallet = _wallet("wallet_search_works_for_empty_tag_plain");
-            wallet.add(_type1(), _id1(), _type1(), &_tags()).unwrap();
+            wallet.add(_type1(), _id1(), _type1(), &_tags(), None).unwrap();
 
             let res = wallet.search(_type1(),
                                     &jsonstr!({
@@ -753,7 +1346,7 @@ mod tests {
         test::cleanup_wallet("wallet_search_works_for_empty_tag_encrypted");
         {
             let mut wallet = _wallet("wallet_search_works_for_empty_tag_encrypted");
-            wallet.add(_type1(), _id1(), _type1(), &_tags()).unwrap();
+            wallet.add(_type1(), _id1(), _type1(), &_tags(), None).unwrap();
 
             let res = wallet.search(_type1(),
                                     &jsonstr!({
@@ -780,7 +1373,7 @@ mod tests {
                 "tag_name_2": "tag_value_2",
              });
 
-            wallet.add(_type1(), _id1(), _value1(), &tags).unwrap();
+            wallet.add(_type1(), _id1(), _value1(), &tags, None).unwrap();
 
             let mut iterator = wallet.search(_type1(),
                                              &jsonstr!({"~tag_name_1": "tag_value_1"}),
@@ -792,6 +1385,7 @@ mod tests {
                     id: _id1().to_string(),
                     value: Some(_value1().to_string()),
                     tags: None,
+                    expires_at: None,
                 }
             ];
 
@@ -847,7 +1441,7 @@ mod tests {
                 "~tag_name_2": "tag_value_2",
              });
 
-            wallet.add(_type1(), _id1(), _value1(), &tags).unwrap();
+            wallet.add(_type1(), _id1(), _value1(), &tags, None).unwrap();
 
             let mut iterator = wallet.search(_type1(),
                                              &jsonstr!({"tag_name_1": {"$neq": "tag_value_different"}}),
@@ -859,6 +1453,7 @@ mod tests {
                     id: _id1().to_string(),
                     value: Some(_value1().to_string()),
                     tags: None,
+                    expires_at: None,
                 }
             ];
 
@@ -913,7 +1508,7 @@ mod tests {
                 "tag_name_2": "tag_value_2",
              });
 
-            wallet.add(_type1(), _id1(), _value1(), &tags).unwrap();
+            wallet.add(_type1(), _id1(), _value1(), &tags, None).unwrap();
 
             let mut iterator = wallet.search(_type1(),
                                              &jsonstr!({"~tag_name_1": {"$neq": "tag_value_different"}}),
@@ -925,6 +1520,7 @@ mod tests {
                     id: _id1().to_string(),
                     value: Some(_value1().to_string()),
                     tags: None,
+                    expires_at: None,
                 }
             ];
 
@@ -973,9 +1569,9 @@ mod tests {
         test::cleanup_wallet("wallet_search_works_for_gt_plain");
         {
             let mut wallet = _wallet("wallet_search_works_for_gt_plain");
-            wallet.add(_type1(), _id1(), _value1(), &jsonmap!({"~tag_name":"1"})).unwrap();
-            wallet.add(_type1(), _id2(), _value2(), &jsonmap!({"~tag_name":"2"})).unwrap();
-            wallet.add(_type1(), _id3(), _value3(), &jsonmap!({"~tag_name":"3"})).unwrap();
+            wallet.add(_type1(), _id1(), _value1(), &jsonmap!({"~tag_name":"1"}), None).unwrap();
+            wallet.add(_type1(), _id2(), _value2(), &jsonmap!({"~tag_name":"2"}), None).unwrap();
+            wallet.add(_type1(), _id3(), _value3(), &jsonmap!({"~tag_name":"3"}), None).unwrap();
 
             let mut iterator = wallet.search(_type1(),
                                              &jsonstr!({"~tag_name": {"$gt": "1"}}),
@@ -987,12 +1583,14 @@ mod tests {
                     id: _id2().to_string(),
                     value: Some(_value2().to_string()),
                     tags: None,
+                    expires_at: None,
                 },
                 WalletRecord {
                     type_: None,
                     id: _id3().to_string(),
                     value: Some(_value3().to_string()),
                     tags: None,
+                    expires_at: None,
                 },
             ]);
 
@@ -1049,9 +1647,9 @@ mod tests {
         test::cleanup_wallet("wallet_search_works_for_gte_plain");
         {
             let mut wallet = _wallet("wallet_search_works_for_gte_plain");
-            wallet.add(_type1(), _id1(), _value1(), &jsonmap!({"~tag_name":"1"})).unwrap();
-            wallet.add(_type1(), _id2(), _value2(), &jsonmap!({"~tag_name":"2"})).unwrap();
-            wallet.add(_type1(), _id3(), _value3(), &jsonmap!({"~tag_name":"3"})).unwrap();
+            wallet.add(_type1(), _id1(), _value1(), &jsonmap!({"~tag_name":"1"}), None).unwrap();
+            wallet.add(_type1(), _id2(), _value2(), &jsonmap!({"~tag_name":"2"}), None).unwrap();
+            wallet.add(_type1(), _id3(), _value3(), &jsonmap!({"~tag_name":"3"}), None).unwrap();
 
             let mut iterator = wallet.search(_type1(),
                                              &jsonstr!({"~tag_name": {"$gte": "2"}}),
@@ -1063,12 +1661,14 @@ mod tests {
                     id: _id2().to_string(),
                     value: Some(_value2().to_string()),
                     tags: None,
+                    expires_at: None,
                 },
                 WalletRecord {
                     type_: None,
                     id: _id3().to_string(),
                     value: Some(_value3().to_string()),
                     tags: None,
+                    expires_at: None,
                 },
             ]);
 
@@ -1124,9 +1724,9 @@ mod tests {
         test::cleanup_wallet("wallet_search_works_for_lt_plain");
         {
             let mut wallet = _wallet("wallet_search_works_for_lt_plain");
-            wallet.add(_type1(), _id1(), _value1(), &jsonmap!({"~tag_name":"2"})).unwrap();
-            wallet.add(_type1(), _id2(), _value2(), &jsonmap!({"~tag_name":"3"})).unwrap();
-            wallet.add(_type1(), _id3(), _value3(), &jsonmap!({"~tag_name":"4"})).unwrap();
+            wallet.add(_type1(), _id1(), _value1(), &jsonmap!({"~tag_name":"2"}), None).unwrap();
+            wallet.add(_type1(), _id2(), _value2(), &jsonmap!({"~tag_name":"3"}), None).unwrap();
+            wallet.add(_type1(), _id3(), _value3(), &jsonmap!({"~tag_name":"4"}), None).unwrap();
 
             let mut iterator = wallet.search(_type1(),
                                              &jsonstr!({"~tag_name": {"$lt": "4"}}),
@@ -1138,12 +1738,14 @@ mod tests {
                     id: _id1().to_string(),
                     value: Some(_value1().to_string()),
                     tags: None,
+                    expires_at: None,
                 },
                 WalletRecord {
                     type_: None,
                     id: _id2().to_string(),
                     value: Some(_value2().to_string()),
                     tags: None,
+                    expires_at: None,
                 },
             ]);
 
@@ -1199,9 +1801,9 @@ mod tests {
         test::cleanup_wallet("wallet_search_works_for_lte_plain");
         {
             let mut wallet = _wallet("wallet_search_works_for_lte_plain");
-            wallet.add(_type1(), _id1(), _value1(), &jsonmap!({"~tag_name":"2"})).unwrap();
-            wallet.add(_type1(), _id2(), _value2(), &jsonmap!({"~tag_name":"3"})).unwrap();
-            wallet.add(_type1(), _id3(), _value3(), &jsonmap!({"~tag_name":"4"})).unwrap();
+            wallet.add(_type1(), _id1(), _value1(), &jsonmap!({"~tag_name":"2"}), None).unwrap();
+            wallet.add(_type1(), _id2(), _value2(), &jsonmap!({"~tag_name":"3"}), None).unwrap();
+            wallet.add(_type1(), _id3(), _value3(), &jsonmap!({"~tag_name":"4"}), None).unwrap();
 
             let mut iterator = wallet.search(_type1(),
                                              &jsonstr!({"~tag_name": {"$lte": "3"}}),
@@ -1213,12 +1815,14 @@ mod tests {
                     id: _id1().to_string(),
                     value: Some(_value1().to_string()),
                     tags: None,
+                    expires_at: None,
                 },
                 WalletRecord {
                     type_: None,
                     id: _id2().to_string(),
                     value: Some(_value2().to_string()),
                     tags: None,
+                    expires_at: None,
                 },
             ]);
 
@@ -1274,9 +1878,9 @@ mod tests {
         test::cleanup_wallet("wallet_search_works_for_like_plain");
         {
             let mut wallet = _wallet("wallet_search_works_for_like_plain");
-            wallet.add(_type1(), _id1(), _value1(), &jsonmap!({"~tag_name": "tag_value_1"})).unwrap();
-            wallet.add(_type1(), _id2(), _value2(), &jsonmap!({"~tag_name": "tag_value_2"})).unwrap();
-            wallet.add(_type1(), _id3(), _value3(), &jsonmap!({"~tag_name": "not_matching"})).unwrap();
+            wallet.add(_type1(), _id1(), _value1(), &jsonmap!({"~tag_name": "tag_value_1"}), None).unwrap();
+            wallet.add(_type1(), _id2(), _value2(), &jsonmap!({"~tag_name": "tag_value_2"}), None).unwrap();
+            wallet.add(_type1(), _id3(), _value3(), &jsonmap!({"~tag_name": "not_matching"}), None).unwrap();
 
             let mut iterator = wallet.search(_type1(),
                                              &jsonstr!({"~tag_name": {"$like": "tag_value_%"}}),
@@ -1288,12 +1892,14 @@ mod tests {
                     id: _id1().to_string(),
                     value: Some(_value1().to_string()),
                     tags: None,
+                    expires_at: None,
                 },
                 WalletRecord {
                     type_: None,
                     id: _id2().to_string(),
                     value: Some(_value2().to_string()),
                     tags: None,
+                    expires_at: None,
                 },
             ]);
 
@@ -1346,14 +1952,107 @@ mod tests {
         test::cleanup_wallet("wallet_search_works_for_like_encrypted");
     }
 
+    #[test]
+    fn wallet_search_works_for_prefix_plain() {
+        test::cleanup_wallet("wallet_search_works_for_prefix_plain");
+        {
+            let wallet = _wallet("wallet_search_works_for_prefix_plain");
+            wallet.add(_type1(), _id1(), _value1(), &jsonmap!({"~tag_name": "tag_value_1"}), None).unwrap();
+            wallet.add(_type1(), _id2(), _value2(), &jsonmap!({"~tag_name": "tag_value_2"}), None).unwrap();
+            wallet.add(_type1(), _id3(), _value3(), &jsonmap!({"~tag_name": "tag%value"}), None).unwrap();
+
+            assert_eq!(vec![_id1(), _id2()], _search_ids(&wallet, &jsonstr!({"~tag_name": {"$prefix": "tag_"}})));
+            assert_eq!(vec![_id3()], _search_ids(&wallet, &jsonstr!({"~tag_name": {"$prefix": "tag%"}})));
+            assert!(_search_ids(&wallet, &jsonstr!({"~tag_name": {"$prefix": "value"}})).is_empty());
+        }
+        test::cleanup_wallet("wallet_search_works_for_prefix_plain");
+    }
+
+    #[test]
+    fn wallet_search_works_for_prefix_encrypted() {
+        test::cleanup_wallet("wallet_search_works_for_prefix_encrypted");
+        {
+            let mut wallet = _wallet("wallet_search_works_for_prefix_encrypted");
+            wallet.set_prefix_indexes(serde_json::from_value(json!({"tag_name": 4})).unwrap());
+
+            wallet.add(_type1(), _id1(), _value1(), &jsonmap!({"tag_name": "tag_value_1", "tag2": "value"}), None).unwrap();
+            wallet.add(_type1(), _id2(), _value2(), &jsonmap!({"tag_name": "tag_value_2"}), None).unwrap();
+            wallet.add(_type1(), _id3(), _value3(), &jsonmap!({"tag_name": "ta"}), None).unwrap();
+
+            assert_eq!(vec![_id1(), _id2()], _search_ids(&wallet, &jsonstr!({"tag_name": {"$prefix": "tag_"}})));
+            assert_eq!(vec![_id1(), _id2(), _id3()], _search_ids(&wallet, &jsonstr!({"tag_name": {"$prefix": "ta"}})));
+            assert_eq!(vec![_id1(), _id2(), _id3()], _search_ids(&wallet, &jsonstr!({"tag_name": {"$prefix": ""}})));
+            assert!(_search_ids(&wallet, &jsonstr!({"tag_name": {"$prefix": "tax"}})).is_empty());
+
+            // prefix indexes are hidden from record tags
+            let record = wallet.get(_type1(), _id1(), &_fetch_options(false, false, true)).unwrap();
+            assert_eq!(Some(jsonmap!({"tag_name": "tag_value_1", "tag2": "value"})), record.tags);
+
+            // prefix indexes follow tags changes
+            wallet.update_tags(_type1(), _id1(), &jsonmap!({"tag_name": "other"})).unwrap();
+            wallet.delete_tags(_type1(), _id2(), &["tag_name"]).unwrap();
+            assert_eq!(vec![_id3()], _search_ids(&wallet, &jsonstr!({"tag_name": {"$prefix": "t"}})));
+            assert_eq!(vec![_id1()], _search_ids(&wallet, &jsonstr!({"tag_name": {"$prefix": "oth"}})));
+
+            // prefix is longer than indexed one
+            let res = wallet.search(_type1(), &jsonstr!({"tag_name": {"$prefix": "tag_v"}}), None);
+            assert_kind!(IndyErrorKind::WalletQueryError, res);
+
+            // tag isn't indexed
+            let res = wallet.search(_type1(), &jsonstr!({"tag2": {"$prefix": "v"}}), None);
+            assert_kind!(IndyErrorKind::WalletQueryError, res);
+        }
+        test::cleanup_wallet("wallet_search_works_for_prefix_encrypted");
+    }
+
+    #[test]
+    fn wallet_rebuild_prefix_indexes_works() {
+        test::cleanup_wallet("wallet_rebuild_prefix_indexes_works");
+        {
+            let mut wallet = _wallet("wallet_rebuild_prefix_indexes_works");
+
+            // records added before prefix indexes are configured
+            wallet.add(_type1(), _id1(), _value1(), &jsonmap!({"tag_name": "tag_value_1", "tag2": "value"}), None).unwrap();
+            wallet.add(_type1(), _id2(), _value2(), &jsonmap!({"tag_name": "other"}), None).unwrap();
+
+            wallet.set_prefix_indexes(serde_json::from_value(json!({"tag_name": 4})).unwrap());
+            assert!(_search_ids(&wallet, &jsonstr!({"tag_name": {"$prefix": "tag_"}})).is_empty());
+
+            let pending = wallet.get_prefix_indexes_rebuild_pending().unwrap().next_chunk(PREFIX_INDEXES_REBUILD_CHUNK_SIZE).unwrap();
+            assert_eq!(2, pending.len());
+            assert_eq!(2, wallet.rebuild_prefix_indexes_chunk(&pending).unwrap());
+
+            assert_eq!(vec![_id1()], _search_ids(&wallet, &jsonstr!({"tag_name": {"$prefix": "tag_"}})));
+            assert_eq!(vec![_id2()], _search_ids(&wallet, &jsonstr!({"tag_name": {"$prefix": "o"}})));
+
+            // indexes of tags that aren't indexed anymore are removed
+            wallet.set_prefix_indexes(serde_json::from_value(json!({"tag2": 1})).unwrap());
+            assert_eq!(2, wallet.rebuild_prefix_indexes_chunk(&pending).unwrap());
+
+            assert_eq!(vec![_id1()], _search_ids(&wallet, &jsonstr!({"tag2": {"$prefix": "v"}})));
+
+            // 3 tags set by user and index of "tag2" of the first record
+            let tags_count: usize = pending.iter()
+                .map(|&(ref type_, ref id)| wallet.storage.get(type_, id, r##"{"retrieveType": false, "retrieveValue": false, "retrieveTags": true}"##).unwrap())
+                .map(|record| record.tags.unwrap().len())
+                .sum();
+            assert_eq!(4, tags_count);
+
+            // tags of records are kept
+            let record = wallet.get(_type1(), _id1(), &_fetch_options(false, false, true)).unwrap();
+            assert_eq!(Some(jsonmap!({"tag_name": "tag_value_1", "tag2": "value"})), record.tags);
+        }
+        test::cleanup_wallet("wallet_rebuild_prefix_indexes_works");
+    }
+
     #[test]
     fn wallet_search_works_for_in_plain() {
         test::cleanup_wallet("wallet_search_works_for_in_plain");
         {
             let mut wallet = _wallet("wallet_search_works_for_in_plain");
-            wallet.add(_type1(), _id1(), _value1(), &jsonmap!({"~tag_name": "tag_value_1"})).unwrap();
-            wallet.add(_type1(), _id2(), _value2(), &jsonmap!({"~tag_name": "tag_value_2"})).unwrap();
-            wallet.add(_type1(), _id3(), _value3(), &jsonmap!({"~tag_name": "tag_value_3"})).unwrap();
+            wallet.add(_type1(), _id1(), _value1(), &jsonmap!({"~tag_name": "tag_value_1"}), None).unwrap();
+            wallet.add(_type1(), _id2(), _value2(), &jsonmap!({"~tag_name": "tag_value_2"}), None).unwrap();
+            wallet.add(_type1(), _id3(), _value3(), &jsonmap!({"~tag_name": "tag_value_3"}), None).unwrap();
 
             let mut iterator = wallet.search(_type1(),
                                              &jsonstr!({"~tag_name": {"$in": ["tag_value_1", "tag_value_3"]}}),
@@ -1365,12 +2064,14 @@ mod tests {
                     id: _id1().to_string(),
                     value: Some(_value1().to_string()),
                     tags: None,
+                    expires_at: None,
                 },
                 WalletRecord {
                     type_: None,
                     id: _id3().to_string(),
                     value: Some(_value3().to_string()),
                     tags: None,
+                    expires_at: None,
                 },
             ]);
 
@@ -1419,9 +2120,9 @@ mod tests {
         test::cleanup_wallet("wallet_search_works_for_in_encrypted");
         {
             let mut wallet = _wallet("wallet_search_works_for_in_encrypted");
-            wallet.add(_type1(), _id1(), _value1(), &jsonmap!({"tag_name": "tag_value_1"})).unwrap();
-            wallet.add(_type1(), _id2(), _value2(), &jsonmap!({"tag_name": "tag_value_2"})).unwrap();
-            wallet.add(_type1(), _id3(), _value3(), &jsonmap!({"tag_name": "tag_value_3"})).unwrap();
+            wallet.add(_type1(), _id1(), _value1(), &jsonmap!({"tag_name": "tag_value_1"}), None).unwrap();
+            wallet.add(_type1(), _id2(), _value2(), &jsonmap!({"tag_name": "tag_value_2"}), None).unwrap();
+            wallet.add(_type1(), _id3(), _value3(), &jsonmap!({"tag_name": "tag_value_3"}), None).unwrap();
 
             let mut iterator = wallet.search(_type1(),
                                              &jsonstr!({"tag_name": {"$in": ["tag_value_1", "tag_value_3"]}}),
@@ -1433,12 +2134,14 @@ mod tests {
                     id: _id1().to_string(),
                     value: Some(_value1().to_string()),
                     tags: None,
+                    expires_at: None,
                 },
                 WalletRecord {
                     type_: None,
                     id: _id3().to_string(),
                     value: Some(_value3().to_string()),
                     tags: None,
+                    expires_at: None,
                 },
             ]);
 
@@ -1496,7 +2199,7 @@ mod tests {
                             "tag_name_1": "tag_value_1",
                             "tag_name_2": "tag_value_2",
                             "~tag_name_2": "tag_value_2",
-                            "~tag_name_3": "tag_value_3"})).unwrap();
+                            "~tag_name_3": "tag_value_3"}), None).unwrap();
 
             wallet.add(_type1(),
                        _id2(),
@@ -1505,7 +2208,7 @@ mod tests {
                             "tag_name_1": "tag_value_1",
                             "tag_name_2": "tag_value_2",
                             "~tag_name_2": "tag_value_3",
-                            "~tag_name_3": "tag_value_3"})).unwrap();
+                            "~tag_name_3": "tag_value_3"}), None).unwrap();
 
             let mut iterator = wallet.search(_type1(),
                                              &jsonstr!({
@@ -1521,6 +2224,7 @@ mod tests {
                     id: _id1().to_string(),
                     value: Some(_value1().to_string()),
                     tags: None,
+                    expires_at: None,
                 }
             ];
 
@@ -1540,6 +2244,7 @@ mod tests {
                     id: _id2().to_string(),
                     value: Some(_value2().to_string()),
                     tags: None,
+                    expires_at: None,
                 }
             ];
 
@@ -1559,12 +2264,14 @@ mod tests {
                     id: _id1().to_string(),
                     value: Some(_value1().to_string()),
                     tags: None,
+                    expires_at: None,
                 },
                 WalletRecord {
                     type_: None,
                     id: _id2().to_string(),
                     value: Some(_value2().to_string()),
                     tags: None,
+                    expires_at: None,
                 },
             ]);
 
@@ -1633,7 +2340,7 @@ mod tests {
                        &jsonmap!({
                            "tag_name_1": "tag_value_1",
                            "~tag_name_2": "tag_value_21",
-                           "~tag_name_3": "tag_value_3"})).unwrap();
+                           "~tag_name_3": "tag_value_3"}), None).unwrap();
 
             wallet.add(_type1(),
                        _id2(),
@@ -1641,7 +2348,7 @@ mod tests {
                        &jsonmap!({
                            "tag_name_1": "tag_value_1",
                            "~tag_name_2": "tag_value_22",
-                           "~tag_name_3": "tag_value_3"})).unwrap();
+                           "~tag_name_3": "tag_value_3"}), None).unwrap();
 
             wallet.add(_type1(),
                        _id3(),
@@ -1649,7 +2356,7 @@ mod tests {
                        &jsonmap!({
                            "tag_name_1": "tag_value_1",
                            "~tag_name_3": "tag_value_3",
-                           "~tag_name_4": "tag_value_4"})).unwrap();
+                           "~tag_name_4": "tag_value_4"}), None).unwrap();
 
             // All 3
             let mut iterator = wallet.search(_type1(),
@@ -1668,18 +2375,21 @@ mod tests {
                     id: _id1().to_string(),
                     value: Some(_value1().to_string()),
                     tags: None,
+                    expires_at: None,
                 },
                 WalletRecord {
                     type_: None,
                     id: _id2().to_string(),
                     value: Some(_value2().to_string()),
                     tags: None,
+                    expires_at: None,
                 },
                 WalletRecord {
                     type_: None,
                     id: _id3().to_string(),
                     value: Some(_value3().to_string()),
                     tags: None,
+                    expires_at: None,
                 },
             ]);
 
@@ -1703,12 +2413,14 @@ mod tests {
                     id: _id1().to_string(),
                     value: Some(_value1().to_string()),
                     tags: None,
+                    expires_at: None,
                 },
                 WalletRecord {
                     type_: None,
                     id: _id3().to_string(),
                     value: Some(_value3().to_string()),
                     tags: None,
+                    expires_at: None,
                 },
             ]);
 
@@ -1731,6 +2443,7 @@ mod tests {
                     id: _id3().to_string(),
                     value: Some(_value3().to_string()),
                     tags: None,
+                    expires_at: None,
                 },
             ];
 
@@ -1779,21 +2492,21 @@ mod tests {
                        &jsonmap!({
                            "tag_name_1": "tag_value_1",
                            "~tag_name_2": "tag_value_21",
-                           "~tag_name_3": "tag_value_3"})).unwrap();
+                           "~tag_name_3": "tag_value_3"}), None).unwrap();
 
             wallet.add(_type1(),
                        _id2(),
                        _value2(),
                        &jsonmap!({
                            "tag_name_12": "tag_value_12",
-                           "~tag_name_2": "tag_value_22"})).unwrap();
+                           "~tag_name_2": "tag_value_22"}), None).unwrap();
 
             wallet.add(_type1(),
                        _id3(),
                        _value3(),
                        &jsonmap!({
                            "tag_name_13": "tag_value_13",
-                           "~tag_name_4": "tag_value_4"})).unwrap();
+                           "~tag_name_4": "tag_value_4"}), None).unwrap();
 
 
             let mut iterator = wallet.search(_type1(),
@@ -1806,18 +2519,21 @@ mod tests {
                     id: _id1().to_string(),
                     value: Some(_value1().to_string()),
                     tags: None,
+                    expires_at: None,
                 },
                 WalletRecord {
                     type_: None,
                     id: _id2().to_string(),
                     value: Some(_value2().to_string()),
                     tags: None,
+                    expires_at: None,
                 },
                 WalletRecord {
                     type_: None,
                     id: _id3().to_string(),
                     value: Some(_value3().to_string()),
                     tags: None,
+                    expires_at: None,
                 },
             ]);
 
@@ -1834,12 +2550,14 @@ mod tests {
                     id: _id1().to_string(),
                     value: Some(_value1().to_string()),
                     tags: None,
+                    expires_at: None,
                 },
                 WalletRecord {
                     type_: None,
                     id: _id3().to_string(),
                     value: Some(_value3().to_string()),
                     tags: None,
+                    expires_at: None,
                 },
             ]);
 
@@ -1871,7 +2589,7 @@ mod tests {
         test::cleanup_wallet("wallet_search_works_for_nested");
         {
             let mut wallet = _wallet("wallet_search_works_for_nested");
-            wallet.add(_type1(), _id1(), _value1(), &_tags()).unwrap();
+            wallet.add(_type1(), _id1(), _value1(), &_tags(), None).unwrap();
 
             let query = jsonstr!({
                 "$or": [
@@ -1900,6 +2618,7 @@ mod tests {
                     id: _id1().to_string(),
                     value: Some(_value1().to_string()),
                     tags: None,
+                    expires_at: None,
                 },
             ];
 
@@ -1916,7 +2635,7 @@ mod tests {
         test::cleanup_wallet("wallet_search_works_for_nested_empty");
         {
             let mut wallet = _wallet("wallet_search_works_for_nested_empty");
-            wallet.add(_type1(), _id1(), _value1(), &_tags()).unwrap();
+            wallet.add(_type1(), _id1(), _value1(), &_tags(), None).unwrap();
 
             let query = json!({
                 "$and": [
@@ -1937,6 +2656,7 @@ mod tests {
                     id: _id1().to_string(),
                     value: Some(_value1().to_string()),
                     tags: None,
+                    expires_at: None,
                 },
             ];
 
@@ -2000,6 +2720,9 @@ mod tests {
             let metadata = Metadata::MetadataArgon(MetadataArgon {
                 master_key_salt: master_key_salt[..].to_vec(),
                 keys: keys.serialize_encrypted(&master_key).unwrap(),
+                keys_rotation: None,
+                prefix_indexes: HashMap::new(),
+                pending_prefix_indexes: None,
             });
 
             serde_json::to_vec(&metadata).unwrap()
@@ -2080,6 +2803,17 @@ mod tests {
         _sort(v)
     }
 
+    fn _search_ids(wallet: &Wallet, query: &str) -> Vec<String> {
+        let mut iterator = wallet.search(_type1(), query, Some(&_search_options(true, false, false, false, false))).unwrap();
+
+        let mut ids = Vec::new();
+        while let Some(record) = iterator.next().unwrap() {
+            ids.push(record.get_id().to_string());
+        }
+        ids.sort();
+        ids
+    }
+
     fn _sort(mut v: Vec<WalletRecord>) -> Vec<WalletRecord> {
         v.sort();
         v
@@ -1390,6 +1390,7 @@ pub extern fn indy_prover_search_credentials(command_handle: CommandHandle,
                 ProverCommand::SearchCredentials(
                     wallet_handle,
                     query_json,
+                    None,
                     Box::new(move |result| {
                         let (err, handle, total_count) = prepare_result_2!(result, INVALID_SEARCH_HANDLE, 0);
                         cb(command_handle, err, handle, total_count)
@@ -1403,6 +1404,69 @@ pub extern fn indy_prover_search_credentials(command_handle: CommandHandle,
     res
 }
 
+/// Search for credentials stored in wallet with paging of the results.
+/// Credentials can be filtered by tags created during saving of credential.
+///
+/// Instead of immediately returning of fetched credentials
+/// this call returns search_handle that can be used later
+/// to fetch records by small batches (with indy_prover_fetch_credentials).
+///
+/// #Params
+/// wallet_handle: wallet handle (created by open_wallet).
+/// query_json: Wql query filter for credentials searching based on tags.
+///     where query: indy-sdk/docs/design/011-wallet-query-language/README.md
+/// options_json: paging of found credentials:
+///  {
+///    offset: (optional, 0 by default) Number of credentials to skip,
+///    limit: (optional, no limit by default) Max number of credentials to return,
+///  }
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// search_handle: Search handle that can be used later to fetch records by small batches (with indy_prover_fetch_credentials)
+/// total_count: Total count of records matching the query regardless of paging
+///
+/// #Errors
+/// Anoncreds*
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn indy_prover_search_credentials_with_options(command_handle: CommandHandle,
+                                                          wallet_handle: WalletHandle,
+                                                          query_json: *const c_char,
+                                                          options_json: *const c_char,
+                                                          cb: Option<extern fn(
+                                                              command_handle_: CommandHandle, err: ErrorCode,
+                                                              search_handle: SearchHandle,
+                                                              total_count: usize)>) -> ErrorCode {
+    trace!("indy_prover_search_credentials_with_options: >>> wallet_handle: {:?}, query_json: {:?}, options_json: {:?}", wallet_handle, query_json, options_json);
+
+    check_useful_opt_c_str!(query_json, ErrorCode::CommonInvalidParam3);
+    check_useful_opt_c_str!(options_json, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    trace!("indy_prover_search_credentials_with_options: entities >>> wallet_handle: {:?}, query_json: {:?}, options_json: {:?}", wallet_handle, query_json, options_json);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Anoncreds(
+            AnoncredsCommand::Prover(
+                ProverCommand::SearchCredentials(
+                    wallet_handle,
+                    query_json,
+                    options_json,
+                    Box::new(move |result| {
+                        let (err, handle, total_count) = prepare_result_2!(result, INVALID_SEARCH_HANDLE, 0);
+                        cb(command_handle, err, handle, total_count)
+                    })
+                ))));
+
+    let res = prepare_result!(result);
+
+    trace!("indy_prover_search_credentials_with_options: <<< res: {:?}", res);
+
+    res
+}
+
 /// Fetch next credentials for search.
 ///
 /// #Params
@@ -1492,6 +1556,107 @@ pub  extern fn indy_prover_close_credentials_search(command_handle: CommandHandl
     res
 }
 
+/// Count credentials stored in wallet per value of the tag created during saving of credential.
+/// Credentials without the tag are not counted.
+///
+/// #Params
+/// wallet_handle: wallet handle (created by open_wallet).
+/// query_json: (optional) Wql query filter for credentials based on tags.
+///     where query: indy-sdk/docs/design/011-wallet-query-language/README.md
+/// tag_name: the name of the credential tag to aggregate values of, for example "cred_def_id" or "schema_name".
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// counts_json: tag values mapped to the count of credentials, ordered by value:
+/// {
+///   "tagValue1": <int>,
+///   "tagValue2": <int>,
+/// }
+///
+/// #Errors
+/// Anoncreds*
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn indy_prover_count_credentials_by_tag(command_handle: CommandHandle,
+                                                   wallet_handle: WalletHandle,
+                                                   query_json: *const c_char,
+                                                   tag_name: *const c_char,
+                                                   cb: Option<extern fn(command_handle_: CommandHandle, err: ErrorCode,
+                                                                        counts_json: *const c_char)>) -> ErrorCode {
+    trace!("indy_prover_count_credentials_by_tag: >>> wallet_handle: {:?}, query_json: {:?}, tag_name: {:?}", wallet_handle, query_json, tag_name);
+
+    check_useful_opt_c_str!(query_json, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(tag_name, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    trace!("indy_prover_count_credentials_by_tag: entities >>> wallet_handle: {:?}, query_json: {:?}, tag_name: {:?}", wallet_handle, query_json, tag_name);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Anoncreds(
+            AnoncredsCommand::Prover(
+                ProverCommand::CountCredentialsByTag(
+                    wallet_handle,
+                    query_json,
+                    tag_name,
+                    boxed_callback_string!("indy_prover_count_credentials_by_tag", cb, command_handle)
+                ))));
+
+    let res = prepare_result!(result);
+
+    trace!("indy_prover_count_credentials_by_tag: <<< res: {:?}", res);
+
+    res
+}
+
+/// Get the sorted set of distinct values of the tag over credentials stored in wallet.
+///
+/// #Params
+/// wallet_handle: wallet handle (created by open_wallet).
+/// query_json: (optional) Wql query filter for credentials based on tags.
+///     where query: indy-sdk/docs/design/011-wallet-query-language/README.md
+/// tag_name: the name of the credential tag to aggregate values of, for example "cred_def_id" or "schema_name".
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// values_json: ["tagValue1", "tagValue2"]
+///
+/// #Errors
+/// Anoncreds*
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn indy_prover_get_distinct_credential_tag_values(command_handle: CommandHandle,
+                                                             wallet_handle: WalletHandle,
+                                                             query_json: *const c_char,
+                                                             tag_name: *const c_char,
+                                                             cb: Option<extern fn(command_handle_: CommandHandle, err: ErrorCode,
+                                                                                  values_json: *const c_char)>) -> ErrorCode {
+    trace!("indy_prover_get_distinct_credential_tag_values: >>> wallet_handle: {:?}, query_json: {:?}, tag_name: {:?}", wallet_handle, query_json, tag_name);
+
+    check_useful_opt_c_str!(query_json, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(tag_name, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    trace!("indy_prover_get_distinct_credential_tag_values: entities >>> wallet_handle: {:?}, query_json: {:?}, tag_name: {:?}", wallet_handle, query_json, tag_name);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Anoncreds(
+            AnoncredsCommand::Prover(
+                ProverCommand::GetDistinctCredentialTagValues(
+                    wallet_handle,
+                    query_json,
+                    tag_name,
+                    boxed_callback_string!("indy_prover_get_distinct_credential_tag_values", cb, command_handle)
+                ))));
+
+    let res = prepare_result!(result);
+
+    trace!("indy_prover_get_distinct_credential_tag_values: <<< res: {:?}", res);
+
+    res
+}
+
 /// Gets human readable credentials matching the given proof request.
 ///
 /// NOTE: This method is deprecated because immediately returns all fetched credentials.
@@ -5,6 +5,7 @@ pub mod pairwise;
 pub mod pool;
 pub mod did;
 pub mod wallet;
+pub mod wallet_async;
 pub mod blob_storage;
 pub mod non_secrets;
 pub mod payments;
@@ -1,5 +1,5 @@
 
-use indy_api_types::{ErrorCode, CommandHandle, WalletHandle, SearchHandle, INVALID_SEARCH_HANDLE};
+use indy_api_types::{ErrorCode, CommandHandle, IndyHandle, WalletHandle, SearchHandle, INVALID_SEARCH_HANDLE};
 use crate::commands::{Command, CommandExecutor};
 use crate::commands::non_secrets::NonSecretsCommand;
 use indy_api_types::domain::wallet::Tags;
@@ -54,6 +54,7 @@ pub extern fn indy_add_wallet_record(command_handle: CommandHandle,
                 id,
                 value,
                 tags_json,
+                None,
                 Box::new(move |result| {
                     let err = prepare_result!(result);
                     trace!("indy_add_wallet_record:");
@@ -68,6 +69,64 @@ pub extern fn indy_add_wallet_record(command_handle: CommandHandle,
     res
 }
 
+/// Create a new non-secret record in the wallet that expires at the given time.
+/// Expired record isn't returned by indy_get_wallet_record and wallet searches,
+/// but it is physically deleted only by indy_purge_expired_wallet_records call.
+/// Expiry time isn't preserved by export of the wallet.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context
+/// wallet_handle: wallet handle (created by open_wallet)
+/// type_: allows to separate different record types collections
+/// id: the id of record
+/// value: the value of record
+/// tags_json: (optional) the record tags used for search and storing meta information as json.
+///   Tags json has the same format as for indy_add_wallet_record
+/// expires_at: expiry time of the record as unix timestamp in seconds
+#[no_mangle]
+pub extern fn indy_add_wallet_record_with_expiry(command_handle: CommandHandle,
+                                                 wallet_handle: WalletHandle,
+                                                 type_: *const c_char,
+                                                 id: *const c_char,
+                                                 value: *const c_char,
+                                                 tags_json: *const c_char,
+                                                 expires_at: u64,
+                                                 cb: Option<extern fn(command_handle_: CommandHandle, err: ErrorCode)>) -> ErrorCode {
+    trace!("indy_add_wallet_record_with_expiry: >>> wallet_handle: {:?}, type_: {:?}, id: {:?}, value: {:?}, tags_json: {:?}, expires_at: {:?}",
+           wallet_handle, type_, id, value, tags_json, expires_at);
+
+    check_useful_c_str!(type_, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(id, ErrorCode::CommonInvalidParam4);
+    check_useful_c_str!(value, ErrorCode::CommonInvalidParam5);
+    check_useful_opt_json!(tags_json, ErrorCode::CommonInvalidParam6, Tags);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam8);
+
+    trace!("indy_add_wallet_record_with_expiry: entities >>> wallet_handle: {:?}, type_: {:?}, id: {:?}, value: {:?}, tags_json: {:?}, expires_at: {:?}",
+           wallet_handle, type_, id, value, tags_json, expires_at);
+
+    let result = CommandExecutor::instance()
+        .send(Command::NonSecrets(
+            NonSecretsCommand::AddRecord(
+                wallet_handle,
+                type_,
+                id,
+                value,
+                tags_json,
+                Some(expires_at),
+                Box::new(move |result| {
+                    let err = prepare_result!(result);
+                    trace!("indy_add_wallet_record_with_expiry:");
+                    cb(command_handle, err)
+                })
+            )));
+
+    let res = prepare_result!(result);
+
+    trace!("indy_add_wallet_record_with_expiry: <<< res: {:?}", res);
+
+    res
+}
+
 /// Update a non-secret wallet record value
 ///
 /// #Params
@@ -310,6 +369,341 @@ pub extern fn indy_delete_wallet_record(command_handle: CommandHandle,
     res
 }
 
+/// Apply several non-secret record operations to the wallet as a single atomic unit.
+///
+/// Either all operations are stored or, if any of them fails, the wallet is left unchanged.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context
+/// wallet_handle: wallet handle (created by open_wallet)
+/// operations_json: the list of operations to apply in order as json array:
+///   [
+///     {"op": "add", "type": <str>, "id": <str>, "value": <str>, "tags": <tags json> (optional),
+///      "expiresAt": <expiry time as unix timestamp in seconds> (optional)},
+///     {"op": "updateValue", "type": <str>, "id": <str>, "value": <str>},
+///     {"op": "addTags", "type": <str>, "id": <str>, "tags": <tags json>},
+///     {"op": "updateTags", "type": <str>, "id": <str>, "tags": <tags json>},
+///     {"op": "deleteTags", "type": <str>, "id": <str>, "tagNames": [<str>, ...]},
+///     {"op": "delete", "type": <str>, "id": <str>},
+///   ]
+///   Tags json has the same format as for indy_add_wallet_record
+#[no_mangle]
+pub extern fn indy_wallet_batch(command_handle: CommandHandle,
+                                wallet_handle: WalletHandle,
+                                operations_json: *const c_char,
+                                cb: Option<extern fn(command_handle_: CommandHandle, err: ErrorCode)>) -> ErrorCode {
+    trace!("indy_wallet_batch: >>> wallet_handle: {:?}, operations_json: {:?}", wallet_handle, operations_json);
+
+    check_useful_c_str!(operations_json, ErrorCode::CommonInvalidParam3);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
+
+    trace!("indy_wallet_batch: entities >>> wallet_handle: {:?}, operations_json: {:?}", wallet_handle, operations_json);
+
+    let result = CommandExecutor::instance()
+        .send(Command::NonSecrets(
+            NonSecretsCommand::Batch(
+                wallet_handle,
+                operations_json,
+                Box::new(move |result| {
+                    let err = prepare_result!(result);
+                    trace!("indy_wallet_batch:");
+                    cb(command_handle, err)
+                })
+            )));
+
+    let res = prepare_result!(result);
+
+    trace!("indy_wallet_batch: <<< res: {:?}", res);
+
+    res
+}
+
+/// Start staged batch of non-secret record operations.
+///
+/// Operations staged with indy_wallet_batch_stage are kept in memory and nothing is written
+/// to the wallet until indy_wallet_batch_commit applies all of them as a single atomic unit.
+/// Batch is discarded by indy_wallet_batch_rollback or when wallet_handle is closed.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context
+/// wallet_handle: wallet handle (created by open_wallet)
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// batch_handle: staged batch handle
+#[no_mangle]
+pub extern fn indy_wallet_batch_begin(command_handle: CommandHandle,
+                                      wallet_handle: WalletHandle,
+                                      cb: Option<extern fn(command_handle_: CommandHandle, err: ErrorCode,
+                                                           batch_handle: IndyHandle)>) -> ErrorCode {
+    trace!("indy_wallet_batch_begin: >>> wallet_handle: {:?}", wallet_handle);
+
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam3);
+
+    trace!("indy_wallet_batch_begin: entities >>> wallet_handle: {:?}", wallet_handle);
+
+    let result = CommandExecutor::instance()
+        .send(Command::NonSecrets(
+            NonSecretsCommand::BeginBatch(
+                wallet_handle,
+                Box::new(move |result| {
+                    let (err, batch_handle) = prepare_result_1!(result, 0);
+                    trace!("indy_wallet_batch_begin: batch_handle: {:?}", batch_handle);
+                    cb(command_handle, err, batch_handle)
+                })
+            )));
+
+    let res = prepare_result!(result);
+
+    trace!("indy_wallet_batch_begin: <<< res: {:?}", res);
+
+    res
+}
+
+/// Stage non-secret record operations to the batch started by indy_wallet_batch_begin.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context
+/// batch_handle: staged batch handle (created by indy_wallet_batch_begin)
+/// operations_json: the list of operations to add to the batch in the same format as for indy_wallet_batch
+/// cb: Callback that takes command result as parameter.
+#[no_mangle]
+pub extern fn indy_wallet_batch_stage(command_handle: CommandHandle,
+                                      batch_handle: IndyHandle,
+                                      operations_json: *const c_char,
+                                      cb: Option<extern fn(command_handle_: CommandHandle, err: ErrorCode)>) -> ErrorCode {
+    trace!("indy_wallet_batch_stage: >>> batch_handle: {:?}, operations_json: {:?}", batch_handle, operations_json);
+
+    check_useful_c_str!(operations_json, ErrorCode::CommonInvalidParam3);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
+
+    trace!("indy_wallet_batch_stage: entities >>> batch_handle: {:?}, operations_json: {:?}", batch_handle, operations_json);
+
+    let result = CommandExecutor::instance()
+        .send(Command::NonSecrets(
+            NonSecretsCommand::StageBatch(
+                batch_handle,
+                operations_json,
+                Box::new(move |result| {
+                    let err = prepare_result!(result);
+                    trace!("indy_wallet_batch_stage:");
+                    cb(command_handle, err)
+                })
+            )));
+
+    let res = prepare_result!(result);
+
+    trace!("indy_wallet_batch_stage: <<< res: {:?}", res);
+
+    res
+}
+
+/// Apply all staged operations of the batch as a single atomic unit.
+///
+/// Either all operations are stored or, if any of them fails, the wallet is left unchanged.
+/// Batch handle is released in both cases.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context
+/// batch_handle: staged batch handle (created by indy_wallet_batch_begin)
+/// cb: Callback that takes command result as parameter.
+#[no_mangle]
+pub extern fn indy_wallet_batch_commit(command_handle: CommandHandle,
+                                       batch_handle: IndyHandle,
+                                       cb: Option<extern fn(command_handle_: CommandHandle, err: ErrorCode)>) -> ErrorCode {
+    trace!("indy_wallet_batch_commit: >>> batch_handle: {:?}", batch_handle);
+
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam3);
+
+    trace!("indy_wallet_batch_commit: entities >>> batch_handle: {:?}", batch_handle);
+
+    let result = CommandExecutor::instance()
+        .send(Command::NonSecrets(
+            NonSecretsCommand::CommitBatch(
+                batch_handle,
+                Box::new(move |result| {
+                    let err = prepare_result!(result);
+                    trace!("indy_wallet_batch_commit:");
+                    cb(command_handle, err)
+                })
+            )));
+
+    let res = prepare_result!(result);
+
+    trace!("indy_wallet_batch_commit: <<< res: {:?}", res);
+
+    res
+}
+
+/// Discard all staged operations of the batch and release batch handle.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context
+/// batch_handle: staged batch handle (created by indy_wallet_batch_begin)
+/// cb: Callback that takes command result as parameter.
+#[no_mangle]
+pub extern fn indy_wallet_batch_rollback(command_handle: CommandHandle,
+                                         batch_handle: IndyHandle,
+                                         cb: Option<extern fn(command_handle_: CommandHandle, err: ErrorCode)>) -> ErrorCode {
+    trace!("indy_wallet_batch_rollback: >>> batch_handle: {:?}", batch_handle);
+
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam3);
+
+    trace!("indy_wallet_batch_rollback: entities >>> batch_handle: {:?}", batch_handle);
+
+    let result = CommandExecutor::instance()
+        .send(Command::NonSecrets(
+            NonSecretsCommand::RollbackBatch(
+                batch_handle,
+                Box::new(move |result| {
+                    let err = prepare_result!(result);
+                    trace!("indy_wallet_batch_rollback:");
+                    cb(command_handle, err)
+                })
+            )));
+
+    let res = prepare_result!(result);
+
+    trace!("indy_wallet_batch_rollback: <<< res: {:?}", res);
+
+    res
+}
+
+/// Delete all expired non-secret records of the wallet
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context
+/// wallet_handle: wallet handle (created by open_wallet)
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// purged_count: count of deleted records
+#[no_mangle]
+pub extern fn indy_purge_expired_wallet_records(command_handle: CommandHandle,
+                                                wallet_handle: WalletHandle,
+                                                cb: Option<extern fn(command_handle_: CommandHandle, err: ErrorCode,
+                                                                     purged_count: usize)>) -> ErrorCode {
+    trace!("indy_purge_expired_wallet_records: >>> wallet_handle: {:?}", wallet_handle);
+
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam3);
+
+    trace!("indy_purge_expired_wallet_records: entities >>> wallet_handle: {:?}", wallet_handle);
+
+    let result = CommandExecutor::instance()
+        .send(Command::NonSecrets(
+            NonSecretsCommand::PurgeExpiredRecords(
+                wallet_handle,
+                Box::new(move |result| {
+                    let (err, purged_count) = prepare_result_1!(result, 0);
+                    trace!("indy_purge_expired_wallet_records: purged_count: {:?}", purged_count);
+                    cb(command_handle, err, purged_count)
+                })
+            )));
+
+    let res = prepare_result!(result);
+
+    trace!("indy_purge_expired_wallet_records: <<< res: {:?}", res);
+
+    res
+}
+
+/// Count wallet records matching the query per value of the tag.
+/// Records without the tag are not counted.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context
+/// wallet_handle: wallet handle (created by open_wallet)
+/// type_: allows to separate different record types collections
+/// query_json: MongoDB style query to wallet record tags (see indy_open_wallet_search)
+/// tag_name: the name of the tag to aggregate values of ("~tagName" for un-encrypted tag)
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// counts_json: tag values mapped to the count of records, ordered by value:
+/// {
+///   "tagValue1": <int>,
+///   "tagValue2": <int>,
+/// }
+#[no_mangle]
+pub extern fn indy_wallet_count_records_by_tag(command_handle: CommandHandle,
+                                               wallet_handle: WalletHandle,
+                                               type_: *const c_char,
+                                               query_json: *const c_char,
+                                               tag_name: *const c_char,
+                                               cb: Option<extern fn(command_handle_: CommandHandle, err: ErrorCode,
+                                                                    counts_json: *const c_char)>) -> ErrorCode {
+    trace!("indy_wallet_count_records_by_tag: >>> wallet_handle: {:?}, type_: {:?}, query_json: {:?}, tag_name: {:?}", wallet_handle, type_, query_json, tag_name);
+
+    check_useful_c_str!(type_, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(query_json, ErrorCode::CommonInvalidParam4);
+    check_useful_c_str!(tag_name, ErrorCode::CommonInvalidParam5);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam6);
+
+    trace!("indy_wallet_count_records_by_tag: entities >>> wallet_handle: {:?}, type_: {:?}, query_json: {:?}, tag_name: {:?}", wallet_handle, type_, query_json, tag_name);
+
+    let result = CommandExecutor::instance()
+        .send(Command::NonSecrets(
+            NonSecretsCommand::CountRecordsByTag(
+                wallet_handle,
+                type_,
+                query_json,
+                tag_name,
+                boxed_callback_string!("indy_wallet_count_records_by_tag", cb, command_handle)
+            )));
+
+    let res = prepare_result!(result);
+
+    trace!("indy_wallet_count_records_by_tag: <<< res: {:?}", res);
+
+    res
+}
+
+/// Get the sorted set of distinct values of the tag over wallet records matching the query.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context
+/// wallet_handle: wallet handle (created by open_wallet)
+/// type_: allows to separate different record types collections
+/// query_json: MongoDB style query to wallet record tags (see indy_open_wallet_search)
+/// tag_name: the name of the tag to aggregate values of ("~tagName" for un-encrypted tag)
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// values_json: ["tagValue1", "tagValue2"]
+#[no_mangle]
+pub extern fn indy_wallet_get_distinct_tag_values(command_handle: CommandHandle,
+                                                  wallet_handle: WalletHandle,
+                                                  type_: *const c_char,
+                                                  query_json: *const c_char,
+                                                  tag_name: *const c_char,
+                                                  cb: Option<extern fn(command_handle_: CommandHandle, err: ErrorCode,
+                                                                       values_json: *const c_char)>) -> ErrorCode {
+    trace!("indy_wallet_get_distinct_tag_values: >>> wallet_handle: {:?}, type_: {:?}, query_json: {:?}, tag_name: {:?}", wallet_handle, type_, query_json, tag_name);
+
+    check_useful_c_str!(type_, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(query_json, ErrorCode::CommonInvalidParam4);
+    check_useful_c_str!(tag_name, ErrorCode::CommonInvalidParam5);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam6);
+
+    trace!("indy_wallet_get_distinct_tag_values: entities >>> wallet_handle: {:?}, type_: {:?}, query_json: {:?}, tag_name: {:?}", wallet_handle, type_, query_json, tag_name);
+
+    let result = CommandExecutor::instance()
+        .send(Command::NonSecrets(
+            NonSecretsCommand::GetDistinctRecordTagValues(
+                wallet_handle,
+                type_,
+                query_json,
+                tag_name,
+                boxed_callback_string!("indy_wallet_get_distinct_tag_values", cb, command_handle)
+            )));
+
+    let res = prepare_result!(result);
+
+    trace!("indy_wallet_get_distinct_tag_values: <<< res: {:?}", res);
+
+    res
+}
+
 /// Get an wallet record by id
 ///
 /// #Params
@@ -389,6 +783,12 @@ pub  extern fn indy_get_wallet_record(command_handle: CommandHandle,
 ///    retrieveType: (optional, false by default) Retrieve record type,
 ///    retrieveValue: (optional, true by default) Retrieve record value,
 ///    retrieveTags: (optional, false by default) Retrieve record tags,
+///    sortBy: (optional) {
+///      tagName: name of un-encrypted tag ("~tagName") to order records by,
+///      order: (optional, "asc" by default) "asc" or "desc"
+///    }, Sorting isn't supported by plugged storages (WalletQueryError),
+///    offset: (optional, 0 by default) Number of records to skip,
+///    limit: (optional, no limit by default) Max number of records to return,
 ///  }
 /// #Returns
 /// search_handle: Wallet search handle that can be used later
@@ -70,6 +70,8 @@ pub extern fn indy_create_pool_ledger_config(command_handle: CommandHandle,
 /// {
 ///     "timeout": int (optional), timeout for network request (in sec).
 ///     "extended_timeout": int (optional), extended timeout for network request (in sec).
+///     "pool_ledger_probe_timeout": int (optional), pool ledger state is checked with LEDGER_STATUS on sending of a request
+///         if it wasn't checked for this timeout (in sec, 600 by default).
 ///     "preordered_nodes": array<string> -  (optional), names of nodes which will have a priority during request sending:
 ///         ["name_of_1st_prior_node",  "name_of_2nd_prior_node", .... ]
 ///         This can be useful if a user prefers querying specific nodes.
@@ -80,6 +82,9 @@ pub extern fn indy_create_pool_ledger_config(command_handle: CommandHandle,
 ///         By default Libindy sends a read requests to 2 nodes in the pool.
 ///         If response isn't received or `state proof` is invalid Libindy sends the request again but to 2 (`number_read_nodes`) * 2 = 4 nodes and so far until completion.
 ///     "socks_proxy": string (optional) - ZMQ socks proxy host name and port (example: proxy1.intranet.company.com:1080)
+///     "transport": string (optional) - name of the transport to send messages to nodes instead of ZMQ.
+///         Transport must be registered with indy_register_pool_transport before.
+///         Built-in "loopback" transport doesn't require real nodes: each node replies with the message it receives.
 /// }
 ///
 /// #Returns
@@ -123,6 +128,10 @@ pub extern fn indy_open_pool_ledger(command_handle: CommandHandle,
 
 /// Refreshes a local copy of a pool ledger and updates pool nodes connections.
 ///
+/// Note that opened pool is also refreshed in background when nodes report pool ledger
+/// bigger than the local copy or enough nodes reply with state proofs signed by unknown BLS keys.
+/// Pool ledger state is requested from nodes on sending of a request if it wasn't checked for 10 minutes.
+///
 /// #Params
 /// handle: pool handle returned by indy_open_pool_ledger
 ///
@@ -160,6 +169,58 @@ pub extern fn indy_refresh_pool_ledger(command_handle: CommandHandle,
     res
 }
 
+/// Returns health statistics collected for the nodes of opened pool.
+///
+/// Libindy scores every node by its replies to requests. Nodes that repeatedly time out,
+/// reply with NACK or return an invalid state proof are temporarily blacklisted:
+/// read requests are sent to them only after all other nodes.
+///
+/// #Params
+/// handle: pool handle returned by indy_open_pool_ledger
+///
+/// #Returns
+/// status_json: pool status json:
+/// {
+///     "nodes": [{
+///         "alias": string - node alias,
+///         "score": float - share of requests answered by the node (from 0.0 to 1.0),
+///         "blacklisted": bool - whether the node is blacklisted now,
+///         "latency_ms": int (optional) - average reply latency in milliseconds,
+///         "replies": int - count of received replies,
+///         "timeouts": int - count of timed out requests,
+///         "nacks": int - count of received NACKs and rejects,
+///         "invalid_state_proofs": int - count of replies with invalid state proof,
+///     }]
+/// }
+///
+/// #Errors
+/// Common*
+/// Ledger*
+#[no_mangle]
+pub extern fn indy_get_pool_status(command_handle: CommandHandle,
+                                   handle: PoolHandle,
+                                   cb: Option<extern fn(command_handle_: CommandHandle,
+                                                        err: ErrorCode,
+                                                        status_json: *const c_char)>) -> ErrorCode {
+    trace!("indy_get_pool_status: >>> handle: {:?}", handle);
+
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam3);
+
+    trace!("indy_get_pool_status: entities >>> handle: {:?}", handle);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Pool(PoolCommand::GetStatus(
+            handle,
+            boxed_callback_string!("indy_get_pool_status", cb, command_handle)
+        )));
+
+    let res = prepare_result!(result);
+
+    trace!("indy_get_pool_status: <<< res: {:?}", res);
+
+    res
+}
+
 /// Lists names of created pool ledgers
 ///
 /// #Params
@@ -313,3 +374,121 @@ pub extern fn indy_set_protocol_version(command_handle: CommandHandle,
 
     res
 }
+
+/// Callback type for sending message to pool node with custom transport
+///
+/// # params
+/// pool_handle: handle of the pool that sends message.
+/// node_alias: alias of the destination node.
+/// node_address: node client address from pool ledger in "tcp://<ip>:<port>" format.
+/// message: message to send "as is".
+///
+/// # return
+/// result ErrorCode
+///
+/// Note: it is called from pool worker thread and must not block.
+/// Node replies must be passed back with indy_deliver_pool_transport_reply.
+pub type CustomTransportSend = extern fn(pool_handle: PoolHandle,
+                                         node_alias: *const c_char,
+                                         node_address: *const c_char,
+                                         message: *const c_char) -> ErrorCode;
+
+/// Register transport (see type description for `CustomTransportSend`) to communicate with pool nodes
+/// without ZMQ, for example through HTTP gateway.
+///
+/// Pool uses the transport if its name is passed as "transport" in indy_open_pool_ledger config.
+/// Registering transport with the same name again replaces it for pools opened later.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// transport_name: name of the transport.
+/// send: required callback to send message to node.
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// Error code
+///
+/// #Errors
+/// Common*
+#[no_mangle]
+pub extern fn indy_register_pool_transport(command_handle: CommandHandle,
+                                           transport_name: *const c_char,
+                                           send: Option<CustomTransportSend>,
+                                           cb: Option<extern fn(command_handle_: CommandHandle,
+                                                                err: ErrorCode)>) -> ErrorCode {
+    trace!("indy_register_pool_transport: >>> transport_name: {:?}, send: {:?}", transport_name, send);
+
+    check_useful_c_str!(transport_name, ErrorCode::CommonInvalidParam2);
+    check_useful_c_callback!(send, ErrorCode::CommonInvalidParam3);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
+
+    trace!("indy_register_pool_transport: entities >>> transport_name: {:?}, send: {:?}", transport_name, send);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Pool(
+            PoolCommand::RegisterTransport(
+                transport_name,
+                send,
+                Box::new(move |result| {
+                    let err = prepare_result!(result);
+                    trace!("indy_register_pool_transport:");
+                    cb(command_handle, err)
+                })
+            )));
+
+    let res = prepare_result!(result);
+
+    trace!("indy_register_pool_transport: <<< res: {:?}", res);
+
+    res
+}
+
+/// Pass node reply received by custom transport to the pool.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// handle: pool handle passed to `CustomTransportSend` callback.
+/// node_alias: alias of the node that replied.
+/// reply: node reply "as is".
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// Error code
+///
+/// #Errors
+/// Common*
+/// Ledger*
+#[no_mangle]
+pub extern fn indy_deliver_pool_transport_reply(command_handle: CommandHandle,
+                                                handle: PoolHandle,
+                                                node_alias: *const c_char,
+                                                reply: *const c_char,
+                                                cb: Option<extern fn(command_handle_: CommandHandle,
+                                                                     err: ErrorCode)>) -> ErrorCode {
+    trace!("indy_deliver_pool_transport_reply: >>> handle: {:?}, node_alias: {:?}, reply: {:?}", handle, node_alias, reply);
+
+    check_useful_c_str!(node_alias, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(reply, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    trace!("indy_deliver_pool_transport_reply: entities >>> handle: {:?}, node_alias: {:?}, reply: {:?}", handle, node_alias, reply);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Pool(
+            PoolCommand::DeliverTransportReply(
+                handle,
+                node_alias,
+                reply,
+                Box::new(move |result| {
+                    let err = prepare_result!(result);
+                    trace!("indy_deliver_pool_transport_reply:");
+                    cb(command_handle, err)
+                })
+            )));
+
+    let res = prepare_result!(result);
+
+    trace!("indy_deliver_pool_transport_reply: <<< res: {:?}", res);
+
+    res
+}
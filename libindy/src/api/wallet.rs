@@ -1,12 +1,13 @@
 
-use indy_api_types::{ErrorCode, CommandHandle, WalletHandle, INVALID_WALLET_HANDLE};
+use indy_api_types::{ErrorCode, CommandHandle, IndyHandle, WalletHandle, INVALID_WALLET_HANDLE};
 use crate::commands::{Command, CommandExecutor};
 use crate::commands::wallet::WalletCommand;
-use indy_api_types::domain::wallet::{Config, Credentials, ExportConfig, KeyConfig};
+use indy_api_types::domain::wallet::{CheckConfig, Config, Credentials, ExportConfig, KeyConfig};
 use indy_api_types::wallet::*;
 use indy_api_types::errors::prelude::*;
 use indy_utils::ctypes;
 use indy_api_types::validation::Validatable;
+use indy_wallet::{ExportSink, ImportSource};
 
 use serde_json;
 use libc::c_char;
@@ -143,6 +144,60 @@ pub extern fn indy_register_wallet_storage(command_handle: CommandHandle,
     res
 }
 
+/// Register custom key wrap provider that protects wallet master key (KMS, HSM, OS keystore).
+///
+/// Wallets created with WRAPPED key derivation method keep random master key wrapped by the provider
+/// in the storage metadata, "key" of wallet credentials is used as id of the wrapping key.
+/// 'file' provider that keeps wrapping key unprotected in the file is registered only by builds
+/// with file_key_wrap_provider feature and is intended for testing.
+///
+/// #Params
+/// command_handle: Command handle to map callback to caller context.
+/// name: Key wrap provider name.
+/// wrap_key: Handler that wraps wallet master key with the key identified by key id
+/// unwrap_key: Handler that unwraps wallet master key with the key identified by key id
+/// free_key: Handler that allows to de-allocate keys returned by wrap_key and unwrap_key handlers
+///
+/// #Returns
+/// Error code
+#[no_mangle]
+pub extern fn indy_register_wallet_key_wrap_provider(command_handle: CommandHandle,
+                                                     name: *const c_char,
+                                                     wrap_key: Option<WalletKeyWrap>,
+                                                     unwrap_key: Option<WalletKeyUnwrap>,
+                                                     free_key: Option<WalletKeyFree>,
+                                                     cb: Option<extern fn(command_handle_: CommandHandle,
+                                                                          err: ErrorCode)>) -> ErrorCode {
+    trace!("indy_register_wallet_key_wrap_provider: >>> command_handle: {:?}, name: {:?}, cb: {:?}",
+           command_handle, name, cb);
+
+    check_useful_c_str!(name, ErrorCode::CommonInvalidParam2);
+    check_useful_c_callback!(wrap_key, ErrorCode::CommonInvalidParam3);
+    check_useful_c_callback!(unwrap_key, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(free_key, ErrorCode::CommonInvalidParam5);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam6);
+
+    trace!("indy_register_wallet_key_wrap_provider: params name: {:?}", name);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Wallet(
+            WalletCommand::RegisterKeyWrapProvider(
+                name,
+                wrap_key,
+                unwrap_key,
+                free_key,
+                Box::new(move |result| {
+                    let err = prepare_result!(result);
+                    trace!("indy_register_wallet_key_wrap_provider: cb command_handle: {:?}, err: {:?}", command_handle, err);
+                    cb(command_handle, err)
+                })
+            )));
+
+    let res = prepare_result!(result);
+    trace!("indy_register_wallet_key_wrap_provider: <<< res: {:?}", res);
+    res
+}
+
 /// Create a new secure wallet.
 ///
 /// #Params
@@ -152,6 +207,8 @@ pub extern fn indy_register_wallet_storage(command_handle: CommandHandle,
 ///         Configured storage uses this identifier to lookup exact wallet data placement.
 ///   "storage_type": optional<string>, Type of the wallet storage. Defaults to 'default'.
 ///                  'Default' storage type allows to store wallet data in the local file.
+///                  'indy_inmem' storage type keeps wallet data in process memory until the wallet is deleted.
+///                  'postgres' storage type (libindy built with postgres_storage feature) keeps wallet data in Postgres database.
 ///                  Custom storage types can be registered with indy_register_wallet_storage call.
 ///   "storage_config": optional<object>, Storage configuration json. Storage type defines set of supported keys.
 ///                     Can be optional if storage supports default configuration.
@@ -161,6 +218,9 @@ pub extern fn indy_register_wallet_storage(command_handle: CommandHandle,
 ///             Defaults to $HOME/.indy_client/wallet.
 ///             Wallet will be stored in the file {path}/{id}/sqlite.db
 ///   }
+///   "profile": optional<string>, Name of the profile to create inside already created storage of the wallet.
+///              Profile is independent logical wallet with own keys, the storage is shared with its default profile.
+///              Supported by 'default' storage type only.
 /// }
 /// credentials: Wallet credentials json
 /// {
@@ -174,6 +234,11 @@ pub extern fn indy_register_wallet_storage(command_handle: CommandHandle,
 ///                          ARGON2I_INT - derive secured wallet master key (less secured but faster)
 ///                          RAW - raw wallet key master provided (skip derivation).
 ///                                RAW keys can be generated with indy_generate_wallet_key call
+///                          WRAPPED - random wallet master key wrapped by key wrap provider, key is id of the wrapping key.
+///   "key_wrap_provider": optional<string> Name of key wrap provider used with WRAPPED key derivation method.
+///                         'file' provider (testing builds only) keeps wrapping key in the file, key is name of the file
+///                         in wrapping_keys directory of indy home.
+///                         Custom providers can be registered with indy_register_wallet_key_wrap_provider call.
 /// }
 ///
 /// #Returns
@@ -225,6 +290,8 @@ pub extern fn indy_create_wallet(command_handle: CommandHandle,
 ///             Configured storage uses this identifier to lookup exact wallet data placement.
 ///       "storage_type": optional<string>, Type of the wallet storage. Defaults to 'default'.
 ///                       'Default' storage type allows to store wallet data in the local file.
+///                       'indy_inmem' storage type keeps wallet data in process memory until the wallet is deleted.
+///                       'postgres' storage type (libindy built with postgres_storage feature) keeps wallet data in Postgres database.
 ///                       Custom storage types can be registered with indy_register_wallet_storage call.
 ///       "storage_config": optional<object>, Storage configuration json. Storage type defines set of supported keys.
 ///                         Can be optional if storage supports default configuration.
@@ -234,6 +301,17 @@ pub extern fn indy_create_wallet(command_handle: CommandHandle,
 ///                      Defaults to $HOME/.indy_client/wallet.
 ///                      Wallet will be stored in the file {path}/{id}/sqlite.db
 ///           }
+///       "read_only": optional<bool>, Open the wallet in read-only mode. Defaults to false.
+///                    Wallet can be opened in read-only mode by several handles and processes at the same time,
+///                    mutating calls return WalletReadOnlyError. Opening fails with WalletReadOnlyError
+///                    if rotation of item keys was interrupted and wasn't finished in read-write mode.
+///       "profile": optional<string>, Name of the profile created by indy_create_wallet to open.
+///                  Default profile of the storage is opened if not set.
+///       "prefix_indexes": optional<object>, Map of encrypted tag names to max prefix length (1..64) of their blind indexes.
+///                         Enables $prefix WQL operator for these tags. The map is stored in the wallet, so map
+///                         stored on previous opening is used if not set. Indexes of all records are rebuilt
+///                         on opening with other map, so opening with other map fails with WalletReadOnlyError
+///                         in read-only mode.
 ///
 ///   }
 /// credentials: Wallet credentials json
@@ -249,11 +327,17 @@ pub extern fn indy_create_wallet(command_handle: CommandHandle,
 ///                          ARGON2I_INT - derive secured wallet master key (less secured but faster)
 ///                          RAW - raw wallet key master provided (skip derivation).
 ///                                RAW keys can be generated with indy_generate_wallet_key call
+///                          WRAPPED - random wallet master key wrapped by key wrap provider, key is id of the wrapping key.
 ///       "rekey_derivation_method": optional<string> Algorithm to use for wallet rekey derivation:
 ///                          ARGON2I_MOD - derive secured wallet master rekey (used by default)
 ///                          ARGON2I_INT - derive secured wallet master rekey (less secured but faster)
 ///                          RAW - raw wallet rekey master provided (skip derivation).
 ///                                RAW keys can be generated with indy_generate_wallet_key call
+///                          WRAPPED - random wallet master rekey wrapped by key wrap provider, rekey is id of the wrapping key.
+///       "key_wrap_provider": optional<string> Name of key wrap provider used with WRAPPED key derivation method.
+///                             'file' provider (testing builds only) keeps wrapping key in the file, key is name of the file
+///                             in wrapping_keys directory of indy home.
+///                             Custom providers can be registered with indy_register_wallet_key_wrap_provider call.
 ///   }
 ///
 /// #Returns
@@ -311,6 +395,16 @@ pub extern fn indy_open_wallet(command_handle: CommandHandle,
 ///                              ARGON2I_INT - derive secured export key (less secured but faster)
 ///                              RAW - raw export key provided (skip derivation).
 ///                                RAW keys can be generated with indy_generate_wallet_key call
+///     "types": optional<[string]>, Types of records to export (all records are exported by default)
+///     "query": optional<object>, WQL query to filter exported records of listed types (requires "types")
+///     "since_revision": optional<u64>, Export only changes made after this wallet revision (delta export).
+///                       Current revision can be got with indy_get_wallet_revision call.
+///                       Delta contains changed records and ids of deleted ones, it can be applied
+///                       with indy_import_wallet_delta. Can't be combined with "query".
+///     "version": optional<int>, Version of export format (1 by default).
+///                1 - single encrypted stream, any damage breaks the rest of the export.
+///                2 - each record is authenticated separately and the trailer keeps records count,
+///                    so damaged records can be skipped by tolerant import.
 ///   }
 ///
 /// #Returns
@@ -327,7 +421,7 @@ pub extern fn indy_export_wallet(command_handle: CommandHandle,
                                                       err: ErrorCode)>) -> ErrorCode {
     trace!("indy_export_wallet: >>> wallet_handle: {:?}, export_config: {:?}", wallet_handle, export_config);
 
-    check_useful_json!(export_config, ErrorCode::CommonInvalidParam3, ExportConfig);
+    check_useful_validatable_json!(export_config, ErrorCode::CommonInvalidParam3, ExportConfig);
     check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
 
     trace!("indy_export_wallet: params wallet_handle: {:?}, export_config: {:?}", wallet_handle, secret!(&export_config));
@@ -348,6 +442,74 @@ pub extern fn indy_export_wallet(command_handle: CommandHandle,
     res
 }
 
+/// Exports opened wallet to the application provided sink instead of the file.
+/// Exported data has the same encrypted format as the file produced by indy_export_wallet.
+///
+/// #Params:
+/// wallet_handle: wallet handle returned by indy_open_wallet
+/// export_config: JSON containing settings for input operation.
+///   {
+///     "key": <string>, Key or passphrase used for wallet export key derivation.
+///                     Look to key_derivation_method param for information about supported key derivation methods.
+///     "key_derivation_method": optional<string> Algorithm to use for wallet export key derivation:
+///                              ARGON2I_MOD - derive secured export key (used by default)
+///                              ARGON2I_INT - derive secured export key (less secured but faster)
+///                              RAW - raw export key provided (skip derivation).
+///                                RAW keys can be generated with indy_generate_wallet_key call
+///     "types": optional<[string]>, Types of records to export (all records are exported by default)
+///     "query": optional<object>, WQL query to filter exported records of listed types (requires "types")
+///     "since_revision": optional<u64>, Export only changes made after this wallet revision (delta export).
+///                       Current revision can be got with indy_get_wallet_revision call.
+///                       Delta contains changed records and ids of deleted ones, it can be applied
+///                       with indy_import_wallet_delta. Can't be combined with "query".
+///     "version": optional<int>, Version of export format (1 by default).
+///                1 - single encrypted stream, any damage breaks the rest of the export.
+///                2 - each record is authenticated separately and the trailer keeps records count,
+///                    so damaged records can be skipped by tolerant import.
+///   }
+/// sink_handle: application defined handle passed to write callback
+/// write: callback that receives exported data. It is called from libindy thread
+///        several times before cb and must return Success to continue export.
+///
+/// #Returns
+/// Error code
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn indy_export_wallet_to_sink(command_handle: CommandHandle,
+                                         wallet_handle: WalletHandle,
+                                         export_config: *const c_char,
+                                         sink_handle: IndyHandle,
+                                         write: Option<WalletExportSinkWrite>,
+                                         cb: Option<extern fn(command_handle_: CommandHandle,
+                                                              err: ErrorCode)>) -> ErrorCode {
+    trace!("indy_export_wallet_to_sink: >>> wallet_handle: {:?}, export_config: {:?}, sink_handle: {:?}", wallet_handle, export_config, sink_handle);
+
+    check_useful_validatable_json!(export_config, ErrorCode::CommonInvalidParam3, ExportConfig);
+    check_useful_c_callback!(write, ErrorCode::CommonInvalidParam5);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam6);
+
+    trace!("indy_export_wallet_to_sink: params wallet_handle: {:?}, export_config: {:?}, sink_handle: {:?}", wallet_handle, secret!(&export_config), sink_handle);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Wallet(WalletCommand::ExportToSink(
+            wallet_handle,
+            export_config,
+            ExportSink::new(sink_handle, write),
+            Box::new(move |result| {
+                let err = prepare_result!(result);
+                trace!("indy_export_wallet_to_sink: cb command_handle: {:?} err: {:?}", command_handle, err);
+                cb(command_handle, err)
+            })
+        )));
+
+    let res = prepare_result!(result);
+    trace!("indy_export_wallet_to_sink: <<< res: {:?}", res);
+    res
+}
+
 
 /// Creates a new secure wallet and then imports its content
 /// according to fields provided in import_config
@@ -360,6 +522,8 @@ pub extern fn indy_export_wallet(command_handle: CommandHandle,
 ///         Configured storage uses this identifier to lookup exact wallet data placement.
 ///   "storage_type": optional<string>, Type of the wallet storage. Defaults to 'default'.
 ///                  'Default' storage type allows to store wallet data in the local file.
+///                  'indy_inmem' storage type keeps wallet data in process memory until the wallet is deleted.
+///                  'postgres' storage type (libindy built with postgres_storage feature) keeps wallet data in Postgres database.
 ///                  Custom storage types can be registered with indy_register_wallet_storage call.
 ///   "storage_config": optional<object>, Storage configuration json. Storage type defines set of supported keys.
 ///                     Can be optional if storage supports default configuration.
@@ -382,11 +546,18 @@ pub extern fn indy_export_wallet(command_handle: CommandHandle,
 ///                             ARGON2I_INT - derive secured wallet master key (less secured but faster)
 ///                             RAW - raw wallet key master provided (skip derivation).
 ///                                RAW keys can be generated with indy_generate_wallet_key call
+///                             WRAPPED - random wallet master key wrapped by key wrap provider, key is id of the wrapping key.
+///   "key_wrap_provider": optional<string> Name of key wrap provider used with WRAPPED key derivation method.
+///                         'file' provider (testing builds only) keeps wrapping key in the file, key is name of the file
+///                         in wrapping_keys directory of indy home.
+///                         Custom providers can be registered with indy_register_wallet_key_wrap_provider call.
 /// }
 /// import_config: Import settings json.
 /// {
 ///   "path": <string>, path of the file that contains exported wallet content
 ///   "key": <string>, key used for export of the wallet
+///   "tolerant": optional<bool>, skip damaged records of version 2 export instead of failing (false by default).
+///               Use indy_import_wallet_with_report to know how many records were skipped.
 /// }
 ///
 /// #Returns
@@ -407,7 +578,7 @@ pub extern fn indy_import_wallet(command_handle: CommandHandle,
 
     check_useful_validatable_json!(config, ErrorCode::CommonInvalidParam2, Config);
     check_useful_json!(credentials, ErrorCode::CommonInvalidParam3, Credentials);
-    check_useful_json!(import_config, ErrorCode::CommonInvalidParam4, ExportConfig);
+    check_useful_validatable_json!(import_config, ErrorCode::CommonInvalidParam4, ExportConfig);
     check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
 
     trace!("indy_import_wallet: params config: {:?}, credentials: {:?}, import_config: {:?}",
@@ -430,6 +601,181 @@ pub extern fn indy_import_wallet(command_handle: CommandHandle,
     res
 }
 
+/// Creates a new secure wallet and then imports its content like indy_import_wallet
+/// and returns report about imported records.
+///
+/// #Params
+/// config: Wallet configuration json (See indy_import_wallet).
+/// credentials: Wallet credentials json (See indy_import_wallet).
+/// import_config: Import settings json (See indy_import_wallet).
+///
+/// #Returns
+/// report: Import report json.
+/// {
+///   "imported": <int>, count of imported records
+///   "skipped": <int>, count of damaged records skipped by tolerant import
+///   "expected": optional<int>, count of exported records according to trailer of version 2 export
+///               (missed for version 1 export and damaged trailer)
+/// }
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn indy_import_wallet_with_report(command_handle: CommandHandle,
+                                             config: *const c_char,
+                                             credentials: *const c_char,
+                                             import_config: *const c_char,
+                                             cb: Option<extern fn(command_handle_: CommandHandle,
+                                                                  err: ErrorCode,
+                                                                  report: *const c_char)>) -> ErrorCode {
+    trace!("indy_import_wallet_with_report: >>> command_handle: {:?}, config: {:?}, credentials: {:?}, import_config: {:?}, cb: {:?}",
+           command_handle, config, credentials, import_config, cb);
+
+    check_useful_validatable_json!(config, ErrorCode::CommonInvalidParam2, Config);
+    check_useful_json!(credentials, ErrorCode::CommonInvalidParam3, Credentials);
+    check_useful_validatable_json!(import_config, ErrorCode::CommonInvalidParam4, ExportConfig);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    trace!("indy_import_wallet_with_report: params config: {:?}, credentials: {:?}, import_config: {:?}",
+           config, secret!(&credentials), secret!(&import_config));
+
+    let result = CommandExecutor::instance()
+        .send(Command::Wallet(WalletCommand::Import(
+            config,
+            credentials,
+            import_config,
+            boxed_callback_string!("indy_import_wallet_with_report", cb, command_handle)
+        )));
+
+    let res = prepare_result!(result);
+    trace!("indy_import_wallet_with_report: <<< res: {:?}", res);
+    res
+}
+
+/// Creates a new secure wallet and then imports its content from the application provided source
+/// instead of the file. Source must provide data in the format produced by indy_export_wallet
+/// or indy_export_wallet_to_sink.
+///
+/// #Params
+/// config: Wallet configuration json (See indy_import_wallet).
+/// credentials: Wallet credentials json (See indy_import_wallet).
+/// import_config: Import settings json.
+/// {
+///   "key": <string>, key used for export of the wallet
+///   "tolerant": optional<bool>, skip damaged records of version 2 export instead of failing (false by default).
+/// }
+/// source_handle: application defined handle passed to read callback
+/// read: callback that provides exported data. It is called from libindy thread
+///       several times before cb and must set 0 read length at the end of data.
+///
+/// #Returns
+/// Error code
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn indy_import_wallet_from_source(command_handle: CommandHandle,
+                                             config: *const c_char,
+                                             credentials: *const c_char,
+                                             import_config: *const c_char,
+                                             source_handle: IndyHandle,
+                                             read: Option<WalletImportSourceRead>,
+                                             cb: Option<extern fn(command_handle_: CommandHandle,
+                                                                  err: ErrorCode)>) -> ErrorCode {
+    trace!("indy_import_wallet_from_source: >>> command_handle: {:?}, config: {:?}, credentials: {:?}, import_config: {:?}, source_handle: {:?}, cb: {:?}",
+           command_handle, config, credentials, import_config, source_handle, cb);
+
+    check_useful_validatable_json!(config, ErrorCode::CommonInvalidParam2, Config);
+    check_useful_json!(credentials, ErrorCode::CommonInvalidParam3, Credentials);
+    check_useful_validatable_json!(import_config, ErrorCode::CommonInvalidParam4, ExportConfig);
+    check_useful_c_callback!(read, ErrorCode::CommonInvalidParam6);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam7);
+
+    trace!("indy_import_wallet_from_source: params config: {:?}, credentials: {:?}, import_config: {:?}, source_handle: {:?}",
+           config, secret!(&credentials), secret!(&import_config), source_handle);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Wallet(WalletCommand::ImportFromSource(
+            config,
+            credentials,
+            import_config,
+            ImportSource::new(source_handle, read),
+            Box::new(move |result| {
+                let err = prepare_result!(result);
+                trace!("indy_import_wallet_from_source: cb command_handle: {:?}, err: {:?}", command_handle, err);
+                cb(command_handle, err)
+            })
+        )));
+
+    let res = prepare_result!(result);
+    trace!("indy_import_wallet_from_source: <<< res: {:?}", res);
+    res
+}
+
+/// Creates a new wallet and copies all records of existing wallet into it.
+/// Records are streamed directly between wallet storages of the same or different types
+/// (for example from 'default' to 'postgres' or custom storage), so no export file is needed.
+///
+/// Item keys of the source wallet are kept, so records are copied in encrypted form without re-encryption.
+/// Only the new wallet master key is derived from destination credentials.
+/// Numbers of records in both wallets are compared at the end, destination wallet is deleted on failure.
+/// Source wallet is left untouched and must not be opened during migration.
+/// Note that expired records aren't migrated, expiry time of other records is kept.
+/// Wallet history isn't migrated: revisions and records of deleted items used by delta export aren't copied,
+/// so revision got from source wallet can't be used as "since_revision" for destination wallet.
+///
+/// #Params
+/// src_config: Source wallet configuration json (See indy_create_wallet).
+/// src_credentials: Source wallet credentials json (See indy_create_wallet).
+/// dst_config: Destination wallet configuration json (See indy_create_wallet).
+/// dst_credentials: Destination wallet credentials json (See indy_create_wallet).
+///
+/// #Returns
+/// Error code
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn indy_migrate_wallet(command_handle: CommandHandle,
+                                  src_config: *const c_char,
+                                  src_credentials: *const c_char,
+                                  dst_config: *const c_char,
+                                  dst_credentials: *const c_char,
+                                  cb: Option<extern fn(command_handle_: CommandHandle,
+                                                       err: ErrorCode)>) -> ErrorCode {
+    trace!("indy_migrate_wallet: >>> command_handle: {:?}, src_config: {:?}, src_credentials: {:?}, dst_config: {:?}, dst_credentials: {:?}, cb: {:?}",
+           command_handle, src_config, src_credentials, dst_config, dst_credentials, cb);
+
+    check_useful_validatable_json!(src_config, ErrorCode::CommonInvalidParam2, Config);
+    check_useful_json!(src_credentials, ErrorCode::CommonInvalidParam3, Credentials);
+    check_useful_validatable_json!(dst_config, ErrorCode::CommonInvalidParam4, Config);
+    check_useful_json!(dst_credentials, ErrorCode::CommonInvalidParam5, Credentials);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam6);
+
+    trace!("indy_migrate_wallet: params src_config: {:?}, src_credentials: {:?}, dst_config: {:?}, dst_credentials: {:?}",
+           src_config, secret!(&src_credentials), dst_config, secret!(&dst_credentials));
+
+    let result = CommandExecutor::instance()
+        .send(Command::Wallet(WalletCommand::Migrate(
+            src_config,
+            src_credentials,
+            dst_config,
+            dst_credentials,
+            Box::new(move |result| {
+                let err = prepare_result!(result);
+                trace!("indy_migrate_wallet: cb command_handle: {:?}, err: {:?}", command_handle, err);
+                cb(command_handle, err)
+            })
+        )));
+
+    let res = prepare_result!(result);
+    trace!("indy_migrate_wallet: <<< res: {:?}", res);
+    res
+}
+
 
 /// Closes opened wallet and frees allocated resources.
 ///
@@ -478,6 +824,8 @@ pub extern fn indy_close_wallet(command_handle: CommandHandle,
 ///         Configured storage uses this identifier to lookup exact wallet data placement.
 ///   "storage_type": optional<string>, Type of the wallet storage. Defaults to 'default'.
 ///                  'Default' storage type allows to store wallet data in the local file.
+///                  'indy_inmem' storage type keeps wallet data in process memory until the wallet is deleted.
+///                  'postgres' storage type (libindy built with postgres_storage feature) keeps wallet data in Postgres database.
 ///                  Custom storage types can be registered with indy_register_wallet_storage call.
 ///   "storage_config": optional<object>, Storage configuration json. Storage type defines set of supported keys.
 ///                     Can be optional if storage supports default configuration.
@@ -487,6 +835,8 @@ pub extern fn indy_close_wallet(command_handle: CommandHandle,
 ///             Defaults to $HOME/.indy_client/wallet.
 ///             Wallet will be stored in the file {path}/{id}/sqlite.db
 ///   }
+///   "profile": optional<string>, Name of the profile to delete, the storage of the wallet with all its profiles
+///              is deleted if not set.
 /// }
 /// credentials: Wallet credentials json
 /// {
@@ -500,6 +850,11 @@ pub extern fn indy_close_wallet(command_handle: CommandHandle,
 ///                             ARGON2I_INT - derive secured wallet master key (less secured but faster)
 ///                             RAW - raw wallet key master provided (skip derivation).
 ///                                RAW keys can be generated with indy_generate_wallet_key call
+///                             WRAPPED - random wallet master key wrapped by key wrap provider, key is id of the wrapping key.
+///   "key_wrap_provider": optional<string> Name of key wrap provider used with WRAPPED key derivation method.
+///                         'file' provider (testing builds only) keeps wrapping key in the file, key is name of the file
+///                         in wrapping_keys directory of indy home.
+///                         Custom providers can be registered with indy_register_wallet_key_wrap_provider call.
 /// }
 ///
 /// #Returns
@@ -539,6 +894,363 @@ pub extern fn indy_delete_wallet(command_handle: CommandHandle,
     res
 }
 
+/// Lists profiles created inside the storage of the wallet.
+///
+/// Profiles are independent logical wallets with own keys kept in one storage instance.
+/// Profile is created, opened and deleted by indy_create_wallet, indy_open_wallet and indy_delete_wallet
+/// with "profile" set in wallet config. Only 'default' storage type supports profiles.
+///
+/// #Params
+/// config: Wallet configuration json of the default profile of the storage (See indy_create_wallet), "profile" must not be set.
+/// credentials: Wallet credentials json of the default profile of the storage (See indy_create_wallet).
+///
+/// #Returns
+/// Error code
+/// profiles: json array with names of the profiles
+///   [string, ...]
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn indy_list_wallet_profiles(command_handle: CommandHandle,
+                                        config: *const c_char,
+                                        credentials: *const c_char,
+                                        cb: Option<extern fn(command_handle_: CommandHandle,
+                                                             err: ErrorCode,
+                                                             profiles: *const c_char)>) -> ErrorCode {
+    trace!("indy_list_wallet_profiles: >>> command_handle: {:?}, config: {:?}, credentials: {:?}, cb: {:?}",
+           command_handle, config, credentials, cb);
+
+    check_useful_validatable_json!(config, ErrorCode::CommonInvalidParam2, Config);
+    check_useful_json!(credentials, ErrorCode::CommonInvalidParam3, Credentials);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
+
+    trace!("indy_list_wallet_profiles: params config: {:?}, credentials: {:?}", config, secret!(&credentials));
+
+    let result = CommandExecutor::instance()
+        .send(Command::Wallet(WalletCommand::ListProfiles(
+            config,
+            credentials,
+            boxed_callback_string!("indy_list_wallet_profiles", cb, command_handle)
+        )));
+
+    let res = prepare_result!(result);
+    trace!("indy_list_wallet_profiles: <<< res: {:?}", res);
+    res
+}
+
+/// Rotates keys used for encryption of wallet records (item keys) of opened wallet.
+///
+/// Fresh item keys are generated and every record value, id, type and tags are re-encrypted with them in place.
+/// Records are processed by chunks that are stored atomically and the progress is kept in storage metadata,
+/// so if the rotation is interrupted it will be finished on the next opening of the wallet.
+/// Master key (wallet passphrase) isn't changed by this call, use "rekey" of indy_open_wallet for that.
+///
+/// #Params
+/// wallet_handle: wallet handle returned by indy_open_wallet.
+/// credentials: Wallet credentials json used to open the wallet (new item keys are protected with wallet master key)
+/// {
+///   "key": string, Key or passphrase used for wallet key derivation.
+///                  Look to key_derivation_method param for information about supported key derivation methods.
+///   "key_derivation_method": optional<string> Algorithm to use for wallet key derivation:
+///                             ARGON2I_MOD - derive secured wallet master key (used by default)
+///                             ARGON2I_INT - derive secured wallet master key (less secured but faster)
+///                             RAW - raw wallet key master provided (skip derivation).
+///                                RAW keys can be generated with indy_generate_wallet_key call
+///                             WRAPPED - random wallet master key wrapped by key wrap provider, key is id of the wrapping key.
+///   "key_wrap_provider": optional<string> Name of key wrap provider used with WRAPPED key derivation method.
+///                         'file' provider (testing builds only) keeps wrapping key in the file, key is name of the file
+///                         in wrapping_keys directory of indy home.
+///                         Custom providers can be registered with indy_register_wallet_key_wrap_provider call.
+/// }
+///
+/// #Returns
+/// Error code
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn indy_rotate_wallet_item_keys(command_handle: CommandHandle,
+                                           wallet_handle: WalletHandle,
+                                           credentials: *const c_char,
+                                           cb: Option<extern fn(command_handle_: CommandHandle,
+                                                                err: ErrorCode)>) -> ErrorCode {
+    trace!("indy_rotate_wallet_item_keys: >>> command_handle: {:?}, wallet_handle: {:?}, credentials: {:?}, cb: {:?}",
+           command_handle, wallet_handle, credentials, cb);
+
+    check_useful_json!(credentials, ErrorCode::CommonInvalidParam3, Credentials);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
+
+    trace!("indy_rotate_wallet_item_keys: params wallet_handle: {:?}, credentials: {:?}", wallet_handle, secret!(&credentials));
+
+    let result = CommandExecutor::instance()
+        .send(Command::Wallet(WalletCommand::RotateItemKeys(
+            wallet_handle,
+            credentials,
+            Box::new(move |result| {
+                let err = prepare_result!(result);
+                trace!("indy_rotate_wallet_item_keys: cb command_handle: {:?}, err: {:?}", command_handle, err);
+                cb(command_handle, err)
+            })
+        )));
+
+    let res = prepare_result!(result);
+    trace!("indy_rotate_wallet_item_keys: <<< res: {:?}", res);
+    res
+}
+
+/// Applies delta export (created by indy_export_wallet with "since_revision") to opened wallet.
+///
+/// Records changed in delta are created or replaced and records deleted since base revision are removed.
+/// Applying the same delta twice has no additional effect.
+///
+/// #Params
+/// wallet_handle: wallet handle returned by indy_open_wallet.
+/// import_config: Import settings json.
+/// {
+///   "path": <string>, path of the file that contains delta export
+///   "key": <string>, key used for export of the delta
+/// }
+///
+/// #Returns
+/// Error code
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn indy_import_wallet_delta(command_handle: CommandHandle,
+                                       wallet_handle: WalletHandle,
+                                       import_config: *const c_char,
+                                       cb: Option<extern fn(command_handle_: CommandHandle,
+                                                            err: ErrorCode)>) -> ErrorCode {
+    trace!("indy_import_wallet_delta: >>> command_handle: {:?}, wallet_handle: {:?}, import_config: {:?}, cb: {:?}",
+           command_handle, wallet_handle, import_config, cb);
+
+    check_useful_validatable_json!(import_config, ErrorCode::CommonInvalidParam3, ExportConfig);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
+
+    trace!("indy_import_wallet_delta: params wallet_handle: {:?}, import_config: {:?}", wallet_handle, secret!(&import_config));
+
+    let result = CommandExecutor::instance()
+        .send(Command::Wallet(WalletCommand::ImportDelta(
+            wallet_handle,
+            import_config,
+            Box::new(move |result| {
+                let err = prepare_result!(result);
+                trace!("indy_import_wallet_delta: cb command_handle: {:?}, err: {:?}", command_handle, err);
+                cb(command_handle, err)
+            })
+        )));
+
+    let res = prepare_result!(result);
+    trace!("indy_import_wallet_delta: <<< res: {:?}", res);
+    res
+}
+
+/// Returns current revision of opened wallet.
+///
+/// Revision is increased on every change of wallet records and can be passed as "since_revision"
+/// to indy_export_wallet for creation of delta export.
+/// Only 'default' storage type supports change tracking.
+///
+/// #Params
+/// wallet_handle: wallet handle returned by indy_open_wallet.
+///
+/// #Returns
+/// revision: current wallet revision
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn indy_get_wallet_revision(command_handle: CommandHandle,
+                                       wallet_handle: WalletHandle,
+                                       cb: Option<extern fn(command_handle_: CommandHandle,
+                                                            err: ErrorCode,
+                                                            revision: u64)>) -> ErrorCode {
+    trace!("indy_get_wallet_revision: >>> command_handle: {:?}, wallet_handle: {:?}, cb: {:?}",
+           command_handle, wallet_handle, cb);
+
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam3);
+
+    trace!("indy_get_wallet_revision: params wallet_handle: {:?}", wallet_handle);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Wallet(WalletCommand::GetRevision(
+            wallet_handle,
+            Box::new(move |result| {
+                let (err, revision) = prepare_result_1!(result, 0);
+                trace!("indy_get_wallet_revision: cb command_handle: {:?}, err: {:?}, revision: {:?}", command_handle, err, revision);
+                cb(command_handle, err, revision)
+            })
+        )));
+
+    let res = prepare_result!(result);
+    trace!("indy_get_wallet_revision: <<< res: {:?}", res);
+    res
+}
+
+/// Checks integrity of opened wallet and optionally repairs it.
+///
+/// Every wallet record is checked that its type, id, value and tags can be decrypted with the wallet keys
+/// and that value of known Indy record types (Indy::Did, Indy::Key, Indy::Credential, ...) is a valid json
+/// of corresponding object. Also storage is checked for tags that reference missing records.
+///
+/// In repair mode records that failed the check are moved to the storage quarantine (they are kept in
+/// the wallet file, but aren't visible through any wallet call) and orphan tags are removed.
+/// Repair is supported only by 'default' storage type.
+///
+/// #Params
+/// wallet_handle: wallet handle returned by indy_open_wallet.
+/// config: (optional) check configuration json.
+/// {
+///   "repair": optional<bool>, Quarantine records that failed the check and remove orphan tags (false by default)
+/// }
+///
+/// #Returns
+/// report: Check report json.
+/// {
+///   "records": <int>, count of checked records
+///   "orphan_tags": <int>, count of tags that reference missing records
+///   "problems": [{
+///       "type": <string>, record type (missed if it can't be decrypted)
+///       "id": <string>, record id (missed if it can't be decrypted)
+///       "kind": <string>, one of "type_decryption", "id_decryption", "value_decryption", "tags_decryption", "invalid_value"
+///       "message": <string>, problem description
+///       "quarantined": <bool>, whether record was moved to quarantine
+///   }],
+///   "repaired": <bool>, whether repair was performed
+/// }
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn indy_check_wallet(command_handle: CommandHandle,
+                                wallet_handle: WalletHandle,
+                                config: *const c_char,
+                                cb: Option<extern fn(command_handle_: CommandHandle,
+                                                     err: ErrorCode,
+                                                     report: *const c_char)>) -> ErrorCode {
+    trace!("indy_check_wallet: >>> command_handle: {:?}, wallet_handle: {:?}, config: {:?}, cb: {:?}",
+           command_handle, wallet_handle, config, cb);
+
+    check_useful_opt_json!(config, ErrorCode::CommonInvalidParam3, CheckConfig);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
+
+    trace!("indy_check_wallet: params wallet_handle: {:?}, config: {:?}", wallet_handle, config);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Wallet(WalletCommand::Check(
+            wallet_handle,
+            config.unwrap_or_default(),
+            boxed_callback_string!("indy_check_wallet", cb, command_handle)
+        )));
+
+    let res = prepare_result!(result);
+    trace!("indy_check_wallet: <<< res: {:?}", res);
+    res
+}
+
+/// Subscribes to changes of wallet records.
+///
+/// on_change callback is called every time a record is added, updated or deleted
+/// through any handle opened for the same wallet (including read-only ones).
+/// Changes made by indy_import_wallet_delta are reported too, records quarantined by indy_check_wallet
+/// are reported as deleted.
+/// Subscription is active until indy_wallet_unsubscribe is called or wallet_handle is closed.
+///
+/// Note that on_change is called from libindy thread, so it must not block.
+///
+/// #Params
+/// wallet_handle: wallet handle returned by indy_open_wallet.
+/// context: caller handle that will be passed to on_change callback
+/// on_change: record change callback
+///     context: context passed to indy_wallet_subscribe
+///     type_: type of changed record
+///     id: id of changed record
+///     operation: kind of change: "add", "update" or "delete"
+///
+/// #Returns
+/// subscription_handle: subscription handle (See indy_wallet_unsubscribe)
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn indy_wallet_subscribe(command_handle: CommandHandle,
+                                    wallet_handle: WalletHandle,
+                                    context: IndyHandle,
+                                    on_change: Option<WalletRecordChanged>,
+                                    cb: Option<extern fn(command_handle_: CommandHandle,
+                                                         err: ErrorCode,
+                                                         subscription_handle: IndyHandle)>) -> ErrorCode {
+    trace!("indy_wallet_subscribe: >>> command_handle: {:?}, wallet_handle: {:?}, context: {:?}, on_change: {:?}, cb: {:?}",
+           command_handle, wallet_handle, context, on_change, cb);
+
+    check_useful_c_callback!(on_change, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    trace!("indy_wallet_subscribe: params wallet_handle: {:?}, context: {:?}", wallet_handle, context);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Wallet(WalletCommand::Subscribe(
+            wallet_handle,
+            context,
+            on_change,
+            Box::new(move |result| {
+                let (err, subscription_handle) = prepare_result_1!(result, 0);
+                trace!("indy_wallet_subscribe: cb command_handle: {:?}, err: {:?}, subscription_handle: {:?}", command_handle, err, subscription_handle);
+                cb(command_handle, err, subscription_handle)
+            })
+        )));
+
+    let res = prepare_result!(result);
+    trace!("indy_wallet_subscribe: <<< res: {:?}", res);
+    res
+}
+
+/// Cancels subscription to changes of wallet records.
+///
+/// #Params
+/// subscription_handle: subscription handle returned by indy_wallet_subscribe.
+///
+/// #Returns
+/// err: Error code
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn indy_wallet_unsubscribe(command_handle: CommandHandle,
+                                      subscription_handle: IndyHandle,
+                                      cb: Option<extern fn(command_handle_: CommandHandle,
+                                                           err: ErrorCode)>) -> ErrorCode {
+    trace!("indy_wallet_unsubscribe: >>> command_handle: {:?}, subscription_handle: {:?}, cb: {:?}",
+           command_handle, subscription_handle, cb);
+
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam3);
+
+    trace!("indy_wallet_unsubscribe: params subscription_handle: {:?}", subscription_handle);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Wallet(WalletCommand::Unsubscribe(
+            subscription_handle,
+            Box::new(move |result| {
+                let err = prepare_result!(result);
+                trace!("indy_wallet_unsubscribe: cb command_handle: {:?}, err: {:?}", command_handle, err);
+                cb(command_handle, err)
+            })
+        )));
+
+    let res = prepare_result!(result);
+    trace!("indy_wallet_unsubscribe: <<< res: {:?}", res);
+    res
+}
+
 /// Generate wallet master key.
 /// Returned key is compatible with "RAW" key derivation method.
 /// It allows to avoid expensive key derivation for use cases when wallet keys can be stored in a secure enclave.
@@ -0,0 +1,259 @@
+//! Rust-native async wallet API for applications linking libindy as rlib.
+//!
+//! Functions call `indy_wallet::WalletService` directly without libindy command thread
+//! and deliver results through `std::future::Future`s instead of C callbacks,
+//! so no `CommandHandle` is needed. The service is owned by the wallet thread of
+//! `indy_wallet::WalletServiceHandle`, returned futures are `Send` and can be awaited
+//! from any executor (tokio, async-std, `indy_utils::future::block_on`).
+//!
+//! Wallets opened by these functions are separate from wallets opened by C API,
+//! so their handles can be used only with functions of this module.
+
+use indy_api_types::WalletHandle;
+use indy_api_types::errors::prelude::*;
+use indy_api_types::validation::Validatable;
+use indy_wallet::{WalletRecord, WalletService, WalletServiceHandle};
+
+pub use indy_api_types::domain::wallet::{Config, Credentials, ExportConfig, Tags};
+pub use indy_wallet::ImportReport;
+
+lazy_static! {
+    static ref WALLET_SERVICE: WalletServiceHandle = WalletServiceHandle::new();
+}
+
+fn _validate<T: Validatable>(value: &T) -> IndyResult<()> {
+    value.validate()
+        .map_err(|err| err_msg(IndyErrorKind::InvalidStructure, err))
+}
+
+fn _check_type(type_: &str) -> IndyResult<()> {
+    if type_.starts_with(WalletService::PREFIX) {
+        return Err(err_msg(IndyErrorKind::WalletAccessFailed, format!("Record of type \"{}\" is not available for fetching", type_)));
+    }
+    Ok(())
+}
+
+/// Creates a new secure wallet. See `indy_create_wallet` for config and credentials format.
+pub async fn create_wallet(config: &Config, credentials: &Credentials) -> IndyResult<()> {
+    _validate(config)?;
+
+    WALLET_SERVICE.create_wallet(config, credentials).await
+}
+
+/// Opens the wallet. See `indy_open_wallet` for config and credentials format.
+pub async fn open_wallet(config: &Config, credentials: &Credentials) -> IndyResult<WalletHandle> {
+    _validate(config)?;
+
+    WALLET_SERVICE.open_wallet(config, credentials).await
+}
+
+/// Closes opened wallet and frees allocated resources.
+pub async fn close_wallet(wallet_handle: WalletHandle) -> IndyResult<()> {
+    WALLET_SERVICE.close_wallet(wallet_handle).await
+}
+
+/// Deletes created wallet. See `indy_delete_wallet` for config and credentials format.
+pub async fn delete_wallet(config: &Config, credentials: &Credentials) -> IndyResult<()> {
+    _validate(config)?;
+
+    WALLET_SERVICE.delete_wallet(config, credentials).await
+}
+
+/// Lists names of profiles created in the storage of the wallet.
+pub async fn list_wallet_profiles(config: &Config, credentials: &Credentials) -> IndyResult<Vec<String>> {
+    _validate(config)?;
+
+    WALLET_SERVICE.list_profiles(config, credentials).await
+}
+
+/// Exports opened wallet to the file. See `indy_export_wallet` for export config format.
+pub async fn export_wallet(wallet_handle: WalletHandle, export_config: &ExportConfig) -> IndyResult<()> {
+    _validate(export_config)?;
+
+    WALLET_SERVICE.export_wallet(wallet_handle, export_config).await
+}
+
+/// Creates a new wallet and imports content of the file exported by `export_wallet` into it.
+pub async fn import_wallet(config: &Config, credentials: &Credentials, import_config: &ExportConfig) -> IndyResult<ImportReport> {
+    _validate(config)?;
+    _validate(import_config)?;
+
+    WALLET_SERVICE.import_wallet(config, credentials, import_config).await
+}
+
+/// Applies delta exported by `export_wallet` with "since_revision" to opened wallet.
+pub async fn import_wallet_delta(wallet_handle: WalletHandle, import_config: &ExportConfig) -> IndyResult<()> {
+    _validate(import_config)?;
+
+    WALLET_SERVICE.import_wallet_delta(wallet_handle, import_config).await
+}
+
+/// Copies the closed wallet into a new wallet, possibly of other storage type.
+/// Returns count of migrated records.
+pub async fn migrate_wallet(src_config: &Config,
+                            src_credentials: &Credentials,
+                            dst_config: &Config,
+                            dst_credentials: &Credentials) -> IndyResult<usize> {
+    _validate(src_config)?;
+    _validate(dst_config)?;
+
+    WALLET_SERVICE.migrate_wallet(src_config, src_credentials, dst_config, dst_credentials).await
+}
+
+/// Re-encrypts records of opened wallet with new item keys.
+pub async fn rotate_wallet_item_keys(wallet_handle: WalletHandle, credentials: &Credentials) -> IndyResult<()> {
+    WALLET_SERVICE.rotate_item_keys(wallet_handle, credentials).await
+}
+
+/// Returns current revision of opened wallet.
+pub async fn get_wallet_revision(wallet_handle: WalletHandle) -> IndyResult<u64> {
+    WALLET_SERVICE.get_revision(wallet_handle).await
+}
+
+/// Creates a new non-secret record. See `indy_add_wallet_record` for tags format.
+pub async fn add_wallet_record(wallet_handle: WalletHandle, type_: &str, id: &str, value: &str, tags: Option<&Tags>, expires_at: Option<u64>) -> IndyResult<()> {
+    _check_type(type_)?;
+
+    WALLET_SERVICE.add_record(wallet_handle, type_, id, value, tags.unwrap_or(&Tags::new()), expires_at).await
+}
+
+/// Updates value of non-secret record.
+pub async fn update_wallet_record_value(wallet_handle: WalletHandle, type_: &str, id: &str, value: &str) -> IndyResult<()> {
+    _check_type(type_)?;
+
+    WALLET_SERVICE.update_record_value(wallet_handle, type_, id, value).await
+}
+
+/// Replaces all tags of non-secret record.
+pub async fn update_wallet_record_tags(wallet_handle: WalletHandle, type_: &str, id: &str, tags: &Tags) -> IndyResult<()> {
+    _check_type(type_)?;
+
+    WALLET_SERVICE.update_record_tags(wallet_handle, type_, id, tags).await
+}
+
+/// Adds new tags to non-secret record, existing tags with the same names are replaced.
+pub async fn add_wallet_record_tags(wallet_handle: WalletHandle, type_: &str, id: &str, tags: &Tags) -> IndyResult<()> {
+    _check_type(type_)?;
+
+    WALLET_SERVICE.add_record_tags(wallet_handle, type_, id, tags).await
+}
+
+/// Deletes tags of non-secret record.
+pub async fn delete_wallet_record_tags(wallet_handle: WalletHandle, type_: &str, id: &str, tag_names: &[&str]) -> IndyResult<()> {
+    _check_type(type_)?;
+
+    WALLET_SERVICE.delete_record_tags(wallet_handle, type_, id, tag_names).await
+}
+
+/// Deletes non-secret record.
+pub async fn delete_wallet_record(wallet_handle: WalletHandle, type_: &str, id: &str) -> IndyResult<()> {
+    _check_type(type_)?;
+
+    WALLET_SERVICE.delete_record(wallet_handle, type_, id).await
+}
+
+/// Returns non-secret record. See `indy_get_wallet_record` for options format.
+pub async fn get_wallet_record(wallet_handle: WalletHandle, type_: &str, id: &str, options_json: &str) -> IndyResult<WalletRecord> {
+    _check_type(type_)?;
+
+    WALLET_SERVICE.get_record(wallet_handle, type_, id, options_json).await
+}
+
+/// Searches non-secret records and returns total count (if it's requested by options) and found records.
+/// See `indy_open_wallet_search` for query and options format, "offset" and "limit" of options
+/// should be used to page through big results.
+pub async fn search_wallet_records(wallet_handle: WalletHandle, type_: &str, query_json: &str, options_json: &str) -> IndyResult<(Option<usize>, Vec<WalletRecord>)> {
+    _check_type(type_)?;
+
+    WALLET_SERVICE.search_records(wallet_handle, type_, query_json, options_json).await
+}
+
+#[cfg(test)]
+mod tests {
+    use indy_api_types::INVALID_WALLET_HANDLE;
+    use indy_api_types::domain::wallet::KeyDerivationMethod;
+    use indy_utils::future::block_on;
+    use indy_utils::test;
+
+    use super::*;
+
+    fn _config(name: &str) -> Config {
+        serde_json::from_value(json!({"id": name})).unwrap()
+    }
+
+    fn _credentials() -> Credentials {
+        Credentials {
+            key: "6nxtSiXFvBd593Y2DCed2dYvRY1PGK9WMtxCBjLzKgbw".to_string(),
+            rekey: None,
+            storage_credentials: None,
+            key_derivation_method: KeyDerivationMethod::RAW,
+            rekey_derivation_method: KeyDerivationMethod::RAW,
+            key_wrap_provider: None,
+        }
+    }
+
+    #[test]
+    fn wallet_async_api_works() {
+        test::cleanup_wallet("wallet_async_api_works");
+
+        let config = _config("wallet_async_api_works");
+        let credentials = _credentials();
+
+        block_on(async {
+            create_wallet(&config, &credentials).await.unwrap();
+
+            let wallet_handle = open_wallet(&config, &credentials).await.unwrap();
+            get_wallet_revision(wallet_handle).await.unwrap();
+
+            add_wallet_record(wallet_handle, "type", "id1", "value1", None, None).await.unwrap();
+            add_wallet_record_tags(wallet_handle, "type", "id1", &serde_json::from_value(json!({"tag1": "a"})).unwrap()).await.unwrap();
+            update_wallet_record_value(wallet_handle, "type", "id1", "value2").await.unwrap();
+
+            rotate_wallet_item_keys(wallet_handle, &credentials).await.unwrap();
+
+            let record = get_wallet_record(wallet_handle, "type", "id1", "{}").await.unwrap();
+            assert_eq!(Some("value2"), record.get_value());
+
+            let (_, records) = search_wallet_records(wallet_handle, "type", r#"{"tag1": "a"}"#, "{}").await.unwrap();
+            assert_eq!(1, records.len());
+
+            delete_wallet_record(wallet_handle, "type", "id1").await.unwrap();
+
+            let res = get_wallet_record(wallet_handle, "type", "id1", "{}").await;
+            assert_eq!(IndyErrorKind::WalletItemNotFound, res.unwrap_err().kind());
+
+            let res = delete_wallet(&config, &credentials).await;
+            assert_eq!(IndyErrorKind::InvalidState, res.unwrap_err().kind());
+
+            close_wallet(wallet_handle).await.unwrap();
+
+            assert!(list_wallet_profiles(&config, &credentials).await.unwrap().is_empty());
+
+            delete_wallet(&config, &credentials).await.unwrap();
+
+            let res = open_wallet(&config, &credentials).await;
+            assert_eq!(IndyErrorKind::WalletNotFound, res.unwrap_err().kind());
+        });
+
+        test::cleanup_wallet("wallet_async_api_works");
+    }
+
+    #[test]
+    fn wallet_async_api_works_for_send_futures() {
+        fn _assert_send<T: Send>(_: T) {}
+
+        let config = _config("wallet_async_api_works_for_send_futures");
+        let credentials = _credentials();
+
+        _assert_send(create_wallet(&config, &credentials));
+        _assert_send(search_wallet_records(INVALID_WALLET_HANDLE, "type", "{}", "{}"));
+    }
+
+    #[test]
+    fn wallet_async_api_works_for_invalid_config() {
+        let config = _config("");
+
+        let res = block_on(create_wallet(&config, &_credentials()));
+        assert_eq!(IndyErrorKind::InvalidStructure, res.unwrap_err().kind());
+    }
+}
@@ -78,6 +78,7 @@ pub enum ProverCommand {
     SearchCredentials(
         WalletHandle,
         Option<String>, // query json
+        Option<String>, // options json
         Box<dyn Fn(IndyResult<(SearchHandle, usize)>) + Send>),
     FetchCredentials(
         SearchHandle,
@@ -86,6 +87,16 @@ pub enum ProverCommand {
     CloseCredentialsSearch(
         SearchHandle,
         Box<dyn Fn(IndyResult<()>) + Send>),
+    CountCredentialsByTag(
+        WalletHandle,
+        Option<String>, // query json
+        String, // tag name
+        Box<dyn Fn(IndyResult<String>) + Send>),
+    GetDistinctCredentialTagValues(
+        WalletHandle,
+        Option<String>, // query json
+        String, // tag name
+        Box<dyn Fn(IndyResult<String>) + Send>),
     GetCredentialsForProofReq(
         WalletHandle,
         ProofRequest, // proof request
@@ -210,9 +221,9 @@ impl ProverCommandExecutor {
                 debug!(target: "prover_command_executor", "DeleteCredential command received");
                 cb(self.delete_credential(wallet_handle, &cred_id));
             }
-            ProverCommand::SearchCredentials(wallet_handle, query_json, cb) => {
+            ProverCommand::SearchCredentials(wallet_handle, query_json, options_json, cb) => {
                 debug!(target: "prover_command_executor", "SearchCredentials command received");
-                cb(self.search_credentials(wallet_handle, query_json.as_ref().map(String::as_str)));
+                cb(self.search_credentials(wallet_handle, query_json.as_ref().map(String::as_str), options_json.as_ref().map(String::as_str)));
             }
             ProverCommand::FetchCredentials(search_handle, count, cb) => {
                 debug!(target: "prover_command_executor", "FetchCredentials command received");
@@ -222,6 +233,14 @@ impl ProverCommandExecutor {
                 debug!(target: "prover_command_executor", "CloseCredentialsSearch command received");
                 cb(self.close_credentials_search(search_handle));
             }
+            ProverCommand::CountCredentialsByTag(wallet_handle, query_json, tag_name, cb) => {
+                debug!(target: "prover_command_executor", "CountCredentialsByTag command received");
+                cb(self.count_credentials_by_tag(wallet_handle, query_json.as_ref().map(String::as_str), &tag_name));
+            }
+            ProverCommand::GetDistinctCredentialTagValues(wallet_handle, query_json, tag_name, cb) => {
+                debug!(target: "prover_command_executor", "GetDistinctCredentialTagValues command received");
+                cb(self.get_distinct_credential_tag_values(wallet_handle, query_json.as_ref().map(String::as_str), &tag_name));
+            }
             ProverCommand::GetCredentialsForProofReq(wallet_handle, proof_req, cb) => {
                 debug!(target: "prover_command_executor", "GetCredentialsForProofReq command received");
                 cb(self.get_credentials_for_proof_req(wallet_handle, &proof_req));
@@ -460,11 +479,12 @@ impl ProverCommandExecutor {
 
     fn search_credentials(&self,
                           wallet_handle: WalletHandle,
-                          query_json: Option<&str>) -> IndyResult<(SearchHandle, usize)> {
-        debug!("search_credentials >>> wallet_handle: {:?}, query_json: {:?}", wallet_handle, query_json);
+                          query_json: Option<&str>,
+                          options_json: Option<&str>) -> IndyResult<(SearchHandle, usize)> {
+        debug!("search_credentials >>> wallet_handle: {:?}, query_json: {:?}, options_json: {:?}", wallet_handle, query_json, options_json);
 
         let credentials_search =
-            self.wallet_service.search_indy_records::<Credential>(wallet_handle, query_json.unwrap_or("{}"), &SearchOptions::id_value())?;
+            self.wallet_service.search_indy_records::<Credential>(wallet_handle, query_json.unwrap_or("{}"), &SearchOptions::id_value_with_paging(options_json)?)?;
 
         let total_count = credentials_search.get_total_count()?.unwrap_or(0);
 
@@ -479,6 +499,38 @@ impl ProverCommandExecutor {
         Ok(res)
     }
 
+    fn count_credentials_by_tag(&self,
+                                wallet_handle: WalletHandle,
+                                query_json: Option<&str>,
+                                tag_name: &str) -> IndyResult<String> {
+        debug!("count_credentials_by_tag >>> wallet_handle: {:?}, query_json: {:?}, tag_name: {:?}", wallet_handle, query_json, tag_name);
+
+        let counts = self.wallet_service.count_indy_records_by_tag::<Credential>(wallet_handle, query_json.unwrap_or("{}"), tag_name)?;
+
+        let res = serde_json::to_string(&counts)
+            .to_indy(IndyErrorKind::InvalidState, "Cannot serialize credential tag value counts")?;
+
+        trace!("count_credentials_by_tag <<< res: {:?}", res);
+
+        Ok(res)
+    }
+
+    fn get_distinct_credential_tag_values(&self,
+                                          wallet_handle: WalletHandle,
+                                          query_json: Option<&str>,
+                                          tag_name: &str) -> IndyResult<String> {
+        debug!("get_distinct_credential_tag_values >>> wallet_handle: {:?}, query_json: {:?}, tag_name: {:?}", wallet_handle, query_json, tag_name);
+
+        let values = self.wallet_service.get_distinct_indy_record_tag_values::<Credential>(wallet_handle, query_json.unwrap_or("{}"), tag_name)?;
+
+        let res = serde_json::to_string(&values)
+            .to_indy(IndyErrorKind::InvalidState, "Cannot serialize credential tag values")?;
+
+        trace!("get_distinct_credential_tag_values <<< res: {:?}", res);
+
+        Ok(res)
+    }
+
     fn fetch_credentials(&self,
                          search_handle: SearchHandle,
                          count: usize, ) -> IndyResult<String> {
@@ -179,7 +179,7 @@ impl CacheCommandExecutor {
             };
             tags.insert("timestamp".to_string(), ts.to_string());
             let _ignore = self.wallet_service.delete_record(wallet_handle, which_cache, &schema_id);
-            self.wallet_service.add_record(wallet_handle, which_cache, &schema_id, &schema_json, &tags)?
+            self.wallet_service.add_record(wallet_handle, which_cache, &schema_id, &schema_json, &tags, None)?
         }
         Ok(())
     }
@@ -4,9 +4,9 @@ use std::rc::Rc;
 
 use indy_api_types::domain::wallet::Tags;
 use indy_api_types::errors::prelude::*;
-use indy_wallet::{RecordOptions, SearchOptions, WalletRecord, WalletSearch, WalletService};
+use indy_wallet::{RecordOptions, SearchOptions, WalletOperation, WalletRecord, WalletSearch, WalletService};
 use indy_utils::next_search_handle;
-use indy_api_types::{WalletHandle, SearchHandle};
+use indy_api_types::{IndyHandle, WalletHandle, SearchHandle};
 
 
 pub enum NonSecretsCommand {
@@ -15,6 +15,7 @@ pub enum NonSecretsCommand {
               String, // id
               String, // value
               Option<Tags>, //tags
+              Option<u64>, // expires at
               Box<dyn Fn(IndyResult<()>) + Send>),
     UpdateRecordValue(WalletHandle,
                       String, // type
@@ -40,6 +41,20 @@ pub enum NonSecretsCommand {
                  String, // type
                  String, // id
                  Box<dyn Fn(IndyResult<()>) + Send>),
+    Batch(WalletHandle,
+          String, // operations json
+          Box<dyn Fn(IndyResult<()>) + Send>),
+    BeginBatch(WalletHandle,
+               Box<dyn Fn(IndyResult<IndyHandle>) + Send>),
+    StageBatch(IndyHandle, // batch handle
+               String, // operations json
+               Box<dyn Fn(IndyResult<()>) + Send>),
+    CommitBatch(IndyHandle, // batch handle
+                Box<dyn Fn(IndyResult<()>) + Send>),
+    RollbackBatch(IndyHandle, // batch handle
+                  Box<dyn Fn(IndyResult<()>) + Send>),
+    PurgeExpiredRecords(WalletHandle,
+                        Box<dyn Fn(IndyResult<usize>) + Send>),
     GetRecord(WalletHandle,
               String, // type
               String, // id
@@ -56,6 +71,16 @@ pub enum NonSecretsCommand {
                            Box<dyn Fn(IndyResult<String>) + Send>),
     CloseSearch(SearchHandle, // wallet search handle
                 Box<dyn Fn(IndyResult<()>) + Send>),
+    CountRecordsByTag(WalletHandle,
+                      String, // type
+                      String, // query json
+                      String, // tag name
+                      Box<dyn Fn(IndyResult<String>) + Send>),
+    GetDistinctRecordTagValues(WalletHandle,
+                               String, // type
+                               String, // query json
+                               String, // tag name
+                               Box<dyn Fn(IndyResult<String>) + Send>),
 }
 
 pub struct NonSecretsCommandExecutor {
@@ -73,9 +98,9 @@ impl NonSecretsCommandExecutor {
 
     pub fn execute(&self, command: NonSecretsCommand) {
         match command {
-            NonSecretsCommand::AddRecord(handle, type_, id, value, tags, cb) => {
+            NonSecretsCommand::AddRecord(handle, type_, id, value, tags, expires_at, cb) => {
                 debug!(target: "non_secrets_command_executor", "AddRecord command received");
-                cb(self.add_record(handle, &type_, &id, &value, tags.as_ref()));
+                cb(self.add_record(handle, &type_, &id, &value, tags.as_ref(), expires_at));
             }
             NonSecretsCommand::UpdateRecordValue(handle, type_, id, value, cb) => {
                 debug!(target: "non_secrets_command_executor", "UpdateRecordValue command received");
@@ -97,6 +122,30 @@ impl NonSecretsCommandExecutor {
                 debug!(target: "non_secrets_command_executor", "DeleteRecord command received");
                 cb(self.delete_record(handle, &type_, &id));
             }
+            NonSecretsCommand::Batch(handle, operations_json, cb) => {
+                debug!(target: "non_secrets_command_executor", "Batch command received");
+                cb(self.batch(handle, &operations_json));
+            }
+            NonSecretsCommand::BeginBatch(handle, cb) => {
+                debug!(target: "non_secrets_command_executor", "BeginBatch command received");
+                cb(self.begin_batch(handle));
+            }
+            NonSecretsCommand::StageBatch(batch_handle, operations_json, cb) => {
+                debug!(target: "non_secrets_command_executor", "StageBatch command received");
+                cb(self.stage_batch(batch_handle, &operations_json));
+            }
+            NonSecretsCommand::CommitBatch(batch_handle, cb) => {
+                debug!(target: "non_secrets_command_executor", "CommitBatch command received");
+                cb(self.commit_batch(batch_handle));
+            }
+            NonSecretsCommand::RollbackBatch(batch_handle, cb) => {
+                debug!(target: "non_secrets_command_executor", "RollbackBatch command received");
+                cb(self.rollback_batch(batch_handle));
+            }
+            NonSecretsCommand::PurgeExpiredRecords(handle, cb) => {
+                debug!(target: "non_secrets_command_executor", "PurgeExpiredRecords command received");
+                cb(self.purge_expired_records(handle));
+            }
             NonSecretsCommand::GetRecord(handle, type_, id, options_json, cb) => {
                 debug!(target: "non_secrets_command_executor", "GetRecord command received");
                 cb(self.get_record(handle, &type_, &id, &options_json));
@@ -113,6 +162,14 @@ impl NonSecretsCommandExecutor {
                 debug!(target: "non_secrets_command_executor", "CloseSearch command received");
                 cb(self.close_search(wallet_search_handle));
             }
+            NonSecretsCommand::CountRecordsByTag(handle, type_, query_json, tag_name, cb) => {
+                debug!(target: "non_secrets_command_executor", "CountRecordsByTag command received");
+                cb(self.count_records_by_tag(handle, &type_, &query_json, &tag_name));
+            }
+            NonSecretsCommand::GetDistinctRecordTagValues(handle, type_, query_json, tag_name, cb) => {
+                debug!(target: "non_secrets_command_executor", "GetDistinctRecordTagValues command received");
+                cb(self.get_distinct_record_tag_values(handle, &type_, &query_json, &tag_name));
+            }
         };
     }
 
@@ -121,12 +178,13 @@ impl NonSecretsCommandExecutor {
                   type_: &str,
                   id: &str,
                   value: &str,
-                  tags: Option<&Tags>) -> IndyResult<()> {
-        trace!("add_record >>> wallet_handle: {:?}, type_: {:?}, id: {:?}, value: {:?}, tags: {:?}", wallet_handle, type_, id, value, tags);
+                  tags: Option<&Tags>,
+                  expires_at: Option<u64>) -> IndyResult<()> {
+        trace!("add_record >>> wallet_handle: {:?}, type_: {:?}, id: {:?}, value: {:?}, tags: {:?}, expires_at: {:?}", wallet_handle, type_, id, value, tags, expires_at);
 
         self._check_type(type_)?;
 
-        self.wallet_service.add_record(wallet_handle, type_, id, value, tags.unwrap_or(&Tags::new()))?;
+        self.wallet_service.add_record(wallet_handle, type_, id, value, tags.unwrap_or(&Tags::new()), expires_at)?;
 
         trace!("add_record <<< res: ()");
 
@@ -215,6 +273,89 @@ impl NonSecretsCommandExecutor {
         Ok(())
     }
 
+    fn batch(&self,
+             wallet_handle: WalletHandle,
+             operations_json: &str) -> IndyResult<()> {
+        trace!("batch >>> wallet_handle: {:?}, operations_json: {:?}", wallet_handle, operations_json);
+
+        let operations = self._parse_operations(operations_json)?;
+
+        self.wallet_service.batch_records(wallet_handle, &operations)?;
+
+        trace!("batch <<< res: ()");
+
+        Ok(())
+    }
+
+    fn begin_batch(&self,
+                   wallet_handle: WalletHandle) -> IndyResult<IndyHandle> {
+        trace!("begin_batch >>> wallet_handle: {:?}", wallet_handle);
+
+        let res = self.wallet_service.begin_batch(wallet_handle)?;
+
+        trace!("begin_batch <<< res: {:?}", res);
+
+        Ok(res)
+    }
+
+    fn stage_batch(&self,
+                   batch_handle: IndyHandle,
+                   operations_json: &str) -> IndyResult<()> {
+        trace!("stage_batch >>> batch_handle: {:?}, operations_json: {:?}", batch_handle, operations_json);
+
+        let operations = self._parse_operations(operations_json)?;
+
+        self.wallet_service.stage_batch(batch_handle, operations)?;
+
+        trace!("stage_batch <<< res: ()");
+
+        Ok(())
+    }
+
+    fn commit_batch(&self,
+                    batch_handle: IndyHandle) -> IndyResult<()> {
+        trace!("commit_batch >>> batch_handle: {:?}", batch_handle);
+
+        self.wallet_service.commit_batch(batch_handle)?;
+
+        trace!("commit_batch <<< res: ()");
+
+        Ok(())
+    }
+
+    fn rollback_batch(&self,
+                      batch_handle: IndyHandle) -> IndyResult<()> {
+        trace!("rollback_batch >>> batch_handle: {:?}", batch_handle);
+
+        self.wallet_service.rollback_batch(batch_handle)?;
+
+        trace!("rollback_batch <<< res: ()");
+
+        Ok(())
+    }
+
+    fn _parse_operations(&self, operations_json: &str) -> IndyResult<Vec<WalletOperation>> {
+        let operations: Vec<WalletOperation> = serde_json::from_str(operations_json)
+            .to_indy(IndyErrorKind::InvalidStructure, "Cannot deserialize batch operations")?;
+
+        for operation in operations.iter() {
+            self._check_type(operation.get_type())?;
+        }
+
+        Ok(operations)
+    }
+
+    fn purge_expired_records(&self,
+                             wallet_handle: WalletHandle) -> IndyResult<usize> {
+        trace!("purge_expired_records >>> wallet_handle: {:?}", wallet_handle);
+
+        let res = self.wallet_service.purge_expired_records(wallet_handle)?;
+
+        trace!("purge_expired_records <<< res: {:?}", res);
+
+        Ok(res)
+    }
+
     fn get_record(&self,
                   wallet_handle: WalletHandle,
                   type_: &str,
@@ -304,6 +445,44 @@ impl NonSecretsCommandExecutor {
         Ok(())
     }
 
+    fn count_records_by_tag(&self,
+                            wallet_handle: WalletHandle,
+                            type_: &str,
+                            query_json: &str,
+                            tag_name: &str) -> IndyResult<String> {
+        trace!("count_records_by_tag >>> wallet_handle: {:?}, type_: {:?}, query_json: {:?}, tag_name: {:?}", wallet_handle, type_, query_json, tag_name);
+
+        self._check_type(type_)?;
+
+        let counts = self.wallet_service.count_records_by_tag(wallet_handle, type_, query_json, tag_name)?;
+
+        let res = serde_json::to_string(&counts)
+            .to_indy(IndyErrorKind::InvalidState, "Cannot serialize tag value counts")?;
+
+        trace!("count_records_by_tag <<< res: {:?}", res);
+
+        Ok(res)
+    }
+
+    fn get_distinct_record_tag_values(&self,
+                                      wallet_handle: WalletHandle,
+                                      type_: &str,
+                                      query_json: &str,
+                                      tag_name: &str) -> IndyResult<String> {
+        trace!("get_distinct_record_tag_values >>> wallet_handle: {:?}, type_: {:?}, query_json: {:?}, tag_name: {:?}", wallet_handle, type_, query_json, tag_name);
+
+        self._check_type(type_)?;
+
+        let values = self.wallet_service.get_distinct_record_tag_values(wallet_handle, type_, query_json, tag_name)?;
+
+        let res = serde_json::to_string(&values)
+            .to_indy(IndyErrorKind::InvalidState, "Cannot serialize tag values")?;
+
+        trace!("get_distinct_record_tag_values <<< res: {:?}", res);
+
+        Ok(res)
+    }
+
     fn _check_type(&self, type_: &str) -> IndyResult<()> {
         if type_.starts_with(WalletService::PREFIX) {
             return Err(err_msg(IndyErrorKind::WalletAccessFailed, format!("Record of type \"{}\" is not available for fetching", type_)));
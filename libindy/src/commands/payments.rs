@@ -366,7 +366,7 @@ impl PaymentsCommandExecutor {
         let total_result: IndyResult<String> = match result {
             Ok(res) => {
                 //TODO: think about deleting payment_address on wallet save failure
-                self.wallet_service.add_record(wallet_handle, &self.wallet_service.add_prefix("PaymentAddress"), &res, &res, &HashMap::new()).map(|_| res)
+                self.wallet_service.add_record(wallet_handle, &self.wallet_service.add_prefix("PaymentAddress"), &res, &res, &HashMap::new(), None).map(|_| res)
                     .map_err(IndyError::from)
             }
             Err(err) => Err(err)
@@ -2,6 +2,7 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
 
+use crate::api::pool::CustomTransportSend;
 use crate::domain::ledger::request::ProtocolVersion;
 use crate::domain::pool::{PoolConfig, PoolOpenConfig};
 use indy_api_types::errors::prelude::*;
@@ -35,15 +36,30 @@ pub enum PoolCommand {
         Box<dyn Fn(IndyResult<()>) + Send>),
     RefreshAck(CommandHandle,
                IndyResult<()>),
+    GetStatus(
+        PoolHandle, // pool handle
+        Box<dyn Fn(IndyResult<String>) + Send>),
+    GetStatusAck(CommandHandle,
+                 IndyResult<String>),
     SetProtocolVersion(
         usize, // protocol version
         Box<dyn Fn(IndyResult<()>) + Send>),
+    RegisterTransport(
+        String, // transport name
+        CustomTransportSend,
+        Box<dyn Fn(IndyResult<()>) + Send>),
+    DeliverTransportReply(
+        PoolHandle, // pool handle
+        String, // node alias
+        String, // node reply
+        Box<dyn Fn(IndyResult<()>) + Send>),
 }
 
 pub struct PoolCommandExecutor {
     pool_service: Rc<PoolService>,
     close_callbacks: RefCell<HashMap<CommandHandle, Box<dyn Fn(IndyResult<()>)>>>,
     refresh_callbacks: RefCell<HashMap<CommandHandle, Box<dyn Fn(IndyResult<()>)>>>,
+    status_callbacks: RefCell<HashMap<CommandHandle, Box<dyn Fn(IndyResult<String>)>>>,
     open_callbacks: RefCell<HashMap<CommandHandle, Box<dyn Fn(IndyResult<PoolHandle>)>>>,
 }
 
@@ -53,6 +69,7 @@ impl PoolCommandExecutor {
             pool_service,
             close_callbacks: RefCell::new(HashMap::new()),
             refresh_callbacks: RefCell::new(HashMap::new()),
+            status_callbacks: RefCell::new(HashMap::new()),
             open_callbacks: RefCell::new(HashMap::new()),
         }
     }
@@ -128,10 +145,37 @@ impl PoolCommandExecutor {
                     Err(err) => { error!("{:?}", err); }
                 }
             }
+            PoolCommand::GetStatus(handle, cb) => {
+                debug!(target: "pool_command_executor", "GetStatus command received");
+                self.get_status(handle, cb);
+            }
+            PoolCommand::GetStatusAck(handle, result) => {
+                debug!(target: "pool_command_executor", "GetStatusAck command received");
+                match self.status_callbacks.try_borrow_mut() {
+                    Ok(mut cbs) => {
+                        match cbs.remove(&handle) {
+                            Some(cb) => cb(result),
+                            None => {
+                                error!("Can't process PoolCommand::GetStatusAck for handle {:?} with result {:?} - appropriate callback not found!",
+                                       handle, result);
+                            }
+                        }
+                    }
+                    Err(err) => { error!("{:?}", err); }
+                }
+            }
             PoolCommand::SetProtocolVersion(protocol_version, cb) => {
                 debug!(target: "pool_command_executor", "SetProtocolVersion command received");
                 cb(self.set_protocol_version(protocol_version));
             }
+            PoolCommand::RegisterTransport(name, send, cb) => {
+                debug!(target: "pool_command_executor", "RegisterTransport command received");
+                cb(self.register_transport(&name, send));
+            }
+            PoolCommand::DeliverTransportReply(handle, node_alias, reply, cb) => {
+                debug!(target: "pool_command_executor", "DeliverTransportReply command received");
+                cb(self.deliver_transport_reply(handle, &node_alias, &reply));
+            }
         };
     }
 
@@ -221,6 +265,24 @@ impl PoolCommandExecutor {
         debug!("refresh <<<");
     }
 
+    fn get_status(&self, handle: PoolHandle, cb: Box<dyn Fn(IndyResult<String>) + Send>) {
+        debug!("get_status >>> handle: {:?}", handle);
+
+        let result = self.pool_service.get_status(handle)
+            .and_then(|cmd_id| {
+                match self.status_callbacks.try_borrow_mut() {
+                    Ok(cbs) => Ok((cbs, cmd_id)),
+                    Err(err) => Err(err.into())
+                }
+            });
+        match result {
+            Err(err) => { cb(Err(err)); }
+            Ok((mut cbs, cmd_id)) => { cbs.insert(cmd_id, cb); }
+        };
+
+        debug!("get_status <<<");
+    }
+
     fn set_protocol_version(&self, version: usize) -> IndyResult<()> {
         debug!("set_protocol_version >>> version: {:?}", version);
 
@@ -234,4 +296,24 @@ impl PoolCommandExecutor {
 
         Ok(())
     }
+
+    fn register_transport(&self, name: &str, send: CustomTransportSend) -> IndyResult<()> {
+        debug!("register_transport >>> name: {:?}, send: {:?}", name, send);
+
+        PoolService::register_transport(name, send)?;
+
+        debug!("register_transport <<<");
+
+        Ok(())
+    }
+
+    fn deliver_transport_reply(&self, handle: PoolHandle, node_alias: &str, reply: &str) -> IndyResult<()> {
+        debug!("deliver_transport_reply >>> handle: {:?}, node_alias: {:?}, reply: {:?}", handle, node_alias, reply);
+
+        self.pool_service.deliver_transport_reply(handle, node_alias, reply)?;
+
+        debug!("deliver_transport_reply <<<");
+
+        Ok(())
+    }
 }
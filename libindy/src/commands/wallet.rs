@@ -4,14 +4,24 @@ use std::rc::Rc;
 
 use indy_api_types::wallet::*;
 use crate::commands::{Command, CommandExecutor};
-use indy_api_types::domain::wallet::{Config, Credentials, ExportConfig, KeyConfig};
+use indy_api_types::domain::wallet::{CheckConfig, Config, Credentials, ExportConfig, KeyConfig};
 use indy_api_types::errors::prelude::*;
 use crate::services::crypto::CryptoService;
-use indy_wallet::{KeyDerivationData, WalletService, Metadata};
+use crate::domain::anoncreds::credential::Credential;
+use crate::domain::anoncreds::credential_definition::CredentialDefinition;
+use crate::domain::anoncreds::master_secret::MasterSecret;
+use crate::domain::anoncreds::schema::Schema;
+use crate::domain::crypto::did::{Did, DidMetadata, TemporaryDid, TheirDid};
+use crate::domain::crypto::key::{Key, KeyMetadata};
+use crate::domain::ledger::attrib::Endpoint;
+use crate::domain::pairwise::Pairwise;
+use indy_wallet::{export_version, ExportSink, ImportSource, KeyDerivationData, WalletService, Metadata, RecordChange};
 use indy_utils::crypto::{chacha20poly1305_ietf, randombytes};
+use indy_utils::ctypes;
 use indy_utils::crypto::chacha20poly1305_ietf::Key as MasterKey;
-use indy_api_types::{WalletHandle, CallbackHandle};
+use indy_api_types::{WalletHandle, CallbackHandle, IndyHandle};
 use rust_base58::ToBase58;
+use serde::de::DeserializeOwned;
 
 type DeriveKeyResult<T> = IndyResult<T>;
 
@@ -42,6 +52,11 @@ pub enum WalletCommand {
                        WalletFetchSearchNextRecord, // fetch search next record
                        WalletFreeSearch, // free search
                        Box<dyn Fn(IndyResult<()>) + Send>),
+    RegisterKeyWrapProvider(String, // name
+                            WalletKeyWrap, // wrap key
+                            WalletKeyUnwrap, // unwrap key
+                            WalletKeyFree, // free key
+                            Box<dyn Fn(IndyResult<()>) + Send>),
     Create(Config, // config
            Credentials, // credentials
            Box<dyn Fn(IndyResult<()>) + Send>),
@@ -66,24 +81,76 @@ pub enum WalletCommand {
                    Metadata, // credentials
                    DeriveKeyResult<MasterKey>,
                    CallbackHandle),
+    ListProfiles(Config, // config
+                 Credentials, // credentials
+                 Box<dyn Fn(IndyResult<String>) + Send>), // profiles json
+    ListProfilesContinue(Config, // config
+                         Credentials, // credentials
+                         Metadata, // metadata
+                         DeriveKeyResult<MasterKey>,
+                         CallbackHandle),
     Export(WalletHandle,
            ExportConfig, // export config
            Box<dyn Fn(IndyResult<()>) + Send>),
+    ExportToSink(WalletHandle,
+                 ExportConfig, // export config
+                 ExportSink, // export sink
+                 Box<dyn Fn(IndyResult<()>) + Send>),
     ExportContinue(WalletHandle,
                    ExportConfig, // export config
+                   u32, // export version
+                   Option<ExportSink>, // export sink
                    KeyDerivationData,
                    DeriveKeyResult<MasterKey>,
                    CallbackHandle),
     Import(Config, // config
            Credentials, // credentials
            ExportConfig, // import config
-           Box<dyn Fn(IndyResult<()>) + Send>),
+           Box<dyn Fn(IndyResult<String>) + Send>), // import report json
+    ImportFromSource(Config, // config
+                     Credentials, // credentials
+                     ExportConfig, // import config
+                     ImportSource, // import source
+                     Box<dyn Fn(IndyResult<String>) + Send>), // import report json
     ImportContinue(Config, // config
                    Credentials, // credentials
                    DeriveKeyResult<(MasterKey, MasterKey)>, // derive_key_result
                    WalletHandle,
                    CallbackHandle
     ),
+    ImportDelta(WalletHandle,
+                ExportConfig, // import config
+                Box<dyn Fn(IndyResult<()>) + Send>),
+    ImportDeltaContinue(WalletHandle,
+                        DeriveKeyResult<MasterKey>,
+                        CallbackHandle),
+    Migrate(Config, // source config
+            Credentials, // source credentials
+            Config, // destination config
+            Credentials, // destination credentials
+            Box<dyn Fn(IndyResult<()>) + Send>),
+    MigrateContinue(Config, // destination config
+                    Credentials, // destination credentials
+                    DeriveKeyResult<(MasterKey, MasterKey)>, // source and destination keys
+                    WalletHandle,
+                    CallbackHandle),
+    GetRevision(WalletHandle,
+                Box<dyn Fn(IndyResult<u64>) + Send>),
+    Check(WalletHandle,
+          CheckConfig, // check config
+          Box<dyn Fn(IndyResult<String>) + Send>),
+    Subscribe(WalletHandle,
+              IndyHandle, // context
+              WalletRecordChanged, // on change
+              Box<dyn Fn(IndyResult<IndyHandle>) + Send>),
+    Unsubscribe(IndyHandle, // subscription handle
+                Box<dyn Fn(IndyResult<()>) + Send>),
+    RotateItemKeys(WalletHandle,
+                   Credentials, // credentials
+                   Box<dyn Fn(IndyResult<()>) + Send>),
+    RotateItemKeysContinue(WalletHandle,
+                           DeriveKeyResult<MasterKey>,
+                           CallbackHandle),
     GenerateKey(Option<KeyConfig>, // config
                 Box<dyn Fn(IndyResult<String>) + Send>),
     DeriveKey(KeyDerivationData,
@@ -101,7 +168,9 @@ pub struct WalletCommandExecutor {
     wallet_service: Rc<WalletService>,
     crypto_service: Rc<CryptoService>,
     open_callbacks: RefCell<HashMap<WalletHandle, Box<dyn Fn(IndyResult<WalletHandle>) + Send>>>,
-    pending_callbacks: RefCell<HashMap<CallbackHandle, Box<dyn Fn(IndyResult<()>) + Send>>>
+    pending_callbacks: RefCell<HashMap<CallbackHandle, Box<dyn Fn(IndyResult<()>) + Send>>>,
+    import_callbacks: RefCell<HashMap<CallbackHandle, Box<dyn Fn(IndyResult<String>) + Send>>>,
+    list_profiles_callbacks: RefCell<HashMap<CallbackHandle, Box<dyn Fn(IndyResult<String>) + Send>>>
 }
 
 impl WalletCommandExecutor {
@@ -110,7 +179,9 @@ impl WalletCommandExecutor {
             wallet_service,
             crypto_service,
             open_callbacks: RefCell::new(HashMap::new()),
-            pending_callbacks: RefCell::new(HashMap::new())
+            pending_callbacks: RefCell::new(HashMap::new()),
+            import_callbacks: RefCell::new(HashMap::new()),
+            list_profiles_callbacks: RefCell::new(HashMap::new())
         }
     }
 
@@ -130,6 +201,10 @@ impl WalletCommandExecutor {
                                        free_storage_metadata, search_records, search_all_records, get_search_total_count,
                                        fetch_search_next_record, free_search));
             }
+            WalletCommand::RegisterKeyWrapProvider(name, wrap_key, unwrap_key, free_key, cb) => {
+                debug!(target: "wallet_command_executor", "RegisterKeyWrapProvider command received");
+                cb(self._register_key_wrap_provider(&name, wrap_key, unwrap_key, free_key));
+            }
             WalletCommand::Create(config, credentials, cb) => {
                 debug!(target: "wallet_command_executor", "Create command received");
                 self._create(&config, &credentials, cb)
@@ -158,22 +233,78 @@ impl WalletCommandExecutor {
                 debug!(target: "wallet_command_executor", "DeleteContinue command received");
                 self._delete_continue(cb_id, &config, &credentials, &metadata, key_result)
             }
+            WalletCommand::ListProfiles(config, credentials, cb) => {
+                debug!(target: "wallet_command_executor", "ListProfiles command received");
+                self._list_profiles(&config, &credentials, cb)
+            }
+            WalletCommand::ListProfilesContinue(config, credentials, metadata, key_result, cb_id) => {
+                debug!(target: "wallet_command_executor", "ListProfilesContinue command received");
+                self._list_profiles_continue(cb_id, &config, &credentials, &metadata, key_result)
+            }
             WalletCommand::Export(wallet_handle, export_config, cb) => {
                 debug!(target: "wallet_command_executor", "Export command received");
-                self._export(wallet_handle, &export_config, cb)
+                self._export(wallet_handle, &export_config, None, cb)
+            }
+            WalletCommand::ExportToSink(wallet_handle, export_config, sink, cb) => {
+                debug!(target: "wallet_command_executor", "ExportToSink command received");
+                self._export(wallet_handle, &export_config, Some(sink), cb)
             }
-            WalletCommand::ExportContinue(wallet_handle, export_config, key_data, key_result, cb_id) => {
+            WalletCommand::ExportContinue(wallet_handle, export_config, version, sink, key_data, key_result, cb_id) => {
                 debug!(target: "wallet_command_executor", "ExportContinue command received");
-                self._export_continue(cb_id, wallet_handle, &export_config, key_data, key_result)
+                self._export_continue(cb_id, wallet_handle, &export_config, version, sink, key_data, key_result)
             }
             WalletCommand::Import(config, credentials, import_config, cb) => {
                 debug!(target: "wallet_command_executor", "Import command received");
-                self._import(&config, &credentials, &import_config, cb);
+                self._import(&config, &credentials, &import_config, None, cb);
+            }
+            WalletCommand::ImportFromSource(config, credentials, import_config, source, cb) => {
+                debug!(target: "wallet_command_executor", "ImportFromSource command received");
+                self._import(&config, &credentials, &import_config, Some(source), cb);
             }
             WalletCommand::ImportContinue(config, credential, key_result, wallet_handle, cb_id) => {
                 debug!(target: "wallet_command_executor", "ImportContinue command received");
                 self._import_continue(cb_id, wallet_handle, &config, &credential, key_result);
             }
+            WalletCommand::ImportDelta(wallet_handle, import_config, cb) => {
+                debug!(target: "wallet_command_executor", "ImportDelta command received");
+                self._import_delta(wallet_handle, &import_config, cb);
+            }
+            WalletCommand::ImportDeltaContinue(wallet_handle, key_result, cb_id) => {
+                debug!(target: "wallet_command_executor", "ImportDeltaContinue command received");
+                self._import_delta_continue(cb_id, wallet_handle, key_result);
+            }
+            WalletCommand::Migrate(src_config, src_credentials, dst_config, dst_credentials, cb) => {
+                debug!(target: "wallet_command_executor", "Migrate command received");
+                self._migrate(&src_config, &src_credentials, &dst_config, &dst_credentials, cb);
+            }
+            WalletCommand::MigrateContinue(dst_config, dst_credentials, key_result, wallet_handle, cb_id) => {
+                debug!(target: "wallet_command_executor", "MigrateContinue command received");
+                self._migrate_continue(cb_id, wallet_handle, &dst_config, &dst_credentials, key_result);
+            }
+            WalletCommand::GetRevision(wallet_handle, cb) => {
+                debug!(target: "wallet_command_executor", "GetRevision command received");
+                cb(self._get_revision(wallet_handle));
+            }
+            WalletCommand::Check(wallet_handle, check_config, cb) => {
+                debug!(target: "wallet_command_executor", "Check command received");
+                cb(self._check(wallet_handle, &check_config));
+            }
+            WalletCommand::Subscribe(wallet_handle, context, on_change, cb) => {
+                debug!(target: "wallet_command_executor", "Subscribe command received");
+                cb(self._subscribe(wallet_handle, context, on_change));
+            }
+            WalletCommand::Unsubscribe(subscription_handle, cb) => {
+                debug!(target: "wallet_command_executor", "Unsubscribe command received");
+                cb(self._unsubscribe(subscription_handle));
+            }
+            WalletCommand::RotateItemKeys(wallet_handle, credentials, cb) => {
+                debug!(target: "wallet_command_executor", "RotateItemKeys command received");
+                self._rotate_item_keys(wallet_handle, &credentials, cb);
+            }
+            WalletCommand::RotateItemKeysContinue(wallet_handle, key_result, cb_id) => {
+                debug!(target: "wallet_command_executor", "RotateItemKeysContinue command received");
+                self._rotate_item_keys_continue(cb_id, wallet_handle, key_result);
+            }
             WalletCommand::GenerateKey(config, cb) => {
                 debug!(target: "wallet_command_executor", "DeriveKey command received");
                 cb(self._generate_key(config.as_ref()));
@@ -226,16 +357,29 @@ impl WalletCommandExecutor {
         Ok(())
     }
 
+    fn _register_key_wrap_provider(&self,
+                                   name: &str,
+                                   wrap_key: WalletKeyWrap,
+                                   unwrap_key: WalletKeyUnwrap,
+                                   free_key: WalletKeyFree) -> IndyResult<()> {
+        trace!("_register_key_wrap_provider >>> name: {:?}", name);
+
+        self.wallet_service.register_key_wrap_provider(name, wrap_key, unwrap_key, free_key)?;
+
+        trace!("_register_key_wrap_provider <<< res: ()");
+        Ok(())
+    }
+
     fn _create(&self,
                config: &Config,
                credentials: &Credentials,
                cb: Box<dyn Fn(IndyResult<()>) + Send>) {
         trace!("_create >>> config: {:?}, credentials: {:?}", config, secret!(credentials));
 
-        let key_data = KeyDerivationData::from_passphrase_with_new_salt(&credentials.key, &credentials.key_derivation_method);
+        let key_data = try_cb!(self.wallet_service.new_key_derivation_data(&credentials.key, &credentials.key_derivation_method, credentials.key_wrap_provider.as_ref()), cb);
 
         let cb_id : CallbackHandle = indy_utils::sequence::get_next_id();
-        self.pending_callbacks.borrow_mut().insert(cb_id, cb);
+        self.import_callbacks.borrow_mut().insert(cb_id, cb);
 
         let config = config.clone();
         let credentials = credentials.clone();
@@ -387,13 +531,66 @@ impl WalletCommandExecutor {
             .and_then(|key| self.wallet_service.delete_wallet_continue(config, credentials, metadata, &key)))
     }
 
+    fn _list_profiles(&self,
+                      config: &Config,
+                      credentials: &Credentials,
+                      cb: Box<dyn Fn(IndyResult<String>) + Send>) {
+        trace!("_list_profiles >>> config: {:?}, credentials: {:?}", config, secret!(credentials));
+
+        let (metadata, key_derivation_data) = try_cb!(self.wallet_service.list_profiles_prepare(&config, &credentials), cb);
+
+        let cb_id: CallbackHandle = indy_utils::sequence::get_next_id();
+        self.list_profiles_callbacks.borrow_mut().insert(cb_id, cb);
+
+        let config = config.clone();
+        let credentials = credentials.clone();
+
+        CommandExecutor::instance().send(
+            Command::Wallet(WalletCommand::DeriveKey(
+                key_derivation_data,
+                Box::new(move |key_result| {
+                    let key_result = key_result.clone();
+                    CommandExecutor::instance().send(
+                        Command::Wallet(WalletCommand::ListProfilesContinue(
+                            config.clone(),
+                            credentials.clone(),
+                            metadata.clone(),
+                            key_result,
+                            cb_id)
+                        )).unwrap()
+                }),
+            ))
+        ).unwrap();
+
+        trace!("_list_profiles <<<");
+    }
+
+    fn _list_profiles_continue(&self,
+                               cb_id: CallbackHandle,
+                               config: &Config,
+                               credentials: &Credentials,
+                               metadata: &Metadata,
+                               key_result: DeriveKeyResult<MasterKey>) {
+        let cb = match self.list_profiles_callbacks.borrow_mut().remove(&cb_id) {
+            Some(cb) => cb,
+            None => return error!("No pending command for id: {}", cb_id)
+        };
+
+        cb(key_result
+            .and_then(|key| self.wallet_service.list_profiles_continue(config, credentials, metadata, &key))
+            .and_then(|profiles| serde_json::to_string(&profiles)
+                .to_indy(IndyErrorKind::InvalidState, "Cannot serialize wallet profiles")))
+    }
+
     fn _export(&self,
                wallet_handle: WalletHandle,
                export_config: &ExportConfig,
+               sink: Option<ExportSink>,
                cb: Box<dyn Fn(IndyResult<()>) + Send>) {
         trace!("_export >>> handle: {:?}, export_config: {:?}", wallet_handle, secret!(export_config));
 
-        let key_data = KeyDerivationData::from_passphrase_with_new_salt(&export_config.key, &export_config.key_derivation_method);
+        let version = try_cb!(export_version(export_config.version), cb);
+        let key_data = try_cb!(KeyDerivationData::from_passphrase_with_new_salt(&export_config.key, &export_config.key_derivation_method), cb);
 
         let cb_id = indy_utils::sequence::get_next_id();
         self.pending_callbacks.borrow_mut().insert(cb_id, cb);
@@ -407,6 +604,8 @@ impl WalletCommandExecutor {
                     CommandExecutor::instance().send(Command::Wallet(WalletCommand::ExportContinue(
                         wallet_handle,
                         export_config.clone(),
+                        version,
+                        sink,
                         key_data.clone(),
                         master_key_res,
                         cb_id,
@@ -422,22 +621,34 @@ impl WalletCommandExecutor {
                         cb_id: CallbackHandle,
                         wallet_handle: WalletHandle,
                         export_config: &ExportConfig,
+                        version: u32,
+                        sink: Option<ExportSink>,
                         key_data: KeyDerivationData,
                         key_result: DeriveKeyResult<MasterKey>) {
         let cb = get_cb!(self, cb_id);
+
         cb(key_result
-            .and_then(|key| self.wallet_service.export_wallet(wallet_handle, export_config, 0, (&key_data,& key)))) // TODO - later add proper versioning
+            .and_then(|key| match sink {
+                Some(mut sink) => self.wallet_service.export_wallet_to_writer(wallet_handle, export_config, version, (&key_data, &key), &mut sink),
+                None => self.wallet_service.export_wallet(wallet_handle, export_config, version, (&key_data, &key)),
+            }))
     }
 
     fn _import(&self,
                config: &Config,
                credentials: &Credentials,
                import_config: &ExportConfig,
-               cb: Box<dyn Fn(IndyResult<()>) + Send>) {
+               source: Option<ImportSource>,
+               cb: Box<dyn Fn(IndyResult<String>) + Send>) {
         trace!("_import >>> config: {:?}, credentials: {:?}, import_config: {:?}",
                config, secret!(credentials), secret!(import_config));
 
-        let (wallet_handle, key_data, import_key_data) = try_cb!(self.wallet_service.import_wallet_prepare(&config, &credentials, &import_config), cb);
+        let res = match source {
+            Some(source) => self.wallet_service.import_wallet_prepare_from_reader(&config, &credentials, &import_config, Box::new(source)),
+            None => self.wallet_service.import_wallet_prepare(&config, &credentials, &import_config),
+        };
+
+        let (wallet_handle, key_data, import_key_data) = try_cb!(res, cb);
 
         let cb_id : CallbackHandle = indy_utils::sequence::get_next_id();
         self.pending_callbacks.borrow_mut().insert(cb_id, cb);
@@ -480,9 +691,226 @@ impl WalletCommandExecutor {
                         config: &Config,
                         credential: &Credentials,
                         key_result: DeriveKeyResult<(MasterKey, MasterKey)>) {
+        let cb = match self.import_callbacks.borrow_mut().remove(&cb_id) {
+            Some(cb) => cb,
+            None => return error!("No pending command for id: {}", cb_id)
+        };
+
+        cb(key_result
+            .and_then(|key| self.wallet_service.import_wallet_continue(wallet_handle, &config, &credential, key))
+            .and_then(|report| serde_json::to_string(&report)
+                .to_indy(IndyErrorKind::InvalidState, "Cannot serialize wallet import report")))
+    }
+
+    fn _migrate(&self,
+                src_config: &Config,
+                src_credentials: &Credentials,
+                dst_config: &Config,
+                dst_credentials: &Credentials,
+                cb: Box<dyn Fn(IndyResult<()>) + Send>) {
+        trace!("_migrate >>> src_config: {:?}, src_credentials: {:?}, dst_config: {:?}, dst_credentials: {:?}",
+               src_config, secret!(src_credentials), dst_config, secret!(dst_credentials));
+
+        let (wallet_handle, src_key_data, key_data) =
+            try_cb!(self.wallet_service.migrate_wallet_prepare(src_config, src_credentials, dst_config, dst_credentials), cb);
+
+        let cb_id: CallbackHandle = indy_utils::sequence::get_next_id();
+        self.pending_callbacks.borrow_mut().insert(cb_id, cb);
+
+        let dst_config = dst_config.clone();
+        let dst_credentials = dst_credentials.clone();
+
+        CommandExecutor::instance().send(
+            Command::Wallet(WalletCommand::DeriveKey(
+                src_key_data,
+                Box::new(move |src_key_result| {
+                    let dst_config = dst_config.clone();
+                    let dst_credentials = dst_credentials.clone();
+
+                    CommandExecutor::instance().send(
+                        Command::Wallet(WalletCommand::DeriveKey(
+                            key_data.clone(),
+                            Box::new(move |key_result| {
+                                let src_key_result = src_key_result.clone();
+                                CommandExecutor::instance().send(Command::Wallet(WalletCommand::MigrateContinue(
+                                    dst_config.clone(),
+                                    dst_credentials.clone(),
+                                    src_key_result.and_then(|src_key| key_result.map(|key| (src_key, key))),
+                                    wallet_handle,
+                                    cb_id
+                                ))).unwrap();
+                            }),
+                        ))
+                    ).unwrap();
+                }),
+            ))
+        ).unwrap();
+
+        trace!("_migrate <<<");
+    }
+
+    fn _migrate_continue(&self,
+                         cb_id: CallbackHandle,
+                         wallet_handle: WalletHandle,
+                         dst_config: &Config,
+                         dst_credentials: &Credentials,
+                         key_result: DeriveKeyResult<(MasterKey, MasterKey)>) {
         let cb = get_cb!(self, cb_id);
         cb(key_result
-            .and_then(|key| self.wallet_service.import_wallet_continue(wallet_handle, &config, &credential, key)))
+            .and_then(|key| self.wallet_service.migrate_wallet_continue(wallet_handle, dst_config, dst_credentials, key))
+            .map(|_| ()))
+    }
+
+    fn _import_delta(&self,
+                     wallet_handle: WalletHandle,
+                     import_config: &ExportConfig,
+                     cb: Box<dyn Fn(IndyResult<()>) + Send>) {
+        trace!("_import_delta >>> handle: {:?}, import_config: {:?}", wallet_handle, secret!(import_config));
+
+        let key_derivation_data = try_cb!(self.wallet_service.import_wallet_delta_prepare(wallet_handle, import_config), cb);
+
+        let cb_id: CallbackHandle = indy_utils::sequence::get_next_id();
+        self.pending_callbacks.borrow_mut().insert(cb_id, cb);
+
+        CommandExecutor::instance().send(
+            Command::Wallet(WalletCommand::DeriveKey(
+                key_derivation_data,
+                Box::new(move |key_result| {
+                    CommandExecutor::instance().send(
+                        Command::Wallet(WalletCommand::ImportDeltaContinue(
+                            wallet_handle,
+                            key_result,
+                            cb_id)
+                        )).unwrap()
+                }),
+            ))
+        ).unwrap();
+
+        trace!("_import_delta <<<");
+    }
+
+    fn _import_delta_continue(&self,
+                              cb_id: CallbackHandle,
+                              wallet_handle: WalletHandle,
+                              key_result: DeriveKeyResult<MasterKey>) {
+        let cb = get_cb!(self, cb_id);
+        cb(key_result
+            .and_then(|key| self.wallet_service.import_wallet_delta_continue(wallet_handle, &key)))
+    }
+
+    fn _get_revision(&self,
+                     wallet_handle: WalletHandle) -> IndyResult<u64> {
+        trace!("_get_revision >>> handle: {:?}", wallet_handle);
+
+        let res = self.wallet_service.get_revision(wallet_handle)?;
+
+        trace!("_get_revision << res: {:?}", res);
+
+        Ok(res)
+    }
+
+    fn _subscribe(&self,
+                  wallet_handle: WalletHandle,
+                  context: IndyHandle,
+                  on_change: WalletRecordChanged) -> IndyResult<IndyHandle> {
+        trace!("_subscribe >>> handle: {:?}, context: {:?}", wallet_handle, context);
+
+        let res = self.wallet_service.subscribe(wallet_handle, Box::new(move |type_: &str, id: &str, change: RecordChange| {
+            let type_ = ctypes::string_to_cstring(type_.to_string());
+            let id = ctypes::string_to_cstring(id.to_string());
+            let operation = ctypes::string_to_cstring(change.as_str().to_string());
+
+            on_change(context, type_.as_ptr(), id.as_ptr(), operation.as_ptr())
+        }))?;
+
+        trace!("_subscribe << res: {:?}", res);
+
+        Ok(res)
+    }
+
+    fn _unsubscribe(&self,
+                    subscription_handle: IndyHandle) -> IndyResult<()> {
+        trace!("_unsubscribe >>> subscription_handle: {:?}", subscription_handle);
+
+        self.wallet_service.unsubscribe(subscription_handle)?;
+
+        trace!("_unsubscribe <<<");
+
+        Ok(())
+    }
+
+    fn _check(&self,
+              wallet_handle: WalletHandle,
+              check_config: &CheckConfig) -> IndyResult<String> {
+        trace!("_check >>> handle: {:?}, check_config: {:?}", wallet_handle, check_config);
+
+        let validators: Vec<(String, fn(&str) -> Result<(), String>)> = vec![
+            (self.wallet_service.indy_type::<Did>(), _validate_json::<Did>),
+            (self.wallet_service.indy_type::<TemporaryDid>(), _validate_json::<TemporaryDid>),
+            (self.wallet_service.indy_type::<TheirDid>(), _validate_json::<TheirDid>),
+            (self.wallet_service.indy_type::<DidMetadata>(), _validate_json::<DidMetadata>),
+            (self.wallet_service.indy_type::<Key>(), _validate_json::<Key>),
+            (self.wallet_service.indy_type::<KeyMetadata>(), _validate_json::<KeyMetadata>),
+            (self.wallet_service.indy_type::<Endpoint>(), _validate_json::<Endpoint>),
+            (self.wallet_service.indy_type::<Pairwise>(), _validate_json::<Pairwise>),
+            (self.wallet_service.indy_type::<Credential>(), _validate_json::<Credential>),
+            (self.wallet_service.indy_type::<CredentialDefinition>(), _validate_json::<CredentialDefinition>),
+            (self.wallet_service.indy_type::<Schema>(), _validate_json::<Schema>),
+            (self.wallet_service.indy_type::<MasterSecret>(), _validate_json::<MasterSecret>),
+        ];
+
+        let validate_value = |type_: &str, value: &str| {
+            match validators.iter().find(|(validator_type, _)| validator_type == type_) {
+                Some((_, validate)) => validate(value),
+                None => Ok(())
+            }
+        };
+
+        let report = self.wallet_service.check_wallet(wallet_handle, check_config.repair, &validate_value)?;
+
+        let res = serde_json::to_string(&report)
+            .to_indy(IndyErrorKind::InvalidState, "Cannot serialize wallet check report")?;
+
+        trace!("_check <<< res: {:?}", res);
+
+        Ok(res)
+    }
+
+    fn _rotate_item_keys(&self,
+                         wallet_handle: WalletHandle,
+                         credentials: &Credentials,
+                         cb: Box<dyn Fn(IndyResult<()>) + Send>) {
+        trace!("_rotate_item_keys >>> handle: {:?}, credentials: {:?}", wallet_handle, secret!(credentials));
+
+        let key_derivation_data = try_cb!(self.wallet_service.rotate_item_keys_prepare(wallet_handle, credentials), cb);
+
+        let cb_id: CallbackHandle = indy_utils::sequence::get_next_id();
+        self.pending_callbacks.borrow_mut().insert(cb_id, cb);
+
+        CommandExecutor::instance().send(
+            Command::Wallet(WalletCommand::DeriveKey(
+                key_derivation_data,
+                Box::new(move |key_result| {
+                    CommandExecutor::instance().send(
+                        Command::Wallet(WalletCommand::RotateItemKeysContinue(
+                            wallet_handle,
+                            key_result,
+                            cb_id)
+                        )).unwrap()
+                }),
+            ))
+        ).unwrap();
+
+        trace!("_rotate_item_keys <<<");
+    }
+
+    fn _rotate_item_keys_continue(&self,
+                                  cb_id: CallbackHandle,
+                                  wallet_handle: WalletHandle,
+                                  key_result: DeriveKeyResult<MasterKey>) {
+        let cb = get_cb!(self, cb_id);
+        cb(key_result
+            .and_then(|key| self.wallet_service.rotate_item_keys_continue(wallet_handle, &key)))
     }
 
     fn _generate_key(&self,
@@ -506,3 +934,9 @@ impl WalletCommandExecutor {
         crate::commands::THREADPOOL.lock().unwrap().execute(move || cb(key_data.calc_master_key()));
     }
 }
+
+fn _validate_json<T>(value: &str) -> Result<(), String> where T: DeserializeOwned {
+    serde_json::from_str::<T>(value)
+        .map(|_| ())
+        .map_err(|err| format!("Invalid json of record value: {}", err))
+}
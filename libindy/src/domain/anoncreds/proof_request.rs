@@ -297,9 +297,15 @@ fn _process_operator(restriction_op: &Query, version: &ProofRequestsVersion) ->
         Query::Gte(ref tag_name, ref tag_value) |
         Query::Lt(ref tag_name, ref tag_value) |
         Query::Lte(ref tag_name, ref tag_value) |
-        Query::Like(ref tag_name, ref tag_value) => {
+        Query::Like(ref tag_name, ref tag_value) |
+        Query::NumGt(ref tag_name, ref tag_value) |
+        Query::NumGte(ref tag_name, ref tag_value) |
+        Query::NumLt(ref tag_name, ref tag_value) |
+        Query::NumLte(ref tag_name, ref tag_value) |
+        Query::Prefix(ref tag_name, ref tag_value) => {
             _check_restriction(tag_name, tag_value, version)
         }
+        Query::Exist(_) => Ok(()),
         Query::In(ref tag_name, ref tag_values) => {
             tag_values
                 .iter()
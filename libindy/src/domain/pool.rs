@@ -5,6 +5,7 @@ pub const POOL_ACK_TIMEOUT: i64 = 20;
 pub const POOL_REPLY_TIMEOUT: i64 = 60;
 pub const MAX_REQ_PER_POOL_CON: usize = 5;
 pub const NUMBER_READ_NODES: u8 = 2;
+pub const POOL_LEDGER_PROBE_TIMEOUT: i64 = 600;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PoolConfig {
@@ -25,6 +26,10 @@ pub struct PoolOpenConfig {
     pub timeout: i64,
     #[serde(default = "PoolOpenConfig::default_extended_timeout")]
     pub extended_timeout: i64,
+    // Nodes don't notify about pool ledger changes, so pool ledger state is probed with LEDGER_STATUS
+    // on sending of a request if it wasn't checked for this timeout (in seconds)
+    #[serde(default = "PoolOpenConfig::default_pool_ledger_probe_timeout")]
+    pub pool_ledger_probe_timeout: i64,
     #[serde(default = "PoolOpenConfig::default_conn_limit")]
     pub conn_limit: usize,
     #[serde(default = "PoolOpenConfig::default_conn_active_timeout")]
@@ -35,6 +40,8 @@ pub struct PoolOpenConfig {
     pub number_read_nodes: u8,
     #[serde(default = "PoolOpenConfig::default_socks_proxy")]
     pub socks_proxy: String,
+    #[serde(default = "PoolOpenConfig::default_transport")]
+    pub transport: Option<String>,
 }
 
 impl Validatable for PoolOpenConfig {
@@ -45,6 +52,9 @@ impl Validatable for PoolOpenConfig {
         if self.extended_timeout <= 0 {
             return Err(String::from("`extended_timeout` must be greater than 0"));
         }
+        if self.pool_ledger_probe_timeout <= 0 {
+            return Err(String::from("`pool_ledger_probe_timeout` must be greater than 0"));
+        }
         if self.conn_limit == 0 {
             return Err(String::from("`conn_limit` must be greater than 0"));
         }
@@ -63,11 +73,13 @@ impl Default for PoolOpenConfig {
         PoolOpenConfig {
             timeout: PoolOpenConfig::default_timeout(),
             extended_timeout: PoolOpenConfig::default_extended_timeout(),
+            pool_ledger_probe_timeout: PoolOpenConfig::default_pool_ledger_probe_timeout(),
             conn_limit: PoolOpenConfig::default_conn_limit(),
             conn_active_timeout: PoolOpenConfig::default_conn_active_timeout(),
             preordered_nodes: PoolOpenConfig::default_preordered_nodes(),
             number_read_nodes: PoolOpenConfig::default_number_read_nodes(),
             socks_proxy: PoolOpenConfig::default_socks_proxy(),
+            transport: PoolOpenConfig::default_transport(),
         }
     }
 }
@@ -81,6 +93,10 @@ impl PoolOpenConfig {
         POOL_REPLY_TIMEOUT
     }
 
+    fn default_pool_ledger_probe_timeout() -> i64 {
+        POOL_LEDGER_PROBE_TIMEOUT
+    }
+
     fn default_conn_limit() -> usize {
         MAX_REQ_PER_POOL_CON
     }
@@ -96,4 +112,6 @@ impl PoolOpenConfig {
     fn default_number_read_nodes() -> u8 { NUMBER_READ_NODES }
 
     fn default_socks_proxy() -> String { String::new() }
+
+    fn default_transport() -> Option<String> { None }
 }
@@ -516,6 +516,15 @@ impl Prover {
                     Query::In(tag_name, tag_values)
                 }
             }
+            Query::Exist(tag_name) => {
+                if let Some(tag_name) = Verifier::attr_request_by_value(&tag_name) {
+                    Query::Exist(Self::_build_attr_value_tag(tag_name))
+                } else if let Some(tag_name) = Verifier::attr_request_by_marker(&tag_name) {
+                    Query::Exist(Self::_build_attr_marker_tag(tag_name))
+                } else {
+                    Query::Exist(tag_name)
+                }
+            }
             Query::And(operators) => {
                 Query::And(
                     operators
@@ -565,6 +574,14 @@ impl Prover {
                     Query::In(tag_name, tag_values)
                 }
             }
+            Query::Exist(tag_name) => {
+                if Credential::QUALIFIABLE_TAGS.contains(&tag_name.as_str()) {
+                    Query::Or(vec![Query::Exist(tag_name.clone()),
+                                   Query::Exist(Credential::add_extra_tag_suffix(&tag_name))])
+                } else {
+                    Query::Exist(tag_name)
+                }
+            }
             Query::And(operators) => {
                 Query::And(
                     operators
@@ -1026,6 +1043,21 @@ mod tests {
 
             assert_eq!(expected_query, query);
         }
+
+        #[test]
+        fn extend_operator_works_for_exist_qualifiable_tag() {
+            let ps = Prover::new();
+
+            let query = Query::Exist(QUALIFIABLE_TAG.to_string());
+            let query = ps.double_restrictions(query).unwrap();
+
+            let expected_query = Query::Or(vec![
+                Query::Exist(QUALIFIABLE_TAG.to_string()),
+                Query::Exist(Credential::add_extra_tag_suffix(QUALIFIABLE_TAG)),
+            ]);
+
+            assert_eq!(expected_query, query);
+        }
     }
 
     mod extend_proof_request_restrictions {
@@ -563,10 +563,66 @@ impl Verifier {
                     Err(IndyError::from_msg(IndyErrorKind::ProofRejected, "$not operator validation failed. All conditions were passed."))
                 }
             }
+            Query::Exist(ref tag_name) => {
+                Verifier::_process_exist_filter(attr_value_map, &tag_name)
+                    .map_err(|err| err.extend(format!("$exist operator validation failed for tag: \"{}\"", tag_name)))
+            }
+            Query::NumGt(ref tag_name, ref tag_value) => {
+                Verifier::_process_numeric_filter(attr_value_map, &tag_name, &tag_value, filter, "$gt", |a, b| a > b)
+            }
+            Query::NumGte(ref tag_name, ref tag_value) => {
+                Verifier::_process_numeric_filter(attr_value_map, &tag_name, &tag_value, filter, "$gte", |a, b| a >= b)
+            }
+            Query::NumLt(ref tag_name, ref tag_value) => {
+                Verifier::_process_numeric_filter(attr_value_map, &tag_name, &tag_value, filter, "$lt", |a, b| a < b)
+            }
+            Query::NumLte(ref tag_name, ref tag_value) => {
+                Verifier::_process_numeric_filter(attr_value_map, &tag_name, &tag_value, filter, "$lte", |a, b| a <= b)
+            }
             _ => Err(IndyError::from_msg(IndyErrorKind::ProofRejected, "unsupported operator"))
         }
     }
 
+    fn _process_exist_filter(attr_value_map: &HashMap<String, Option<&str>>,
+                             tag: &str) -> IndyResult<()> {
+        match tag {
+            "schema_id" | "schema_issuer_did" | "schema_name" | "schema_version" | "cred_def_id" | "issuer_did" => Ok(()),
+            x if Verifier::_is_attr_value_operator(x) => {
+                // attr::<tag>::value -> value must be revealed
+                Verifier::_get_internal_tag_revealed_value(x, attr_value_map).map(|_| ())
+            }
+            x if Verifier::_is_attr_marker_operator(x) => Ok(()),
+            _ => Err(err_msg(IndyErrorKind::InvalidStructure, "Unknown Filter Type"))
+        }
+    }
+
+    fn _process_numeric_filter<F>(attr_value_map: &HashMap<String, Option<&str>>,
+                                  tag: &str,
+                                  tag_value: &str,
+                                  filter: &Filter,
+                                  op_name: &str,
+                                  cmp: F) -> IndyResult<()> where F: Fn(f64, f64) -> bool {
+        let actual_value = match tag {
+            "schema_id" => filter.schema_id.as_str(),
+            "schema_issuer_did" => filter.schema_issuer_did.as_str(),
+            "schema_name" => filter.schema_name.as_str(),
+            "schema_version" => filter.schema_version.as_str(),
+            "cred_def_id" => filter.cred_def_id.as_str(),
+            "issuer_did" => filter.issuer_did.as_str(),
+            x if Verifier::_is_attr_value_operator(x) => Verifier::_get_internal_tag_revealed_value(x, attr_value_map)?,
+            _ => return Err(err_msg(IndyErrorKind::InvalidStructure, format!("Unknown Filter Type for numeric {} operator", op_name)))
+        };
+
+        let expected = tag_value.parse::<f64>()
+            .map_err(|_| err_msg(IndyErrorKind::InvalidStructure, format!("Invalid number \"{}\" for {} operator", tag_value, op_name)))?;
+
+        match actual_value.parse::<f64>() {
+            Ok(actual) if cmp(actual, expected) => Ok(()),
+            _ => Err(IndyError::from_msg(IndyErrorKind::ProofRejected,
+                                         format!("{} operator validation failed for tag: \"{}\", value: \"{}\", actual: \"{}\"", op_name, tag, tag_value, actual_value)))
+        }
+    }
+
     fn _process_filter(attr_value_map: &HashMap<String, Option<&str>>,
                        tag: &str,
                        tag_value: &str,
@@ -620,6 +676,16 @@ impl Verifier {
     }
 
     fn _check_internal_tag_revealed_value(key: &str, tag_value: &str, attr_value_map: &HashMap<String, Option<&str>>) -> IndyResult<()> {
+        let revealed_value = Verifier::_get_internal_tag_revealed_value(key, attr_value_map)?;
+
+        if revealed_value != tag_value {
+            return Err(IndyError::from_msg(IndyErrorKind::ProofRejected,
+                                           format!("\"{}\" values are different: expected: \"{}\", actual: \"{}\"", key, tag_value, revealed_value)));
+        }
+        Ok(())
+    }
+
+    fn _get_internal_tag_revealed_value<'a>(key: &str, attr_value_map: &HashMap<String, Option<&'a str>>) -> IndyResult<&'a str> {
         let captures = VALUE_TAG_MATCHER.captures(key)
             .ok_or(IndyError::from_msg(IndyErrorKind::InvalidState, format!("Attribute name became unparseable")))?;
 
@@ -634,15 +700,11 @@ impl Verifier {
                 .find(|(key, _)| attr_common_view(key)  == attr_common_view(attr_name));
 
         if let Some((_key, Some(revealed_value))) = revealed_value {
-            if *revealed_value != tag_value {
-                return Err(IndyError::from_msg(IndyErrorKind::ProofRejected,
-                                               format!("\"{}\" values are different: expected: \"{}\", actual: \"{}\"", key, tag_value, revealed_value)));
-            }
+            Ok(*revealed_value)
         } else {
-            return Err(IndyError::from_msg(IndyErrorKind::ProofRejected,
-                                           format!("Revealed value hasn't been find by key: expected key: \"{}\", attr_value_map: \"{:?}\"", key, attr_value_map)));
+            Err(IndyError::from_msg(IndyErrorKind::ProofRejected,
+                                    format!("Revealed value hasn't been find by key: expected key: \"{}\", attr_value_map: \"{:?}\"", key, attr_value_map)))
         }
-        Ok(())
     }
 
     fn _is_attr_marker_operator(key: &str) -> bool {
@@ -740,6 +802,44 @@ mod tests {
         Verifier::_process_operator("zip", &op, &filter, None).unwrap()
     }
 
+    #[test]
+    fn test_process_op_exist() {
+        let filter = filter();
+
+        let mut op = Query::Exist(schema_id_tag());
+        Verifier::_process_operator("zip", &op, &filter, None).unwrap();
+
+        op = Query::Exist(attr_tag_value());
+        assert!(Verifier::_process_operator("zip", &op, &filter, None).is_err());
+        Verifier::_process_operator("zip", &op, &filter, Some("NOT HERE")).unwrap();
+
+        op = Query::Exist(attr_tag());
+        Verifier::_process_operator("zip", &op, &filter, None).unwrap();
+
+        op = Query::Exist(bad_attr_tag());
+        assert!(Verifier::_process_operator("zip", &op, &filter, None).is_err());
+    }
+
+    #[test]
+    fn test_process_op_numeric() {
+        let filter = filter();
+
+        let mut op = Query::NumGt(schema_id_tag(), "99".to_string());
+        Verifier::_process_operator("zip", &op, &filter, None).unwrap();
+
+        op = Query::NumLte(schema_id_tag(), "99".to_string());
+        assert!(Verifier::_process_operator("zip", &op, &filter, None).is_err());
+
+        op = Query::NumGte(attr_tag_value(), "10".to_string());
+        Verifier::_process_operator("zip", &op, &filter, Some("10")).unwrap();
+        assert!(Verifier::_process_operator("zip", &op, &filter, Some("9")).is_err());
+        assert!(Verifier::_process_operator("zip", &op, &filter, Some("ten")).is_err());
+        assert!(Verifier::_process_operator("zip", &op, &filter, None).is_err());
+
+        op = Query::NumLt(schema_name_tag(), "10".to_string());
+        assert!(Verifier::_process_operator("zip", &op, &filter, None).is_err());
+    }
+
     #[test]
     fn test_process_op_or() {
         let filter = filter();
@@ -53,28 +53,26 @@ impl MerkleTree {
         }
 
         while cur.len() > 1 {
-            let mut next = Vec::new();
-            while !cur.is_empty() {
-                if cur.len() == 1 {
-                    next.push(cur.remove(0));
-                }
-                else {
-                    let left  = cur.remove(0);
-                    let right = cur.remove(0);
-
-                    let combined_hash = Hash::hash_nodes(
-                        left.hash(),
-                        right.hash()
-                    )?;
-
-                    let node = Tree::Node {
-                       hash: combined_hash.to_vec(),
-                       left: Box::new(left),
-                       right: Box::new(right)
-                    };
-
-                    next.push(node);
-                    nodes_count+=1;
+            let mut next = Vec::with_capacity((cur.len() + 1) / 2);
+            let mut level = cur.into_iter();
+            while let Some(left) = level.next() {
+                match level.next() {
+                    Some(right) => {
+                        let combined_hash = Hash::hash_nodes(
+                            left.hash(),
+                            right.hash()
+                        )?;
+
+                        let node = Tree::Node {
+                           hash: combined_hash.to_vec(),
+                           left: Box::new(left),
+                           right: Box::new(right)
+                        };
+
+                        next.push(node);
+                        nodes_count+=1;
+                    }
+                    None => next.push(left)
                 }
             }
 
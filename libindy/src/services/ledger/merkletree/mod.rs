@@ -47,7 +47,7 @@ impl MerkleTree {
                     }
                 }
             }
-            Tree::Leaf { ref hash, .. } => {
+            Tree::Leaf { ref hash, .. } | Tree::Compact { ref hash, .. } => {
                 if hash == required_hash {
                     Some(from)
                 } else {
@@ -126,6 +126,75 @@ impl MerkleTree {
         Ok(true)
     }
 
+    /// Returns root hashes of the perfect subtrees the tree consists of, from the biggest to the smallest.
+    /// Together with the count they are enough to calculate the root hash.
+    pub fn frontier(&self) -> Vec<Vec<u8>> {
+        let mut frontier = Vec::new();
+        let mut node = &self.root;
+        let mut count = self.count;
+
+        while count > 0 {
+            if Self::count_bits(count) == 1 {
+                frontier.push(node.hash().to_vec());
+                break;
+            }
+
+            match *node {
+                Tree::Node { ref left, ref right, .. } => {
+                    frontier.push(left.hash().to_vec());
+                    count -= count.next_power_of_two() / 2;
+                    node = right;
+                }
+                _ => break
+            }
+        }
+
+        frontier
+    }
+
+    /// Restores the tree from its frontier without hashing of the values.
+    /// Frontier isn't verified, so it must be calculated for the same values.
+    pub fn from_frontier(frontier: Vec<Vec<u8>>, mut values: Vec<TreeLeafData>) -> IndyResult<MerkleTree> {
+        let count = values.len();
+
+        if frontier.len() != Self::count_bits(count) {
+            return Err(err_msg(IndyErrorKind::InvalidStructure, "Frontier doesn't match the number of values"));
+        }
+
+        let mut root: Option<Tree> = None;
+
+        // the smallest perfect subtree is the rightmost one
+        for hash in frontier.into_iter().rev() {
+            let size = values.len() & values.len().wrapping_neg();
+            let mut subtree_values = values.split_off(values.len() - size);
+
+            let subtree = if size == 1 {
+                Tree::new(hash, subtree_values.remove(0))
+            } else {
+                Tree::Compact { hash, values: subtree_values }
+            };
+
+            root = Some(match root {
+                Some(right) => Tree::Node {
+                    hash: Hash::hash_nodes(subtree.hash(), right.hash())?.to_vec(),
+                    left: Box::new(subtree),
+                    right: Box::new(right)
+                },
+                None => subtree
+            });
+        }
+
+        Ok(match root {
+            Some(root) => MerkleTree {
+                root,
+                height: count.next_power_of_two().trailing_zeros() as usize,
+                count,
+                nodes_count: count - 1
+            },
+            None => MerkleTree::default()
+        })
+    }
+
     pub fn append(&mut self, node: TreeLeafData) -> IndyResult<()> {
         if self.count == 0 {
             // empty tree
@@ -158,7 +227,7 @@ impl MerkleTree {
             // add tree layer
             let new_right = MerkleTree::from_vec(vec![node])?;
             match self.root.clone() {
-                Tree::Node { ref hash, .. } => {
+                Tree::Node { ref hash, .. } | Tree::Compact { ref hash, .. } => {
                     let combined_hash = Hash::hash_nodes(
                         hash,
                         new_right.root_hash()
@@ -215,6 +284,69 @@ mod tests {
         assert_eq!(mt.root_hash_hex(), "1285070cf01debc1155cef8dfd5ba54c05abb919a4c08c8632b079fb1e1e5e7c");
     }
 
+    #[test]
+    fn frontier_works() {
+        let values: Vec<TreeLeafData> = (0..7u8).map(|i| vec![i]).collect();
+
+        let mut mt = MerkleTree::from_vec(vec![]).unwrap();
+        assert!(mt.frontier().is_empty());
+
+        for value in values.iter() {
+            mt.append(value.clone()).unwrap();
+        }
+
+        let frontier = mt.frontier();
+        assert_eq!(3, frontier.len());
+        assert_eq!(MerkleTree::from_vec(values[0..4].to_vec()).unwrap().root_hash(), &frontier[0]);
+        assert_eq!(MerkleTree::from_vec(values[4..6].to_vec()).unwrap().root_hash(), &frontier[1]);
+        assert_eq!(Hash::hash_leaf(&values[6]).unwrap(), frontier[2]);
+
+        let root = frontier.iter().rev()
+            .fold(None, |right: Option<Vec<u8>>, left| match right {
+                Some(right) => Some(Hash::hash_nodes(left, &right).unwrap()),
+                None => Some(left.clone())
+            })
+            .unwrap();
+        assert_eq!(mt.root_hash(), &root);
+        assert_eq!(MerkleTree::from_vec(values).unwrap().root_hash(), &root);
+    }
+
+    #[test]
+    fn from_frontier_works() {
+        for count in 0..10u8 {
+            let values: Vec<TreeLeafData> = (0..count).map(|i| vec![i]).collect();
+            let expected = MerkleTree::from_vec(values.clone()).unwrap();
+
+            let mut mt = MerkleTree::from_frontier(expected.frontier(), values.clone()).unwrap();
+            assert_eq!(expected.root_hash(), mt.root_hash());
+            assert_eq!(expected.count(), mt.count());
+            assert_eq!(expected.height(), mt.height());
+            assert_eq!(expected.frontier(), mt.frontier());
+            assert_eq!(values, mt.iter().cloned().collect::<Vec<TreeLeafData>>());
+            assert_eq!(values, mt.clone().into_iter().collect::<Vec<TreeLeafData>>());
+
+            mt.append(vec![count]).unwrap();
+            let mut expected = expected;
+            expected.append(vec![count]).unwrap();
+            assert_eq!(expected.root_hash(), mt.root_hash());
+            assert_eq!(expected.frontier(), mt.frontier());
+
+            if count > 0 {
+                let proof = mt.gen_proof(vec![0]).unwrap().unwrap();
+                assert!(proof.validate(mt.root_hash()).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn from_frontier_works_for_mismatched_frontier() {
+        let values: Vec<TreeLeafData> = (0..7u8).map(|i| vec![i]).collect();
+        let frontier = MerkleTree::from_vec(values[0..4].to_vec()).unwrap().frontier();
+
+        let res = MerkleTree::from_frontier(frontier, values);
+        assert_kind!(IndyErrorKind::InvalidStructure, res);
+    }
+
     #[test]
     fn find_hash_works() {
         let values = vec!["1", "2", "3", "4", "5", "6", "7", "8", "9"];
@@ -1,3 +1,4 @@
+use crate::services::ledger::merkletree::merkletree::MerkleTree;
 use crate::services::ledger::merkletree::tree::{Tree, TreeLeafData};
 use indy_utils::crypto::hash::Hash;
 use indy_api_types::errors::prelude::*;
@@ -89,7 +90,11 @@ impl Lemma {
                 Lemma::new_leaf_proof(hash, needle),
 
             Tree::Node { ref hash, ref left, ref right } =>
-                Lemma::new_tree_proof(hash, needle, left, right)
+                Lemma::new_tree_proof(hash, needle, left, right),
+
+            Tree::Compact { ref values, .. } =>
+                MerkleTree::from_vec(values.clone()).ok()
+                    .and_then(|subtree| Lemma::new(&subtree.root, needle))
         }
     }
 
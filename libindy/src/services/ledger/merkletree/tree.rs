@@ -1,4 +1,4 @@
-use std::cmp;
+use std::{cmp, slice, vec};
 
 use indy_api_types::errors::prelude::*;
 pub use crate::services::ledger::merkletree::proof::{
@@ -26,6 +26,12 @@ pub enum Tree {
         hash: Vec<u8>,
         left: Box<Tree>,
         right: Box<Tree>
+    },
+
+    /// Perfect subtree restored from its root hash, inner nodes aren't built.
+    Compact {
+        hash: Vec<u8>,
+        values: Vec<TreeLeafData>
     }
 }
 
@@ -57,7 +63,8 @@ impl Tree {
         match *self {
             Tree::Empty { ref hash }    => hash,
             Tree::Leaf { ref hash, .. } => hash,
-            Tree::Node { ref hash, .. } => hash
+            Tree::Node { ref hash, .. } => hash,
+            Tree::Compact { ref hash, .. } => hash
         }
     }
 
@@ -72,7 +79,8 @@ impl Tree {
             Tree::Node { ref left, ref right, .. } => {
                 1 + cmp::max(left.get_height(),right.get_height())
             },
-            Tree::Leaf { .. } => { 0 }
+            Tree::Leaf { .. } => { 0 },
+            Tree::Compact { ref values, .. } => { values.len().trailing_zeros() as usize }
         }
     }
 
@@ -82,7 +90,8 @@ impl Tree {
             Tree::Node { ref left, ref right, .. } => {
                 left.get_count() + right.get_count()
             },
-            Tree::Leaf { .. } => { 1 }
+            Tree::Leaf { .. } => { 1 },
+            Tree::Compact { ref values, .. } => { values.len() }
         }
     }
 }
@@ -93,6 +102,7 @@ impl Tree {
 #[allow(missing_debug_implementations)]
 pub struct LeavesIterator<'a> {
     current_value: Option<&'a TreeLeafData>,
+    compact_values: slice::Iter<'a, TreeLeafData>,
     right_nodes: Vec<&'a Tree>
 }
 
@@ -101,6 +111,7 @@ impl <'a> LeavesIterator<'a> {
     fn new(root: &'a Tree) -> Self {
         let mut iter = LeavesIterator {
             current_value: None,
+            compact_values: (&[]).iter(),
             right_nodes: Vec::new()
         };
 
@@ -125,6 +136,12 @@ impl <'a> LeavesIterator<'a> {
                 Tree::Leaf { ref value, .. } => {
                     self.current_value = Some(value);
                     break;
+                },
+
+                Tree::Compact { ref values, .. } => {
+                    self.compact_values = values.iter();
+                    self.current_value = self.compact_values.next();
+                    break;
                 }
             }
         }
@@ -139,7 +156,9 @@ impl <'a> Iterator for LeavesIterator<'a> {
     fn next(&mut self) -> Option<&'a TreeLeafData> {
         let result = self.current_value.take();
 
-        if let Some(rest) = self.right_nodes.pop() {
+        if let Some(value) = self.compact_values.next() {
+            self.current_value = Some(value);
+        } else if let Some(rest) = self.right_nodes.pop() {
             self.add_left(rest);
         }
 
@@ -152,6 +171,7 @@ impl <'a> Iterator for LeavesIterator<'a> {
 #[allow(missing_debug_implementations)]
 pub struct LeavesIntoIterator {
     current_value: Option<TreeLeafData>,
+    compact_values: vec::IntoIter<TreeLeafData>,
     right_nodes: Vec<Tree>
 }
 
@@ -160,6 +180,7 @@ impl LeavesIntoIterator {
     fn new(root: Tree) -> Self {
         let mut iter = LeavesIntoIterator {
             current_value: None,
+            compact_values: Vec::new().into_iter(),
             right_nodes: Vec::new()
         };
 
@@ -184,6 +205,12 @@ impl LeavesIntoIterator {
                 Tree::Leaf { value, .. } => {
                     self.current_value = Some(value);
                     break;
+                },
+
+                Tree::Compact { values, .. } => {
+                    self.compact_values = values.into_iter();
+                    self.current_value = self.compact_values.next();
+                    break;
                 }
             }
         }
@@ -198,7 +225,9 @@ impl Iterator for LeavesIntoIterator {
     fn next(&mut self) -> Option<TreeLeafData> {
         let result = self.current_value.take();
 
-        if let Some(rest) = self.right_nodes.pop() {
+        if let Some(value) = self.compact_values.next() {
+            self.current_value = Some(value);
+        } else if let Some(rest) = self.right_nodes.pop() {
             self.add_left(rest);
         }
 
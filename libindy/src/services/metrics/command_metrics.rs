@@ -85,9 +85,11 @@ impl From<&ProverCommand> for CommandMetric {
             ProverCommand::GetCredentials(_, _, _) => { CommandMetric::ProverCommandGetCredentials }
             ProverCommand::GetCredential(_, _, _) => { CommandMetric::ProverCommandGetCredential }
             ProverCommand::DeleteCredential(_, _, _) => { CommandMetric::ProverCommandDeleteCredential }
-            ProverCommand::SearchCredentials(_, _, _) => { CommandMetric::ProverCommandSearchCredentials }
+            ProverCommand::SearchCredentials(_, _, _, _) => { CommandMetric::ProverCommandSearchCredentials }
             ProverCommand::FetchCredentials(_, _, _) => { CommandMetric::ProverCommandFetchCredentials }
             ProverCommand::CloseCredentialsSearch(_, _) => { CommandMetric::ProverCommandCloseCredentialsSearch }
+            ProverCommand::CountCredentialsByTag(_, _, _, _) => { CommandMetric::ProverCommandCountCredentialsByTag }
+            ProverCommand::GetDistinctCredentialTagValues(_, _, _, _) => { CommandMetric::ProverCommandGetDistinctCredentialTagValues }
             ProverCommand::GetCredentialsForProofReq(_, _, _) => { CommandMetric::ProverCommandGetCredentialsForProofReq }
             ProverCommand::SearchCredentialsForProofReq(_, _, _, _) => { CommandMetric::ProverCommandSearchCredentialsForProofReq }
             ProverCommand::FetchCredentialForProofReq(_, _, _, _) => { CommandMetric::ProverCommandFetchCredentialForProofReq }
@@ -206,7 +208,11 @@ impl From<&Command> for CommandMetric {
                     PoolCommand::CloseAck(_, _) => { CommandMetric::PoolCommandCloseAck }
                     PoolCommand::Refresh(_, _) => { CommandMetric::PoolCommandRefresh }
                     PoolCommand::RefreshAck(_, _) => { CommandMetric::PoolCommandRefreshAck }
+                    PoolCommand::GetStatus(_, _) => { CommandMetric::PoolCommandGetStatus }
+                    PoolCommand::GetStatusAck(_, _) => { CommandMetric::PoolCommandGetStatusAck }
                     PoolCommand::SetProtocolVersion(_, _) => { CommandMetric::PoolCommandSetProtocolVersion }
+                    PoolCommand::RegisterTransport(_, _, _) => { CommandMetric::PoolCommandRegisterTransport }
+                    PoolCommand::DeliverTransportReply(_, _, _, _) => { CommandMetric::PoolCommandDeliverTransportReply }
                 }
             }
             Command::Did(cmd) => {
@@ -232,6 +238,7 @@ impl From<&Command> for CommandMetric {
             Command::Wallet(cmd) => {
                 match cmd {
                     WalletCommand::RegisterWalletType(_, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _) => { CommandMetric::WalletCommandRegisterWalletType }
+                    WalletCommand::RegisterKeyWrapProvider(_, _, _, _, _) => { CommandMetric::WalletCommandRegisterKeyWrapProvider }
                     WalletCommand::Create(_, _, _) => { CommandMetric::WalletCommandCreate }
                     WalletCommand::CreateContinue(_, _, _, _, _) => { CommandMetric::WalletCommandCreateContinue }
                     WalletCommand::Open(_, _, _) => { CommandMetric::WalletCommandOpen }
@@ -239,10 +246,24 @@ impl From<&Command> for CommandMetric {
                     WalletCommand::Close(_, _) => { CommandMetric::WalletCommandClose }
                     WalletCommand::Delete(_, _, _) => { CommandMetric::WalletCommandDelete }
                     WalletCommand::DeleteContinue(_, _, _, _, _) => { CommandMetric::WalletCommandDeleteContinue }
+                    WalletCommand::ListProfiles(_, _, _) => { CommandMetric::WalletCommandListProfiles }
+                    WalletCommand::ListProfilesContinue(_, _, _, _, _) => { CommandMetric::WalletCommandListProfilesContinue }
                     WalletCommand::Export(_, _, _) => { CommandMetric::WalletCommandExport }
-                    WalletCommand::ExportContinue(_, _, _, _, _) => { CommandMetric::WalletCommandExportContinue }
+                    WalletCommand::ExportToSink(_, _, _, _) => { CommandMetric::WalletCommandExportToSink }
+                    WalletCommand::ExportContinue(_, _, _, _, _, _) => { CommandMetric::WalletCommandExportContinue }
                     WalletCommand::Import(_, _, _, _) => { CommandMetric::WalletCommandImport }
+                    WalletCommand::ImportFromSource(_, _, _, _, _) => { CommandMetric::WalletCommandImportFromSource }
                     WalletCommand::ImportContinue(_, _, _, _, _) => { CommandMetric::WalletCommandImportContinue }
+                    WalletCommand::ImportDelta(_, _, _) => { CommandMetric::WalletCommandImportDelta }
+                    WalletCommand::ImportDeltaContinue(_, _, _) => { CommandMetric::WalletCommandImportDeltaContinue }
+                    WalletCommand::Migrate(_, _, _, _, _) => { CommandMetric::WalletCommandMigrate }
+                    WalletCommand::MigrateContinue(_, _, _, _, _) => { CommandMetric::WalletCommandMigrateContinue }
+                    WalletCommand::GetRevision(_, _) => { CommandMetric::WalletCommandGetRevision }
+                    WalletCommand::Check(_, _, _) => { CommandMetric::WalletCommandCheck }
+                    WalletCommand::Subscribe(_, _, _, _) => { CommandMetric::WalletCommandSubscribe }
+                    WalletCommand::Unsubscribe(_, _) => { CommandMetric::WalletCommandUnsubscribe }
+                    WalletCommand::RotateItemKeys(_, _, _) => { CommandMetric::WalletCommandRotateItemKeys }
+                    WalletCommand::RotateItemKeysContinue(_, _, _) => { CommandMetric::WalletCommandRotateItemKeysContinue }
                     WalletCommand::GenerateKey(_, _) => { CommandMetric::WalletCommandGenerateKey }
                     WalletCommand::DeriveKey(_, _) => { CommandMetric::WalletCommandDeriveKey }
                 }
@@ -258,12 +279,20 @@ impl From<&Command> for CommandMetric {
             }
             Command::NonSecrets(cmd) => {
                 match cmd {
-                    NonSecretsCommand::AddRecord(_, _, _, _, _, _) => { CommandMetric::NonSecretsCommandAddRecord }
+                    NonSecretsCommand::AddRecord(_, _, _, _, _, _, _) => { CommandMetric::NonSecretsCommandAddRecord }
                     NonSecretsCommand::UpdateRecordValue(_, _, _, _, _) => { CommandMetric::NonSecretsCommandUpdateRecordValue }
                     NonSecretsCommand::UpdateRecordTags(_, _, _, _, _) => { CommandMetric::NonSecretsCommandUpdateRecordTags }
                     NonSecretsCommand::AddRecordTags(_, _, _, _, _) => { CommandMetric::NonSecretsCommandAddRecordTags }
                     NonSecretsCommand::DeleteRecordTags(_, _, _, _, _) => { CommandMetric::NonSecretsCommandDeleteRecordTags }
                     NonSecretsCommand::DeleteRecord(_, _, _, _) => { CommandMetric::NonSecretsCommandDeleteRecord }
+                    NonSecretsCommand::Batch(_, _, _) => { CommandMetric::NonSecretsCommandBatch }
+                    NonSecretsCommand::BeginBatch(_, _) => { CommandMetric::NonSecretsCommandBeginBatch }
+                    NonSecretsCommand::StageBatch(_, _, _) => { CommandMetric::NonSecretsCommandStageBatch }
+                    NonSecretsCommand::CommitBatch(_, _) => { CommandMetric::NonSecretsCommandCommitBatch }
+                    NonSecretsCommand::RollbackBatch(_, _) => { CommandMetric::NonSecretsCommandRollbackBatch }
+                    NonSecretsCommand::PurgeExpiredRecords(_, _) => { CommandMetric::NonSecretsCommandPurgeExpiredRecords }
+                    NonSecretsCommand::CountRecordsByTag(_, _, _, _, _) => { CommandMetric::NonSecretsCommandCountRecordsByTag }
+                    NonSecretsCommand::GetDistinctRecordTagValues(_, _, _, _, _) => { CommandMetric::NonSecretsCommandGetDistinctRecordTagValues }
                     NonSecretsCommand::GetRecord(_, _, _, _, _) => { CommandMetric::NonSecretsCommandGetRecord }
                     NonSecretsCommand::OpenSearch(_, _, _, _, _) => { CommandMetric::NonSecretsCommandOpenSearch }
                     NonSecretsCommand::FetchSearchNextRecords(_, _, _, _) => { CommandMetric::NonSecretsCommandFetchSearchNextRecords }
@@ -353,6 +382,8 @@ pub enum CommandMetric {
     ProverCommandSearchCredentials,
     ProverCommandFetchCredentials,
     ProverCommandCloseCredentialsSearch,
+    ProverCommandCountCredentialsByTag,
+    ProverCommandGetDistinctCredentialTagValues,
     ProverCommandGetCredentialsForProofReq,
     ProverCommandSearchCredentialsForProofReq,
     ProverCommandFetchCredentialForProofReq,
@@ -441,7 +472,11 @@ pub enum CommandMetric {
     PoolCommandCloseAck,
     PoolCommandRefresh,
     PoolCommandRefreshAck,
+    PoolCommandGetStatus,
+    PoolCommandGetStatusAck,
     PoolCommandSetProtocolVersion,
+    PoolCommandRegisterTransport,
+    PoolCommandDeliverTransportReply,
     // DidCommand
     DidCommandCreateAndStoreMyDid,
     DidCommandReplaceKeysStart,
@@ -461,6 +496,7 @@ pub enum CommandMetric {
     DidCommandQualifyDid,
     // WalletCommand
     WalletCommandRegisterWalletType,
+    WalletCommandRegisterKeyWrapProvider,
     WalletCommandCreate,
     WalletCommandCreateContinue,
     WalletCommandOpen,
@@ -468,10 +504,24 @@ pub enum CommandMetric {
     WalletCommandClose,
     WalletCommandDelete,
     WalletCommandDeleteContinue,
+    WalletCommandListProfiles,
+    WalletCommandListProfilesContinue,
     WalletCommandExport,
+    WalletCommandExportToSink,
     WalletCommandExportContinue,
     WalletCommandImport,
+    WalletCommandImportFromSource,
     WalletCommandImportContinue,
+    WalletCommandImportDelta,
+    WalletCommandImportDeltaContinue,
+    WalletCommandMigrate,
+    WalletCommandMigrateContinue,
+    WalletCommandGetRevision,
+    WalletCommandCheck,
+    WalletCommandSubscribe,
+    WalletCommandUnsubscribe,
+    WalletCommandRotateItemKeys,
+    WalletCommandRotateItemKeysContinue,
     WalletCommandGenerateKey,
     WalletCommandDeriveKey,
     // PairwiseCommand
@@ -487,6 +537,14 @@ pub enum CommandMetric {
     NonSecretsCommandAddRecordTags,
     NonSecretsCommandDeleteRecordTags,
     NonSecretsCommandDeleteRecord,
+    NonSecretsCommandBatch,
+    NonSecretsCommandBeginBatch,
+    NonSecretsCommandStageBatch,
+    NonSecretsCommandCommitBatch,
+    NonSecretsCommandRollbackBatch,
+    NonSecretsCommandPurgeExpiredRecords,
+    NonSecretsCommandCountRecordsByTag,
+    NonSecretsCommandGetDistinctRecordTagValues,
     NonSecretsCommandGetRecord,
     NonSecretsCommandOpenSearch,
     NonSecretsCommandFetchSearchNextRecords,
@@ -5,7 +5,7 @@ use super::zmq;
 
 use byteorder::{ByteOrder, LittleEndian};
 use indy_api_types::INVALID_COMMAND_HANDLE;
-use crate::services::pool::{COMMAND_CONNECT, COMMAND_EXIT, COMMAND_REFRESH};
+use crate::services::pool::{COMMAND_CONNECT, COMMAND_EXIT, COMMAND_REFRESH, COMMAND_STATUS};
 
 pub struct Commander {
     cmd_socket: zmq::Socket,
@@ -37,6 +37,8 @@ impl Commander {
             Some(PoolEvent::Close(id))
         } else if COMMAND_REFRESH.eq(cmd_s.as_str()) {
             Some(PoolEvent::Refresh(id))
+        } else if COMMAND_STATUS.eq(cmd_s.as_str()) {
+            Some(PoolEvent::GetStatus(id))
         } else if COMMAND_CONNECT.eq(cmd_s.as_str()) {
             Some(PoolEvent::CheckCache(id))
         } else {
@@ -65,7 +67,7 @@ mod commander_tests {
     use super::*;
     use indy_api_types::{CommandHandle};
     use indy_utils::next_command_handle;
-    use crate::services::pool::{COMMAND_REFRESH, COMMAND_EXIT, pool_create_pair_of_sockets};
+    use crate::services::pool::{COMMAND_REFRESH, COMMAND_STATUS, COMMAND_EXIT, pool_create_pair_of_sockets};
 
     fn new_commander() -> Commander {
         let zmq_ctx = zmq::Context::new();
@@ -125,6 +127,19 @@ mod commander_tests {
         assert_match!(Some(PoolEvent::Refresh(cmd_id_)), cmd.fetch_events(), cmd_id_, cmd_id);
     }
 
+    #[test]
+    pub fn commander_fetch_get_status_event_works() {
+        let (send_cmd_sock, recv_cmd_sock) = pool_create_pair_of_sockets("get_status");
+
+        let cmd = Commander::new(recv_cmd_sock);
+
+        let cmd_id: CommandHandle = next_command_handle();
+        let mut buf = [0u8; 4];
+        LittleEndian::write_i32(&mut buf, cmd_id);
+        send_cmd_sock.send_multipart(&[COMMAND_STATUS.as_bytes(), &buf], zmq::DONTWAIT).expect("FIXME");
+        assert_match!(Some(PoolEvent::GetStatus(cmd_id_)), cmd.fetch_events(), cmd_id_, cmd_id);
+    }
+
     #[test]
     pub fn commander_fetch_check_cache_event_works() {
         let (send_cmd_sock, recv_cmd_sock) = pool_create_pair_of_sockets("check_cache");
@@ -66,12 +66,27 @@ pub enum NetworkerEvent {
         String, //req_id
         Option<String>, //node_alias
     ),
+    UpdateNodeHealth(
+        String, //req_id
+        String, //node_alias
+        NodeOutcome,
+    ),
+    Timeout,
+}
+
+/// Result of the request sent to the node, networker rates nodes health by them
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum NodeOutcome {
+    Reply,
     Timeout,
+    Nack,
+    InvalidStateProof,
 }
 
 pub const COMMAND_EXIT : &str = "exit";
 pub const COMMAND_CONNECT : &str = "connect";
 pub const COMMAND_REFRESH : &str = "refresh";
+pub const COMMAND_STATUS : &str = "status";
 
 #[derive(Clone, Debug)]
 pub enum PoolEvent {
@@ -86,6 +101,9 @@ pub enum PoolEvent {
     Refresh(
         CommandHandle
     ),
+    GetStatus(
+        CommandHandle
+    ),
     CatchupTargetFound(
         Vec<u8>, //target_mt_root
         usize, //target_mt_size
@@ -95,8 +113,10 @@ pub enum PoolEvent {
         MerkleTree,
     ),
     CatchupTargetNotFound(IndyError),
-    #[allow(dead_code)] //FIXME
     PoolOutdated,
+    InvalidStateProof(
+        String, // node alias
+    ),
     Synced(
         MerkleTree
     ),
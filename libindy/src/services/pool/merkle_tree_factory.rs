@@ -11,11 +11,50 @@ use indy_api_types::errors::prelude::*;
 use crate::services::ledger::merkletree::merkletree::MerkleTree;
 use crate::services::pool::types::{NodeTransaction, NodeTransactionV0, NodeTransactionV1};
 use crate::utils::environment;
+use indy_utils::crypto::hash::Hash;
 
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use rust_base58::{FromBase58, ToBase58};
 
 const POOL_EXT : &str = "txn";
 
+/// Compact state of the pool ledger saved after catchup.
+/// It allows to skip rebuilding of merkle tree and node state on pool opening if cached transactions match it.
+#[derive(Serialize, Deserialize, Debug)]
+struct PoolLedgerSnapshot {
+    size: usize,
+    root: String,
+    frontier: Vec<String>,
+    cache_len: u64,
+    tail: Option<String>,
+    protocol_version: usize,
+    nodes: HashMap<String, NodeTransactionV1>,
+}
+
+impl PoolLedgerSnapshot {
+    fn is_made_for(&self, merkle_tree: &MerkleTree) -> bool {
+        self.size == merkle_tree.count()
+            && self.root.from_base58().ok().as_ref() == Some(merkle_tree.root_hash())
+            && self.frontier == merkle_tree.frontier().iter().map(|hash| hash.to_base58()).collect::<Vec<String>>()
+    }
+
+    /// Returns the frontier if cached transactions have the same number, length and the last one.
+    fn frontier_for(&self, txns: &[Vec<u8>]) -> Option<Vec<Vec<u8>>> {
+        if self.size != txns.len() || self.frontier.len() != self.size.count_ones() as usize {
+            return None;
+        }
+
+        let (cache_len, tail) = _cache_digest(txns.iter());
+        if self.cache_len != cache_len || self.tail != tail {
+            return None;
+        }
+
+        self.frontier.iter()
+            .map(|hash| hash.from_base58().ok())
+            .collect()
+    }
+}
+
 pub fn create(pool_name: &str) -> IndyResult<MerkleTree> {
     let mut p = environment::pool_path(pool_name);
 
@@ -36,11 +75,13 @@ pub fn create(pool_name: &str) -> IndyResult<MerkleTree> {
         _from_genesis(&p)
     } else {
         trace!("Restoring merkle tree from cache");
-        _from_cache(&p_stored)
+        _from_cache(&p_stored, pool_name)
     }
 }
 
 pub fn drop_cache(pool_name: &str) -> IndyResult<()> {
+    _drop_snapshot(pool_name);
+
     let p = get_pool_stored_path(pool_name, false);
     if p.exists() {
         warn!("Cache is invalid -- dropping it!");
@@ -52,9 +93,40 @@ pub fn drop_cache(pool_name: &str) -> IndyResult<()> {
     }
 }
 
-fn _from_cache(file_name: &PathBuf) -> IndyResult<MerkleTree> {
+fn _from_cache(file_name: &PathBuf, pool_name: &str) -> IndyResult<MerkleTree> {
+    let mut txns = _read_cache(file_name)?;
+
+    if let Some(snapshot) = _read_snapshot(pool_name) {
+        if let Some(frontier) = snapshot.frontier_for(&txns) {
+            let mt = MerkleTree::from_frontier(frontier, txns)?;
+            if snapshot.is_made_for(&mt) {
+                trace!("Merkle tree is restored from snapshot");
+                return Ok(mt);
+            }
+
+            txns = mt.into_iter().collect();
+        }
+
+        warn!("Pool ledger snapshot doesn't match cached transactions -- dropping it!");
+        _drop_snapshot(pool_name);
+    }
+
+    _from_txns(txns)
+}
+
+fn _from_txns(txns: Vec<Vec<u8>>) -> IndyResult<MerkleTree> {
     let mut mt = MerkleTree::from_vec(Vec::new())?;
 
+    for txn in txns {
+        mt.append(txn)?;
+    }
+
+    Ok(mt)
+}
+
+fn _read_cache(file_name: &PathBuf) -> IndyResult<Vec<Vec<u8>>> {
+    let mut txns = Vec::new();
+
     let mut f = fs::File::open(file_name)
         .to_indy(IndyErrorKind::IOError, "Can't open pool ledger cache file")?;
 
@@ -78,10 +150,23 @@ fn _from_cache(file_name: &PathBuf) -> IndyResult<MerkleTree> {
             }
         }
 
-        mt.append(buf.to_vec())?;
+        txns.push(buf);
     }
 
-    Ok(mt)
+    Ok(txns)
+}
+
+/// Returns the length of cache file with the given transactions and base58 hash of the last one.
+fn _cache_digest<'a, I>(txns: I) -> (u64, Option<String>) where I: Iterator<Item=&'a Vec<u8>> {
+    let mut cache_len = 0;
+    let mut tail = None;
+
+    for txn in txns {
+        cache_len += 8 + txn.len() as u64;
+        tail = Some(txn);
+    }
+
+    (cache_len, tail.and_then(|txn| Hash::hash_leaf(txn).ok()).map(|hash| hash.to_base58()))
 }
 
 fn _from_genesis(file_name: &PathBuf) -> IndyResult<MerkleTree> {
@@ -228,6 +313,85 @@ pub fn build_node_state(merkle_tree: &MerkleTree) -> IndyResult<HashMap<String,
     Ok(gen_tnxs)
 }
 
+/// Returns node state from the snapshot if it's made for the same pool ledger,
+/// otherwise builds it from transactions and saves new snapshot.
+pub fn load_node_state(pool_name: &str, merkle_tree: &MerkleTree) -> IndyResult<HashMap<String, NodeTransactionV1>> {
+    if let Some(snapshot) = _read_snapshot(pool_name) {
+        if snapshot.protocol_version == ProtocolVersion::get() && snapshot.is_made_for(merkle_tree) {
+            trace!("Node state is restored from snapshot");
+            return Ok(snapshot.nodes);
+        }
+    }
+
+    let (cache_len, tail) = _cache_digest(merkle_tree.iter());
+
+    let snapshot = PoolLedgerSnapshot {
+        size: merkle_tree.count(),
+        root: merkle_tree.root_hash().to_base58(),
+        frontier: merkle_tree.frontier().iter().map(|hash| hash.to_base58()).collect(),
+        cache_len,
+        tail,
+        protocol_version: ProtocolVersion::get(),
+        nodes: build_node_state(merkle_tree)?,
+    };
+
+    if let Err(err) = _write_snapshot(pool_name, &snapshot) {
+        warn!("Can't save pool ledger snapshot: {:?}", err);
+    }
+
+    Ok(snapshot.nodes)
+}
+
+fn get_pool_snapshot_path(pool_name: &str) -> PathBuf {
+    get_pool_stored_path_base(pool_name, false, "snapshot", "json")
+}
+
+fn _read_snapshot(pool_name: &str) -> Option<PoolLedgerSnapshot> {
+    let p = get_pool_snapshot_path(pool_name);
+    if !p.exists() {
+        return None;
+    }
+
+    fs::File::open(p)
+        .to_indy(IndyErrorKind::IOError, "Can't open pool ledger snapshot file")
+        .and_then(|f| serde_json::from_reader(io::BufReader::new(f))
+            .to_indy(IndyErrorKind::InvalidState, "Malformed pool ledger snapshot file"))
+        .map_err(|err| warn!("Pool ledger snapshot is ignored: {:?}", err))
+        .ok()
+}
+
+/// Writes the snapshot to temporary file and renames it, so interrupted write can't leave truncated snapshot.
+fn _write_snapshot(pool_name: &str, snapshot: &PoolLedgerSnapshot) -> IndyResult<()> {
+    let p = get_pool_snapshot_path(pool_name);
+    let p_tmp = get_pool_stored_path_base(pool_name, false, "snapshot", "json.tmp");
+
+    let f = fs::File::create(&p_tmp)
+        .to_indy(IndyErrorKind::IOError, "Can't create pool ledger snapshot file")?;
+
+    let mut writer = io::BufWriter::new(f);
+
+    serde_json::to_writer(&mut writer, snapshot)
+        .to_indy(IndyErrorKind::IOError, "Can't write to pool ledger snapshot file")?;
+
+    writer.flush()
+        .to_indy(IndyErrorKind::IOError, "Can't write to pool ledger snapshot file")?;
+
+    writer.get_ref().sync_all()
+        .to_indy(IndyErrorKind::IOError, "Can't write to pool ledger snapshot file")?;
+
+    fs::rename(p_tmp, p)
+        .to_indy(IndyErrorKind::IOError, "Can't replace pool ledger snapshot file")
+}
+
+fn _drop_snapshot(pool_name: &str) {
+    let p = get_pool_snapshot_path(pool_name);
+    if p.exists() {
+        fs::remove_file(p)
+            .map_err(|err| warn!("Can't drop pool ledger snapshot file: {:?}", err))
+            .ok();
+    }
+}
+
 pub fn from_file(txn_file: &str) -> IndyResult<MerkleTree> {
     _from_genesis(&PathBuf::from(txn_file))
 }
@@ -310,6 +474,103 @@ mod tests {
         test::cleanup_storage("pool_worker_works_for_deserialize_cache");
     }
 
+    #[test]
+    fn pool_worker_load_node_state_works_for_snapshot() {
+        test::cleanup_storage("pool_worker_load_node_state_works_for_snapshot");
+
+        _set_protocol_version(TEST_PROTOCOL_VERSION);
+
+        let pool_name = "pool_worker_load_node_state_works_for_snapshot";
+        _write_genesis_txns(pool_name, &test::gen_txns().join("\n"));
+        let txn = _parse_txn_from_json(&test::gen_txns()[0]).unwrap();
+        dump_new_txns(pool_name, &[txn]).unwrap();
+
+        let merkle_tree = super::create(pool_name).unwrap();
+        let node_state = super::load_node_state(pool_name, &merkle_tree).unwrap();
+        assert!(get_pool_snapshot_path(pool_name).exists());
+        assert!(!get_pool_stored_path_base(pool_name, false, "snapshot", "json.tmp").exists());
+
+        let restored_merkle_tree = super::create(pool_name).unwrap();
+        assert_eq!(5, restored_merkle_tree.count());
+        assert_eq!(merkle_tree.root_hash(), restored_merkle_tree.root_hash());
+        assert!(merkle_tree.iter().eq(restored_merkle_tree.iter()));
+
+        let restored_node_state = super::load_node_state(pool_name, &restored_merkle_tree).unwrap();
+        assert_eq!(node_state, restored_node_state);
+
+        test::cleanup_storage("pool_worker_load_node_state_works_for_snapshot");
+    }
+
+    #[test]
+    fn pool_worker_restore_merkle_tree_works_for_outdated_snapshot() {
+        test::cleanup_storage("pool_worker_restore_merkle_tree_works_for_outdated_snapshot");
+
+        _set_protocol_version(TEST_PROTOCOL_VERSION);
+
+        let pool_name = "pool_worker_restore_merkle_tree_works_for_outdated_snapshot";
+        _write_genesis_txns(pool_name, &test::gen_txns().join("\n"));
+        let txn = _parse_txn_from_json(&test::gen_txns()[0]).unwrap();
+        dump_new_txns(pool_name, &[txn.clone()]).unwrap();
+
+        let merkle_tree = super::create(pool_name).unwrap();
+        super::load_node_state(pool_name, &merkle_tree).unwrap();
+        assert!(get_pool_snapshot_path(pool_name).exists());
+
+        dump_new_txns(pool_name, &[txn]).unwrap();
+
+        let merkle_tree = super::create(pool_name).unwrap();
+        assert_eq!(6, merkle_tree.count());
+        assert!(!get_pool_snapshot_path(pool_name).exists());
+
+        test::cleanup_storage("pool_worker_restore_merkle_tree_works_for_outdated_snapshot");
+    }
+
+    #[test]
+    fn pool_worker_restore_merkle_tree_works_for_changed_cache_tail() {
+        test::cleanup_storage("pool_worker_restore_merkle_tree_works_for_changed_cache_tail");
+
+        _set_protocol_version(TEST_PROTOCOL_VERSION);
+
+        let pool_name = "pool_worker_restore_merkle_tree_works_for_changed_cache_tail";
+        _write_genesis_txns(pool_name, &test::gen_txns().join("\n"));
+        let txn = _parse_txn_from_json(&test::gen_txns()[0]).unwrap();
+        dump_new_txns(pool_name, &[txn]).unwrap();
+
+        let merkle_tree = super::create(pool_name).unwrap();
+        super::load_node_state(pool_name, &merkle_tree).unwrap();
+        assert!(get_pool_snapshot_path(pool_name).exists());
+
+        let p = get_pool_stored_path(pool_name, false);
+        let mut txns = _read_cache(&p).unwrap();
+        txns.last_mut().unwrap()[0] ^= 1;
+        _dump_vec_to_file(&txns, &mut fs::File::create(&p).unwrap()).unwrap();
+
+        let restored_merkle_tree = super::create(pool_name).unwrap();
+        assert_eq!(5, restored_merkle_tree.count());
+        assert_eq!(MerkleTree::from_vec(txns).unwrap().root_hash(), restored_merkle_tree.root_hash());
+        assert_ne!(merkle_tree.root_hash(), restored_merkle_tree.root_hash());
+        assert!(!get_pool_snapshot_path(pool_name).exists());
+
+        test::cleanup_storage("pool_worker_restore_merkle_tree_works_for_changed_cache_tail");
+    }
+
+    #[test]
+    fn pool_worker_load_node_state_works_for_malformed_snapshot() {
+        test::cleanup_storage("pool_worker_load_node_state_works_for_malformed_snapshot");
+
+        _set_protocol_version(TEST_PROTOCOL_VERSION);
+
+        let pool_name = "pool_worker_load_node_state_works_for_malformed_snapshot";
+        _write_genesis_txns(pool_name, &test::gen_txns().join("\n"));
+        fs::write(get_pool_snapshot_path(pool_name), "{}").unwrap();
+
+        let merkle_tree = super::create(pool_name).unwrap();
+        let node_state = super::load_node_state(pool_name, &merkle_tree).unwrap();
+        assert_eq!(4, node_state.len());
+
+        test::cleanup_storage("pool_worker_load_node_state_works_for_malformed_snapshot");
+    }
+
     #[test]
     fn pool_worker_restore_merkle_tree_works_from_genesis_txns() {
         test::cleanup_storage("pool_worker_restore_merkle_tree_works_from_genesis_txns");
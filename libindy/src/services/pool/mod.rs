@@ -12,12 +12,13 @@ use std::{fs, io};
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::io::Write;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 
 use serde_json;
 use serde::de::DeserializeOwned;
 
 use crate::api::ledger::{CustomFree, CustomTransactionParser};
+use crate::api::pool::CustomTransportSend;
 use crate::domain::{
     pool::{PoolConfig, PoolOpenConfig},
     ledger::response::{
@@ -29,7 +30,8 @@ use crate::domain::{
 use indy_api_types::errors::*;
 use crate::services::pool::pool::{Pool, ZMQPool};
 use crate::utils::environment;
-use crate::services::pool::events::{COMMAND_EXIT, COMMAND_CONNECT, COMMAND_REFRESH};
+use crate::services::pool::events::{COMMAND_EXIT, COMMAND_CONNECT, COMMAND_REFRESH, COMMAND_STATUS};
+use crate::services::pool::transport::{CustomTransport, LoopbackTransport, Transport, LOOPBACK_TRANSPORT};
 use indy_api_types::{CommandHandle, PoolHandle};
 use indy_utils::{next_command_handle, next_pool_handle};
 use ursa::bls::VerKey;
@@ -42,10 +44,16 @@ mod networker;
 mod pool;
 mod request_handler;
 mod state_proof;
+mod transport;
 mod types;
 
 lazy_static! {
     static ref REGISTERED_SP_PARSERS: Mutex<HashMap<String, (CustomTransactionParser, CustomFree)>> = Mutex::new(HashMap::new());
+    static ref REGISTERED_TRANSPORTS: Mutex<HashMap<String, Arc<dyn Transport>>> = Mutex::new({
+        let mut transports: HashMap<String, Arc<dyn Transport>> = HashMap::new();
+        transports.insert(LOOPBACK_TRANSPORT.to_string(), Arc::new(LoopbackTransport::echo()));
+        transports
+    });
 }
 
 type Nodes = HashMap<String, Option<VerKey>>;
@@ -153,16 +161,31 @@ impl PoolService {
 
         let config = config.unwrap_or_default();
 
+        let transport = match config.transport {
+            Some(ref transport_name) => Some(PoolService::get_transport(transport_name)
+                .ok_or_else(|| err_msg(IndyErrorKind::InvalidStructure, format!("Pool transport {} isn't registered", transport_name)))?),
+            None => None
+        };
+
         let pool_handle: PoolHandle = next_pool_handle();
         let mut new_pool = Pool::new(name, pool_handle, config);
 
+        let transport_socket = transport.map(|transport| {
+            let (send_transport_sock, recv_transport_sock) = pool_create_pair_of_sockets(&format!("transport_{}", pool_handle));
+            new_pool.set_transport(transport, recv_transport_sock);
+            send_transport_sock
+        });
+
         let (send_cmd_sock, recv_cmd_sock) = pool_create_pair_of_sockets(&format!("pool_{}", name));
 
         new_pool.work(recv_cmd_sock);
         self._send_msg(pool_handle, COMMAND_CONNECT, &send_cmd_sock, None, None)?;
 
+        let mut zmq_pool = ZMQPool::new(new_pool, send_cmd_sock);
+        zmq_pool.transport_socket = transport_socket;
+
         self.pending_pools.try_borrow_mut()?
-            .insert(new_pool.get_id(), ZMQPool::new(new_pool, send_cmd_sock));
+            .insert(pool_handle, zmq_pool);
         Ok(pool_handle)
     }
 
@@ -214,6 +237,41 @@ impl PoolService {
         parsers.get(txn_type).map(Clone::clone)
     }
 
+    pub fn register_transport(name: &str, send: CustomTransportSend) -> IndyResult<()> {
+        if name == LOOPBACK_TRANSPORT {
+            return Err(err_msg(IndyErrorKind::InvalidStructure,
+                               format!("Try to override built-in pool transport {}", name)));
+        }
+
+        REGISTERED_TRANSPORTS.lock()
+            .map(|mut map| {
+                map.insert(name.to_owned(), Arc::new(CustomTransport::new(send)));
+            })
+            .unwrap(); // FIXME: Can we avoid unwrap?
+
+        Ok(())
+    }
+
+    fn get_transport(name: &str) -> Option<Arc<dyn Transport>> {
+        let transports = REGISTERED_TRANSPORTS.lock().unwrap(); // FIXME: Can we avoid unwrap here?
+        transports.get(name).cloned()
+    }
+
+    pub fn deliver_transport_reply(&self, handle: PoolHandle, node_alias: &str, msg: &str) -> IndyResult<()> {
+        // Replies for pool ledger catchup come while pool is still opening
+        let open_pools = self.open_pools.try_borrow()?;
+        let pending_pools = self.pending_pools.try_borrow()?;
+
+        let pool = open_pools.get(&handle)
+            .or_else(|| pending_pools.get(&handle))
+            .ok_or_else(|| err_msg(IndyErrorKind::InvalidPoolHandle, format!("No pool with requested handle {:?}", handle)))?;
+
+        let socket = pool.transport_socket.as_ref()
+            .ok_or_else(|| err_msg(IndyErrorKind::InvalidStructure, format!("Pool {:?} doesn't use custom transport", handle)))?;
+
+        Ok(socket.send_multipart(&[node_alias.as_bytes(), msg.as_bytes()], zmq::DONTWAIT)?)
+    }
+
     pub fn close(&self, handle: PoolHandle) -> IndyResult<CommandHandle> {
         let cmd_id: CommandHandle = next_command_handle();
 
@@ -231,6 +289,10 @@ impl PoolService {
         self.send_action(handle, COMMAND_REFRESH, None, None)
     }
 
+    pub fn get_status(&self, handle: PoolHandle) -> IndyResult<i32> {
+        self.send_action(handle, COMMAND_STATUS, None, None)
+    }
+
     fn _send_msg(&self, cmd_id: CommandHandle, msg: &str, socket: &Socket, nodes: Option<&str>, timeout: Option<i32>) -> IndyResult<()> {
         let mut buf = [0u8; 4];
         let mut buf_to = [0u8; 4];
@@ -407,6 +469,21 @@ mod tests {
             assert_eq!(cmd_id, LittleEndian::read_i32(recv[1].as_slice()));
         }
 
+        #[test]
+        fn pool_service_get_status_works() {
+            test::cleanup_storage("pool_service_get_status_works");
+
+            let ps = PoolService::new();
+            let pool_id = next_pool_handle();
+            let (send_cmd_sock, recv_cmd_sock) = pool_create_pair_of_sockets("pool_service_get_status_works");
+            ps.open_pools.borrow_mut().insert(pool_id, ZMQPool::new(Pool::new("", pool_id, PoolOpenConfig::default()), send_cmd_sock));
+            let cmd_id = ps.get_status(pool_id).unwrap();
+            let recv = recv_cmd_sock.recv_multipart(zmq::DONTWAIT).unwrap();
+            assert_eq!(recv.len(), 3);
+            assert_eq!(COMMAND_STATUS, String::from_utf8(recv[0].clone()).unwrap());
+            assert_eq!(cmd_id, LittleEndian::read_i32(recv[1].as_slice()));
+        }
+
         #[test]
         fn pool_service_delete_works() {
             test::cleanup_storage("pool_service_delete_works");
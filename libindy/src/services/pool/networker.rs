@@ -1,5 +1,6 @@
 use std::cell::RefCell;
 use std::collections::{BTreeMap, HashMap, HashSet};
+use std::rc::Rc;
 
 use rand::thread_rng;
 use rand::prelude::SliceRandom;
@@ -7,6 +8,7 @@ use time::Tm;
 
 use indy_api_types::errors::prelude::*;
 use crate::services::pool::events::*;
+use crate::services::pool::transport::PoolTransport;
 use crate::services::pool::types::*;
 use indy_utils::sequence;
 use indy_utils::crypto::base64;
@@ -22,16 +24,96 @@ pub trait Networker {
     fn process_event(&mut self, pe: Option<NetworkerEvent>) -> Option<RequestEvent>;
     fn get_timeout(&self) -> ((String, String), i64);
     fn get_poll_items(&self) -> Vec<PollItem>;
+    fn get_nodes_status(&self) -> Vec<NodeStatus>;
+    fn set_transport(&mut self, transport: PoolTransport);
+}
+
+// Count of timeouts and NACKs in a row after which the node is blacklisted
+const NODE_FAILURES_TO_BLACKLIST: u64 = 3;
+// Seconds while blacklisted node is asked for reads only if all other nodes are already asked
+const NODE_BLACKLIST_TIMEOUT: i64 = 60;
+// Weight of the last reply latency in the node average latency
+const NODE_LATENCY_WEIGHT: i64 = 5;
+
+#[derive(Debug, Default)]
+struct NodeHealth {
+    replies: u64,
+    timeouts: u64,
+    nacks: u64,
+    invalid_state_proofs: u64,
+    failures_in_row: u64,
+    latency_ms: Option<i64>,
+    blacklisted_until: Option<Tm>,
+}
+
+impl NodeHealth {
+    fn update(&mut self, outcome: NodeOutcome, latency: Option<Duration>) {
+        match outcome {
+            NodeOutcome::Reply => {
+                self.replies += 1;
+                self.failures_in_row = 0;
+
+                if let Some(latency) = latency {
+                    let latency = latency.num_milliseconds();
+                    self.latency_ms = Some(self.latency_ms.map(|avg| avg + (latency - avg) / NODE_LATENCY_WEIGHT).unwrap_or(latency));
+                }
+            }
+            NodeOutcome::Timeout => {
+                self.timeouts += 1;
+                self.failures_in_row += 1;
+            }
+            NodeOutcome::Nack => {
+                self.nacks += 1;
+                self.failures_in_row += 1;
+            }
+            NodeOutcome::InvalidStateProof => {
+                // node returns data that can't be trusted, no reason to wait for more failures
+                self.invalid_state_proofs += 1;
+                self.failures_in_row = NODE_FAILURES_TO_BLACKLIST;
+            }
+        }
+
+        if self.failures_in_row >= NODE_FAILURES_TO_BLACKLIST {
+            self.failures_in_row = 0;
+            self.blacklisted_until = Some(time::now() + Duration::seconds(NODE_BLACKLIST_TIMEOUT));
+        }
+    }
+
+    fn is_blacklisted(&self) -> bool {
+        self.blacklisted_until.map(|until| time::now() < until).unwrap_or(false)
+    }
+
+    // Share of successful replies, nodes without requests are considered healthy
+    fn score(&self) -> f64 {
+        let total = self.replies + self.timeouts + self.nacks + self.invalid_state_proofs;
+
+        if total == 0 {
+            1.0
+        } else {
+            self.replies as f64 / total as f64
+        }
+    }
 }
 
 pub struct ZMQNetworker {
     req_id_mappings: HashMap<String, i32>,
     pool_connections: BTreeMap<i32, PoolConnection>,
     nodes: Vec<RemoteNode>,
+    nodes_health: HashMap<String, NodeHealth>,
     active_timeout: i64,
     conn_limit: usize,
     preordered_nodes: Vec<String>,
     socks_proxy: String,
+    transport: Option<Rc<PoolTransport>>,
+}
+
+impl ZMQNetworker {
+    fn _get_blacklisted_nodes(&self) -> HashSet<String> {
+        self.nodes_health.iter()
+            .filter(|(_, health)| health.is_blacklisted())
+            .map(|(alias, _)| alias.clone())
+            .collect()
+    }
 }
 
 impl Networker for ZMQNetworker {
@@ -40,25 +122,34 @@ impl Networker for ZMQNetworker {
             req_id_mappings: HashMap::new(),
             pool_connections: BTreeMap::new(),
             nodes: Vec::new(),
+            nodes_health: HashMap::new(),
             active_timeout,
             conn_limit,
             preordered_nodes,
             socks_proxy,
+            transport: None,
         }
     }
 
     fn fetch_events(&self, poll_items: &[PollItem]) -> Vec<PoolEvent> {
-        let mut cnt = 0;
-        self.pool_connections.iter().map(|(_, pc)| {
+        // Transport replies socket goes first, see get_poll_items
+        let (mut events, mut cnt) = match self.transport {
+            Some(ref transport) if poll_items[0].is_readable() => (transport.fetch_events(), 1),
+            Some(_) => (Vec::new(), 1),
+            None => (Vec::new(), 0)
+        };
+        events.extend(self.pool_connections.iter().map(|(_, pc)| {
             let ocnt = cnt;
             cnt += pc.sockets.iter().filter(|s| s.is_some()).count();
             pc.fetch_events(&poll_items[ocnt..cnt])
-        }).flat_map(|v| v.into_iter()).collect()
+        }).flat_map(|v| v.into_iter()));
+        events
     }
 
     fn process_event(&mut self, pe: Option<NetworkerEvent>) -> Option<RequestEvent> {
         match pe.clone() {
             Some(NetworkerEvent::SendAllRequest(_, req_id, _, _)) | Some(NetworkerEvent::SendOneRequest(_, req_id, _)) | Some(NetworkerEvent::Resend(req_id, _)) => {
+                let blacklisted = self._get_blacklisted_nodes();
                 let num = self.req_id_mappings.get(&req_id).copied().or_else(|| {
                     trace!("sending new request");
                     self.pool_connections.iter().next_back().and_then(|(pc_idx, pc)| {
@@ -78,7 +169,7 @@ impl Networker for ZMQNetworker {
                         trace!("send request in existing conn");
 
                         match self.pool_connections.get_mut(&idx) {
-                            Some(pc) => pc.send_request(pe).expect("FIXME"),
+                            Some(pc) => pc.send_request(pe, &blacklisted).expect("FIXME"),
                             None => error!("Pool Connection not found")
                         }
                         self.req_id_mappings.insert(req_id.clone(), idx);
@@ -86,8 +177,8 @@ impl Networker for ZMQNetworker {
                     None => {
                         trace!("send request in new conn");
                         let pc_id = sequence::get_next_id();
-                        let mut pc = PoolConnection::new(self.nodes.clone(), self.active_timeout, self.preordered_nodes.clone(), self.socks_proxy.clone());
-                        pc.send_request(pe).expect("FIXME");
+                        let mut pc = PoolConnection::new(self.nodes.clone(), self.active_timeout, self.preordered_nodes.clone(), self.socks_proxy.clone(), self.transport.clone());
+                        pc.send_request(pe, &blacklisted).expect("FIXME");
                         self.pool_connections.insert(pc_id, pc);
                         self.req_id_mappings.insert(req_id.clone(), pc_id);
                     }
@@ -109,6 +200,21 @@ impl Networker for ZMQNetworker {
                 );
                 None
             }
+            Some(NetworkerEvent::UpdateNodeHealth(req_id, node_alias, outcome)) => {
+                let latency = self.req_id_mappings.get(&req_id)
+                    .and_then(|idx| self.pool_connections.get(idx))
+                    .and_then(|pc| pc.get_send_time(&req_id, &node_alias))
+                    .map(|send_time| time::now() - send_time);
+
+                let health = self.nodes_health.entry(node_alias.clone()).or_insert_with(NodeHealth::default);
+                let was_blacklisted = health.is_blacklisted();
+                health.update(outcome, latency);
+
+                if !was_blacklisted && health.is_blacklisted() {
+                    warn!("Node {} is blacklisted for reads for {} seconds after {:?}", node_alias, NODE_BLACKLIST_TIMEOUT, outcome);
+                }
+                None
+            }
             Some(NetworkerEvent::CleanTimeout(req_id, node_alias)) => {
                 {
                     let idx_pc_to_delete = self.req_id_mappings.get(&req_id).and_then(
@@ -162,8 +268,37 @@ impl Networker for ZMQNetworker {
     }
 
     fn get_poll_items(&self) -> Vec<PollItem> {
-        self.pool_connections.iter()
-            .flat_map(|(_, pool)| pool.get_poll_items()).collect()
+        self.transport.iter()
+            .map(|transport| transport.get_poll_item())
+            .chain(self.pool_connections.iter().flat_map(|(_, pool)| pool.get_poll_items()))
+            .collect()
+    }
+
+    fn get_nodes_status(&self) -> Vec<NodeStatus> {
+        let default_health = NodeHealth::default();
+
+        let mut status: Vec<NodeStatus> = self.nodes.iter()
+            .map(|node| {
+                let health = self.nodes_health.get(&node.name).unwrap_or(&default_health);
+                NodeStatus {
+                    alias: node.name.clone(),
+                    score: health.score(),
+                    blacklisted: health.is_blacklisted(),
+                    latency_ms: health.latency_ms,
+                    replies: health.replies,
+                    timeouts: health.timeouts,
+                    nacks: health.nacks,
+                    invalid_state_proofs: health.invalid_state_proofs,
+                }
+            })
+            .collect();
+
+        status.sort_by(|a, b| a.alias.cmp(&b.alias));
+        status
+    }
+
+    fn set_transport(&mut self, transport: PoolTransport) {
+        self.transport = Some(Rc::new(transport));
     }
 }
 
@@ -172,16 +307,18 @@ pub struct PoolConnection {
     sockets: Vec<Option<ZSocket>>,
     ctx: zmq::Context,
     key_pair: zmq::CurveKeyPair,
-    resend: RefCell<HashMap<String, (usize, String)>>,
+    resend: RefCell<HashMap<String, (usize, String, Vec<usize>)>>,
     timeouts: RefCell<HashMap<(String, String), Tm>>,
+    send_times: RefCell<HashMap<(String, String), Tm>>,
     time_created: time::Tm,
     req_cnt: usize,
     active_timeout: i64,
     socks_proxy: String,
+    transport: Option<Rc<PoolTransport>>,
 }
 
 impl PoolConnection {
-    fn new(mut nodes: Vec<RemoteNode>, active_timeout: i64, preordered_nodes: Vec<String>, socks_proxy: String) -> Self {
+    fn new(mut nodes: Vec<RemoteNode>, active_timeout: i64, preordered_nodes: Vec<String>, socks_proxy: String, transport: Option<Rc<PoolTransport>>) -> Self {
         trace!("PoolConnection::new: from nodes {:?}", nodes);
 
         nodes.shuffle(&mut thread_rng());
@@ -206,9 +343,11 @@ impl PoolConnection {
             resend: RefCell::new(HashMap::new()),
             time_created: time::now(),
             timeouts: RefCell::new(HashMap::new()),
+            send_times: RefCell::new(HashMap::new()),
             req_cnt: 0,
             active_timeout,
-            socks_proxy
+            socks_proxy,
+            transport,
         }
     }
 
@@ -257,13 +396,14 @@ impl PoolConnection {
         res
     }
 
-    fn send_request(&mut self, pe: Option<NetworkerEvent>) -> IndyResult<()> {
-        trace!("send_request >> pe: {:?}", pe);
+    fn send_request(&mut self, pe: Option<NetworkerEvent>, blacklisted: &HashSet<String>) -> IndyResult<()> {
+        trace!("send_request >> pe: {:?}, blacklisted: {:?}", pe, blacklisted);
         match pe {
             Some(NetworkerEvent::SendOneRequest(msg, req_id, timeout)) => {
                 self.req_cnt += 1;
-                self._send_msg_to_one_node(0, req_id.clone(), msg.clone(), timeout)?;
-                self.resend.borrow_mut().insert(req_id, (0, msg));
+                let read_order = self._get_read_order(blacklisted);
+                self._send_msg_to_one_node(read_order[0], req_id.clone(), msg.clone(), timeout)?;
+                self.resend.borrow_mut().insert(req_id, (0, msg, read_order));
             }
             Some(NetworkerEvent::SendAllRequest(msg, req_id, timeout, nodes_to_send)) => {
                 self.req_cnt += 1;
@@ -274,10 +414,10 @@ impl PoolConnection {
                 }
             }
             Some(NetworkerEvent::Resend(req_id, timeout)) => {
-                let resend = if let Some(&mut (ref mut cnt, ref req)) = self.resend.borrow_mut().get_mut(&req_id) {
+                let resend = if let Some(&mut (ref mut cnt, ref req, ref read_order)) = self.resend.borrow_mut().get_mut(&req_id) {
                     *cnt += 1;
                     //TODO: FIXME: We can collect consensus just walking through if we are not collecting node aliases on the upper layer.
-                    Some((*cnt % self.nodes.len(), req.clone()))
+                    Some((read_order[*cnt % read_order.len()], req.clone()))
                 } else {
                     error!("Unknown req_id for resending {}", req_id); //FIXME handle at RH level
                     None
@@ -292,6 +432,19 @@ impl PoolConnection {
         Ok(())
     }
 
+    // Indexes of nodes to send read request to one by one, blacklisted nodes are asked last
+    fn _get_read_order(&self, blacklisted: &HashSet<String>) -> Vec<usize> {
+        let (mut read_order, blacklisted): (Vec<usize>, Vec<usize>) = (0..self.nodes.len())
+            .partition(|idx| !blacklisted.contains(&self.nodes[*idx].name));
+
+        read_order.extend(blacklisted);
+        read_order
+    }
+
+    fn get_send_time(&self, req_id: &str, node_alias: &str) -> Option<Tm> {
+        self.send_times.borrow().get(&(req_id.to_string(), node_alias.to_string())).cloned()
+    }
+
     fn extend_timeout(&self, req_id: &str, node_alias: &str, extended_timeout: i64) {
         if let Some(timeout) = self.timeouts.borrow_mut().get_mut(&(req_id.to_string(), node_alias.to_string())) {
             *timeout = time::now() + Duration::seconds(extended_timeout);
@@ -303,12 +456,15 @@ impl PoolConnection {
     fn clean_timeout(&self, req_id: &str, node_alias: Option<String>) {
         match node_alias {
             Some(node_alias) => {
-                self.timeouts.borrow_mut().remove(&(req_id.to_string(), node_alias));
+                let key = (req_id.to_string(), node_alias);
+                self.timeouts.borrow_mut().remove(&key);
+                self.send_times.borrow_mut().remove(&key);
             }
             None => {
                 let keys_to_remove: Vec<(String, String)> = self.timeouts.borrow().keys()
                     .cloned().filter(|&(ref req_id_timeout, _)| req_id == req_id_timeout).collect();
                 keys_to_remove.iter().for_each(|key| { self.timeouts.borrow_mut().remove(key); });
+                self.send_times.borrow_mut().retain(|&(ref req_id_sent, _), _| req_id != req_id_sent);
             }
         }
     }
@@ -323,11 +479,19 @@ impl PoolConnection {
 
     fn _send_msg_to_one_node(&mut self, idx: usize, req_id: String, req: String, timeout: i64) -> IndyResult<()> {
         trace!("_send_msg_to_one_node >> idx {}, req_id {}, req {}", idx, req_id, req);
-        {
-            let s = self._get_socket(idx)?;
-            s.send(&req, zmq::DONTWAIT)?;
+        match self.transport {
+            // Unreachable node is handled by timeout same way as for ZMQ socket
+            Some(ref transport) => if let Err(err) = transport.send(&self.nodes[idx], &req) {
+                warn!("Can't send request to node {} with transport: {:?}", self.nodes[idx].name, err);
+            },
+            None => {
+                let s = self._get_socket(idx)?;
+                s.send(&req, zmq::DONTWAIT)?;
+            }
         }
-        self.timeouts.borrow_mut().insert((req_id, self.nodes[idx].name.clone()), time::now() + Duration::seconds(timeout));
+        let now = time::now();
+        self.send_times.borrow_mut().insert((req_id.clone(), self.nodes[idx].name.clone()), now);
+        self.timeouts.borrow_mut().insert((req_id, self.nodes[idx].name.clone()), now + Duration::seconds(timeout));
         trace!("_send_msg_to_one_node <<");
         Ok(())
     }
@@ -397,6 +561,12 @@ impl Networker for MockNetworker {
     fn get_poll_items(&self) -> Vec<PollItem> {
         unimplemented!()
     }
+
+    fn get_nodes_status(&self) -> Vec<NodeStatus> {
+        Vec::new()
+    }
+
+    fn set_transport(&mut self, _transport: PoolTransport) {}
 }
 
 
@@ -428,6 +598,12 @@ pub mod networker_tests {
     #[cfg(test)]
     mod networker {
         use std::ops::Sub;
+        use std::sync::{Arc, Mutex};
+
+        use indy_api_types::PoolHandle;
+
+        use crate::services::pool::pool_create_pair_of_sockets;
+        use crate::services::pool::transport::Transport;
 
         use super::*;
 
@@ -478,6 +654,47 @@ pub mod networker_tests {
             assert!(nodes_emulator::next(&handle).is_none());
         }
 
+        #[test]
+        fn networker_process_send_request_event_works_for_transport() {
+            struct TestTransport(Mutex<Vec<(String, String, String)>>);
+
+            impl Transport for TestTransport {
+                fn send(&self, _pool_handle: PoolHandle, node_alias: &str, node_address: &str, msg: &str) -> IndyResult<()> {
+                    self.0.lock().unwrap().push((node_alias.to_string(), node_address.to_string(), msg.to_string()));
+                    Ok(())
+                }
+            }
+
+            let txn = nodes_emulator::node();
+            let rn = _remote_node(&txn);
+
+            let transport = Arc::new(TestTransport(Mutex::new(Vec::new())));
+            let (send_sock, recv_sock) = pool_create_pair_of_sockets("networker_process_send_request_event_works_for_transport");
+
+            let mut networker = ZMQNetworker::new(POOL_CON_ACTIVE_TO, MAX_REQ_PER_POOL_CON, vec![], String::new());
+            networker.set_transport(PoolTransport::new(1, transport.clone(), recv_sock));
+            networker.process_event(Some(NetworkerEvent::NodesStateUpdated(vec![rn.clone()])));
+            networker.process_event(Some(NetworkerEvent::SendOneRequest(MESSAGE.to_string(), REQ_ID.to_string(), POOL_ACK_TIMEOUT)));
+
+            assert_eq!(vec![(rn.name.clone(), rn.zaddr.clone(), MESSAGE.to_string())], *transport.0.lock().unwrap());
+
+            send_sock.send_multipart(&[rn.name.as_bytes(), "reply".as_bytes()], zmq::DONTWAIT).unwrap();
+
+            let mut poll_items = networker.get_poll_items();
+            assert_eq!(1, poll_items.len());
+            assert_eq!(1, zmq::poll(&mut poll_items, 1000).unwrap());
+
+            let events = networker.fetch_events(&poll_items);
+            assert_eq!(1, events.len());
+            match events[0] {
+                PoolEvent::NodeReply(ref msg, ref alias) => {
+                    assert_eq!("reply", msg);
+                    assert_eq!(&rn.name, alias);
+                }
+                ref event => panic!("NodeReply expected, got {:?}", event)
+            }
+        }
+
         #[test]
         fn networker_process_send_all_request_event_works() {
             let mut txn_1 = nodes_emulator::node();
@@ -630,7 +847,7 @@ pub mod networker_tests {
         fn networker_process_timeout_event_works() {
             let txn = nodes_emulator::node();
             let rn = _remote_node(&txn);
-            let conn = PoolConnection::new(vec![rn.clone()], POOL_CON_ACTIVE_TO, vec![], String::new());
+            let conn = PoolConnection::new(vec![rn.clone()], POOL_CON_ACTIVE_TO, vec![], String::new(), None);
 
             let mut networker = ZMQNetworker::new(POOL_CON_ACTIVE_TO, MAX_REQ_PER_POOL_CON, vec![], String::new());
             networker.process_event(Some(NetworkerEvent::NodesStateUpdated(vec![rn])));
@@ -716,6 +933,77 @@ pub mod networker_tests {
 
             assert_ne!(::std::i64::MAX, timeout);
         }
+
+        #[test]
+        fn networker_process_update_node_health_event_works() {
+            let mut txn_1 = nodes_emulator::node();
+            let handle_1 = nodes_emulator::start(&mut txn_1);
+            let rn_1 = _remote_node(&txn_1);
+
+            let txn_2 = nodes_emulator::node_2();
+            let rn_2 = _remote_node(&txn_2);
+
+            let mut networker = ZMQNetworker::new(POOL_CON_ACTIVE_TO, MAX_REQ_PER_POOL_CON, vec![], String::new());
+            networker.process_event(Some(NetworkerEvent::NodesStateUpdated(vec![rn_1, rn_2])));
+
+            networker.process_event(Some(NetworkerEvent::SendAllRequest(MESSAGE.to_string(), REQ_ID.to_string(), POOL_ACK_TIMEOUT, Some(vec![NODE_NAME.to_string()]))));
+            assert_eq!(MESSAGE.to_string(), nodes_emulator::next(&handle_1).unwrap());
+
+            networker.process_event(Some(NetworkerEvent::UpdateNodeHealth(REQ_ID.to_string(), NODE_NAME.to_string(), NodeOutcome::Reply)));
+            networker.process_event(Some(NetworkerEvent::UpdateNodeHealth(REQ_ID.to_string(), "n2".to_string(), NodeOutcome::Nack)));
+
+            let status = networker.get_nodes_status();
+            assert_eq!(2, status.len());
+
+            assert_eq!(NODE_NAME, status[0].alias);
+            assert_eq!(1, status[0].replies);
+            assert_eq!(1.0, status[0].score);
+            assert!(status[0].latency_ms.is_some());
+            assert!(!status[0].blacklisted);
+
+            assert_eq!("n2", status[1].alias);
+            assert_eq!(1, status[1].nacks);
+            assert_eq!(0.0, status[1].score);
+            assert!(status[1].latency_ms.is_none());
+            assert!(!status[1].blacklisted);
+        }
+
+        #[test]
+        fn networker_blacklists_node_after_failures() {
+            let txn = nodes_emulator::node();
+            let rn = _remote_node(&txn);
+
+            let mut networker = ZMQNetworker::new(POOL_CON_ACTIVE_TO, MAX_REQ_PER_POOL_CON, vec![], String::new());
+            networker.process_event(Some(NetworkerEvent::NodesStateUpdated(vec![rn])));
+
+            for _ in 0..NODE_FAILURES_TO_BLACKLIST - 1 {
+                networker.process_event(Some(NetworkerEvent::UpdateNodeHealth(REQ_ID.to_string(), NODE_NAME.to_string(), NodeOutcome::Timeout)));
+            }
+            networker.process_event(Some(NetworkerEvent::UpdateNodeHealth(REQ_ID.to_string(), NODE_NAME.to_string(), NodeOutcome::Reply)));
+            networker.process_event(Some(NetworkerEvent::UpdateNodeHealth(REQ_ID.to_string(), NODE_NAME.to_string(), NodeOutcome::Timeout)));
+            assert!(!networker.get_nodes_status()[0].blacklisted);
+
+            for _ in 0..NODE_FAILURES_TO_BLACKLIST - 1 {
+                networker.process_event(Some(NetworkerEvent::UpdateNodeHealth(REQ_ID.to_string(), NODE_NAME.to_string(), NodeOutcome::Nack)));
+            }
+            assert!(networker.get_nodes_status()[0].blacklisted);
+            assert_eq!(vec![NODE_NAME.to_string()].into_iter().collect::<HashSet<String>>(), networker._get_blacklisted_nodes());
+        }
+
+        #[test]
+        fn networker_blacklists_node_for_invalid_state_proof() {
+            let txn = nodes_emulator::node();
+            let rn = _remote_node(&txn);
+
+            let mut networker = ZMQNetworker::new(POOL_CON_ACTIVE_TO, MAX_REQ_PER_POOL_CON, vec![], String::new());
+            networker.process_event(Some(NetworkerEvent::NodesStateUpdated(vec![rn])));
+
+            networker.process_event(Some(NetworkerEvent::UpdateNodeHealth(REQ_ID.to_string(), NODE_NAME.to_string(), NodeOutcome::InvalidStateProof)));
+
+            let status = networker.get_nodes_status();
+            assert_eq!(1, status[0].invalid_state_proofs);
+            assert!(status[0].blacklisted);
+        }
     }
 
     #[cfg(test)]
@@ -764,7 +1052,7 @@ pub mod networker_tests {
             let txn = nodes_emulator::node();
             let rn = _remote_node(&txn);
 
-            PoolConnection::new(vec![rn], POOL_CON_ACTIVE_TO, vec![], String::new());
+            PoolConnection::new(vec![rn], POOL_CON_ACTIVE_TO, vec![], String::new(), None);
         }
 
         #[test]
@@ -780,7 +1068,7 @@ pub mod networker_tests {
                 nodes.push(_remote_node(&txn));
             }
 
-            let pc = PoolConnection::new(nodes, POOL_CON_ACTIVE_TO, vec![], String::new());
+            let pc = PoolConnection::new(nodes, POOL_CON_ACTIVE_TO, vec![], String::new(), None);
 
             let act_names: Vec<String> = pc.nodes.iter().map(|n| n.name.to_string()).collect();
 
@@ -809,7 +1097,8 @@ pub mod networker_tests {
             let pc = PoolConnection::new(vec![rn_1.clone(), rn_2.clone(), rn_3.clone(), rn_4.clone(), rn_5.clone()],
                                          POOL_CON_ACTIVE_TO,
                                          vec![rn_2.name.clone(), rn_1.name.clone(), rn_5.name.clone()],
-                                         String::new());
+                                         String::new(),
+                                         None);
 
             assert_eq!(rn_2.name, pc.nodes[0].name);
             assert_eq!(rn_1.name, pc.nodes[1].name);
@@ -821,7 +1110,7 @@ pub mod networker_tests {
             let txn = nodes_emulator::node();
             let rn = _remote_node(&txn);
 
-            let mut conn = PoolConnection::new(vec![rn], POOL_CON_ACTIVE_TO, vec![], String::new());
+            let mut conn = PoolConnection::new(vec![rn], POOL_CON_ACTIVE_TO, vec![], String::new(), None);
 
             assert!(conn.is_active());
 
@@ -835,11 +1124,11 @@ pub mod networker_tests {
             let txn = nodes_emulator::node();
             let rn = _remote_node(&txn);
 
-            let mut conn = PoolConnection::new(vec![rn], POOL_CON_ACTIVE_TO, vec![], String::new());
+            let mut conn = PoolConnection::new(vec![rn], POOL_CON_ACTIVE_TO, vec![], String::new(), None);
 
             assert!(!conn.has_active_requests());
 
-            conn.send_request(Some(NetworkerEvent::SendOneRequest(MESSAGE.to_string(), REQ_ID.to_string(), POOL_ACK_TIMEOUT))).unwrap();
+            conn.send_request(Some(NetworkerEvent::SendOneRequest(MESSAGE.to_string(), REQ_ID.to_string(), POOL_ACK_TIMEOUT)), &HashSet::new()).unwrap();
 
             assert!(conn.has_active_requests());
         }
@@ -849,7 +1138,7 @@ pub mod networker_tests {
             let txn = nodes_emulator::node();
             let rn = _remote_node(&txn);
 
-            let mut conn = PoolConnection::new(vec![rn], POOL_CON_ACTIVE_TO, vec![], String::new());
+            let mut conn = PoolConnection::new(vec![rn], POOL_CON_ACTIVE_TO, vec![], String::new(), None);
 
             let ((req_id, node_alias), timeout) = conn.get_timeout();
             assert_eq!(req_id, "".to_string());
@@ -857,7 +1146,7 @@ pub mod networker_tests {
             assert!(POOL_CON_ACTIVE_TO * 1000 - 10 <= timeout);
             assert!(POOL_CON_ACTIVE_TO * 1000 >= timeout);
 
-            conn.send_request(Some(NetworkerEvent::SendOneRequest(MESSAGE.to_string(), REQ_ID.to_string(), POOL_ACK_TIMEOUT))).unwrap();
+            conn.send_request(Some(NetworkerEvent::SendOneRequest(MESSAGE.to_string(), REQ_ID.to_string(), POOL_ACK_TIMEOUT)), &HashSet::new()).unwrap();
 
             let (id, timeout) = conn.get_timeout();
             assert_eq!((REQ_ID.to_string(), NODE_NAME.to_string()), id);
@@ -870,9 +1159,9 @@ pub mod networker_tests {
             let txn = nodes_emulator::node();
             let rn = _remote_node(&txn);
 
-            let mut conn = PoolConnection::new(vec![rn], POOL_CON_ACTIVE_TO, vec![], String::new());
+            let mut conn = PoolConnection::new(vec![rn], POOL_CON_ACTIVE_TO, vec![], String::new(), None);
 
-            conn.send_request(Some(NetworkerEvent::SendOneRequest(MESSAGE.to_string(), REQ_ID.to_string(), POOL_ACK_TIMEOUT))).unwrap();
+            conn.send_request(Some(NetworkerEvent::SendOneRequest(MESSAGE.to_string(), REQ_ID.to_string(), POOL_ACK_TIMEOUT)), &HashSet::new()).unwrap();
 
             thread::sleep(std::time::Duration::from_secs(1));
 
@@ -890,9 +1179,9 @@ pub mod networker_tests {
             let txn = nodes_emulator::node();
             let rn = _remote_node(&txn);
 
-            let mut conn = PoolConnection::new(vec![rn], POOL_CON_ACTIVE_TO, vec![], String::new());
+            let mut conn = PoolConnection::new(vec![rn], POOL_CON_ACTIVE_TO, vec![], String::new(), None);
 
-            conn.send_request(Some(NetworkerEvent::SendOneRequest(MESSAGE.to_string(), REQ_ID.to_string(), POOL_ACK_TIMEOUT))).unwrap();
+            conn.send_request(Some(NetworkerEvent::SendOneRequest(MESSAGE.to_string(), REQ_ID.to_string(), POOL_ACK_TIMEOUT)), &HashSet::new()).unwrap();
 
             assert!(conn.has_active_requests());
 
@@ -906,7 +1195,7 @@ pub mod networker_tests {
             let txn = nodes_emulator::node();
             let rn = _remote_node(&txn);
 
-            let mut conn = PoolConnection::new(vec![rn], POOL_CON_ACTIVE_TO, vec![], String::new());
+            let mut conn = PoolConnection::new(vec![rn], POOL_CON_ACTIVE_TO, vec![], String::new(), None);
 
             let _socket = conn._get_socket(0).unwrap();
         }
@@ -917,7 +1206,7 @@ pub mod networker_tests {
             let mut rn = _remote_node(&txn);
             rn.zaddr = "invalid_address".to_string();
 
-            let mut conn = PoolConnection::new(vec![rn], POOL_CON_ACTIVE_TO, vec![], String::new());
+            let mut conn = PoolConnection::new(vec![rn], POOL_CON_ACTIVE_TO, vec![], String::new(), None);
 
             let res = conn._get_socket(0);
             assert_kind!(IndyErrorKind::IOError, res);
@@ -929,10 +1218,10 @@ pub mod networker_tests {
             let handle = nodes_emulator::start(&mut txn);
             let rn = _remote_node(&txn);
 
-            let mut conn = PoolConnection::new(vec![rn], POOL_CON_ACTIVE_TO, vec![], String::new());
+            let mut conn = PoolConnection::new(vec![rn], POOL_CON_ACTIVE_TO, vec![], String::new(), None);
 
-            conn.send_request(Some(NetworkerEvent::SendOneRequest(MESSAGE.to_string(), REQ_ID.to_string(), POOL_ACK_TIMEOUT))).unwrap();
-            conn.send_request(Some(NetworkerEvent::SendOneRequest("msg2".to_string(), "12".to_string(), POOL_ACK_TIMEOUT))).unwrap();
+            conn.send_request(Some(NetworkerEvent::SendOneRequest(MESSAGE.to_string(), REQ_ID.to_string(), POOL_ACK_TIMEOUT)), &HashSet::new()).unwrap();
+            conn.send_request(Some(NetworkerEvent::SendOneRequest("msg2".to_string(), "12".to_string(), POOL_ACK_TIMEOUT)), &HashSet::new()).unwrap();
 
             assert_eq!(MESSAGE.to_string(), nodes_emulator::next(&handle).unwrap());
             assert_eq!("msg2".to_string(), nodes_emulator::next(&handle).unwrap());
@@ -949,9 +1238,9 @@ pub mod networker_tests {
             let handle_2 = nodes_emulator::start(&mut txn_2);
             let rn_2 = _remote_node(&txn_2);
 
-            let mut conn = PoolConnection::new(vec![rn_1, rn_2], POOL_CON_ACTIVE_TO, vec!["n1".to_string(), "n2".to_string()], String::new());
+            let mut conn = PoolConnection::new(vec![rn_1, rn_2], POOL_CON_ACTIVE_TO, vec!["n1".to_string(), "n2".to_string()], String::new(), None);
 
-            conn.send_request(Some(NetworkerEvent::SendOneRequest(MESSAGE.to_string(), REQ_ID.to_string(), POOL_ACK_TIMEOUT))).unwrap();
+            conn.send_request(Some(NetworkerEvent::SendOneRequest(MESSAGE.to_string(), REQ_ID.to_string(), POOL_ACK_TIMEOUT)), &HashSet::new()).unwrap();
 
             assert_eq!(MESSAGE.to_string(), nodes_emulator::next(&handle_1).unwrap());
             assert!(nodes_emulator::next(&handle_1).is_none());
@@ -969,9 +1258,9 @@ pub mod networker_tests {
             let handle_2 = nodes_emulator::start(&mut txn_2);
             let rn_2 = _remote_node(&txn_2);
 
-            let mut conn = PoolConnection::new(vec![rn_1, rn_2], POOL_CON_ACTIVE_TO, vec![], String::new());
+            let mut conn = PoolConnection::new(vec![rn_1, rn_2], POOL_CON_ACTIVE_TO, vec![], String::new(), None);
 
-            conn.send_request(Some(NetworkerEvent::SendAllRequest(MESSAGE.to_string(), REQ_ID.to_string(), POOL_ACK_TIMEOUT, None))).unwrap();
+            conn.send_request(Some(NetworkerEvent::SendAllRequest(MESSAGE.to_string(), REQ_ID.to_string(), POOL_ACK_TIMEOUT, None)), &HashSet::new()).unwrap();
 
             for handle in vec![handle_1, handle_2] {
                 assert_eq!(MESSAGE.to_string(), nodes_emulator::next(&handle).unwrap());
@@ -985,11 +1274,11 @@ pub mod networker_tests {
             let handle = nodes_emulator::start(&mut txn);
             let rn = _remote_node(&txn);
 
-            let mut conn = PoolConnection::new(vec![rn], POOL_CON_ACTIVE_TO, vec![], String::new());
+            let mut conn = PoolConnection::new(vec![rn], POOL_CON_ACTIVE_TO, vec![], String::new(), None);
 
-            conn.send_request(Some(NetworkerEvent::SendOneRequest(MESSAGE.to_string(), REQ_ID.to_string(), POOL_ACK_TIMEOUT))).unwrap();
+            conn.send_request(Some(NetworkerEvent::SendOneRequest(MESSAGE.to_string(), REQ_ID.to_string(), POOL_ACK_TIMEOUT)), &HashSet::new()).unwrap();
 
-            conn.send_request(Some(NetworkerEvent::Resend(REQ_ID.to_string(), POOL_ACK_TIMEOUT))).unwrap();
+            conn.send_request(Some(NetworkerEvent::Resend(REQ_ID.to_string(), POOL_ACK_TIMEOUT)), &HashSet::new()).unwrap();
 
             assert_eq!(MESSAGE.to_string(), nodes_emulator::next(&handle).unwrap());
             assert_eq!(MESSAGE.to_string(), nodes_emulator::next(&handle).unwrap());
@@ -1006,11 +1295,11 @@ pub mod networker_tests {
             let handle_2 = nodes_emulator::start(&mut txn_2);
             let rn_2 = _remote_node(&txn_2);
 
-            let mut conn = PoolConnection::new(vec![rn_1, rn_2], POOL_CON_ACTIVE_TO, vec![], String::new());
+            let mut conn = PoolConnection::new(vec![rn_1, rn_2], POOL_CON_ACTIVE_TO, vec![], String::new(), None);
 
-            conn.send_request(Some(NetworkerEvent::SendOneRequest(MESSAGE.to_string(), REQ_ID.to_string(), POOL_ACK_TIMEOUT))).unwrap();
+            conn.send_request(Some(NetworkerEvent::SendOneRequest(MESSAGE.to_string(), REQ_ID.to_string(), POOL_ACK_TIMEOUT)), &HashSet::new()).unwrap();
 
-            conn.send_request(Some(NetworkerEvent::Resend(REQ_ID.to_string(), POOL_ACK_TIMEOUT))).unwrap();
+            conn.send_request(Some(NetworkerEvent::Resend(REQ_ID.to_string(), POOL_ACK_TIMEOUT)), &HashSet::new()).unwrap();
 
             for handle in vec![handle_1, handle_2] {
                 assert_eq!(MESSAGE.to_string(), nodes_emulator::next(&handle).unwrap());
@@ -1018,15 +1307,40 @@ pub mod networker_tests {
             }
         }
 
+        #[test]
+        fn pool_connection_send_request_one_node_works_for_blacklisted_node() {
+            let mut txn_1 = nodes_emulator::node();
+            let handle_1 = nodes_emulator::start(&mut txn_1);
+            let rn_1 = _remote_node(&txn_1);
+
+            let mut txn_2 = nodes_emulator::node_2();
+            let handle_2 = nodes_emulator::start(&mut txn_2);
+            let rn_2 = _remote_node(&txn_2);
+
+            let mut conn = PoolConnection::new(vec![rn_1, rn_2], POOL_CON_ACTIVE_TO, vec![NODE_NAME.to_string()], String::new(), None);
+            let blacklisted = vec![NODE_NAME.to_string()].into_iter().collect::<HashSet<String>>();
+
+            conn.send_request(Some(NetworkerEvent::SendOneRequest(MESSAGE.to_string(), REQ_ID.to_string(), POOL_ACK_TIMEOUT)), &blacklisted).unwrap();
+
+            assert!(nodes_emulator::next(&handle_1).is_none());
+            assert_eq!(MESSAGE.to_string(), nodes_emulator::next(&handle_2).unwrap());
+
+            // blacklisted node is asked when other nodes are asked already
+            conn.send_request(Some(NetworkerEvent::Resend(REQ_ID.to_string(), POOL_ACK_TIMEOUT)), &blacklisted).unwrap();
+
+            assert_eq!(MESSAGE.to_string(), nodes_emulator::next(&handle_1).unwrap());
+            assert!(nodes_emulator::next(&handle_2).is_none());
+        }
+
         #[test]
         fn pool_connection_send_works_for_invalid_node() {
             let txn = nodes_emulator::node();
             let mut rn = _remote_node(&txn);
             rn.zaddr = "invalid_address".to_string();
 
-            let mut conn = PoolConnection::new(vec![rn], POOL_CON_ACTIVE_TO, vec![], String::new());
+            let mut conn = PoolConnection::new(vec![rn], POOL_CON_ACTIVE_TO, vec![], String::new(), None);
 
-            let res = conn.send_request(Some(NetworkerEvent::SendOneRequest(MESSAGE.to_string(), REQ_ID.to_string(), POOL_ACK_TIMEOUT)));
+            let res = conn.send_request(Some(NetworkerEvent::SendOneRequest(MESSAGE.to_string(), REQ_ID.to_string(), POOL_ACK_TIMEOUT)), &HashSet::new());
             assert_kind!(IndyErrorKind::IOError, res);
         }
     }
@@ -1,12 +1,14 @@
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::collections::VecDeque;
 use std::marker::PhantomData;
 use std::rc::Rc;
+use std::sync::Arc;
 use std::thread;
 use std::thread::JoinHandle;
 
 use failure::Context;
+use time::Tm;
 
 use crate::commands::Command;
 use crate::commands::CommandExecutor;
@@ -21,11 +23,13 @@ use crate::services::pool::events::*;
 use crate::services::pool::{merkle_tree_factory, Nodes};
 use crate::services::pool::networker::{Networker, ZMQNetworker};
 use crate::services::pool::request_handler::{RequestHandler, RequestHandlerImpl};
+use crate::services::pool::transport::{PoolTransport, Transport};
 use rust_base58::{FromBase58, ToBase58};
-use crate::services::pool::types::{LedgerStatus, RemoteNode};
+use crate::services::pool::types::{LedgerStatus, PoolStatus, RemoteNode};
 use indy_utils::crypto::ed25519_sign;
 
 use super::ursa::bls::VerKey;
+use super::time::Duration;
 use super::zmq;
 use indy_api_types::{PoolHandle, CommandHandle};
 
@@ -35,6 +39,7 @@ struct PoolSM<T: Networker, R: RequestHandler<T>> {
     timeout: i64,
     extended_timeout: i64,
     number_read_nodes: u8,
+    pool_ledger_probe_timeout: i64,
     state: PoolState<T, R>,
 }
 
@@ -69,6 +74,13 @@ struct ActiveState<T: Networker, R: RequestHandler<T>> {
     networker: Rc<RefCell<T>>,
     request_handlers: HashMap<String, R>,
     nodes: Nodes,
+    mt_size: usize,
+    // catchup started in background when the pool is detected as outdated
+    refresher: Option<R>,
+    invalid_sp_nodes: HashSet<String>,
+    // nodes that reported pool ledger bigger than ours without request
+    outdated_pool_nodes: HashSet<String>,
+    last_probe: Tm,
 }
 
 struct SyncCatchupState<T: Networker, R: RequestHandler<T>> {
@@ -84,22 +96,116 @@ struct TerminatedState<T: Networker> {
 
 struct ClosedState {}
 
+impl<T: Networker, R: RequestHandler<T>> ActiveState<T, R> {
+    fn new(networker: Rc<RefCell<T>>, nodes: Nodes, mt_size: usize) -> Self {
+        ActiveState {
+            networker,
+            request_handlers: HashMap::new(),
+            nodes,
+            mt_size,
+            refresher: None,
+            invalid_sp_nodes: HashSet::new(),
+            outdated_pool_nodes: HashSet::new(),
+            last_probe: time::now(),
+        }
+    }
+
+    /// Starts background refresh of the pool ledger if its state wasn't checked for a while.
+    /// Refresh finishes without changes of the node set if nodes report the same pool ledger.
+    fn probe_pool_ledger(&mut self, pool_name: &str, timeout: i64, extended_timeout: i64, number_read_nodes: u8, probe_timeout: i64) {
+        if self.refresher.is_none() && time::now() - self.last_probe > Duration::seconds(probe_timeout) {
+            debug!("Probing pool {} ledger state", pool_name);
+            self.start_refresh(pool_name, timeout, extended_timeout, number_read_nodes);
+        }
+    }
+
+    fn start_refresh(&mut self, pool_name: &str, timeout: i64, extended_timeout: i64, number_read_nodes: u8) {
+        self.last_probe = time::now();
+        match _get_request_handler_with_ledger_status_sent(self.networker.clone(), pool_name, timeout, extended_timeout, number_read_nodes) {
+            Ok(request_handler) => self.refresher = Some(request_handler),
+            Err(err) => warn!("Can't start background refresh of pool {}: {:?}", pool_name, err)
+        }
+    }
+
+    /// Single node can report bigger pool ledger by mistake or maliciously,
+    /// so the pool is outdated only if more than f nodes report it.
+    fn report_outdated_pool(&mut self, node_alias: String, pool_name: &str, timeout: i64, extended_timeout: i64, number_read_nodes: u8) {
+        self.outdated_pool_nodes.insert(node_alias);
+        if self.outdated_pool_nodes.len() > _get_f(self.nodes.len()) {
+            self.process_refresh_event(PoolEvent::PoolOutdated, pool_name, timeout, extended_timeout, number_read_nodes);
+        }
+    }
+
+    /// Drives background refresh of the pool ledger.
+    /// Active requests keep their connections, only new ones are sent to the updated nodes.
+    fn process_refresh_event(&mut self, pe: PoolEvent, pool_name: &str, timeout: i64, extended_timeout: i64, number_read_nodes: u8) {
+        match pe {
+            PoolEvent::InvalidStateProof(node_alias) => {
+                self.invalid_sp_nodes.insert(node_alias);
+                // At least one honest node replied with a proof we can't verify, so our BLS keys are outdated
+                if self.invalid_sp_nodes.len() > _get_f(self.nodes.len()) {
+                    self.process_refresh_event(PoolEvent::PoolOutdated, pool_name, timeout, extended_timeout, number_read_nodes);
+                }
+            }
+            PoolEvent::PoolOutdated => {
+                self.invalid_sp_nodes.clear();
+                self.outdated_pool_nodes.clear();
+                if self.refresher.is_none() {
+                    info!("Pool {} is outdated, starting background refresh", pool_name);
+                    self.start_refresh(pool_name, timeout, extended_timeout, number_read_nodes);
+                }
+            }
+            PoolEvent::CatchupTargetFound(target_mt_root, target_mt_size, merkle_tree) => {
+                let mut request_handler = R::new(self.networker.clone(), _get_f(self.nodes.len()), &[], &self.nodes, pool_name, timeout, extended_timeout, number_read_nodes);
+                let pe = request_handler.process_event(Some(RequestEvent::CatchupReq(merkle_tree, target_mt_size, target_mt_root)));
+                self.refresher = Some(request_handler);
+                if let Some(pe) = pe {
+                    self.process_refresh_event(pe, pool_name, timeout, extended_timeout, number_read_nodes);
+                }
+            }
+            PoolEvent::Synced(merkle) => {
+                self.refresher = None;
+                self.outdated_pool_nodes.clear();
+                match _get_nodes_and_remotes(pool_name, &merkle) {
+                    Ok((nodes, remotes)) => {
+                        trace!("PoolSM: background refresh finished, nodes {:?}", nodes.keys());
+                        self.networker.borrow_mut().process_event(Some(NetworkerEvent::NodesStateUpdated(remotes)));
+                        self.nodes = nodes;
+                        self.mt_size = merkle.count();
+                    }
+                    Err(err) => warn!("Can't apply background refresh of pool {}: {:?}", pool_name, err)
+                }
+            }
+            PoolEvent::CatchupTargetNotFound(err) => {
+                warn!("Background refresh of pool {} failed: {:?}", pool_name, err);
+                self.refresher = None;
+            }
+            PoolEvent::CatchupRestart(_) => {
+                warn!("Background refresh of pool {} timed out", pool_name);
+                self.refresher = None;
+            }
+            _ => ()
+        }
+    }
+}
+
 impl<T: Networker, R: RequestHandler<T>> PoolSM<T, R> {
-    pub fn new(networker: Rc<RefCell<T>>, pname: &str, id: PoolHandle, timeout: i64, extended_timeout: i64, number_read_nodes: u8) -> PoolSM<T, R> {
+    pub fn new(networker: Rc<RefCell<T>>, pname: &str, id: PoolHandle, timeout: i64, extended_timeout: i64, number_read_nodes: u8, pool_ledger_probe_timeout: i64) -> PoolSM<T, R> {
         PoolSM {
             pool_name: pname.to_string(),
             id,
             timeout,
             extended_timeout,
             number_read_nodes,
+            pool_ledger_probe_timeout,
             state: PoolState::Initialization(InitializationState {
                 networker
             }),
         }
     }
 
-    pub fn step(pool_name: String, id: PoolHandle, timeout: i64, extended_timeout: i64, number_read_nodes: u8, state: PoolState<T, R>) -> Self {
-        PoolSM { pool_name, id, timeout, extended_timeout, number_read_nodes, state }
+    pub fn step(pool_name: String, id: PoolHandle, timeout: i64, extended_timeout: i64, number_read_nodes: u8, pool_ledger_probe_timeout: i64, state: PoolState<T, R>) -> Self {
+        PoolSM { pool_name, id, timeout, extended_timeout, number_read_nodes, pool_ledger_probe_timeout, state }
     }
 }
 
@@ -132,14 +238,10 @@ impl<T: Networker> From<InitializationState<T>> for TerminatedState<T> {
     }
 }
 
-impl<T: Networker, R: RequestHandler<T>> From<(InitializationState<T>, Nodes)> for ActiveState<T, R> {
-    fn from((state, nodes): (InitializationState<T>, Nodes)) -> ActiveState<T, R> {
+impl<T: Networker, R: RequestHandler<T>> From<(InitializationState<T>, Nodes, usize)> for ActiveState<T, R> {
+    fn from((state, nodes, mt_size): (InitializationState<T>, Nodes, usize)) -> ActiveState<T, R> {
         trace!("PoolSM: from init to active");
-        ActiveState {
-            networker: state.networker,
-            request_handlers: HashMap::new(),
-            nodes,
-        }
+        ActiveState::new(state.networker, nodes, mt_size)
     }
 }
 
@@ -157,13 +259,9 @@ impl<T: Networker, R: RequestHandler<T>> From<(R, GettingCatchupTargetState<T, R
     }
 }
 
-impl<T: Networker, R: RequestHandler<T>> From<(GettingCatchupTargetState<T, R>, Nodes)> for ActiveState<T, R> {
-    fn from((state, nodes): (GettingCatchupTargetState<T, R>, Nodes)) -> Self {
-        ActiveState {
-            networker: state.networker,
-            request_handlers: HashMap::new(),
-            nodes,
-        }
+impl<T: Networker, R: RequestHandler<T>> From<(GettingCatchupTargetState<T, R>, Nodes, usize)> for ActiveState<T, R> {
+    fn from((state, nodes, mt_size): (GettingCatchupTargetState<T, R>, Nodes, usize)) -> Self {
+        ActiveState::new(state.networker, nodes, mt_size)
     }
 }
 
@@ -219,14 +317,10 @@ impl<T: Networker, R: RequestHandler<T>> From<ActiveState<T, R>> for ClosedState
 
 // transitions from SyncCatchup
 
-impl<T: Networker, R: RequestHandler<T>> From<(SyncCatchupState<T, R>, Nodes)> for ActiveState<T, R> {
-    fn from((state, nodes): (SyncCatchupState<T, R>, Nodes)) -> Self {
+impl<T: Networker, R: RequestHandler<T>> From<(SyncCatchupState<T, R>, Nodes, usize)> for ActiveState<T, R> {
+    fn from((state, nodes, mt_size): (SyncCatchupState<T, R>, Nodes, usize)) -> Self {
         trace!("PoolSM: from sync catchup to active");
-        ActiveState {
-            networker: state.networker,
-            request_handlers: HashMap::new(),
-            nodes,
-        }
+        ActiveState::new(state.networker, nodes, mt_size)
     }
 }
 
@@ -268,7 +362,7 @@ impl<T: Networker> From<TerminatedState<T>> for ClosedState {
 
 impl<T: Networker, R: RequestHandler<T>> PoolSM<T, R> {
     pub fn handle_event(self, pe: PoolEvent) -> Self {
-        let PoolSM { pool_name, id, state, timeout, extended_timeout, number_read_nodes } = self;
+        let PoolSM { pool_name, id, state, timeout, extended_timeout, number_read_nodes, pool_ledger_probe_timeout } = self;
         let state = match state {
             PoolState::Initialization(state) => match pe {
                 PoolEvent::CheckCache(cmd_id) => {
@@ -308,7 +402,7 @@ impl<T: Networker, R: RequestHandler<T>> PoolSM<T, R> {
                         PoolState::Terminated(state.into())
                     }
                     PoolEvent::CatchupRestart(merkle_tree) => {
-                        if let Ok((nodes, remotes)) = _get_nodes_and_remotes(&merkle_tree) {
+                        if let Ok((nodes, remotes)) = _get_nodes_and_remotes(&pool_name, &merkle_tree) {
                             state.networker.borrow_mut().process_event(Some(NetworkerEvent::NodesStateUpdated(remotes)));
                             state.request_handler = R::new(state.networker.clone(), _get_f(nodes.len()), &[], &nodes, &pool_name, timeout, extended_timeout, number_read_nodes);
                             let ls = _ledger_status(&merkle_tree);
@@ -319,7 +413,7 @@ impl<T: Networker, R: RequestHandler<T>> PoolSM<T, R> {
                         }
                     }
                     PoolEvent::CatchupTargetFound(target_mt_root, target_mt_size, merkle_tree) => {
-                        if let Ok((nodes, remotes)) = _get_nodes_and_remotes(&merkle_tree) {
+                        if let Ok((nodes, remotes)) = _get_nodes_and_remotes(&pool_name, &merkle_tree) {
                             state.networker.borrow_mut().process_event(Some(NetworkerEvent::NodesStateUpdated(remotes)));
                             let mut request_handler = R::new(state.networker.clone(), _get_f(nodes.len()), &[], &nodes, &pool_name, timeout, extended_timeout, number_read_nodes);
                             request_handler.process_event(Some(RequestEvent::CatchupReq(merkle_tree, target_mt_size, target_mt_root)));
@@ -329,10 +423,10 @@ impl<T: Networker, R: RequestHandler<T>> PoolSM<T, R> {
                         }
                     }
                     PoolEvent::Synced(merkle) => {
-                        if let Ok((nodes, remotes)) = _get_nodes_and_remotes(&merkle) {
+                        if let Ok((nodes, remotes)) = _get_nodes_and_remotes(&pool_name, &merkle) {
                             state.networker.borrow_mut().process_event(Some(NetworkerEvent::NodesStateUpdated(remotes)));
                             _send_open_refresh_ack(state.cmd_id, id, state.refresh, Ok(()));
-                            PoolState::Active((state, nodes).into())
+                            PoolState::Active((state, nodes, merkle.count()).into())
                         } else {
                             PoolState::Terminated(state.into())
                         }
@@ -367,13 +461,26 @@ impl<T: Networker, R: RequestHandler<T>> PoolSM<T, R> {
             PoolState::Closed(state) => PoolState::Closed(state),
             PoolState::Active(mut state) => {
                 match pe.clone() {
-                    PoolEvent::PoolOutdated => PoolState::Terminated(state.into()),
+                    PoolEvent::PoolOutdated |
+                    PoolEvent::InvalidStateProof(_) |
+                    PoolEvent::CatchupTargetFound(_, _, _) |
+                    PoolEvent::CatchupTargetNotFound(_) |
+                    PoolEvent::CatchupRestart(_) |
+                    PoolEvent::Synced(_) => {
+                        state.process_refresh_event(pe, &pool_name, timeout, extended_timeout, number_read_nodes);
+                        PoolState::Active(state)
+                    }
                     PoolEvent::Close(cmd_id) => {
                         _close_pool_ack(cmd_id);
                         PoolState::Closed(state.into())
                     }
                     PoolEvent::Refresh(cmd_id) => {
-                        if let Ok(request_handler) = _get_request_handler_with_ledger_status_sent(state.networker.clone(), &pool_name, timeout, extended_timeout, number_read_nodes) {
+                        // running background refresh is continued instead of starting a new one
+                        let request_handler = match state.refresher.take() {
+                            Some(refresher) => Ok(refresher),
+                            None => _get_request_handler_with_ledger_status_sent(state.networker.clone(), &pool_name, timeout, extended_timeout, number_read_nodes)
+                        };
+                        if let Ok(request_handler) = request_handler {
                             PoolState::GettingCatchupTarget((state, request_handler, cmd_id).into())
                         } else {
                             PoolState::Terminated(state.into())
@@ -381,6 +488,7 @@ impl<T: Networker, R: RequestHandler<T>> PoolSM<T, R> {
                     }
                     PoolEvent::SendRequest(cmd_id, _, _, _) => {
                         trace!("received request to send");
+                        state.probe_pool_ledger(&pool_name, timeout, extended_timeout, number_read_nodes, pool_ledger_probe_timeout);
                         let re: Option<RequestEvent> = pe.into();
                         match re.as_ref().map(|r| r.get_req_id()) {
                             Some(req_id) => {
@@ -399,16 +507,29 @@ impl<T: Networker, R: RequestHandler<T>> PoolSM<T, R> {
                         trace!("received reply from node {:?}: {:?}", node, reply);
                         let re: Option<RequestEvent> = pe.into();
                         match re.as_ref().map(|r| r.get_req_id()) {
+                            // pool ledger messages have no request id
+                            Some(ref req_id) if req_id.is_empty() => {
+                                if state.refresher.is_some() {
+                                    if let Some(pe) = state.refresher.as_mut().and_then(|rh| rh.process_event(re)) {
+                                        state.process_refresh_event(pe, &pool_name, timeout, extended_timeout, number_read_nodes);
+                                    }
+                                } else if _is_pool_outdated(&re, state.mt_size) {
+                                    state.report_outdated_pool(node, &pool_name, timeout, extended_timeout, number_read_nodes);
+                                }
+                            }
                             Some(req_id) => {
-                                let remove = if let Some(rh) = state.request_handlers.get_mut(&req_id) {
-                                    rh.process_event(re);
-                                    rh.is_terminal()
+                                let (remove, pe) = if let Some(rh) = state.request_handlers.get_mut(&req_id) {
+                                    let pe = rh.process_event(re);
+                                    (rh.is_terminal(), pe)
                                 } else {
-                                    false
+                                    (false, None)
                                 };
                                 if remove {
                                     state.request_handlers.remove(&req_id);
                                 }
+                                if let Some(pe) = pe {
+                                    state.process_refresh_event(pe, &pool_name, timeout, extended_timeout, number_read_nodes);
+                                }
                             }
                             None => warn!("Request id not found in Reply: {:?}", reply)
                         };
@@ -420,6 +541,10 @@ impl<T: Networker, R: RequestHandler<T>> PoolSM<T, R> {
                             rh.process_event(pe.into());
                         } else if "".eq(&req_id) {
                             state.networker.borrow_mut().process_event(Some(NetworkerEvent::Timeout));
+                        } else if let Some(rh) = state.refresher.as_mut() {
+                            if let Some(pe) = rh.process_event(pe.into()) {
+                                state.process_refresh_event(pe, &pool_name, timeout, extended_timeout, number_read_nodes);
+                            }
                         } else {
                             warn!("Unexpected timeout: req_id {}, node_alias {}", req_id, node_alias)
                         }
@@ -437,10 +562,10 @@ impl<T: Networker, R: RequestHandler<T>> PoolSM<T, R> {
                     }
                     PoolEvent::NodesBlacklisted => PoolState::Terminated(state.into()),
                     PoolEvent::Synced(merkle) => {
-                        if let Ok((nodes, remotes)) = _get_nodes_and_remotes(&merkle).map_err(map_err_err!()) {
+                        if let Ok((nodes, remotes)) = _get_nodes_and_remotes(&pool_name, &merkle).map_err(map_err_err!()) {
                             state.networker.borrow_mut().process_event(Some(NetworkerEvent::NodesStateUpdated(remotes)));
                             _send_open_refresh_ack(state.cmd_id, id, state.refresh, Ok(()));
-                            PoolState::Active((state, nodes).into())
+                            PoolState::Active((state, nodes, merkle.count()).into())
                         } else {
                             PoolState::Terminated(state.into())
                         }
@@ -449,7 +574,7 @@ impl<T: Networker, R: RequestHandler<T>> PoolSM<T, R> {
                 }
             }
         };
-        PoolSM::step(pool_name, id, timeout, extended_timeout, number_read_nodes, state)
+        PoolSM::step(pool_name, id, timeout, extended_timeout, number_read_nodes, pool_ledger_probe_timeout, state)
     }
 
     pub fn is_terminal(&self) -> bool {
@@ -471,11 +596,13 @@ pub struct Pool<S: Networker, R: RequestHandler<S>> {
     id: PoolHandle,
     timeout: i64,
     extended_timeout: i64,
+    pool_ledger_probe_timeout: i64,
     active_timeout: i64,
     conn_limit: usize,
     preordered_nodes: Vec<String>,
     number_read_nodes: u8,
     socks_proxy: String,
+    transport: Option<PoolTransport>,
 }
 
 impl<S: Networker, R: RequestHandler<S>> Pool<S, R> {
@@ -488,31 +615,41 @@ impl<S: Networker, R: RequestHandler<S>> Pool<S, R> {
             id,
             timeout: config.timeout,
             extended_timeout: config.extended_timeout,
+            pool_ledger_probe_timeout: config.pool_ledger_probe_timeout,
             active_timeout: config.conn_active_timeout,
             conn_limit: config.conn_limit,
             preordered_nodes: config.preordered_nodes,
             number_read_nodes: config.number_read_nodes,
             socks_proxy: config.socks_proxy,
+            transport: None,
         }
     }
 
+    pub fn set_transport(&mut self, transport: Arc<dyn Transport>, inbox: zmq::Socket) {
+        self.transport = Some(PoolTransport::new(self.id, transport, inbox));
+    }
+
     pub fn work(&mut self, cmd_socket: zmq::Socket) {
         let name = self.name.as_str().to_string();
         let id = self.id;
         let timeout = self.timeout;
         let extended_timeout = self.extended_timeout;
+        let pool_ledger_probe_timeout = self.pool_ledger_probe_timeout;
         let active_timeout = self.active_timeout;
         let conn_limit = self.conn_limit;
         let preordered_nodes = self.preordered_nodes.clone();
         let number_read_nodes = self.number_read_nodes;
         let socks_proxy = self.socks_proxy.clone();
+        let transport = self.transport.take();
         self.worker = Some(thread::spawn(move || {
             let mut pool_thread: PoolThread<S, R> = PoolThread::new(cmd_socket, name, id,
                                                                     timeout, extended_timeout,
                                                                     active_timeout, conn_limit,
                                                                     preordered_nodes,
                                                                     number_read_nodes,
-                                                                    socks_proxy);
+                                                                    pool_ledger_probe_timeout,
+                                                                    socks_proxy,
+                                                                    transport);
             pool_thread.work();
         }));
     }
@@ -535,10 +672,13 @@ struct PoolThread<S: Networker, R: RequestHandler<S>> {
 
 impl<S: Networker, R: RequestHandler<S>> PoolThread<S, R> {
     pub fn new(cmd_socket: zmq::Socket, name: String, id: PoolHandle, timeout: i64, extended_timeout: i64, active_timeout: i64, conn_limit: usize,
-               preordered_nodes: Vec<String>, number_read_nodes: u8, socks_proxy: String) -> Self {
+               preordered_nodes: Vec<String>, number_read_nodes: u8, pool_ledger_probe_timeout: i64, socks_proxy: String, transport: Option<PoolTransport>) -> Self {
         let networker = Rc::new(RefCell::new(S::new(active_timeout, conn_limit, preordered_nodes, socks_proxy)));
+        if let Some(transport) = transport {
+            networker.borrow_mut().set_transport(transport);
+        }
         PoolThread {
-            pool_sm: Some(PoolSM::new(networker.clone(), &name, id, timeout, extended_timeout, number_read_nodes)),
+            pool_sm: Some(PoolSM::new(networker.clone(), &name, id, timeout, extended_timeout, number_read_nodes, pool_ledger_probe_timeout)),
             events: VecDeque::new(),
             commander: Commander::new(cmd_socket),
             networker,
@@ -560,6 +700,11 @@ impl<S: Networker, R: RequestHandler<S>> PoolThread<S, R> {
             let pe = self.events.pop_front();
             trace!("received pool event: {:?}", pe);
             match pe {
+                // Status doesn't depend on pool state, so it's answered without state machine
+                Some(PoolEvent::GetStatus(cmd_id)) => {
+                    let status = PoolStatus { nodes: self.networker.borrow().get_nodes_status() };
+                    _send_status_ack(cmd_id, &status);
+                }
                 Some(pe) => {
                     self.pool_sm = self.pool_sm.take().map(|w| w.handle_event(pe));
                 }
@@ -619,13 +764,13 @@ fn _get_request_handler_with_ledger_status_sent<T: Networker, R: RequestHandler<
 {
     let mut merkle = merkle_tree_factory::create(pool_name)?;
 
-    let (nodes, remotes) = match _get_nodes_and_remotes(&merkle) {
+    let (nodes, remotes) = match _get_nodes_and_remotes(pool_name, &merkle) {
         Ok(n) => n,
         Err(err) => {
             match merkle_tree_factory::drop_cache(pool_name) {
                 Ok(_) => {
                     merkle = merkle_tree_factory::create(pool_name)?;
-                    _get_nodes_and_remotes(&merkle)?
+                    _get_nodes_and_remotes(pool_name, &merkle)?
                 }
                 Err(_) => { return Err(err); }
             }
@@ -638,6 +783,15 @@ fn _get_request_handler_with_ledger_status_sent<T: Networker, R: RequestHandler<
     Ok(request_handler)
 }
 
+// Nodes report pool ledger bigger than ours, so nodes could be added or their keys changed
+fn _is_pool_outdated(re: &Option<RequestEvent>, mt_size: usize) -> bool {
+    match *re {
+        Some(RequestEvent::LedgerStatus(ref ls, _, _)) => ls.ledgerId == 0 && ls.txnSeqNo > mt_size,
+        Some(RequestEvent::ConsistencyProof(ref cp, _)) => cp.ledgerId == 0 && cp.seqNoEnd > mt_size,
+        _ => false
+    }
+}
+
 fn _ledger_status(merkle: &MerkleTree) -> LedgerStatus {
     let protocol_version = ProtocolVersion::get();
 
@@ -651,8 +805,8 @@ fn _ledger_status(merkle: &MerkleTree) -> LedgerStatus {
     }
 }
 
-fn _get_nodes_and_remotes(merkle: &MerkleTree) -> IndyResult<(Nodes, Vec<RemoteNode>)> {
-    let nodes = merkle_tree_factory::build_node_state(merkle)?;
+fn _get_nodes_and_remotes(pool_name: &str, merkle: &MerkleTree) -> IndyResult<(Nodes, Vec<RemoteNode>)> {
+    let nodes = merkle_tree_factory::load_node_state(pool_name, merkle)?;
 
     Ok(nodes.iter().map(|(_, txn)| {
         let node_alias = txn.txn.data.data.alias.clone();
@@ -725,6 +879,14 @@ fn _send_submit_ack(cmd_id: CommandHandle, res: IndyResult<String>) {
     CommandExecutor::instance().send(Command::Ledger(lc)).unwrap();
 }
 
+fn _send_status_ack(cmd_id: CommandHandle, status: &PoolStatus) {
+    let res = serde_json::to_string(status)
+        .to_indy(IndyErrorKind::InvalidState, "Can't serialize pool status");
+
+    let pc = PoolCommand::GetStatusAck(cmd_id, res);
+    CommandExecutor::instance().send(Command::Pool(pc)).unwrap();
+}
+
 fn _send_open_refresh_ack(cmd_id: CommandHandle, id: PoolHandle, is_refresh: bool, res: IndyResult<()>) {
     trace!("PoolSM: from getting catchup target to active");
     let pc = if is_refresh {
@@ -738,6 +900,8 @@ fn _send_open_refresh_ack(cmd_id: CommandHandle, id: PoolHandle, is_refresh: boo
 pub struct ZMQPool {
     pub(super) pool: Pool<ZMQNetworker, RequestHandlerImpl<ZMQNetworker>>,
     pub(super) cmd_socket: zmq::Socket,
+    // Nodes replies received by custom transport are passed to pool worker through it
+    pub(super) transport_socket: Option<zmq::Socket>,
 }
 
 impl ZMQPool {
@@ -745,6 +909,7 @@ impl ZMQPool {
         ZMQPool {
             pool,
             cmd_socket,
+            transport_socket: None,
         }
     }
 }
@@ -810,11 +975,11 @@ mod tests {
 
         use super::*;
         use indy_utils::next_pool_handle;
-        use crate::domain::pool::NUMBER_READ_NODES;
+        use crate::domain::pool::{NUMBER_READ_NODES, POOL_LEDGER_PROBE_TIMEOUT};
 
         #[test]
         pub fn pool_wrapper_new_initialization_works() {
-            let _p: PoolSM<MockNetworker, MockRequestHandler> = PoolSM::new(Rc::new(RefCell::new(MockNetworker::new(0, 0, vec![], String::new()))), "name", next_pool_handle(), 0, 0, NUMBER_READ_NODES);
+            let _p: PoolSM<MockNetworker, MockRequestHandler> = PoolSM::new(Rc::new(RefCell::new(MockNetworker::new(0, 0, vec![], String::new()))), "name", next_pool_handle(), 0, 0, NUMBER_READ_NODES, POOL_LEDGER_PROBE_TIMEOUT);
         }
 
         #[test]
@@ -824,7 +989,7 @@ mod tests {
             ProtocolVersion::set(2);
             _write_genesis_txns("pool_wrapper_check_cache_works");
 
-            let p: PoolSM<MockNetworker, MockRequestHandler> = PoolSM::new(Rc::new(RefCell::new(MockNetworker::new(0, 0, vec![], String::new()))), "pool_wrapper_check_cache_works", next_pool_handle(), 0, 0, NUMBER_READ_NODES);
+            let p: PoolSM<MockNetworker, MockRequestHandler> = PoolSM::new(Rc::new(RefCell::new(MockNetworker::new(0, 0, vec![], String::new()))), "pool_wrapper_check_cache_works", next_pool_handle(), 0, 0, NUMBER_READ_NODES, POOL_LEDGER_PROBE_TIMEOUT);
             let cmd_id: CommandHandle = next_command_handle();
             let p = p.handle_event(PoolEvent::CheckCache(cmd_id));
             assert_match!(PoolState::GettingCatchupTarget(_), p.state);
@@ -836,7 +1001,7 @@ mod tests {
         pub fn pool_wrapper_check_cache_works_for_no_pool_created() {
             let p: PoolSM<MockNetworker, MockRequestHandler> =
                 PoolSM::new(Rc::new(RefCell::new(MockNetworker::new(0, 0, vec![], String::new()))),
-                            "pool_wrapper_check_cache_works_for_no_pool_created", next_pool_handle(), 0, 0, NUMBER_READ_NODES);
+                            "pool_wrapper_check_cache_works_for_no_pool_created", next_pool_handle(), 0, 0, NUMBER_READ_NODES, POOL_LEDGER_PROBE_TIMEOUT);
             let cmd_id: CommandHandle = next_command_handle();
             let p = p.handle_event(PoolEvent::CheckCache(cmd_id));
             assert_match!(PoolState::Terminated(_), p.state);
@@ -844,7 +1009,7 @@ mod tests {
 
         #[test]
         pub fn pool_wrapper_terminated_close_works() {
-            let p: PoolSM<MockNetworker, MockRequestHandler> = PoolSM::new(Rc::new(RefCell::new(MockNetworker::new(0, 0, vec![], String::new()))), "pool_wrapper_terminated_close_works", next_pool_handle(), 0, 0, NUMBER_READ_NODES);
+            let p: PoolSM<MockNetworker, MockRequestHandler> = PoolSM::new(Rc::new(RefCell::new(MockNetworker::new(0, 0, vec![], String::new()))), "pool_wrapper_terminated_close_works", next_pool_handle(), 0, 0, NUMBER_READ_NODES, POOL_LEDGER_PROBE_TIMEOUT);
             let cmd_id: CommandHandle = next_command_handle();
             let p = p.handle_event(PoolEvent::CheckCache(cmd_id));
             let cmd_id: CommandHandle = next_command_handle();
@@ -855,7 +1020,7 @@ mod tests {
         #[test]
         pub fn pool_wrapper_terminated_refresh_works() {
             test::cleanup_pool("pool_wrapper_terminated_refresh_works");
-            let p: PoolSM<MockNetworker, MockRequestHandler> = PoolSM::new(Rc::new(RefCell::new(MockNetworker::new(0, 0, vec![], String::new()))), "pool_wrapper_terminated_refresh_works", next_pool_handle(), 0, 0, NUMBER_READ_NODES);
+            let p: PoolSM<MockNetworker, MockRequestHandler> = PoolSM::new(Rc::new(RefCell::new(MockNetworker::new(0, 0, vec![], String::new()))), "pool_wrapper_terminated_refresh_works", next_pool_handle(), 0, 0, NUMBER_READ_NODES, POOL_LEDGER_PROBE_TIMEOUT);
             let cmd_id: CommandHandle = next_command_handle();
             let p = p.handle_event(PoolEvent::CheckCache(cmd_id));
 
@@ -879,6 +1044,7 @@ mod tests {
                 timeout: 0,
                 extended_timeout: 0,
                 number_read_nodes: NUMBER_READ_NODES,
+                pool_ledger_probe_timeout: POOL_LEDGER_PROBE_TIMEOUT,
             };
 
             let p = p.handle_event(PoolEvent::Timeout("".to_string(), "".to_string()));
@@ -895,7 +1061,7 @@ mod tests {
 
         #[test]
         pub fn pool_wrapper_cloe_works_from_initialization() {
-            let p: PoolSM<MockNetworker, MockRequestHandler> = PoolSM::new(Rc::new(RefCell::new(MockNetworker::new(0, 0, vec![], String::new()))), "pool_wrapper_cloe_works_from_initialization", next_pool_handle(), 0, 0, NUMBER_READ_NODES);
+            let p: PoolSM<MockNetworker, MockRequestHandler> = PoolSM::new(Rc::new(RefCell::new(MockNetworker::new(0, 0, vec![], String::new()))), "pool_wrapper_cloe_works_from_initialization", next_pool_handle(), 0, 0, NUMBER_READ_NODES, POOL_LEDGER_PROBE_TIMEOUT);
             let cmd_id: CommandHandle = next_command_handle();
             let p = p.handle_event(PoolEvent::Close(cmd_id));
             assert_match!(PoolState::Closed(_), p.state);
@@ -909,7 +1075,7 @@ mod tests {
             _write_genesis_txns("pool_wrapper_close_works_from_getting_catchup_target");
 
             let p: PoolSM<MockNetworker, MockRequestHandler> =
-                PoolSM::new(Rc::new(RefCell::new(MockNetworker::new(0, 0, vec![], String::new()))), "pool_wrapper_close_works_from_getting_catchup_target", next_pool_handle(), 0, 0, NUMBER_READ_NODES);
+                PoolSM::new(Rc::new(RefCell::new(MockNetworker::new(0, 0, vec![], String::new()))), "pool_wrapper_close_works_from_getting_catchup_target", next_pool_handle(), 0, 0, NUMBER_READ_NODES, POOL_LEDGER_PROBE_TIMEOUT);
             let cmd_id: CommandHandle = next_command_handle();
             let p = p.handle_event(PoolEvent::CheckCache(cmd_id));
             let cmd_id: CommandHandle = next_command_handle();
@@ -927,7 +1093,7 @@ mod tests {
             _write_genesis_txns("pool_wrapper_catchup_target_not_found_works");
 
             let p: PoolSM<MockNetworker, MockRequestHandler> =
-                PoolSM::new(Rc::new(RefCell::new(MockNetworker::new(0, 0, vec![], String::new()))), "pool_wrapper_catchup_target_not_found_works", next_pool_handle(), 0, 0, NUMBER_READ_NODES);
+                PoolSM::new(Rc::new(RefCell::new(MockNetworker::new(0, 0, vec![], String::new()))), "pool_wrapper_catchup_target_not_found_works", next_pool_handle(), 0, 0, NUMBER_READ_NODES, POOL_LEDGER_PROBE_TIMEOUT);
             let cmd_id: CommandHandle = next_command_handle();
             let p = p.handle_event(PoolEvent::CheckCache(cmd_id));
             let p = p.handle_event(PoolEvent::CatchupTargetNotFound(err_msg(IndyErrorKind::PoolTimeout, "Pool timeout")));
@@ -944,7 +1110,7 @@ mod tests {
             _write_genesis_txns("pool_wrapper_getting_catchup_target_synced_works");
 
             let p: PoolSM<MockNetworker, MockRequestHandler> =
-                PoolSM::new(Rc::new(RefCell::new(MockNetworker::new(0, 0, vec![], String::new()))), "pool_wrapper_getting_catchup_target_synced_works", next_pool_handle(), 0, 0, NUMBER_READ_NODES);
+                PoolSM::new(Rc::new(RefCell::new(MockNetworker::new(0, 0, vec![], String::new()))), "pool_wrapper_getting_catchup_target_synced_works", next_pool_handle(), 0, 0, NUMBER_READ_NODES, POOL_LEDGER_PROBE_TIMEOUT);
             let cmd_id: CommandHandle = next_command_handle();
             let p = p.handle_event(PoolEvent::CheckCache(cmd_id));
             let p = p.handle_event(PoolEvent::Synced(MerkleTree::from_vec(vec![]).unwrap()));
@@ -967,7 +1133,7 @@ mod tests {
                 "pool_wrapper_getting_catchup_target_synced_works_for_node_state_error",
                 next_pool_handle(),
                 0,
-                0, NUMBER_READ_NODES);
+                0, NUMBER_READ_NODES, POOL_LEDGER_PROBE_TIMEOUT);
             let cmd_id: CommandHandle = next_command_handle();
             let p = p.handle_event(PoolEvent::CheckCache(cmd_id));
             ProtocolVersion::set(1);
@@ -995,7 +1161,7 @@ mod tests {
                 "pool_wrapper_getting_catchup_target_catchup_target_found_works",
                 next_pool_handle(),
                 0,
-                0, NUMBER_READ_NODES);
+                0, NUMBER_READ_NODES, POOL_LEDGER_PROBE_TIMEOUT);
             let cmd_id: CommandHandle = next_command_handle();
             let p = p.handle_event(PoolEvent::CheckCache(cmd_id));
             let p = p.handle_event(PoolEvent::CatchupTargetFound(mt.root_hash().to_vec(), mt.count, mt));
@@ -1019,7 +1185,7 @@ mod tests {
                             "pool_wrapper_getting_catchup_target_catchup_target_found_works_for_node_state_error",
                             next_pool_handle(),
                             0,
-                            0, NUMBER_READ_NODES);
+                            0, NUMBER_READ_NODES, POOL_LEDGER_PROBE_TIMEOUT);
             let cmd_id: CommandHandle = next_command_handle();
             let p = p.handle_event(PoolEvent::CheckCache(cmd_id));
             ProtocolVersion::set(1);
@@ -1048,7 +1214,7 @@ mod tests {
                             "pool_wrapper_sync_catchup_close_works",
                             next_pool_handle(),
                             0,
-                            0, NUMBER_READ_NODES);
+                            0, NUMBER_READ_NODES, POOL_LEDGER_PROBE_TIMEOUT);
             let cmd_id: CommandHandle = next_command_handle();
             let p = p.handle_event(PoolEvent::CheckCache(cmd_id));
             let p = p.handle_event(PoolEvent::CatchupTargetFound(mt.root_hash().to_vec(), mt.count, mt));
@@ -1077,7 +1243,7 @@ mod tests {
                 "pool_wrapper_sync_catchup_synced_works",
                 next_pool_handle(),
                 0,
-                0, NUMBER_READ_NODES);
+                0, NUMBER_READ_NODES, POOL_LEDGER_PROBE_TIMEOUT);
             let cmd_id: CommandHandle = next_command_handle();
             let p = p.handle_event(PoolEvent::CheckCache(cmd_id));
             let p = p.handle_event(PoolEvent::CatchupTargetFound(mt.root_hash().to_vec(), mt.count, mt));
@@ -1105,7 +1271,7 @@ mod tests {
                 "pool_wrapper_sync_catchup_synced_works_for_node_state_error",
                 next_pool_handle(),
                 0,
-                0, NUMBER_READ_NODES);
+                0, NUMBER_READ_NODES, POOL_LEDGER_PROBE_TIMEOUT);
             let cmd_id: CommandHandle = next_command_handle();
             let p = p.handle_event(PoolEvent::CheckCache(cmd_id));
             let p = p.handle_event(PoolEvent::CatchupTargetFound(mt.root_hash().to_vec(), mt.count, mt));
@@ -1138,7 +1304,7 @@ mod tests {
                                                                            "pool_wrapper_active_send_request_works",
                                                                            next_pool_handle(),
                                                                            0,
-                                                                           0, NUMBER_READ_NODES);
+                                                                           0, NUMBER_READ_NODES, POOL_LEDGER_PROBE_TIMEOUT);
             let cmd_id: CommandHandle = next_command_handle();
             let p = p.handle_event(PoolEvent::CheckCache(cmd_id));
             let p = p.handle_event(PoolEvent::Synced(MerkleTree::from_vec(vec![]).unwrap()));
@@ -1179,7 +1345,7 @@ mod tests {
                             "pool_wrapper_active_send_request_works_for_no_req_id",
                             next_pool_handle(),
                             0,
-                            0, NUMBER_READ_NODES);
+                            0, NUMBER_READ_NODES, POOL_LEDGER_PROBE_TIMEOUT);
             let cmd_id: CommandHandle = next_command_handle();
             let p = p.handle_event(PoolEvent::CheckCache(cmd_id));
             let p = p.handle_event(PoolEvent::Synced(MerkleTree::from_vec(vec![]).unwrap()));
@@ -1233,7 +1399,7 @@ mod tests {
                 "pool_wrapper_active_node_reply_works",
                 next_pool_handle(),
                 0,
-                0, NUMBER_READ_NODES);
+                0, NUMBER_READ_NODES, POOL_LEDGER_PROBE_TIMEOUT);
             let cmd_id: CommandHandle = next_command_handle();
             let p = p.handle_event(PoolEvent::CheckCache(cmd_id));
             let p = p.handle_event(PoolEvent::Synced(MerkleTree::from_vec(vec![]).unwrap()));
@@ -1272,7 +1438,7 @@ mod tests {
                                        vec![],
                                        String::new()))),
                             "pool_wrapper_sends_requests_to_two_nodes",
-                            next_pool_handle(), 0, 0, NUMBER_READ_NODES);
+                            next_pool_handle(), 0, 0, NUMBER_READ_NODES, POOL_LEDGER_PROBE_TIMEOUT);
             let cmd_id: CommandHandle = next_command_handle();
             let p = p.handle_event(PoolEvent::CheckCache(cmd_id));
             let p = p.handle_event(PoolEvent::Synced(MerkleTree::from_vec(vec![]).unwrap()));
@@ -1325,7 +1491,7 @@ mod tests {
                                                                            "pool_wrapper_active_node_reply_works_for_no_request",
                                                                            next_pool_handle(),
                                                                            0,
-                                                                           0, NUMBER_READ_NODES);
+                                                                           0, NUMBER_READ_NODES, POOL_LEDGER_PROBE_TIMEOUT);
             let cmd_id: CommandHandle = next_command_handle();
             let p = p.handle_event(PoolEvent::CheckCache(cmd_id));
             let p = p.handle_event(PoolEvent::Synced(MerkleTree::from_vec(vec![]).unwrap()));
@@ -1369,7 +1535,7 @@ mod tests {
                             "pool_wrapper_active_node_reply_works_for_invalid_reply",
                             next_pool_handle(),
                             0,
-                            0, NUMBER_READ_NODES);
+                            0, NUMBER_READ_NODES, POOL_LEDGER_PROBE_TIMEOUT);
             let cmd_id: CommandHandle = next_command_handle();
             let p = p.handle_event(PoolEvent::CheckCache(cmd_id));
             let p = p.handle_event(PoolEvent::Synced(MerkleTree::from_vec(vec![]).unwrap()));
@@ -1387,6 +1553,313 @@ mod tests {
             test::cleanup_storage("pool_wrapper_active_node_reply_works_for_invalid_reply");
         }
 
+        #[test]
+        pub fn pool_wrapper_active_pool_outdated_works() {
+            test::cleanup_storage("pool_wrapper_active_pool_outdated_works");
+
+            let p = _active_pool_sm("pool_wrapper_active_pool_outdated_works");
+            let p = p.handle_event(PoolEvent::PoolOutdated);
+            assert_match!(PoolState::Active(_), p.state);
+            match p.state {
+                PoolState::Active(state) => {
+                    assert!(state.refresher.is_some());
+                }
+                _ => assert!(false)
+            };
+
+            test::cleanup_storage("pool_wrapper_active_pool_outdated_works");
+        }
+
+        #[test]
+        pub fn pool_wrapper_active_invalid_state_proof_works() {
+            test::cleanup_storage("pool_wrapper_active_invalid_state_proof_works");
+
+            let p = _active_pool_sm("pool_wrapper_active_invalid_state_proof_works");
+            let p = p.handle_event(PoolEvent::InvalidStateProof("Node1".to_string()));
+            let p = p.handle_event(PoolEvent::InvalidStateProof("Node1".to_string()));
+            match p.state {
+                PoolState::Active(ref state) => {
+                    assert!(state.refresher.is_none());
+                }
+                _ => assert!(false)
+            };
+
+            let p = p.handle_event(PoolEvent::InvalidStateProof("Node2".to_string()));
+            match p.state {
+                PoolState::Active(state) => {
+                    assert!(state.refresher.is_some());
+                    assert!(state.invalid_sp_nodes.is_empty());
+                }
+                _ => assert!(false)
+            };
+
+            test::cleanup_storage("pool_wrapper_active_invalid_state_proof_works");
+        }
+
+        #[test]
+        pub fn pool_wrapper_active_ledger_status_works_for_outdated_pool() {
+            test::cleanup_storage("pool_wrapper_active_ledger_status_works_for_outdated_pool");
+
+            let ls = Message::LedgerStatus(LedgerStatus {
+                txnSeqNo: 10,
+                merkleRoot: String::new(),
+                ledgerId: 0,
+                ppSeqNo: None,
+                viewNo: None,
+                protocolVersion: Some(2),
+            });
+            let ls = serde_json::to_string(&ls).unwrap();
+
+            let p = _active_pool_sm("pool_wrapper_active_ledger_status_works_for_outdated_pool");
+            let p = p.handle_event(PoolEvent::NodeReply(ls.clone(), "Node1".to_string()));
+            let p = p.handle_event(PoolEvent::NodeReply(ls.clone(), "Node1".to_string()));
+            match p.state {
+                PoolState::Active(ref state) => {
+                    assert!(state.refresher.is_none());
+                }
+                _ => assert!(false)
+            };
+
+            let p = p.handle_event(PoolEvent::NodeReply(ls, "Node2".to_string()));
+            match p.state {
+                PoolState::Active(state) => {
+                    assert!(state.refresher.is_some());
+                    assert!(state.outdated_pool_nodes.is_empty());
+                }
+                _ => assert!(false)
+            };
+
+            test::cleanup_storage("pool_wrapper_active_ledger_status_works_for_outdated_pool");
+        }
+
+        #[test]
+        pub fn pool_wrapper_active_ledger_status_works_for_actual_pool() {
+            test::cleanup_storage("pool_wrapper_active_ledger_status_works_for_actual_pool");
+
+            let ls = Message::LedgerStatus(LedgerStatus {
+                txnSeqNo: 4,
+                merkleRoot: String::new(),
+                ledgerId: 0,
+                ppSeqNo: None,
+                viewNo: None,
+                protocolVersion: Some(2),
+            });
+            let ls = serde_json::to_string(&ls).unwrap();
+
+            let p = _active_pool_sm("pool_wrapper_active_ledger_status_works_for_actual_pool");
+            let p = p.handle_event(PoolEvent::NodeReply(ls, "Node1".to_string()));
+            match p.state {
+                PoolState::Active(state) => {
+                    assert!(state.refresher.is_none());
+                }
+                _ => assert!(false)
+            };
+
+            test::cleanup_storage("pool_wrapper_active_ledger_status_works_for_actual_pool");
+        }
+
+        #[test]
+        pub fn pool_wrapper_active_background_synced_works() {
+            test::cleanup_storage("pool_wrapper_active_background_synced_works");
+
+            let req = json!({
+                "reqId": 1,
+                "operation": {
+                    "type": "1"
+                }
+            }).to_string();
+
+            let p = _active_pool_sm("pool_wrapper_active_background_synced_works");
+            let cmd_id: CommandHandle = next_command_handle();
+            let p = p.handle_event(PoolEvent::SendRequest(cmd_id, req, None, None));
+            let p = p.handle_event(PoolEvent::PoolOutdated);
+            let p = p.handle_event(PoolEvent::Synced(merkle_tree_factory::create("pool_wrapper_active_background_synced_works").unwrap()));
+            assert_match!(PoolState::Active(_), p.state);
+            match p.state {
+                PoolState::Active(state) => {
+                    assert!(state.refresher.is_none());
+                    assert_eq!(state.nodes.len(), 4);
+                    assert_eq!(state.request_handlers.len(), 1);
+                }
+                _ => assert!(false)
+            };
+
+            test::cleanup_storage("pool_wrapper_active_background_synced_works");
+        }
+
+        #[test]
+        pub fn pool_wrapper_active_refresh_works_for_background_refresh() {
+            test::cleanup_storage("pool_wrapper_active_refresh_works_for_background_refresh");
+
+            let networker = Rc::new(RefCell::new(MockNetworker::new(0, 0, vec![], String::new())));
+            let p: PoolSM<MockNetworker, RequestHandlerImpl<MockNetworker>> =
+                _active_pool_sm_with_networker("pool_wrapper_active_refresh_works_for_background_refresh", networker.clone());
+            let p = p.handle_event(PoolEvent::PoolOutdated);
+
+            // running background refresh is continued without sending of LEDGER_STATUS again
+            networker.borrow_mut().events.clear();
+            let p = p.handle_event(PoolEvent::Refresh(next_command_handle()));
+            assert_match!(PoolState::GettingCatchupTarget(_), p.state);
+            assert!(networker.borrow().events.is_empty());
+
+            test::cleanup_storage("pool_wrapper_active_refresh_works_for_background_refresh");
+        }
+
+        #[test]
+        pub fn pool_wrapper_active_refresh_works() {
+            test::cleanup_storage("pool_wrapper_active_refresh_works");
+
+            let networker = Rc::new(RefCell::new(MockNetworker::new(0, 0, vec![], String::new())));
+            let p: PoolSM<MockNetworker, RequestHandlerImpl<MockNetworker>> =
+                _active_pool_sm_with_networker("pool_wrapper_active_refresh_works", networker.clone());
+
+            networker.borrow_mut().events.clear();
+            let p = p.handle_event(PoolEvent::Refresh(next_command_handle()));
+            assert_match!(PoolState::GettingCatchupTarget(_), p.state);
+            assert!(networker.borrow().events.iter().any(|event| match *event {
+                Some(NetworkerEvent::SendAllRequest(ref msg, _, _, _)) => msg.contains("LEDGER_STATUS"),
+                _ => false
+            }));
+
+            test::cleanup_storage("pool_wrapper_active_refresh_works");
+        }
+
+        #[test]
+        pub fn pool_wrapper_active_send_request_works_for_pool_ledger_probe() {
+            test::cleanup_storage("pool_wrapper_active_send_request_works_for_pool_ledger_probe");
+
+            let p = _active_pool_sm("pool_wrapper_active_send_request_works_for_pool_ledger_probe");
+            let p = _send_request_after_probe_timeout(p);
+            match p.state {
+                PoolState::Active(state) => {
+                    assert!(state.refresher.is_some());
+                    assert!(time::now() - state.last_probe < Duration::seconds(POOL_LEDGER_PROBE_TIMEOUT));
+                    assert_eq!(state.request_handlers.len(), 1);
+                }
+                _ => assert!(false)
+            };
+
+            test::cleanup_storage("pool_wrapper_active_send_request_works_for_pool_ledger_probe");
+        }
+
+        #[test]
+        pub fn pool_wrapper_active_pool_ledger_probe_works_for_outdated_pool() {
+            test::cleanup_storage("pool_wrapper_active_pool_ledger_probe_works_for_outdated_pool");
+
+            let networker = Rc::new(RefCell::new(MockNetworker::new(0, 0, vec![], String::new())));
+            let p: PoolSM<MockNetworker, RequestHandlerImpl<MockNetworker>> =
+                _active_pool_sm_with_networker("pool_wrapper_active_pool_ledger_probe_works_for_outdated_pool", networker.clone());
+            let p = _send_request_after_probe_timeout(p);
+
+            // f + 1 nodes reply to the probe with bigger pool ledger
+            networker.borrow_mut().events.clear();
+            let ls = _pool_ledger_status(10, vec![1u8; 32].to_base58());
+            let p = p.handle_event(PoolEvent::NodeReply(ls.clone(), "Node1".to_string()));
+            let p = p.handle_event(PoolEvent::NodeReply(ls, "Node2".to_string()));
+
+            match p.state {
+                PoolState::Active(state) => {
+                    assert!(state.refresher.is_some());
+                    assert_eq!(state.request_handlers.len(), 1);
+                }
+                _ => assert!(false)
+            };
+
+            // catchup of pool ledger is started
+            assert!(networker.borrow().events.iter().any(|event| match *event {
+                Some(NetworkerEvent::SendOneRequest(ref msg, _, _)) => msg.contains("CATCHUP_REQ"),
+                _ => false
+            }));
+
+            test::cleanup_storage("pool_wrapper_active_pool_ledger_probe_works_for_outdated_pool");
+        }
+
+        #[test]
+        pub fn pool_wrapper_active_pool_ledger_probe_works_for_actual_pool() {
+            test::cleanup_storage("pool_wrapper_active_pool_ledger_probe_works_for_actual_pool");
+
+            let networker = Rc::new(RefCell::new(MockNetworker::new(0, 0, vec![], String::new())));
+            let p: PoolSM<MockNetworker, RequestHandlerImpl<MockNetworker>> =
+                _active_pool_sm_with_networker("pool_wrapper_active_pool_ledger_probe_works_for_actual_pool", networker.clone());
+            let p = _send_request_after_probe_timeout(p);
+
+            let merkle = merkle_tree_factory::create("pool_wrapper_active_pool_ledger_probe_works_for_actual_pool").unwrap();
+            let ls = _pool_ledger_status(merkle.count(), merkle.root_hash().as_slice().to_base58());
+            let p = p.handle_event(PoolEvent::NodeReply(ls.clone(), "Node1".to_string()));
+            let p = p.handle_event(PoolEvent::NodeReply(ls, "Node2".to_string()));
+
+            match p.state {
+                PoolState::Active(state) => {
+                    assert!(state.refresher.is_none());
+                    assert_eq!(state.nodes.len(), 4);
+                    assert_eq!(state.mt_size, merkle.count());
+                    assert_eq!(state.request_handlers.len(), 1);
+                }
+                _ => assert!(false)
+            };
+
+            test::cleanup_storage("pool_wrapper_active_pool_ledger_probe_works_for_actual_pool");
+        }
+
+        #[test]
+        pub fn pool_wrapper_active_background_catchup_target_not_found_works() {
+            test::cleanup_storage("pool_wrapper_active_background_catchup_target_not_found_works");
+
+            let p = _active_pool_sm("pool_wrapper_active_background_catchup_target_not_found_works");
+            let p = p.handle_event(PoolEvent::PoolOutdated);
+            let p = p.handle_event(PoolEvent::CatchupTargetNotFound(err_msg(IndyErrorKind::PoolTimeout, "Pool timeout")));
+            assert_match!(PoolState::Active(_), p.state);
+            match p.state {
+                PoolState::Active(state) => {
+                    assert!(state.refresher.is_none());
+                }
+                _ => assert!(false)
+            };
+
+            test::cleanup_storage("pool_wrapper_active_background_catchup_target_not_found_works");
+        }
+
+        fn _active_pool_sm(pool_name: &str) -> PoolSM<MockNetworker, MockRequestHandler> {
+            _active_pool_sm_with_networker(pool_name, Rc::new(RefCell::new(MockNetworker::new(0, 0, vec![], String::new()))))
+        }
+
+        fn _active_pool_sm_with_networker<R: RequestHandler<MockNetworker>>(pool_name: &str, networker: Rc<RefCell<MockNetworker>>) -> PoolSM<MockNetworker, R> {
+            ProtocolVersion::set(2);
+            _write_genesis_txns(pool_name);
+
+            let p: PoolSM<MockNetworker, R> = PoolSM::new(networker, pool_name, next_pool_handle(), 0, 0, NUMBER_READ_NODES, POOL_LEDGER_PROBE_TIMEOUT);
+            let cmd_id: CommandHandle = next_command_handle();
+            let p = p.handle_event(PoolEvent::CheckCache(cmd_id));
+            p.handle_event(PoolEvent::Synced(merkle_tree_factory::create(pool_name).unwrap()))
+        }
+
+        // Sends request to the pool which ledger state wasn't checked for probe timeout
+        fn _send_request_after_probe_timeout<R: RequestHandler<MockNetworker>>(mut p: PoolSM<MockNetworker, R>) -> PoolSM<MockNetworker, R> {
+            if let PoolState::Active(ref mut state) = p.state {
+                state.last_probe = time::now() - Duration::seconds(POOL_LEDGER_PROBE_TIMEOUT + 1);
+            }
+
+            let req = json!({
+                "reqId": 1,
+                "operation": {
+                    "type": "1"
+                }
+            }).to_string();
+
+            p.handle_event(PoolEvent::SendRequest(next_command_handle(), req, None, None))
+        }
+
+        fn _pool_ledger_status(txn_seq_no: usize, merkle_root: String) -> String {
+            serde_json::to_string(&Message::LedgerStatus(LedgerStatus {
+                txnSeqNo: txn_seq_no,
+                merkleRoot: merkle_root,
+                ledgerId: 0,
+                ppSeqNo: None,
+                viewNo: None,
+                protocolVersion: Some(2),
+            })).unwrap()
+        }
+
         fn _write_genesis_txns(pool_name: &str) {
             let txns = test::gen_txns().join("\n");
 
@@ -18,6 +18,7 @@ use indy_api_types::errors::prelude::*;
 use crate::services::ledger::merkletree::merkletree::MerkleTree;
 use crate::services::pool::catchup::{build_catchup_req, CatchupProgress, check_cons_proofs, check_nodes_responses_on_status};
 use crate::services::pool::events::NetworkerEvent;
+use crate::services::pool::events::NodeOutcome;
 use crate::services::pool::events::PoolEvent;
 use crate::services::pool::events::RequestEvent;
 use crate::services::pool::{get_last_signed_time, Nodes};
@@ -325,11 +326,13 @@ impl<T: Networker> RequestSM<T> {
                 }
             }
             RequestState::Consensus(mut state) => {
+                let outcome = _get_node_outcome(&re);
                 match re {
                     RequestEvent::Reply(_, raw_msg, node_alias, req_id) |
                     RequestEvent::ReqNACK(_, raw_msg, node_alias, req_id) |
                     RequestEvent::Reject(_, raw_msg, node_alias, req_id)
                     => {
+                        state.networker.borrow_mut().process_event(Some(NetworkerEvent::UpdateNodeHealth(req_id.clone(), node_alias.clone(), outcome)));
                         if let Ok((_, result_without_proof)) = _get_msg_result_without_state_proof(&raw_msg) {
                             let hashable = HashableValue { inner: result_without_proof };
 
@@ -367,6 +370,7 @@ impl<T: Networker> RequestSM<T> {
                         (RequestState::Consensus(state), None)
                     }
                     RequestEvent::Timeout(req_id, node_alias) => {
+                        state.networker.borrow_mut().process_event(Some(NetworkerEvent::UpdateNodeHealth(req_id.clone(), node_alias.clone(), NodeOutcome::Timeout)));
                         state.timeout_nodes.insert(node_alias.clone());
                         if state.is_consensus_reachable(f, nodes.len()) {
                             state.networker.borrow_mut().process_event(Some(NetworkerEvent::CleanTimeout(req_id, Some(node_alias))));
@@ -386,6 +390,7 @@ impl<T: Networker> RequestSM<T> {
                 }
             }
             RequestState::Single(mut state) => {
+                let outcome = _get_node_outcome(&re);
                 match re {
                     RequestEvent::Reply(_, raw_msg, node_alias, req_id) |
                     RequestEvent::ReqNACK(_, raw_msg, node_alias, req_id) |
@@ -406,15 +411,30 @@ impl<T: Networker> RequestSM<T> {
                                 )
                             };
 
-                            if cnt > f
-                                || _check_state_proof(&result, f, &generator, &nodes, &raw_msg, state.sp_key.as_ref().map(Vec::as_slice), state.timestamps, last_write_time) {
+                            if cnt > f {
+                                // consensus is reached, so state proof of the reply isn't checked and node health isn't updated
                                 state.networker.borrow_mut().process_event(Some(NetworkerEvent::CleanTimeout(req_id, None)));
-                                _send_ok_replies(&cmd_ids, if cnt > f { &soonest } else { &raw_msg });
+                                _send_ok_replies(&cmd_ids, &soonest);
                                 (RequestState::finish(), None)
                             } else {
-                                (state.try_to_continue(req_id, node_alias, &cmd_ids, nodes.len(), timeout), None)
+                                let state_proof = _check_state_proof(&result, f, &generator, &nodes, &raw_msg, state.sp_key.as_ref().map(Vec::as_slice), state.timestamps, last_write_time);
+                                let (outcome, event) = match state_proof {
+                                    // Pool decides if it's the node fault or our BLS keys are outdated
+                                    Some((false, _)) => (NodeOutcome::InvalidStateProof, Some(PoolEvent::InvalidStateProof(node_alias.clone()))),
+                                    _ => (outcome, None)
+                                };
+                                state.networker.borrow_mut().process_event(Some(NetworkerEvent::UpdateNodeHealth(req_id.clone(), node_alias.clone(), outcome)));
+
+                                if state_proof == Some((true, true)) {
+                                    state.networker.borrow_mut().process_event(Some(NetworkerEvent::CleanTimeout(req_id, None)));
+                                    _send_ok_replies(&cmd_ids, &raw_msg);
+                                    (RequestState::finish(), event)
+                                } else {
+                                    (state.try_to_continue(req_id, node_alias, &cmd_ids, nodes.len(), timeout), event)
+                                }
                             }
                         } else {
+                            state.networker.borrow_mut().process_event(Some(NetworkerEvent::UpdateNodeHealth(req_id.clone(), node_alias.clone(), NodeOutcome::Nack)));
                             state.denied_nodes.insert(node_alias.clone());
                             (state.try_to_continue(req_id, node_alias, &cmd_ids, nodes.len(), timeout), None)
                         }
@@ -424,6 +444,7 @@ impl<T: Networker> RequestSM<T> {
                         (RequestState::Single(state), None)
                     }
                     RequestEvent::Timeout(req_id, node_alias) => {
+                        state.networker.borrow_mut().process_event(Some(NetworkerEvent::UpdateNodeHealth(req_id.clone(), node_alias.clone(), NodeOutcome::Timeout)));
                         state.timeout_nodes.insert(node_alias.clone());
                         (state.try_to_continue(req_id, node_alias, &cmd_ids, nodes.len(), timeout), None)
                     }
@@ -677,6 +698,13 @@ impl<T: Networker> ConsensusState<T> {
     }
 }
 
+fn _get_node_outcome(re: &RequestEvent) -> NodeOutcome {
+    match *re {
+        RequestEvent::ReqNACK(..) => NodeOutcome::Nack,
+        _ => NodeOutcome::Reply,
+    }
+}
+
 fn _parse_nack(denied_nodes: &mut HashSet<String>, f: usize, raw_msg: &str, cmd_ids: &[CommandHandle], node_alias: &str) -> bool {
     if denied_nodes.len() == f {
         _send_ok_replies(cmd_ids, raw_msg);
@@ -740,20 +768,20 @@ fn _get_msg_result_without_state_proof(msg: &str) -> IndyResult<(SJsonValue, SJs
     Ok((msg_result, msg_result_without_proof))
 }
 
-fn _check_state_proof(msg_result: &SJsonValue, f: usize, gen: &Generator, bls_keys: &Nodes, raw_msg: &str, sp_key: Option<&[u8]>, requested_timestamps: (Option<u64>, Option<u64>), last_write_time: u64) -> bool {
+// Returns (proof is valid, reply is fresh) or None if the reply doesn't contain state proof
+fn _check_state_proof(msg_result: &SJsonValue, f: usize, gen: &Generator, bls_keys: &Nodes, raw_msg: &str, sp_key: Option<&[u8]>, requested_timestamps: (Option<u64>, Option<u64>), last_write_time: u64) -> Option<(bool, bool)> {
     debug!("TransactionHandler::process_reply: Try to verify proof and signature >>");
 
-    let proof_checking_res = match state_proof::parse_generic_reply_for_proof_checking(&msg_result, raw_msg, sp_key) {
+    let res = match state_proof::parse_generic_reply_for_proof_checking(&msg_result, raw_msg, sp_key) {
         Some(parsed_sps) => {
             debug!("TransactionHandler::process_reply: Proof and signature are present");
-            state_proof::verify_parsed_sp(parsed_sps, bls_keys, f, gen)
+            Some((state_proof::verify_parsed_sp(parsed_sps, bls_keys, f, gen),
+                  _check_freshness(msg_result, requested_timestamps, last_write_time)))
         }
-        None => false
+        None => None
     };
 
-    let res = proof_checking_res && _check_freshness(msg_result, requested_timestamps, last_write_time);
-
-    debug!("TransactionHandler::process_reply: Try to verify proof and signature << {}", res);
+    debug!("TransactionHandler::process_reply: Try to verify proof and signature << {:?}", res);
     res
 }
 
@@ -925,7 +953,10 @@ pub mod tests {
 
     fn _request_handler(pool_name: &str, f: usize, nodes_cnt: usize) -> RequestHandlerImpl<MockNetworker> {
         let networker = Rc::new(RefCell::new(MockNetworker::new(0, 0, vec![], String::new())));
+        _request_handler_with_networker(pool_name, f, nodes_cnt, networker)
+    }
 
+    fn _request_handler_with_networker(pool_name: &str, f: usize, nodes_cnt: usize, networker: Rc<RefCell<MockNetworker>>) -> RequestHandlerImpl<MockNetworker> {
         let mut default_nodes: Nodes = HashMap::new();
         default_nodes.insert(NODE.to_string(), None);
 
@@ -1229,6 +1260,24 @@ pub mod tests {
             assert_match!(RequestState::Finish(_), request_handler.request_wrapper.unwrap().state);
         }
 
+        #[test]
+        fn request_handler_process_reply_event_from_single_state_works_for_consensus_reached_and_node_health() {
+            let networker = Rc::new(RefCell::new(MockNetworker::new(0, 0, vec![], String::new())));
+            let mut request_handler = _request_handler_with_networker("request_handler_process_reply_event_from_single_state_works_for_consensus_reached_and_node_health", 1, 2, networker.clone());
+            request_handler.process_event(Some(RequestEvent::CustomSingleRequest(MESSAGE.to_string(), REQ_ID.to_string(), None, (None, None))));
+            request_handler.process_event(Some(RequestEvent::Reply(Reply::default(), "{}".to_string(), NODE.to_string(), REQ_ID.to_string())));
+            request_handler.process_event(Some(RequestEvent::Reply(Reply::default(), "{}".to_string(), NODE_2.to_string(), REQ_ID.to_string())));
+            assert_match!(RequestState::Finish(_), request_handler.request_wrapper.unwrap().state);
+
+            let health_updates = networker.borrow().events.iter()
+                .filter_map(|event| match *event {
+                    Some(NetworkerEvent::UpdateNodeHealth(_, ref node_alias, outcome)) => Some((node_alias.clone(), outcome)),
+                    _ => None
+                })
+                .collect::<Vec<(String, NodeOutcome)>>();
+            assert_eq!(vec![(NODE.to_string(), NodeOutcome::Reply)], health_updates);
+        }
+
         fn correct_state_proof_reply(timestamp: u64) -> String {
             json!({
                 "result": {
@@ -0,0 +1,183 @@
+use std::ffi::CString;
+use std::sync::Arc;
+
+use indy_api_types::{ErrorCode, PoolHandle};
+use indy_api_types::errors::prelude::*;
+
+use crate::api::pool::CustomTransportSend;
+use crate::commands::{Command, CommandExecutor};
+use crate::commands::pool::PoolCommand;
+use crate::services::pool::events::PoolEvent;
+use crate::services::pool::types::RemoteNode;
+
+use super::zmq::PollItem;
+use super::zmq::Socket as ZSocket;
+
+pub const LOOPBACK_TRANSPORT: &str = "loopback";
+
+/// Delivers messages to pool nodes instead of CurveZMQ sockets.
+/// Nodes replies must be passed back with `PoolService::deliver_transport_reply`.
+pub trait Transport: Send + Sync {
+    fn send(&self, pool_handle: PoolHandle, node_alias: &str, node_address: &str, msg: &str) -> IndyResult<()>;
+}
+
+/// Transport registered with `indy_register_pool_transport`.
+pub struct CustomTransport {
+    send: CustomTransportSend,
+}
+
+impl CustomTransport {
+    pub fn new(send: CustomTransportSend) -> Self {
+        CustomTransport { send }
+    }
+}
+
+impl Transport for CustomTransport {
+    fn send(&self, pool_handle: PoolHandle, node_alias: &str, node_address: &str, msg: &str) -> IndyResult<()> {
+        let node_alias = CString::new(node_alias)
+            .to_indy(IndyErrorKind::InvalidState, "Node alias contains null byte")?;
+        let node_address = CString::new(node_address)
+            .to_indy(IndyErrorKind::InvalidState, "Node address contains null byte")?;
+        let msg = CString::new(msg)
+            .to_indy(IndyErrorKind::InvalidState, "Message contains null byte")?;
+
+        let err = (self.send)(pool_handle, node_alias.as_ptr(), node_address.as_ptr(), msg.as_ptr());
+
+        match err {
+            ErrorCode::Success => Ok(()),
+            err => Err(err_msg(IndyErrorKind::IOError, format!("Transport failed to send message to node {}: {:?}", node_alias.to_string_lossy(), err)))
+        }
+    }
+}
+
+type LoopbackResponder = dyn Fn(&str, &str) -> Option<String> + Send + Sync;
+
+/// In-process transport without real nodes. Every message is passed to the responder
+/// together with the node alias and the returned reply (if any) is delivered back as this node reply.
+pub struct LoopbackTransport {
+    responder: Box<LoopbackResponder>,
+}
+
+impl LoopbackTransport {
+    pub fn new(responder: Box<LoopbackResponder>) -> Self {
+        LoopbackTransport { responder }
+    }
+
+    /// Nodes echo every message. Echoed LEDGER_STATUS matches the local pool ledger,
+    /// so it is enough to open and refresh pool.
+    pub fn echo() -> Self {
+        LoopbackTransport::new(Box::new(|_: &str, msg: &str| Some(msg.to_string())))
+    }
+}
+
+impl Transport for LoopbackTransport {
+    fn send(&self, pool_handle: PoolHandle, node_alias: &str, _node_address: &str, msg: &str) -> IndyResult<()> {
+        if let Some(reply) = (self.responder)(node_alias, msg) {
+            // Pool worker owns receiving side only, so reply goes the same way as external one
+            CommandExecutor::instance()
+                .send(Command::Pool(PoolCommand::DeliverTransportReply(
+                    pool_handle,
+                    node_alias.to_string(),
+                    reply,
+                    Box::new(|res| {
+                        if let Err(err) = res {
+                            warn!("Loopback transport can't deliver reply: {:?}", err);
+                        }
+                    }),
+                )))?;
+        }
+        Ok(())
+    }
+}
+
+/// Transport bound to the opened pool: sends through registered transport and
+/// receives replies from the socket paired with `ZMQPool::transport_socket`.
+pub struct PoolTransport {
+    pool_handle: PoolHandle,
+    transport: Arc<dyn Transport>,
+    inbox: ZSocket,
+}
+
+impl PoolTransport {
+    pub fn new(pool_handle: PoolHandle, transport: Arc<dyn Transport>, inbox: ZSocket) -> Self {
+        PoolTransport {
+            pool_handle,
+            transport,
+            inbox,
+        }
+    }
+
+    pub fn send(&self, node: &RemoteNode, msg: &str) -> IndyResult<()> {
+        self.transport.send(self.pool_handle, &node.name, &node.zaddr, msg)
+    }
+
+    pub fn get_poll_item(&self) -> PollItem {
+        self.inbox.as_poll_item(zmq::POLLIN)
+    }
+
+    pub fn fetch_events(&self) -> Vec<PoolEvent> {
+        let mut events = Vec::new();
+        while let Ok(parts) = self.inbox.recv_multipart(zmq::DONTWAIT) {
+            match parts.as_slice() {
+                [node_alias, msg] => {
+                    match (String::from_utf8(node_alias.clone()), String::from_utf8(msg.clone())) {
+                        (Ok(node_alias), Ok(msg)) => events.push(PoolEvent::NodeReply(msg, node_alias)),
+                        _ => warn!("Transport reply is not valid utf-8, skipped")
+                    }
+                }
+                _ => warn!("Malformed transport reply with {} parts, skipped", parts.len())
+            }
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::services::pool::pool_create_pair_of_sockets;
+
+    use super::*;
+
+    struct NoopTransport;
+
+    impl Transport for NoopTransport {
+        fn send(&self, _pool_handle: PoolHandle, _node_alias: &str, _node_address: &str, _msg: &str) -> IndyResult<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn pool_transport_fetch_events_works() {
+        let (send_sock, recv_sock) = pool_create_pair_of_sockets("pool_transport_fetch_events_works");
+        let transport = PoolTransport::new(1, Arc::new(NoopTransport), recv_sock);
+
+        send_sock.send_multipart(&["n1".as_bytes(), "reply1".as_bytes()], zmq::DONTWAIT).unwrap();
+        send_sock.send_multipart(&["n2".as_bytes(), "reply2".as_bytes()], zmq::DONTWAIT).unwrap();
+
+        let mut poll_items = [transport.get_poll_item()];
+        assert_eq!(1, zmq::poll(&mut poll_items, 1000).unwrap());
+
+        let events = transport.fetch_events();
+        assert_eq!(2, events.len());
+        match (&events[0], &events[1]) {
+            (PoolEvent::NodeReply(msg1, alias1), PoolEvent::NodeReply(msg2, alias2)) => {
+                assert_eq!(("reply1", "n1"), (msg1.as_str(), alias1.as_str()));
+                assert_eq!(("reply2", "n2"), (msg2.as_str(), alias2.as_str()));
+            }
+            _ => panic!("NodeReply events expected, got {:?}", events)
+        }
+    }
+
+    #[test]
+    fn pool_transport_fetch_events_works_for_malformed_reply() {
+        let (send_sock, recv_sock) = pool_create_pair_of_sockets("pool_transport_fetch_events_works_for_malformed_reply");
+        let transport = PoolTransport::new(1, Arc::new(NoopTransport), recv_sock);
+
+        send_sock.send("reply", zmq::DONTWAIT).unwrap();
+
+        let mut poll_items = [transport.get_poll_item()];
+        assert_eq!(1, zmq::poll(&mut poll_items, 1000).unwrap());
+
+        assert!(transport.fetch_events().is_empty());
+    }
+}
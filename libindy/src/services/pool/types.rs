@@ -476,6 +476,25 @@ pub struct KeyValuesSubTrieData {
     pub kvs: Vec<(String /* b64-encoded key_suffix */, Option<String /* val */>)>,
 }
 
+/// Health of the node rated by networker by results of requests sent to it
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct NodeStatus {
+    pub alias: String,
+    pub score: f64,
+    pub blacklisted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<i64>,
+    pub replies: u64,
+    pub timeouts: u64,
+    pub nacks: u64,
+    pub invalid_state_proofs: u64,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct PoolStatus {
+    pub nodes: Vec<NodeStatus>,
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct RemoteNode {
     pub name: String,
@@ -327,6 +327,37 @@ mod high_cases {
             wallet::close_wallet(wallet_handle).unwrap();
         }
 
+        #[test]
+        fn credentials_search_works_for_paging() {
+            anoncreds::init_common_wallet();
+
+            let wallet_handle = wallet::open_wallet(ANONCREDS_WALLET_CONFIG, WALLET_CREDENTIALS).unwrap();
+
+            let options_json = json!({"offset": 1, "limit": 2}).to_string();
+            let (search_handle, count) = anoncreds::prover_search_credentials_with_options(wallet_handle, "{}", &options_json).unwrap();
+            assert_eq!(count, 4);
+
+            let credentials = anoncreds::prover_fetch_credentials(search_handle, count).unwrap();
+            let credentials: Vec<CredentialInfo> = serde_json::from_str(&credentials).unwrap();
+            assert_eq!(credentials.len(), 2);
+
+            anoncreds::prover_close_credentials_search(search_handle).unwrap();
+            wallet::close_wallet(wallet_handle).unwrap();
+        }
+
+        #[test]
+        fn credentials_search_works_for_sort_by() {
+            anoncreds::init_common_wallet();
+
+            let wallet_handle = wallet::open_wallet(ANONCREDS_WALLET_CONFIG, WALLET_CREDENTIALS).unwrap();
+
+            let options_json = json!({"sortBy": {"tagName": "~issuer_did"}}).to_string();
+            let res = anoncreds::prover_search_credentials_with_options(wallet_handle, "{}", &options_json);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+
+            wallet::close_wallet(wallet_handle).unwrap();
+        }
+
         #[test]
         fn credentials_search_works_for_filter_by_schema_id() {
             anoncreds::init_common_wallet();
@@ -451,6 +482,53 @@ mod high_cases {
     // {"issuer_did": ISSUER_DID, "schema_id": gvt_schema_id}
     // {"issuer_did": ISSUER_DID, "schema_id": xyz_schema_id}
     // {"issuer_did": DID, "schema_id": gvt_schema_id}
+    mod prover_count_credentials_by_tag {
+        use super::*;
+
+        #[test]
+        fn prover_count_credentials_by_tag_works() {
+            anoncreds::init_common_wallet();
+
+            let wallet_handle = wallet::open_wallet(ANONCREDS_WALLET_CONFIG, WALLET_CREDENTIALS).unwrap();
+
+            let counts = anoncreds::prover_count_credentials_by_tag(wallet_handle, "{}", "schema_name").unwrap();
+            let counts: serde_json::Value = serde_json::from_str(&counts).unwrap();
+
+            assert_eq!(json!({GVT_SCHEMA_NAME: 2, GVT_SUB_SCHEMA_NAME: 1, XYZ_SCHEMA_NAME: 1}), counts);
+
+            wallet::close_wallet(wallet_handle).unwrap();
+        }
+
+        #[test]
+        fn prover_count_credentials_by_tag_works_for_filter_by_issuer_did() {
+            anoncreds::init_common_wallet();
+
+            let wallet_handle = wallet::open_wallet(ANONCREDS_WALLET_CONFIG, WALLET_CREDENTIALS).unwrap();
+
+            let filter_json = json!({"issuer_did": ISSUER_DID}).to_string();
+            let counts = anoncreds::prover_count_credentials_by_tag(wallet_handle, &filter_json, "schema_name").unwrap();
+            let counts: serde_json::Value = serde_json::from_str(&counts).unwrap();
+
+            assert_eq!(json!({GVT_SCHEMA_NAME: 1, XYZ_SCHEMA_NAME: 1}), counts);
+
+            wallet::close_wallet(wallet_handle).unwrap();
+        }
+
+        #[test]
+        fn prover_get_distinct_credential_tag_values_works() {
+            anoncreds::init_common_wallet();
+
+            let wallet_handle = wallet::open_wallet(ANONCREDS_WALLET_CONFIG, WALLET_CREDENTIALS).unwrap();
+
+            let values = anoncreds::prover_get_distinct_credential_tag_values(wallet_handle, "{}", "schema_name").unwrap();
+            let values: Vec<String> = serde_json::from_str(&values).unwrap();
+
+            assert_eq!(vec![GVT_SCHEMA_NAME, GVT_SUB_SCHEMA_NAME, XYZ_SCHEMA_NAME], values);
+
+            wallet::close_wallet(wallet_handle).unwrap();
+        }
+    }
+
     mod prover_get_credentials_for_proof_req {
         use super::*;
         use crate::utils::domain::anoncreds::schema::SchemaId;
@@ -4322,6 +4400,22 @@ mod medium_cases {
         }
     }
 
+    mod prover_count_credentials_by_tag {
+        use super::*;
+
+        #[test]
+        fn prover_count_credentials_by_tag_works_for_invalid_json() {
+            anoncreds::init_common_wallet();
+
+            let wallet_handle = wallet::open_wallet(ANONCREDS_WALLET_CONFIG, WALLET_CREDENTIALS).unwrap();
+
+            let res = anoncreds::prover_count_credentials_by_tag(wallet_handle, r#""issuer_did": 12345"#, "schema_name");
+            assert_code!(ErrorCode::WalletQueryError, res);
+
+            wallet::close_wallet(wallet_handle).unwrap();
+        }
+    }
+
     mod prover_delete_credential {
         use super::*;
 
@@ -50,6 +50,68 @@ mod high_cases {
         }
     }
 
+    mod add_record_with_expiry {
+        use super::*;
+
+        #[test]
+        fn indy_add_wallet_record_with_expiry_works() {
+            let setup = Setup::wallet();
+
+            add_wallet_record_with_expiry(setup.wallet_handle, TYPE, ID, VALUE, Some(TAGS), 1).unwrap();
+            add_wallet_record_with_expiry(setup.wallet_handle, TYPE, ID_2, VALUE_2, Some(TAGS), u64::from(u32::max_value())).unwrap();
+
+            let res = get_wallet_record(setup.wallet_handle, TYPE, ID, OPTIONS_EMPTY);
+            assert_code!(ErrorCode::WalletItemNotFound, res);
+
+            get_wallet_record(setup.wallet_handle, TYPE, ID_2, OPTIONS_EMPTY).unwrap();
+
+            let search_handle = open_wallet_search(setup.wallet_handle, TYPE, QUERY_EMPTY, OPTIONS_FULL).unwrap();
+            let search_records = fetch_wallet_search_next_records(setup.wallet_handle, search_handle, 5).unwrap();
+            let search_records: SearchRecords = serde_json::from_str(&search_records).unwrap();
+            assert_eq!(Some(1), search_records.total_count);
+            assert_eq!(1, search_records.records.unwrap().len());
+            close_wallet_search(search_handle).unwrap();
+        }
+
+        #[test]
+        fn indy_add_wallet_record_with_expiry_works_for_plugged_wallet() {
+            let setup = Setup::plugged_wallet();
+
+            add_wallet_record_with_expiry(setup.wallet_handle, TYPE, ID, VALUE, None, 1).unwrap();
+
+            let res = get_wallet_record(setup.wallet_handle, TYPE, ID, OPTIONS_EMPTY);
+            assert_code!(ErrorCode::WalletItemNotFound, res);
+        }
+
+        #[test]
+        fn indy_add_wallet_record_with_expiry_works_for_expired_duplicate() {
+            let setup = Setup::wallet();
+
+            add_wallet_record_with_expiry(setup.wallet_handle, TYPE, ID, VALUE, None, 1).unwrap();
+            add_wallet_record(setup.wallet_handle, TYPE, ID, VALUE_2, None).unwrap();
+
+            check_record_field(setup.wallet_handle, TYPE, ID, "value", VALUE_2);
+        }
+    }
+
+    mod purge_expired_records {
+        use super::*;
+
+        #[test]
+        fn indy_purge_expired_wallet_records_works() {
+            let setup = Setup::wallet();
+
+            add_wallet_record_with_expiry(setup.wallet_handle, TYPE, ID, VALUE, None, 1).unwrap();
+            add_wallet_record_with_expiry(setup.wallet_handle, TYPE_2, ID_2, VALUE_2, None, 1).unwrap();
+            add_wallet_record(setup.wallet_handle, TYPE, ID_3, VALUE_3, None).unwrap();
+
+            assert_eq!(2, purge_expired_wallet_records(setup.wallet_handle).unwrap());
+            assert_eq!(0, purge_expired_wallet_records(setup.wallet_handle).unwrap());
+
+            get_wallet_record(setup.wallet_handle, TYPE, ID_3, OPTIONS_EMPTY).unwrap();
+        }
+    }
+
     mod update_record_value {
         use super::*;
 
@@ -232,6 +294,103 @@ mod high_cases {
         }
     }
 
+    mod batch {
+        use super::*;
+
+        #[test]
+        fn indy_wallet_batch_works() {
+            let setup = Setup::wallet();
+
+            add_wallet_record(setup.wallet_handle, TYPE, ID, VALUE, None).unwrap();
+
+            let operations = json!([
+                {"op": "add", "type": TYPE, "id": ID_2, "value": VALUE_2, "tags": {"tagName1": "str1"}},
+                {"op": "delete", "type": TYPE, "id": ID}
+            ]).to_string();
+            batch_wallet_records(setup.wallet_handle, &operations).unwrap();
+
+            get_wallet_record(setup.wallet_handle, TYPE, ID_2, OPTIONS_EMPTY).unwrap();
+
+            let res = get_wallet_record(setup.wallet_handle, TYPE, ID, OPTIONS_EMPTY);
+            assert_code!(ErrorCode::WalletItemNotFound, res);
+        }
+
+        #[test]
+        fn indy_wallet_batch_works_for_rollback() {
+            let setup = Setup::wallet();
+
+            add_wallet_record(setup.wallet_handle, TYPE, ID, VALUE, None).unwrap();
+
+            let operations = json!([
+                {"op": "delete", "type": TYPE, "id": ID},
+                {"op": "updateValue", "type": TYPE, "id": ID_2, "value": VALUE_2}
+            ]).to_string();
+            let res = batch_wallet_records(setup.wallet_handle, &operations);
+            assert_code!(ErrorCode::WalletItemNotFound, res);
+
+            get_wallet_record(setup.wallet_handle, TYPE, ID, OPTIONS_EMPTY).unwrap();
+        }
+
+        #[test]
+        fn indy_wallet_batch_works_for_forbidden_type() {
+            let setup = Setup::wallet();
+
+            let operations = json!([
+                {"op": "add", "type": FORBIDDEN_TYPE, "id": ID, "value": VALUE}
+            ]).to_string();
+            let res = batch_wallet_records(setup.wallet_handle, &operations);
+            assert_code!(ErrorCode::WalletAccessFailed, res);
+        }
+
+        #[test]
+        fn indy_wallet_staged_batch_works() {
+            let setup = Setup::wallet();
+
+            add_wallet_record(setup.wallet_handle, TYPE, ID, VALUE, None).unwrap();
+
+            let batch_handle = begin_wallet_batch(setup.wallet_handle).unwrap();
+            stage_wallet_batch(batch_handle, &json!([{"op": "add", "type": TYPE, "id": ID_2, "value": VALUE_2}]).to_string()).unwrap();
+            stage_wallet_batch(batch_handle, &json!([{"op": "delete", "type": TYPE, "id": ID}]).to_string()).unwrap();
+
+            let res = get_wallet_record(setup.wallet_handle, TYPE, ID_2, OPTIONS_EMPTY);
+            assert_code!(ErrorCode::WalletItemNotFound, res);
+
+            commit_wallet_batch(batch_handle).unwrap();
+
+            get_wallet_record(setup.wallet_handle, TYPE, ID_2, OPTIONS_EMPTY).unwrap();
+
+            let res = get_wallet_record(setup.wallet_handle, TYPE, ID, OPTIONS_EMPTY);
+            assert_code!(ErrorCode::WalletItemNotFound, res);
+        }
+
+        #[test]
+        fn indy_wallet_staged_batch_works_for_rollback() {
+            let setup = Setup::wallet();
+
+            let batch_handle = begin_wallet_batch(setup.wallet_handle).unwrap();
+            stage_wallet_batch(batch_handle, &json!([{"op": "add", "type": TYPE, "id": ID, "value": VALUE}]).to_string()).unwrap();
+            rollback_wallet_batch(batch_handle).unwrap();
+
+            let res = commit_wallet_batch(batch_handle);
+            assert_code!(ErrorCode::WalletInvalidHandle, res);
+
+            let res = get_wallet_record(setup.wallet_handle, TYPE, ID, OPTIONS_EMPTY);
+            assert_code!(ErrorCode::WalletItemNotFound, res);
+        }
+
+        #[test]
+        fn indy_wallet_staged_batch_works_for_forbidden_type() {
+            let setup = Setup::wallet();
+
+            let batch_handle = begin_wallet_batch(setup.wallet_handle).unwrap();
+
+            let res = stage_wallet_batch(batch_handle, &json!([{"op": "add", "type": FORBIDDEN_TYPE, "id": ID, "value": VALUE}]).to_string());
+            assert_code!(ErrorCode::WalletAccessFailed, res);
+
+            rollback_wallet_batch(batch_handle).unwrap();
+        }
+    }
+
     mod get_record {
         use super::*;
 
@@ -591,6 +750,85 @@ mod high_cases {
                 cleanup_wallet("indy_wallet_search_for_or_query");
             }
 
+            #[test]
+            fn indy_wallet_search_for_numeric_lt_query() {
+                const SEARCH_WALLET_CONFIG: &str = r#"{"id":"indy_wallet_search_for_numeric_lt_query"}"#;
+                let wallet_handle = setup("indy_wallet_search_for_numeric_lt_query", SEARCH_WALLET_CONFIG);
+
+                let query_json = r#"{
+                    "~tagName3": {"$lt": 10}
+                }"#;
+
+                let search_handle = open_wallet_search(wallet_handle, TYPE, query_json, OPTIONS_FULL).unwrap();
+
+                let search_records = fetch_wallet_search_next_records(wallet_handle, search_handle, 5).unwrap();
+
+                check_search_records(&search_records, vec![record_1(),
+                                                           record_2(),
+                                                           record_4(),
+                                                           record_5()]);
+
+                tear_down(wallet_handle, search_handle);
+                cleanup_wallet("indy_wallet_search_for_numeric_lt_query");
+            }
+
+            #[test]
+            fn indy_wallet_search_for_numeric_gte_query() {
+                const SEARCH_WALLET_CONFIG: &str = r#"{"id":"indy_wallet_search_for_numeric_gte_query"}"#;
+                let wallet_handle = setup("indy_wallet_search_for_numeric_gte_query", SEARCH_WALLET_CONFIG);
+
+                let query_json = r#"{
+                    "~tagName2": {"$gte": 4.5}
+                }"#;
+
+                let search_handle = open_wallet_search(wallet_handle, TYPE, query_json, OPTIONS_FULL).unwrap();
+
+                let search_records = fetch_wallet_search_next_records(wallet_handle, search_handle, 5).unwrap();
+
+                check_search_records(&search_records, vec![record_1()]);
+
+                tear_down(wallet_handle, search_handle);
+                cleanup_wallet("indy_wallet_search_for_numeric_gte_query");
+            }
+
+            #[test]
+            fn indy_wallet_search_for_exist_query() {
+                const SEARCH_WALLET_CONFIG: &str = r#"{"id":"indy_wallet_search_for_exist_query"}"#;
+                let wallet_handle = setup("indy_wallet_search_for_exist_query", SEARCH_WALLET_CONFIG);
+
+                let query_json = r#"{
+                    "tagName2": {"$exist": true}
+                }"#;
+
+                let search_handle = open_wallet_search(wallet_handle, TYPE, query_json, OPTIONS_FULL).unwrap();
+
+                let search_records = fetch_wallet_search_next_records(wallet_handle, search_handle, 5).unwrap();
+
+                check_search_records(&search_records, vec![record_3()]);
+
+                tear_down(wallet_handle, search_handle);
+                cleanup_wallet("indy_wallet_search_for_exist_query");
+            }
+
+            #[test]
+            fn indy_wallet_search_for_not_exist_query() {
+                const SEARCH_WALLET_CONFIG: &str = r#"{"id":"indy_wallet_search_for_not_exist_query"}"#;
+                let wallet_handle = setup("indy_wallet_search_for_not_exist_query", SEARCH_WALLET_CONFIG);
+
+                let query_json = r#"{
+                    "~tagName3": {"$exist": false}
+                }"#;
+
+                let search_handle = open_wallet_search(wallet_handle, TYPE, query_json, OPTIONS_FULL).unwrap();
+
+                let search_records = fetch_wallet_search_next_records(wallet_handle, search_handle, 5).unwrap();
+
+                check_search_records(&search_records, vec![record_3()]);
+
+                tear_down(wallet_handle, search_handle);
+                cleanup_wallet("indy_wallet_search_for_not_exist_query");
+            }
+
             #[test]
             fn indy_wallet_search_for_not_query() {
                 const SEARCH_WALLET_CONFIG: &str = r#"{"id":"indy_wallet_search_for_not_query"}"#;
@@ -855,6 +1093,61 @@ mod high_cases {
             }
         }
     }
+
+    mod count_records_by_tag {
+        use super::*;
+
+        #[test]
+        fn indy_wallet_count_records_by_tag_works() {
+            const WALLET_CONFIG: &str = r#"{"id":"indy_wallet_count_records_by_tag_works"}"#;
+            init_non_secret_test_wallet("indy_wallet_count_records_by_tag_works", WALLET_CONFIG);
+            let wallet_handle = wallet::open_wallet(WALLET_CONFIG, WALLET_CREDENTIALS).unwrap();
+
+            let counts_json = count_wallet_records_by_tag(wallet_handle, TYPE, QUERY_EMPTY, "tagName1").unwrap();
+            let counts: HashMap<String, usize> = serde_json::from_str(&counts_json).unwrap();
+            let expected_counts: HashMap<String, usize> = serde_json::from_str(r#"{"prefix_str2":1,"somestr":1,"str1":2,"str2":1}"#).unwrap();
+            assert_eq!(expected_counts, counts);
+
+            let counts_json = count_wallet_records_by_tag(wallet_handle, TYPE, r#"{"tagName1":"str1"}"#, "~tagName2").unwrap();
+            let counts: HashMap<String, usize> = serde_json::from_str(&counts_json).unwrap();
+            let expected_counts: HashMap<String, usize> = serde_json::from_str(r#"{"5":1}"#).unwrap();
+            assert_eq!(expected_counts, counts);
+
+            wallet::close_wallet(wallet_handle).unwrap();
+            cleanup_wallet("indy_wallet_count_records_by_tag_works");
+        }
+
+        #[test]
+        fn indy_wallet_get_distinct_tag_values_works() {
+            const WALLET_CONFIG: &str = r#"{"id":"indy_wallet_get_distinct_tag_values_works"}"#;
+            init_non_secret_test_wallet("indy_wallet_get_distinct_tag_values_works", WALLET_CONFIG);
+            let wallet_handle = wallet::open_wallet(WALLET_CONFIG, WALLET_CREDENTIALS).unwrap();
+
+            let values_json = get_wallet_distinct_tag_values(wallet_handle, TYPE, QUERY_EMPTY, "~tagName2").unwrap();
+            let values: Vec<String> = serde_json::from_str(&values_json).unwrap();
+            assert_eq!(vec!["4", "5", "pre_str3", "str3"], values);
+
+            let values_json = get_wallet_distinct_tag_values(wallet_handle, TYPE, QUERY_EMPTY, "tagName4").unwrap();
+            let values: Vec<String> = serde_json::from_str(&values_json).unwrap();
+            assert!(values.is_empty());
+
+            wallet::close_wallet(wallet_handle).unwrap();
+            cleanup_wallet("indy_wallet_get_distinct_tag_values_works");
+        }
+
+        #[test]
+        fn indy_wallet_count_records_by_tag_works_for_plugged_wallet() {
+            let setup = Setup::plugged_wallet();
+
+            add_wallet_record(setup.wallet_handle, TYPE, ID, VALUE, Some(TAGS)).unwrap();
+            add_wallet_record(setup.wallet_handle, TYPE, ID_2, VALUE_2, Some(TAGS_3)).unwrap();
+
+            let counts_json = count_wallet_records_by_tag(setup.wallet_handle, TYPE, QUERY_EMPTY, "tagName1").unwrap();
+            let counts: HashMap<String, usize> = serde_json::from_str(&counts_json).unwrap();
+            let expected_counts: HashMap<String, usize> = serde_json::from_str(r#"{"str1":2}"#).unwrap();
+            assert_eq!(expected_counts, counts);
+        }
+    }
 }
 
 #[cfg(not(feature = "only_high_cases"))]
@@ -928,6 +1221,38 @@ mod medium_cases {
         }
     }
 
+    mod purge_expired_records {
+        use super::*;
+
+        #[test]
+        fn indy_purge_expired_wallet_records_works_for_invalid_handle() {
+            Setup::empty();
+
+            let res = purge_expired_wallet_records(INVALID_WALLET_HANDLE);
+            assert_code!(ErrorCode::WalletInvalidHandle, res);
+        }
+    }
+
+    mod count_records_by_tag {
+        use super::*;
+
+        #[test]
+        fn indy_wallet_count_records_by_tag_works_for_invalid_handle() {
+            Setup::empty();
+
+            let res = count_wallet_records_by_tag(INVALID_WALLET_HANDLE, TYPE, QUERY_EMPTY, "tagName1");
+            assert_code!(ErrorCode::WalletInvalidHandle, res);
+        }
+
+        #[test]
+        fn indy_wallet_count_records_by_tag_works_for_invalid_query() {
+            let setup = Setup::wallet();
+
+            let res = count_wallet_records_by_tag(setup.wallet_handle, TYPE, r#"{"tagName1": {"$gt": "str1"}}"#, "tagName2");
+            assert_code!(ErrorCode::WalletQueryError, res);
+        }
+    }
+
     mod update_record_value {
         use super::*;
 
@@ -1234,6 +1559,18 @@ mod medium_cases {
             cleanup_wallet("indy_wallet_search_for_invalid_type");
         }
 
+        #[test]
+        fn indy_wallet_search_for_numeric_query_on_encrypted_tag() {
+            const SEARCH_WALLET_CONFIG: &str = r#"{"id":"indy_wallet_search_for_numeric_query_on_encrypted_tag"}"#;
+            let wallet_handle = setup("indy_wallet_search_for_numeric_query_on_encrypted_tag", SEARCH_WALLET_CONFIG);
+
+            let res = open_wallet_search(wallet_handle, TYPE, r#"{"tagName3": {"$gt": 1}}"#, OPTIONS_EMPTY);
+            assert_code!(ErrorCode::WalletQueryError, res);
+
+            wallet::close_wallet(wallet_handle).unwrap();
+            cleanup_wallet("indy_wallet_search_for_numeric_query_on_encrypted_tag");
+        }
+
         #[test]
         fn indy_close_wallet_search_works_for_invalid_handle() {
             const SEARCH_WALLET_CONFIG: &str = r#"{"id":"indy_close_wallet_search_works_for_invalid_handle"}"#;
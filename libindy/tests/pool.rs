@@ -121,6 +121,23 @@ mod high_cases {
         }
     }
 
+    mod get_status {
+        use super::*;
+
+        #[test]
+        #[cfg(feature = "local_nodes_pool")]
+        fn indy_get_pool_status_works() {
+            let setup = Setup::pool();
+
+            let status = pool::get_status(setup.pool_handle).unwrap();
+            let status: serde_json::Value = serde_json::from_str(&status).unwrap();
+
+            let nodes = status["nodes"].as_array().unwrap();
+            assert_eq!(4, nodes.len());
+            assert!(nodes.iter().all(|node| node["blacklisted"] == json!(false)));
+        }
+    }
+
     mod close {
         use super::*;
 
@@ -173,6 +190,59 @@ mod high_cases {
             pool::set_protocol_version(2).unwrap();
         }
     }
+
+    mod transport {
+        extern crate libc;
+
+        use super::*;
+
+        use std::ffi::CStr;
+        use std::thread;
+
+        use self::libc::c_char;
+        use crate::api::PoolHandle;
+
+        #[test]
+        fn open_pool_ledger_works_for_loopback_transport() {
+            let mut setup = Setup::empty();
+
+            let txn_file_path = pool::create_genesis_txn_file_for_test_pool(&setup.name, None, None);
+            let pool_config = pool::pool_config_json(txn_file_path.as_path());
+            pool::create_pool_ledger_config(&setup.name, Some(pool_config.as_str())).unwrap();
+
+            setup.pool_handle = pool::open_pool_ledger(&setup.name, Some(r#"{"transport": "loopback"}"#)).unwrap();
+
+            let status = pool::get_status(setup.pool_handle).unwrap();
+            let status: serde_json::Value = serde_json::from_str(&status).unwrap();
+            assert_eq!(4, status["nodes"].as_array().unwrap().len());
+        }
+
+        #[test]
+        fn open_pool_ledger_works_for_custom_transport() {
+            extern fn echo_send(pool_handle: PoolHandle, node_alias: *const c_char, _node_address: *const c_char, message: *const c_char) -> i32 {
+                let node_alias = unsafe { CStr::from_ptr(node_alias) }.to_str().unwrap().to_string();
+                let message = unsafe { CStr::from_ptr(message) }.to_str().unwrap().to_string();
+
+                // transport must not block pool worker thread
+                thread::spawn(move || {
+                    let _ = pool::deliver_transport_reply(pool_handle, &node_alias, &message);
+                });
+
+                ErrorCode::Success as i32
+            }
+
+            let mut setup = Setup::empty();
+
+            pool::register_transport("open_pool_ledger_works_for_custom_transport", echo_send).unwrap();
+
+            let txn_file_path = pool::create_genesis_txn_file_for_test_pool(&setup.name, None, None);
+            let pool_config = pool::pool_config_json(txn_file_path.as_path());
+            pool::create_pool_ledger_config(&setup.name, Some(pool_config.as_str())).unwrap();
+
+            let config = json!({"transport": "open_pool_ledger_works_for_custom_transport"}).to_string();
+            setup.pool_handle = pool::open_pool_ledger(&setup.name, Some(&config)).unwrap();
+        }
+    }
 }
 
 #[cfg(not(feature = "only_high_cases"))]
@@ -477,6 +547,18 @@ mod medium_cases {
         }
     }
 
+    mod get_status {
+        use super::*;
+
+        #[test]
+        fn indy_get_pool_status_works_for_invalid_handle() {
+            Setup::empty();
+
+            let res = pool::get_status(0);
+            assert_code!(ErrorCode::PoolLedgerInvalidPoolHandle, res);
+        }
+    }
+
     mod set_protocol_version {
         use super::*;
 
@@ -486,4 +568,69 @@ mod medium_cases {
             assert_code!(ErrorCode::PoolIncompatibleProtocolVersion, res);
         }
     }
+
+    mod transport {
+        extern crate libc;
+
+        use super::*;
+
+        use self::libc::c_char;
+        use crate::api::PoolHandle;
+
+        extern fn failed_send(_pool_handle: PoolHandle, _node_alias: *const c_char, _node_address: *const c_char, _message: *const c_char) -> i32 {
+            ErrorCode::CommonIOError as i32
+        }
+
+        #[test]
+        fn open_pool_ledger_works_for_unknown_transport() {
+            let setup = Setup::empty();
+
+            let txn_file_path = pool::create_genesis_txn_file_for_test_pool(&setup.name, None, None);
+            let pool_config = pool::pool_config_json(txn_file_path.as_path());
+            pool::create_pool_ledger_config(&setup.name, Some(pool_config.as_str())).unwrap();
+
+            let res = pool::open_pool_ledger(&setup.name, Some(r#"{"transport": "unknown_transport"}"#));
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
+
+        #[test]
+        fn open_pool_ledger_works_for_failed_transport() {
+            let setup = Setup::empty();
+
+            pool::register_transport("open_pool_ledger_works_for_failed_transport", failed_send).unwrap();
+
+            let txn_file_path = pool::create_genesis_txn_file_for_test_pool(&setup.name, None, None);
+            let pool_config = pool::pool_config_json(txn_file_path.as_path());
+            pool::create_pool_ledger_config(&setup.name, Some(pool_config.as_str())).unwrap();
+
+            let config = json!({"transport": "open_pool_ledger_works_for_failed_transport", "timeout": 1}).to_string();
+            let res = pool::open_pool_ledger(&setup.name, Some(&config));
+            assert_code!(ErrorCode::PoolLedgerTimeout, res);
+        }
+
+        #[test]
+        fn indy_register_pool_transport_works_for_loopback() {
+            Setup::empty();
+
+            let res = pool::register_transport("loopback", failed_send);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
+
+        #[test]
+        fn indy_deliver_pool_transport_reply_works_for_invalid_handle() {
+            Setup::empty();
+
+            let res = pool::deliver_transport_reply(0, "Node1", "{}");
+            assert_code!(ErrorCode::PoolLedgerInvalidPoolHandle, res);
+        }
+
+        #[test]
+        #[cfg(feature = "local_nodes_pool")]
+        fn indy_deliver_pool_transport_reply_works_for_zmq_pool() {
+            let setup = Setup::pool();
+
+            let res = pool::deliver_transport_reply(setup.pool_handle, "Node1", "{}");
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
+    }
 }
@@ -0,0 +1,448 @@
+extern crate indyrs as indy;
+extern crate indyrs as api;
+
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::utils::{anoncreds, blob_storage, did, ledger, pool, pool_simulator, wallet};
+use crate::utils::constants::*;
+use crate::utils::domain::anoncreds::schema::SchemaV1;
+use crate::utils::Setup;
+use crate::utils::types::ResponseType;
+
+use self::indy::ErrorCode;
+
+use crate::api::PoolHandle;
+#[macro_use]
+mod utils;
+
+inject_indy_dependencies!();
+
+fn single_read_node_setup() -> Setup {
+    let mut setup = Setup::wallet();
+    setup.pool_handle = pool_simulator::create_and_open_pool_ledger(&setup.name, Some(r#"{"number_read_nodes": 1}"#)).unwrap();
+    let (did, verkey) = did::create_and_store_my_did(setup.wallet_handle, Some(TRUSTEE_SEED)).unwrap();
+    setup.did = did;
+    setup.verkey = verkey;
+    setup
+}
+
+// Returns count of replies and invalid state proofs of all nodes
+fn pool_stats(pool_handle: PoolHandle) -> (u64, u64) {
+    let status: serde_json::Value = serde_json::from_str(&pool::get_status(pool_handle).unwrap()).unwrap();
+
+    status["nodes"].as_array().unwrap().iter()
+        .fold((0, 0), |(replies, invalid_state_proofs), node| {
+            (replies + node["replies"].as_u64().unwrap(), invalid_state_proofs + node["invalid_state_proofs"].as_u64().unwrap())
+        })
+}
+
+fn txn_time(response: &str) -> u64 {
+    let response: serde_json::Value = serde_json::from_str(response).unwrap();
+    response["result"]["txnMetadata"]["txnTime"].as_u64().unwrap()
+}
+
+fn wait_next_second(time: u64) {
+    while SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() <= time {
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+fn publish_schema(setup: &Setup, issuer_did: &str) -> (String, String) {
+    let (schema_id, schema_json) = anoncreds::issuer_create_schema(issuer_did, GVT_SCHEMA_NAME, SCHEMA_VERSION, GVT_SCHEMA_ATTRIBUTES).unwrap();
+
+    let schema_request = ledger::build_schema_request(issuer_did, &schema_json).unwrap();
+    let schema_response = ledger::sign_and_submit_request(setup.pool_handle, setup.wallet_handle, issuer_did, &schema_request).unwrap();
+    pool::check_response_type(&schema_response, ResponseType::REPLY);
+
+    let get_schema_request = ledger::build_get_schema_request(Some(issuer_did), &schema_id).unwrap();
+    let get_schema_response = ledger::submit_request(setup.pool_handle, &get_schema_request).unwrap();
+    ledger::parse_get_schema_response(&get_schema_response).unwrap()
+}
+
+fn publish_cred_def(setup: &Setup, issuer_did: &str) -> (String, String) {
+    let (_, schema_json) = publish_schema(setup, issuer_did);
+
+    let (cred_def_id, cred_def_json) = anoncreds::issuer_create_credential_definition(setup.wallet_handle,
+                                                                                      issuer_did,
+                                                                                      &schema_json,
+                                                                                      TAG_1,
+                                                                                      None,
+                                                                                      Some(&anoncreds::revocation_cred_def_config())).unwrap();
+
+    let cred_def_request = ledger::build_cred_def_txn(issuer_did, &cred_def_json).unwrap();
+    let cred_def_response = ledger::sign_and_submit_request(setup.pool_handle, setup.wallet_handle, issuer_did, &cred_def_request).unwrap();
+    pool::check_response_type(&cred_def_response, ResponseType::REPLY);
+
+    (cred_def_id, cred_def_json)
+}
+
+fn publish_revoc_reg_def(setup: &Setup, issuer_did: &str) -> (String, String) {
+    let (cred_def_id, _) = publish_cred_def(setup, issuer_did);
+
+    let tails_writer_handle = blob_storage::open_writer("default", &anoncreds::tails_writer_config()).unwrap();
+
+    let (rev_reg_id, rev_reg_def_json, rev_reg_entry_json) =
+        anoncreds::issuer_create_and_store_revoc_reg(setup.wallet_handle,
+                                                     issuer_did,
+                                                     None,
+                                                     TAG_1,
+                                                     &cred_def_id,
+                                                     &anoncreds::issuance_by_default_rev_reg_config(),
+                                                     tails_writer_handle).unwrap();
+
+    let rev_reg_def_request = ledger::build_revoc_reg_def_request(issuer_did, &rev_reg_def_json).unwrap();
+    let rev_reg_def_response = ledger::sign_and_submit_request(setup.pool_handle, setup.wallet_handle, issuer_did, &rev_reg_def_request).unwrap();
+    pool::check_response_type(&rev_reg_def_response, ResponseType::REPLY);
+
+    (rev_reg_id, rev_reg_entry_json)
+}
+
+fn publish_revoc_reg_entry(setup: &Setup, issuer_did: &str, rev_reg_id: &str, rev_reg_entry_json: &str) -> u64 {
+    let rev_reg_entry_request = ledger::build_revoc_reg_entry_request(issuer_did, rev_reg_id, REVOC_REG_TYPE, rev_reg_entry_json).unwrap();
+    let rev_reg_entry_response = ledger::sign_and_submit_request(setup.pool_handle, setup.wallet_handle, issuer_did, &rev_reg_entry_request).unwrap();
+    pool::check_response_type(&rev_reg_entry_response, ResponseType::REPLY);
+
+    txn_time(&rev_reg_entry_response)
+}
+
+mod high_cases {
+    use super::*;
+
+    mod open_pool_ledger {
+        use super::*;
+
+        #[test]
+        fn open_simulated_pool_ledger_works() {
+            let setup = Setup::wallet_and_simulated_pool();
+
+            let status: serde_json::Value = serde_json::from_str(&pool::get_status(setup.pool_handle).unwrap()).unwrap();
+            assert_eq!(4, status["nodes"].as_array().unwrap().len());
+        }
+    }
+
+    mod nym {
+        use super::*;
+
+        #[test]
+        fn nym_requests_work_for_simulated_pool() {
+            let setup = Setup::simulated_trustee();
+
+            let (did, verkey) = did::create_and_store_my_did(setup.wallet_handle, None).unwrap();
+
+            let nym_request = ledger::build_nym_request(&setup.did, &did, Some(&verkey), None, Some("ENDORSER")).unwrap();
+            let nym_response = ledger::sign_and_submit_request(setup.pool_handle, setup.wallet_handle, &setup.did, &nym_request).unwrap();
+            pool::check_response_type(&nym_response, ResponseType::REPLY);
+
+            let get_nym_request = ledger::build_get_nym_request(Some(&setup.did), &did).unwrap();
+            let get_nym_response = ledger::submit_request(setup.pool_handle, &get_nym_request).unwrap();
+
+            let nym: serde_json::Value = serde_json::from_str(&ledger::parse_get_nym_response(&get_nym_response).unwrap()).unwrap();
+            assert_eq!(json!(did), nym["did"]);
+            assert_eq!(json!(verkey), nym["verkey"]);
+            assert_eq!(json!("101"), nym["role"]);
+        }
+
+        #[test]
+        fn key_for_did_works_for_simulated_pool() {
+            let setup = Setup::simulated_trustee();
+
+            let (did, verkey) = did::create_store_and_publish_did(setup.wallet_handle, setup.pool_handle, "ENDORSER", None).unwrap();
+
+            let (wallet_handle, wallet_config) = wallet::create_and_open_default_wallet(&format!("{}_other", setup.name)).unwrap();
+            let received_verkey = did::key_for_did(setup.pool_handle, wallet_handle, &did).unwrap();
+            assert_eq!(verkey, received_verkey);
+
+            wallet::close_and_delete_wallet(wallet_handle, &wallet_config).unwrap();
+        }
+    }
+
+    mod attrib {
+        use super::*;
+
+        #[test]
+        fn attrib_requests_work_for_simulated_pool() {
+            let setup = Setup::simulated_trustee();
+
+            let (did, _) = did::create_store_and_publish_did(setup.wallet_handle, setup.pool_handle, "ENDORSER", None).unwrap();
+
+            let attrib_request = ledger::build_attrib_request(&did, &did, None, Some(ATTRIB_RAW_DATA), None).unwrap();
+            let attrib_response = ledger::sign_and_submit_request(setup.pool_handle, setup.wallet_handle, &did, &attrib_request).unwrap();
+            pool::check_response_type(&attrib_response, ResponseType::REPLY);
+
+            let get_attrib_request = ledger::build_get_attrib_request(Some(&did), &did, Some("endpoint"), None, None).unwrap();
+            let get_attrib_response = ledger::submit_request(setup.pool_handle, &get_attrib_request).unwrap();
+
+            let get_attrib_response: serde_json::Value = serde_json::from_str(&get_attrib_response).unwrap();
+            assert_eq!(json!(ATTRIB_RAW_DATA), get_attrib_response["result"]["data"]);
+        }
+    }
+
+    mod schema {
+        use super::*;
+
+        #[test]
+        fn schema_requests_work_for_simulated_pool() {
+            let setup = Setup::simulated_trustee();
+
+            let (issuer_did, _) = did::create_store_and_publish_did(setup.wallet_handle, setup.pool_handle, "ENDORSER", None).unwrap();
+
+            let (schema_id, schema_json) = publish_schema(&setup, &issuer_did);
+
+            let schema: SchemaV1 = serde_json::from_str(&schema_json).unwrap();
+            assert_eq!(schema_id, schema.id.0);
+            assert_eq!(GVT_SCHEMA_NAME, schema.name);
+            assert!(schema.seq_no.is_some());
+        }
+    }
+
+    mod cred_def {
+        use super::*;
+
+        #[test]
+        fn cred_def_requests_work_for_simulated_pool() {
+            let setup = Setup::simulated_trustee();
+
+            let (issuer_did, _) = did::create_store_and_publish_did(setup.wallet_handle, setup.pool_handle, "ENDORSER", None).unwrap();
+
+            let (cred_def_id, _) = publish_cred_def(&setup, &issuer_did);
+
+            let get_cred_def_request = ledger::build_get_cred_def_request(Some(&issuer_did), &cred_def_id).unwrap();
+            let get_cred_def_response = ledger::submit_request(setup.pool_handle, &get_cred_def_request).unwrap();
+            let (received_cred_def_id, _) = ledger::parse_get_cred_def_response(&get_cred_def_response).unwrap();
+            assert_eq!(cred_def_id, received_cred_def_id);
+        }
+    }
+
+    mod revocation {
+        use super::*;
+
+        #[test]
+        fn revoc_reg_requests_work_for_simulated_pool() {
+            let setup = Setup::simulated_trustee();
+
+            let (issuer_did, _) = did::create_store_and_publish_did(setup.wallet_handle, setup.pool_handle, "ENDORSER", None).unwrap();
+
+            let (rev_reg_id, rev_reg_entry_json) = publish_revoc_reg_def(&setup, &issuer_did);
+            let issued_time = publish_revoc_reg_entry(&setup, &issuer_did, &rev_reg_id, &rev_reg_entry_json);
+
+            wait_next_second(issued_time);
+
+            let blob_storage_reader_handle = blob_storage::open_reader("default", &anoncreds::tails_writer_config()).unwrap();
+            let rev_reg_delta_json = anoncreds::issuer_revoke_credential(setup.wallet_handle, blob_storage_reader_handle, &rev_reg_id, "1").unwrap();
+            let revoked_time = publish_revoc_reg_entry(&setup, &issuer_did, &rev_reg_id, &rev_reg_delta_json);
+
+            let get_rev_reg_def_request = ledger::build_get_revoc_reg_def_request(Some(&issuer_did), &rev_reg_id).unwrap();
+            let get_rev_reg_def_response = ledger::submit_request(setup.pool_handle, &get_rev_reg_def_request).unwrap();
+            let (received_rev_reg_id, _) = ledger::parse_get_revoc_reg_def_response(&get_rev_reg_def_response).unwrap();
+            assert_eq!(rev_reg_id, received_rev_reg_id);
+
+            let get_rev_reg_request = ledger::build_get_revoc_reg_request(Some(&issuer_did), &rev_reg_id, issued_time).unwrap();
+            let get_rev_reg_response = ledger::submit_request(setup.pool_handle, &get_rev_reg_request).unwrap();
+            let (_, _, timestamp) = ledger::parse_get_revoc_reg_response(&get_rev_reg_response).unwrap();
+            assert_eq!(issued_time, timestamp);
+
+            let get_rev_reg_request = ledger::build_get_revoc_reg_request(Some(&issuer_did), &rev_reg_id, revoked_time).unwrap();
+            let get_rev_reg_response = ledger::submit_request(setup.pool_handle, &get_rev_reg_request).unwrap();
+            let (_, _, timestamp) = ledger::parse_get_revoc_reg_response(&get_rev_reg_response).unwrap();
+            assert_eq!(revoked_time, timestamp);
+
+            for from in &[None, Some(issued_time)] {
+                let get_rev_reg_delta_request = ledger::build_get_revoc_reg_delta_request(Some(&issuer_did), &rev_reg_id, *from, revoked_time).unwrap();
+                let get_rev_reg_delta_response = ledger::submit_request(setup.pool_handle, &get_rev_reg_delta_request).unwrap();
+                let (_, rev_reg_delta_json, timestamp) = ledger::parse_get_revoc_reg_delta_response(&get_rev_reg_delta_response).unwrap();
+                assert_eq!(revoked_time, timestamp);
+
+                let rev_reg_delta: serde_json::Value = serde_json::from_str(&rev_reg_delta_json).unwrap();
+                assert_eq!(json!([1]), rev_reg_delta["value"]["revoked"]);
+            }
+        }
+    }
+
+    mod state_proofs {
+        use super::*;
+
+        #[test]
+        fn get_nym_works_for_single_read_node() {
+            let setup = single_read_node_setup();
+
+            let (replies, _) = pool_stats(setup.pool_handle);
+
+            let get_nym_request = ledger::build_get_nym_request(Some(&setup.did), &setup.did).unwrap();
+            let get_nym_response = ledger::submit_request(setup.pool_handle, &get_nym_request).unwrap();
+            ledger::parse_get_nym_response(&get_nym_response).unwrap();
+
+            // single reply is accepted only if its state proof is valid
+            assert_eq!((replies + 1, 0), pool_stats(setup.pool_handle));
+        }
+
+        #[test]
+        fn get_attrib_works_for_single_read_node() {
+            let setup = single_read_node_setup();
+
+            let (did, _) = did::create_store_and_publish_did(setup.wallet_handle, setup.pool_handle, "ENDORSER", None).unwrap();
+
+            let attrib_request = ledger::build_attrib_request(&did, &did, None, Some(ATTRIB_RAW_DATA), None).unwrap();
+            let attrib_response = ledger::sign_and_submit_request(setup.pool_handle, setup.wallet_handle, &did, &attrib_request).unwrap();
+            pool::check_response_type(&attrib_response, ResponseType::REPLY);
+
+            let (replies, _) = pool_stats(setup.pool_handle);
+
+            let get_attrib_request = ledger::build_get_attrib_request(Some(&did), &did, Some("endpoint"), None, None).unwrap();
+            ledger::submit_request(setup.pool_handle, &get_attrib_request).unwrap();
+
+            assert_eq!((replies + 1, 0), pool_stats(setup.pool_handle));
+        }
+    }
+}
+
+#[cfg(not(feature = "only_high_cases"))]
+mod medium_cases {
+    use super::*;
+
+    mod nym {
+        use super::*;
+
+        #[test]
+        fn nym_request_works_for_unknown_submitter() {
+            let setup = Setup::wallet_and_simulated_pool();
+
+            let (did, verkey) = did::create_and_store_my_did(setup.wallet_handle, None).unwrap();
+
+            let nym_request = ledger::build_nym_request(&did, &did, Some(&verkey), None, None).unwrap();
+            let nym_response = ledger::sign_and_submit_request(setup.pool_handle, setup.wallet_handle, &did, &nym_request).unwrap();
+            pool::check_response_type(&nym_response, ResponseType::REQNACK);
+        }
+
+        #[test]
+        fn nym_request_works_without_signature() {
+            let setup = Setup::simulated_trustee();
+
+            let (did, verkey) = did::create_and_store_my_did(setup.wallet_handle, None).unwrap();
+
+            let nym_request = ledger::build_nym_request(&setup.did, &did, Some(&verkey), None, None).unwrap();
+            let nym_response = ledger::submit_request(setup.pool_handle, &nym_request).unwrap();
+            pool::check_response_type(&nym_response, ResponseType::REQNACK);
+        }
+
+        #[test]
+        fn nym_request_works_for_submitter_without_role() {
+            let setup = Setup::simulated_trustee();
+
+            let (my_did, my_verkey) = did::create_and_store_my_did(setup.wallet_handle, None).unwrap();
+
+            let nym_request = ledger::build_nym_request(&setup.did, &my_did, Some(&my_verkey), None, None).unwrap();
+            let nym_response = ledger::sign_and_submit_request(setup.pool_handle, setup.wallet_handle, &setup.did, &nym_request).unwrap();
+            pool::check_response_type(&nym_response, ResponseType::REPLY);
+
+            let (did, verkey) = did::create_and_store_my_did(setup.wallet_handle, None).unwrap();
+
+            let nym_request = ledger::build_nym_request(&my_did, &did, Some(&verkey), None, None).unwrap();
+            let nym_response = ledger::sign_and_submit_request(setup.pool_handle, setup.wallet_handle, &my_did, &nym_request).unwrap();
+            pool::check_response_type(&nym_response, ResponseType::REJECT);
+        }
+
+        #[test]
+        fn nym_request_works_for_steward_granting_trustee_role() {
+            let setup = Setup::simulated_trustee();
+
+            let (steward_did, _) = did::create_and_store_my_did(setup.wallet_handle, Some(STEWARD_SEED)).unwrap();
+            let (did, verkey) = did::create_and_store_my_did(setup.wallet_handle, None).unwrap();
+
+            let nym_request = ledger::build_nym_request(&steward_did, &did, Some(&verkey), None, Some("TRUSTEE")).unwrap();
+            let nym_response = ledger::sign_and_submit_request(setup.pool_handle, setup.wallet_handle, &steward_did, &nym_request).unwrap();
+            pool::check_response_type(&nym_response, ResponseType::REJECT);
+        }
+
+        #[test]
+        fn get_nym_request_works_for_unknown_did() {
+            let setup = single_read_node_setup();
+
+            let (did, _) = did::create_and_store_my_did(setup.wallet_handle, None).unwrap();
+
+            let (replies, _) = pool_stats(setup.pool_handle);
+
+            let get_nym_request = ledger::build_get_nym_request(Some(&did), &did).unwrap();
+            let get_nym_response = ledger::submit_request(setup.pool_handle, &get_nym_request).unwrap();
+            let res = ledger::parse_get_nym_response(&get_nym_response);
+            assert_code!(ErrorCode::LedgerNotFound, res);
+
+            // absence of the DID is proven by the single node
+            assert_eq!((replies + 1, 0), pool_stats(setup.pool_handle));
+        }
+    }
+
+    mod schema {
+        use super::*;
+
+        #[test]
+        fn schema_request_works_for_submitter_without_role() {
+            let setup = Setup::simulated_trustee();
+
+            let (did, verkey) = did::create_and_store_my_did(setup.wallet_handle, None).unwrap();
+
+            let nym_request = ledger::build_nym_request(&setup.did, &did, Some(&verkey), None, None).unwrap();
+            let nym_response = ledger::sign_and_submit_request(setup.pool_handle, setup.wallet_handle, &setup.did, &nym_request).unwrap();
+            pool::check_response_type(&nym_response, ResponseType::REPLY);
+
+            let (_, schema_json) = anoncreds::issuer_create_schema(&did, GVT_SCHEMA_NAME, SCHEMA_VERSION, GVT_SCHEMA_ATTRIBUTES).unwrap();
+            let schema_request = ledger::build_schema_request(&did, &schema_json).unwrap();
+            let schema_response = ledger::sign_and_submit_request(setup.pool_handle, setup.wallet_handle, &did, &schema_request).unwrap();
+            pool::check_response_type(&schema_response, ResponseType::REJECT);
+        }
+
+        #[test]
+        fn schema_request_works_for_duplicate() {
+            let setup = Setup::simulated_trustee();
+
+            let (issuer_did, _) = did::create_store_and_publish_did(setup.wallet_handle, setup.pool_handle, "ENDORSER", None).unwrap();
+
+            publish_schema(&setup, &issuer_did);
+
+            let (_, schema_json) = anoncreds::issuer_create_schema(&issuer_did, GVT_SCHEMA_NAME, SCHEMA_VERSION, GVT_SCHEMA_ATTRIBUTES).unwrap();
+            let schema_request = ledger::build_schema_request(&issuer_did, &schema_json).unwrap();
+            let schema_response = ledger::sign_and_submit_request(setup.pool_handle, setup.wallet_handle, &issuer_did, &schema_request).unwrap();
+            pool::check_response_type(&schema_response, ResponseType::REJECT);
+        }
+    }
+
+    mod revocation {
+        use super::*;
+
+        #[test]
+        fn get_revoc_reg_request_works_before_first_entry() {
+            let setup = Setup::simulated_trustee();
+
+            let (issuer_did, _) = did::create_store_and_publish_did(setup.wallet_handle, setup.pool_handle, "ENDORSER", None).unwrap();
+
+            let (rev_reg_id, _) = publish_revoc_reg_def(&setup, &issuer_did);
+
+            let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+            let get_rev_reg_request = ledger::build_get_revoc_reg_request(Some(&issuer_did), &rev_reg_id, timestamp).unwrap();
+            let get_rev_reg_response = ledger::submit_request(setup.pool_handle, &get_rev_reg_request).unwrap();
+            let res = ledger::parse_get_revoc_reg_response(&get_rev_reg_response);
+            assert_code!(ErrorCode::LedgerNotFound, res);
+        }
+    }
+
+    mod state_proofs {
+        use super::*;
+
+        #[test]
+        fn get_nym_works_for_invalid_state_proofs() {
+            let setup = Setup::simulated_trustee();
+
+            pool_simulator::corrupt_state_proofs(setup.pool_handle);
+
+            let get_nym_request = ledger::build_get_nym_request(Some(&setup.did), &setup.did).unwrap();
+            let get_nym_response = ledger::submit_request(setup.pool_handle, &get_nym_request).unwrap();
+
+            // data is still accepted when enough nodes return the same
+            let nym: serde_json::Value = serde_json::from_str(&ledger::parse_get_nym_response(&get_nym_response).unwrap()).unwrap();
+            assert_eq!(json!(setup.verkey), nym["verkey"]);
+
+            let (_, invalid_state_proofs) = pool_stats(setup.pool_handle);
+            assert!(invalid_state_proofs > 0);
+        }
+    }
+}
@@ -128,6 +128,18 @@ pub fn prover_search_credentials(wallet_handle: WalletHandle, filter_json: &str)
     anoncreds::prover_search_credentials(wallet_handle, Some(filter_json)).wait()
 }
 
+pub fn prover_count_credentials_by_tag(wallet_handle: WalletHandle, filter_json: &str, tag_name: &str) -> Result<String, IndyError> {
+    anoncreds::prover_count_credentials_by_tag(wallet_handle, Some(filter_json), tag_name).wait()
+}
+
+pub fn prover_get_distinct_credential_tag_values(wallet_handle: WalletHandle, filter_json: &str, tag_name: &str) -> Result<String, IndyError> {
+    anoncreds::prover_get_distinct_credential_tag_values(wallet_handle, Some(filter_json), tag_name).wait()
+}
+
+pub fn prover_search_credentials_with_options(wallet_handle: WalletHandle, filter_json: &str, options_json: &str) -> Result<(i32, usize), IndyError> {
+    anoncreds::prover_search_credentials_with_options(wallet_handle, Some(filter_json), Some(options_json)).wait()
+}
+
 pub fn prover_fetch_credentials(search_handle: i32, count: usize) -> Result<String, IndyError> {
     anoncreds::prover_fetch_credentials(search_handle, count).wait()
 }
@@ -11,6 +11,7 @@ pub mod callback;
 pub mod environment;
 
 pub mod pool;
+pub mod pool_simulator;
 pub mod crypto;
 pub mod did;
 pub mod wallet;
@@ -145,6 +146,13 @@ impl Setup {
         Setup { name, wallet_config, wallet_handle, pool_handle, did: String::new(), verkey: String::new() }
     }
 
+    pub fn wallet_and_simulated_pool() -> Setup {
+        let name = setup();
+        let (wallet_handle, wallet_config) = wallet::create_and_open_default_wallet(&name).unwrap();
+        let pool_handle = pool_simulator::create_and_open_pool_ledger(&name, None).unwrap();
+        Setup { name, wallet_config, wallet_handle, pool_handle, did: String::new(), verkey: String::new() }
+    }
+
     pub fn trustee() -> Setup {
         let mut setup = Setup::wallet_and_pool();
         let (did, verkey) = did::create_and_store_my_did(setup.wallet_handle, Some(constants::TRUSTEE_SEED)).unwrap();
@@ -153,6 +161,14 @@ impl Setup {
         setup
     }
 
+    pub fn simulated_trustee() -> Setup {
+        let mut setup = Setup::wallet_and_simulated_pool();
+        let (did, verkey) = did::create_and_store_my_did(setup.wallet_handle, Some(constants::TRUSTEE_SEED)).unwrap();
+        setup.did = did;
+        setup.verkey = verkey;
+        setup
+    }
+
     pub fn trustee_fully_qualified() -> Setup {
         let mut setup = Setup::wallet_and_pool();
         let (did, verkey) = did::create_and_store_my_did_v1(setup.wallet_handle, Some(constants::TRUSTEE_SEED)).unwrap();
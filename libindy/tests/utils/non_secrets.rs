@@ -12,7 +12,7 @@ use crate::utils::types::WalletRecord;
 use std::sync::Once;
 use std::collections::HashMap;
 
-use indy::WalletHandle;
+use indy::{IndyHandle, WalletHandle};
 
 pub const SEARCH_COMMON_WALLET_CONFIG: &'static str = r#"{"id":"search_common"}"#;
 pub const TYPE: &'static str = "TestType";
@@ -42,6 +42,14 @@ pub fn add_wallet_record(wallet_handle: WalletHandle, type_: &str, id: &str, val
     wallet::add_wallet_record(wallet_handle, type_, id, value, tags_json).wait()
 }
 
+pub fn add_wallet_record_with_expiry(wallet_handle: WalletHandle, type_: &str, id: &str, value: &str, tags_json: Option<&str>, expires_at: u64) -> Result<(), IndyError> {
+    wallet::add_wallet_record_with_expiry(wallet_handle, type_, id, value, tags_json, expires_at).wait()
+}
+
+pub fn purge_expired_wallet_records(wallet_handle: WalletHandle) -> Result<usize, IndyError> {
+    wallet::purge_expired_wallet_records(wallet_handle).wait()
+}
+
 pub fn update_wallet_record_value(wallet_handle: WalletHandle, type_: &str, id: &str, value: &str) -> Result<(), IndyError> {
     wallet::update_wallet_record_value(wallet_handle, type_, id, value).wait()
 }
@@ -62,10 +70,38 @@ pub fn delete_wallet_record(wallet_handle: WalletHandle, type_: &str, id: &str)
     wallet::delete_wallet_record(wallet_handle, type_, id).wait()
 }
 
+pub fn batch_wallet_records(wallet_handle: WalletHandle, operations_json: &str) -> Result<(), IndyError> {
+    wallet::batch_wallet_records(wallet_handle, operations_json).wait()
+}
+
+pub fn begin_wallet_batch(wallet_handle: WalletHandle) -> Result<IndyHandle, IndyError> {
+    wallet::begin_wallet_batch(wallet_handle).wait()
+}
+
+pub fn stage_wallet_batch(batch_handle: IndyHandle, operations_json: &str) -> Result<(), IndyError> {
+    wallet::stage_wallet_batch(batch_handle, operations_json).wait()
+}
+
+pub fn commit_wallet_batch(batch_handle: IndyHandle) -> Result<(), IndyError> {
+    wallet::commit_wallet_batch(batch_handle).wait()
+}
+
+pub fn rollback_wallet_batch(batch_handle: IndyHandle) -> Result<(), IndyError> {
+    wallet::rollback_wallet_batch(batch_handle).wait()
+}
+
 pub fn get_wallet_record(wallet_handle: WalletHandle, type_: &str, id: &str, options_json: &str) -> Result<String, IndyError> {
     wallet::get_wallet_record(wallet_handle, type_, id, options_json).wait()
 }
 
+pub fn count_wallet_records_by_tag(wallet_handle: WalletHandle, type_: &str, query_json: &str, tag_name: &str) -> Result<String, IndyError> {
+    wallet::count_wallet_records_by_tag(wallet_handle, type_, query_json, tag_name).wait()
+}
+
+pub fn get_wallet_distinct_tag_values(wallet_handle: WalletHandle, type_: &str, query_json: &str, tag_name: &str) -> Result<String, IndyError> {
+    wallet::get_wallet_distinct_tag_values(wallet_handle, type_, query_json, tag_name).wait()
+}
+
 pub fn open_wallet_search(wallet_handle: WalletHandle, type_: &str, query_json: &str, options_json: &str) -> Result<i32, IndyError> {
     wallet::open_wallet_search(wallet_handle, type_, query_json, options_json).wait()
 }
@@ -1,8 +1,10 @@
 extern crate byteorder;
 extern crate futures;
+extern crate libc;
 extern crate rmp_serde;
 
 use self::byteorder::{LittleEndian, WriteBytesExt};
+use self::libc::c_char;
 
 use std::fs;
 use std::io::Write;
@@ -114,7 +116,6 @@ pub fn create_pool_ledger_config(pool_name: &str, pool_config: Option<&str>) ->
     pool::create_pool_ledger_config(pool_name, pool_config).wait()
 }
 
-#[cfg(feature = "local_nodes_pool")]
 pub fn open_pool_ledger(pool_name: &str, config: Option<&str>) -> Result<PoolHandle, IndyError> {
     pool::open_pool_ledger(pool_name, config).wait()
 }
@@ -174,6 +175,10 @@ pub fn refresh(pool_handle: PoolHandle) -> Result<(), IndyError> {
     pool::refresh_pool_ledger(pool_handle).wait()
 }
 
+pub fn get_status(pool_handle: PoolHandle) -> Result<String, IndyError> {
+    pool::get_pool_status(pool_handle).wait()
+}
+
 pub fn close(pool_handle: PoolHandle) -> Result<(), IndyError> {
     pool::close_pool_ledger(pool_handle).wait()
 }
@@ -186,6 +191,14 @@ pub fn set_protocol_version(protocol_version: usize) -> Result<(), IndyError> {
     pool::set_protocol_version(protocol_version).wait()
 }
 
+pub fn register_transport(transport_name: &str, send: extern fn(PoolHandle, *const c_char, *const c_char, *const c_char) -> i32) -> Result<(), IndyError> {
+    pool::register_pool_transport(transport_name, send).wait()
+}
+
+pub fn deliver_transport_reply(pool_handle: PoolHandle, node_alias: &str, reply: &str) -> Result<(), IndyError> {
+    pool::deliver_pool_transport_reply(pool_handle, node_alias, reply).wait()
+}
+
 pub fn check_response_type(response: &str, _type: ResponseType) {
     let response: Response = serde_json::from_str(&response).unwrap();
     assert_eq!(response.op, _type);
@@ -0,0 +1,954 @@
+//! In-process simulation of a 4 nodes Indy pool for integration tests without network.
+//!
+//! Pools opened with `create_and_open_pool_ledger` communicate through the registered
+//! "simulator" transport with a single domain ledger shared by all of them. Nodes check
+//! request signatures and basic permissions, apply writes once and answer reads with
+//! state proofs signed by BLS multi-signature of the nodes, so libindy validates replies
+//! the same way as replies of the real pool.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::ffi::CStr;
+use std::sync::{mpsc, Arc, Mutex, Once};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use indy::{ErrorCode, IndyError};
+use indy_utils::crypto::base64;
+use indy_utils::crypto::ed25519_sign::{self, PublicKey, Seed, Signature};
+use indy_utils::crypto::hash::hash;
+use libc::c_char;
+use rlp::RlpStream;
+use rust_base58::{FromBase58, ToBase58};
+use serde_json::Value;
+use sha3::{Digest, Sha3_256};
+use ursa::bls::{Bls, Generator, MultiSignature, SignKey, VerKey};
+
+use crate::utils::{pool, test};
+use crate::utils::constants::{STEWARD_SEED, TRUSTEE_SEED};
+use crate::utils::domain::ledger::constants::*;
+use crate::api::PoolHandle;
+
+pub const SIMULATOR_TRANSPORT: &str = "simulator";
+
+const NODES: [&str; 4] = ["Node1", "Node2", "Node3", "Node4"];
+// Count of faulty nodes tolerated by the pool of 4 nodes
+const F: usize = 1;
+
+// Must be the same as DEFAULT_GENERATOR of libindy to verify multi-signatures
+const GENERATOR: &str = "3LHpUjiyFC2q2hD7MnwwNmVXiuaFbQx2XkAFJWzswCjgN1utjsCeLzHsKk1nJvFEaS4fcrUmVAkdhtPCYbrVyATZcmzwJReTcJqwqBCPTmTQ9uWPwz6rEncKb2pYYYFcdHa8N17HzVyTqKfgPi4X9pMetfT3A5xCHq54R2pDNYWVLDX";
+
+const READ_TYPES: [&str; 8] = [GET_NYM, GET_ATTR, GET_SCHEMA, GET_CRED_DEF, GET_REVOC_REG_DEF, GET_REVOC_REG, GET_REVOC_REG_DELTA, GET_TXN];
+const WRITE_TYPES: [&str; 6] = [NYM, ATTRIB, SCHEMA, CRED_DEF, REVOC_REG_DEF, REVOC_REG_ENTRY];
+
+const DOMAIN_LEDGER_ID: u64 = 1;
+
+lazy_static! {
+    static ref SIMULATOR: Mutex<Simulator> = Mutex::new(Simulator::new());
+
+    // Replies are delivered by the single thread: transport must not block pool worker
+    // and REQACK of the node has to come before its REPLY.
+    static ref REPLIES: Mutex<mpsc::Sender<(PoolHandle, String, String)>> = {
+        let (sender, receiver) = mpsc::channel::<(PoolHandle, String, String)>();
+        thread::spawn(move || {
+            for (pool_handle, node_alias, reply) in receiver {
+                // pool can be already closed, nobody waits for the reply in this case
+                let _ = pool::deliver_transport_reply(pool_handle, &node_alias, &reply);
+            }
+        });
+        Mutex::new(sender)
+    };
+}
+
+/// Registers simulator transport. Is called by the helpers below, so tests don't need it.
+pub fn init() {
+    lazy_static! {
+        static ref SIMULATOR_INIT: Once = Once::new();
+    }
+
+    SIMULATOR_INIT.call_once(|| {
+        pool::register_transport(SIMULATOR_TRANSPORT, _send).unwrap();
+    });
+}
+
+/// Genesis transactions of the test pool with BLS keys of the simulated nodes.
+pub fn genesis_txns() -> Vec<String> {
+    let generator = _generator();
+
+    test::gen_txns()
+        .iter()
+        .zip(NODES.iter())
+        .map(|(txn, alias)| {
+            let mut txn: Value = serde_json::from_str(txn).unwrap();
+            let blskey = VerKey::new(&generator, &_bls_sign_key(alias)).unwrap().as_bytes().to_base58();
+            let node_data = &mut txn["txn"]["data"]["data"];
+            node_data["blskey"] = json!(blskey);
+            node_data.as_object_mut().unwrap().remove("blskey_pop");
+            txn.to_string()
+        })
+        .collect()
+}
+
+pub fn create_and_open_pool_ledger(pool_name: &str, config: Option<&str>) -> Result<PoolHandle, IndyError> {
+    let txn_file_path = pool::create_genesis_txn_file(pool_name, &genesis_txns().join("\n"), None);
+    let pool_config = pool::pool_config_json(txn_file_path.as_path());
+    pool::create_pool_ledger_config(pool_name, Some(pool_config.as_str()))?;
+    open_pool_ledger(pool_name, config)
+}
+
+/// Opens pool ledger with simulator transport added to the open `config`.
+pub fn open_pool_ledger(pool_name: &str, config: Option<&str>) -> Result<PoolHandle, IndyError> {
+    init();
+
+    let mut config: Value = config.map(|config| serde_json::from_str(config).unwrap()).unwrap_or_else(|| json!({}));
+    config["transport"] = json!(SIMULATOR_TRANSPORT);
+
+    pool::open_pool_ledger(pool_name, Some(&config.to_string()))
+}
+
+/// Nodes of the pool start to sign state proofs for another state,
+/// so replies can be accepted only if enough nodes return the same data.
+pub fn corrupt_state_proofs(pool_handle: PoolHandle) {
+    SIMULATOR.lock().unwrap().corrupted_pools.insert(pool_handle);
+}
+
+extern fn _send(pool_handle: PoolHandle, node_alias: *const c_char, _node_address: *const c_char, message: *const c_char) -> i32 {
+    let node_alias = unsafe { CStr::from_ptr(node_alias) }.to_string_lossy().to_string();
+    let message = unsafe { CStr::from_ptr(message) }.to_string_lossy().to_string();
+
+    let replies = SIMULATOR.lock().unwrap().process(pool_handle, &message);
+
+    let sender = REPLIES.lock().unwrap();
+    for reply in replies {
+        let _ = sender.send((pool_handle, node_alias.clone(), reply));
+    }
+
+    ErrorCode::Success as i32
+}
+
+fn _generator() -> Generator {
+    Generator::from_bytes(&GENERATOR.from_base58().unwrap()).unwrap()
+}
+
+fn _bls_sign_key(alias: &str) -> SignKey {
+    SignKey::new(Some(format!("{:0>32}", alias).as_bytes())).unwrap()
+}
+
+fn _now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+fn _sha256(data: &[u8]) -> Vec<u8> {
+    hash(data).unwrap()
+}
+
+/// Value of the ledger state. `value` is what is stored in the state trie,
+/// `data` is what is returned to the client.
+struct Record {
+    seq_no: u64,
+    txn_time: Option<u64>,
+    owner: String,
+    data: Value,
+    value: String,
+}
+
+impl Record {
+    fn new(seq_no: u64, txn_time: Option<u64>, owner: &str, data: Value, val: Value) -> Record {
+        let value = json!({"lsn": seq_no, "lut": txn_time, "val": val}).to_string();
+        Record { seq_no, txn_time, owner: owner.to_string(), data, value }
+    }
+
+    fn nym(seq_no: u64, txn_time: Option<u64>, owner: &str, dest: &str, role: Option<&str>, verkey: Option<&str>) -> Record {
+        let data = json!({
+            "dest": dest,
+            "identifier": owner,
+            "role": role,
+            "seqNo": seq_no,
+            "txnTime": txn_time,
+            "verkey": verkey,
+        });
+        let value = json!({
+            "identifier": owner,
+            "role": role,
+            "seqNo": seq_no,
+            "txnTime": txn_time,
+            "verkey": verkey,
+        }).to_string();
+        Record { seq_no, txn_time, owner: owner.to_string(), data, value }
+    }
+}
+
+type State = BTreeMap<Vec<u8>, Arc<Record>>;
+
+enum Rejection {
+    // request is invalid or isn't authenticated
+    Nack(String),
+    // request is valid, but can't be applied to the ledger
+    Reject(String),
+}
+
+fn _nack(reason: &str) -> Rejection {
+    Rejection::Nack(reason.to_string())
+}
+
+fn _reject(reason: &str) -> Rejection {
+    Rejection::Reject(reason.to_string())
+}
+
+struct Simulator {
+    bls_keys: Vec<(&'static str, SignKey)>,
+    // States after each write with the time of the write, genesis state goes first
+    history: Vec<(u64, State)>,
+    txns: Vec<Value>,
+    // Nodes apply write once and return the same replies for it
+    write_replies: HashMap<(String, u64), Vec<String>>,
+    corrupted_pools: HashSet<PoolHandle>,
+}
+
+impl Simulator {
+    fn new() -> Simulator {
+        let mut simulator = Simulator {
+            bls_keys: NODES.iter().map(|alias| (*alias, _bls_sign_key(alias))).collect(),
+            history: vec![(0, State::new())],
+            txns: Vec::new(),
+            write_replies: HashMap::new(),
+            corrupted_pools: HashSet::new(),
+        };
+
+        simulator.add_genesis_nym(TRUSTEE_SEED, TRUSTEE);
+        simulator.add_genesis_nym(STEWARD_SEED, STEWARD);
+        simulator
+    }
+
+    fn add_genesis_nym(&mut self, seed: &str, role: &str) {
+        let seed = Seed::from_slice(seed.as_bytes()).unwrap();
+        let (verkey, _) = ed25519_sign::create_key_pair_for_signature(Some(&seed)).unwrap();
+        let did = verkey[0..16].to_base58();
+        let verkey = verkey[..].to_base58();
+        let seq_no = self.txns.len() as u64 + 1;
+
+        self.txns.push(json!({
+            "txn": {
+                "type": NYM,
+                "data": {"dest": did, "role": role, "verkey": verkey},
+                "metadata": {},
+            },
+            "txnMetadata": {"seqNo": seq_no},
+            "reqSignature": {},
+            "ver": "1",
+        }));
+
+        let record = Record::nym(seq_no, None, &did, &did, Some(role), Some(&verkey));
+        self.history[0].1.insert(_nym_key(&did), Arc::new(record));
+    }
+
+    fn state(&self) -> &State {
+        &self.history.last().unwrap().1
+    }
+
+    fn state_at(&self, timestamp: u64) -> &State {
+        self.history.iter().rev()
+            .find(|(time, _)| *time <= timestamp)
+            .map(|(_, state)| state)
+            .unwrap_or(&self.history[0].1)
+    }
+
+    fn process(&mut self, pool_handle: PoolHandle, msg: &str) -> Vec<String> {
+        if msg == "pi" {
+            return vec!["po".to_string()];
+        }
+
+        let request: Value = match serde_json::from_str(msg) {
+            Ok(request) => request,
+            Err(_) => return vec![]
+        };
+
+        // the ledgers of the simulated pool never change, so the status of client is always actual
+        if request["op"] == json!("LEDGER_STATUS") {
+            return vec![msg.to_string()];
+        }
+
+        let (identifier, req_id, type_) = match (request["identifier"].as_str(),
+                                                 request["reqId"].as_u64(),
+                                                 request["operation"]["type"].as_str()) {
+            (Some(identifier), Some(req_id), Some(type_)) => (identifier.to_string(), req_id, type_.to_string()),
+            _ => return vec![]
+        };
+
+        if READ_TYPES.contains(&type_.as_str()) {
+            return vec![self.read(pool_handle, &request, &type_)];
+        }
+
+        let key = (identifier.clone(), req_id);
+
+        if let Some(replies) = self.write_replies.get(&key) {
+            return replies.clone();
+        }
+
+        let ack = json!({"op": "REQACK", "identifier": identifier, "reqId": req_id}).to_string();
+
+        let replies = match self.write(&request, &identifier, &type_) {
+            Ok(txn) => vec![ack, json!({"op": "REPLY", "result": txn}).to_string()],
+            Err(Rejection::Nack(reason)) =>
+                vec![json!({"op": "REQNACK", "identifier": identifier, "reqId": req_id, "reason": reason}).to_string()],
+            Err(Rejection::Reject(reason)) =>
+                vec![ack, json!({"op": "REJECT", "identifier": identifier, "reqId": req_id, "reason": reason}).to_string()],
+        };
+
+        self.write_replies.insert(key, replies.clone());
+        replies
+    }
+
+    fn write(&mut self, request: &Value, identifier: &str, type_: &str) -> Result<Value, Rejection> {
+        if !WRITE_TYPES.contains(&type_) {
+            return Err(_nack("Unsupported request type"));
+        }
+
+        let signature = request["signature"].as_str().or_else(|| request["signatures"][identifier].as_str());
+        self.authenticate(request, identifier, signature)?;
+
+        // endorser takes responsibility for the request, so his role is checked
+        let author_role = match request["endorser"].as_str() {
+            Some(endorser) => {
+                self.authenticate(request, endorser, request["signatures"][endorser].as_str())?;
+                self.role(endorser)
+            }
+            None => self.role(identifier)
+        };
+
+        let operation = &request["operation"];
+        let seq_no = self.txns.len() as u64 + 1;
+        let txn_time = _now();
+        let mut state = self.state().clone();
+
+        match type_ {
+            NYM => Simulator::write_nym(&mut state, identifier, author_role.as_ref().map(String::as_str), operation, seq_no, txn_time)?,
+            ATTRIB => Simulator::write_attrib(&mut state, identifier, operation, seq_no, txn_time)?,
+            SCHEMA => Simulator::write_schema(&mut state, identifier, author_role.as_ref().map(String::as_str), operation, seq_no, txn_time)?,
+            CRED_DEF => self.write_cred_def(&mut state, identifier, author_role.as_ref().map(String::as_str), operation, seq_no, txn_time)?,
+            REVOC_REG_DEF => Simulator::write_revoc_reg_def(&mut state, identifier, author_role.as_ref().map(String::as_str), operation, seq_no, txn_time)?,
+            _ => Simulator::write_revoc_reg_entry(&mut state, identifier, operation, seq_no, txn_time)?,
+        }
+
+        let txn = _txn(request, type_, seq_no, txn_time);
+        self.txns.push(txn.clone());
+        self.history.push((txn_time, state));
+
+        Ok(txn)
+    }
+
+    fn authenticate(&self, request: &Value, did: &str, signature: Option<&str>) -> Result<(), Rejection> {
+        let signature = signature
+            .ok_or_else(|| Rejection::Nack(format!("Missed signature of {}", did)))?;
+
+        let verkey = self.verkey(did)
+            .ok_or_else(|| Rejection::Nack(format!("Unknown identifier {}", did)))?;
+
+        let signature = signature.from_base58().ok()
+            .and_then(|signature| Signature::from_slice(&signature).ok())
+            .ok_or_else(|| _nack("Invalid signature format"))?;
+
+        match ed25519_sign::verify(&verkey, _serialize_signature(request).as_bytes(), &signature) {
+            Ok(true) => Ok(()),
+            _ => Err(Rejection::Nack(format!("Invalid signature of {}", did)))
+        }
+    }
+
+    fn verkey(&self, did: &str) -> Option<PublicKey> {
+        let nym = self.state().get(&_nym_key(did))?;
+        let mut did_bytes = did.from_base58().ok()?;
+
+        let verkey = match nym.data["verkey"].as_str() {
+            Some(abbr_verkey) if abbr_verkey.starts_with('~') => {
+                did_bytes.extend(abbr_verkey[1..].from_base58().ok()?);
+                did_bytes
+            }
+            Some(verkey) => verkey.from_base58().ok()?,
+            // DID is the verkey itself
+            None => did_bytes
+        };
+
+        PublicKey::from_slice(&verkey).ok()
+    }
+
+    fn role(&self, did: &str) -> Option<String> {
+        self.state().get(&_nym_key(did))
+            .and_then(|nym| nym.data["role"].as_str().map(String::from))
+    }
+
+    fn write_nym(state: &mut State, author: &str, author_role: Option<&str>, operation: &Value, seq_no: u64, txn_time: u64) -> Result<(), Rejection> {
+        let dest = operation["dest"].as_str().ok_or_else(|| _nack("Missed dest"))?;
+
+        let role_change: Option<Option<String>> = match operation.get("role") {
+            None => None,
+            Some(Value::Null) => Some(None),
+            Some(Value::String(role)) if role.as_str() == ROLE_REMOVE => Some(None),
+            Some(Value::String(role)) if ROLES.contains(&role.as_str()) => Some(Some(role.clone())),
+            Some(role) => return Err(Rejection::Nack(format!("Invalid role {}", role)))
+        };
+
+        let key = _nym_key(dest);
+
+        let (owner, role, verkey) = match state.get(&key) {
+            None => {
+                let role = role_change.unwrap_or(None);
+                _check_role_assignment(author_role, role.as_ref().map(String::as_str))?;
+                (author.to_string(), role, operation["verkey"].as_str().map(String::from))
+            }
+            Some(nym) => {
+                let mut role = nym.data["role"].as_str().map(String::from);
+                let mut verkey = nym.data["verkey"].as_str().map(String::from);
+
+                if let Some(new_role) = role_change {
+                    if new_role != role {
+                        if author_role != Some(TRUSTEE) {
+                            return Err(_reject("Only TRUSTEE can change role of existing NYM"));
+                        }
+                        role = new_role;
+                    }
+                }
+
+                if let Some(new_verkey) = operation["verkey"].as_str() {
+                    if author != dest && author != nym.owner {
+                        return Err(_reject("Only owner can rotate verkey of NYM"));
+                    }
+                    verkey = Some(new_verkey.to_string());
+                }
+
+                (nym.owner.clone(), role, verkey)
+            }
+        };
+
+        let record = Record::nym(seq_no, Some(txn_time), &owner, dest,
+                                 role.as_ref().map(String::as_str), verkey.as_ref().map(String::as_str));
+        state.insert(key, Arc::new(record));
+        Ok(())
+    }
+
+    fn write_attrib(state: &mut State, author: &str, operation: &Value, seq_no: u64, txn_time: u64) -> Result<(), Rejection> {
+        let dest = operation["dest"].as_str().ok_or_else(|| _nack("Missed dest"))?;
+
+        let nym = state.get(&_nym_key(dest)).ok_or_else(|| _reject("Unknown dest"))?;
+        if author != dest && author != nym.owner {
+            return Err(_reject("Only owner can add attributes to NYM"));
+        }
+
+        let (name, data) = if let Some(raw) = operation["raw"].as_str() {
+            let name = serde_json::from_str::<Value>(raw).ok()
+                .and_then(|raw| raw.as_object().filter(|raw| raw.len() == 1).and_then(|raw| raw.keys().next().cloned()))
+                .ok_or_else(|| _nack("raw must be JSON object with the single attribute"))?;
+            (name, raw)
+        } else if let Some(hash) = operation["hash"].as_str() {
+            (hash.to_string(), hash)
+        } else if let Some(enc) = operation["enc"].as_str() {
+            (enc.to_string(), enc)
+        } else {
+            return Err(_nack("One of raw, hash or enc is required"));
+        };
+
+        let key = _attrib_key(dest, &name);
+        let val = hex::encode(_sha256(data.as_bytes()));
+        state.insert(key, Arc::new(Record::new(seq_no, Some(txn_time), author, json!(data), json!(val))));
+        Ok(())
+    }
+
+    fn write_schema(state: &mut State, author: &str, author_role: Option<&str>, operation: &Value, seq_no: u64, txn_time: u64) -> Result<(), Rejection> {
+        let data = &operation["data"];
+
+        let (name, version) = match (data["name"].as_str(), data["version"].as_str(), data["attr_names"].as_array()) {
+            (Some(name), Some(version), Some(_)) => (name, version),
+            _ => return Err(_nack("Invalid schema data"))
+        };
+
+        if author_role.is_none() {
+            return Err(_reject("Only DID with a role can write schemas"));
+        }
+
+        let key = _schema_key(author, name, version);
+        if state.contains_key(&key) {
+            return Err(_reject("Schema already exists"));
+        }
+
+        let data = json!({"attr_names": data["attr_names"], "name": name, "version": version});
+        let val = json!({"attr_names": data["attr_names"]});
+        state.insert(key, Arc::new(Record::new(seq_no, Some(txn_time), author, data, val)));
+        Ok(())
+    }
+
+    fn write_cred_def(&self, state: &mut State, author: &str, author_role: Option<&str>, operation: &Value, seq_no: u64, txn_time: u64) -> Result<(), Rejection> {
+        let (schema_seq_no, signature_type) = match (operation["ref"].as_u64(), operation["signature_type"].as_str(), operation["data"].as_object()) {
+            (Some(schema_seq_no), Some(signature_type), Some(_)) => (schema_seq_no, signature_type),
+            _ => return Err(_nack("Invalid credential definition"))
+        };
+
+        if author_role.is_none() {
+            return Err(_reject("Only DID with a role can write credential definitions"));
+        }
+
+        let is_schema = schema_seq_no.checked_sub(1)
+            .and_then(|idx| self.txns.get(idx as usize))
+            .map(|txn| txn["txn"]["type"] == json!(SCHEMA))
+            .unwrap_or(false);
+
+        if !is_schema {
+            return Err(Rejection::Reject(format!("Schema with seqNo {} not found", schema_seq_no)));
+        }
+
+        let key = _cred_def_key(author, signature_type, schema_seq_no, operation["tag"].as_str());
+        if state.contains_key(&key) {
+            return Err(_reject("Credential definition already exists"));
+        }
+
+        let data = operation["data"].clone();
+        state.insert(key, Arc::new(Record::new(seq_no, Some(txn_time), author, data.clone(), data)));
+        Ok(())
+    }
+
+    fn write_revoc_reg_def(state: &mut State, author: &str, author_role: Option<&str>, operation: &Value, seq_no: u64, txn_time: u64) -> Result<(), Rejection> {
+        let (id, cred_def_id) = match (operation["id"].as_str(), operation["credDefId"].as_str()) {
+            (Some(id), Some(cred_def_id)) => (id, cred_def_id),
+            _ => return Err(_nack("Invalid revocation registry definition"))
+        };
+
+        if author_role.is_none() {
+            return Err(_reject("Only DID with a role can write revocation registry definitions"));
+        }
+
+        if !state.contains_key(cred_def_id.as_bytes()) {
+            return Err(Rejection::Reject(format!("Credential definition {} not found", cred_def_id)));
+        }
+
+        if state.contains_key(id.as_bytes()) {
+            return Err(_reject("Revocation registry definition already exists"));
+        }
+
+        let mut data = operation.clone();
+        data.as_object_mut().map(|data| data.remove("type"));
+        state.insert(id.as_bytes().to_vec(), Arc::new(Record::new(seq_no, Some(txn_time), author, data.clone(), data)));
+        Ok(())
+    }
+
+    fn write_revoc_reg_entry(state: &mut State, author: &str, operation: &Value, seq_no: u64, txn_time: u64) -> Result<(), Rejection> {
+        let (id, accum) = match (operation["revocRegDefId"].as_str(), operation["value"].get("accum")) {
+            (Some(id), Some(accum)) => (id, accum),
+            _ => return Err(_nack("Invalid revocation registry entry"))
+        };
+
+        let def = state.get(id.as_bytes()).ok_or_else(|| _reject("Revocation registry definition not found"))?;
+        if def.owner != author {
+            return Err(_reject("Only owner of revocation registry definition can update it"));
+        }
+
+        let (mut issued, mut revoked) = match state.get(_revoc_reg_entry_key(id).as_slice()) {
+            Some(entry) => {
+                if operation["value"]["prevAccum"] != entry.data["value"]["accum"] {
+                    return Err(_reject("prevAccum doesn't match current accumulator"));
+                }
+                (_indices(&entry.data["value"]["issued"]), _indices(&entry.data["value"]["revoked"]))
+            }
+            None => (BTreeSet::new(), BTreeSet::new())
+        };
+
+        for idx in _indices(&operation["value"]["issued"]) {
+            revoked.remove(&idx);
+            issued.insert(idx);
+        }
+
+        for idx in _indices(&operation["value"]["revoked"]) {
+            issued.remove(&idx);
+            revoked.insert(idx);
+        }
+
+        let entry = |value: Value| json!({
+            "revocDefType": operation["revocDefType"],
+            "revocRegDefId": id,
+            "seqNo": seq_no,
+            "txnTime": txn_time,
+            "value": value,
+        });
+
+        let accum_entry = entry(json!({"accum": accum}));
+        let full_entry = entry(json!({"accum": accum, "issued": issued, "revoked": revoked}));
+
+        state.insert(_revoc_reg_key(id), Arc::new(Record::new(seq_no, Some(txn_time), author, accum_entry.clone(), accum_entry)));
+        state.insert(_revoc_reg_entry_key(id), Arc::new(Record::new(seq_no, Some(txn_time), author, full_entry.clone(), full_entry)));
+        Ok(())
+    }
+
+    fn read(&self, pool_handle: PoolHandle, request: &Value, type_: &str) -> String {
+        let operation = &request["operation"];
+        let now = _now();
+
+        let mut result = operation.clone();
+        result["identifier"] = request["identifier"].clone();
+        result["reqId"] = request["reqId"].clone();
+
+        match type_ {
+            GET_TXN => {
+                let txn = operation["data"].as_u64()
+                    .filter(|_| operation["ledgerId"].as_u64().unwrap_or(DOMAIN_LEDGER_ID) == DOMAIN_LEDGER_ID)
+                    .and_then(|seq_no| seq_no.checked_sub(1))
+                    .and_then(|idx| self.txns.get(idx as usize));
+                result["seqNo"] = operation["data"].clone();
+                result["data"] = txn.cloned().unwrap_or(Value::Null);
+            }
+            GET_REVOC_REG_DELTA if operation["from"].is_u64() => {
+                let (id, from, to) = match (operation["revocRegDefId"].as_str(), operation["from"].as_u64(), operation["to"].as_u64()) {
+                    (Some(id), Some(from), Some(to)) => (id, from, to),
+                    _ => return _invalid_request(request)
+                };
+
+                let key = _revoc_reg_key(id);
+                let (state_from, state_to) = (self.state_at(from), self.state_at(to));
+
+                self.set_record(&mut result, state_to.get(&key).map(|entry| {
+                    let entry_from = state_from.get(&key).map(|entry| entry.data.clone()).unwrap_or(Value::Null);
+                    let entries = |state: &State| state.get(&_revoc_reg_entry_key(id)).map(|entry| entry.data["value"].clone()).unwrap_or(Value::Null);
+                    let (values_from, values_to) = (entries(state_from), entries(state_to));
+
+                    let diff = |name: &str| -> Vec<u64> {
+                        _indices(&values_to[name]).difference(&_indices(&values_from[name])).cloned().collect()
+                    };
+
+                    json!({
+                        "revocDefType": entry.data["revocDefType"],
+                        "revocRegDefId": id,
+                        "stateProofFrom": self.state_proof(pool_handle, state_from, &key, from.min(now)),
+                        "value": {
+                            "accum_from": entry_from,
+                            "accum_to": entry.data,
+                            "issued": diff("issued"),
+                            "revoked": diff("revoked"),
+                        },
+                    })
+                }), Value::Null, state_to.get(&key));
+
+                result["state_proof"] = self.state_proof(pool_handle, state_to, &key, to.min(now));
+            }
+            _ => {
+                let key = match _read_key(type_, operation) {
+                    Some(key) => key,
+                    None => return _invalid_request(request)
+                };
+
+                let timestamp = match type_ {
+                    GET_REVOC_REG => operation["timestamp"].as_u64().unwrap_or(now),
+                    GET_REVOC_REG_DELTA => operation["to"].as_u64().unwrap_or(now),
+                    _ => now
+                };
+                let state = self.state_at(timestamp);
+                let record = state.get(&key);
+
+                let data = record.map(|record| match type_ {
+                    // nodes return NYM data as a string
+                    GET_NYM => json!(record.data.to_string()),
+                    GET_REVOC_REG_DELTA => json!({
+                        "revocDefType": record.data["revocDefType"],
+                        "revocRegDefId": record.data["revocRegDefId"],
+                        "value": {
+                            "accum_to": record.data,
+                            "issued": record.data["value"]["issued"],
+                            "revoked": record.data["value"]["revoked"],
+                        },
+                    }),
+                    _ => record.data.clone()
+                });
+
+                let not_found = if type_ == GET_SCHEMA {
+                    json!({"name": operation["data"]["name"], "version": operation["data"]["version"]})
+                } else {
+                    Value::Null
+                };
+
+                self.set_record(&mut result, data, not_found, record);
+                result["state_proof"] = self.state_proof(pool_handle, state, &key, timestamp.min(now));
+            }
+        }
+
+        json!({"op": "REPLY", "result": result}).to_string()
+    }
+
+    fn set_record(&self, result: &mut Value, data: Option<Value>, not_found: Value, record: Option<&Arc<Record>>) {
+        result["data"] = data.unwrap_or(not_found);
+        result["seqNo"] = json!(record.map(|record| record.seq_no));
+        result["txnTime"] = json!(record.and_then(|record| record.txn_time));
+    }
+
+    fn state_proof(&self, pool_handle: PoolHandle, state: &State, key: &[u8], timestamp: u64) -> Value {
+        let (root_hash, proof_nodes) = _prove(state, key);
+        let root_hash = root_hash.to_base58();
+
+        json!({
+            "root_hash": root_hash,
+            "proof_nodes": base64::encode(&proof_nodes),
+            "multi_signature": self.multi_signature(pool_handle, &root_hash, timestamp),
+        })
+    }
+
+    fn multi_signature(&self, pool_handle: PoolHandle, state_root_hash: &str, timestamp: u64) -> Value {
+        let value = json!({
+            "ledger_id": DOMAIN_LEDGER_ID,
+            "pool_state_root_hash": Sha3_256::digest(b"pool").to_base58(),
+            "state_root_hash": state_root_hash,
+            "timestamp": timestamp,
+            "txn_root_hash": Sha3_256::digest(self.txns.len().to_string().as_bytes()).to_base58(),
+        });
+
+        let mut signed_value = value.clone();
+        if self.corrupted_pools.contains(&pool_handle) {
+            signed_value["state_root_hash"] = json!(Sha3_256::digest(state_root_hash.as_bytes()).to_base58());
+        }
+        let message = rmp_serde::to_vec_named(&signed_value).unwrap();
+
+        let participants = &self.bls_keys[..self.bls_keys.len() - F];
+        let signatures = participants.iter()
+            .map(|(_, sign_key)| Bls::sign(&message, sign_key).unwrap())
+            .collect::<Vec<_>>();
+        let signature = MultiSignature::new(&signatures.iter().collect::<Vec<_>>()).unwrap();
+
+        json!({
+            "participants": participants.iter().map(|(alias, _)| alias).collect::<Vec<_>>(),
+            "signature": signature.as_bytes().to_base58(),
+            "value": value,
+        })
+    }
+}
+
+fn _invalid_request(request: &Value) -> String {
+    json!({"op": "REQNACK", "identifier": request["identifier"], "reqId": request["reqId"], "reason": "Invalid request"}).to_string()
+}
+
+fn _txn(request: &Value, type_: &str, seq_no: u64, txn_time: u64) -> Value {
+    let mut data = request["operation"].clone();
+    data.as_object_mut().map(|data| data.remove("type"));
+
+    let mut metadata = json!({"from": request["identifier"], "reqId": request["reqId"]});
+    if let Some(endorser) = request["endorser"].as_str() {
+        metadata["endorser"] = json!(endorser);
+    }
+
+    let mut signatures = Vec::new();
+    if let Some(signature) = request["signature"].as_str() {
+        signatures.push(json!({"from": request["identifier"], "value": signature}));
+    }
+    if let Some(multi_signatures) = request["signatures"].as_object() {
+        signatures.extend(multi_signatures.iter().map(|(from, signature)| json!({"from": from, "value": signature})));
+    }
+
+    json!({
+        "txn": {
+            "type": type_,
+            "data": data,
+            "metadata": metadata,
+            "protocolVersion": request["protocolVersion"],
+        },
+        "txnMetadata": {"seqNo": seq_no, "txnTime": txn_time},
+        "reqSignature": {"type": "ED25519", "values": signatures},
+        "ver": "1",
+    })
+}
+
+fn _check_role_assignment(author_role: Option<&str>, role: Option<&str>) -> Result<(), Rejection> {
+    let allowed = match role {
+        Some(TRUSTEE) | Some(STEWARD) => author_role == Some(TRUSTEE),
+        Some(_) => author_role == Some(TRUSTEE) || author_role == Some(STEWARD),
+        None => author_role.is_some()
+    };
+
+    if allowed {
+        Ok(())
+    } else {
+        Err(_reject("Author has no permission to create NYM with the role"))
+    }
+}
+
+fn _indices(value: &Value) -> BTreeSet<u64> {
+    value.as_array()
+        .map(|indices| indices.iter().filter_map(Value::as_u64).collect())
+        .unwrap_or_default()
+}
+
+fn _nym_key(did: &str) -> Vec<u8> {
+    _sha256(did.as_bytes())
+}
+
+fn _attrib_key(dest: &str, name: &str) -> Vec<u8> {
+    format!("{}:1:{}", dest, hex::encode(_sha256(name.as_bytes()))).into_bytes()
+}
+
+fn _schema_key(dest: &str, name: &str, version: &str) -> Vec<u8> {
+    format!("{}:2:{}:{}", dest, name, version).into_bytes()
+}
+
+fn _cred_def_key(origin: &str, signature_type: &str, schema_seq_no: u64, tag: Option<&str>) -> Vec<u8> {
+    let tag = tag.map(|tag| format!(":{}", tag)).unwrap_or_default();
+    format!("{}:3:{}:{}{}", origin, signature_type, schema_seq_no, tag).into_bytes()
+}
+
+fn _revoc_reg_key(revoc_reg_def_id: &str) -> Vec<u8> {
+    format!("6:{}", revoc_reg_def_id).into_bytes()
+}
+
+fn _revoc_reg_entry_key(revoc_reg_def_id: &str) -> Vec<u8> {
+    format!("5:{}", revoc_reg_def_id).into_bytes()
+}
+
+fn _read_key(type_: &str, operation: &Value) -> Option<Vec<u8>> {
+    let key = match type_ {
+        GET_NYM => _nym_key(operation["dest"].as_str()?),
+        GET_ATTR => {
+            let name = operation["raw"].as_str()
+                .or_else(|| operation["enc"].as_str())
+                .or_else(|| operation["hash"].as_str())?;
+            _attrib_key(operation["dest"].as_str()?, name)
+        }
+        GET_SCHEMA => _schema_key(operation["dest"].as_str()?, operation["data"]["name"].as_str()?, operation["data"]["version"].as_str()?),
+        GET_CRED_DEF => _cred_def_key(operation["origin"].as_str()?, operation["signature_type"].as_str()?,
+                                      operation["ref"].as_u64()?, operation["tag"].as_str()),
+        GET_REVOC_REG_DEF => operation["id"].as_str()?.as_bytes().to_vec(),
+        GET_REVOC_REG => _revoc_reg_key(operation["revocRegDefId"].as_str()?),
+        GET_REVOC_REG_DELTA => _revoc_reg_entry_key(operation["revocRegDefId"].as_str()?),
+        _ => return None
+    };
+    Some(key)
+}
+
+// Port of libindy signature serializer: request data signed by the client
+fn _serialize_signature(request: &Value) -> String {
+    let type_ = request["operation"]["type"].as_str();
+    _serialize_signature_value(request, true, type_)
+}
+
+fn _serialize_signature_value(value: &Value, is_top_level: bool, type_: Option<&str>) -> String {
+    match value {
+        Value::Bool(value) => if *value { "True".to_string() } else { "False".to_string() },
+        Value::Number(value) => value.to_string(),
+        Value::String(value) => value.clone(),
+        Value::Array(array) => array.iter()
+            .map(|element| _serialize_signature_value(element, false, type_))
+            .collect::<Vec<String>>()
+            .join(","),
+        Value::Object(map) => map.iter()
+            .filter(|(key, _)| !(is_top_level && (*key == "signature" || *key == "fees" || *key == "signatures")))
+            .map(|(key, value)| {
+                let value = match value.as_str() {
+                    Some(attr) if (type_ == Some(ATTRIB) || type_ == Some(GET_ATTR)) && (key == "raw" || key == "hash" || key == "enc") =>
+                        hex::encode(_sha256(attr.as_bytes())),
+                    _ => _serialize_signature_value(value, false, type_)
+                };
+                format!("{}:{}", key, value)
+            })
+            .collect::<Vec<String>>()
+            .join("|"),
+        Value::Null => String::new()
+    }
+}
+
+/// Builds Patricia Merkle trie of the whole state and returns its root hash
+/// with RLP list of the nodes on the path to `key`: enough to prove the value or absence of the key.
+fn _prove(state: &State, key: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let entries = state.iter()
+        .map(|(key, record)| {
+            let mut value = RlpStream::new_list(1);
+            value.append(&record.value.as_bytes().to_vec());
+            (_nibbles(key), value.out().to_vec())
+        })
+        .collect::<Vec<_>>();
+
+    let mut proof_nodes = Vec::new();
+    let root_hash = _trie_node(&entries, Some(&_nibbles(key)), &mut proof_nodes);
+
+    let mut proof = RlpStream::new_list(proof_nodes.len());
+    for node in &proof_nodes {
+        proof.append_raw(node, 1);
+    }
+
+    (root_hash, proof.out().to_vec())
+}
+
+// Encodes trie node for the entries with the same path prefix, nodes on the path to `key` are collected to `proof_nodes`
+fn _trie_node(entries: &[(Vec<u8>, Vec<u8>)], key: Option<&[u8]>, proof_nodes: &mut Vec<Vec<u8>>) -> Vec<u8> {
+    let encoded = match entries {
+        [] => {
+            let mut stream = RlpStream::new();
+            stream.append_empty_data();
+            stream.out().to_vec()
+        }
+        [(path, value)] => {
+            let mut stream = RlpStream::new_list(2);
+            stream.append(&_hex_prefix(path, true));
+            stream.append(value);
+            stream.out().to_vec()
+        }
+        _ => {
+            let prefix = _common_prefix(entries);
+
+            if !prefix.is_empty() {
+                let children = entries.iter()
+                    .map(|(path, value)| (path[prefix.len()..].to_vec(), value.clone()))
+                    .collect::<Vec<_>>();
+                let key = key.filter(|key| key.starts_with(prefix)).map(|key| &key[prefix.len()..]);
+
+                let mut stream = RlpStream::new_list(2);
+                stream.append(&_hex_prefix(prefix, false));
+                stream.append(&_trie_node(&children, key, proof_nodes));
+                stream.out().to_vec()
+            } else {
+                let mut stream = RlpStream::new_list(17);
+
+                for nibble in 0..16u8 {
+                    let children = entries.iter()
+                        .filter(|(path, _)| path.first() == Some(&nibble))
+                        .map(|(path, value)| (path[1..].to_vec(), value.clone()))
+                        .collect::<Vec<_>>();
+
+                    if children.is_empty() {
+                        stream.append_empty_data();
+                    } else {
+                        let key = key.filter(|key| key.first() == Some(&nibble)).map(|key| &key[1..]);
+                        stream.append(&_trie_node(&children, key, proof_nodes));
+                    }
+                }
+
+                match entries.iter().find(|(path, _)| path.is_empty()) {
+                    Some((_, value)) => stream.append(value),
+                    None => stream.append_empty_data()
+                };
+
+                stream.out().to_vec()
+            }
+        }
+    };
+
+    if key.is_some() {
+        proof_nodes.push(encoded.clone());
+    }
+
+    Sha3_256::digest(&encoded).to_vec()
+}
+
+fn _common_prefix(entries: &[(Vec<u8>, Vec<u8>)]) -> &[u8] {
+    let first = &entries[0].0;
+    let len = entries[1..].iter()
+        .map(|(path, _)| first.iter().zip(path.iter()).take_while(|(a, b)| a == b).count())
+        .min()
+        .unwrap_or_else(|| first.len());
+    &first[..len]
+}
+
+fn _nibbles(key: &[u8]) -> Vec<u8> {
+    key.iter().flat_map(|byte| vec![byte >> 4, byte & 0x0F]).collect()
+}
+
+// Compact encoding of the nibbles path with the node type flag
+fn _hex_prefix(nibbles: &[u8], is_leaf: bool) -> Vec<u8> {
+    let flag = if is_leaf { 0x20 } else { 0x00 };
+
+    let (mut encoded, rest) = if nibbles.len() % 2 == 1 {
+        (vec![flag | 0x10 | nibbles[0]], &nibbles[1..])
+    } else {
+        (vec![flag], nibbles)
+    };
+
+    encoded.extend(rest.chunks(2).map(|pair| pair[0] << 4 | pair[1]));
+    encoded
+}
@@ -10,9 +10,10 @@ use self::futures::Future;
 use crate::utils::{callback, sequence, environment};
 use crate::utils::inmem_wallet::InmemWallet;
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Mutex;
-use std::ffi::CString;
+use std::ffi::{CStr, CString};
+use std::slice;
 use super::libc::c_char;
 
 use crate::utils::constants::{TYPE, INMEM_TYPE, WALLET_CREDENTIALS};
@@ -108,6 +109,14 @@ pub fn delete_wallet(config: &str, credentials: &str) -> Result<(), IndyError> {
     wallet::delete_wallet(config, credentials).wait()
 }
 
+pub fn list_wallet_profiles(config: &str, credentials: &str) -> Result<String, IndyError> {
+    wallet::list_wallet_profiles(config, credentials).wait()
+}
+
+pub fn rotate_wallet_item_keys(wallet_handle: WalletHandle, credentials: &str) -> Result<(), IndyError> {
+    wallet::rotate_wallet_item_keys(wallet_handle, credentials).wait()
+}
+
 pub fn close_wallet(wallet_handle: WalletHandle) -> Result<(), IndyError> {
     wallet::close_wallet(wallet_handle).wait()
 }
@@ -125,6 +134,134 @@ pub fn import_wallet(config: &str, credentials: &str, import_config: &str) -> Re
     wallet::import_wallet(config, credentials, import_config).wait()
 }
 
+pub fn import_wallet_with_report(config: &str, credentials: &str, import_config: &str) -> Result<String, IndyError> {
+    wallet::import_wallet_with_report(config, credentials, import_config).wait()
+}
+
+pub fn migrate_wallet(src_config: &str, src_credentials: &str, dst_config: &str, dst_credentials: &str) -> Result<(), IndyError> {
+    wallet::migrate_wallet(src_config, src_credentials, dst_config, dst_credentials).wait()
+}
+
+pub fn import_wallet_delta(wallet_handle: WalletHandle, import_config: &str) -> Result<(), IndyError> {
+    wallet::import_wallet_delta(wallet_handle, import_config).wait()
+}
+
+pub fn get_wallet_revision(wallet_handle: WalletHandle) -> Result<u64, IndyError> {
+    wallet::get_wallet_revision(wallet_handle).wait()
+}
+
+pub fn check_wallet(wallet_handle: WalletHandle, config: Option<&str>) -> Result<String, IndyError> {
+    wallet::check_wallet(wallet_handle, config).wait()
+}
+
+lazy_static! {
+    static ref EXPORT_BUFFERS: Mutex<HashMap<i32, (Vec<u8>, usize)>> = Default::default();
+}
+
+extern fn _export_buffer_write(sink_handle: i32, data: *const u8, data_len: usize) -> i32 {
+    let data = unsafe { slice::from_raw_parts(data, data_len) };
+
+    match EXPORT_BUFFERS.lock().unwrap().get_mut(&sink_handle) {
+        Some((buffer, _)) => {
+            buffer.extend_from_slice(data);
+            ErrorCode::Success as i32
+        }
+        None => ErrorCode::CommonInvalidState as i32
+    }
+}
+
+extern fn _export_buffer_read(source_handle: i32, buf: *mut u8, buf_len: usize, read_len_p: *mut usize) -> i32 {
+    let buf = unsafe { slice::from_raw_parts_mut(buf, buf_len) };
+
+    match EXPORT_BUFFERS.lock().unwrap().get_mut(&source_handle) {
+        Some((buffer, pos)) => {
+            let read_len = ::std::cmp::min(buf_len, buffer.len() - *pos);
+            buf[..read_len].copy_from_slice(&buffer[*pos..*pos + read_len]);
+            *pos += read_len;
+            unsafe { *read_len_p = read_len; }
+            ErrorCode::Success as i32
+        }
+        None => ErrorCode::CommonInvalidState as i32
+    }
+}
+
+pub fn export_wallet_to_buffer(wallet_handle: WalletHandle, export_config_json: &str) -> Result<Vec<u8>, IndyError> {
+    let sink_handle = sequence::get_next_id();
+    EXPORT_BUFFERS.lock().unwrap().insert(sink_handle, (Vec::new(), 0));
+
+    let res = wallet::export_wallet_to_sink(wallet_handle, export_config_json, sink_handle, Some(_export_buffer_write)).wait();
+
+    let (buffer, _) = EXPORT_BUFFERS.lock().unwrap().remove(&sink_handle).unwrap();
+    res.map(|_| buffer)
+}
+
+pub fn import_wallet_from_buffer(config: &str, credentials: &str, import_config: &str, buffer: Vec<u8>) -> Result<(), IndyError> {
+    let source_handle = sequence::get_next_id();
+    EXPORT_BUFFERS.lock().unwrap().insert(source_handle, (buffer, 0));
+
+    let res = wallet::import_wallet_from_source(config, credentials, import_config, source_handle, Some(_export_buffer_read)).wait();
+
+    EXPORT_BUFFERS.lock().unwrap().remove(&source_handle);
+    res
+}
+
+pub const TEST_KEY_WRAP_PROVIDER: &str = "test_key_wrap";
+pub const TEST_KEY_WRAP_KEY_ID: &str = "test_key_id";
+
+lazy_static! {
+    static ref KEY_WRAP_BUFFERS: Mutex<HashMap<i32, Vec<u8>>> = Default::default();
+}
+
+// Test key wrap provider "wraps" key by reversing its bytes and knows only TEST_KEY_WRAP_KEY_ID key
+extern fn _test_key_wrap(key_id: *const c_char, key: *const u8, key_len: usize,
+                         out_p: *mut *const u8, out_len_p: *mut usize, key_handle_p: *mut i32) -> i32 {
+    let key_id = unsafe { CStr::from_ptr(key_id) }.to_str().unwrap();
+
+    if key_id != TEST_KEY_WRAP_KEY_ID {
+        return ErrorCode::WalletAccessFailed as i32;
+    }
+
+    let mut out = unsafe { slice::from_raw_parts(key, key_len) }.to_vec();
+    out.reverse();
+
+    let key_handle = sequence::get_next_id();
+
+    unsafe {
+        *out_p = out.as_ptr();
+        *out_len_p = out.len();
+        *key_handle_p = key_handle;
+    }
+
+    KEY_WRAP_BUFFERS.lock().unwrap().insert(key_handle, out);
+    ErrorCode::Success as i32
+}
+
+extern fn _test_key_free(key_handle: i32) -> i32 {
+    match KEY_WRAP_BUFFERS.lock().unwrap().remove(&key_handle) {
+        Some(_) => ErrorCode::Success as i32,
+        None => ErrorCode::CommonInvalidState as i32
+    }
+}
+
+pub fn register_wallet_key_wrap_provider(name: &str) -> Result<(), IndyError> {
+    wallet::register_wallet_key_wrap_provider(name, Some(_test_key_wrap), Some(_test_key_wrap), Some(_test_key_free)).wait()
+}
+
+#[cfg(feature = "file_key_wrap_provider")]
+pub fn file_key_wrap_credentials(name: &str) -> String {
+    let key_id = format!("{}_wrapping_key", name);
+    let key_path = environment::indy_home_path().join("wrapping_keys").join(&key_id);
+    if key_path.exists() {
+        ::std::fs::remove_file(&key_path).unwrap();
+    }
+
+    json!({
+        "key": key_id,
+        "key_derivation_method": "WRAPPED",
+        "key_wrap_provider": "file",
+    }).to_string()
+}
+
 pub fn export_wallet_path(name: &str) -> PathBuf {
     environment::tmp_file_path(name)
 }
@@ -7,7 +7,7 @@ extern crate indyrs as indy;
 extern crate indyrs as api;
 
 use crate::utils::inmem_wallet::InmemWallet;
-use crate::utils::{environment, wallet, test, did};
+use crate::utils::{environment, wallet, test, did, non_secrets};
 use crate::utils::constants::*;
 use crate::utils::Setup;
 
@@ -44,6 +44,16 @@ mod high_cases {
         }
     }
 
+    mod register_wallet_key_wrap_provider {
+        use super::*;
+
+        #[test]
+        fn indy_register_wallet_key_wrap_provider_works() {
+            let setup = Setup::empty();
+            wallet::register_wallet_key_wrap_provider(&setup.name).unwrap();
+        }
+    }
+
     mod create_wallet {
         use super::*;
 
@@ -186,6 +196,147 @@ mod high_cases {
 
             InmemWallet::cleanup();
         }
+
+        #[test]
+        fn indy_open_wallet_works_for_inmem() {
+            let setup = Setup::empty();
+
+            let config = json!({
+                "id": &setup.name,
+                "storage_type": "indy_inmem"
+            }).to_string();
+
+            wallet::create_wallet(&config, WALLET_CREDENTIALS).unwrap();
+            let wallet_handle = wallet::open_wallet(&config, WALLET_CREDENTIALS).unwrap();
+
+            let (did, verkey) = did::create_and_store_my_did(wallet_handle, None).unwrap();
+            non_secrets::add_wallet_record(wallet_handle, "type", "id1", "value1", Some(r#"{"tagName1":"str1","~tagName2":"5"}"#)).unwrap();
+            non_secrets::add_wallet_record(wallet_handle, "type", "id2", "value2", Some(r#"{"tagName1":"str2","~tagName2":"8"}"#)).unwrap();
+            wallet::close_wallet(wallet_handle).unwrap();
+
+            let wallet_handle = wallet::open_wallet(&config, WALLET_CREDENTIALS).unwrap();
+            assert_eq!(verkey, did::key_for_local_did(wallet_handle, &did).unwrap());
+
+            let search_handle = non_secrets::open_wallet_search(wallet_handle, "type",
+                                                                r#"{"tagName1":"str2","~tagName2":{"$gt":"6"}}"#, "{}").unwrap();
+            let records = non_secrets::fetch_wallet_search_next_records(wallet_handle, search_handle, 10).unwrap();
+            let records: serde_json::Value = serde_json::from_str(&records).unwrap();
+            assert_eq!(1, records["records"].as_array().unwrap().len());
+            assert_eq!("id2", records["records"][0]["id"].as_str().unwrap());
+            non_secrets::close_wallet_search(search_handle).unwrap();
+
+            wallet::close_wallet(wallet_handle).unwrap();
+            wallet::delete_wallet(&config, WALLET_CREDENTIALS).unwrap();
+
+            let res = wallet::open_wallet(&config, WALLET_CREDENTIALS);
+            assert_code!(ErrorCode::WalletNotFoundError, res);
+        }
+
+        #[test]
+        fn indy_open_wallet_works_for_read_only() {
+            let setup = Setup::empty();
+            let config = config(&setup.name);
+
+            let read_only_config = json!({
+                "id": &setup.name,
+                "read_only": true
+            }).to_string();
+
+            wallet::create_wallet(&config, WALLET_CREDENTIALS).unwrap();
+            let wallet_handle = wallet::open_wallet(&config, WALLET_CREDENTIALS).unwrap();
+            let (did, verkey) = did::create_and_store_my_did(wallet_handle, None).unwrap();
+
+            let read_only_handle_1 = wallet::open_wallet(&read_only_config, WALLET_CREDENTIALS).unwrap();
+            let read_only_handle_2 = wallet::open_wallet(&read_only_config, WALLET_CREDENTIALS).unwrap();
+
+            assert_eq!(verkey, did::key_for_local_did(read_only_handle_1, &did).unwrap());
+            assert_eq!(verkey, did::key_for_local_did(read_only_handle_2, &did).unwrap());
+
+            let res = did::create_and_store_my_did(read_only_handle_1, None);
+            assert_code!(ErrorCode::WalletReadOnlyError, res);
+
+            let res = non_secrets::add_wallet_record(read_only_handle_2, "type", "id1", "value1", None);
+            assert_code!(ErrorCode::WalletReadOnlyError, res);
+
+            wallet::close_wallet(read_only_handle_1).unwrap();
+            wallet::close_wallet(read_only_handle_2).unwrap();
+            wallet::close_wallet(wallet_handle).unwrap();
+            wallet::delete_wallet(&config, WALLET_CREDENTIALS).unwrap();
+        }
+
+        #[test]
+        #[cfg(feature = "file_key_wrap_provider")]
+        fn indy_open_wallet_works_for_file_key_wrap_provider() {
+            let setup = Setup::empty();
+            let config = config(&setup.name);
+            let credentials = wallet::file_key_wrap_credentials(&setup.name);
+
+            wallet::create_wallet(&config, &credentials).unwrap();
+            let wallet_handle = wallet::open_wallet(&config, &credentials).unwrap();
+            let (did, verkey) = did::create_and_store_my_did(wallet_handle, None).unwrap();
+            wallet::close_wallet(wallet_handle).unwrap();
+
+            let wallet_handle = wallet::open_wallet(&config, &credentials).unwrap();
+            assert_eq!(verkey, did::key_for_local_did(wallet_handle, &did).unwrap());
+            wallet::close_wallet(wallet_handle).unwrap();
+
+            wallet::delete_wallet(&config, &credentials).unwrap();
+        }
+
+        #[test]
+        fn indy_open_wallet_works_for_registered_key_wrap_provider() {
+            let setup = Setup::empty();
+            let config = config(&setup.name);
+
+            wallet::register_wallet_key_wrap_provider(&setup.name).unwrap();
+
+            let credentials = json!({
+                "key": wallet::TEST_KEY_WRAP_KEY_ID,
+                "key_derivation_method": "WRAPPED",
+                "key_wrap_provider": &setup.name,
+            }).to_string();
+
+            wallet::create_wallet(&config, &credentials).unwrap();
+            let wallet_handle = wallet::open_wallet(&config, &credentials).unwrap();
+            let (did, verkey) = did::create_and_store_my_did(wallet_handle, None).unwrap();
+            wallet::close_wallet(wallet_handle).unwrap();
+
+            let wallet_handle = wallet::open_wallet(&config, &credentials).unwrap();
+            assert_eq!(verkey, did::key_for_local_did(wallet_handle, &did).unwrap());
+            wallet::close_wallet(wallet_handle).unwrap();
+
+            wallet::delete_wallet(&config, &credentials).unwrap();
+        }
+
+        #[test]
+        fn indy_open_wallet_works_for_rekey_to_wrapped_key() {
+            let setup = Setup::empty();
+            let config = config(&setup.name);
+            let wrapped_credentials = wallet::file_key_wrap_credentials(&setup.name);
+
+            let rekey_credentials = {
+                let wrapped_credentials: serde_json::Value = serde_json::from_str(&wrapped_credentials).unwrap();
+                json!({
+                    "key": "8dvfYSt5d1taSd6yJdpjq4emkwsPDDLYxkNFysFD2cZY",
+                    "key_derivation_method": "RAW",
+                    "rekey": wrapped_credentials["key"],
+                    "rekey_derivation_method": "WRAPPED",
+                    "key_wrap_provider": "file",
+                }).to_string()
+            };
+
+            wallet::create_wallet(&config, WALLET_CREDENTIALS).unwrap();
+            let wallet_handle = wallet::open_wallet(&config, &rekey_credentials).unwrap();
+            wallet::close_wallet(wallet_handle).unwrap();
+
+            let res = wallet::open_wallet(&config, WALLET_CREDENTIALS);
+            assert_code!(ErrorCode::WalletAccessFailed, res);
+
+            let wallet_handle = wallet::open_wallet(&config, &wrapped_credentials).unwrap();
+            wallet::close_wallet(wallet_handle).unwrap();
+
+            wallet::delete_wallet(&config, &wrapped_credentials).unwrap();
+        }
     }
 
     mod close_wallet {
@@ -224,6 +375,49 @@ mod high_cases {
         }
     }
 
+    mod rotate_wallet_item_keys {
+        use super::*;
+
+        #[test]
+        fn indy_rotate_wallet_item_keys_works() {
+            let setup = Setup::empty();
+            let config = config(&setup.name);
+
+            wallet::create_wallet(&config, WALLET_CREDENTIALS).unwrap();
+            let wallet_handle = wallet::open_wallet(&config, WALLET_CREDENTIALS).unwrap();
+
+            let (did, verkey) = did::create_my_did(wallet_handle, "{}").unwrap();
+
+            wallet::rotate_wallet_item_keys(wallet_handle, WALLET_CREDENTIALS).unwrap();
+
+            assert_eq!(verkey, did::key_for_local_did(wallet_handle, &did).unwrap());
+            wallet::close_wallet(wallet_handle).unwrap();
+
+            let wallet_handle = wallet::open_wallet(&config, WALLET_CREDENTIALS).unwrap();
+            assert_eq!(verkey, did::key_for_local_did(wallet_handle, &did).unwrap());
+            wallet::close_wallet(wallet_handle).unwrap();
+
+            wallet::delete_wallet(&config, WALLET_CREDENTIALS).unwrap();
+        }
+
+        #[test]
+        fn indy_rotate_wallet_item_keys_works_for_plugged() {
+            Setup::empty();
+            InmemWallet::cleanup();
+
+            let (wallet_handle, _) = wallet::create_and_open_plugged_wallet().unwrap();
+
+            let (did, verkey) = did::create_my_did(wallet_handle, "{}").unwrap();
+
+            wallet::rotate_wallet_item_keys(wallet_handle, WALLET_CREDENTIALS).unwrap();
+
+            assert_eq!(verkey, did::key_for_local_did(wallet_handle, &did).unwrap());
+            wallet::close_wallet(wallet_handle).unwrap();
+
+            InmemWallet::cleanup();
+        }
+    }
+
     mod export_wallet {
         use super::*;
 
@@ -281,6 +475,317 @@ mod high_cases {
             wallet::close_and_delete_wallet(wallet_handle, &config).unwrap();
             cleanup_file(&path);
         }
+
+        #[test]
+        fn indy_import_wallet_with_report_works() {
+            let setup = Setup::empty();
+            let config = config(&setup.name);
+
+            let path = wallet::export_wallet_path(&setup.name);
+            let config_json = wallet::prepare_export_wallet_config(&path);
+
+            let (wallet_handle, wallet_config) = wallet::create_and_open_default_wallet(&setup.name).unwrap();
+
+            non_secrets::add_wallet_record(wallet_handle, "type1", "id1", "value1", None).unwrap();
+            non_secrets::add_wallet_record(wallet_handle, "type1", "id2", "value2", None).unwrap();
+
+            cleanup_file(&path);
+            wallet::export_wallet(wallet_handle, &config_json).unwrap();
+
+            wallet::close_wallet(wallet_handle).unwrap();
+            wallet::delete_wallet(&wallet_config, WALLET_CREDENTIALS).unwrap();
+
+            let report = wallet::import_wallet_with_report(&config, WALLET_CREDENTIALS, &config_json).unwrap();
+            let report: serde_json::Value = serde_json::from_str(&report).unwrap();
+            assert_eq!(json!({"imported": 2, "skipped": 0, "expected": 2}), report);
+
+            let wallet_handle = wallet::open_wallet(&config, WALLET_CREDENTIALS).unwrap();
+            non_secrets::get_wallet_record(wallet_handle, "type1", "id1", "{}").unwrap();
+            non_secrets::get_wallet_record(wallet_handle, "type1", "id2", "{}").unwrap();
+
+            wallet::close_and_delete_wallet(wallet_handle, &config).unwrap();
+            cleanup_file(&path);
+        }
+    }
+
+    mod export_wallet_to_sink {
+        use super::*;
+
+        #[test]
+        fn indy_export_wallet_to_sink_works() {
+            let setup = Setup::wallet();
+
+            did::create_my_did(setup.wallet_handle, "{}").unwrap();
+
+            let config_json = json!({"key": "export_key"}).to_string();
+            let buffer = wallet::export_wallet_to_buffer(setup.wallet_handle, &config_json).unwrap();
+
+            assert!(!buffer.is_empty());
+        }
+    }
+
+    mod import_wallet_from_source {
+        use super::*;
+
+        #[test]
+        fn indy_import_wallet_from_source_works() {
+            let setup = Setup::empty();
+            let config = config(&setup.name);
+
+            let config_json = json!({"key": "export_key"}).to_string();
+
+            let (wallet_handle, wallet_config) = wallet::create_and_open_default_wallet(&setup.name).unwrap();
+
+            let (did, _) = did::create_my_did(wallet_handle, "{}").unwrap();
+            did::set_did_metadata(wallet_handle, &did, METADATA).unwrap();
+
+            let did_with_meta = did::get_my_did_with_metadata(wallet_handle, &did).unwrap();
+
+            let buffer = wallet::export_wallet_to_buffer(wallet_handle, &config_json).unwrap();
+
+            wallet::close_wallet(wallet_handle).unwrap();
+            wallet::delete_wallet(&wallet_config, WALLET_CREDENTIALS).unwrap();
+
+            wallet::import_wallet_from_buffer(&config, WALLET_CREDENTIALS, &config_json, buffer).unwrap();
+
+            let wallet_handle = wallet::open_wallet(&config, WALLET_CREDENTIALS).unwrap();
+
+            let did_with_meta_after_import = did::get_my_did_with_metadata(wallet_handle, &did).unwrap();
+
+            assert_eq!(did_with_meta, did_with_meta_after_import);
+
+            wallet::close_and_delete_wallet(wallet_handle, &config).unwrap();
+        }
+
+        #[test]
+        fn indy_import_wallet_from_source_works_for_types_and_query_filter() {
+            let setup = Setup::empty();
+            let config = config(&setup.name);
+
+            let (wallet_handle, wallet_config) = wallet::create_and_open_default_wallet(&setup.name).unwrap();
+
+            non_secrets::add_wallet_record(wallet_handle, "type1", "id1", "value1", Some(r#"{"tag": "1"}"#)).unwrap();
+            non_secrets::add_wallet_record(wallet_handle, "type1", "id2", "value2", Some(r#"{"tag": "2"}"#)).unwrap();
+            non_secrets::add_wallet_record(wallet_handle, "type2", "id3", "value3", Some(r#"{"tag": "1"}"#)).unwrap();
+
+            let config_json = json!({
+                "key": "export_key",
+                "types": ["type1"],
+                "query": {"tag": "1"},
+            }).to_string();
+
+            let buffer = wallet::export_wallet_to_buffer(wallet_handle, &config_json).unwrap();
+
+            wallet::close_wallet(wallet_handle).unwrap();
+            wallet::delete_wallet(&wallet_config, WALLET_CREDENTIALS).unwrap();
+
+            wallet::import_wallet_from_buffer(&config, WALLET_CREDENTIALS, &config_json, buffer).unwrap();
+
+            let wallet_handle = wallet::open_wallet(&config, WALLET_CREDENTIALS).unwrap();
+
+            non_secrets::get_wallet_record(wallet_handle, "type1", "id1", "{}").unwrap();
+
+            let res = non_secrets::get_wallet_record(wallet_handle, "type1", "id2", "{}");
+            assert_code!(ErrorCode::WalletItemNotFound, res);
+
+            let res = non_secrets::get_wallet_record(wallet_handle, "type2", "id3", "{}");
+            assert_code!(ErrorCode::WalletItemNotFound, res);
+
+            wallet::close_and_delete_wallet(wallet_handle, &config).unwrap();
+        }
+    }
+
+    mod import_wallet_delta {
+        use super::*;
+
+        #[test]
+        fn indy_import_wallet_delta_works() {
+            let setup = Setup::empty();
+
+            let base_path = wallet::export_wallet_path(&format!("{}_base", setup.name));
+            let delta_path = wallet::export_wallet_path(&format!("{}_delta", setup.name));
+            let base_config_json = wallet::prepare_export_wallet_config(&base_path);
+
+            let (wallet_handle, wallet_config) = wallet::create_and_open_default_wallet(&setup.name).unwrap();
+
+            non_secrets::add_wallet_record(wallet_handle, "type1", "id1", "value1", None).unwrap();
+            non_secrets::add_wallet_record(wallet_handle, "type1", "id2", "value2", None).unwrap();
+
+            cleanup_file(&base_path);
+            wallet::export_wallet(wallet_handle, &base_config_json).unwrap();
+            let revision = wallet::get_wallet_revision(wallet_handle).unwrap();
+
+            non_secrets::update_wallet_record_value(wallet_handle, "type1", "id1", "value1_updated").unwrap();
+            non_secrets::delete_wallet_record(wallet_handle, "type1", "id2").unwrap();
+            non_secrets::add_wallet_record(wallet_handle, "type1", "id3", "value3", None).unwrap();
+
+            let delta_config_json = json!({
+                "path": delta_path.to_str().unwrap(),
+                "key": "export_key",
+                "since_revision": revision,
+            }).to_string();
+
+            cleanup_file(&delta_path);
+            wallet::export_wallet(wallet_handle, &delta_config_json).unwrap();
+
+            wallet::close_wallet(wallet_handle).unwrap();
+            wallet::delete_wallet(&wallet_config, WALLET_CREDENTIALS).unwrap();
+
+            wallet::import_wallet(&wallet_config, WALLET_CREDENTIALS, &base_config_json).unwrap();
+            let wallet_handle = wallet::open_wallet(&wallet_config, WALLET_CREDENTIALS).unwrap();
+
+            wallet::import_wallet_delta(wallet_handle, &delta_config_json).unwrap();
+
+            let record = non_secrets::get_wallet_record(wallet_handle, "type1", "id1", "{}").unwrap();
+            assert!(record.contains("value1_updated"));
+
+            let res = non_secrets::get_wallet_record(wallet_handle, "type1", "id2", "{}");
+            assert_code!(ErrorCode::WalletItemNotFound, res);
+
+            non_secrets::get_wallet_record(wallet_handle, "type1", "id3", "{}").unwrap();
+
+            wallet::close_and_delete_wallet(wallet_handle, &wallet_config).unwrap();
+            cleanup_file(&base_path);
+            cleanup_file(&delta_path);
+        }
+    }
+
+    mod migrate_wallet {
+        use super::*;
+
+        #[test]
+        fn indy_migrate_wallet_works_for_plugged_storage() {
+            let setup = Setup::empty();
+
+            let (wallet_handle, src_config) = wallet::create_and_open_default_wallet(&setup.name).unwrap();
+
+            non_secrets::add_wallet_record(wallet_handle, "type1", "id1", "value1", Some(r#"{"tag": "1"}"#)).unwrap();
+            non_secrets::add_wallet_record(wallet_handle, "type2", "id2", "value2", None).unwrap();
+
+            wallet::close_wallet(wallet_handle).unwrap();
+
+            wallet::register_wallet_storage(INMEM_TYPE, false).unwrap();
+            let dst_config = json!({"id": format!("{}_dst", setup.name), "storage_type": INMEM_TYPE}).to_string();
+
+            wallet::migrate_wallet(&src_config, WALLET_CREDENTIALS, &dst_config, WALLET_CREDENTIALS_ARGON2I_INT).unwrap();
+
+            let wallet_handle = wallet::open_wallet(&dst_config, WALLET_CREDENTIALS_ARGON2I_INT).unwrap();
+
+            let record = non_secrets::get_wallet_record(wallet_handle, "type1", "id1", r#"{"retrieveTags": true}"#).unwrap();
+            assert!(record.contains("value1"));
+            assert!(record.contains(r#""tag":"1""#));
+            non_secrets::get_wallet_record(wallet_handle, "type2", "id2", "{}").unwrap();
+
+            wallet::close_wallet(wallet_handle).unwrap();
+            wallet::delete_wallet(&dst_config, WALLET_CREDENTIALS_ARGON2I_INT).unwrap();
+            wallet::delete_wallet(&src_config, WALLET_CREDENTIALS).unwrap();
+        }
+    }
+
+    mod wallet_profiles {
+        use super::*;
+
+        #[test]
+        fn indy_wallet_profiles_work() {
+            let setup = Setup::empty();
+            let config = config(&setup.name);
+            let profile_config = json!({"id": setup.name, "profile": "profile1"}).to_string();
+
+            wallet::create_wallet(&config, WALLET_CREDENTIALS).unwrap();
+            wallet::create_wallet(&profile_config, WALLET_CREDENTIALS_ARGON2I_INT).unwrap();
+
+            let profiles = wallet::list_wallet_profiles(&config, WALLET_CREDENTIALS).unwrap();
+            let profiles: serde_json::Value = serde_json::from_str(&profiles).unwrap();
+            assert_eq!(json!(["profile1"]), profiles);
+
+            let wallet_handle = wallet::open_wallet(&config, WALLET_CREDENTIALS).unwrap();
+            let profile_handle = wallet::open_wallet(&profile_config, WALLET_CREDENTIALS_ARGON2I_INT).unwrap();
+
+            non_secrets::add_wallet_record(wallet_handle, "type1", "id1", "value1", None).unwrap();
+            non_secrets::add_wallet_record(profile_handle, "type1", "id1", "value2", None).unwrap();
+
+            let record = non_secrets::get_wallet_record(wallet_handle, "type1", "id1", "{}").unwrap();
+            assert!(record.contains("value1"));
+
+            let record = non_secrets::get_wallet_record(profile_handle, "type1", "id1", "{}").unwrap();
+            assert!(record.contains("value2"));
+
+            wallet::close_wallet(profile_handle).unwrap();
+            wallet::close_wallet(wallet_handle).unwrap();
+
+            wallet::delete_wallet(&profile_config, WALLET_CREDENTIALS_ARGON2I_INT).unwrap();
+
+            let profiles = wallet::list_wallet_profiles(&config, WALLET_CREDENTIALS).unwrap();
+            assert_eq!("[]", profiles);
+
+            wallet::delete_wallet(&config, WALLET_CREDENTIALS).unwrap();
+        }
+    }
+
+    mod get_wallet_revision {
+        use super::*;
+
+        #[test]
+        fn indy_get_wallet_revision_works() {
+            let setup = Setup::wallet();
+
+            let revision = wallet::get_wallet_revision(setup.wallet_handle).unwrap();
+
+            non_secrets::add_wallet_record(setup.wallet_handle, "type1", "id1", "value1", None).unwrap();
+
+            assert!(wallet::get_wallet_revision(setup.wallet_handle).unwrap() > revision);
+        }
+    }
+
+    mod check_wallet {
+        use super::*;
+
+        #[test]
+        fn indy_check_wallet_works() {
+            let setup = Setup::wallet();
+
+            did::create_my_did(setup.wallet_handle, "{}").unwrap();
+            non_secrets::add_wallet_record(setup.wallet_handle, "type1", "id1", "value1", Some(r#"{"tag": "1"}"#)).unwrap();
+
+            let report = wallet::check_wallet(setup.wallet_handle, None).unwrap();
+            let report: serde_json::Value = serde_json::from_str(&report).unwrap();
+
+            assert_eq!(3, report["records"].as_u64().unwrap()); // did, key and non secret record
+            assert_eq!(0, report["orphan_tags"].as_u64().unwrap());
+            assert!(report["problems"].as_array().unwrap().is_empty());
+            assert_eq!(false, report["repaired"].as_bool().unwrap());
+        }
+
+        #[test]
+        fn indy_check_wallet_works_for_repair() {
+            let setup = Setup::wallet();
+
+            did::create_my_did(setup.wallet_handle, "{}").unwrap();
+
+            let report = wallet::check_wallet(setup.wallet_handle, Some(r#"{"repair": true}"#)).unwrap();
+            let report: serde_json::Value = serde_json::from_str(&report).unwrap();
+
+            assert!(report["problems"].as_array().unwrap().is_empty());
+            assert_eq!(true, report["repaired"].as_bool().unwrap());
+        }
+
+        #[test]
+        fn indy_check_wallet_works_for_plugged() {
+            Setup::empty();
+            InmemWallet::cleanup();
+
+            let (wallet_handle, _) = wallet::create_and_open_plugged_wallet().unwrap();
+            did::create_my_did(wallet_handle, "{}").unwrap();
+
+            let report = wallet::check_wallet(wallet_handle, None).unwrap();
+            let report: serde_json::Value = serde_json::from_str(&report).unwrap();
+
+            assert_eq!(2, report["records"].as_u64().unwrap());
+            assert!(report["problems"].as_array().unwrap().is_empty());
+
+            wallet::close_wallet(wallet_handle).unwrap();
+            InmemWallet::cleanup();
+        }
     }
 
     mod generate_wallet_key {
@@ -361,12 +866,45 @@ mod medium_cases {
             };
             assert_eq!(ErrorCode::CommonInvalidParam3, res);
 
-            InmemWallet::cleanup();
-        }
-    }
+            InmemWallet::cleanup();
+        }
+    }
+
+    mod register_wallet_key_wrap_provider {
+        use super::*;
+
+        #[test]
+        fn indy_register_wallet_key_wrap_provider_does_not_work_twice_with_same_name() {
+            let setup = Setup::empty();
+
+            wallet::register_wallet_key_wrap_provider(&setup.name).unwrap();
+            let res = wallet::register_wallet_key_wrap_provider(&setup.name);
+            assert_code!(ErrorCode::CommonInvalidState, res);
+        }
+
+        #[test]
+        fn indy_register_wallet_key_wrap_provider_does_not_work_for_file_name() {
+            Setup::empty();
+
+            let res = wallet::register_wallet_key_wrap_provider("file");
+            assert_code!(ErrorCode::CommonInvalidState, res);
+        }
+    }
+
+    mod create_wallet {
+        use super::*;
+
+        #[test]
+        fn indy_create_wallet_works_for_wrapped_key_without_provider() {
+            let setup = Setup::empty();
+            let config = config(&setup.name);
+
+            let res = wallet::create_wallet(&config, r#"{"key":"key_id", "key_derivation_method":"WRAPPED"}"#);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
 
-    mod create_wallet {
-        use super::*;
+            let res = wallet::create_wallet(&config, r#"{"key":"key_id", "key_derivation_method":"WRAPPED", "key_wrap_provider":"unknown"}"#);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
 
         #[test]
         fn indy_create_wallet_works_for_empty_type() {
@@ -473,6 +1011,41 @@ mod medium_cases {
             assert_code!(ErrorCode::WalletNotFoundError, res);
         }
 
+        #[test]
+        fn indy_open_wallet_works_for_wrapped_key_and_other_key_id() {
+            let setup = Setup::empty();
+            let config = config(&setup.name);
+
+            wallet::register_wallet_key_wrap_provider(&setup.name).unwrap();
+
+            let credentials = json!({
+                "key": wallet::TEST_KEY_WRAP_KEY_ID,
+                "key_derivation_method": "WRAPPED",
+                "key_wrap_provider": &setup.name,
+            }).to_string();
+            wallet::create_wallet(&config, &credentials).unwrap();
+
+            let other_credentials = json!({
+                "key": "other_key_id",
+                "key_derivation_method": "WRAPPED",
+                "key_wrap_provider": &setup.name,
+            }).to_string();
+
+            let res = wallet::open_wallet(&config, &other_credentials);
+            assert_code!(ErrorCode::WalletAccessFailed, res);
+
+            let other_credentials = json!({
+                "key": wallet::TEST_KEY_WRAP_KEY_ID,
+                "key_derivation_method": "WRAPPED",
+                "key_wrap_provider": "file",
+            }).to_string();
+
+            let res = wallet::open_wallet(&config, &other_credentials);
+            assert_code!(ErrorCode::WalletAccessFailed, res);
+
+            wallet::delete_wallet(&config, &credentials).unwrap();
+        }
+
         #[test]
         fn indy_open_wallet_works_for_twice() {
             let setup = Setup::empty();
@@ -594,6 +1167,26 @@ mod medium_cases {
         }
     }
 
+    mod rotate_wallet_item_keys {
+        use super::*;
+
+        #[test]
+        fn indy_rotate_wallet_item_keys_works_for_invalid_handle() {
+            Setup::empty();
+
+            let res = wallet::rotate_wallet_item_keys(INVALID_WALLET_HANDLE, WALLET_CREDENTIALS);
+            assert_code!(ErrorCode::WalletInvalidHandle, res);
+        }
+
+        #[test]
+        fn indy_rotate_wallet_item_keys_works_for_invalid_credentials() {
+            let setup = Setup::wallet();
+
+            let res = wallet::rotate_wallet_item_keys(setup.wallet_handle, WALLET_CREDENTIALS_ARGON2I_MOD);
+            assert_code!(ErrorCode::WalletAccessFailed, res);
+        }
+    }
+
     mod export_wallet {
         use super::*;
         use std::fs;
@@ -633,6 +1226,232 @@ mod medium_cases {
             let res = wallet::export_wallet(INVALID_WALLET_HANDLE, &config_json);
             assert_code!(ErrorCode::WalletInvalidHandle, res);
         }
+
+        #[test]
+        fn indy_export_wallet_returns_error_if_query_without_types() {
+            let setup = Setup::wallet();
+
+            let path = wallet::export_wallet_path(&setup.name);
+            let config_json = json!({
+                "path": path.to_str().unwrap(),
+                "key": "export_key",
+                "query": {"tag": "1"},
+            }).to_string();
+
+            let res = wallet::export_wallet(setup.wallet_handle, &config_json);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
+    }
+
+    mod export_wallet_to_sink {
+        use super::*;
+
+        #[test]
+        fn indy_export_wallet_to_sink_returns_error_if_invalid_config() {
+            let setup = Setup::wallet();
+
+            let res = wallet::export_wallet_to_buffer(setup.wallet_handle, "{}");
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
+
+        #[test]
+        fn indy_export_wallet_to_sink_returns_error_if_invalid_handle() {
+            Setup::empty();
+
+            let config_json = json!({"key": "export_key"}).to_string();
+
+            let res = wallet::export_wallet_to_buffer(INVALID_WALLET_HANDLE, &config_json);
+            assert_code!(ErrorCode::WalletInvalidHandle, res);
+        }
+    }
+
+    mod import_wallet_from_source {
+        use super::*;
+
+        #[test]
+        fn indy_import_wallet_from_source_returns_error_if_empty_source() {
+            let setup = Setup::empty();
+            let config = config(&setup.name);
+
+            let config_json = json!({"key": "export_key"}).to_string();
+
+            let res = wallet::import_wallet_from_buffer(&config, WALLET_CREDENTIALS, &config_json, Vec::new());
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+
+            let res = wallet::open_wallet(&config, WALLET_CREDENTIALS);
+            assert_code!(ErrorCode::WalletNotFoundError, res);
+        }
+    }
+
+    mod import_wallet_delta {
+        use super::*;
+
+        #[test]
+        fn indy_import_wallet_delta_returns_error_if_invalid_handle() {
+            let setup = Setup::empty();
+
+            let path = wallet::export_wallet_path(&setup.name);
+            let config_json = wallet::prepare_export_wallet_config(&path);
+
+            let res = wallet::import_wallet_delta(INVALID_WALLET_HANDLE, &config_json);
+            assert_code!(ErrorCode::WalletInvalidHandle, res);
+        }
+
+        #[test]
+        fn indy_import_wallet_delta_returns_error_if_path_doesnt_exist() {
+            let setup = Setup::wallet();
+
+            let path = wallet::export_wallet_path(&setup.name);
+            cleanup_file(&path);
+            let config_json = wallet::prepare_export_wallet_config(&path);
+
+            let res = wallet::import_wallet_delta(setup.wallet_handle, &config_json);
+            assert_code!(ErrorCode::CommonIOError, res);
+        }
+    }
+
+    mod migrate_wallet {
+        use super::*;
+
+        #[test]
+        fn indy_migrate_wallet_returns_error_for_opened_source() {
+            let setup = Setup::wallet();
+
+            let dst_config = config(&format!("{}_dst", setup.name));
+
+            let res = wallet::migrate_wallet(&setup.wallet_config, WALLET_CREDENTIALS, &dst_config, WALLET_CREDENTIALS);
+            assert_code!(ErrorCode::WalletAlreadyOpenedError, res);
+        }
+
+        #[test]
+        fn indy_migrate_wallet_returns_error_for_invalid_source_credentials() {
+            let setup = Setup::empty();
+            let src_config = config(&format!("{}_src", setup.name));
+            let dst_config = config(&format!("{}_dst", setup.name));
+
+            wallet::create_wallet(&src_config, WALLET_CREDENTIALS).unwrap();
+
+            let res = wallet::migrate_wallet(&src_config, WALLET_CREDENTIALS_ARGON2I_INT, &dst_config, WALLET_CREDENTIALS);
+            assert_code!(ErrorCode::WalletAccessFailed, res);
+
+            let res = wallet::open_wallet(&dst_config, WALLET_CREDENTIALS);
+            assert_code!(ErrorCode::WalletNotFoundError, res);
+
+            wallet::delete_wallet(&src_config, WALLET_CREDENTIALS).unwrap();
+        }
+
+        #[test]
+        fn indy_migrate_wallet_returns_error_for_existing_destination() {
+            let setup = Setup::empty();
+            let src_config = config(&format!("{}_src", setup.name));
+            let dst_config = config(&format!("{}_dst", setup.name));
+
+            wallet::create_wallet(&src_config, WALLET_CREDENTIALS).unwrap();
+            wallet::create_wallet(&dst_config, WALLET_CREDENTIALS).unwrap();
+
+            let res = wallet::migrate_wallet(&src_config, WALLET_CREDENTIALS, &dst_config, WALLET_CREDENTIALS);
+            assert_code!(ErrorCode::WalletAlreadyExistsError, res);
+
+            wallet::delete_wallet(&src_config, WALLET_CREDENTIALS).unwrap();
+            wallet::delete_wallet(&dst_config, WALLET_CREDENTIALS).unwrap();
+        }
+    }
+
+    mod wallet_profiles {
+        use super::*;
+
+        #[test]
+        fn indy_create_wallet_works_for_profile_of_not_created_storage() {
+            let setup = Setup::empty();
+            let profile_config = json!({"id": setup.name, "profile": "profile1"}).to_string();
+
+            let res = wallet::create_wallet(&profile_config, WALLET_CREDENTIALS);
+            assert_code!(ErrorCode::WalletNotFoundError, res);
+        }
+
+        #[test]
+        fn indy_open_wallet_works_for_profile_and_credentials_of_default_profile() {
+            let setup = Setup::empty();
+            let config = config(&setup.name);
+            let profile_config = json!({"id": setup.name, "profile": "profile1"}).to_string();
+
+            wallet::create_wallet(&config, WALLET_CREDENTIALS).unwrap();
+            wallet::create_wallet(&profile_config, WALLET_CREDENTIALS_ARGON2I_INT).unwrap();
+
+            let res = wallet::open_wallet(&profile_config, WALLET_CREDENTIALS);
+            assert_code!(ErrorCode::WalletAccessFailed, res);
+
+            let res = wallet::list_wallet_profiles(&config, WALLET_CREDENTIALS_ARGON2I_INT);
+            assert_code!(ErrorCode::WalletAccessFailed, res);
+
+            let res = wallet::list_wallet_profiles(&profile_config, WALLET_CREDENTIALS_ARGON2I_INT);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+
+            wallet::delete_wallet(&config, WALLET_CREDENTIALS).unwrap();
+        }
+
+        #[test]
+        fn indy_delete_wallet_works_for_storage_with_opened_profile() {
+            let setup = Setup::empty();
+            let config = config(&setup.name);
+            let profile_config = json!({"id": setup.name, "profile": "profile1"}).to_string();
+
+            wallet::create_wallet(&config, WALLET_CREDENTIALS).unwrap();
+            wallet::create_wallet(&profile_config, WALLET_CREDENTIALS).unwrap();
+            let profile_handle = wallet::open_wallet(&profile_config, WALLET_CREDENTIALS).unwrap();
+
+            let res = wallet::delete_wallet(&config, WALLET_CREDENTIALS);
+            assert_code!(ErrorCode::CommonInvalidState, res);
+
+            wallet::close_wallet(profile_handle).unwrap();
+            wallet::delete_wallet(&config, WALLET_CREDENTIALS).unwrap();
+        }
+    }
+
+    mod get_wallet_revision {
+        use super::*;
+
+        #[test]
+        fn indy_get_wallet_revision_returns_error_if_invalid_handle() {
+            Setup::empty();
+
+            let res = wallet::get_wallet_revision(INVALID_WALLET_HANDLE);
+            assert_code!(ErrorCode::WalletInvalidHandle, res);
+        }
+
+        #[test]
+        fn indy_get_wallet_revision_returns_error_for_plugged() {
+            Setup::empty();
+            InmemWallet::cleanup();
+
+            let (wallet_handle, _) = wallet::create_and_open_plugged_wallet().unwrap();
+
+            let res = wallet::get_wallet_revision(wallet_handle);
+            assert_code!(ErrorCode::CommonInvalidState, res);
+
+            wallet::close_wallet(wallet_handle).unwrap();
+            InmemWallet::cleanup();
+        }
+    }
+
+    mod check_wallet {
+        use super::*;
+
+        #[test]
+        fn indy_check_wallet_returns_error_if_invalid_handle() {
+            Setup::empty();
+
+            let res = wallet::check_wallet(INVALID_WALLET_HANDLE, None);
+            assert_code!(ErrorCode::WalletInvalidHandle, res);
+        }
+
+        #[test]
+        fn indy_check_wallet_returns_error_if_invalid_config() {
+            let setup = Setup::wallet();
+
+            let res = wallet::check_wallet(setup.wallet_handle, Some(r#"{"repair": "yes"}"#));
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
     }
 
     mod import_wallet {
@@ -717,6 +1536,82 @@ mod medium_cases {
 
             cleanup_file(&path);
         }
+
+        #[test]
+        fn indy_import_wallet_works_for_version1_export() {
+            let setup = Setup::empty();
+            let config = config(&setup.name);
+
+            let (wallet_handle, wallet_config) = wallet::create_and_open_default_wallet(&setup.name).unwrap();
+
+            non_secrets::add_wallet_record(wallet_handle, "type1", "id1", "value1", None).unwrap();
+
+            let config_json = json!({"key": "export_key", "version": 1}).to_string();
+            let buffer = wallet::export_wallet_to_buffer(wallet_handle, &config_json).unwrap();
+
+            wallet::close_wallet(wallet_handle).unwrap();
+            wallet::delete_wallet(&wallet_config, WALLET_CREDENTIALS).unwrap();
+
+            wallet::import_wallet_from_buffer(&config, WALLET_CREDENTIALS, &json!({"key": "export_key"}).to_string(), buffer).unwrap();
+
+            let wallet_handle = wallet::open_wallet(&config, WALLET_CREDENTIALS).unwrap();
+            non_secrets::get_wallet_record(wallet_handle, "type1", "id1", "{}").unwrap();
+
+            wallet::close_and_delete_wallet(wallet_handle, &config).unwrap();
+        }
+
+        #[test]
+        fn indy_import_wallet_works_for_tolerant_mode_and_damaged_record() {
+            let setup = Setup::empty();
+            let config = config(&setup.name);
+
+            let (wallet_handle, wallet_config) = wallet::create_and_open_default_wallet(&setup.name).unwrap();
+
+            non_secrets::add_wallet_record(wallet_handle, "type1", "id1", "value1", None).unwrap();
+            non_secrets::add_wallet_record(wallet_handle, "type1", "id2", "value2", None).unwrap();
+
+            let config_json = json!({"key": "export_key", "version": 2}).to_string();
+            let mut buffer = wallet::export_wallet_to_buffer(wallet_handle, &config_json).unwrap();
+
+            wallet::close_wallet(wallet_handle).unwrap();
+            wallet::delete_wallet(&wallet_config, WALLET_CREDENTIALS).unwrap();
+
+            // Damage first record chunk that follows plain header and header hash chunk
+            let header_len = buffer[0] as usize + ((buffer[1] as usize) << 8);
+            let hash_chunk_len = buffer[4 + header_len] as usize;
+            let pos = 4 + header_len + 4 + hash_chunk_len + 4 + 2;
+            buffer[pos] ^= 0xFF;
+
+            let res = wallet::import_wallet_from_buffer(&config, WALLET_CREDENTIALS, &config_json, buffer.clone());
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+
+            let import_config_json = json!({"key": "export_key", "tolerant": true}).to_string();
+            wallet::import_wallet_from_buffer(&config, WALLET_CREDENTIALS, &import_config_json, buffer).unwrap();
+
+            let wallet_handle = wallet::open_wallet(&config, WALLET_CREDENTIALS).unwrap();
+
+            let res = non_secrets::get_wallet_record(wallet_handle, "type1", "id1", "{}")
+                .and(non_secrets::get_wallet_record(wallet_handle, "type1", "id2", "{}"));
+            assert_code!(ErrorCode::WalletItemNotFound, res);
+
+            wallet::close_and_delete_wallet(wallet_handle, &config).unwrap();
+        }
+
+        #[test]
+        fn indy_import_wallet_works_for_unsupported_version() {
+            let setup = Setup::empty();
+            let config = config(&setup.name);
+
+            let path = wallet::export_wallet_path(&setup.name);
+            let config_json = json!({
+                "path": path.to_str().unwrap(),
+                "key": "export_key",
+                "version": 3,
+            }).to_string();
+
+            let res = wallet::import_wallet(&config, WALLET_CREDENTIALS, &config_json);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
     }
 }
 
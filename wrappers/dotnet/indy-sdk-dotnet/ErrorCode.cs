@@ -239,6 +239,11 @@
         /// </summary>
         WalletQueryError = 214,
 
+        /// <summary>
+        /// Returned if mutating operation is called on the wallet opened in read-only mode
+        /// </summary>
+        WalletReadOnlyError = 215,
+
         // Ledger errors
 
         /// <summary>
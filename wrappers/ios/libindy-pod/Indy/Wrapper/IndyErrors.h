@@ -100,6 +100,9 @@ typedef NS_ENUM(NSInteger, IndyErrorCode)
 
     // Returned if provided wallet query is invalid
     WalletQueryError = 214,
+
+    // Returned if mutating operation is called on the wallet opened in read-only mode
+    WalletReadOnlyError = 215,
     
     // Ledger errors
     // Trying to open pool ledger that wasn't created before
@@ -177,6 +177,11 @@ public enum ErrorCode {
 	 */
 	WalletQueryError(214),
 
+	/**
+	 * Returned if mutating operation is called on the wallet opened in read-only mode
+	 */
+	WalletReadOnlyError(215),
+
 	// Ledger errors
 	
 	/**
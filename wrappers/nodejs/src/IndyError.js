@@ -33,6 +33,7 @@ const errors = {
   212: 'WalletItemNotFound',
   213: 'WalletItemAlreadyExists',
   214: 'WalletQueryError',
+  215: 'WalletReadOnlyError',
   300: 'PoolLedgerNotCreatedError',
   301: 'PoolLedgerInvalidPoolHandle',
   302: 'PoolLedgerTerminated',
@@ -99,6 +99,9 @@ class ErrorCode(IntEnum):
     # Returned if provided wallet query is invalid
     WalletQueryError = 214
 
+    # Returned if mutating operation is called on the wallet opened in read-only mode
+    WalletReadOnlyError = 215
+
     # Ledger errors
     # Trying to open pool ledger that wasn't created before
     PoolLedgerNotCreatedError = 300
@@ -287,6 +290,9 @@ class WalletItemAlreadyExists(IndyError):
 class WalletQueryError(IndyError):
     """ Returned if provided wallet query is invalid """
 
+class WalletReadOnlyError(IndyError):
+    """ Returned if mutating operation is called on the wallet opened in read-only mode """
+
 # Ledger errors
 class PoolLedgerNotCreatedError(IndyError):
     """ Trying to open pool ledger that wasn't created before """
@@ -399,6 +405,7 @@ def errorcode_to_exception(errorcode):
         ErrorCode.WalletItemNotFound: WalletItemNotFound,
         ErrorCode.WalletItemAlreadyExists: WalletItemAlreadyExists,
         ErrorCode.WalletQueryError: WalletQueryError,
+        ErrorCode.WalletReadOnlyError: WalletReadOnlyError,
         # Pool Errors
         ErrorCode.PoolLedgerNotCreatedError: PoolLedgerNotCreatedError,
         ErrorCode.PoolLedgerInvalidPoolHandle: PoolLedgerInvalidPoolHandle,
@@ -140,6 +140,13 @@ extern {
                                           query_json: CString,
                                           cb: Option<ResponseI32UsizeCB>) -> Error;
 
+    #[no_mangle]
+    pub fn indy_prover_search_credentials_with_options(command_handle: CommandHandle,
+                                                       wallet_handle: WalletHandle,
+                                                       query_json: CString,
+                                                       options_json: CString,
+                                                       cb: Option<ResponseI32UsizeCB>) -> Error;
+
     #[no_mangle]
     pub fn indy_prover_fetch_credentials(command_handle: CommandHandle,
                                          search_handle: SearchHandle,
@@ -151,6 +158,20 @@ extern {
                                                 search_handle: SearchHandle,
                                                 cb: Option<ResponseEmptyCB>) -> Error;
 
+    #[no_mangle]
+    pub fn indy_prover_count_credentials_by_tag(command_handle: CommandHandle,
+                                                wallet_handle: WalletHandle,
+                                                query_json: CString,
+                                                tag_name: CString,
+                                                cb: Option<ResponseStringCB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_prover_get_distinct_credential_tag_values(command_handle: CommandHandle,
+                                                          wallet_handle: WalletHandle,
+                                                          query_json: CString,
+                                                          tag_name: CString,
+                                                          cb: Option<ResponseStringCB>) -> Error;
+
     #[no_mangle]
     pub fn indy_prover_get_credentials_for_proof_req(command_handle: CommandHandle,
                                                      wallet_handle: WalletHandle,
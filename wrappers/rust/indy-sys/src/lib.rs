@@ -52,6 +52,8 @@ pub type Error = i32;
 
 pub type ResponseEmptyCB = extern fn(xcommand_handle: CommandHandle, err: Error);
 pub type ResponseBoolCB = extern fn(xcommand_handle: CommandHandle, err: Error, bool1: bool);
+pub type ResponseU64CB = extern fn(xcommand_handle: CommandHandle, err: Error, num: u64);
+pub type ResponseUsizeCB = extern fn(xcommand_handle: CommandHandle, err: Error, num: usize);
 pub type ResponseI32CB = extern fn(xcommand_handle: CommandHandle, err: Error, handle: IndyHandle);
 pub type ResponseWalletHandleCB = extern fn(xcommand_handle: CommandHandle, err: Error, handle: WalletHandle);
 pub type ResponseI32UsizeCB = extern fn(xcommand_handle: CommandHandle, err: Error, handle: IndyHandle, total_count: usize);
@@ -1,6 +1,6 @@
 use super::*;
 
-use {CString, Error, CommandHandle, WalletHandle};
+use {CString, Error, CommandHandle, IndyHandle, WalletHandle};
 
 extern {
 
@@ -13,6 +13,16 @@ extern {
                                   tags_json: CString,
                                   cb: Option<ResponseEmptyCB>) -> Error;
 
+    #[no_mangle]
+    pub fn indy_add_wallet_record_with_expiry(command_handle: CommandHandle,
+                                              wallet_handle: WalletHandle,
+                                              type_: CString,
+                                              id: CString,
+                                              value: CString,
+                                              tags_json: CString,
+                                              expires_at: u64,
+                                              cb: Option<ResponseEmptyCB>) -> Error;
+
     #[no_mangle]
     pub fn indy_update_wallet_record_value(command_handle: CommandHandle,
                                            wallet_handle: WalletHandle,
@@ -52,6 +62,54 @@ extern {
                                      id: CString,
                                      cb: Option<ResponseEmptyCB>) -> Error;
 
+    #[no_mangle]
+    pub fn indy_wallet_batch(command_handle: CommandHandle,
+                             wallet_handle: WalletHandle,
+                             operations_json: CString,
+                             cb: Option<ResponseEmptyCB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_wallet_batch_begin(command_handle: CommandHandle,
+                                   wallet_handle: WalletHandle,
+                                   cb: Option<ResponseI32CB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_wallet_batch_stage(command_handle: CommandHandle,
+                                   batch_handle: IndyHandle,
+                                   operations_json: CString,
+                                   cb: Option<ResponseEmptyCB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_wallet_batch_commit(command_handle: CommandHandle,
+                                    batch_handle: IndyHandle,
+                                    cb: Option<ResponseEmptyCB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_wallet_batch_rollback(command_handle: CommandHandle,
+                                      batch_handle: IndyHandle,
+                                      cb: Option<ResponseEmptyCB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_purge_expired_wallet_records(command_handle: CommandHandle,
+                                             wallet_handle: WalletHandle,
+                                             cb: Option<ResponseUsizeCB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_wallet_count_records_by_tag(command_handle: CommandHandle,
+                                            wallet_handle: WalletHandle,
+                                            type_: CString,
+                                            query_json: CString,
+                                            tag_name: CString,
+                                            cb: Option<ResponseStringCB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_wallet_get_distinct_tag_values(command_handle: CommandHandle,
+                                               wallet_handle: WalletHandle,
+                                               type_: CString,
+                                               query_json: CString,
+                                               tag_name: CString,
+                                               cb: Option<ResponseStringCB>) -> Error;
+
     #[no_mangle]
     pub fn indy_get_wallet_record(command_handle: CommandHandle,
                                   wallet_handle: WalletHandle,
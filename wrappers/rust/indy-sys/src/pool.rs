@@ -21,6 +21,11 @@ extern {
                                     handle: PoolHandle,
                                     cb: Option<ResponseEmptyCB>) -> Error;
 
+    #[no_mangle]
+    pub fn indy_get_pool_status(command_handle: CommandHandle,
+                                handle: PoolHandle,
+                                cb: Option<ResponseStringCB>) -> Error;
+
     #[no_mangle]
     pub fn indy_list_pools(command_handle: CommandHandle,
                            cb: Option<ResponseStringCB>) -> Error;
@@ -39,5 +44,20 @@ extern {
     pub fn indy_set_protocol_version(command_handle: CommandHandle,
                                      protocol_version: usize,
                                      cb: Option<ResponseEmptyCB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_register_pool_transport(command_handle: CommandHandle,
+                                        transport_name: CString,
+                                        send: Option<CustomTransportSend>,
+                                        cb: Option<ResponseEmptyCB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_deliver_pool_transport_reply(command_handle: CommandHandle,
+                                             handle: PoolHandle,
+                                             node_alias: CString,
+                                             reply: CString,
+                                             cb: Option<ResponseEmptyCB>) -> Error;
 }
 
+pub type CustomTransportSend = extern fn(pool_handle: PoolHandle, node_alias: CString, node_address: CString, message: CString) -> Error;
+
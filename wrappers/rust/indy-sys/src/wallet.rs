@@ -33,6 +33,14 @@ extern {
                                         free_search: Option<WalletFreeSearch>,
                                         cb: Option<ResponseEmptyCB>) -> Error;
 
+    #[no_mangle]
+    pub fn indy_register_wallet_key_wrap_provider(command_handle: CommandHandle,
+                                                  name: CString,
+                                                  wrap_key: Option<WalletKeyWrap>,
+                                                  unwrap_key: Option<WalletKeyUnwrap>,
+                                                  free_key: Option<WalletKeyFree>,
+                                                  cb: Option<ResponseEmptyCB>) -> Error;
+
     #[no_mangle]
     pub fn indy_create_wallet(command_handle: CommandHandle,
                               config: CString,
@@ -51,6 +59,14 @@ extern {
                               export_config: CString,
                               cb: Option<ResponseEmptyCB>) -> Error;
 
+    #[no_mangle]
+    pub fn indy_export_wallet_to_sink(command_handle: CommandHandle,
+                                      wallet_handle: WalletHandle,
+                                      export_config: CString,
+                                      sink_handle: IndyHandle,
+                                      write: Option<WalletExportSinkWrite>,
+                                      cb: Option<ResponseEmptyCB>) -> Error;
+
     #[no_mangle]
     pub fn indy_import_wallet(command_handle: CommandHandle,
                               config: CString,
@@ -58,6 +74,30 @@ extern {
                               import_config: CString,
                               cb: Option<ResponseEmptyCB>) -> Error;
 
+    #[no_mangle]
+    pub fn indy_import_wallet_with_report(command_handle: CommandHandle,
+                                          config: CString,
+                                          credentials: CString,
+                                          import_config: CString,
+                                          cb: Option<ResponseStringCB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_import_wallet_from_source(command_handle: CommandHandle,
+                                          config: CString,
+                                          credentials: CString,
+                                          import_config: CString,
+                                          source_handle: IndyHandle,
+                                          read: Option<WalletImportSourceRead>,
+                                          cb: Option<ResponseEmptyCB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_migrate_wallet(command_handle: CommandHandle,
+                               src_config: CString,
+                               src_credentials: CString,
+                               dst_config: CString,
+                               dst_credentials: CString,
+                               cb: Option<ResponseEmptyCB>) -> Error;
+
     #[no_mangle]
     pub fn indy_close_wallet(command_handle: CommandHandle,
                              wallet_handle: WalletHandle,
@@ -69,6 +109,47 @@ extern {
                               credentials: CString,
                               cb: Option<ResponseEmptyCB>) -> Error;
 
+    #[no_mangle]
+    pub fn indy_list_wallet_profiles(command_handle: CommandHandle,
+                                     config: CString,
+                                     credentials: CString,
+                                     cb: Option<ResponseStringCB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_rotate_wallet_item_keys(command_handle: CommandHandle,
+                                        wallet_handle: WalletHandle,
+                                        credentials: CString,
+                                        cb: Option<ResponseEmptyCB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_import_wallet_delta(command_handle: CommandHandle,
+                                    wallet_handle: WalletHandle,
+                                    import_config: CString,
+                                    cb: Option<ResponseEmptyCB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_get_wallet_revision(command_handle: CommandHandle,
+                                    wallet_handle: WalletHandle,
+                                    cb: Option<ResponseU64CB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_check_wallet(command_handle: CommandHandle,
+                             wallet_handle: WalletHandle,
+                             config: CString,
+                             cb: Option<ResponseStringCB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_wallet_subscribe(command_handle: CommandHandle,
+                                 wallet_handle: WalletHandle,
+                                 context: IndyHandle,
+                                 on_change: Option<WalletRecordChanged>,
+                                 cb: Option<ResponseI32CB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_wallet_unsubscribe(command_handle: CommandHandle,
+                                   subscription_handle: IndyHandle,
+                                   cb: Option<ResponseEmptyCB>) -> Error;
+
     #[no_mangle]
     pub fn indy_generate_wallet_key(command_handle: CommandHandle,
                                     config: CString,
@@ -155,3 +236,27 @@ pub type WalletFetchSearchNextRecord = extern fn(storage_handle: StorageHandle,
                                                  record_handle_p: *mut RecordHandle) -> Error;
 pub type WalletFreeSearch = extern fn(storage_handle: StorageHandle,
                                       search_handle: SearchHandle) -> Error;
+pub type WalletKeyWrap = extern fn(key_id: CString,
+                                   key: BString,
+                                   key_len: usize,
+                                   wrapped_key_p: *mut BString,
+                                   wrapped_key_len_p: *mut usize,
+                                   key_handle_p: *mut IndyHandle) -> Error;
+pub type WalletKeyUnwrap = extern fn(key_id: CString,
+                                     wrapped_key: BString,
+                                     wrapped_key_len: usize,
+                                     key_p: *mut BString,
+                                     key_len_p: *mut usize,
+                                     key_handle_p: *mut IndyHandle) -> Error;
+pub type WalletKeyFree = extern fn(key_handle: IndyHandle) -> Error;
+pub type WalletExportSinkWrite = extern fn(sink_handle: IndyHandle,
+                                           data: BString,
+                                           data_len: usize) -> Error;
+pub type WalletImportSourceRead = extern fn(source_handle: IndyHandle,
+                                            buf: *mut u8,
+                                            buf_len: usize,
+                                            read_len_p: *mut usize) -> Error;
+pub type WalletRecordChanged = extern fn(context: IndyHandle,
+                                         type_: CString,
+                                         id: CString,
+                                         operation: CString);
@@ -784,6 +784,39 @@ fn _prover_search_credentials(command_handle: CommandHandle, wallet_handle: Wall
     })
 }
 
+/// Search for credentials stored in wallet with paging of the results.
+/// Credentials can be filtered by tags created during saving of credential.
+///
+/// # Arguments
+/// * `wallet_handle`: wallet handle (created by Wallet::open_wallet).
+/// * `query_json`: Wql query filter for credentials searching based on tags.
+///     where query: indy-sdk/doc/design/011-wallet-query-language/README.md
+/// * `options_json`: paging of found credentials:
+///  {
+///    offset: (optional, 0 by default) Number of credentials to skip,
+///    limit: (optional, no limit by default) Max number of credentials to return,
+///  }
+///
+/// # Returns
+/// * `search_handle`: Search handle that can be used later to fetch records by small batches (with fetch_credentials)
+/// * `total_count`: Total count of records matching the query regardless of paging
+pub fn prover_search_credentials_with_options(wallet_handle: WalletHandle, query_json: Option<&str>, options_json: Option<&str>) -> Box<dyn Future<Item=(SearchHandle, usize), Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec_handle_usize();
+
+    let err = _prover_search_credentials_with_options(command_handle, wallet_handle, query_json, options_json, cb);
+
+    ResultHandler::handle_usize(command_handle, err, receiver)
+}
+
+fn _prover_search_credentials_with_options(command_handle: CommandHandle, wallet_handle: WalletHandle, query_json: Option<&str>, options_json: Option<&str>, cb: Option<ResponseI32UsizeCB>) -> ErrorCode {
+    let query_json_str = opt_c_str!(query_json);
+    let options_json_str = opt_c_str!(options_json);
+
+    ErrorCode::from(unsafe {
+        anoncreds::indy_prover_search_credentials_with_options(command_handle, wallet_handle, opt_c_ptr!(query_json, query_json_str), opt_c_ptr!(options_json, options_json_str), cb)
+    })
+}
+
 /// Fetch next credentials for search.
 ///
 /// # Arguments
@@ -832,6 +865,63 @@ fn _prover_close_credentials_search(command_handle: CommandHandle, search_handle
     })
 }
 
+/// Count credentials stored in wallet per value of the tag created during saving of credential.
+/// Credentials without the tag are not counted.
+///
+/// # Arguments
+/// * `wallet_handle`: wallet handle (created by open_wallet).
+/// * `query_json`: (optional) Wql query filter for credentials based on tags.
+/// * `tag_name`: the name of the credential tag to aggregate values of, for example "cred_def_id" or "schema_name".
+///
+/// # Returns
+/// * `counts_json`: tag values mapped to the count of credentials, ordered by value:
+/// {
+///   "tagValue1": <int>,
+///   "tagValue2": <int>,
+/// }
+pub fn prover_count_credentials_by_tag(wallet_handle: WalletHandle, query_json: Option<&str>, tag_name: &str) -> Box<dyn Future<Item=String, Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec_string();
+
+    let err = _prover_count_credentials_by_tag(command_handle, wallet_handle, query_json, tag_name, cb);
+
+    ResultHandler::str(command_handle, err, receiver)
+}
+
+fn _prover_count_credentials_by_tag(command_handle: CommandHandle, wallet_handle: WalletHandle, query_json: Option<&str>, tag_name: &str, cb: Option<ResponseStringCB>) -> ErrorCode {
+    let query_json_str = opt_c_str!(query_json);
+    let tag_name = c_str!(tag_name);
+
+    ErrorCode::from(unsafe {
+        anoncreds::indy_prover_count_credentials_by_tag(command_handle, wallet_handle, opt_c_ptr!(query_json, query_json_str), tag_name.as_ptr(), cb)
+    })
+}
+
+/// Get the sorted set of distinct values of the tag over credentials stored in wallet.
+///
+/// # Arguments
+/// * `wallet_handle`: wallet handle (created by open_wallet).
+/// * `query_json`: (optional) Wql query filter for credentials based on tags.
+/// * `tag_name`: the name of the credential tag to aggregate values of, for example "cred_def_id" or "schema_name".
+///
+/// # Returns
+/// * `values_json`: ["tagValue1", "tagValue2"]
+pub fn prover_get_distinct_credential_tag_values(wallet_handle: WalletHandle, query_json: Option<&str>, tag_name: &str) -> Box<dyn Future<Item=String, Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec_string();
+
+    let err = _prover_get_distinct_credential_tag_values(command_handle, wallet_handle, query_json, tag_name, cb);
+
+    ResultHandler::str(command_handle, err, receiver)
+}
+
+fn _prover_get_distinct_credential_tag_values(command_handle: CommandHandle, wallet_handle: WalletHandle, query_json: Option<&str>, tag_name: &str, cb: Option<ResponseStringCB>) -> ErrorCode {
+    let query_json_str = opt_c_str!(query_json);
+    let tag_name = c_str!(tag_name);
+
+    ErrorCode::from(unsafe {
+        anoncreds::indy_prover_get_distinct_credential_tag_values(command_handle, wallet_handle, opt_c_ptr!(query_json, query_json_str), tag_name.as_ptr(), cb)
+    })
+}
+
 /// Gets human readable credentials matching the given proof request.
 ///
 /// NOTE: This method is deprecated because immediately returns all fetched credentials.
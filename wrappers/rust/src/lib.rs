@@ -228,6 +228,10 @@ pub enum ErrorCode
     // Returned if provided wallet query is invalid
     #[fail(display = "WalletQueryError")]
     WalletQueryError = 214,
+
+    // Returned if mutating operation is called on the wallet opened in read-only mode
+    #[fail(display = "WalletReadOnlyError")]
+    WalletReadOnlyError = 215,
     // Ledger errors
     // Trying to open pool ledger that wasn't created before
     #[fail(display = "PoolLedgerNotCreatedError")]
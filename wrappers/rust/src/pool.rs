@@ -6,6 +6,7 @@ use std::ptr::null;
 use utils::callbacks::{ClosureHandler, ResultHandler};
 
 use ffi::pool;
+use ffi::pool::CustomTransportSend;
 use ffi::{ResponseEmptyCB,
           ResponseStringCB,
           ResponseI32CB};
@@ -49,6 +50,8 @@ fn _create_pool_ledger_config(command_handle: CommandHandle, pool_name: &str, po
 /// {
 ///     "timeout": int (optional), timeout for network request (in sec).
 ///     "extended_timeout": int (optional), extended timeout for network request (in sec).
+///     "pool_ledger_probe_timeout": int (optional), pool ledger state is checked with LEDGER_STATUS on sending of a request
+///         if it wasn't checked for this timeout (in sec, 600 by default).
 ///     "preordered_nodes": array<string> -  (optional), names of nodes which will have a priority during request sending:
 ///         ["name_of_1st_prior_node",  "name_of_2nd_prior_node", .... ]
 ///         This can be useful if a user prefers querying specific nodes.
@@ -58,6 +61,8 @@ fn _create_pool_ledger_config(command_handle: CommandHandle, pool_name: &str, po
 ///     "number_read_nodes": int (optional) - the number of nodes to send read requests (2 by default)
 ///         By default Libindy sends a read requests to 2 nodes in the pool.
 ///         If response isn't received or `state proof` is invalid Libindy sends the request again but to 2 (`number_read_nodes`) * 2 = 4 nodes and so far until completion.
+///     "transport": string (optional) - name of the transport registered with `register_pool_transport` to send messages to nodes instead of ZMQ.
+///         Built-in "loopback" transport doesn't require real nodes: each node replies with the message it receives.
 /// }
 ///
 /// # Returns
@@ -93,6 +98,25 @@ fn _refresh_pool_ledger(command_handle: CommandHandle, pool_handle: PoolHandle,
     ErrorCode::from(unsafe { pool::indy_refresh_pool_ledger(command_handle, pool_handle, cb) })
 }
 
+/// Returns health statistics collected for the nodes of opened pool.
+///
+/// # Arguments
+/// * `handle` - pool handle returned by open_ledger
+///
+/// # Returns
+/// Pool status json with per-node scores and blacklisting state.
+pub fn get_pool_status(pool_handle: PoolHandle) -> Box<dyn Future<Item=String, Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec_string();
+
+    let err = _get_pool_status(command_handle, pool_handle, cb);
+
+    ResultHandler::str(command_handle, err, receiver)
+}
+
+fn _get_pool_status(command_handle: CommandHandle, pool_handle: PoolHandle, cb: Option<ResponseStringCB>) -> ErrorCode {
+    ErrorCode::from(unsafe { pool::indy_get_pool_status(command_handle, pool_handle, cb) })
+}
+
 /// Lists names of created pool ledgers
 pub fn list_pools() -> Box<dyn Future<Item=String, Error=IndyError>> {
     let (receiver, command_handle, cb) = ClosureHandler::cb_ec_string();
@@ -165,3 +189,46 @@ fn _set_protocol_version(command_handle: CommandHandle, protocol_version: usize,
       pool::indy_set_protocol_version(command_handle, protocol_version, cb)
     })
 }
+
+/// Register transport to communicate with pool nodes without ZMQ.
+///
+/// `send` is called from pool worker thread for each message to node and must not block.
+/// Node replies must be passed back with `deliver_pool_transport_reply`.
+///
+/// # Arguments
+/// * `transport_name` - name of the transport to pass as "transport" in `open_pool_ledger` config.
+/// * `send` - callback to send message to node.
+pub fn register_pool_transport(transport_name: &str, send: CustomTransportSend) -> Box<dyn Future<Item=(), Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+    let err = _register_pool_transport(command_handle, transport_name, send, cb);
+
+    ResultHandler::empty(command_handle, err, receiver)
+}
+
+fn _register_pool_transport(command_handle: CommandHandle, transport_name: &str, send: CustomTransportSend, cb: Option<ResponseEmptyCB>) -> ErrorCode {
+    let transport_name = c_str!(transport_name);
+
+    ErrorCode::from(unsafe { pool::indy_register_pool_transport(command_handle, transport_name.as_ptr(), Some(send), cb) })
+}
+
+/// Pass node reply received by custom transport to the pool.
+///
+/// # Arguments
+/// * `pool_handle` - pool handle passed to transport `send` callback.
+/// * `node_alias` - alias of the node that replied.
+/// * `reply` - node reply "as is".
+pub fn deliver_pool_transport_reply(pool_handle: PoolHandle, node_alias: &str, reply: &str) -> Box<dyn Future<Item=(), Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+    let err = _deliver_pool_transport_reply(command_handle, pool_handle, node_alias, reply, cb);
+
+    ResultHandler::empty(command_handle, err, receiver)
+}
+
+fn _deliver_pool_transport_reply(command_handle: CommandHandle, pool_handle: PoolHandle, node_alias: &str, reply: &str, cb: Option<ResponseEmptyCB>) -> ErrorCode {
+    let node_alias = c_str!(node_alias);
+    let reply = c_str!(reply);
+
+    ErrorCode::from(unsafe { pool::indy_deliver_pool_transport_reply(command_handle, pool_handle, node_alias.as_ptr(), reply.as_ptr(), cb) })
+}
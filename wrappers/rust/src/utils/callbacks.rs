@@ -18,6 +18,8 @@ lazy_static! {
     static ref CALLBACKS_HANDLE: Mutex<HashMap<CommandHandle, oneshot::Sender<Result<CommandHandle, IndyError>>>> = Default::default();
     static ref CALLBACKS_WALLETHANDLE: Mutex<HashMap<CommandHandle, oneshot::Sender<Result<WalletHandle, IndyError>>>> = Default::default();
     static ref CALLBACKS_BOOL: Mutex<HashMap<CommandHandle, oneshot::Sender<Result<bool, IndyError>>>> = Default::default();
+    static ref CALLBACKS_U64: Mutex<HashMap<CommandHandle, oneshot::Sender<Result<u64, IndyError>>>> = Default::default();
+    static ref CALLBACKS_USIZE: Mutex<HashMap<CommandHandle, oneshot::Sender<Result<usize, IndyError>>>> = Default::default();
     static ref CALLBACKS_STR_SLICE: Mutex<HashMap<CommandHandle, oneshot::Sender<Result<(String, Vec<u8>), IndyError>>>> = Default::default();
     static ref CALLBACKS_HANDLE_USIZE: Mutex<HashMap<CommandHandle, oneshot::Sender<Result<(CommandHandle, usize), IndyError>>>> = Default::default();
     static ref CALLBACKS_STR_STR_U64: Mutex<HashMap<CommandHandle, oneshot::Sender<Result<(String, String, u64), IndyError>>>> = Default::default();
@@ -107,6 +109,10 @@ impl ClosureHandler {
            (rust_str!(str), rust_slice!(data, len).to_owned()));
 
     cb_ec!(cb_ec_bool(b: bool)->bool, CALLBACKS_BOOL, b);
+
+    cb_ec!(cb_ec_u64(u: u64)->u64, CALLBACKS_U64, u);
+
+    cb_ec!(cb_ec_usize(u: usize)->usize, CALLBACKS_USIZE, u);
 }
 
 macro_rules! result_handler {
@@ -135,6 +141,8 @@ impl ResultHandler {
     result_handler!(wallethandle(WalletHandle), CALLBACKS_WALLETHANDLE);
     result_handler!(slice(Vec<u8>), CALLBACKS_SLICE);
     result_handler!(bool(bool), CALLBACKS_BOOL);
+    result_handler!(u64(u64), CALLBACKS_U64);
+    result_handler!(usize(usize), CALLBACKS_USIZE);
     result_handler!(str(String), CALLBACKS_STR);
     result_handler!(str_i64((String, i64)), CALLBACKS_STR_I64);
     result_handler!(handle_usize((CommandHandle, usize)), CALLBACKS_HANDLE_USIZE);
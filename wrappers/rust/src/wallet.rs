@@ -1,18 +1,31 @@
 use futures::Future;
+use futures::sync::mpsc;
 
 use {ErrorCode, IndyError};
 
-use std::ffi::CString;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
 use std::ptr::null;
+use std::sync::Mutex;
+
+use libc::c_char;
 
 use utils::callbacks::{ClosureHandler, ResultHandler};
+use utils::sequence::SequenceUtils;
 
 use ffi::{wallet, non_secrets};
 use ffi::{ResponseEmptyCB,
           ResponseStringCB,
           ResponseI32CB,
-          ResponseWalletHandleCB};
-use {CommandHandle, WalletHandle, SearchHandle};
+          ResponseWalletHandleCB,
+          ResponseU64CB,
+          ResponseUsizeCB};
+use {CommandHandle, IndyHandle, WalletHandle, SearchHandle};
+
+lazy_static! {
+    static ref SUBSCRIPTIONS: Mutex<HashMap<IndyHandle, (WalletHandle, mpsc::UnboundedSender<(String, String, String)>)>> = Default::default();
+    static ref SUBSCRIPTION_CONTEXTS: Mutex<HashMap<IndyHandle, IndyHandle>> = Default::default();
+}
 
 /// Registers custom wallet implementation.
 ///
@@ -148,6 +161,40 @@ fn _register_storage(command_handle: CommandHandle,
     })
 }
 
+/// Registers custom key wrap provider that protects wallet master key.
+///
+/// Wallets created with `WRAPPED` key derivation method and `key_wrap_provider` set to the provider name
+/// keep random master key wrapped by the provider, `key` of wallet credentials is id of the wrapping key.
+///
+/// # Arguments
+/// * `name` - Key wrap provider name.
+/// * `wrap_key` - Handler that wraps wallet master key with the key identified by key id
+/// * `unwrap_key` - Handler that unwraps wallet master key with the key identified by key id
+/// * `free_key` - Handler that allows to de-allocate keys returned by wrap_key and unwrap_key handlers
+pub fn register_wallet_key_wrap_provider(name: &str,
+                                         wrap_key: Option<wallet::WalletKeyWrap>,
+                                         unwrap_key: Option<wallet::WalletKeyUnwrap>,
+                                         free_key: Option<wallet::WalletKeyFree>) -> Box<dyn Future<Item=(), Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+    let err = _register_key_wrap_provider(command_handle, name, wrap_key, unwrap_key, free_key, cb);
+
+    ResultHandler::empty(command_handle, err, receiver)
+}
+
+fn _register_key_wrap_provider(command_handle: CommandHandle,
+                               name: &str,
+                               wrap_key: Option<wallet::WalletKeyWrap>,
+                               unwrap_key: Option<wallet::WalletKeyUnwrap>,
+                               free_key: Option<wallet::WalletKeyFree>,
+                               cb: Option<ResponseEmptyCB>) -> ErrorCode {
+    let name = c_str!(name);
+
+    ErrorCode::from(unsafe {
+        wallet::indy_register_wallet_key_wrap_provider(command_handle, name.as_ptr(), wrap_key, unwrap_key, free_key, cb)
+    })
+}
+
 /// Creates a new secure wallet with the given unique name.
 ///
 /// # Arguments
@@ -216,6 +263,8 @@ fn _open_wallet(command_handle: CommandHandle, config: &str, credentials: &str,
 ///   {
 ///     "path": path of the file that contains exported wallet content
 ///     "key": passphrase used to derive export key
+///     "since_revision": optional<u64> export only changes made after this wallet revision
+///     "version": optional<int> export format version, 1 (default) or 2
 ///   }
 pub fn export_wallet(wallet_handle: WalletHandle, export_config: &str) -> Box<dyn Future<Item=(), Error=IndyError>> {
     let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
@@ -233,6 +282,37 @@ fn _export_wallet(command_handle: CommandHandle, wallet_handle: WalletHandle, ex
     })
 }
 
+/// Exports opened wallet to the caller provided sink instead of the file
+///
+/// Note this endpoint is EXPERIMENTAL. Function signature and behaviour may change
+/// in the future releases.
+///
+/// # Arguments:
+/// * `wallet_handle` - wallet handle returned by indy_open_wallet
+/// * `export_config` - JSON containing settings for input operation.
+///   {
+///     "key": passphrase used to derive export key
+///     "types": optional<[string]> types of records to export
+///     "query": optional<object> WQL query to filter exported records of listed types
+///   }
+/// * `sink_handle` - caller defined handle passed to `write`
+/// * `write` - handler that receives exported data
+pub fn export_wallet_to_sink(wallet_handle: WalletHandle, export_config: &str, sink_handle: IndyHandle, write: Option<wallet::WalletExportSinkWrite>) -> Box<dyn Future<Item=(), Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+    let err = _export_wallet_to_sink(command_handle, wallet_handle, export_config, sink_handle, write, cb);
+
+    ResultHandler::empty(command_handle, err, receiver)
+}
+
+fn _export_wallet_to_sink(command_handle: CommandHandle, wallet_handle: WalletHandle, export_config: &str, sink_handle: IndyHandle, write: Option<wallet::WalletExportSinkWrite>, cb: Option<ResponseEmptyCB>) -> ErrorCode {
+    let export_config = c_str!(export_config);
+
+    ErrorCode::from(unsafe {
+      wallet::indy_export_wallet_to_sink(command_handle, wallet_handle, export_config.as_ptr(), sink_handle, write, cb)
+    })
+}
+
 /// Creates a new secure wallet with the given unique name and then imports its content
 /// according to fields provided in import_config
 /// This can be seen as an create call with additional content import
@@ -274,6 +354,103 @@ fn _import_wallet(command_handle: CommandHandle, config: &str, credentials: &str
     })
 }
 
+/// Creates a new secure wallet and then imports its content like `import_wallet`
+///
+/// Note this endpoint is EXPERIMENTAL. Function signature and behaviour may change
+/// in the future releases.
+///
+/// # Arguments
+/// * `config` - Wallet configuration json (see `import_wallet`).
+/// * `credentials` - Wallet credentials json (see `import_wallet`).
+/// * `import_config` - JSON containing settings for input operation.
+///   {
+///     "path": path of the file that contains exported wallet content
+///     "key": passphrase used to derive export key
+///     "tolerant": optional<bool> skip damaged records of version 2 export instead of failing
+///   }
+///
+/// # Returns
+/// import report json: {"imported": <int>, "skipped": <int>, "expected": optional<int>}
+pub fn import_wallet_with_report(config: &str, credentials: &str, import_config: &str) -> Box<dyn Future<Item=String, Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec_string();
+
+    let err = _import_wallet_with_report(command_handle, config, credentials, import_config, cb);
+
+    ResultHandler::str(command_handle, err, receiver)
+}
+
+fn _import_wallet_with_report(command_handle: CommandHandle, config: &str, credentials: &str, import_config: &str, cb: Option<ResponseStringCB>) -> ErrorCode {
+    let config = c_str!(config);
+    let credentials = c_str!(credentials);
+    let import_config = c_str!(import_config);
+
+    ErrorCode::from(unsafe {
+      wallet::indy_import_wallet_with_report(command_handle, config.as_ptr(), credentials.as_ptr(), import_config.as_ptr(), cb)
+    })
+}
+
+/// Creates a new secure wallet with the given unique name and then imports its content
+/// from the caller provided source instead of the file
+///
+/// Note this endpoint is EXPERIMENTAL. Function signature and behaviour may change
+/// in the future releases.
+///
+/// # Arguments
+/// * `config` - Wallet configuration json (see `import_wallet`).
+/// * `credentials` - Wallet credentials json (see `import_wallet`).
+/// * `import_config` - JSON containing settings for input operation.
+///   {
+///     "key": passphrase used to derive export key
+///   }
+/// * `source_handle` - caller defined handle passed to `read`
+/// * `read` - handler that provides exported data (0 read length means end of data)
+pub fn import_wallet_from_source(config: &str, credentials: &str, import_config: &str, source_handle: IndyHandle, read: Option<wallet::WalletImportSourceRead>) -> Box<dyn Future<Item=(), Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+    let err = _import_wallet_from_source(command_handle, config, credentials, import_config, source_handle, read, cb);
+
+    ResultHandler::empty(command_handle, err, receiver)
+}
+
+fn _import_wallet_from_source(command_handle: CommandHandle, config: &str, credentials: &str, import_config: &str, source_handle: IndyHandle, read: Option<wallet::WalletImportSourceRead>, cb: Option<ResponseEmptyCB>) -> ErrorCode {
+    let config = c_str!(config);
+    let credentials = c_str!(credentials);
+    let import_config = c_str!(import_config);
+
+    ErrorCode::from(unsafe {
+      wallet::indy_import_wallet_from_source(command_handle, config.as_ptr(), credentials.as_ptr(), import_config.as_ptr(), source_handle, read, cb)
+    })
+}
+
+/// Creates a new wallet and copies all records of existing wallet into it
+/// directly between wallet storages (for example from `default` to `postgres` storage)
+///
+/// Records are copied in encrypted form, only the new master key is derived from `dst_credentials`.
+///
+/// # Arguments
+/// * `src_config` - Source wallet configuration json (see `create_wallet`).
+/// * `src_credentials` - Source wallet credentials json (see `create_wallet`).
+/// * `dst_config` - Destination wallet configuration json (see `create_wallet`).
+/// * `dst_credentials` - Destination wallet credentials json (see `create_wallet`).
+pub fn migrate_wallet(src_config: &str, src_credentials: &str, dst_config: &str, dst_credentials: &str) -> Box<dyn Future<Item=(), Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+    let err = _migrate_wallet(command_handle, src_config, src_credentials, dst_config, dst_credentials, cb);
+
+    ResultHandler::empty(command_handle, err, receiver)
+}
+
+fn _migrate_wallet(command_handle: CommandHandle, src_config: &str, src_credentials: &str, dst_config: &str, dst_credentials: &str, cb: Option<ResponseEmptyCB>) -> ErrorCode {
+    let src_config = c_str!(src_config);
+    let src_credentials = c_str!(src_credentials);
+    let dst_config = c_str!(dst_config);
+    let dst_credentials = c_str!(dst_credentials);
+
+    ErrorCode::from(unsafe {
+      wallet::indy_migrate_wallet(command_handle, src_config.as_ptr(), src_credentials.as_ptr(), dst_config.as_ptr(), dst_credentials.as_ptr(), cb)
+    })
+}
+
 /// Deletes created wallet.
 pub fn delete_wallet(config: &str, credentials: &str) -> Box<dyn Future<Item=(), Error=IndyError>> {
     let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
@@ -292,6 +469,186 @@ fn _delete_wallet(command_handle: CommandHandle, config: &str, credentials: &str
     })
 }
 
+/// Lists profiles created inside the storage of the wallet.
+/// Profiles are created, opened and deleted by create_wallet, open_wallet and delete_wallet with "profile" in config.
+///
+/// # Arguments
+/// * `config` - wallet configuration json of the default profile of the storage
+/// * `credentials` - wallet credentials json of the default profile of the storage
+///
+/// # Returns
+/// json array with names of the profiles
+pub fn list_wallet_profiles(config: &str, credentials: &str) -> Box<dyn Future<Item=String, Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec_string();
+
+    let err = _list_wallet_profiles(command_handle, config, credentials, cb);
+
+    ResultHandler::str(command_handle, err, receiver)
+}
+
+fn _list_wallet_profiles(command_handle: CommandHandle, config: &str, credentials: &str, cb: Option<ResponseStringCB>) -> ErrorCode {
+    let config = c_str!(config);
+    let credentials = c_str!(credentials);
+
+    ErrorCode::from(unsafe {
+      wallet::indy_list_wallet_profiles(command_handle, config.as_ptr(), credentials.as_ptr(), cb)
+    })
+}
+
+/// Rotates keys used for encryption of wallet records (item keys) of opened wallet.
+/// Records are re-encrypted in place by chunks, interrupted rotation is finished on the next opening of the wallet.
+///
+/// # Arguments
+/// * `wallet_handle` - wallet handle returned by open_wallet
+/// * `credentials` - wallet credentials json used to open the wallet
+pub fn rotate_wallet_item_keys(wallet_handle: WalletHandle, credentials: &str) -> Box<dyn Future<Item=(), Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+    let err = _rotate_wallet_item_keys(command_handle, wallet_handle, credentials, cb);
+
+    ResultHandler::empty(command_handle, err, receiver)
+}
+
+fn _rotate_wallet_item_keys(command_handle: CommandHandle, wallet_handle: WalletHandle, credentials: &str, cb: Option<ResponseEmptyCB>) -> ErrorCode {
+    let credentials = c_str!(credentials);
+
+    ErrorCode::from(unsafe {
+      wallet::indy_rotate_wallet_item_keys(command_handle, wallet_handle, credentials.as_ptr(), cb)
+    })
+}
+
+/// Applies delta export (created by export_wallet with "since_revision") to opened wallet.
+///
+/// # Arguments
+/// * `wallet_handle` - wallet handle returned by open_wallet
+/// * `import_config` - JSON containing settings for import operation.
+///   {
+///     "path": path of the file that contains delta export
+///     "key": passphrase used to derive export key
+///   }
+pub fn import_wallet_delta(wallet_handle: WalletHandle, import_config: &str) -> Box<dyn Future<Item=(), Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+    let err = _import_wallet_delta(command_handle, wallet_handle, import_config, cb);
+
+    ResultHandler::empty(command_handle, err, receiver)
+}
+
+fn _import_wallet_delta(command_handle: CommandHandle, wallet_handle: WalletHandle, import_config: &str, cb: Option<ResponseEmptyCB>) -> ErrorCode {
+    let import_config = c_str!(import_config);
+
+    ErrorCode::from(unsafe {
+      wallet::indy_import_wallet_delta(command_handle, wallet_handle, import_config.as_ptr(), cb)
+    })
+}
+
+/// Returns current revision of opened wallet that can be used as "since_revision" of delta export.
+///
+/// # Arguments
+/// * `wallet_handle` - wallet handle returned by open_wallet
+pub fn get_wallet_revision(wallet_handle: WalletHandle) -> Box<dyn Future<Item=u64, Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec_u64();
+
+    let err = _get_wallet_revision(command_handle, wallet_handle, cb);
+
+    ResultHandler::u64(command_handle, err, receiver)
+}
+
+fn _get_wallet_revision(command_handle: CommandHandle, wallet_handle: WalletHandle, cb: Option<ResponseU64CB>) -> ErrorCode {
+    ErrorCode::from(unsafe {
+      wallet::indy_get_wallet_revision(command_handle, wallet_handle, cb)
+    })
+}
+
+/// Checks integrity of opened wallet: every record must be decryptable with the wallet keys,
+/// values of known Indy record types must be valid and tags must reference existing records.
+///
+/// # Arguments
+/// * `wallet_handle` - wallet handle returned by open_wallet
+/// * `config` - (optional) check configuration json.
+/// {
+///   "repair": optional<bool>, quarantine records that failed the check and remove orphan tags
+/// }
+///
+/// # Returns
+/// check report json
+pub fn check_wallet(wallet_handle: WalletHandle, config: Option<&str>) -> Box<dyn Future<Item=String, Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec_string();
+
+    let err = _check_wallet(command_handle, wallet_handle, config, cb);
+
+    ResultHandler::str(command_handle, err, receiver)
+}
+
+fn _check_wallet(command_handle: CommandHandle, wallet_handle: WalletHandle, config: Option<&str>, cb: Option<ResponseStringCB>) -> ErrorCode {
+    let config = opt_c_str_json!(config);
+
+    ErrorCode::from(unsafe { wallet::indy_check_wallet(command_handle, wallet_handle, config.as_ptr(), cb) })
+}
+
+/// Subscribes to changes of wallet records made through any handle of the same wallet.
+///
+/// # Arguments
+/// * `wallet_handle` - wallet handle returned by open_wallet
+///
+/// # Returns
+/// subscription handle (See unsubscribe_wallet) and stream of changes: (type, id, operation),
+/// where operation is one of "add", "update" or "delete".
+/// Stream ends when unsubscribe_wallet is called for the subscription or the wallet handle is closed.
+pub fn subscribe_wallet(wallet_handle: WalletHandle) -> Box<dyn Future<Item=(IndyHandle, mpsc::UnboundedReceiver<(String, String, String)>), Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec_handle();
+
+    let context = SequenceUtils::get_next_id();
+    let (sender, changes) = mpsc::unbounded();
+    SUBSCRIPTIONS.lock().unwrap().insert(context, (wallet_handle, sender));
+
+    let err = _subscribe_wallet(command_handle, wallet_handle, context, Some(_on_record_changed), cb);
+
+    Box::new(ResultHandler::handle(command_handle, err, receiver)
+        .then(move |res| match res {
+            Ok(subscription_handle) => {
+                SUBSCRIPTION_CONTEXTS.lock().unwrap().insert(subscription_handle, context);
+                Ok((subscription_handle, changes))
+            }
+            Err(err) => {
+                SUBSCRIPTIONS.lock().unwrap().remove(&context);
+                Err(err)
+            }
+        }))
+}
+
+fn _subscribe_wallet(command_handle: CommandHandle, wallet_handle: WalletHandle, context: IndyHandle, on_change: Option<wallet::WalletRecordChanged>, cb: Option<ResponseI32CB>) -> ErrorCode {
+    ErrorCode::from(unsafe { wallet::indy_wallet_subscribe(command_handle, wallet_handle, context, on_change, cb) })
+}
+
+extern fn _on_record_changed(context: IndyHandle, type_: *const c_char, id: *const c_char, operation: *const c_char) {
+    if let Some((_, sender)) = SUBSCRIPTIONS.lock().unwrap().get(&context) {
+        // Receiver can be already dropped by caller, so send errors are ignored
+        let _ = sender.unbounded_send((rust_str!(type_), rust_str!(id), rust_str!(operation)));
+    }
+}
+
+/// Cancels subscription to changes of wallet records and ends stream of changes.
+///
+/// # Arguments
+/// * `subscription_handle` - subscription handle returned by subscribe_wallet
+pub fn unsubscribe_wallet(subscription_handle: IndyHandle) -> Box<dyn Future<Item=(), Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+    let err = _unsubscribe_wallet(command_handle, subscription_handle, cb);
+
+    Box::new(ResultHandler::empty(command_handle, err, receiver)
+        .map(move |_| {
+            if let Some(context) = SUBSCRIPTION_CONTEXTS.lock().unwrap().remove(&subscription_handle) {
+                SUBSCRIPTIONS.lock().unwrap().remove(&context);
+            }
+        }))
+}
+
+fn _unsubscribe_wallet(command_handle: CommandHandle, subscription_handle: IndyHandle, cb: Option<ResponseEmptyCB>) -> ErrorCode {
+    ErrorCode::from(unsafe { wallet::indy_wallet_unsubscribe(command_handle, subscription_handle, cb) })
+}
+
 /// Closes opened wallet and frees allocated resources.
 ///
 /// # Arguments
@@ -301,7 +658,13 @@ pub fn close_wallet(wallet_handle: WalletHandle) -> Box<dyn Future<Item=(), Erro
 
     let err = _close_wallet(command_handle, wallet_handle, cb);
 
-    ResultHandler::empty(command_handle, err, receiver)
+    Box::new(ResultHandler::empty(command_handle, err, receiver)
+        .map(move |_| {
+            // libindy drops subscriptions of the closed handle, dropping of senders ends their streams
+            let mut subscriptions = SUBSCRIPTIONS.lock().unwrap();
+            subscriptions.retain(|_, (subscribed_handle, _)| *subscribed_handle != wallet_handle);
+            SUBSCRIPTION_CONTEXTS.lock().unwrap().retain(|_, context| subscriptions.contains_key(context));
+        }))
 }
 
 fn _close_wallet(command_handle: CommandHandle, wallet_handle: WalletHandle, cb: Option<ResponseEmptyCB>) -> ErrorCode {
@@ -350,6 +713,43 @@ fn _add_wallet_record(command_handle: CommandHandle, wallet_handle: WalletHandle
     })
 }
 
+/// Create a new non-secret record in the wallet that expires at the given time.
+/// Expired record isn't returned by reading and searching of records,
+/// but it is deleted only by `purge_expired_wallet_records` call.
+///
+/// # Arguments
+/// * `wallet_handle` - wallet handle (created by open_wallet)
+/// * `xtype` - allows to separate different record types collections
+/// * `id` - the id of record
+/// * `value` - the value of record
+/// * `tags_json` -  the record tags used for search and storing meta information as json.
+///   Tags json has the same format as for `add_wallet_record`
+/// * `expires_at` - expiry time of the record as unix timestamp in seconds
+pub fn add_wallet_record_with_expiry(wallet_handle: WalletHandle, xtype: &str, id: &str, value: &str, tags_json: Option<&str>, expires_at: u64) -> Box<dyn Future<Item=(), Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+    let err = _add_wallet_record_with_expiry(command_handle, wallet_handle, xtype, id, value, tags_json, expires_at, cb);
+
+    ResultHandler::empty(command_handle, err, receiver)
+}
+
+fn _add_wallet_record_with_expiry(command_handle: CommandHandle, wallet_handle: WalletHandle, xtype: &str, id: &str, value: &str, tags_json: Option<&str>, expires_at: u64, cb: Option<ResponseEmptyCB>) -> ErrorCode {
+    let xtype = c_str!(xtype);
+    let id = c_str!(id);
+    let value = c_str!(value);
+    let tags_json_str = opt_c_str!(tags_json);
+    ErrorCode::from(unsafe {
+        non_secrets::indy_add_wallet_record_with_expiry(command_handle,
+                                                        wallet_handle,
+                                                        xtype.as_ptr(),
+                                                        id.as_ptr(),
+                                                        value.as_ptr(),
+                                                        opt_c_ptr!(tags_json, tags_json_str),
+                                                        expires_at,
+                                                        cb)
+    })
+}
+
 /// Update a non-secret wallet record value
 ///
 /// # Arguments
@@ -499,6 +899,188 @@ fn _delete_wallet_record(command_handle: CommandHandle, wallet_handle: WalletHan
     })
 }
 
+/// Apply several non-secret record operations to the wallet as a single atomic unit
+///
+/// # Arguments
+/// * `wallet_handle` - wallet handle (created by open_wallet)
+/// * `operations_json` - the list of operations to apply in order as json array:
+///   [
+///     {"op": "add", "type": <str>, "id": <str>, "value": <str>, "tags": <tags json> (optional)},
+///     {"op": "updateValue", "type": <str>, "id": <str>, "value": <str>},
+///     {"op": "addTags", "type": <str>, "id": <str>, "tags": <tags json>},
+///     {"op": "updateTags", "type": <str>, "id": <str>, "tags": <tags json>},
+///     {"op": "deleteTags", "type": <str>, "id": <str>, "tagNames": [<str>, ...]},
+///     {"op": "delete", "type": <str>, "id": <str>},
+///   ]
+pub fn batch_wallet_records(wallet_handle: WalletHandle, operations_json: &str) -> Box<dyn Future<Item=(), Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+    let err = _batch_wallet_records(command_handle, wallet_handle, operations_json, cb);
+
+    ResultHandler::empty(command_handle, err, receiver)
+}
+
+fn _batch_wallet_records(command_handle: CommandHandle, wallet_handle: WalletHandle, operations_json: &str, cb: Option<ResponseEmptyCB>) -> ErrorCode {
+    let operations_json = c_str!(operations_json);
+
+    ErrorCode::from(unsafe {
+      non_secrets::indy_wallet_batch(command_handle, wallet_handle, operations_json.as_ptr(), cb)
+    })
+}
+
+/// Start staged batch of non-secret record operations.
+/// Nothing is written to the wallet until `commit_wallet_batch` applies all staged operations atomically.
+///
+/// # Arguments
+/// * `wallet_handle` - wallet handle (created by open_wallet)
+///
+/// # Returns
+/// staged batch handle
+pub fn begin_wallet_batch(wallet_handle: WalletHandle) -> Box<dyn Future<Item=IndyHandle, Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec_handle();
+
+    let err = _begin_wallet_batch(command_handle, wallet_handle, cb);
+
+    ResultHandler::handle(command_handle, err, receiver)
+}
+
+fn _begin_wallet_batch(command_handle: CommandHandle, wallet_handle: WalletHandle, cb: Option<ResponseI32CB>) -> ErrorCode {
+    ErrorCode::from(unsafe { non_secrets::indy_wallet_batch_begin(command_handle, wallet_handle, cb) })
+}
+
+/// Stage non-secret record operations to the batch
+///
+/// # Arguments
+/// * `batch_handle` - staged batch handle (created by begin_wallet_batch)
+/// * `operations_json` - the list of operations in the same format as for batch_wallet_records
+pub fn stage_wallet_batch(batch_handle: IndyHandle, operations_json: &str) -> Box<dyn Future<Item=(), Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+    let err = _stage_wallet_batch(command_handle, batch_handle, operations_json, cb);
+
+    ResultHandler::empty(command_handle, err, receiver)
+}
+
+fn _stage_wallet_batch(command_handle: CommandHandle, batch_handle: IndyHandle, operations_json: &str, cb: Option<ResponseEmptyCB>) -> ErrorCode {
+    let operations_json = c_str!(operations_json);
+
+    ErrorCode::from(unsafe {
+      non_secrets::indy_wallet_batch_stage(command_handle, batch_handle, operations_json.as_ptr(), cb)
+    })
+}
+
+/// Apply all staged operations of the batch as a single atomic unit and release the batch
+///
+/// # Arguments
+/// * `batch_handle` - staged batch handle (created by begin_wallet_batch)
+pub fn commit_wallet_batch(batch_handle: IndyHandle) -> Box<dyn Future<Item=(), Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+    let err = _commit_wallet_batch(command_handle, batch_handle, cb);
+
+    ResultHandler::empty(command_handle, err, receiver)
+}
+
+fn _commit_wallet_batch(command_handle: CommandHandle, batch_handle: IndyHandle, cb: Option<ResponseEmptyCB>) -> ErrorCode {
+    ErrorCode::from(unsafe { non_secrets::indy_wallet_batch_commit(command_handle, batch_handle, cb) })
+}
+
+/// Discard all staged operations of the batch and release the batch
+///
+/// # Arguments
+/// * `batch_handle` - staged batch handle (created by begin_wallet_batch)
+pub fn rollback_wallet_batch(batch_handle: IndyHandle) -> Box<dyn Future<Item=(), Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+    let err = _rollback_wallet_batch(command_handle, batch_handle, cb);
+
+    ResultHandler::empty(command_handle, err, receiver)
+}
+
+fn _rollback_wallet_batch(command_handle: CommandHandle, batch_handle: IndyHandle, cb: Option<ResponseEmptyCB>) -> ErrorCode {
+    ErrorCode::from(unsafe { non_secrets::indy_wallet_batch_rollback(command_handle, batch_handle, cb) })
+}
+
+/// Delete all expired non-secret records of the wallet
+///
+/// # Arguments
+/// * `wallet_handle` - wallet handle (created by open_wallet)
+///
+/// # Returns
+/// count of deleted records
+pub fn purge_expired_wallet_records(wallet_handle: WalletHandle) -> Box<dyn Future<Item=usize, Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec_usize();
+
+    let err = _purge_expired_wallet_records(command_handle, wallet_handle, cb);
+
+    ResultHandler::usize(command_handle, err, receiver)
+}
+
+fn _purge_expired_wallet_records(command_handle: CommandHandle, wallet_handle: WalletHandle, cb: Option<ResponseUsizeCB>) -> ErrorCode {
+    ErrorCode::from(unsafe {
+      non_secrets::indy_purge_expired_wallet_records(command_handle, wallet_handle, cb)
+    })
+}
+
+/// Count wallet records matching the query per value of the tag.
+/// Records without the tag are not counted.
+///
+/// # Arguments
+/// * `wallet_handle` - wallet handle (created by open_wallet)
+/// * `xtype` - allows to separate different record types collections
+/// * `query_json` - MongoDB style query to wallet record tags (see open_wallet_search)
+/// * `tag_name` - the name of the tag to aggregate values of ("~tagName" for un-encrypted tag)
+/// # Returns
+/// * `counts_json` - tag values mapped to the count of records, ordered by value:
+/// {
+///   "tagValue1": <int>,
+///   "tagValue2": <int>,
+/// }
+pub fn count_wallet_records_by_tag(wallet_handle: WalletHandle, xtype: &str, query_json: &str, tag_name: &str) -> Box<dyn Future<Item=String, Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec_string();
+
+    let err = _count_wallet_records_by_tag(command_handle, wallet_handle, xtype, query_json, tag_name, cb);
+
+    ResultHandler::str(command_handle, err, receiver)
+}
+
+fn _count_wallet_records_by_tag(command_handle: CommandHandle, wallet_handle: WalletHandle, xtype: &str, query_json: &str, tag_name: &str, cb: Option<ResponseStringCB>) -> ErrorCode {
+    let xtype = c_str!(xtype);
+    let query_json = c_str!(query_json);
+    let tag_name = c_str!(tag_name);
+
+    ErrorCode::from(unsafe {
+      non_secrets::indy_wallet_count_records_by_tag(command_handle, wallet_handle, xtype.as_ptr(), query_json.as_ptr(), tag_name.as_ptr(), cb)
+    })
+}
+
+/// Get the sorted set of distinct values of the tag over wallet records matching the query.
+///
+/// # Arguments
+/// * `wallet_handle` - wallet handle (created by open_wallet)
+/// * `xtype` - allows to separate different record types collections
+/// * `query_json` - MongoDB style query to wallet record tags (see open_wallet_search)
+/// * `tag_name` - the name of the tag to aggregate values of ("~tagName" for un-encrypted tag)
+/// # Returns
+/// * `values_json` - ["tagValue1", "tagValue2"]
+pub fn get_wallet_distinct_tag_values(wallet_handle: WalletHandle, xtype: &str, query_json: &str, tag_name: &str) -> Box<dyn Future<Item=String, Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec_string();
+
+    let err = _get_wallet_distinct_tag_values(command_handle, wallet_handle, xtype, query_json, tag_name, cb);
+
+    ResultHandler::str(command_handle, err, receiver)
+}
+
+fn _get_wallet_distinct_tag_values(command_handle: CommandHandle, wallet_handle: WalletHandle, xtype: &str, query_json: &str, tag_name: &str, cb: Option<ResponseStringCB>) -> ErrorCode {
+    let xtype = c_str!(xtype);
+    let query_json = c_str!(query_json);
+    let tag_name = c_str!(tag_name);
+
+    ErrorCode::from(unsafe {
+      non_secrets::indy_wallet_get_distinct_tag_values(command_handle, wallet_handle, xtype.as_ptr(), query_json.as_ptr(), tag_name.as_ptr(), cb)
+    })
+}
+
 /// Get an wallet record by id
 ///
 /// # Arguments
@@ -561,6 +1143,12 @@ fn _get_wallet_record(command_handle: CommandHandle, wallet_handle: WalletHandle
 ///    retrieveType: (optional, false by default) Retrieve record type,
 ///    retrieveValue: (optional, true by default) Retrieve record value,
 ///    retrieveTags: (optional, false by default) Retrieve record tags,
+///    sortBy: (optional) {
+///      tagName: name of un-encrypted tag ("~tagName") to order records by,
+///      order: (optional, "asc" by default) "asc" or "desc"
+///    }, Sorting isn't supported by plugged storages (WalletQueryError),
+///    offset: (optional, 0 by default) Number of records to skip,
+///    limit: (optional, no limit by default) Max number of records to return,
 ///  }
 /// # Returns
 /// * `search_handle` - Wallet search handle that can be used later
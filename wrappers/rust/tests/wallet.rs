@@ -427,6 +427,72 @@ mod test_wallet_close {
 
 }
 
+#[cfg(test)]
+mod test_wallet_subscribe {
+    use super::*;
+    use futures::Stream;
+    use indy::INVALID_WALLET_HANDLE;
+
+    #[test]
+    fn subscribe_wallet() {
+        let config = wallet_config::new();
+        wallet::create_wallet(&config, DEFAULT_CREDENTIALS).wait().unwrap();
+        let handle = wallet::open_wallet(&config, DEFAULT_CREDENTIALS).wait().unwrap();
+
+        let (subscription_handle, changes) = wallet::subscribe_wallet(handle).wait().unwrap();
+
+        wallet::add_wallet_record(handle, "type", "id", "value", None).wait().unwrap();
+        wallet::update_wallet_record_value(handle, "type", "id", "new_value").wait().unwrap();
+        wallet::delete_wallet_record(handle, "type", "id").wait().unwrap();
+
+        wallet::unsubscribe_wallet(subscription_handle).wait().unwrap();
+
+        let changes = changes.collect().wait().unwrap();
+        assert_eq!(vec![
+            ("type".to_string(), "id".to_string(), "add".to_string()),
+            ("type".to_string(), "id".to_string(), "update".to_string()),
+            ("type".to_string(), "id".to_string(), "delete".to_string()),
+        ], changes);
+
+        wallet::close_wallet(handle).wait().unwrap();
+        wallet::delete_wallet(&config, DEFAULT_CREDENTIALS).wait().unwrap();
+    }
+
+    #[test]
+    fn subscribe_wallet_works_for_closed_wallet() {
+        let config = wallet_config::new();
+        wallet::create_wallet(&config, DEFAULT_CREDENTIALS).wait().unwrap();
+        let handle = wallet::open_wallet(&config, DEFAULT_CREDENTIALS).wait().unwrap();
+
+        let (subscription_handle, changes) = wallet::subscribe_wallet(handle).wait().unwrap();
+
+        wallet::add_wallet_record(handle, "type", "id", "value", None).wait().unwrap();
+
+        wallet::close_wallet(handle).wait().unwrap();
+
+        // stream ends when the wallet is closed
+        let changes = changes.collect().wait().unwrap();
+        assert_eq!(vec![("type".to_string(), "id".to_string(), "add".to_string())], changes);
+
+        let result = wallet::unsubscribe_wallet(subscription_handle).wait();
+        assert_eq!(ErrorCode::WalletInvalidHandle, result.unwrap_err().error_code);
+
+        wallet::delete_wallet(&config, DEFAULT_CREDENTIALS).wait().unwrap();
+    }
+
+    #[test]
+    fn subscribe_wallet_invalid_handle() {
+        let result = wallet::subscribe_wallet(INVALID_WALLET_HANDLE).wait();
+        assert_eq!(ErrorCode::WalletInvalidHandle, result.unwrap_err().error_code);
+    }
+
+    #[test]
+    fn unsubscribe_wallet_invalid_handle() {
+        let result = wallet::unsubscribe_wallet(0).wait();
+        assert_eq!(ErrorCode::WalletInvalidHandle, result.unwrap_err().error_code);
+    }
+}
+
 #[cfg(test)]
 mod test_wallet_export {
     use super::*;